@@ -0,0 +1,198 @@
+use chrono::{Datelike, Duration, NaiveDate, TimeZone};
+use chrono_tz::Tz;
+use mugen_core::data::{filter_period, process_truth, read_csv_to_df};
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::{MugenError, Result};
+use mugen_core::export::{partition_path, write_partitioned};
+use mugen_core::fetch::{download_days, FetchOptions, Report, NYISO_BASE_URL};
+use mugen_core::zone::Zone;
+use polars::prelude::*;
+
+/// What became of one day of a backfill.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Coverage {
+    /// Every zone was already in the store.
+    Stored,
+    /// Downloaded, checked and written to the store.
+    Added,
+    /// Written to the store, but some zone has fewer hours than the day.
+    Short,
+    /// Could not be downloaded or read; tried again on the next backfill.
+    Missing,
+}
+
+/// Downloads, checks and stores every day's actual load and forecast from `from` to
+/// `to`, a month at a time, skipping days whose zones are all in the store, and
+/// prints how many days of each month are covered, then each short or missing day.
+///
+/// # Errors
+///
+/// Returns `MugenError::NoData` if any day is missing, after the report, so the
+/// exit status shows that running again is needed.
+pub(crate) fn backfill(
+    from: NaiveDate,
+    to: NaiveDate,
+    dir: &str,
+    store: &str,
+    options: &FetchOptions,
+) -> Result<()> {
+    let root: String = std::path::Path::new(store)
+        .join("actual")
+        .to_string_lossy()
+        .into_owned();
+    let mut totals: [usize; 4] = [0; 4];
+    let mut notes: Vec<String> = Vec::new();
+    println!("month      days  stored  added  short  missing");
+    let mut start: NaiveDate = from;
+    while start <= to {
+        let end: NaiveDate = month_end(start).min(to);
+        let days: Vec<NaiveDate> = start.iter_days().take_while(|day| *day <= end).collect();
+        let (stored, pending): (Vec<NaiveDate>, Vec<NaiveDate>) = days
+            .iter()
+            .partition(|day| Zone::ALL.iter().all(|zone| is_stored(&root, *zone, **day)));
+        let mut counts: [usize; 4] = [0; 4];
+        counts[Coverage::Stored as usize] = stored.len();
+        for (day, outcome) in backfill_days(&pending, dir, &root, options) {
+            let coverage: Coverage = match outcome {
+                Ok(short) if short.is_empty() => Coverage::Added,
+                Ok(short) => {
+                    notes.push(format!("short   {}: {}", day, short.join(", ")));
+                    Coverage::Short
+                }
+                Err(err) => {
+                    notes.push(format!("missing {}: {}", day, err));
+                    Coverage::Missing
+                }
+            };
+            counts[coverage as usize] += 1;
+        }
+        println!(
+            "{}",
+            coverage_row(&start.format("%Y-%m").to_string(), days.len(), counts)
+        );
+        totals
+            .iter_mut()
+            .zip(counts)
+            .for_each(|(total, count)| *total += count);
+        start = end + Duration::days(1);
+    }
+    let days: usize = totals.iter().sum();
+    println!("{}", coverage_row("total", days, totals));
+    for note in &notes {
+        println!("{}", note);
+    }
+    match totals[Coverage::Missing as usize] {
+        0 => Ok(()),
+        missing => Err(MugenError::NoData(format!(
+            "{} of {} days could not be backfilled; run the command again to retry them",
+            missing, days
+        ))),
+    }
+}
+
+/// Returns whether `zone`'s actual load for `day` is in the store at `root`.
+fn is_stored(root: &str, zone: Zone, day: NaiveDate) -> bool {
+    std::path::Path::new(&partition_path(root, zone, day)).exists()
+}
+
+/// Returns the last day of the month of `day`.
+fn month_end(day: NaiveDate) -> NaiveDate {
+    let next: Option<NaiveDate> = match day.month() {
+        12 => NaiveDate::from_ymd_opt(day.year() + 1, 1, 1),
+        month => NaiveDate::from_ymd_opt(day.year(), month + 1, 1),
+    };
+    next.and_then(|next| next.pred_opt()).unwrap_or(day)
+}
+
+/// Formats one line of the backfill coverage report.
+fn coverage_row(label: &str, days: usize, counts: [usize; 4]) -> String {
+    let [stored, added, short, missing] = counts;
+    format!(
+        "{:<9} {:>5} {:>7} {:>6} {:>6} {:>8}",
+        label, days, stored, added, short, missing
+    )
+}
+
+/// Downloads the files of `days`, one month's days in date order, and stores each
+/// day, returning per day the zones short of hours, or why the day is missing.
+///
+/// The whole stretch is downloaded at once; if any file fails, each day is
+/// downloaded again on its own, skipping files already on disk, so the failure
+/// is pinned to its day.
+fn backfill_days(
+    days: &[NaiveDate],
+    dir: &str,
+    root: &str,
+    options: &FetchOptions,
+) -> Vec<(NaiveDate, Result<Vec<String>>)> {
+    let (Some(first), Some(last)) = (days.first(), days.last()) else {
+        return Vec::new();
+    };
+    let bulk: bool = download_day_files(*first, *last, dir, options).is_ok();
+    days.iter()
+        .map(|day| {
+            let downloaded: Result<()> = if bulk {
+                Ok(())
+            } else {
+                download_day_files(*day, *day, dir, options)
+            };
+            (*day, downloaded.and_then(|_| store_day(*day, dir, root)))
+        })
+        .collect()
+}
+
+/// Downloads the actual load and forecast files from `from` to `to` into `dir`.
+fn download_day_files(
+    from: NaiveDate,
+    to: NaiveDate,
+    dir: &str,
+    options: &FetchOptions,
+) -> Result<()> {
+    for report in [Report::ActualLoad, Report::LoadForecast] {
+        download_days(NYISO_BASE_URL, report, from, to, dir, options)?;
+    }
+    Ok(())
+}
+
+/// Checks the downloaded files of `day` against their recorded hashes, reads them,
+/// and writes every zone's actual load into the store at `root`, returning the
+/// zones with fewer hours than the day has, e.g. "N.Y.C. 20 of 24 hours".
+fn store_day(day: NaiveDate, dir: &str, root: &str) -> Result<Vec<String>> {
+    let file = |report: Report| {
+        std::path::Path::new(dir)
+            .join(report.file_name(day))
+            .to_string_lossy()
+            .into_owned()
+    };
+    read_csv_to_df(&file(Report::LoadForecast))?;
+    let raw_truth: DataFrame = read_csv_to_df(&file(Report::ActualLoad))?;
+    let mut short: Vec<String> = Vec::new();
+    for zone in Zone::ALL {
+        let truth: TimeSeriesDataset =
+            filter_period(process_truth(raw_truth.clone(), zone)?, day, day)?;
+        let hours: usize = hours_in(day, truth.timezone());
+        if truth.height() < hours {
+            short.push(format!("{} {} of {} hours", zone, truth.height(), hours));
+        }
+        if truth.height() > 0 {
+            write_partitioned(&truth, root, zone)?;
+        }
+    }
+    Ok(short)
+}
+
+/// Returns the number of hours in the local day `day` of `timezone`: 23 or 25 on
+/// daylight saving changes, otherwise 24.
+fn hours_in(day: NaiveDate, timezone: &str) -> usize {
+    let Ok(timezone) = timezone.parse::<Tz>() else {
+        return 24;
+    };
+    let midnight = |day: NaiveDate| {
+        day.and_hms_opt(0, 0, 0)
+            .and_then(|local| timezone.from_local_datetime(&local).earliest())
+    };
+    match (midnight(day), day.succ_opt().and_then(midnight)) {
+        (Some(start), Some(end)) => (end - start).num_hours() as usize,
+        _ => 24,
+    }
+}
@@ -0,0 +1,146 @@
+use chrono::{Duration, NaiveDate};
+use mugen_core::backtest::{backtest, Backtest, Forecaster};
+use mugen_core::baseline::{persistence, HoltWinters, Seasonality};
+use mugen_core::data::{process_pred, process_truth, read_multiple_csvs_with};
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::Result;
+use mugen_core::export::{write_table, ExportFormat};
+use mugen_core::fetch::Report;
+use mugen_core::split::Split;
+use mugen_core::zone::Zone;
+use mugen_viz::config::{PipelineConfig, Timezones};
+use mugen_viz::pipeline::{DatasetSummary, PipelineReport};
+use polars::prelude::*;
+
+use crate::described;
+
+/// Scores `forecaster` for the configured zone on every split, reading the actual
+/// load and stored forecasts from `dir`, prints each split's metrics and their
+/// averages, and writes the splits' metrics to `output` if given.
+///
+/// Stored forecasts are those issued `lead` days before each test day; the models
+/// are fitted to the actual load of each split's training period.
+pub(crate) fn run_backtest(
+    folds: &[Split],
+    dir: &str,
+    forecaster: Forecaster,
+    seasonality: Seasonality,
+    lead: u32,
+    output: Option<&str>,
+    settings: &PipelineConfig,
+) -> Result<PipelineReport> {
+    let zone: Zone = settings.zone;
+    let timezones: &Timezones = &settings.timezones;
+    let to: NaiveDate = folds.last().map_or(NaiveDate::MIN, |split| split.test_to);
+    let suffix: String = format!("{}.csv", Report::ActualLoad.name());
+    let mut truth_files: Vec<String> = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path: std::path::PathBuf = entry?.path();
+        let name: String = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let day: Option<NaiveDate> = name
+            .strip_suffix(&suffix)
+            .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok());
+        if day.is_some_and(|day| day <= to) {
+            truth_files.push(path.to_string_lossy().into_owned());
+        }
+    }
+    truth_files.sort();
+    let raw_truth: DataFrame = read_multiple_csvs_with(
+        truth_files.iter().map(String::as_str).collect(),
+        &settings.resources,
+    )?;
+    let truth: TimeSeriesDataset = timezones.truth(process_truth(raw_truth, zone)?)?;
+
+    let result: Backtest = backtest(&truth, folds, |split, history| {
+        forecast_split(forecaster, seasonality, lead, dir, split, history, settings)
+    })?;
+
+    for fold in &result.folds {
+        let split: &Split = &fold.split;
+        println!(
+            "{} to {}, trained on {} to {}: {} ({} hours)",
+            split.test_from.format("%Y-%m-%d"),
+            split.test_to.format("%Y-%m-%d"),
+            split.train_from.format("%Y-%m-%d"),
+            split.train_to.format("%Y-%m-%d"),
+            described(&fold.metrics),
+            fold.hours
+        );
+    }
+    let summary: Vec<(String, f64)> = result.summary();
+    println!(
+        "{} for {} over {} of {} splits: {}",
+        forecaster,
+        zone,
+        result.folds.len(),
+        folds.len(),
+        if summary.is_empty() {
+            "nothing to score".to_string()
+        } else {
+            described(&summary)
+        }
+    );
+    let table: TimeSeriesDataset = result.table()?;
+    if let Some(output) = output {
+        let format: ExportFormat = std::path::Path::new(output)
+            .extension()
+            .and_then(|extension| extension.to_str()?.parse().ok())
+            .unwrap_or_default();
+        write_table(&table, output, format)?;
+        println!("{}", output);
+    }
+    Ok(PipelineReport {
+        datasets: vec![DatasetSummary::of(&truth)?, DatasetSummary::of(&table)?],
+        metrics: summary,
+        warnings: result
+            .skipped
+            .iter()
+            .map(|(day, reason)| format!("skipped {}: {}", day.format("%Y-%m-%d"), reason))
+            .collect(),
+        ..Default::default()
+    })
+}
+
+/// Returns the forecast of `split`'s test period for the configured zone: the
+/// stored forecasts in `dir` issued `lead` days before each test day, or a model
+/// fitted to `history`.
+pub(crate) fn forecast_split(
+    forecaster: Forecaster,
+    seasonality: Seasonality,
+    lead: u32,
+    dir: &str,
+    split: &Split,
+    history: &TimeSeriesDataset,
+    settings: &PipelineConfig,
+) -> Result<TimeSeriesDataset> {
+    let hours: usize = split.test_days() as usize * 24;
+    match forecaster {
+        Forecaster::Stored => {
+            let paths: Vec<String> = split
+                .test_from
+                .iter_days()
+                .take_while(|day| *day <= split.test_to)
+                .map(|day| {
+                    let issued: NaiveDate = day - Duration::days(i64::from(lead));
+                    let name: String = Report::LoadForecast.file_name(issued);
+                    std::path::Path::new(dir)
+                        .join(name)
+                        .to_string_lossy()
+                        .into_owned()
+                })
+                .collect();
+            let raw: DataFrame = read_multiple_csvs_with(
+                paths.iter().map(String::as_str).collect(),
+                &settings.resources,
+            )?;
+            settings
+                .timezones
+                .forecast(process_pred(raw, settings.zone)?)
+        }
+        Forecaster::HoltWinters => HoltWinters::fit(history, seasonality)?.forecast(history, hours),
+        Forecaster::Persistence => persistence(history, hours, seasonality),
+    }
+}
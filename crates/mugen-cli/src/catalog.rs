@@ -0,0 +1,64 @@
+use chrono::NaiveDateTime;
+use mugen_core::catalog::{Catalog, CatalogEntry, CatalogQuery};
+use mugen_core::error::{MugenError, Result};
+use mugen_viz::pipeline::PipelineReport;
+
+/// Brings the catalog of `dir` up to date after a download, returning a report that
+/// warns if it could not be.
+pub(crate) fn cataloged(dir: &str) -> PipelineReport {
+    let mut report: PipelineReport = PipelineReport::default();
+    let updated: Result<String> = Catalog::load(dir).and_then(|mut catalog| {
+        catalog.refresh()?;
+        catalog.save()
+    });
+    if let Err(err) = updated {
+        report.warnings.push(format!(
+            "could not update the catalog of \"{}\": {}",
+            dir, err
+        ));
+    }
+    report
+}
+
+/// Brings the catalog of `dir` up to date and prints the entries matching `query`,
+/// as a table or as JSON.
+pub(crate) fn print_catalog(dir: &str, query: &CatalogQuery, json: bool) -> Result<()> {
+    let mut catalog: Catalog = Catalog::load(dir)?;
+    catalog.refresh()?;
+    catalog.save()?;
+    let entries: Vec<&CatalogEntry> = catalog.select(query);
+    if json {
+        let text: String = serde_json::to_string_pretty(&entries)
+            .map_err(|err| MugenError::InvalidArgument(err.to_string()))?;
+        println!("{}", text);
+        return Ok(());
+    }
+    let time = |at: Option<NaiveDateTime>| match at {
+        Some(at) => at.format("%Y-%m-%d %H:%M").to_string(),
+        None => "-".to_string(),
+    };
+    println!(
+        "{:<36} {:<14} {:<16} {:<16} {:>5} {:>6}  sha256",
+        "file", "kind", "first", "last", "zones", "rows"
+    );
+    for entry in &entries {
+        println!(
+            "{:<36} {:<14} {:<16} {:<16} {:>5} {:>6}  {}",
+            entry.file.path,
+            entry.kind,
+            time(entry.first),
+            time(entry.last),
+            entry.zones.len(),
+            entry.rows,
+            entry.file.sha256.get(..12).unwrap_or_default()
+        );
+    }
+    let rows: usize = entries.iter().map(|entry| entry.rows).sum();
+    println!(
+        "{} of {} files, {} rows",
+        entries.len(),
+        catalog.len(),
+        rows
+    );
+    Ok(())
+}
@@ -0,0 +1,607 @@
+use std::time::Duration;
+
+use chrono::{NaiveDate, NaiveDateTime, ParseResult};
+use clap::{Args, Parser, Subcommand};
+
+use mugen_core::backtest::Forecaster;
+use mugen_core::baseline::Seasonality;
+use mugen_core::ensemble::Blend;
+use mugen_core::error::MugenError;
+use mugen_core::export::ExportFormat;
+use mugen_core::features::DEFAULT_LAGS;
+use mugen_core::fetch::{FetchOptions, RateLimit, RetryPolicy};
+use mugen_core::iso::Market;
+use mugen_core::scenario::{Adjustment, Target};
+use mugen_core::split::Window;
+use mugen_core::zone::Zone;
+use mugen_viz::graph::{Collision, NumberFormat, Resolution, Style};
+use mugen_viz::render::Backend;
+
+/// Command-line interface for rendering NYISO load and forecast charts.
+///
+/// Running without a subcommand renders the standard chart set for the configured
+/// zone, NYC unless a config file says otherwise.
+#[derive(Parser)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+pub(crate) struct Cli {
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+
+    /// Print actual vs forecast as a braille chart instead of saving PNGs.
+    #[arg(long)]
+    pub(crate) terminal: bool,
+
+    /// TOML or JSON file with the input files, zone and chart settings; the flags
+    /// below override it. Without one, the bundled December 2023 data and NYC are used.
+    #[arg(long, global = true)]
+    pub(crate) config: Option<String>,
+
+    /// Output size preset: thumbnail, slide, report or 4k. Fonts and line widths
+    /// scale with it. Defaults to 4k.
+    #[arg(long, global = true)]
+    pub(crate) resolution: Option<Resolution>,
+
+    /// Locale for axis numbers, e.g. "en-US" for 5,200 MW or "de-DE" for 5.200 MW.
+    /// Defaults to en-US.
+    #[arg(long, global = true)]
+    pub(crate) locale: Option<NumberFormat>,
+
+    /// Unit written after axis numbers, e.g. "MW" or "GW"; empty for none. Defaults
+    /// to MW.
+    #[arg(long, global = true)]
+    pub(crate) unit: Option<String>,
+
+    /// Style template: dark, light, print or presentation. Defaults to dark.
+    #[arg(long, global = true)]
+    pub(crate) style: Option<Style>,
+
+    /// Rendering backend: echarts for images or spec for the ECharts option as JSON,
+    /// which needs no image renderer. Defaults to echarts.
+    #[arg(long, global = true)]
+    pub(crate) backend: Option<Backend>,
+
+    /// What to do when a chart file already exists: overwrite, increment to save
+    /// under the next free name, or error. Defaults to overwrite.
+    #[arg(long, global = true)]
+    pub(crate) on_collision: Option<Collision>,
+
+    /// Threads for loading and processing. Defaults to one per core.
+    #[arg(long, global = true)]
+    pub(crate) threads: Option<usize>,
+
+    /// Approximate memory budget for loading, in MiB; larger loads are streamed in
+    /// batches and files read one at a time. Defaults to no limit.
+    #[arg(long, global = true)]
+    pub(crate) memory_mb: Option<u64>,
+
+    /// IANA timezone charts and reports show, e.g. "UTC" or "America/New_York";
+    /// the input files are converted from the timezones set in the config. Defaults
+    /// to the actual-load files' timezone.
+    #[arg(long, global = true)]
+    pub(crate) display_timezone: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Command {
+    /// Render a single chart.
+    Plot {
+        #[command(subcommand)]
+        chart: PlotCommand,
+    },
+    /// Download the daily actual-load and forecast files for a period from NYISO,
+    /// or another market, skipping days already on disk.
+    Fetch {
+        /// First day to download, e.g. "2023-12-01" or "12/01/2023".
+        #[arg(long, value_parser = parse_date)]
+        from: NaiveDate,
+        /// Last day to download, inclusive.
+        #[arg(long, value_parser = parse_date)]
+        to: NaiveDate,
+        /// Directory to save into.
+        #[arg(long, default_value = "data")]
+        dir: String,
+        #[command(flatten)]
+        download: DownloadArgs,
+        /// Market to download from: NYISO, ERCOT, ISO-NE or ENTSO-E. ERCOT only
+        /// lists its last few days of files, ISO-NE needs an ISO Express login in
+        /// ISO_NE_USERNAME and ISO_NE_PASSWORD, and ENTSO-E a security token in
+        /// ENTSO_E_SECURITY_TOKEN.
+        #[arg(long, default_value_t = Market::Nyiso)]
+        iso: Market,
+        /// Also download the real-time load published every five minutes.
+        #[arg(long)]
+        realtime: bool,
+        /// Also download the day-ahead and real-time zonal prices (LBMP).
+        #[arg(long)]
+        prices: bool,
+        /// Also write every zone's actual load into a Parquet store in this
+        /// directory, under "actual", partitioned by zone and date.
+        #[arg(long)]
+        store: Option<String>,
+    },
+    /// Download, check and store every day's actual load and forecast for a long
+    /// period, skipping days already in the store, then report the coverage. Run it
+    /// again to retry the days that failed.
+    Backfill {
+        /// First day to backfill, e.g. "2022-01-01" or "01/01/2022".
+        #[arg(long, value_parser = parse_date)]
+        from: NaiveDate,
+        /// Last day to backfill, inclusive.
+        #[arg(long, value_parser = parse_date)]
+        to: NaiveDate,
+        /// Directory to download the daily files into.
+        #[arg(long, default_value = "data")]
+        dir: String,
+        /// Directory of the Parquet store; each zone's actual load is written under
+        /// "actual", partitioned by zone and date.
+        #[arg(long, default_value = "store")]
+        store: String,
+        #[command(flatten)]
+        download: DownloadArgs,
+    },
+    /// Report where and by how much two forecast files differ for one zone: the
+    /// largest and mean difference and each hour that moved, optionally with a chart
+    /// of both.
+    Diff {
+        /// The forecast compared against, e.g. "data/20231209isolf.csv".
+        first: String,
+        /// The forecast compared, e.g. "data/20231210isolf.csv".
+        second: String,
+        /// Zone to compare, e.g. "N.Y.C."; defaults to the configured zone.
+        #[arg(long)]
+        region: Option<Zone>,
+        /// List only the hours that moved by more than this many MW.
+        #[arg(long, default_value_t = 0.0)]
+        min_delta: f64,
+        /// Also render both forecasts with the difference at each hour.
+        #[arg(long)]
+        chart: bool,
+    },
+    /// Score one zone's forecast against its actual load for any supported market,
+    /// printing the MAPE, peak error and bias, optionally with a chart of both.
+    Score {
+        /// Market the files come from: NYISO, ERCOT, ISO-NE, MISO or ENTSO-E.
+        #[arg(long, default_value_t = Market::Nyiso)]
+        iso: Market,
+        /// Zone to score, as spelled in the market's files or by its full name, e.g.
+        /// "N.Y.C.", "COAST", "Connecticut", "LRZ1" or "DE_LU".
+        #[arg(long)]
+        zone: String,
+        /// Actual-load files, e.g. "data/20231209isone_actual.csv"; repeat for
+        /// several days. MISO's files hold both, so pass the same file twice.
+        #[arg(long = "actual", required = true)]
+        actuals: Vec<String>,
+        /// The forecast file, e.g. "data/20231209isone_forecast.csv".
+        #[arg(long)]
+        forecast: String,
+        /// Also render actual vs forecast with the error shaded.
+        #[arg(long)]
+        chart: bool,
+    },
+    /// Check NYISO's actual load, summed over its zones, against the hourly demand
+    /// NYISO reports to the EIA, downloading both for a period, and list the hours
+    /// where they disagree, optionally with a chart of both. Needs EIA_API_KEY set.
+    Crosscheck {
+        /// First day to check, e.g. "2023-12-01" or "12/01/2023".
+        #[arg(long, value_parser = parse_date)]
+        from: NaiveDate,
+        /// Last day to check, inclusive.
+        #[arg(long, value_parser = parse_date)]
+        to: NaiveDate,
+        /// Directory to save into and read from.
+        #[arg(long, default_value = "data")]
+        dir: String,
+        /// List the hours where EIA's demand differs from NYISO's load by more than
+        /// this percentage of it.
+        #[arg(long, default_value_t = 2.0)]
+        threshold: f64,
+        /// Also render NYISO's load and EIA's demand on one chart.
+        #[arg(long)]
+        chart: bool,
+    },
+    /// Score a forecaster over the train/test splits of a past period, each test
+    /// period forecast from the actual load of its training period or read from the
+    /// stored forecasts, and summarize the metrics over the splits.
+    Backtest {
+        /// First day to forecast, e.g. "2023-12-01" or "12/01/2023".
+        #[arg(long, value_parser = parse_date)]
+        from: NaiveDate,
+        /// Last day to forecast, inclusive.
+        #[arg(long, value_parser = parse_date)]
+        to: NaiveDate,
+        /// Directory of the actual-load and forecast files.
+        #[arg(long, default_value = "data")]
+        dir: String,
+        /// Forecaster to score: stored for the forecast files, holt-winters or
+        /// persistence.
+        #[arg(long, default_value_t = Forecaster::Stored)]
+        forecaster: Forecaster,
+        /// Seasons holt-winters and persistence follow: daily or weekly.
+        #[arg(long, default_value_t = Seasonality::Weekly)]
+        seasonality: Seasonality,
+        /// How training moves from split to split: expanding from a fixed first day,
+        /// or sliding with the same number of days.
+        #[arg(long, default_value_t = Window::Expanding)]
+        window: Window,
+        /// Days the first split trains on, before --from.
+        #[arg(long, default_value_t = 28)]
+        train_days: u32,
+        /// Days each split forecasts and scores.
+        #[arg(long, default_value_t = 1)]
+        test_days: u32,
+        /// Days before each day the stored forecast was issued; 0 for the same-day
+        /// update.
+        #[arg(long, default_value_t = 1)]
+        lead: u32,
+        /// Also write the metrics of every split to this CSV or Parquet file.
+        #[arg(long)]
+        output: Option<String>,
+    },
+    /// List the data files in a directory with their time span, zones, rows and
+    /// hash, from the catalog kept there, bringing it up to date first.
+    Catalog {
+        /// Directory of the data files; the catalog is kept there as "catalog.json".
+        #[arg(long, default_value = "data")]
+        dir: String,
+        /// Only files holding this zone, e.g. "N.Y.C." or "nyc".
+        #[arg(long)]
+        zone: Option<Zone>,
+        /// Only files with data on or after this day, e.g. "2023-12-01".
+        #[arg(long, value_parser = parse_date)]
+        from: Option<NaiveDate>,
+        /// Only files with data on or before this day.
+        #[arg(long, value_parser = parse_date)]
+        to: Option<NaiveDate>,
+        /// Only files of this kind, e.g. "palIntegrated", "isolf" or "weather".
+        #[arg(long)]
+        kind: Option<String>,
+        /// Print the matching entries as JSON instead of a table.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Compact the daily CSVs of every month older than a number of days into one
+    /// Parquet file per kind and month, e.g. "archive/palIntegrated/2023-11.parquet",
+    /// optionally deleting the originals; the other commands then read them from
+    /// the archive.
+    Compact {
+        /// Directory of the daily files.
+        #[arg(long, default_value = "data")]
+        dir: String,
+        /// Compact the months whose last day is more than this many days ago.
+        #[arg(long, default_value_t = 90)]
+        older_than: u32,
+        /// Directory of the monthly files; defaults to "archive" in `--dir`.
+        #[arg(long)]
+        archive: Option<String>,
+        /// Delete the daily files of each month once its monthly file is written
+        /// and every file's rows read back from it. Only the default `--archive` is
+        /// read back by the other commands.
+        #[arg(long)]
+        delete: bool,
+    },
+    /// Download hourly temperatures at each zone's weather station for a period,
+    /// saved as one file per day next to the NYISO files.
+    Weather {
+        /// First day to download, e.g. "2023-12-01" or "12/01/2023".
+        #[arg(long, value_parser = parse_date)]
+        from: NaiveDate,
+        /// Last day to download, inclusive.
+        #[arg(long, value_parser = parse_date)]
+        to: NaiveDate,
+        /// Directory to save into.
+        #[arg(long, default_value = "data")]
+        dir: String,
+    },
+    /// Serve an auto-refreshing actual vs forecast chart over HTTP, re-reading
+    /// the configured files when they change, with forecast-quality gauges for
+    /// Prometheus on `/metrics`.
+    Live {
+        /// Address to listen on.
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        address: String,
+        /// Seconds between refreshes.
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+    },
+    /// Show every zone's recent load vs forecast, current error and data freshness
+    /// as a full-screen terminal dashboard, re-reading the configured files when they
+    /// change.
+    /// Press r to refresh at once and q to quit.
+    Dashboard {
+        /// Seconds between refreshes.
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+        /// Hours of load shown in each zone's sparklines.
+        #[arg(long, default_value_t = 72)]
+        hours: usize,
+    },
+    /// Write the tables behind the charts to files, for analysis elsewhere.
+    Export {
+        #[command(subcommand)]
+        table: ExportCommand,
+    },
+    /// Keep running, and at each time in the `[daemon]` schedule download the
+    /// latest files and render, score and publish the standard chart set, making up
+    /// a run missed while stopped. Serves Prometheus metrics on `/metrics` at the
+    /// `metrics_address` of `[daemon]`, if set.
+    Daemon,
+    /// Serve charts and metrics of any zone over HTTP, built on demand from the
+    /// configured files: `/chart/{zone}?from=&to=`, `/metrics/{zone}.json`,
+    /// Prometheus metrics of the configured zone on `/metrics` and a Grafana
+    /// datasource on `/grafana`, and push the configured zone's new data points
+    /// over a WebSocket on `/live`.
+    Serve {
+        /// Address to listen on.
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        address: String,
+        /// Seconds between looks for new data points to push.
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+    },
+}
+
+/// How downloads from NYISO are retried and paced.
+#[derive(Args)]
+pub(crate) struct DownloadArgs {
+    /// Attempts per file before giving up, including the first. Server errors,
+    /// timeouts and dropped connections are retried with exponential backoff.
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..))]
+    attempts: u32,
+    /// Seconds each attempt may take before it is abandoned.
+    #[arg(long, default_value_t = 60)]
+    timeout: u64,
+    /// Most requests started per second, retries included; 0 for no limit.
+    #[arg(long, default_value_t = 5.0)]
+    rate: f64,
+    /// Most downloads running at once.
+    #[arg(long, default_value_t = 8, value_parser = clap::value_parser!(u64).range(1..))]
+    concurrency: u64,
+}
+
+impl DownloadArgs {
+    /// Returns the fetch options the flags describe.
+    pub(crate) fn options(&self) -> FetchOptions {
+        FetchOptions {
+            retry: RetryPolicy {
+                attempts: self.attempts,
+                timeout: Duration::from_secs(self.timeout),
+                ..Default::default()
+            },
+            rate_limit: RateLimit {
+                requests_per_second: self.rate,
+                concurrency: self.concurrency as usize,
+            },
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub(crate) enum ExportCommand {
+    /// The configured zone's actual load joined against the forecast at each hour,
+    /// with the residual, as plotted in the standard line chart.
+    Joined {
+        /// File format: csv or parquet.
+        #[arg(long, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+        /// Write a Parquet store instead of one file, one file per day at hive-style
+        /// paths such as `zone=n_y_c/date=2023-12-09/part-0.parquet`.
+        #[arg(long, conflicts_with = "format")]
+        partitioned: bool,
+        /// File to write, or directory of the store; defaults to
+        /// "joined_<zone>.<format>" or "joined" in the chart output directory.
+        #[arg(long)]
+        output: Option<String>,
+        /// First day to keep, e.g. "2023-12-01" or "12/01/2023"; defaults to the
+        /// first in the data.
+        #[arg(long, value_parser = parse_date)]
+        from: Option<NaiveDate>,
+        /// Last day to keep, inclusive; defaults to the last in the data.
+        #[arg(long, value_parser = parse_date)]
+        to: Option<NaiveDate>,
+    },
+    /// A table to train forecasting models on, from the configured zone's actual
+    /// load: the load some hours before, hour, weekday, month, weekend and holiday
+    /// flags, the temperature where weather files exist, and the load as target.
+    Features {
+        /// File format: csv or parquet.
+        #[arg(long, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+        /// File to write; defaults to "features_<zone>.<format>" in the chart output
+        /// directory.
+        #[arg(long)]
+        output: Option<String>,
+        /// First day to keep, e.g. "2023-12-01" or "12/01/2023"; the days before
+        /// still supply the lagged load.
+        #[arg(long, value_parser = parse_date)]
+        from: Option<NaiveDate>,
+        /// Last day to keep, inclusive; defaults to the last in the data.
+        #[arg(long, value_parser = parse_date)]
+        to: Option<NaiveDate>,
+        /// Hours before each hour to take the load of, comma-separated.
+        #[arg(long, value_delimiter = ',', default_values_t = DEFAULT_LAGS)]
+        lags: Vec<usize>,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum PlotCommand {
+    /// The full evaluation chart set for a period, saved into a dated folder.
+    All {
+        /// First day of the period, e.g. "2023-12-01" or "12/01/2023".
+        #[arg(long, value_parser = parse_date)]
+        from: NaiveDate,
+        /// Last day of the period, inclusive.
+        #[arg(long, value_parser = parse_date)]
+        to: NaiveDate,
+    },
+    /// The forecast issued the day before each day against the update issued on the
+    /// day, overlaid on the actuals, with metrics for both.
+    DayAhead {
+        /// First day of the period, e.g. "2023-12-01" or "12/01/2023".
+        #[arg(long, value_parser = parse_date)]
+        from: NaiveDate,
+        /// Last day of the period, inclusive.
+        #[arg(long, value_parser = parse_date)]
+        to: NaiveDate,
+    },
+    /// Zonal loads stacked over time, adding up to the NYISO total.
+    Area,
+    /// Forecast error metrics compared across zones.
+    Radar,
+    /// Successive forecast issues for one day overlaid on the actuals.
+    Vintages {
+        /// Target day, e.g. "2023-12-10" or "12/10/2023".
+        #[arg(long, value_parser = parse_date)]
+        day: NaiveDate,
+        /// Forecast files to compare, named like "20231209isolf.csv"; defaults to
+        /// every `*isolf.csv` under `data/`.
+        #[arg(long = "forecast")]
+        forecasts: Vec<String>,
+    },
+    /// Several forecasts blended into one, evaluated and overlaid on the actuals with
+    /// its members, to compare blend strategies.
+    Ensemble {
+        /// Forecast files to blend, named like "20231209isolf.csv"; defaults to
+        /// every `*isolf.csv` under `data/`.
+        forecasts: Vec<String>,
+        /// How to weigh the forecasts: mean for evenly, or skill for by their MAPE
+        /// over the hours just before.
+        #[arg(long, default_value_t = Blend::Mean)]
+        blend: Blend,
+        /// Hours of actual load the skill is measured over.
+        #[arg(long, default_value_t = 24, value_parser = clap::value_parser!(u64).range(1..))]
+        window: u64,
+    },
+    /// A Holt-Winters forecast made from the actual load before a day, and a
+    /// persistence forecast, both scored against the actuals of the days that follow.
+    Baseline {
+        /// First day to forecast, e.g. "2023-12-10" or "12/10/2023"; the actual
+        /// load before it is the history.
+        #[arg(long, value_parser = parse_date)]
+        day: NaiveDate,
+        /// Days to forecast.
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+        days: u64,
+        /// Seasons to follow: daily, or weekly for the shape of both the day and the
+        /// week, which needs a week of history.
+        #[arg(long, default_value_t = Seasonality::Weekly)]
+        seasonality: Seasonality,
+    },
+    /// A forecast with the prediction interval its errors over the days before
+    /// call for, at each hour of the day, scored by how much of the actual load
+    /// falls inside.
+    Interval {
+        /// First day to forecast, e.g. "2023-12-10" or "12/10/2023".
+        #[arg(long, value_parser = parse_date)]
+        day: NaiveDate,
+        /// Days to forecast.
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        days: u32,
+        /// Forecaster to put the interval around: stored for the forecast files,
+        /// holt-winters or persistence.
+        #[arg(long, default_value_t = Forecaster::Stored)]
+        forecaster: Forecaster,
+        /// Seasons holt-winters and persistence follow: daily or weekly.
+        #[arg(long, default_value_t = Seasonality::Weekly)]
+        seasonality: Seasonality,
+        /// Days before each day the stored forecast was issued; 0 for the same-day
+        /// update.
+        #[arg(long, default_value_t = 1)]
+        lead: u32,
+        /// Days before --day whose forecast errors the interval is learned from.
+        #[arg(long, default_value_t = 7, value_parser = clap::value_parser!(u32).range(1..))]
+        calibration_days: u32,
+        /// Share of the actual load the interval should hold, between 0 and 1.
+        #[arg(long, default_value_t = 0.8)]
+        coverage: f64,
+    },
+    /// The actual load and forecast with what-if adjustments made to one of them,
+    /// drawn and scored alongside the original.
+    Scenario {
+        /// First day to plot, e.g. "2023-12-01" or "12/01/2023".
+        #[arg(long, value_parser = parse_date)]
+        from: NaiveDate,
+        /// Last day to plot, inclusive.
+        #[arg(long, value_parser = parse_date)]
+        to: NaiveDate,
+        /// Adjustment to make, in order, e.g. scale=5%, shift=1h or add=500@18 for
+        /// 500 MW more in the hour from 18:00; repeat for several.
+        #[arg(long = "adjust", required = true)]
+        adjustments: Vec<Adjustment>,
+        /// Series to adjust: forecast or actual.
+        #[arg(long, default_value_t = Target::Forecast)]
+        target: Target,
+    },
+    /// A forecast from an ONNX model trained on the `export features` table, run on
+    /// the same features and scored against the actual load; needs the `onnx`
+    /// feature and ONNX Runtime at `ORT_DYLIB_PATH`.
+    Onnx {
+        /// Model file, taking the feature columns in table order and returning the
+        /// load of each hour.
+        #[arg(long)]
+        model: String,
+        /// First day to forecast; defaults to the first with every lag.
+        #[arg(long, value_parser = parse_date)]
+        from: Option<NaiveDate>,
+        /// Last day to forecast, inclusive; defaults to the last in the data.
+        #[arg(long, value_parser = parse_date)]
+        to: Option<NaiveDate>,
+        /// Hours before each hour to take the load of, comma-separated; the lags the
+        /// model was trained with.
+        #[arg(long, value_delimiter = ',', default_values_t = DEFAULT_LAGS)]
+        lags: Vec<usize>,
+    },
+    /// Each zone's share of total NYISO load at one moment.
+    Pie {
+        /// Timestamp to plot, e.g. "12/05/2023 18:00" or "2023-12-05 18:00".
+        #[arg(long, value_parser = parse_timestamp)]
+        at: NaiveDateTime,
+    },
+}
+
+/// Parses a timestamp in any of the formats accepted on the command line.
+///
+/// # Errors
+///
+/// Returns `MugenError::Parse` listing the accepted formats if `value` matches none
+/// of them.
+fn parse_timestamp(value: &str) -> Result<NaiveDateTime, MugenError> {
+    const FORMATS: [&str; 4] = [
+        "%m/%d/%Y %H:%M",
+        "%m/%d/%Y %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+    ];
+    parse_any(value, &FORMATS, NaiveDateTime::parse_from_str)
+}
+
+/// Parses a calendar date in any of the formats accepted on the command line.
+///
+/// # Errors
+///
+/// Returns `MugenError::Parse` listing the accepted formats if `value` matches none
+/// of them.
+pub(crate) fn parse_date(value: &str) -> Result<NaiveDate, MugenError> {
+    const FORMATS: [&str; 2] = ["%Y-%m-%d", "%m/%d/%Y"];
+    parse_any(value, &FORMATS, NaiveDate::parse_from_str)
+}
+
+/// Tries each of the non-empty `formats` in turn, keeping the error of the last one.
+fn parse_any<T>(
+    value: &str,
+    formats: &[&str],
+    parse: fn(&str, &str) -> ParseResult<T>,
+) -> Result<T, MugenError> {
+    let mut parsed = parse(value, formats[0]);
+    for format in &formats[1..] {
+        if parsed.is_ok() {
+            break;
+        }
+        parsed = parse(value, format);
+    }
+    parsed.map_err(|source| MugenError::Parse {
+        input: value.to_string(),
+        expected: formats.join(", "),
+        source,
+    })
+}
@@ -0,0 +1,28 @@
+use chrono::NaiveDate;
+use mugen_core::archive::{compact, delete_files, monthly_files, MonthlyFiles};
+use mugen_core::error::Result;
+use mugen_core::resources::Resources;
+
+/// Compacts the daily files in `dir` of every month that ended before `before` into
+/// `archive`, reading them within `resources` and deleting them afterwards if
+/// `delete`, and prints each monthly file.
+pub(crate) fn compact_months(
+    dir: &str,
+    before: NaiveDate,
+    archive: &str,
+    delete: bool,
+    resources: &Resources,
+) -> Result<()> {
+    let months: Vec<MonthlyFiles> = monthly_files(dir, before)?;
+    if months.is_empty() {
+        println!("No month before {} to compact", before);
+    }
+    for month in &months {
+        let (path, rows): (String, usize) = compact(month, archive, resources)?;
+        println!("{} ({} files, {} rows)", path, month.files.len(), rows);
+        if delete {
+            delete_files(month)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use chrono::NaiveDate;
+use mugen_core::data::{compare_demand, process_demand, read_multiple_csvs_with, total_load};
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::eia::{
+    discrepancies, download_demand, Discrepancy, EIA_BASE_URL, NYISO_RESPONDENT,
+};
+use mugen_core::error::Result;
+use mugen_core::fetch::{download_days, FetchOptions, Report, NYISO_BASE_URL};
+use mugen_core::iso::{Iso, Nyiso};
+use mugen_core::resources::Resources;
+use mugen_viz::graph::{Graph, GraphConfig, Labels, LineGraph};
+use mugen_viz::pipeline::{DatasetSummary, PipelineReport};
+use polars::prelude::*;
+
+/// Downloads NYISO's actual load and the demand NYISO reported to the EIA for
+/// `from..=to` into `dir`, prints the hours where they differ by more than
+/// `threshold` percent, and renders both if `chart` is set.
+pub(crate) fn crosscheck(
+    from: NaiveDate,
+    to: NaiveDate,
+    dir: &str,
+    threshold: f64,
+    chart: bool,
+    base: &GraphConfig,
+    resources: &Resources,
+) -> Result<PipelineReport> {
+    let options: FetchOptions = FetchOptions::default();
+    let truth_files: Vec<String> =
+        download_days(NYISO_BASE_URL, Report::ActualLoad, from, to, dir, &options)?;
+    let demand_files: Vec<String> = download_demand(
+        EIA_BASE_URL,
+        NYISO_RESPONDENT,
+        Nyiso.timezone(),
+        from,
+        to,
+        dir,
+        &options,
+    )?;
+    let reported: TimeSeriesDataset = total_load(read_multiple_csvs_with(
+        truth_files.iter().map(String::as_str).collect(),
+        resources,
+    )?)?;
+    let eia: TimeSeriesDataset = process_demand(
+        read_multiple_csvs_with(demand_files.iter().map(String::as_str).collect(), resources)?,
+        NYISO_RESPONDENT,
+    )?;
+    let compared: TimeSeriesDataset = compare_demand(&reported, &eia)?;
+    let found: Vec<Discrepancy> = discrepancies(&compared, threshold)?;
+    let mut report: PipelineReport = PipelineReport {
+        datasets: vec![
+            DatasetSummary::of(&reported)?,
+            DatasetSummary::of(&eia)?,
+            DatasetSummary::of(&compared)?,
+        ],
+        ..Default::default()
+    };
+    let difference: f64 = compared
+        .values("Percent")?
+        .apply_values(f64::abs)
+        .mean()
+        .unwrap_or(f64::NAN);
+    report
+        .metrics
+        .push(("EIA difference".to_string(), difference));
+    println!(
+        "NYISO vs EIA over {} hours: mean difference {:.2}%, hours over {}%: {}",
+        compared.height(),
+        difference,
+        threshold,
+        found.len()
+    );
+    for discrepancy in &found {
+        println!(
+            "{}  NYISO {:>9.1} MW  EIA {:>9.1} MW  {:>+8.1} MW ({:+.2}%)",
+            discrepancy.at.format("%Y-%m-%d %H:%M"),
+            discrepancy.reported,
+            discrepancy.eia,
+            discrepancy.difference,
+            discrepancy.percent
+        );
+    }
+    if chart {
+        let line_graph: LineGraph = LineGraph::builder()
+            .config(base.clone())
+            .labels(Labels {
+                actual: "NYISO".to_string(),
+                forecast: "EIA".to_string(),
+                ..base.labels.clone()
+            })
+            .truth(reported)
+            .forecast(eia)
+            .title(base.labels.titles.crosscheck.as_str())
+            .error_band(true)
+            .build()?;
+        let chart = line_graph.draw()?;
+        println!("{}", chart);
+        report.artifacts.push(chart);
+    }
+    Ok(report)
+}
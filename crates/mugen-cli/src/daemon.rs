@@ -0,0 +1,262 @@
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use mugen_core::checksum::{self, file_digest};
+use mugen_core::data::read_multiple_csvs_with;
+use mugen_core::error::{MugenError, Result};
+use mugen_core::fetch::{download_days, FetchOptions, Report, NYISO_BASE_URL};
+use mugen_core::schedule::Schedule;
+use mugen_viz::config::PipelineConfig;
+use mugen_viz::pipeline::PipelineReport;
+use mugen_viz::prometheus::{self, exposition, forecast_gauges, run_gauges, Gauge};
+use mugen_viz::server::{self, Request, Response, Routes};
+use polars::prelude::*;
+
+use crate::plot::plot_standard;
+use crate::{joined, publish};
+
+/// Runs the standard chart set at each time in the `[daemon]` schedule until
+/// stopped, downloading its inputs first.
+///
+/// On start, every run due since the one recorded in the state file and at most
+/// `catch_up_hours` late is made up, oldest first, since each charts its own day
+/// and a later run does not redraw an earlier day's charts. A failed run is reported and the
+/// next one goes ahead as scheduled. With `metrics_address` set, the outcome of the
+/// last run and its forecast-quality gauges are served there on `/metrics`.
+pub(crate) fn daemon(settings: &PipelineConfig) -> Result<()> {
+    let config = &settings.daemon;
+    let schedule: Schedule = Schedule::new(&config.schedule, &config.timezone)?;
+    println!("Running on the schedule {}", schedule);
+    let status: Arc<Mutex<DaemonStatus>> = Arc::default();
+    if let Some(address) = config.metrics_address.clone() {
+        let status: Arc<Mutex<DaemonStatus>> = Arc::clone(&status);
+        println!("Serving metrics on http://{}/metrics", address);
+        std::thread::spawn(move || {
+            let routes = Routes::new().get("/metrics", move |_: &Request| daemon_metrics(&status));
+            if let Err(err) = server::serve(&address, routes) {
+                eprintln!("Error: could not serve metrics on {}: {}", address, err);
+            }
+        });
+    }
+    let now: DateTime<Utc> = Utc::now();
+    let earliest: DateTime<Utc> = now - Duration::hours(i64::from(config.catch_up_hours));
+    let missed: Vec<DateTime<Utc>> = last_run(&config.state_file)
+        .filter(|_| config.catch_up_hours > 0)
+        .map(|last| schedule.runs_between(last.max(earliest), now))
+        .unwrap_or_default();
+    for due in missed {
+        println!(
+            "Catching up on the run due at {}",
+            local_time(due, &schedule)
+        );
+        scheduled_run(settings, &schedule, due, &status);
+    }
+    loop {
+        let Some(due) = schedule.next_after(Utc::now()) else {
+            return Err(MugenError::InvalidArgument(format!(
+                "the schedule {} never runs",
+                schedule
+            )));
+        };
+        println!("Next run at {}", local_time(due, &schedule));
+        // Sleeping in short steps keeps the run on time after the machine was
+        // suspended or its clock was set.
+        while let Ok(left) = (due - Utc::now()).to_std() {
+            std::thread::sleep(left.min(std::time::Duration::from_secs(60)));
+        }
+        scheduled_run(settings, &schedule, due, &status);
+    }
+}
+
+/// What the daemon's `/metrics` endpoint reports on.
+#[derive(Default)]
+struct DaemonStatus {
+    /// When the last run finished and whether it succeeded.
+    last_run: Option<(SystemTime, bool)>,
+    /// The settings of the last successful run, naming the files it downloaded.
+    settings: Option<PipelineConfig>,
+}
+
+/// Answers a scrape of the daemon's `/metrics`: the outcome of the last run and the
+/// forecast-quality gauges of the last successful one, read from its files.
+fn daemon_metrics(status: &Mutex<DaemonStatus>) -> Response {
+    let (last_run, settings) = {
+        let status = status
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        (status.last_run, status.settings.clone())
+    };
+    let mut gauges: Vec<Gauge> = Vec::new();
+    if let Some((finished, succeeded)) = last_run {
+        gauges.extend(run_gauges(finished, succeeded));
+    }
+    if let Some(settings) = settings {
+        let mut inputs: Vec<&str> = settings.truth_files.iter().map(String::as_str).collect();
+        inputs.push(&settings.forecast_file);
+        match joined(&settings).and_then(|joined| forecast_gauges(settings.zone, &joined, &inputs))
+        {
+            Ok(forecast) => gauges.extend(forecast),
+            Err(err) => return Response::error(&err),
+        }
+    }
+    Response::ok(prometheus::CONTENT_TYPE, exposition(&gauges))
+}
+
+/// Runs the standard chart set for the local day of `due`, reports the outcome,
+/// records it in `status` and records `due` in the state file.
+fn scheduled_run(
+    settings: &PipelineConfig,
+    schedule: &Schedule,
+    due: DateTime<Utc>,
+    status: &Mutex<DaemonStatus>,
+) {
+    let day: NaiveDate = due.with_timezone(&schedule.timezone()).date_naive();
+    let outcome: Result<(PipelineConfig, PipelineReport)> = daily_run(settings, day);
+    let mut status = status
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    status.last_run = Some((SystemTime::now(), outcome.is_ok()));
+    match outcome {
+        Ok((run_settings, report)) => {
+            for alert in &report.alerts {
+                eprintln!("Alert: {}: {}", alert.rule, alert.message);
+            }
+            for warning in &report.warnings {
+                eprintln!("Warning: {}", warning);
+            }
+            status.settings = Some(run_settings);
+        }
+        Err(err) => eprintln!(
+            "Error: the run due at {} failed: {}",
+            local_time(due, schedule),
+            err
+        ),
+    }
+    drop(status);
+    // Failed runs are recorded too, so a lasting failure is not retried on every
+    // restart.
+    let path: &str = &settings.daemon.state_file;
+    if let Err(err) = std::fs::write(path, due.to_rfc3339()) {
+        eprintln!("Warning: could not record the run in \"{}\": {}", path, err);
+    }
+}
+
+/// Downloads the actual load of `day` and the configured days before it, and the
+/// forecast issued the day before, then renders, scores and publishes the standard
+/// chart set.
+///
+/// The files of `day` and the day before are downloaded again, since NYISO keeps
+/// adding to them, see [`refresh_days`]. Returns the settings the run used, naming
+/// the downloaded files, and its report.
+fn daily_run(
+    settings: &PipelineConfig,
+    day: NaiveDate,
+) -> Result<(PipelineConfig, PipelineReport)> {
+    let config = &settings.daemon;
+    let from: NaiveDate = day - Duration::days(i64::from(config.days));
+    let issued: NaiveDate = day - Duration::days(1);
+    let mut reports: Vec<Report> = vec![Report::ActualLoad, Report::LoadForecast];
+    if !settings.price_files.is_empty() {
+        reports.push(Report::DayAheadPrice);
+    }
+    let options: FetchOptions = FetchOptions::default();
+    let data_dir: &str = &config.data_dir;
+    for report in &reports {
+        refresh_days(*report, issued, day, data_dir, &options)?;
+    }
+    let download = |report: Report, from: NaiveDate| {
+        download_days(NYISO_BASE_URL, report, from, day, data_dir, &options)
+    };
+    let truth_files: Vec<String> = download(Report::ActualLoad, from)?;
+    let forecast_file: String = download(Report::LoadForecast, issued)?.remove(0);
+    let price_files: Vec<String> = if settings.price_files.is_empty() {
+        Vec::new()
+    } else {
+        download(Report::DayAheadPrice, from)?
+    };
+    let run_settings: PipelineConfig = PipelineConfig {
+        truth_files,
+        forecast_file,
+        price_files,
+        ..settings.clone()
+    };
+    run_settings.validate()?;
+
+    let mut inputs: Vec<String> = run_settings.truth_files.clone();
+    inputs.push(run_settings.forecast_file.clone());
+    inputs.extend(run_settings.price_files.iter().cloned());
+    let truth_files: Vec<&str> = run_settings
+        .truth_files
+        .iter()
+        .map(String::as_str)
+        .collect();
+    let truth: DataFrame = read_multiple_csvs_with(truth_files, &settings.resources)?;
+    let report: PipelineReport = plot_standard(truth, &run_settings)?;
+    let report: PipelineReport = publish(&run_settings, &inputs, report)?;
+    Ok((run_settings, report))
+}
+
+/// Downloads `report`'s files of `from..=to` again and moves each over the copy in
+/// `dir`, along with a new record of its digest.
+///
+/// The files are downloaded into a staging folder inside `dir` first, so a failed or
+/// interrupted download leaves the previous copies in place for the next run.
+///
+/// # Errors
+///
+/// Returns the errors of [`download_days`], and `MugenError::Write` if a file cannot
+/// be moved into place.
+fn refresh_days(
+    report: Report,
+    from: NaiveDate,
+    to: NaiveDate,
+    dir: &str,
+    options: &FetchOptions,
+) -> Result<()> {
+    let staging: String = std::path::Path::new(dir)
+        .join(".refresh")
+        .to_string_lossy()
+        .into_owned();
+    // Leftovers of an interrupted refresh would be taken as already downloaded.
+    if let Err(err) = std::fs::remove_dir_all(&staging) {
+        if err.kind() != std::io::ErrorKind::NotFound {
+            return Err(MugenError::Write {
+                path: staging,
+                source: err,
+            });
+        }
+    }
+    let fresh: Vec<String> = download_days(NYISO_BASE_URL, report, from, to, &staging, options)?;
+    // The files come in date order.
+    for (fresh, day) in fresh.into_iter().zip(from.iter_days()) {
+        let target: String = std::path::Path::new(dir)
+            .join(report.file_name(day))
+            .to_string_lossy()
+            .into_owned();
+        std::fs::rename(&fresh, &target).map_err(|source| MugenError::Write {
+            path: target.clone(),
+            source,
+        })?;
+        checksum::record(&file_digest(&target)?)?;
+    }
+    // Only the staged digests are left.
+    let _ = std::fs::remove_dir_all(&staging);
+    Ok(())
+}
+
+/// Returns when the last run recorded in the state file at `path` was due, or
+/// `None` if there is no readable record.
+fn last_run(path: &str) -> Option<DateTime<Utc>> {
+    let text: String = std::fs::read_to_string(path).ok()?;
+    DateTime::parse_from_rfc3339(text.trim())
+        .ok()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+/// Formats `at` in the timezone of `schedule`.
+fn local_time(at: DateTime<Utc>, schedule: &Schedule) -> String {
+    at.with_timezone(&schedule.timezone())
+        .format("%Y-%m-%d %H:%M %Z")
+        .to_string()
+}
@@ -0,0 +1,32 @@
+use chrono::{NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use mugen_core::error::Result;
+use mugen_core::zone::Zone;
+use mugen_viz::config::PipelineConfig;
+use mugen_viz::dashboard::{self, ZoneStatus};
+
+use crate::inputs::Inputs;
+
+/// Shows every zone's recent actual vs forecast load, current error and data
+/// freshness in a terminal dashboard, refreshed every `interval` seconds.
+///
+/// Each refresh reads the configured files through [`Inputs`], parsing only those
+/// that changed, and measures freshness against the current time in the timezone
+/// of the data.
+pub(crate) fn show_dashboard(interval: u64, hours: usize, settings: &PipelineConfig) -> Result<()> {
+    let inputs: Inputs = Inputs::new(settings);
+    let load = || -> Result<Vec<ZoneStatus>> {
+        let mut statuses: Vec<ZoneStatus> = Vec::new();
+        for zone in Zone::ALL {
+            let (truth, forecast, _) = inputs.series(zone, None)?;
+            let timezone: Tz = truth
+                .timezone()
+                .parse()
+                .unwrap_or(chrono_tz::America::New_York);
+            let now: NaiveDateTime = Utc::now().with_timezone(&timezone).naive_local();
+            statuses.push(ZoneStatus::of(zone, &truth, &forecast, hours, now)?);
+        }
+        Ok(statuses)
+    };
+    dashboard::run(std::time::Duration::from_secs(interval.max(1)), load)
+}
@@ -0,0 +1,125 @@
+use chrono::{DateTime, NaiveDateTime};
+use mugen_core::data::{compare_forecasts, process_pred, read_csv_to_df};
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::{MugenError, Result};
+use mugen_core::zone::Zone;
+use mugen_viz::config::PipelineConfig;
+use mugen_viz::graph::{ForecastDiffGraph, Graph, GraphConfig, Vintage};
+use mugen_viz::pipeline::{DatasetSummary, PipelineReport};
+
+use crate::issue_label;
+
+/// Compares the configured zone's forecasts in `first` and `second`, printing the
+/// largest and mean difference and every hour that moved by more than `min_delta`,
+/// and renders both with the differences if `chart`.
+pub(crate) fn diff(
+    first: &str,
+    second: &str,
+    min_delta: f64,
+    chart: bool,
+    settings: &PipelineConfig,
+) -> Result<PipelineReport> {
+    let zone: Zone = settings.zone;
+    let read = |path: &str| -> Result<Vintage> {
+        Ok(Vintage {
+            issued: issue_label(path),
+            forecast: process_pred(read_csv_to_df(path)?, zone)?,
+        })
+    };
+    let (first_forecast, second_forecast): (Vintage, Vintage) = (read(first)?, read(second)?);
+    let compared: TimeSeriesDataset =
+        compare_forecasts(&first_forecast.forecast, &second_forecast.forecast)?;
+    if compared.height() == 0 {
+        return Err(MugenError::NoData(format!(
+            "{} and {} share no hours to compare",
+            first, second
+        )));
+    }
+
+    let (before, after, delta) = (
+        compared.values("First")?,
+        compared.values("Second")?,
+        compared.values("Delta")?,
+    );
+    let hours: Vec<(NaiveDateTime, f64, f64, f64)> = compared
+        .timestamps()?
+        .into_iter()
+        .zip(&before)
+        .zip(after.into_iter().zip(&delta))
+        .filter_map(|((ms, before), (after, delta))| {
+            let at: NaiveDateTime = DateTime::from_timestamp_millis(ms?)?.naive_utc();
+            Some((at, before?, after?, delta?))
+        })
+        .collect();
+    let moved: Vec<&(NaiveDateTime, f64, f64, f64)> = hours
+        .iter()
+        .filter(|hour| hour.3.abs() > min_delta)
+        .collect();
+    let mean_delta: f64 = hours.iter().map(|hour| hour.3).sum::<f64>() / hours.len() as f64;
+    let mean_abs: f64 = hours.iter().map(|hour| hour.3.abs()).sum::<f64>() / hours.len() as f64;
+    println!(
+        "{} against {} for {} over {} shared hours",
+        second,
+        first,
+        zone,
+        hours.len()
+    );
+    // `min_by` on the reversed order keeps the earliest of equally large differences.
+    if let Some((at, before, after, delta)) =
+        hours.iter().min_by(|a, b| b.3.abs().total_cmp(&a.3.abs()))
+    {
+        println!(
+            "Largest difference: {:+.1} MW at {} ({:.1} to {:.1} MW)",
+            delta,
+            at.format("%Y-%m-%d %H:%M"),
+            before,
+            after
+        );
+    }
+    println!(
+        "Mean difference: {:+.1} MW, mean absolute difference: {:.1} MW",
+        mean_delta, mean_abs
+    );
+    println!(
+        "{} of {} hours moved by more than {} MW",
+        moved.len(),
+        hours.len(),
+        min_delta
+    );
+    if !moved.is_empty() {
+        println!(
+            "{:<16} {:>10} {:>10} {:>9}",
+            "time", "first", "second", "delta"
+        );
+        for (at, before, after, delta) in moved {
+            println!(
+                "{:<16} {:>10.1} {:>10.1} {:>+9.1}",
+                at.format("%Y-%m-%d %H:%M"),
+                before,
+                after,
+                delta
+            );
+        }
+    }
+
+    let mut report: PipelineReport = PipelineReport::default();
+    report
+        .datasets
+        .push(DatasetSummary::of(&first_forecast.forecast)?);
+    report
+        .datasets
+        .push(DatasetSummary::of(&second_forecast.forecast)?);
+    if chart {
+        let graph: ForecastDiffGraph = ForecastDiffGraph {
+            config: GraphConfig {
+                title: settings.graph.labels.titles.difference.clone(),
+                ..settings.graph.clone()
+            },
+            first: first_forecast,
+            second: second_forecast,
+            zone,
+        };
+        report.artifacts.push(graph.draw()?);
+    }
+    Ok(report)
+}
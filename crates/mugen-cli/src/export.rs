@@ -0,0 +1,144 @@
+use chrono::NaiveDate;
+use mugen_core::data::{filter_period, process_truth, process_weather, read_multiple_csvs_with};
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::{MugenError, Result};
+use mugen_core::export::{write_partitioned, write_table, ExportFormat};
+use mugen_core::features::features;
+use mugen_viz::config::PipelineConfig;
+use mugen_viz::pipeline::{DatasetSummary, PipelineReport};
+use polars::prelude::*;
+
+use crate::{data_files, joined};
+
+/// Writes the configured zone's actual load joined against the forecast, with the
+/// residual, keeping the days from `from` to `to` if given, and returns the paths
+/// written.
+///
+/// With a `format`, the table goes to `output` or "joined_<zone>.<format>" in the
+/// chart output directory. Without one, it goes to a Parquet store partitioned by
+/// zone and date under `output` or "joined" in the chart output directory. The
+/// table is the one the standard line chart plots, see [`joined`].
+pub(crate) fn export_joined(
+    format: Option<ExportFormat>,
+    output: Option<String>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    settings: &PipelineConfig,
+) -> Result<Vec<String>> {
+    let mut joined: TimeSeriesDataset = joined(settings)?;
+    if from.is_some() || to.is_some() {
+        joined = filter_period(
+            joined,
+            from.unwrap_or(NaiveDate::MIN),
+            to.unwrap_or(NaiveDate::MAX),
+        )?;
+    }
+    let name: String = match format {
+        Some(format) => format!("joined_{}.{}", settings.zone.slug(), format),
+        None => "joined".to_string(),
+    };
+    let path: String = match output {
+        Some(path) => path,
+        None => {
+            let output_dir: &str = &settings.graph.output_dir;
+            std::fs::create_dir_all(output_dir).map_err(|source| MugenError::Write {
+                path: output_dir.to_string(),
+                source,
+            })?;
+            std::path::Path::new(output_dir)
+                .join(name)
+                .to_string_lossy()
+                .into_owned()
+        }
+    };
+    match format {
+        Some(format) => {
+            write_table(&joined, &path, format)?;
+            Ok(vec![path])
+        }
+        None => write_partitioned(&joined, &path, settings.zone),
+    }
+}
+
+/// Writes the configured zone's training features, see [`features`], keeping the
+/// days from `from` to `to` if given, to `output` or "features_<zone>.<format>" in
+/// the chart output directory.
+///
+/// The temperature comes from the weather files under `data/`; without any, the
+/// column is left out with a warning.
+pub(crate) fn export_features(
+    format: ExportFormat,
+    output: Option<String>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    lags: &[usize],
+    settings: &PipelineConfig,
+) -> Result<PipelineReport> {
+    let raw_truth: DataFrame = read_multiple_csvs_with(
+        settings.truth_files.iter().map(String::as_str).collect(),
+        &settings.resources,
+    )?;
+    let truth: TimeSeriesDataset = settings
+        .timezones
+        .truth(process_truth(raw_truth, settings.zone)?)?;
+    let mut report: PipelineReport = PipelineReport::default();
+    let table: TimeSeriesDataset = feature_table(&truth, from, to, lags, settings, &mut report)?;
+    let path: String = match output {
+        Some(path) => path,
+        None => {
+            let output_dir: &str = &settings.graph.output_dir;
+            std::fs::create_dir_all(output_dir).map_err(|source| MugenError::Write {
+                path: output_dir.to_string(),
+                source,
+            })?;
+            std::path::Path::new(output_dir)
+                .join(format!("features_{}.{}", settings.zone.slug(), format))
+                .to_string_lossy()
+                .into_owned()
+        }
+    };
+    write_table(&table, &path, format)?;
+    println!("{}", path);
+    report.datasets = vec![DatasetSummary::of(&truth)?, DatasetSummary::of(&table)?];
+    Ok(report)
+}
+
+/// Builds the training features of `truth`, see [`features`], keeping the days from
+/// `from` to `to` if given.
+///
+/// The temperature comes from the weather files under `data/`; without any, the
+/// column is left out with a warning in `report`.
+pub(crate) fn feature_table(
+    truth: &TimeSeriesDataset,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    lags: &[usize],
+    settings: &PipelineConfig,
+    report: &mut PipelineReport,
+) -> Result<TimeSeriesDataset> {
+    let weather_files: Vec<String> = data_files("weather.csv")?;
+    let temperature: Option<TimeSeriesDataset> = if weather_files.is_empty() {
+        report.warnings.push(
+            "no weather files under data/; features built without temperature; \
+             download them with `mugen_park weather`"
+                .to_string(),
+        );
+        None
+    } else {
+        let raw: DataFrame = read_multiple_csvs_with(
+            weather_files.iter().map(String::as_str).collect(),
+            &settings.resources,
+        )?;
+        Some(process_weather(raw, settings.zone)?.in_timezone(settings.timezones.shown())?)
+    };
+    let table: TimeSeriesDataset = features(truth, temperature.as_ref(), lags)?;
+    if from.is_some() || to.is_some() {
+        filter_period(
+            table,
+            from.unwrap_or(NaiveDate::MIN),
+            to.unwrap_or(NaiveDate::MAX),
+        )
+    } else {
+        Ok(table)
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::NaiveDate;
+use mugen_core::data::{process_truth, read_multiple_csvs_with, zones_in};
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::Result;
+use mugen_core::export::write_partitioned;
+use mugen_core::fetch::{
+    download_days, download_market_days, FetchOptions, Report, NYISO_BASE_URL,
+};
+use mugen_core::iso::{FileKind, Market};
+use mugen_core::resources::Resources;
+use polars::prelude::*;
+
+/// Downloads the actual-load and forecast files for `from..=to` into `dir`, the
+/// real-time load files if `realtime` is set and both zonal price files if `prices`
+/// is set, retried and paced as `options` says, and returns the actual-load files.
+pub(crate) fn fetch(
+    from: NaiveDate,
+    to: NaiveDate,
+    dir: &str,
+    realtime: bool,
+    prices: bool,
+    options: &FetchOptions,
+) -> Result<Vec<String>> {
+    let mut reports: Vec<Report> = vec![Report::ActualLoad, Report::LoadForecast];
+    if realtime {
+        reports.push(Report::RealTimeLoad);
+    }
+    if prices {
+        reports.extend([Report::DayAheadPrice, Report::RealTimePrice]);
+    }
+    let mut truth_files: Vec<String> = Vec::new();
+    for report in reports {
+        for path in download_days(NYISO_BASE_URL, report, from, to, dir, options)? {
+            println!("{}", path);
+            if report == Report::ActualLoad {
+                truth_files.push(path);
+            }
+        }
+    }
+    Ok(truth_files)
+}
+
+/// Downloads `market`'s actual-load and forecast files for `from..=to` into `dir`,
+/// retried and paced as `options` says, and prints their paths.
+pub(crate) fn fetch_market(
+    market: Market,
+    from: NaiveDate,
+    to: NaiveDate,
+    dir: &str,
+    options: &FetchOptions,
+) -> Result<()> {
+    for kind in [FileKind::Actual, FileKind::Forecast] {
+        for path in download_market_days(market, kind, from, to, dir, options)? {
+            println!("{}", path);
+        }
+    }
+    Ok(())
+}
+
+/// Writes every zone's actual load in `truth_files` into the Parquet store at
+/// `store`, under "actual", partitioned by zone and date, and prints the files
+/// written.
+pub(crate) fn store_actuals(
+    truth_files: &[String],
+    store: &str,
+    resources: &Resources,
+) -> Result<()> {
+    let paths: Vec<&str> = truth_files.iter().map(String::as_str).collect();
+    let raw_truth: DataFrame = read_multiple_csvs_with(paths, resources)?;
+    let root: String = std::path::Path::new(store)
+        .join("actual")
+        .to_string_lossy()
+        .into_owned();
+    for zone in zones_in(&raw_truth)? {
+        let truth: TimeSeriesDataset = process_truth(raw_truth.clone(), zone)?;
+        for path in write_partitioned(&truth, &root, zone)? {
+            println!("{}", path);
+        }
+    }
+    Ok(())
+}
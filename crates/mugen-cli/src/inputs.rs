@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use chrono::NaiveDate;
+use mugen_core::data::{
+    filter_period, process_pred, process_truth, read_csv_to_df, read_multiple_csvs_with,
+};
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::{MugenError, Result};
+use mugen_core::resources::Resources;
+use mugen_core::zone::Zone;
+use mugen_viz::config::{PipelineConfig, Timezones};
+use polars::prelude::*;
+
+/// The configured input files of `live`, `serve` and `dashboard`: the actual load of
+/// `truth_files` and the forecasts of `forecast_file` and `forecast_files`.
+///
+/// Files are parsed when first needed and again only once their modification time
+/// changes, so requests do not re-read every file. Files that do not exist yet are
+/// skipped, so one downloaded later, e.g. by the daemon, shows up without a restart.
+pub(crate) struct Inputs {
+    truth_files: Vec<String>,
+    forecast_files: Vec<String>,
+    timezones: Timezones,
+    resources: Resources,
+    /// The actual load of every truth file read, combined, and when each file was
+    /// last modified as it was read.
+    truth: Mutex<Option<(Modified, DataFrame)>>,
+    /// Each forecast file read, by path, and when it was last modified as it was read.
+    forecasts: Mutex<HashMap<String, (SystemTime, DataFrame)>>,
+}
+
+impl Inputs {
+    /// Collects the input files of `settings`, without reading them yet.
+    pub(crate) fn new(settings: &PipelineConfig) -> Self {
+        let mut forecast_files: Vec<String> = vec![settings.forecast_file.clone()];
+        for path in &settings.forecast_files {
+            if !forecast_files.contains(path) {
+                forecast_files.push(path.clone());
+            }
+        }
+        Inputs {
+            truth_files: settings.truth_files.clone(),
+            forecast_files,
+            timezones: settings.timezones.clone(),
+            resources: settings.resources.clone(),
+            truth: Mutex::new(None),
+            forecasts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns `zone`'s actual load and forecast on the days of `period`, inclusive,
+    /// or on every day if `None`, and the files they were read from.
+    ///
+    /// Each hour's forecast comes from the forecast file starting latest among those
+    /// with that hour, i.e. the one issued last, so files issued on different days
+    /// cover a range none of them covers alone.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::NoData` if none of the truth files exist or no forecast
+    /// file has an hour in `period`, and the errors of reading and processing the
+    /// files.
+    pub(crate) fn series(
+        &self,
+        zone: Zone,
+        period: Option<(NaiveDate, NaiveDate)>,
+    ) -> Result<(TimeSeriesDataset, TimeSeriesDataset, Vec<String>)> {
+        let (raw_truth, mut paths) = self.raw_truth()?;
+        let mut truth: TimeSeriesDataset = self.timezones.truth(process_truth(raw_truth, zone)?)?;
+        // Every file with an hour in the period, by its first hour.
+        let mut covering: Vec<(i64, String, TimeSeriesDataset)> = Vec::new();
+        for (path, raw) in self.raw_forecasts()? {
+            let mut forecast: TimeSeriesDataset =
+                self.timezones.forecast(process_pred(raw, zone)?)?;
+            if let Some((from, to)) = period {
+                forecast = filter_period(forecast, from, to)?;
+            }
+            if let Some(start) = forecast.timestamps()?.into_iter().flatten().min() {
+                covering.push((start, path, forecast));
+            }
+        }
+        covering.sort_by(|(first, ..), (second, ..)| second.cmp(first));
+        let mut forecast: Option<TimeSeriesDataset> = None;
+        // Hours from here on are taken from a later file already.
+        let mut covered_from: i64 = i64::MAX;
+        for (start, path, file_forecast) in covering {
+            let mask: BooleanChunked = file_forecast
+                .timestamps()?
+                .into_iter()
+                .map(|at| at.is_some_and(|at| at < covered_from))
+                .collect();
+            let earlier: TimeSeriesDataset =
+                file_forecast.map_frame(|frame| Ok(frame.filter(&mask)?))?;
+            covered_from = covered_from.min(start);
+            if earlier.height() == 0 {
+                continue;
+            }
+            paths.push(path);
+            forecast = Some(match forecast {
+                Some(later) => later.map_frame(|frame| Ok(frame.vstack(earlier.frame())?))?,
+                None => earlier,
+            });
+        }
+        let Some(forecast) = forecast else {
+            return Err(MugenError::NoData(match period {
+                Some((from, to)) => format!(
+                    "no forecast file has hours from {} to {}; list one in forecast_files",
+                    from, to
+                ),
+                None => "none of the forecast files exist yet".to_string(),
+            }));
+        };
+        if let Some((from, to)) = period {
+            truth = filter_period(truth, from, to)?;
+        }
+        Ok((truth, forecast.sorted()?, paths))
+    }
+
+    /// Returns the actual load of every truth file that exists, combined, and their
+    /// paths, parsing them again only if one changed or appeared.
+    fn raw_truth(&self) -> Result<(DataFrame, Vec<String>)> {
+        let present: Modified = modified(&self.truth_files);
+        let paths: Vec<String> = present.iter().map(|(path, _)| path.clone()).collect();
+        if paths.is_empty() {
+            return Err(MugenError::NoData(
+                "none of the truth files exist yet; download them with `mugen_park fetch`"
+                    .to_string(),
+            ));
+        }
+        let mut cached = self
+            .truth
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some((read, frame)) = cached.as_ref() {
+            if *read == present {
+                return Ok((frame.clone(), paths));
+            }
+        }
+        let frame: DataFrame =
+            read_multiple_csvs_with(paths.iter().map(String::as_str).collect(), &self.resources)?;
+        *cached = Some((present, frame.clone()));
+        Ok((frame, paths))
+    }
+
+    /// Returns every forecast file that exists and its contents, parsing only those
+    /// that changed or appeared since the last call.
+    fn raw_forecasts(&self) -> Result<Vec<(String, DataFrame)>> {
+        let mut cached = self
+            .forecasts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut frames: Vec<(String, DataFrame)> = Vec::new();
+        for (path, modified) in modified(&self.forecast_files) {
+            let frame: DataFrame = match cached.get(&path) {
+                Some((read, frame)) if *read == modified => frame.clone(),
+                _ => {
+                    let frame: DataFrame = read_csv_to_df(&path)?;
+                    cached.insert(path.clone(), (modified, frame.clone()));
+                    frame
+                }
+            };
+            frames.push((path, frame));
+        }
+        Ok(frames)
+    }
+}
+
+/// Files that exist, in order, each with when it was last modified.
+type Modified = Vec<(String, SystemTime)>;
+
+/// Returns each of `paths` that exists with when it was last modified, in order.
+fn modified(paths: &[String]) -> Modified {
+    paths
+        .iter()
+        .filter_map(|path| {
+            let modified: SystemTime = std::fs::metadata(path).ok()?.modified().ok()?;
+            Some((path.clone(), modified))
+        })
+        .collect()
+}
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use mugen_core::data::join_actual_forecast;
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::Result;
+use mugen_core::zone::Zone;
+use mugen_viz::config::PipelineConfig;
+use mugen_viz::graph::{Graph, GraphConfig, LineGraph};
+use mugen_viz::live;
+use mugen_viz::prometheus::{exposition, forecast_gauges};
+
+use crate::inputs::Inputs;
+
+/// Serves the configured zone's actual vs forecast as a live chart that refreshes
+/// every `interval` seconds, and its forecast-quality gauges on `/metrics`.
+///
+/// Each refresh and scrape reads the configured files through [`Inputs`], so a file
+/// that is rewritten, or that did not exist yet, shows up without a restart.
+pub(crate) fn plot_live(address: &str, interval: u64, settings: &PipelineConfig) -> Result<()> {
+    let zone: Zone = settings.zone;
+    let inputs: Arc<Inputs> = Arc::new(Inputs::new(settings));
+    let config: GraphConfig = GraphConfig {
+        deterministic: true,
+        ..settings.graph.clone()
+    };
+    let chart_inputs: Arc<Inputs> = Arc::clone(&inputs);
+    let chart_config: GraphConfig = config.clone();
+    let chart = move || {
+        let (truth, forecast, _) = chart_inputs.series(zone, None)?;
+        let line_graph: LineGraph = LineGraph::builder()
+            .config(chart_config.clone())
+            .truth(truth)
+            .forecast(forecast)
+            .zone(zone)
+            .title(chart_config.labels.titles.live.as_str())
+            .error_band(true)
+            .max_points(2000)
+            .time_format("%m/%d %H:%M")
+            .build()?;
+        line_graph.to_echarts_json()
+    };
+    let metrics = move || {
+        let (truth, forecast, paths) = inputs.series(zone, None)?;
+        let joined: TimeSeriesDataset = join_actual_forecast(&truth, &forecast)?;
+        let inputs: Vec<&str> = paths.iter().map(String::as_str).collect();
+        Ok(exposition(&forecast_gauges(zone, &joined, &inputs)?))
+    };
+    println!("Serving the live chart on http://{}", address);
+    live::serve(address, interval, &config, chart, metrics)
+}
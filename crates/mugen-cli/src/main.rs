@@ -0,0 +1,776 @@
+use chrono::{Duration, NaiveDate, Utc};
+use clap::Parser;
+use mugen_core::archive::ARCHIVE_DIR;
+use mugen_core::catalog::CatalogQuery;
+use mugen_core::data::{
+    join_actual_forecast, load_joined_with, process_pred, process_truth, read_csv_to_df,
+    read_multiple_csvs_with,
+};
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::{MugenError, Result};
+use mugen_core::export::ExportFormat;
+use mugen_core::fetch::{FetchOptions, RateLimit};
+#[cfg(feature = "sqlite")]
+use mugen_core::history::{MetricsHistory, RunRecord};
+use mugen_core::iso::Market;
+use mugen_core::metrics::{bias, mape, peak_error};
+use mugen_core::split::{splits, Split};
+use mugen_core::weather::{download_weather, WEATHER_BASE_URL};
+use mugen_viz::config::{read_config, PipelineConfig, Timezones};
+use mugen_viz::graph::NumberFormat;
+use mugen_viz::manifest::Manifest;
+use mugen_viz::pipeline::PipelineReport;
+use mugen_viz::report::write_html;
+use mugen_viz::sink::{ArtifactSink, Sink};
+use polars::prelude::*;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+mod backfill;
+mod backtest;
+mod catalog;
+mod cli;
+mod compact;
+mod crosscheck;
+mod daemon;
+mod dashboard;
+mod diff;
+mod export;
+mod fetch;
+mod inputs;
+mod live;
+mod plot;
+mod score;
+mod serve;
+mod timing;
+use backfill::backfill;
+use backtest::run_backtest;
+use catalog::{cataloged, print_catalog};
+use cli::{Cli, Command, ExportCommand, PlotCommand};
+use compact::compact_months;
+use crosscheck::crosscheck;
+use daemon::daemon;
+use dashboard::show_dashboard;
+use diff::diff;
+use export::{export_features, export_joined};
+use fetch::{fetch, fetch_market, store_actuals};
+use live::plot_live;
+use plot::{
+    day_ahead_files, plot_all, plot_area, plot_baseline, plot_day_ahead, plot_ensemble,
+    plot_interval, plot_onnx, plot_pie, plot_radar, plot_scenario, plot_standard, plot_terminal,
+    plot_vintages, vintage_files,
+};
+use score::score_market;
+use serve::serve;
+use timing::StageTimings;
+fn main() {
+    let cli: Cli = Cli::parse();
+    limit_polars_threads(&cli);
+
+    // Per-span log lines are opt-in through RUST_LOG, e.g. RUST_LOG=info; stage
+    // timings are always collected.
+    let timings = StageTimings::default();
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_span_events(FmtSpan::CLOSE)
+                .with_filter(EnvFilter::from_default_env()),
+        )
+        .with(timings.clone())
+        .init();
+
+    let result = run(cli);
+    eprint!("{}", timings);
+    match result {
+        Ok(report) => {
+            for alert in &report.alerts {
+                eprintln!("Alert: {}: {}", alert.rule, alert.message);
+            }
+            for warning in &report.warnings {
+                eprintln!("Warning: {}", warning);
+            }
+        }
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Caps polars' thread pool at the thread limit given on the command line or in the
+/// config file.
+///
+/// polars sizes its pool from `POLARS_MAX_THREADS` when first used, and changing the
+/// environment is only sound while no other thread can read it, so this runs first
+/// thing in `main`. A config file that cannot be read is reported by [`run`].
+fn limit_polars_threads(cli: &Cli) {
+    let threads: Option<usize> = cli.threads.or_else(|| {
+        let path: &String = cli.config.as_ref()?;
+        read_config::<PipelineConfig>(path).ok()?.resources.threads
+    });
+    if let Some(threads) = threads {
+        std::env::set_var("POLARS_MAX_THREADS", threads.to_string());
+    }
+}
+
+/// Loads the ground truth and renders the charts selected on the command line,
+/// returning what the run produced.
+fn run(cli: Cli) -> Result<PipelineReport> {
+    let mut settings: PipelineConfig = match &cli.config {
+        Some(path) => read_config(path)?,
+        None => PipelineConfig::default(),
+    };
+    if let Some(resolution) = cli.resolution {
+        let (graph_width, graph_height) = resolution.size();
+        settings.graph.graph_width = graph_width;
+        settings.graph.graph_height = graph_height;
+    }
+    if let Some(locale) = cli.locale {
+        settings.graph.number_format = NumberFormat {
+            unit: settings.graph.number_format.unit.take(),
+            ..locale
+        };
+    }
+    if let Some(unit) = cli.unit {
+        settings.graph.number_format.unit = Some(unit).filter(|unit| !unit.is_empty());
+    }
+    if let Some(style) = cli.style {
+        settings.graph.style = style;
+    }
+    if let Some(backend) = cli.backend {
+        settings.graph.backend = backend;
+    }
+    if let Some(on_collision) = cli.on_collision {
+        settings.graph.on_collision = on_collision;
+    }
+    if let Some(threads) = cli.threads {
+        settings.resources.threads = Some(threads);
+    }
+    if let Some(memory_mb) = cli.memory_mb {
+        settings.resources.memory_mb = Some(memory_mb);
+    }
+    if let Some(timezone) = cli.display_timezone {
+        settings.timezones.display = Some(timezone);
+    }
+
+    // Fetching is how missing input files get downloaded, so only the period matters;
+    // the daemon downloads its own inputs, and the live chart, server and dashboard
+    // read the configured files as they appear.
+    let mut problems: Vec<String> = match &cli.command {
+        Some(
+            Command::Fetch { .. }
+            | Command::Backfill { .. }
+            | Command::Catalog { .. }
+            | Command::Compact { .. }
+            | Command::Weather { .. },
+        ) => Vec::new(),
+        Some(
+            Command::Daemon
+            | Command::Live { .. }
+            | Command::Serve { .. }
+            | Command::Dashboard { .. }
+            | Command::Diff { .. }
+            | Command::Score { .. }
+            | Command::Crosscheck { .. }
+            | Command::Backtest { .. },
+        ) => settings.setting_problems(),
+        _ => settings.problems(),
+    };
+    if let Some(
+        Command::Plot {
+            chart:
+                PlotCommand::All { from, to }
+                | PlotCommand::DayAhead { from, to }
+                | PlotCommand::Scenario { from, to, .. },
+        }
+        | Command::Fetch { from, to, .. }
+        | Command::Backfill { from, to, .. }
+        | Command::Weather { from, to, .. }
+        | Command::Crosscheck { from, to, .. }
+        | Command::Backtest { from, to, .. },
+    ) = &cli.command
+    {
+        if from > to {
+            problems.push(format!(
+                "--from {} is after --to {}; swap them",
+                from.format("%Y-%m-%d"),
+                to.format("%Y-%m-%d")
+            ));
+        }
+    }
+    if let Some(Command::Plot {
+        chart: PlotCommand::Interval { coverage, .. },
+    }) = &cli.command
+    {
+        if !(*coverage > 0.0 && *coverage < 1.0) {
+            problems.push(format!(
+                "--coverage {} must be between 0 and 1, e.g. 0.8 for an 80% interval",
+                coverage
+            ));
+        }
+    }
+    if let Some(Command::Backtest {
+        train_days,
+        test_days,
+        ..
+    }) = &cli.command
+    {
+        if *train_days == 0 || *test_days == 0 {
+            problems.push(format!(
+                "--train-days {} and --test-days {} must both be at least 1",
+                train_days, test_days
+            ));
+        }
+    }
+    if let Some(Command::Fetch {
+        iso,
+        realtime,
+        prices,
+        store,
+        ..
+    }) = &cli.command
+    {
+        if *iso != Market::Nyiso && (*realtime || *prices || store.is_some()) {
+            problems.push(format!(
+                "--realtime, --prices and --store are only available for NYISO, not {}",
+                iso
+            ));
+        }
+    }
+    if !problems.is_empty() {
+        return Err(MugenError::Settings(problems));
+    }
+    let load_truth = || -> Result<DataFrame> {
+        read_multiple_csvs_with(
+            settings.truth_files.iter().map(String::as_str).collect(),
+            &settings.resources,
+        )
+    };
+
+    let mut inputs: Vec<String> = settings.truth_files.clone();
+    let report: PipelineReport = match cli.command {
+        Some(Command::Plot {
+            chart: PlotCommand::All { from, to },
+        }) => {
+            inputs.push(settings.forecast_file.clone());
+            plot_all(load_truth()?, from, to, &settings)?
+        }
+        Some(Command::Plot {
+            chart: PlotCommand::Vintages { day, forecasts },
+        }) => {
+            let forecasts: Vec<String> = vintage_files(forecasts)?;
+            inputs.extend(forecasts.iter().cloned());
+            plot_vintages(load_truth()?, day, forecasts, &settings)?
+        }
+        Some(Command::Plot {
+            chart:
+                PlotCommand::Baseline {
+                    day,
+                    days,
+                    seasonality,
+                },
+        }) => plot_baseline(load_truth()?, day, days as usize, seasonality, &settings)?,
+        Some(Command::Plot {
+            chart:
+                PlotCommand::Scenario {
+                    from,
+                    to,
+                    adjustments,
+                    target,
+                },
+        }) => {
+            inputs.push(settings.forecast_file.clone());
+            plot_scenario(load_truth()?, from, to, &adjustments, target, &settings)?
+        }
+        Some(Command::Plot {
+            chart:
+                PlotCommand::Onnx {
+                    model,
+                    from,
+                    to,
+                    lags,
+                },
+        }) => plot_onnx(load_truth()?, &model, from, to, &lags, &settings)?,
+        Some(Command::Plot {
+            chart:
+                PlotCommand::Interval {
+                    day,
+                    days,
+                    forecaster,
+                    seasonality,
+                    lead,
+                    calibration_days,
+                    coverage,
+                },
+        }) => plot_interval(
+            load_truth()?,
+            day,
+            days,
+            forecaster,
+            seasonality,
+            lead,
+            calibration_days,
+            coverage,
+            &settings,
+        )?,
+        Some(Command::Plot {
+            chart:
+                PlotCommand::Ensemble {
+                    forecasts,
+                    blend,
+                    window,
+                },
+        }) => {
+            let forecasts: Vec<String> = vintage_files(forecasts)?;
+            inputs.extend(forecasts.iter().cloned());
+            plot_ensemble(load_truth()?, forecasts, blend, window as usize, &settings)?
+        }
+        Some(Command::Plot {
+            chart: PlotCommand::DayAhead { from, to },
+        }) => {
+            inputs = day_ahead_files(from, to);
+            plot_day_ahead(from, to, &settings)?
+        }
+        Some(Command::Plot {
+            chart: PlotCommand::Pie { at },
+        }) => plot_pie(load_truth()?, at, &settings.graph)?,
+        Some(Command::Plot {
+            chart: PlotCommand::Area,
+        }) => plot_area(load_truth()?, &settings.graph)?,
+        Some(Command::Plot {
+            chart: PlotCommand::Radar,
+        }) => {
+            inputs.push(settings.forecast_file.clone());
+            plot_radar(load_truth()?, &settings)?
+        }
+        Some(Command::Fetch {
+            from,
+            to,
+            dir,
+            download,
+            iso,
+            realtime,
+            prices,
+            store,
+        }) => {
+            let options: FetchOptions = download.options();
+            if iso != Market::Nyiso {
+                fetch_market(iso, from, to, &dir, &options)?;
+                return Ok(cataloged(&dir));
+            }
+            let truth_files: Vec<String> = fetch(from, to, &dir, realtime, prices, &options)?;
+            if let Some(store) = store {
+                store_actuals(&truth_files, &store, &settings.resources)?;
+            }
+            return Ok(cataloged(&dir));
+        }
+        Some(Command::Backfill {
+            from,
+            to,
+            dir,
+            store,
+            download,
+        }) => {
+            let backfilled: Result<()> = backfill(from, to, &dir, &store, &download.options());
+            let report: PipelineReport = cataloged(&dir);
+            backfilled?;
+            return Ok(report);
+        }
+        Some(Command::Diff {
+            first,
+            second,
+            region,
+            min_delta,
+            chart,
+        }) => {
+            let settings: PipelineConfig = PipelineConfig {
+                zone: region.unwrap_or(settings.zone),
+                ..settings
+            };
+            let report: PipelineReport = diff(&first, &second, min_delta, chart, &settings)?;
+            if !chart {
+                return Ok(report);
+            }
+            return publish(&settings, &[first, second], report);
+        }
+        Some(Command::Score {
+            iso,
+            zone,
+            actuals,
+            forecast,
+            chart,
+        }) => {
+            return score_market(
+                iso,
+                &zone,
+                &actuals,
+                &forecast,
+                chart,
+                &settings.graph,
+                &settings.resources,
+            );
+        }
+        Some(Command::Crosscheck {
+            from,
+            to,
+            dir,
+            threshold,
+            chart,
+        }) => {
+            let crosschecked: Result<PipelineReport> = crosscheck(
+                from,
+                to,
+                &dir,
+                threshold,
+                chart,
+                &settings.graph,
+                &settings.resources,
+            );
+            let catalog: PipelineReport = cataloged(&dir);
+            let mut report: PipelineReport = crosschecked?;
+            report.warnings.extend(catalog.warnings);
+            return Ok(report);
+        }
+        Some(Command::Backtest {
+            from,
+            to,
+            dir,
+            forecaster,
+            seasonality,
+            window,
+            train_days,
+            test_days,
+            lead,
+            output,
+        }) => {
+            let folds: Vec<Split> = splits(from, to, window, train_days, test_days)?;
+            let tested: Result<PipelineReport> = run_backtest(
+                &folds,
+                &dir,
+                forecaster,
+                seasonality,
+                lead,
+                output.as_deref(),
+                &settings,
+            );
+            let catalog: PipelineReport = cataloged(&dir);
+            let mut report: PipelineReport = tested?;
+            report.warnings.extend(catalog.warnings);
+            return Ok(report);
+        }
+        Some(Command::Compact {
+            dir,
+            older_than,
+            archive,
+            delete,
+        }) => {
+            let before: NaiveDate = Utc::now().date_naive() - Duration::days(i64::from(older_than));
+            let default_archive: String = std::path::Path::new(&dir)
+                .join(ARCHIVE_DIR)
+                .to_string_lossy()
+                .into_owned();
+            let archive: String = archive.unwrap_or_else(|| default_archive.clone());
+            if delete && archive != default_archive {
+                return Err(MugenError::InvalidArgument(format!(
+                    "--delete needs the archive in \"{}\", where the other commands read \
+                     deleted files from, not \"{}\"",
+                    default_archive, archive
+                )));
+            }
+            let compacted: Result<()> =
+                compact_months(&dir, before, &archive, delete, &settings.resources);
+            let report: PipelineReport = cataloged(&dir);
+            compacted?;
+            return Ok(report);
+        }
+        Some(Command::Catalog {
+            dir,
+            zone,
+            from,
+            to,
+            kind,
+            json,
+        }) => {
+            let query: CatalogQuery = CatalogQuery {
+                zone,
+                from,
+                to,
+                kind,
+            };
+            print_catalog(&dir, &query, json)?;
+            return Ok(PipelineReport::default());
+        }
+        Some(Command::Weather { from, to, dir }) => {
+            // The archive is a shared academic service, so go gently.
+            let options: FetchOptions = FetchOptions {
+                rate_limit: RateLimit {
+                    requests_per_second: 1.0,
+                    concurrency: 2,
+                },
+                ..Default::default()
+            };
+            for path in download_weather(WEATHER_BASE_URL, from, to, &dir, &options)? {
+                println!("{}", path);
+            }
+            return Ok(cataloged(&dir));
+        }
+        Some(Command::Live { address, interval }) => {
+            plot_live(&address, interval, &settings)?;
+            return Ok(PipelineReport::default());
+        }
+        Some(Command::Dashboard { interval, hours }) => {
+            show_dashboard(interval, hours, &settings)?;
+            return Ok(PipelineReport::default());
+        }
+        Some(Command::Export {
+            table:
+                ExportCommand::Joined {
+                    format,
+                    partitioned,
+                    output,
+                    from,
+                    to,
+                },
+        }) => {
+            let format: Option<ExportFormat> = (!partitioned).then_some(format);
+            for path in export_joined(format, output, from, to, &settings)? {
+                println!("{}", path);
+            }
+            return Ok(PipelineReport::default());
+        }
+        Some(Command::Export {
+            table:
+                ExportCommand::Features {
+                    format,
+                    output,
+                    from,
+                    to,
+                    lags,
+                },
+        }) => return export_features(format, output, from, to, &lags, &settings),
+        Some(Command::Daemon) => {
+            daemon(&settings)?;
+            return Ok(PipelineReport::default());
+        }
+        Some(Command::Serve { address, interval }) => {
+            serve(&address, interval, &settings)?;
+            return Ok(PipelineReport::default());
+        }
+        None if cli.terminal => return plot_terminal(&settings),
+        None => {
+            inputs.push(settings.forecast_file.clone());
+            inputs.extend(settings.price_files.iter().cloned());
+            plot_standard(load_truth()?, &settings)?
+        }
+    };
+
+    publish(&settings, &inputs, report)
+}
+
+/// Records the run in a manifest next to its charts, writes its HTML report, appends
+/// its metrics to the history database if one is set, copies the outputs to every
+/// sink, and posts and emails the summary.
+///
+/// A failed append, copy, post or email only adds a warning, since the charts are
+/// already written.
+fn publish(
+    settings: &PipelineConfig,
+    inputs: &[String],
+    mut report: PipelineReport,
+) -> Result<PipelineReport> {
+    let mut manifest: Manifest = Manifest::new(settings)?;
+    for path in inputs {
+        manifest.input(path)?;
+    }
+    for chart in &report.artifacts {
+        println!("{}", chart);
+        manifest.artifact(chart)?;
+    }
+    let title: String = format!("Load forecast run for {}", settings.zone.full_name());
+    let report_path: String = write_html(&title, &report, &manifest.dir().to_string_lossy())?;
+    println!("{}", report_path);
+
+    let mut outputs: Vec<String> = report
+        .artifacts
+        .iter()
+        .map(|chart| chart.path.clone())
+        .collect();
+    outputs.push(report_path);
+    let outputs: Vec<&str> = outputs.iter().map(String::as_str).collect();
+    let mut stored: Vec<&Sink> = Vec::new();
+    for sink in &settings.sinks {
+        match sink.store(&outputs) {
+            Ok(copies) => {
+                for (path, url) in copies {
+                    println!("{}", url);
+                    manifest.upload(&path, &url);
+                }
+                stored.push(sink);
+            }
+            Err(err) => report.warnings.push(format!(
+                "could not copy the charts to {}: {}",
+                sink.describe(),
+                err
+            )),
+        }
+    }
+    let manifest_path: String = manifest.write()?;
+    println!("{}", manifest_path);
+    if let Some(history) = &settings.history {
+        if let Err(err) = record_history(history, settings, &manifest, &manifest_path, &report) {
+            report.warnings.push(format!(
+                "could not record the metrics in \"{}\": {}",
+                history, err
+            ));
+        }
+    }
+    for sink in stored {
+        match sink.store(&[&manifest_path]) {
+            Ok(copies) => copies.iter().for_each(|(_, url)| println!("{}", url)),
+            Err(err) => report.warnings.push(format!(
+                "could not copy the manifest to {}: {}",
+                sink.describe(),
+                err
+            )),
+        }
+    }
+    if let Err(err) = settings.notify.send(&title, &report) {
+        report
+            .warnings
+            .push(format!("could not post the run summary: {}", err));
+    }
+    if let Err(err) = settings.email.send(&title, &report) {
+        report
+            .warnings
+            .push(format!("could not email the run report: {}", err));
+    }
+    Ok(report)
+}
+
+/// Appends the metrics of the run described by `manifest` to the history database
+/// at `path`, over the span of every dataset in `report`; runs without metrics are
+/// left out.
+#[cfg(feature = "sqlite")]
+fn record_history(
+    path: &str,
+    settings: &PipelineConfig,
+    manifest: &Manifest,
+    manifest_path: &str,
+    report: &PipelineReport,
+) -> Result<()> {
+    if report.metrics.is_empty() {
+        return Ok(());
+    }
+    let run = RunRecord {
+        started: manifest.created.clone(),
+        version: manifest.version.clone(),
+        command: manifest.command.join(" "),
+        zone: settings.zone,
+        data_start: report.datasets.iter().filter_map(|set| set.start).min(),
+        data_end: report.datasets.iter().filter_map(|set| set.end).max(),
+        manifest: Some(manifest_path.to_string()),
+        metrics: report.metrics.clone(),
+    };
+    MetricsHistory::open(path)?.append(&run)?;
+    Ok(())
+}
+
+/// Refuses to record the metrics history in builds without SQLite.
+#[cfg(not(feature = "sqlite"))]
+fn record_history(
+    _path: &str,
+    _settings: &PipelineConfig,
+    _manifest: &Manifest,
+    _manifest_path: &str,
+    _report: &PipelineReport,
+) -> Result<()> {
+    Err(MugenError::InvalidArgument(
+        "this build cannot record the metrics history; rebuild with the `sqlite` feature"
+            .to_string(),
+    ))
+}
+
+/// Scores the forecast in `joined` with every metric in [`mugen_core::metrics`], or
+/// warns in `report` if there is nothing to score.
+fn score(joined: &TimeSeriesDataset, report: &mut PipelineReport) -> Result<()> {
+    if joined.height() == 0 {
+        report
+            .warnings
+            .push("no hours with both actual load and a forecast; metrics skipped".to_string());
+        return Ok(());
+    }
+    report.metrics.push(("MAPE".to_string(), mape(joined)?));
+    report
+        .metrics
+        .push(("Peak Error".to_string(), peak_error(joined)?));
+    report.metrics.push(("Bias".to_string(), bias(joined)?));
+    Ok(())
+}
+
+/// Formats metrics by name as "MAPE 1.23, Peak Error -0.45".
+fn described(metrics: &[(String, f64)]) -> String {
+    metrics
+        .iter()
+        .map(|(name, value)| format!("{} {:.2}", name, value))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Labels a forecast file by its issue date, read from a leading "YYYYMMDD" in the
+/// file name, falling back to the file name itself.
+fn issue_label(path: &str) -> String {
+    let name: &str = std::path::Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(path);
+    match name
+        .get(..8)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+    {
+        Some(issued) => format!("Issued {}", issued.format("%m/%d/%Y")),
+        None => name.to_string(),
+    }
+}
+
+/// Returns the configured zone's actual load joined against the forecast, in the
+/// display timezone.
+///
+/// When every file is already in the display timezone, only the zone's rows are
+/// read, in one lazy pass over the files; otherwise both are processed and
+/// converted before joining.
+fn joined(settings: &PipelineConfig) -> Result<TimeSeriesDataset> {
+    let truth_files: Vec<&str> = settings.truth_files.iter().map(String::as_str).collect();
+    let timezones: &Timezones = &settings.timezones;
+    if timezones.is_uniform() {
+        return load_joined_with(
+            &truth_files,
+            &settings.forecast_file,
+            settings.zone,
+            &settings.resources,
+        );
+    }
+    let truth: TimeSeriesDataset = timezones.truth(process_truth(
+        read_multiple_csvs_with(truth_files, &settings.resources)?,
+        settings.zone,
+    )?)?;
+    let forecast: TimeSeriesDataset = timezones.forecast(process_pred(
+        read_csv_to_df(&settings.forecast_file)?,
+        settings.zone,
+    )?)?;
+    join_actual_forecast(&truth, &forecast)
+}
+
+/// Lists the files under `data/` whose names end with `suffix`, sorted by name.
+fn data_files(suffix: &str) -> Result<Vec<String>> {
+    let mut paths: Vec<String> = Vec::new();
+    for entry in std::fs::read_dir("data")? {
+        let path = entry?.path().to_string_lossy().to_string();
+        if path.ends_with(suffix) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
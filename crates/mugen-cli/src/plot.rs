@@ -0,0 +1,915 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use mugen_core::alert::check_all;
+use mugen_core::backtest::{backtest, Backtest, Forecaster};
+use mugen_core::baseline::{persistence, HoltWinters, Seasonality};
+use mugen_core::data::{
+    convert_timezone, filter_period, join_actual_forecast, process_pred, process_price,
+    process_truth, process_truth_regions, read_csv_to_df, read_multiple_csvs_with, resample,
+    zone_metrics, zone_shares_at, zones_in,
+};
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::ensemble::{ensemble, Blend, Ensemble};
+#[cfg(not(feature = "onnx"))]
+use mugen_core::error::MugenError;
+use mugen_core::error::Result;
+use mugen_core::fetch::Report;
+use mugen_core::interval::ErrorQuantiles;
+use mugen_core::metrics::{bias, coverage, interval_width, mape, peak_error};
+#[cfg(feature = "onnx")]
+use mugen_core::onnx::OnnxModel;
+use mugen_core::scenario::{scenario, Adjustment, Target};
+use mugen_core::source::{CsvSource, DataSource};
+use mugen_core::split::{splits, Split, Window};
+use mugen_core::zone::Zone;
+use mugen_viz::config::{PipelineConfig, Timezones};
+use mugen_viz::graph::{
+    draw_all, fill_title, GaugeGraph, Graph, GraphConfig, HeatmapGraph, Labels, LineGraph,
+    OverlayGraph, PieGraph, RadarGraph, ScatterGraph, StackedAreaGraph, Vintage, VintageGraph,
+};
+use mugen_viz::pipeline::{DatasetSummary, Evaluation, Pipeline, PipelineReport};
+use mugen_viz::terminal::braille_chart;
+use polars::prelude::*;
+
+use crate::backtest::forecast_split;
+#[cfg(feature = "onnx")]
+use crate::export::feature_table;
+use crate::{data_files, described, issue_label, joined, score};
+
+/// Renders the evaluation chart set for `from..=to` into `charts/<from>_<to>/`.
+///
+/// The set is the configured zone's line chart with its residual panel, an
+/// actual-vs-forecast scatter and an hour-by-day load heatmap, plus a line chart per
+/// zone in its own subfolder. Filenames are fixed, since the folder already
+/// identifies the run.
+pub(crate) fn plot_all(
+    raw_truth: DataFrame,
+    from: NaiveDate,
+    to: NaiveDate,
+    settings: &PipelineConfig,
+) -> Result<PipelineReport> {
+    let base: &GraphConfig = &settings.graph;
+    let zone: Zone = settings.zone;
+    let output_dir: String = format!(
+        "charts/{}_{}",
+        from.format("%Y-%m-%d"),
+        to.format("%Y-%m-%d")
+    );
+    let raw_pred: DataFrame = read_csv_to_df(&settings.forecast_file)?;
+    let config: GraphConfig = GraphConfig {
+        deterministic: true,
+        output_dir: output_dir.clone(),
+        ..base.clone()
+    };
+
+    let timezones: &Timezones = &settings.timezones;
+    let ground_truth: TimeSeriesDataset = filter_period(
+        timezones.truth(process_truth(raw_truth.clone(), zone)?)?,
+        from,
+        to,
+    )?;
+    let predictions: TimeSeriesDataset = filter_period(
+        timezones.forecast(process_pred(raw_pred.clone(), zone)?)?,
+        from,
+        to,
+    )?;
+    let joined: TimeSeriesDataset = join_actual_forecast(&ground_truth, &predictions)?;
+    let mut report: PipelineReport = PipelineReport {
+        datasets: vec![
+            DatasetSummary::of(&ground_truth)?,
+            DatasetSummary::of(&predictions)?,
+            DatasetSummary::of(&joined)?,
+        ],
+        ..Default::default()
+    };
+    score(&joined, &mut report)?;
+    report.alerts = check_all(&settings.notify.rules, &ground_truth, &joined)?;
+
+    let line_graph: LineGraph = LineGraph::builder()
+        .config(base.clone())
+        .truth(ground_truth.clone())
+        .forecast(predictions.clone())
+        .zone(zone)
+        .error_band(true)
+        .residual_panel(true)
+        .deterministic(true)
+        .output_dir(output_dir.as_str())
+        .build()?;
+
+    let labels: &Labels = &base.labels;
+    let region = |template: &str| fill_title(template, &[("region", zone.to_string())]);
+    let unit = |template: &str| fill_title(template, &[("unit", joined.unit().to_string())]);
+    let scatter_graph: ScatterGraph = ScatterGraph {
+        config: GraphConfig {
+            title: region(&labels.titles.scatter),
+            x_label: unit(&labels.forecast_axis),
+            y_label: unit(&labels.actual_axis),
+            ..config.clone()
+        },
+        data: joined,
+    };
+
+    let heatmap_graph: HeatmapGraph = HeatmapGraph {
+        config: GraphConfig {
+            title: region(&labels.titles.heatmap),
+            x_label: labels.day.clone(),
+            y_label: labels.hour.clone(),
+            ..config.clone()
+        },
+        data: ground_truth,
+    };
+
+    let mut graphs: Vec<Box<dyn Graph>> = vec![
+        Box::new(line_graph),
+        Box::new(scatter_graph),
+        Box::new(heatmap_graph),
+    ];
+    for zone in zones_in(&raw_truth)? {
+        if raw_pred.column(zone.forecast_column()).is_err() {
+            report
+                .warnings
+                .push(format!("no forecast for {}; skipped its chart", zone));
+            continue;
+        }
+        let folder: String = zone.slug();
+        let zone_graph: LineGraph = LineGraph::builder()
+            .config(base.clone())
+            .truth(filter_period(
+                timezones.truth(process_truth(raw_truth.clone(), zone)?)?,
+                from,
+                to,
+            )?)
+            .forecast(filter_period(
+                timezones.forecast(process_pred(raw_pred.clone(), zone)?)?,
+                from,
+                to,
+            )?)
+            .zone(zone)
+            .residual_panel(true)
+            .deterministic(true)
+            .output_dir(format!("{}/zones/{}", output_dir, folder))
+            .build()?;
+        graphs.push(Box::new(zone_graph));
+    }
+
+    report.artifacts = draw_all(&graphs)?;
+    Ok(report)
+}
+
+/// Returns the forecast files to compare as vintages: `forecasts`, or every
+/// `*isolf.csv` under `data/` if it is empty, ordered by name, which for ISO-dated
+/// names is also issue order.
+pub(crate) fn vintage_files(forecasts: Vec<String>) -> Result<Vec<String>> {
+    let mut forecasts: Vec<String> = forecasts;
+    if forecasts.is_empty() {
+        forecasts = data_files("isolf.csv")?;
+    }
+    forecasts.sort();
+    Ok(forecasts)
+}
+
+/// Renders every forecast issue in `forecasts`, as returned by [`vintage_files`],
+/// for the configured zone covering `day` against the actual load.
+pub(crate) fn plot_vintages(
+    raw_truth: DataFrame,
+    day: NaiveDate,
+    forecasts: Vec<String>,
+    settings: &PipelineConfig,
+) -> Result<PipelineReport> {
+    let mut report: PipelineReport = PipelineReport::default();
+    let mut vintages: Vec<Vintage> = Vec::new();
+    let timezones: &Timezones = &settings.timezones;
+    for path in forecasts {
+        let forecast: TimeSeriesDataset = process_pred(read_csv_to_df(&path)?, settings.zone)?;
+        let forecast: TimeSeriesDataset = filter_period(timezones.forecast(forecast)?, day, day)?;
+        if forecast.height() == 0 {
+            report.warnings.push(format!(
+                "{} does not cover {}; skipped it",
+                path,
+                day.format("%Y-%m-%d")
+            ));
+            continue;
+        }
+        report.datasets.push(DatasetSummary::of(&forecast)?);
+        vintages.push(Vintage {
+            issued: issue_label(&path),
+            forecast,
+        });
+    }
+
+    let vintage_graph: VintageGraph = VintageGraph {
+        config: GraphConfig {
+            title: settings.graph.labels.titles.vintages.clone(),
+            time_format: "%H:%M".to_string(),
+            ..settings.graph.clone()
+        },
+        data: filter_period(
+            timezones.truth(process_truth(raw_truth, settings.zone)?)?,
+            day,
+            day,
+        )?,
+        vintages,
+        zone: settings.zone,
+    };
+    report
+        .datasets
+        .insert(0, DatasetSummary::of(&vintage_graph.data)?);
+    report.artifacts.push(vintage_graph.draw()?);
+    Ok(report)
+}
+
+/// Blends the configured zone's forecasts in `forecasts`, as returned by
+/// [`vintage_files`], with `blend` over a skill window of `window` hours, prints
+/// the metrics of every member and of the blend, and renders the blend and its
+/// members against the actual load over the hours the blend covers.
+pub(crate) fn plot_ensemble(
+    raw_truth: DataFrame,
+    forecasts: Vec<String>,
+    blend: Blend,
+    window: usize,
+    settings: &PipelineConfig,
+) -> Result<PipelineReport> {
+    let timezones: &Timezones = &settings.timezones;
+    let truth: TimeSeriesDataset = timezones.truth(process_truth(raw_truth, settings.zone)?)?;
+    let members: Vec<TimeSeriesDataset> = forecasts
+        .iter()
+        .map(|path| timezones.forecast(process_pred(read_csv_to_df(path)?, settings.zone)?))
+        .collect::<Result<_>>()?;
+    let blended: Ensemble = ensemble(&members, &truth, blend, window)?;
+    let joined: TimeSeriesDataset = join_actual_forecast(&truth, &blended.forecast)?;
+    let mut report: PipelineReport = PipelineReport {
+        datasets: vec![
+            DatasetSummary::of(&truth)?,
+            DatasetSummary::of(&blended.forecast)?,
+            DatasetSummary::of(&joined)?,
+        ],
+        ..Default::default()
+    };
+    score(&joined, &mut report)?;
+    let label: String = format!("Ensemble ({})", blend);
+    let summary: String = format!("{}: {}", label, described(&report.metrics));
+
+    let mut vintages: Vec<Vintage> = Vec::new();
+    for ((path, member), weight) in forecasts.iter().zip(members).zip(&blended.weights) {
+        let mut scores: PipelineReport = PipelineReport::default();
+        score(&join_actual_forecast(&truth, &member)?, &mut scores)?;
+        let issued: String = issue_label(path);
+        println!(
+            "{} (weight {:.2}): {}",
+            issued,
+            weight,
+            described(&scores.metrics)
+        );
+        report.metrics.extend(
+            scores
+                .metrics
+                .into_iter()
+                .map(|(name, value)| (format!("{} ({})", name, issued), value)),
+        );
+        vintages.push(Vintage {
+            issued,
+            forecast: member,
+        });
+    }
+    println!("{}", summary);
+
+    let span: DatasetSummary = DatasetSummary::of(&joined)?;
+    let (Some(start), Some(end)) = (span.start, span.end) else {
+        report
+            .warnings
+            .push("the blend covers no hours with actual load; chart skipped".to_string());
+        return Ok(report);
+    };
+    let (from, to) = (start.date(), end.date());
+    let period = |dataset: TimeSeriesDataset| filter_period(dataset, from, to);
+    let vintages: Vec<Vintage> = vintages
+        .into_iter()
+        .chain([Vintage {
+            issued: label,
+            forecast: blended.forecast,
+        }])
+        .map(|vintage| {
+            Ok(Vintage {
+                forecast: period(vintage.forecast)?,
+                ..vintage
+            })
+        })
+        .collect::<Result<_>>()?;
+    let ensemble_graph: VintageGraph = VintageGraph {
+        config: GraphConfig {
+            title: settings.graph.labels.titles.ensemble.clone(),
+            time_format: "%m/%d %H:%M".to_string(),
+            ..settings.graph.clone()
+        },
+        data: period(truth)?,
+        vintages,
+        zone: settings.zone,
+    };
+    report.artifacts.push(ensemble_graph.draw()?);
+    Ok(report)
+}
+
+/// Forecasts the configured zone's load for the `days` days from `day` with a
+/// Holt-Winters model fitted to the actual load before `day` and by persistence,
+/// prints the metrics of both and renders both against the actual load.
+pub(crate) fn plot_baseline(
+    raw_truth: DataFrame,
+    day: NaiveDate,
+    days: usize,
+    seasonality: Seasonality,
+    settings: &PipelineConfig,
+) -> Result<PipelineReport> {
+    let truth: TimeSeriesDataset = settings
+        .timezones
+        .truth(process_truth(raw_truth, settings.zone)?)?;
+    let history: TimeSeriesDataset =
+        filter_period(truth.clone(), NaiveDate::MIN, day - Duration::days(1))?;
+    let model: HoltWinters = HoltWinters::fit(&history, seasonality)?;
+    let hours: usize = days * 24;
+    let forecast: TimeSeriesDataset = model.forecast(&history, hours)?;
+    let naive: TimeSeriesDataset = persistence(&history, hours, seasonality)?;
+    let last: NaiveDate = day + Duration::days(days as i64 - 1);
+    let actual: TimeSeriesDataset = filter_period(truth, day, last)?;
+    let joined: TimeSeriesDataset = join_actual_forecast(&actual, &forecast)?;
+    let mut report: PipelineReport = PipelineReport {
+        datasets: vec![
+            DatasetSummary::of(&history)?,
+            DatasetSummary::of(&forecast)?,
+            DatasetSummary::of(&joined)?,
+        ],
+        ..Default::default()
+    };
+    score(&joined, &mut report)?;
+    println!(
+        "Holt-Winters (alpha {:.2}, beta {:.2}, gamma {:.2}, delta {:.2}): {}",
+        model.alpha,
+        model.beta,
+        model.gamma,
+        model.delta,
+        described(&report.metrics)
+    );
+    let mut scores: PipelineReport = PipelineReport::default();
+    score(&join_actual_forecast(&actual, &naive)?, &mut scores)?;
+    println!(
+        "Persistence ({}): {}",
+        seasonality,
+        described(&scores.metrics)
+    );
+    report.metrics.extend(
+        scores
+            .metrics
+            .into_iter()
+            .map(|(name, value)| (format!("{} (persistence)", name), value)),
+    );
+    report.warnings.extend(scores.warnings);
+
+    let baseline_graph: VintageGraph = VintageGraph {
+        config: GraphConfig {
+            title: settings.graph.labels.titles.baseline.clone(),
+            time_format: "%m/%d %H:%M".to_string(),
+            ..settings.graph.clone()
+        },
+        data: actual,
+        vintages: vec![
+            Vintage {
+                issued: format!("Holt-Winters ({})", seasonality),
+                forecast,
+            },
+            Vintage {
+                issued: format!("Persistence ({})", seasonality),
+                forecast: naive,
+            },
+        ],
+        zone: settings.zone,
+    };
+    report.artifacts.push(baseline_graph.draw()?);
+    Ok(report)
+}
+
+/// Forecasts `days` days from `day` for the configured zone with `forecaster`, puts
+/// the prediction interval holding a `level` share of the actual load, learned from
+/// its errors over the `calibration` days before, around it, and charts both against
+/// the actual load.
+///
+/// The forecasts scored for the errors are made the same way and as far ahead as
+/// the one the interval goes around; stored forecasts are read from `data/`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn plot_interval(
+    raw_truth: DataFrame,
+    day: NaiveDate,
+    days: u32,
+    forecaster: Forecaster,
+    seasonality: Seasonality,
+    lead: u32,
+    calibration: u32,
+    level: f64,
+    settings: &PipelineConfig,
+) -> Result<PipelineReport> {
+    let truth: TimeSeriesDataset = settings
+        .timezones
+        .truth(process_truth(raw_truth, settings.zone)?)?;
+    let forecast = |split: &Split, history: &TimeSeriesDataset| {
+        forecast_split(
+            forecaster,
+            seasonality,
+            lead,
+            "data",
+            split,
+            history,
+            settings,
+        )
+    };
+    let (start, end): (NaiveDate, NaiveDate) = (
+        day - Duration::days(i64::from(calibration)),
+        day - Duration::days(1),
+    );
+    let past: Vec<Split> = splits(start, end, Window::Expanding, calibration, days)?;
+    let scored: Backtest = backtest(&truth, &past, forecast)?;
+    let joined: Vec<TimeSeriesDataset> = scored
+        .folds
+        .iter()
+        .map(|fold| fold.joined.clone())
+        .collect();
+    let errors: ErrorQuantiles = ErrorQuantiles::fit(&joined, level)?;
+
+    let target: Split = Split {
+        train_from: past[0].train_from,
+        train_to: day - Duration::days(1),
+        test_from: day,
+        test_to: day + Duration::days(i64::from(days) - 1),
+    };
+    let point: TimeSeriesDataset = target.test(&forecast(&target, &target.train(&truth)?)?)?;
+    let interval: TimeSeriesDataset = errors.interval(&point)?;
+    let actual: TimeSeriesDataset = target.test(&truth)?;
+    let joined: TimeSeriesDataset = join_actual_forecast(&actual, &point)?;
+    let mut report: PipelineReport = PipelineReport {
+        datasets: vec![
+            DatasetSummary::of(&actual)?,
+            DatasetSummary::of(&interval)?,
+            DatasetSummary::of(&joined)?,
+        ],
+        warnings: scored
+            .skipped
+            .iter()
+            .map(|(day, reason)| {
+                format!("calibration skipped {}: {}", day.format("%Y-%m-%d"), reason)
+            })
+            .collect(),
+        ..Default::default()
+    };
+    score(&joined, &mut report)?;
+    if joined.height() > 0 {
+        report
+            .metrics
+            .push(("Coverage".to_string(), coverage(&actual, &interval)?));
+    }
+    report
+        .metrics
+        .push(("Interval Width".to_string(), interval_width(&interval)?));
+    println!(
+        "{} with a {:.0}% interval learned over {} of {} days: {}",
+        forecaster,
+        level * 100.0,
+        scored
+            .folds
+            .iter()
+            .map(|fold| fold.split.test_days())
+            .sum::<i64>(),
+        calibration,
+        described(&report.metrics)
+    );
+
+    let line_graph: LineGraph = LineGraph::builder()
+        .config(settings.graph.clone())
+        .truth(actual)
+        .forecast(point)
+        .interval(interval)
+        .zone(settings.zone)
+        .title(fill_title(
+            &settings.graph.labels.titles.interval,
+            &[("level", format!("{:.0}", level * 100.0))],
+        ))
+        .build()?;
+    report.artifacts.push(line_graph.draw()?);
+    Ok(report)
+}
+
+/// Charts the configured zone's actual load and forecast over `from..=to` with
+/// `adjustments` made to the `target` series, and scores the forecast against the
+/// actual load both before and after.
+///
+/// The metrics of the scenario are named with " (scenario)", next to "Peak Change"
+/// and "Energy Change", how much the adjusted series' peak and total moved, in
+/// percent.
+pub(crate) fn plot_scenario(
+    raw_truth: DataFrame,
+    from: NaiveDate,
+    to: NaiveDate,
+    adjustments: &[Adjustment],
+    target: Target,
+    settings: &PipelineConfig,
+) -> Result<PipelineReport> {
+    let timezones: &Timezones = &settings.timezones;
+    let truth: TimeSeriesDataset = filter_period(
+        timezones.truth(process_truth(raw_truth, settings.zone)?)?,
+        from,
+        to,
+    )?;
+    let pred: TimeSeriesDataset = filter_period(
+        timezones.forecast(process_pred(
+            read_csv_to_df(&settings.forecast_file)?,
+            settings.zone,
+        )?)?,
+        from,
+        to,
+    )?;
+    let original: &TimeSeriesDataset = match target {
+        Target::Forecast => &pred,
+        Target::Actual => &truth,
+    };
+    let adjusted: TimeSeriesDataset = filter_period(scenario(original, adjustments)?, from, to)?;
+    let joined: TimeSeriesDataset = join_actual_forecast(&truth, &pred)?;
+    let what_if: TimeSeriesDataset = match target {
+        Target::Forecast => join_actual_forecast(&truth, &adjusted)?,
+        Target::Actual => join_actual_forecast(&adjusted, &pred)?,
+    };
+    let mut report: PipelineReport = PipelineReport {
+        datasets: vec![
+            DatasetSummary::of(&truth)?,
+            DatasetSummary::of(&pred)?,
+            DatasetSummary::of(&adjusted)?,
+        ],
+        ..Default::default()
+    };
+    score(&joined, &mut report)?;
+    let mut scores: PipelineReport = PipelineReport::default();
+    score(&what_if, &mut scores)?;
+    let label: String = adjustments
+        .iter()
+        .map(Adjustment::to_string)
+        .collect::<Vec<String>>()
+        .join(", ");
+    println!("Original: {}", described(&report.metrics));
+    println!(
+        "Scenario ({} {}): {}",
+        target,
+        label,
+        described(&scores.metrics)
+    );
+    report.metrics.extend(
+        scores
+            .metrics
+            .into_iter()
+            .map(|(name, value)| (format!("{} (scenario)", name), value)),
+    );
+    report.warnings.extend(scores.warnings);
+    let change = |statistic: fn(&Float64Chunked) -> Option<f64>| -> Result<f64> {
+        let before: f64 = statistic(&original.values(original.value_column())?).unwrap_or(f64::NAN);
+        let after: f64 = statistic(&adjusted.values(adjusted.value_column())?).unwrap_or(f64::NAN);
+        Ok((after - before) / before * 100.0)
+    };
+    let changes: Vec<(String, f64)> = vec![
+        ("Peak Change".to_string(), change(|values| values.max())?),
+        ("Energy Change".to_string(), change(|values| values.sum())?),
+    ];
+    println!("Scenario {}: {}", target, described(&changes));
+    report.metrics.extend(changes);
+
+    let scenario_graph: VintageGraph = VintageGraph {
+        config: GraphConfig {
+            title: settings.graph.labels.titles.scenario.clone(),
+            time_format: "%m/%d %H:%M".to_string(),
+            ..settings.graph.clone()
+        },
+        data: truth,
+        vintages: vec![
+            Vintage {
+                issued: "Forecast".to_string(),
+                forecast: pred,
+            },
+            Vintage {
+                issued: format!("Scenario {} ({})", target, label),
+                forecast: adjusted,
+            },
+        ],
+        zone: settings.zone,
+    };
+    report.artifacts.push(scenario_graph.draw()?);
+    Ok(report)
+}
+
+/// Lists the files under `data/` that [`plot_day_ahead`] reads for `from..=to`: the
+/// actual load of each day and the forecasts issued on it and the day before.
+pub(crate) fn day_ahead_files(from: NaiveDate, to: NaiveDate) -> Vec<String> {
+    let days = |from: NaiveDate| from.iter_days().take_while(move |day| *day <= to);
+    let truth = days(from).map(|day| Report::ActualLoad.file_name(day));
+    let forecasts = days(from - Duration::days(1)).map(|day| Report::LoadForecast.file_name(day));
+    truth
+        .chain(forecasts)
+        .map(|name| format!("data/{}", name))
+        .collect()
+}
+
+/// Renders, for the configured zone over `from..=to`, the forecast issued the day
+/// before each day and the update issued on the day against the actual load, and
+/// prints how much the update improved each metric.
+pub(crate) fn plot_day_ahead(
+    from: NaiveDate,
+    to: NaiveDate,
+    settings: &PipelineConfig,
+) -> Result<PipelineReport> {
+    let config: GraphConfig = GraphConfig {
+        title: settings.graph.labels.titles.day_ahead.clone(),
+        time_format: "%m/%d %H:%M".to_string(),
+        ..settings.graph.clone()
+    };
+    let timezones: &Timezones = &settings.timezones;
+    let mut pipeline: Pipeline = Pipeline::new()
+        .source(
+            CsvSource {
+                dir: "data".to_string(),
+                report: Report::ActualLoad,
+            }
+            .with_timezone(&timezones.truth),
+        )
+        .source(
+            CsvSource {
+                dir: "data".to_string(),
+                report: Report::LoadForecast,
+            }
+            .with_timezone(&timezones.forecast),
+        )
+        .display_timezone(timezones.shown())
+        .period(from, to)
+        .region(settings.zone)
+        .metric("MAPE", mape)
+        .metric("Peak Error", peak_error)
+        .metric("Bias", bias)
+        .compare_day_ahead(true)
+        .chart(move |evaluation| {
+            let vintages: Vec<Vintage> = evaluation
+                .day_ahead
+                .as_ref()
+                .map(|comparison| comparison.vintages())
+                .unwrap_or_default();
+            Ok(Box::new(VintageGraph {
+                config: config.clone(),
+                data: evaluation.truth.clone(),
+                vintages,
+                zone: evaluation.zone,
+            }) as Box<dyn Graph>)
+        });
+    for rule in &settings.notify.rules {
+        pipeline = pipeline.rule(rule.clone());
+    }
+    let evaluation: Evaluation = pipeline.run()?;
+
+    if let Some(comparison) = &evaluation.day_ahead {
+        for ((name, day_ahead, same_day), (_, improvement)) in
+            comparison.metrics.iter().zip(comparison.improvement())
+        {
+            println!(
+                "{}: day-ahead {:.2}, same-day {:.2}, improved by {:.2}",
+                name, day_ahead, same_day, improvement
+            );
+        }
+    }
+    evaluation.report()
+}
+
+/// Renders every zone's load as a stacked area over the whole period.
+pub(crate) fn plot_area(raw_truth: DataFrame, base: &GraphConfig) -> Result<PipelineReport> {
+    let zones: Vec<Zone> = zones_in(&raw_truth)?;
+
+    let area_graph: StackedAreaGraph = StackedAreaGraph {
+        config: GraphConfig {
+            title: base.labels.titles.zonal_load.clone(),
+            ..base.clone()
+        },
+        data: process_truth_regions(raw_truth, &zones)?,
+        ..Default::default()
+    };
+    Ok(PipelineReport {
+        artifacts: vec![area_graph.draw()?],
+        ..Default::default()
+    })
+}
+
+/// Renders each zone's forecast error metrics on a radar.
+pub(crate) fn plot_radar(
+    raw_truth: DataFrame,
+    settings: &PipelineConfig,
+) -> Result<PipelineReport> {
+    let radar_graph: RadarGraph = RadarGraph {
+        config: GraphConfig {
+            title: settings.graph.labels.titles.zone_errors.clone(),
+            ..settings.graph.clone()
+        },
+        data: zone_metrics(raw_truth, read_csv_to_df(&settings.forecast_file)?)?,
+    };
+    Ok(PipelineReport {
+        artifacts: vec![radar_graph.draw()?],
+        ..Default::default()
+    })
+}
+
+/// Renders each zone's share of the total load at `at`.
+pub(crate) fn plot_pie(
+    raw_truth: DataFrame,
+    at: NaiveDateTime,
+    base: &GraphConfig,
+) -> Result<PipelineReport> {
+    let pie_graph: PieGraph = PieGraph {
+        config: GraphConfig {
+            title: fill_title(
+                &base.labels.titles.zone_shares,
+                &[("at", at.format(&base.time_format).to_string())],
+            ),
+            ..base.clone()
+        },
+        data: zone_shares_at(raw_truth, at)?,
+        ..Default::default()
+    };
+    Ok(PipelineReport {
+        artifacts: vec![pie_graph.draw()?],
+        ..Default::default()
+    })
+}
+
+/// Prints the configured zone's actual vs forecast load as a braille chart in the
+/// terminal.
+pub(crate) fn plot_terminal(settings: &PipelineConfig) -> Result<PipelineReport> {
+    let joined: TimeSeriesDataset = joined(settings)?;
+    println!("{} Actual vs Forecast (MW)", settings.zone);
+    print!("{}", braille_chart(&joined, 100, 20)?);
+    let mut report: PipelineReport = PipelineReport {
+        datasets: vec![DatasetSummary::of(&joined)?],
+        ..Default::default()
+    };
+    score(&joined, &mut report)?;
+    Ok(report)
+}
+
+/// Charts and scores the forecast of the ONNX model at `model` for the configured
+/// zone, run on the features of its actual load, see [`feature_table`], over the
+/// days from `from` to `to` if given.
+#[cfg(feature = "onnx")]
+pub(crate) fn plot_onnx(
+    raw_truth: DataFrame,
+    model: &str,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    lags: &[usize],
+    settings: &PipelineConfig,
+) -> Result<PipelineReport> {
+    let truth: TimeSeriesDataset = settings
+        .timezones
+        .truth(process_truth(raw_truth, settings.zone)?)?;
+    let mut report: PipelineReport = PipelineReport::default();
+    let table: TimeSeriesDataset = feature_table(&truth, from, to, lags, settings, &mut report)?;
+    let forecast: TimeSeriesDataset = OnnxModel::load(model)?.forecast(&table)?;
+    let joined: TimeSeriesDataset = join_actual_forecast(&truth, &forecast)?;
+    report.datasets = vec![
+        DatasetSummary::of(&table)?,
+        DatasetSummary::of(&forecast)?,
+        DatasetSummary::of(&joined)?,
+    ];
+    score(&joined, &mut report)?;
+    let name: String = std::path::Path::new(model).file_stem().map_or_else(
+        || model.to_string(),
+        |stem| stem.to_string_lossy().into_owned(),
+    );
+    println!("{}: {}", name, described(&report.metrics));
+
+    let model_graph: VintageGraph = VintageGraph {
+        config: GraphConfig {
+            title: settings.graph.labels.titles.model.clone(),
+            time_format: "%m/%d %H:%M".to_string(),
+            ..settings.graph.clone()
+        },
+        data: joined,
+        vintages: vec![Vintage {
+            issued: name,
+            forecast,
+        }],
+        zone: settings.zone,
+    };
+    report.artifacts.push(model_graph.draw()?);
+    Ok(report)
+}
+
+/// Refuses to run ONNX models in builds without ONNX Runtime.
+#[cfg(not(feature = "onnx"))]
+pub(crate) fn plot_onnx(
+    _raw_truth: DataFrame,
+    _model: &str,
+    _from: Option<NaiveDate>,
+    _to: Option<NaiveDate>,
+    _lags: &[usize],
+    _settings: &PipelineConfig,
+) -> Result<PipelineReport> {
+    Err(MugenError::InvalidArgument(
+        "this build cannot run ONNX models; rebuild with the `onnx` feature".to_string(),
+    ))
+}
+
+/// Renders the standard gauge and line charts for the configured zone, with the
+/// zonal price averaged per hour on the line chart if price files are set, and the
+/// downstate overlay.
+pub(crate) fn plot_standard(
+    raw_truth: DataFrame,
+    settings: &PipelineConfig,
+) -> Result<PipelineReport> {
+    let base: &GraphConfig = &settings.graph;
+    let timezones: &Timezones = &settings.timezones;
+    let ground_truth: TimeSeriesDataset =
+        timezones.truth(process_truth(raw_truth.clone(), settings.zone)?)?;
+
+    let predictions: TimeSeriesDataset = timezones.forecast(process_pred(
+        read_csv_to_df(&settings.forecast_file)?,
+        settings.zone,
+    )?)?;
+    let joined: TimeSeriesDataset = join_actual_forecast(&ground_truth, &predictions)?;
+    let mut report: PipelineReport = PipelineReport {
+        datasets: vec![
+            DatasetSummary::of(&ground_truth)?,
+            DatasetSummary::of(&predictions)?,
+            DatasetSummary::of(&joined)?,
+        ],
+        ..Default::default()
+    };
+    score(&joined, &mut report)?;
+    report.alerts = check_all(&settings.notify.rules, &ground_truth, &joined)?;
+
+    let gauge_graph: GaugeGraph = GaugeGraph {
+        config: GraphConfig {
+            title: base.labels.titles.gauge.clone(),
+            ..base.clone()
+        },
+        data: ground_truth.clone(),
+        forecast: predictions.clone(),
+        zone: settings.zone,
+        capacity: settings.capacities.get(&settings.zone).copied(),
+    };
+
+    let downstate_zones = vec![
+        Zone::NewYorkCity,
+        Zone::LongIsland,
+        Zone::Dunwoodie,
+        Zone::Millwood,
+    ];
+    let overlay_graph: OverlayGraph = OverlayGraph {
+        config: GraphConfig {
+            title: base.labels.titles.overlay.clone(),
+            ..base.clone()
+        },
+        data: convert_timezone(
+            &process_truth_regions(raw_truth, &downstate_zones)?,
+            "ground truth",
+            "Time Stamp",
+            &timezones.truth,
+            timezones.shown(),
+        )?,
+        zones: downstate_zones,
+        colors: vec![
+            "#5470c6".to_string(),
+            "#91cc75".to_string(),
+            "#fac858".to_string(),
+            "#ee6666".to_string(),
+        ],
+        ..Default::default()
+    };
+
+    let mut line_graph: LineGraph = LineGraph::builder()
+        .config(base.clone())
+        .truth(ground_truth)
+        .forecast(predictions)
+        .zone(settings.zone)
+        .error_band(true)
+        .moving_average(24)
+        .day_markers(true)
+        .day_shading(true)
+        .max_points(2000)
+        .time_format("%m/%d %H:%M")
+        .label_every(6)
+        .step_forecast(true)
+        .residual_panel(true)
+        .build()?;
+    if !settings.price_files.is_empty() {
+        let raw_price: DataFrame = read_multiple_csvs_with(
+            settings.price_files.iter().map(String::as_str).collect(),
+            &settings.resources,
+        )?;
+        let price: TimeSeriesDataset = timezones.price(process_price(raw_price, settings.zone)?)?;
+        let price: TimeSeriesDataset = resample(&price, Duration::hours(1))?;
+        report.datasets.push(DatasetSummary::of(&price)?);
+        line_graph.price = Some(price);
+    }
+
+    let graphs: Vec<Box<dyn Graph>> = vec![
+        Box::new(gauge_graph),
+        Box::new(overlay_graph),
+        Box::new(line_graph),
+    ];
+    report.artifacts = draw_all(&graphs)?;
+    Ok(report)
+}
@@ -0,0 +1,71 @@
+use mugen_core::data::{
+    join_actual_forecast, process_load, read_csv_to_df, read_multiple_csvs_with,
+};
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::Result;
+use mugen_core::iso::{FileKind, Iso, IsoZone, Market};
+use mugen_core::resources::Resources;
+use mugen_viz::graph::{fill_title, Graph, GraphConfig, LineGraph};
+use mugen_viz::pipeline::{DatasetSummary, PipelineReport};
+use polars::prelude::*;
+
+use crate::score;
+
+/// Scores `zone`'s forecast in `forecast` against its actual load in `actuals`,
+/// both files of `market`, prints the metrics, and renders both if `chart` is set.
+pub(crate) fn score_market(
+    market: Market,
+    zone: &str,
+    actuals: &[String],
+    forecast: &str,
+    chart: bool,
+    base: &GraphConfig,
+    resources: &Resources,
+) -> Result<PipelineReport> {
+    let iso: &dyn Iso = market.iso();
+    let zone: IsoZone = iso.zone(zone)?;
+    let raw_truth: DataFrame =
+        read_multiple_csvs_with(actuals.iter().map(String::as_str).collect(), resources)?;
+    let truth: TimeSeriesDataset = process_load(iso, FileKind::Actual, raw_truth, &zone)?;
+    let pred: TimeSeriesDataset =
+        process_load(iso, FileKind::Forecast, read_csv_to_df(forecast)?, &zone)?;
+    let joined: TimeSeriesDataset = join_actual_forecast(&truth, &pred)?;
+    let mut report: PipelineReport = PipelineReport {
+        datasets: vec![
+            DatasetSummary::of(&truth)?,
+            DatasetSummary::of(&pred)?,
+            DatasetSummary::of(&joined)?,
+        ],
+        ..Default::default()
+    };
+    score(&joined, &mut report)?;
+    println!(
+        "{} {} ({}) over {} hours",
+        iso.name(),
+        zone,
+        zone.full_name,
+        joined.height()
+    );
+    for (name, value) in &report.metrics {
+        println!("{}: {:.2}%", name, value);
+    }
+    if chart {
+        let line_graph: LineGraph = LineGraph::builder()
+            .config(base.clone())
+            .truth(truth)
+            .forecast(pred)
+            .title(fill_title(
+                &base.labels.titles.market,
+                &[
+                    ("market", iso.name().to_string()),
+                    ("region", zone.full_name.to_string()),
+                ],
+            ))
+            .error_band(true)
+            .build()?;
+        let chart = line_graph.draw()?;
+        println!("{}", chart);
+        report.artifacts.push(chart);
+    }
+    Ok(report)
+}
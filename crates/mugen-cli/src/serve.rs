@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDate};
+use image::ImageFormat;
+use mugen_core::data::join_actual_forecast;
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::{MugenError, Result};
+use mugen_core::metrics::{bias, mape, peak_error};
+use mugen_core::zone::Zone;
+use mugen_viz::config::PipelineConfig;
+use mugen_viz::grafana;
+use mugen_viz::graph::{Graph, GraphConfig, LineGraph};
+use mugen_viz::prometheus::{self, exposition, forecast_gauges};
+use mugen_viz::server::{self, Channel, Request, Response, Routes};
+
+use crate::cli::parse_date;
+use crate::inputs::Inputs;
+
+/// Serves charts and metrics of any zone over HTTP, built on demand from the
+/// configured files through [`Inputs`] like the live chart, so dashboards need no
+/// access to the chart folder:
+///
+/// * `GET /chart/{zone}` - actual vs forecast, rendered with the configured backend.
+/// * `GET /metrics/{zone}.json` - MAPE, bias and peak error of the forecast as JSON.
+/// * `GET /grafana/series/{zone}.json` - the actual, forecast and error at each hour
+///   as JSON rows, for the Grafana Infinity datasource.
+/// * `GET /metrics` - the configured zone's forecast-quality gauges for Prometheus,
+///   as on the live chart.
+///
+/// These take optional `from` and `to` days, inclusive, to narrow the period, e.g.
+/// `/chart/nyc?from=2023-12-04&to=2023-12-10`, whose forecast is taken from the
+/// files covering it. Zones may be spelled any way
+/// [`Zone`] parses. `/grafana` is also the URL of a Grafana SimpleJSON datasource,
+/// answering its `/search` and `/query` requests with the same series.
+///
+/// A WebSocket opened on `/live` is sent the configured zone's actual and forecast
+/// points as they show up in the files, checked every `interval` seconds, so a
+/// browser chart can follow the operating day.
+pub(crate) fn serve(address: &str, interval: u64, settings: &PipelineConfig) -> Result<()> {
+    let inputs: Arc<Inputs> = Arc::new(Inputs::new(settings));
+    let config: GraphConfig = GraphConfig {
+        deterministic: true,
+        ..settings.graph.clone()
+    };
+    let channel: Channel = Channel::new();
+    let zone: Zone = settings.zone;
+    let mut pushed: PushedPoints = PushedPoints::default();
+    // The points already on disk are the baseline; only later ones are pushed.
+    if let Err(err) = pushed.fresh(&inputs, zone) {
+        eprintln!("Failed to read the data to push: {}", err);
+    }
+    let subscribers: Channel = channel.clone();
+    let pushed_inputs: Arc<Inputs> = Arc::clone(&inputs);
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_secs(interval.max(1)));
+        match pushed.fresh(&pushed_inputs, zone) {
+            Ok(Some(message)) => subscribers.broadcast(&message),
+            Ok(None) => {}
+            Err(err) => eprintln!("Failed to read the data to push: {}", err),
+        }
+    });
+    let grafana_ok = |_: &Request| Response::text(200, "OK");
+    let routes = Routes::new()
+        .get(
+            "/chart/{zone}",
+            answer(&inputs, move |inputs, request| {
+                served_chart(inputs, &config, request)
+            }),
+        )
+        .get("/grafana", grafana_ok)
+        .get("/grafana/", grafana_ok)
+        .post(
+            "/grafana/search",
+            answer(&inputs, |_, request| {
+                grafana::search(&request.body).map(|targets| Response::json(&targets))
+            }),
+        )
+        .post(
+            "/grafana/query",
+            answer(&inputs, |inputs, request| {
+                let joined = |zone: Zone| -> Result<TimeSeriesDataset> {
+                    let (truth, forecast, _) = inputs.series(zone, None)?;
+                    join_actual_forecast(&truth, &forecast)
+                };
+                grafana::query(&request.body, joined).map(|series| Response::json(&series))
+            }),
+        )
+        .get(
+            "/grafana/series/{file}",
+            answer(&inputs, |inputs, request| {
+                let Some(zone) = json_name(request) else {
+                    return Ok(Response::not_found());
+                };
+                let (_, truth, forecast) = served_series(inputs, zone, request)?;
+                let joined: TimeSeriesDataset = join_actual_forecast(&truth, &forecast)?;
+                grafana::rows(&joined).map(|rows| Response::json(&rows))
+            }),
+        )
+        .get(
+            "/metrics",
+            answer(&inputs, move |inputs, _| {
+                let (truth, forecast, paths) = inputs.series(zone, None)?;
+                let joined: TimeSeriesDataset = join_actual_forecast(&truth, &forecast)?;
+                let inputs: Vec<&str> = paths.iter().map(String::as_str).collect();
+                Ok(Response::ok(
+                    prometheus::CONTENT_TYPE,
+                    exposition(&forecast_gauges(zone, &joined, &inputs)?),
+                ))
+            }),
+        )
+        .get(
+            "/metrics/{file}",
+            answer(&inputs, |inputs, request| match json_name(request) {
+                Some(zone) => served_metrics(inputs, zone, request),
+                None => Ok(Response::not_found()),
+            }),
+        )
+        .channel("/live", &channel);
+    println!("Serving charts on http://{}", address);
+    server::serve(address, routes)
+}
+
+/// Wraps `handle` into a handler of the chart server that reads `inputs`, answering
+/// a failed request with the status of its error.
+fn answer<H>(inputs: &Arc<Inputs>, handle: H) -> impl Fn(&Request) -> Response + Send + Sync
+where
+    H: Fn(&Inputs, &Request) -> Result<Response> + Send + Sync + 'static,
+{
+    let inputs: Arc<Inputs> = Arc::clone(inputs);
+    move |request| handle(&inputs, request).unwrap_or_else(|err| Response::error(&err))
+}
+
+/// Returns the zone named by the `file` path parameter of `request`, e.g. "nyc" for
+/// "nyc.json", or `None` if it is not a JSON file name.
+fn json_name(request: &Request) -> Option<&str> {
+    request.segment("file")?.strip_suffix(".json")
+}
+
+/// Renders the actual vs forecast of the zone named `zone`, on the days `request`
+/// asks for, with the chart settings of `config`.
+fn served_chart(inputs: &Inputs, config: &GraphConfig, request: &Request) -> Result<Response> {
+    let zone: &str = request.segment("zone").unwrap_or_default();
+    let (zone, truth, forecast) = served_series(inputs, zone, request)?;
+    let line_graph: LineGraph = LineGraph::builder()
+        .config(config.clone())
+        .truth(truth)
+        .forecast(forecast)
+        .zone(zone)
+        .title(config.labels.titles.served.as_str())
+        .error_band(true)
+        .max_points(2000)
+        .time_format("%m/%d %H:%M")
+        .build()?;
+    let extension: &str = config.backend.renderer().extension(ImageFormat::Png);
+    Ok(Response::file(
+        &format!("{}.{}", zone.truth_name(), extension),
+        line_graph.render_to_bytes(ImageFormat::Png)?,
+    ))
+}
+
+/// Returns the MAPE, bias and peak error of the forecast of the zone named `zone`,
+/// on the days `request` asks for, as JSON.
+fn served_metrics(inputs: &Inputs, zone: &str, request: &Request) -> Result<Response> {
+    let (zone, truth, forecast) = served_series(inputs, zone, request)?;
+    let joined: TimeSeriesDataset = join_actual_forecast(&truth, &forecast)?;
+    if joined.height() == 0 {
+        return Err(MugenError::NoData(format!(
+            "no hours with both actual load and a forecast for {}",
+            zone
+        )));
+    }
+    Ok(Response::json(&serde_json::json!({
+        "zone": zone,
+        "hours": joined.height(),
+        "mape": mape(&joined)?,
+        "bias": bias(&joined)?,
+        "peak_error": peak_error(&joined)?,
+    })))
+}
+
+/// The actual and forecast values last pushed to WebSocket subscribers, by
+/// timestamp in milliseconds.
+#[derive(Default)]
+struct PushedPoints {
+    actual: HashMap<i64, f64>,
+    forecast: HashMap<i64, f64>,
+}
+
+impl PushedPoints {
+    /// Reads `zone`'s data from `inputs` and returns the points that are new or
+    /// changed since the last call as a JSON message, or `None` if there are none.
+    /// Forecast points change when a newer forecast file arrives.
+    fn fresh(&mut self, inputs: &Inputs, zone: Zone) -> Result<Option<String>> {
+        let (truth, forecast, _) = inputs.series(zone, None)?;
+        let actual: Vec<serde_json::Value> = changed_points(&truth, &mut self.actual)?;
+        let forecast: Vec<serde_json::Value> = changed_points(&forecast, &mut self.forecast)?;
+        if actual.is_empty() && forecast.is_empty() {
+            return Ok(None);
+        }
+        let message = serde_json::json!({
+            "zone": zone,
+            "actual": actual,
+            "forecast": forecast,
+        });
+        Ok(Some(message.to_string()))
+    }
+}
+
+/// Returns the points of `dataset` whose value differs from the one in `sent`, in
+/// time order as `{"time", "value"}` objects, and records them in `sent`.
+fn changed_points(
+    dataset: &TimeSeriesDataset,
+    sent: &mut HashMap<i64, f64>,
+) -> Result<Vec<serde_json::Value>> {
+    let mut changed: Vec<(i64, f64)> = dataset
+        .values_by_timestamp(dataset.value_column())?
+        .into_iter()
+        .filter(|(at, value)| sent.get(at) != Some(value))
+        .collect();
+    changed.sort_by_key(|(at, _)| *at);
+    sent.extend(changed.iter().copied());
+    Ok(changed
+        .into_iter()
+        .filter_map(|(at, value)| {
+            let time = DateTime::from_timestamp_millis(at)?.naive_utc();
+            Some(serde_json::json!({
+                "time": time.format("%Y-%m-%dT%H:%M").to_string(),
+                "value": value,
+            }))
+        })
+        .collect())
+}
+
+/// Reads the actual load and forecast of the zone named `zone` from `inputs`, on the
+/// days between the `from` and `to` parameters of `request` if given.
+fn served_series(
+    inputs: &Inputs,
+    zone: &str,
+    request: &Request,
+) -> Result<(Zone, TimeSeriesDataset, TimeSeriesDataset)> {
+    let zone: Zone = zone.parse()?;
+    let from: Option<NaiveDate> = request.param("from").map(parse_date).transpose()?;
+    let to: Option<NaiveDate> = request.param("to").map(parse_date).transpose()?;
+    let period: Option<(NaiveDate, NaiveDate)> = if from.is_some() || to.is_some() {
+        Some((from.unwrap_or(NaiveDate::MIN), to.unwrap_or(NaiveDate::MAX)))
+    } else {
+        None
+    };
+    let (truth, forecast, _) = inputs.series(zone, period)?;
+    Ok((zone, truth, forecast))
+}
@@ -0,0 +1,93 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Adds up how long each pipeline stage took, keyed by span name, so a run can end
+/// with a summary of where its time went.
+///
+/// A span nested in one of the same name, such as a file read inside a multi-file
+/// read, is not counted again. Cloning shares the totals, so keep a clone to print
+/// after installing the layer.
+#[derive(Clone, Default)]
+pub(crate) struct StageTimings {
+    stages: Arc<Mutex<Vec<Stage>>>,
+}
+
+/// Calls and total wall time of one stage.
+struct Stage {
+    name: &'static str,
+    calls: usize,
+    total: Duration,
+}
+
+/// When a span was opened, kept in its extensions until it closes.
+struct Opened(Instant);
+
+impl<S> Layer<S> for StageTimings
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(Opened(Instant::now()));
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        if span
+            .scope()
+            .skip(1)
+            .any(|parent| parent.name() == span.name())
+        {
+            return;
+        }
+        let Some(opened) = span.extensions().get::<Opened>().map(|opened| opened.0) else {
+            return;
+        };
+
+        let mut stages = self.stages.lock().unwrap_or_else(|err| err.into_inner());
+        let elapsed = opened.elapsed();
+        match stages.iter_mut().find(|stage| stage.name == span.name()) {
+            Some(stage) => {
+                stage.calls += 1;
+                stage.total += elapsed;
+            }
+            None => stages.push(Stage {
+                name: span.name(),
+                calls: 1,
+                total: elapsed,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for StageTimings {
+    /// Writes one line per stage in the order they first finished, or nothing if no
+    /// stage ran.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stages = self.stages.lock().unwrap_or_else(|err| err.into_inner());
+        if stages.is_empty() {
+            return Ok(());
+        }
+        writeln!(f, "Timings:")?;
+        for stage in stages.iter() {
+            writeln!(
+                f,
+                "  {:<8} {:>4} call{} {:>10.1} ms",
+                stage.name,
+                stage.calls,
+                if stage.calls == 1 { " " } else { "s" },
+                stage.total.as_secs_f64() * 1000.0
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,388 @@
+use chrono::{DateTime, Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::dataset::TimeSeriesDataset;
+use crate::error::Result;
+
+/// A condition on the data or the forecast that someone should hear about, checked
+/// after every run.
+///
+/// # Examples
+///
+/// ```toml
+/// [[notify.rules]]
+/// kind = "gap"
+/// hours = 1.0
+///
+/// [[notify.rules]]
+/// kind = "ape"
+/// percent = 10.0
+/// hours = 3
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Rule {
+    /// Actual load missing for longer than `hours`: consecutive timestamps further
+    /// apart than that.
+    Gap {
+        /// Longest acceptable time between readings, in hours.
+        hours: f64,
+    },
+    /// The absolute percentage error of the forecast over `percent` for at least
+    /// `hours` consecutive hours.
+    Ape {
+        /// Largest acceptable absolute percentage error.
+        percent: f64,
+        /// Consecutive hours over `percent` it takes to fire.
+        hours: usize,
+    },
+}
+
+/// A rule that fired, over the hours it fired for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Alert {
+    /// The rule, as described by [`Rule::describe`].
+    pub rule: String,
+    /// First timestamp the alert covers.
+    pub start: NaiveDateTime,
+    /// Last timestamp the alert covers.
+    pub end: NaiveDateTime,
+    /// What happened, e.g. "no ground truth between 12/09 04:00 and 12/09 07:00".
+    pub message: String,
+}
+
+impl Rule {
+    /// Describes the rule, e.g. "data gap over 1h".
+    pub fn describe(&self) -> String {
+        match self {
+            Rule::Gap { hours } => format!("data gap over {}h", hours),
+            Rule::Ape { percent, hours } => {
+                format!("APE over {}% for {} consecutive hours", percent, hours)
+            }
+        }
+    }
+
+    /// Describes what is wrong with the rule's settings, with a suggested fix, or
+    /// `None` if they are valid.
+    pub fn problem(&self) -> Option<String> {
+        match self {
+            Rule::Gap { hours } if !(hours.is_finite() && *hours > 0.0) => Some(format!(
+                "gap rule hours {} must be above 0, e.g. 1 to alert on a missing hour",
+                hours
+            )),
+            Rule::Ape { percent, .. } if !(percent.is_finite() && *percent >= 0.0) => {
+                Some(format!("ape rule percent {} must be 0 or more", percent))
+            }
+            Rule::Ape { hours: 0, .. } => {
+                Some("ape rule hours is 0; use 1 or more consecutive hours".to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Checks the rule against a run's data.
+    ///
+    /// # Arguments
+    ///
+    /// * `truth` - The processed actual load, with one row per timestamp.
+    /// * `joined` - `truth` joined against the forecast, as returned by
+    ///   [`crate::data::join_actual_forecast`].
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing every alert, earliest first, or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a time column is not a datetime, or `joined` lacks the
+    /// "Actual" or "Residual" column.
+    pub fn check(
+        &self,
+        truth: &TimeSeriesDataset,
+        joined: &TimeSeriesDataset,
+    ) -> Result<Vec<Alert>> {
+        let alerts: Vec<(NaiveDateTime, NaiveDateTime, String)> = match self {
+            Rule::Gap { hours } => gaps(truth, Duration::seconds((hours * 3600.0) as i64))?
+                .into_iter()
+                .map(|(before, after)| {
+                    let message: String = format!(
+                        "no {} between {} and {}",
+                        truth.name(),
+                        before.format("%m/%d %H:%M"),
+                        after.format("%m/%d %H:%M")
+                    );
+                    (before, after, message)
+                })
+                .collect(),
+            Rule::Ape { percent, hours } => ape_runs(joined, *percent, *hours)?
+                .into_iter()
+                .map(|(start, end, peak)| {
+                    let message: String = format!(
+                        "APE over {}% from {} to {}, peaking at {:.1}%",
+                        percent,
+                        start.format("%m/%d %H:%M"),
+                        end.format("%m/%d %H:%M"),
+                        peak
+                    );
+                    (start, end, message)
+                })
+                .collect(),
+        };
+        Ok(alerts
+            .into_iter()
+            .map(|(start, end, message)| Alert {
+                rule: self.describe(),
+                start,
+                end,
+                message,
+            })
+            .collect())
+    }
+}
+
+/// Checks every rule in `rules` against a run's data, see [`Rule::check`].
+///
+/// # Errors
+///
+/// Returns the first error of a rule.
+pub fn check_all(
+    rules: &[Rule],
+    truth: &TimeSeriesDataset,
+    joined: &TimeSeriesDataset,
+) -> Result<Vec<Alert>> {
+    let mut alerts: Vec<Alert> = Vec::new();
+    for rule in rules {
+        alerts.extend(rule.check(truth, joined)?);
+    }
+    Ok(alerts)
+}
+
+/// Finds the gaps in `dataset`: every pair of consecutive timestamps further apart
+/// than `max`.
+///
+/// # Arguments
+///
+/// * `dataset` - The series to check, in any order.
+/// * `max` - The longest acceptable time between timestamps.
+///
+/// # Returns
+///
+/// * A `Result` containing the timestamps before and after each gap, earliest
+///   first, or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns an error if the time column of `dataset` is not a datetime.
+pub fn gaps(
+    dataset: &TimeSeriesDataset,
+    max: Duration,
+) -> Result<Vec<(NaiveDateTime, NaiveDateTime)>> {
+    let mut times: Vec<i64> = dataset.timestamps()?.into_iter().flatten().collect();
+    times.sort_unstable();
+    times.dedup();
+    Ok(times
+        .windows(2)
+        .filter(|pair| pair[1] - pair[0] > max.num_milliseconds())
+        .filter_map(|pair| Some((at(pair[0])?, at(pair[1])?)))
+        .collect())
+}
+
+/// Finds the runs of at least `hours` consecutive rows of `joined` whose absolute
+/// percentage error is over `percent`.
+///
+/// Rows are consecutive if they follow each other in time order; a missing hour
+/// does not break a run.
+///
+/// # Arguments
+///
+/// * `joined` - Actual load joined against the forecast, as returned by
+///   [`crate::data::join_actual_forecast`].
+/// * `percent` - The largest acceptable absolute percentage error.
+/// * `hours` - The shortest run to report.
+///
+/// # Returns
+///
+/// * A `Result` containing the first and last timestamp and the largest error of
+///   each run, earliest first, or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns an error if the time column of `joined` is not a datetime, or the
+/// "Actual" or "Residual" column is missing or not numeric.
+pub fn ape_runs(
+    joined: &TimeSeriesDataset,
+    percent: f64,
+    hours: usize,
+) -> Result<Vec<(NaiveDateTime, NaiveDateTime, f64)>> {
+    let actual = joined.values("Actual")?;
+    let residual = joined.values("Residual")?;
+    let mut rows: Vec<(i64, f64)> = joined
+        .timestamps()?
+        .into_iter()
+        .zip(actual.into_iter().zip(&residual))
+        .filter_map(|(at, (actual, residual))| Some((at?, (residual? / actual?).abs() * 100.0)))
+        .collect();
+    rows.sort_by_key(|(at, _)| *at);
+
+    let mut runs: Vec<(NaiveDateTime, NaiveDateTime, f64)> = Vec::new();
+    let mut run: Vec<(i64, f64)> = Vec::new();
+    for row in rows.into_iter().map(Some).chain([None]) {
+        match row {
+            Some(row) if row.1 > percent => run.push(row),
+            _ => {
+                if run.len() >= hours.max(1) {
+                    let peak: f64 = run.iter().map(|(_, ape)| *ape).fold(f64::MIN, f64::max);
+                    if let (Some(start), Some(end)) = (at(run[0].0), at(run[run.len() - 1].0)) {
+                        runs.push((start, end, peak));
+                    }
+                }
+                run.clear();
+            }
+        }
+    }
+    Ok(runs)
+}
+
+/// Returns the local time `ms` milliseconds after the epoch.
+fn at(ms: i64) -> Option<NaiveDateTime> {
+    DateTime::from_timestamp_millis(ms).map(|at| at.naive_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use polars::prelude::{df, DataFrame, DataType, NamedFrom, Series, TimeUnit};
+
+    use super::*;
+
+    /// Midnight of December 9th 2023 plus `hours`.
+    fn hour(hours: i64) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2023, 12, 9)
+            .and_then(|day| day.and_hms_opt(0, 0, 0))
+            .unwrap()
+            + Duration::hours(hours)
+    }
+
+    /// Joined rows at `hours` with an actual load of 100 and the residual giving
+    /// each row's APE in percent; `None` leaves the residual missing.
+    fn joined(hours: &[i64], apes: &[Option<f64>]) -> TimeSeriesDataset {
+        let times: Vec<i64> = hours
+            .iter()
+            .map(|h| hour(*h).and_utc().timestamp_millis())
+            .collect();
+        let frame: DataFrame = df!(
+            "Time Stamp" => Series::new("Time Stamp", times)
+                .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))
+                .unwrap(),
+            "Actual" => vec![100.0; hours.len()],
+            "Residual" => apes.to_vec(),
+        )
+        .unwrap();
+        TimeSeriesDataset::new(
+            "ground truth",
+            frame,
+            "Time Stamp",
+            vec!["Actual".to_string()],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn gap_fires_only_over_the_limit() {
+        let data = joined(&[3, 0, 1, 1, 5, 9], &[Some(0.0); 6]);
+        let rule = Rule::Gap { hours: 2.0 };
+        let alerts = rule.check(&data, &data).unwrap();
+        // 1 to 3 and 3 to 5 are exactly two hours apart; 5 to 9 is over.
+        assert_eq!(alerts.len(), 1);
+        assert_eq!((alerts[0].start, alerts[0].end), (hour(5), hour(9)));
+        assert_eq!(alerts[0].rule, "data gap over 2h");
+        assert_eq!(
+            alerts[0].message,
+            "no ground truth between 12/09 05:00 and 12/09 09:00"
+        );
+    }
+
+    #[test]
+    fn gap_ignores_too_little_data() {
+        let rule = Rule::Gap { hours: 1.0 };
+        for data in [joined(&[], &[]), joined(&[4], &[Some(0.0)])] {
+            assert!(rule.check(&data, &data).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn ape_fires_on_runs_strictly_over_the_percent() {
+        let apes = [10.0, 12.0, -15.0, 11.0, 5.0, 20.0, 30.0, 10.0];
+        let data = joined(&(0..8).collect::<Vec<i64>>(), &apes.map(Some));
+        let rule = Rule::Ape {
+            percent: 10.0,
+            hours: 2,
+        };
+        let alerts = rule.check(&data, &data).unwrap();
+        // 10% itself is not over, and an underforecast counts by its absolute value.
+        assert_eq!(alerts.len(), 2);
+        assert_eq!((alerts[0].start, alerts[0].end), (hour(1), hour(3)));
+        assert_eq!(
+            alerts[0].message,
+            "APE over 10% from 12/09 01:00 to 12/09 03:00, peaking at 15.0%"
+        );
+        assert_eq!((alerts[1].start, alerts[1].end), (hour(5), hour(6)));
+        let runs = ape_runs(&data, 10.0, 3).unwrap();
+        assert_eq!(runs, [(hour(1), hour(3), 15.0)]);
+    }
+
+    #[test]
+    fn ape_skips_missing_rows_without_breaking_a_run() {
+        let data = joined(&[0, 1, 2, 3], &[Some(20.0), None, Some(20.0), Some(1.0)]);
+        assert_eq!(
+            ape_runs(&data, 10.0, 2).unwrap(),
+            [(hour(0), hour(2), 20.0)]
+        );
+        let missing = joined(&[0, 1], &[None, None]);
+        assert!(ape_runs(&missing, 10.0, 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn check_all_keeps_rule_order() {
+        let data = joined(&[0, 4], &[Some(50.0), Some(50.0)]);
+        let rules = [
+            Rule::Ape {
+                percent: 10.0,
+                hours: 1,
+            },
+            Rule::Gap { hours: 1.0 },
+        ];
+        let alerts = check_all(&rules, &data, &data).unwrap();
+        let kinds: Vec<&str> = alerts.iter().map(|alert| alert.rule.as_str()).collect();
+        assert_eq!(
+            kinds,
+            ["APE over 10% for 1 consecutive hours", "data gap over 1h"]
+        );
+    }
+
+    #[test]
+    fn problem_flags_unusable_settings() {
+        assert!(Rule::Gap { hours: 0.0 }.problem().is_some());
+        assert!(Rule::Gap { hours: f64::NAN }.problem().is_some());
+        assert!(Rule::Ape {
+            percent: -1.0,
+            hours: 1
+        }
+        .problem()
+        .is_some());
+        assert!(Rule::Ape {
+            percent: 10.0,
+            hours: 0
+        }
+        .problem()
+        .is_some());
+        assert_eq!(
+            Rule::Ape {
+                percent: 0.0,
+                hours: 1
+            }
+            .problem(),
+            None
+        );
+    }
+}
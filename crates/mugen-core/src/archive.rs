@@ -0,0 +1,402 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::{Datelike, NaiveDate};
+use polars::prelude::*;
+
+use crate::catalog::kind;
+use crate::checksum::digest_path;
+use crate::data::{read_csv_to_df, read_each_csv_with};
+use crate::error::{MugenError, Result};
+use crate::export::{parquet_rows, scan_parquet, write_parquet};
+use crate::resources::Resources;
+
+/// Name of the directory, inside a data directory, that months are compacted into.
+pub const ARCHIVE_DIR: &str = "archive";
+
+/// Column added to a compacted month naming the daily file each row came from, e.g.
+/// "20231109palIntegrated.csv", so the file can be read back on its own.
+pub const SOURCE_COLUMN: &str = "Source File";
+
+/// The daily CSVs of one kind and month, e.g. every "palIntegrated" file of
+/// November 2023.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonthlyFiles {
+    /// What the files hold, e.g. "palIntegrated" or "isolf".
+    pub kind: String,
+    /// First day of the month.
+    pub month: NaiveDate,
+    /// The daily files, in date order.
+    pub files: Vec<String>,
+}
+
+impl MonthlyFiles {
+    /// Returns the path of the month's compacted file under `archive`, e.g.
+    /// "data/archive/palIntegrated/2023-11.parquet".
+    pub fn archive_path(&self, archive: &str) -> String {
+        Path::new(archive)
+            .join(&self.kind)
+            .join(format!("{}.parquet", self.month.format("%Y-%m")))
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Finds the daily CSVs in `dir`, named like "20231109palIntegrated.csv", of every
+/// month that ended before `before`, grouped by kind and month.
+///
+/// Only whole months are returned, so a month is compacted once, with all its
+/// days, rather than piece by piece as they age.
+///
+/// # Returns
+///
+/// * A `Result` containing the groups, by kind then month, or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::InFile` if `dir` cannot be listed.
+pub fn monthly_files(dir: &str, before: NaiveDate) -> Result<Vec<MonthlyFiles>> {
+    let listing = std::fs::read_dir(dir).map_err(|source| MugenError::InFile {
+        path: dir.to_string(),
+        source: Box::new(source.into()),
+    })?;
+    let mut groups: BTreeMap<(String, NaiveDate), Vec<String>> = BTreeMap::new();
+    for entry in listing.filter_map(|entry| entry.ok()) {
+        let path = entry.path().to_string_lossy().into_owned();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some(month) = month_of(&name) else {
+            continue;
+        };
+        let next_month = month
+            .checked_add_months(chrono::Months::new(1))
+            .unwrap_or(NaiveDate::MAX);
+        if next_month <= before {
+            groups.entry((kind(&path), month)).or_default().push(path);
+        }
+    }
+    Ok(groups
+        .into_iter()
+        .map(|((kind, month), mut files)| {
+            files.sort();
+            MonthlyFiles { kind, month, files }
+        })
+        .collect())
+}
+
+/// Writes the rows of every file of `month` into one Parquet file under `archive`,
+/// at [`MonthlyFiles::archive_path`], and reads it back to check that no row was
+/// lost; the columns are kept as in the CSVs, plus [`SOURCE_COLUMN`] naming the
+/// file of each row.
+///
+/// A month already archived with as many rows is left as it is, so compacting
+/// again, e.g. to delete the originals after all, is safe. One archived with a
+/// different number of rows is never overwritten, since the daily files may have
+/// changed since it was written.
+///
+/// # Arguments
+///
+/// * `month` - The files to compact, e.g. from [`monthly_files`].
+/// * `archive` - Directory of the archive, e.g. "data/archive"; created if missing.
+/// * `resources` - The thread and memory budget of reading the daily files.
+///
+/// # Returns
+///
+/// * A `Result` containing the path written and the number of rows, or a
+///   `MugenError`.
+///
+/// # Errors
+///
+/// Returns the errors of [`crate::data::read_multiple_csvs_with`], including
+/// `MugenError::Checksum` for a file that changed since download, `MugenError::Write`
+/// if the file cannot be written, and `MugenError::InvalidArgument` if the archived
+/// month holds a different number of rows or without the `parquet` feature.
+pub fn compact(
+    month: &MonthlyFiles,
+    archive: &str,
+    resources: &Resources,
+) -> Result<(String, usize)> {
+    let path = month.archive_path(archive);
+    let paths: Vec<&str> = month.files.iter().map(String::as_str).collect();
+    let mut frame: DataFrame = DataFrame::default();
+    for (file, mut df) in paths.iter().zip(read_each_csv_with(&paths, resources)?) {
+        let source = Series::new(SOURCE_COLUMN, vec![file_name(file); df.height()]);
+        df.with_column(source)?;
+        frame.vstack_mut(&df).map_err(|source| MugenError::InFile {
+            path: file.to_string(),
+            source: Box::new(source.into()),
+        })?;
+    }
+    if Path::new(&path).exists() {
+        let rows = parquet_rows(&path)?;
+        if rows == frame.height() {
+            return Ok((path, rows));
+        }
+        return Err(MugenError::InvalidArgument(format!(
+            "\"{}\" already holds {} rows but the daily files hold {}; move it aside to \
+             compact the month again",
+            path,
+            rows,
+            frame.height()
+        )));
+    }
+    if let Some(dir) = Path::new(&path).parent() {
+        std::fs::create_dir_all(dir).map_err(|source| MugenError::Write {
+            path: dir.to_string_lossy().into_owned(),
+            source,
+        })?;
+    }
+    write_parquet(&mut frame, &path)?;
+    let rows = parquet_rows(&path)?;
+    if rows != frame.height() {
+        return Err(MugenError::InvalidArgument(format!(
+            "\"{}\" holds {} rows after writing, expected {}",
+            path,
+            rows,
+            frame.height()
+        )));
+    }
+    Ok((path, rows))
+}
+
+/// Returns the path of the monthly file the daily file at `path` is compacted into,
+/// in the archive next to it, e.g. "data/archive/palIntegrated/2023-11.parquet" for
+/// "data/20231109palIntegrated.csv", or `None` if `path` is not a daily file.
+pub fn archived_path(path: &str) -> Option<String> {
+    let month = month_of(&file_name(path))?;
+    let archive = Path::new(path)
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(ARCHIVE_DIR);
+    let month = MonthlyFiles {
+        kind: kind(path),
+        month,
+        files: Vec::new(),
+    };
+    Some(month.archive_path(&archive.to_string_lossy()))
+}
+
+/// Reads the rows of the daily file at `path` back from its monthly file, see
+/// [`archived_path`], with the columns the file had; the loaders in
+/// [`crate::data`] read a daily file this way once [`delete_files`] removed it.
+///
+/// # Returns
+///
+/// * A `Result` containing the rows, or `None` if the month is not archived, was
+///   archived before [`SOURCE_COLUMN`] was kept, or holds no row of `path`.
+///
+/// # Errors
+///
+/// Returns `MugenError::Read` if the monthly file cannot be read, and
+/// `MugenError::InvalidArgument` without the `parquet` feature.
+pub fn read_archived(path: &str) -> Result<Option<DataFrame>> {
+    let Some(archive) = archived_path(path) else {
+        return Ok(None);
+    };
+    if !Path::new(&archive).exists() {
+        return Ok(None);
+    }
+    let scan: LazyFrame = scan_parquet(&archive)?;
+    if scan.schema()?.get(SOURCE_COLUMN).is_none() {
+        return Ok(None);
+    }
+    let rows: DataFrame = scan
+        .filter(col(SOURCE_COLUMN).eq(lit(file_name(path))))
+        .drop([SOURCE_COLUMN])
+        .collect()?;
+    Ok((rows.height() > 0).then_some(rows))
+}
+
+/// Lists the daily files in `dir` whose rows are held in its archive, as paths in
+/// `dir` like the files themselves, e.g. "data/20231109palIntegrated.csv", whether
+/// or not the file is still there.
+///
+/// # Errors
+///
+/// Returns `MugenError::InFile` if the archive cannot be listed, and the errors of
+/// [`read_archived`] if a monthly file cannot be read.
+pub fn archived_files(dir: &str) -> Result<Vec<String>> {
+    let archive = Path::new(dir).join(ARCHIVE_DIR);
+    if !archive.is_dir() {
+        return Ok(Vec::new());
+    }
+    let list = |dir: &Path| {
+        std::fs::read_dir(dir).map_err(|source| MugenError::InFile {
+            path: dir.to_string_lossy().into_owned(),
+            source: Box::new(source.into()),
+        })
+    };
+    let mut files: Vec<String> = Vec::new();
+    for kind in list(&archive)?.filter_map(|entry| entry.ok()) {
+        if !kind.path().is_dir() {
+            continue;
+        }
+        for month in list(&kind.path())?.filter_map(|entry| entry.ok()) {
+            let month = month.path().to_string_lossy().into_owned();
+            if !month.ends_with(".parquet") {
+                continue;
+            }
+            let scan: LazyFrame = scan_parquet(&month)?;
+            if scan.schema()?.get(SOURCE_COLUMN).is_none() {
+                continue;
+            }
+            let names: DataFrame = scan.select([col(SOURCE_COLUMN).unique()]).collect()?;
+            files.extend(
+                names
+                    .column(SOURCE_COLUMN)?
+                    .str()?
+                    .into_iter()
+                    .flatten()
+                    .map(|name| Path::new(dir).join(name).to_string_lossy().into_owned()),
+            );
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Deletes the daily files of `month` and the size and hash recorded for each, once
+/// every file's rows read back from the archive next to them, see
+/// [`read_archived`], match the file; the loaders then read them from there.
+///
+/// # Errors
+///
+/// Returns `MugenError::InvalidArgument`, before deleting anything, naming the first
+/// file the archive does not hold as it is, e.g. when the month was compacted into
+/// another directory, the errors of [`read_csv_to_df`] and [`read_archived`], and
+/// `MugenError::Write` naming the first file that cannot be deleted; the files
+/// before it are gone.
+pub fn delete_files(month: &MonthlyFiles) -> Result<()> {
+    for path in &month.files {
+        let held: bool = match read_archived(path)? {
+            Some(archived) => archived.equals_missing(&read_csv_to_df(path)?),
+            None => false,
+        };
+        if !held {
+            return Err(MugenError::InvalidArgument(format!(
+                "\"{}\" is not held as it is by \"{}\"; keeping the daily files",
+                path,
+                archived_path(path).unwrap_or_default()
+            )));
+        }
+    }
+    for path in &month.files {
+        std::fs::remove_file(path).map_err(|source| MugenError::Write {
+            path: path.clone(),
+            source,
+        })?;
+        let record = digest_path(path);
+        if Path::new(&record).exists() {
+            std::fs::remove_file(&record).map_err(|source| MugenError::Write {
+                path: record.clone(),
+                source,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the month of the daily file named `name`, e.g. November 2023 for
+/// "20231109palIntegrated.csv", or `None` if it is not named like one.
+fn month_of(name: &str) -> Option<NaiveDate> {
+    if !name.ends_with(".csv") {
+        return None;
+    }
+    name.get(..8)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+        .and_then(|day| day.with_day(1))
+}
+
+/// Returns the file name of `path`, e.g. "20231109palIntegrated.csv".
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(all(test, feature = "parquet"))]
+mod tests {
+    use std::fs::File;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::catalog::Catalog;
+    use crate::checksum::{file_digest, record};
+    use crate::data::scan_csvs;
+    use crate::synthetic::Synthetic;
+
+    /// Writes two days of synthetic actual load from November 2023 as daily files,
+    /// with their sizes and hashes recorded, under a directory of their own,
+    /// returning the directory and the days' frames.
+    fn days(name: &str) -> (String, Vec<DataFrame>) {
+        let dir: PathBuf =
+            std::env::temp_dir().join(format!("mugen-archive-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let synthetic: Synthetic = Synthetic::default();
+        let frames: Vec<DataFrame> = [8, 9]
+            .into_iter()
+            .map(|day| {
+                let day = NaiveDate::from_ymd_opt(2023, 11, day).unwrap();
+                let path: PathBuf = dir.join(format!("{}palIntegrated.csv", day.format("%Y%m%d")));
+                let mut frame: DataFrame = synthetic.truth(day..=day).unwrap();
+                CsvWriter::new(File::create(&path).unwrap())
+                    .finish(&mut frame)
+                    .unwrap();
+                let path: String = path.to_string_lossy().into_owned();
+                record(&file_digest(&path).unwrap()).unwrap();
+                read_csv_to_df(&path).unwrap()
+            })
+            .collect();
+        (dir.to_string_lossy().into_owned(), frames)
+    }
+
+    #[test]
+    fn deleted_files_are_read_back_from_the_archive() {
+        let (dir, frames) = days("delete");
+        let december: NaiveDate = NaiveDate::from_ymd_opt(2023, 12, 1).unwrap();
+        let months: Vec<MonthlyFiles> = monthly_files(&dir, december).unwrap();
+        assert_eq!(months.len(), 1);
+        let archive: String = Path::new(&dir)
+            .join(ARCHIVE_DIR)
+            .to_string_lossy()
+            .into_owned();
+        let (path, rows): (String, usize) =
+            compact(&months[0], &archive, &Resources::default()).unwrap();
+        assert_eq!(Some(path), archived_path(&months[0].files[0]));
+        assert_eq!(rows, frames.iter().map(DataFrame::height).sum::<usize>());
+
+        delete_files(&months[0]).unwrap();
+        for (file, frame) in months[0].files.iter().zip(&frames) {
+            assert!(!Path::new(file).exists());
+            assert!(!Path::new(&digest_path(file)).exists());
+            assert!(read_csv_to_df(file).unwrap().equals_missing(frame));
+        }
+        let paths: Vec<&str> = months[0].files.iter().map(String::as_str).collect();
+        assert_eq!(scan_csvs(&paths).unwrap().collect().unwrap().height(), rows);
+        assert!(monthly_files(&dir, december).unwrap().is_empty());
+
+        let mut catalog: Catalog = Catalog::load(&dir).unwrap();
+        assert_eq!(catalog.refresh().unwrap(), months[0].files);
+        assert!(catalog.refresh().unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn files_compacted_elsewhere_are_not_deleted() {
+        let (dir, _) = days("elsewhere");
+        let months: Vec<MonthlyFiles> =
+            monthly_files(&dir, NaiveDate::from_ymd_opt(2023, 12, 1).unwrap()).unwrap();
+        let elsewhere: String = Path::new(&dir)
+            .join("elsewhere")
+            .to_string_lossy()
+            .into_owned();
+        compact(&months[0], &elsewhere, &Resources::default()).unwrap();
+
+        assert!(matches!(
+            delete_files(&months[0]),
+            Err(MugenError::InvalidArgument(_))
+        ));
+        assert!(months[0].files.iter().all(|file| Path::new(file).exists()));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,359 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::data::join_actual_forecast;
+use crate::dataset::TimeSeriesDataset;
+use crate::error::Result;
+use crate::metrics::{bias, mape, peak_error};
+use crate::split::Split;
+
+/// Where the forecasts a backtest scores come from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Forecaster {
+    /// Forecasts stored on disk, e.g. NYISO's own forecast issued the day before.
+    #[default]
+    Stored,
+    /// [`crate::baseline::HoltWinters`], fitted to the history before each day.
+    HoltWinters,
+    /// [`crate::baseline::persistence`] of the history before each day.
+    Persistence,
+}
+
+impl fmt::Display for Forecaster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Forecaster::Stored => "stored",
+            Forecaster::HoltWinters => "holt-winters",
+            Forecaster::Persistence => "persistence",
+        })
+    }
+}
+
+impl FromStr for Forecaster {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "stored" => Ok(Forecaster::Stored),
+            "holt-winters" | "holtwinters" => Ok(Forecaster::HoltWinters),
+            "persistence" => Ok(Forecaster::Persistence),
+            _ => Err(format!(
+                "unknown forecaster \"{}\", expected stored, holt-winters or persistence",
+                name
+            )),
+        }
+    }
+}
+
+/// One split of a backtest: the forecast of its test period scored against the
+/// actual load.
+#[derive(Clone, Debug)]
+pub struct Fold {
+    /// The days trained on and forecast.
+    pub split: Split,
+    /// Hours with both actual load and a forecast.
+    pub hours: usize,
+    /// Every metric by name: "MAPE", "Peak Error" and "Bias".
+    pub metrics: Vec<(String, f64)>,
+    /// The actual load of the test period joined against the forecast, e.g. to
+    /// learn [`crate::interval::ErrorQuantiles`] from.
+    pub joined: TimeSeriesDataset,
+}
+
+/// What a backtest produced: a fold per split, and the splits that could not be
+/// scored.
+#[derive(Clone, Debug, Default)]
+pub struct Backtest {
+    /// The scored days, in order.
+    pub folds: Vec<Fold>,
+    /// The first test day of each split left out, with why, e.g. a missing forecast
+    /// file.
+    pub skipped: Vec<(NaiveDate, String)>,
+}
+
+impl Backtest {
+    /// Returns each metric averaged over the folds, weighing every fold the same, in
+    /// the order of the folds' metrics; empty without folds.
+    pub fn summary(&self) -> Vec<(String, f64)> {
+        let Some(first) = self.folds.first() else {
+            return Vec::new();
+        };
+        first
+            .metrics
+            .iter()
+            .enumerate()
+            .map(|(index, (name, _))| {
+                let values: Vec<f64> = self
+                    .folds
+                    .iter()
+                    .filter_map(|fold| fold.metrics.get(index).map(|(_, value)| *value))
+                    .filter(|value| value.is_finite())
+                    .collect();
+                let mean: f64 = if values.is_empty() {
+                    f64::NAN
+                } else {
+                    values.iter().sum::<f64>() / values.len() as f64
+                };
+                (name.clone(), mean)
+            })
+            .collect()
+    }
+
+    /// Returns the folds as a table with the columns "Day" (the first test day),
+    /// "Days" tested, "Train From", "Train To", "Hours" scored and one per metric,
+    /// e.g. to write with [`crate::export::write_table`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table cannot be built.
+    pub fn table(&self) -> Result<TimeSeriesDataset> {
+        let dates = |name: &str, day: fn(&Split) -> NaiveDate| -> Series {
+            let days: Vec<i64> = self
+                .folds
+                .iter()
+                .map(|fold| {
+                    day(&fold.split)
+                        .and_hms_opt(0, 0, 0)
+                        .map(|at| at.and_utc().timestamp_millis())
+                        .unwrap_or_default()
+                })
+                .collect();
+            Int64Chunked::from_vec(name, days)
+                .into_datetime(TimeUnit::Milliseconds, None)
+                .into_series()
+        };
+        let mut columns: Vec<Series> = vec![
+            dates("Day", |split| split.test_from),
+            Series::new(
+                "Days",
+                self.folds
+                    .iter()
+                    .map(|fold| fold.split.test_days() as u32)
+                    .collect::<Vec<u32>>(),
+            ),
+            dates("Train From", |split| split.train_from),
+            dates("Train To", |split| split.train_to),
+            Series::new(
+                "Hours",
+                self.folds
+                    .iter()
+                    .map(|fold| fold.hours as u32)
+                    .collect::<Vec<u32>>(),
+            ),
+        ];
+        let names: Vec<String> = self
+            .folds
+            .first()
+            .map(|fold| fold.metrics.iter().map(|(name, _)| name.clone()).collect())
+            .unwrap_or_else(|| vec!["MAPE".into(), "Peak Error".into(), "Bias".into()]);
+        for (index, name) in names.iter().enumerate() {
+            let values: Vec<f64> = self
+                .folds
+                .iter()
+                .map(|fold| {
+                    fold.metrics
+                        .get(index)
+                        .map_or(f64::NAN, |(_, value)| *value)
+                })
+                .collect();
+            columns.push(Series::new(name, values));
+        }
+        TimeSeriesDataset::new("backtest", DataFrame::new(columns)?, "Day", names)
+    }
+}
+
+/// Rolls a forecaster over the splits and scores each test period's forecast
+/// against its actual load, one fold per split.
+///
+/// For each split, `forecast` gets the split and the actual load of its training
+/// period, so a model sees only what was known at the time and every forecaster
+/// sees the same, and returns the forecast of the test period. A split it fails
+/// for, or without actual load or forecast hours, is skipped and noted rather than
+/// ending the backtest.
+///
+/// # Arguments
+///
+/// * `truth` - The processed actual load, covering the training and test periods.
+/// * `splits` - The periods to train and test on, e.g. from [`crate::split::splits`].
+/// * `forecast` - Returns the forecast of a split's test period from its training
+///   data.
+///
+/// # Returns
+///
+/// * A `Result` containing the folds and skipped splits, or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns an error if the time column of `truth` is not a datetime.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use mugen_core::backtest::backtest;
+/// use mugen_core::baseline::{HoltWinters, Seasonality};
+/// use mugen_core::data::process_truth;
+/// use mugen_core::split::{splits, Window};
+/// use mugen_core::synthetic::Synthetic;
+/// use mugen_core::zone::Zone;
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let first = NaiveDate::from_ymd_opt(2023, 12, 1).unwrap();
+/// let last = NaiveDate::from_ymd_opt(2023, 12, 10).unwrap();
+/// let truth = process_truth(Synthetic::default().truth(first..=last)?, Zone::NewYorkCity)?;
+/// let days = splits(
+///     NaiveDate::from_ymd_opt(2023, 12, 8).unwrap(),
+///     NaiveDate::from_ymd_opt(2023, 12, 10).unwrap(),
+///     Window::Sliding,
+///     7,
+///     1,
+/// )?;
+/// let result = backtest(&truth, &days, |_, history| {
+///     HoltWinters::fit(history, Seasonality::Weekly)?.forecast(history, 24)
+/// })?;
+/// assert_eq!(result.folds.len(), 3);
+/// println!("{:?}", result.summary());
+/// # Ok(())
+/// # }
+/// ```
+pub fn backtest(
+    truth: &TimeSeriesDataset,
+    splits: &[Split],
+    mut forecast: impl FnMut(&Split, &TimeSeriesDataset) -> Result<TimeSeriesDataset>,
+) -> Result<Backtest> {
+    let mut result: Backtest = Backtest::default();
+    for split in splits {
+        let history: TimeSeriesDataset = split.train(truth)?;
+        let actual: TimeSeriesDataset = split.test(truth)?;
+        if actual.height() == 0 {
+            result
+                .skipped
+                .push((split.test_from, format!("no {} to test on", truth.name())));
+            continue;
+        }
+        let joined: TimeSeriesDataset = match forecast(split, &history)
+            .and_then(|predicted| split.test(&predicted))
+            .and_then(|predicted| join_actual_forecast(&actual, &predicted))
+        {
+            Ok(joined) => joined,
+            Err(err) => {
+                result.skipped.push((split.test_from, err.to_string()));
+                continue;
+            }
+        };
+        if joined.height() == 0 {
+            result.skipped.push((
+                split.test_from,
+                "the forecast covers no hour of the test days".to_string(),
+            ));
+            continue;
+        }
+        result.folds.push(Fold {
+            split: *split,
+            hours: joined.height(),
+            metrics: vec![
+                ("MAPE".to_string(), mape(&joined)?),
+                ("Peak Error".to_string(), peak_error(&joined)?),
+                ("Bias".to_string(), bias(&joined)?),
+            ],
+            joined,
+        });
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::process_truth;
+    use crate::error::MugenError;
+    use crate::split::{splits, Window};
+    use crate::synthetic::Synthetic;
+    use crate::zone::Zone;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 12, day).unwrap()
+    }
+
+    /// Actual load of New York City from 1 to 10 December 2023.
+    fn truth() -> TimeSeriesDataset {
+        process_truth(
+            Synthetic::default().truth(day(1)..=day(10)).unwrap(),
+            Zone::NewYorkCity,
+        )
+        .unwrap()
+    }
+
+    /// A forecast of the test period of `split` 10% above the actual load.
+    fn ten_percent_high(truth: &TimeSeriesDataset, split: &Split) -> Result<TimeSeriesDataset> {
+        let value: &str = truth.value_column();
+        split.test(truth)?.map_frame(|frame| {
+            Ok(frame
+                .clone()
+                .lazy()
+                .with_column(col(value) * lit(1.1))
+                .collect()?)
+        })
+    }
+
+    #[test]
+    fn folds_score_each_test_period_from_its_training_data_alone() {
+        let truth: TimeSeriesDataset = truth();
+        let days: Vec<Split> = splits(day(6), day(9), Window::Sliding, 5, 2).unwrap();
+        let result: Backtest = backtest(&truth, &days, |split, history| {
+            let last: i64 = *history.unique_timestamps()?.last().unwrap();
+            let test_start: i64 = split
+                .test_from
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+                .and_utc()
+                .timestamp_millis();
+            assert!(
+                last < test_start,
+                "training data reaches into the test days"
+            );
+            ten_percent_high(&truth, split)
+        })
+        .unwrap();
+
+        assert!(result.skipped.is_empty());
+        assert_eq!(result.folds.len(), 2);
+        for fold in &result.folds {
+            assert_eq!(fold.hours, 48);
+            let (name, mape) = &fold.metrics[0];
+            assert_eq!(name, "MAPE");
+            assert!((mape - 10.0).abs() < 1e-6, "MAPE {}", mape);
+        }
+        let (_, mean) = &result.summary()[0];
+        assert!((mean - 10.0).abs() < 1e-6);
+        assert_eq!(result.table().unwrap().height(), 2);
+    }
+
+    #[test]
+    fn splits_that_cannot_be_scored_are_skipped_with_a_reason() {
+        let truth: TimeSeriesDataset = truth();
+        let days: Vec<Split> = splits(day(8), day(12), Window::Expanding, 7, 1).unwrap();
+        let result: Backtest = backtest(&truth, &days, |split, _| {
+            if split.test_from == day(9) {
+                return Err(MugenError::NoData("no forecast file".to_string()));
+            }
+            ten_percent_high(&truth, split)
+        })
+        .unwrap();
+
+        let scored: Vec<NaiveDate> = result
+            .folds
+            .iter()
+            .map(|fold| fold.split.test_from)
+            .collect();
+        assert_eq!(scored, vec![day(8), day(10)]);
+        let skipped: Vec<NaiveDate> = result.skipped.iter().map(|(day, _)| *day).collect();
+        assert_eq!(skipped, vec![day(9), day(11), day(12)]);
+        assert!(result.skipped[0].1.contains("no forecast file"));
+    }
+}
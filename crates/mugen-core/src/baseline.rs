@@ -0,0 +1,434 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::dataset::{TimeSeriesDataset, HOUR_MS};
+use crate::error::{MugenError, Result};
+
+/// Name of the dataset a Holt-Winters forecast is returned as.
+const HOLT_WINTERS_FRAME: &str = "Holt-Winters forecast";
+
+/// Name of the dataset a persistence forecast is returned as.
+const PERSISTENCE_FRAME: &str = "persistence forecast";
+
+/// Hours in a day and in a week, the seasons of hourly load.
+const DAY: usize = 24;
+const WEEK: usize = 168;
+
+/// The repeating patterns a [`HoltWinters`] model follows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Seasonality {
+    /// The shape of the day only; needs a day of history.
+    Daily,
+    /// The shape of the day and the difference between weekdays and weekends;
+    /// needs a week of history.
+    #[default]
+    Weekly,
+}
+
+impl Seasonality {
+    /// Returns the hours of history a model needs, one full longest season.
+    pub fn min_hours(self) -> usize {
+        match self {
+            Seasonality::Daily => DAY,
+            Seasonality::Weekly => WEEK,
+        }
+    }
+}
+
+impl fmt::Display for Seasonality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Seasonality::Daily => "daily",
+            Seasonality::Weekly => "weekly",
+        })
+    }
+}
+
+impl FromStr for Seasonality {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "daily" => Ok(Seasonality::Daily),
+            "weekly" => Ok(Seasonality::Weekly),
+            _ => Err(format!(
+                "unknown seasonality \"{}\", expected daily or weekly",
+                name
+            )),
+        }
+    }
+}
+
+/// Additive Holt-Winters exponential smoothing of hourly load, with a daily and
+/// optionally a weekly season (Taylor's double seasonal method), as a forecast
+/// made from the load history alone.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use mugen_core::baseline::{HoltWinters, Seasonality};
+/// use mugen_core::data::process_truth;
+/// use mugen_core::synthetic::Synthetic;
+/// use mugen_core::zone::Zone;
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let first = NaiveDate::from_ymd_opt(2023, 12, 1).unwrap();
+/// let last = NaiveDate::from_ymd_opt(2023, 12, 14).unwrap();
+/// let history = process_truth(Synthetic::default().truth(first..=last)?, Zone::NewYorkCity)?;
+/// let model = HoltWinters::fit(&history, Seasonality::Weekly)?;
+/// let forecast = model.forecast(&history, 24)?;
+/// assert_eq!(forecast.height(), 24);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HoltWinters {
+    /// Smoothing of the level, from 0 to 1.
+    pub alpha: f64,
+    /// Smoothing of the trend, from 0 to 1; 0 keeps the trend flat.
+    pub beta: f64,
+    /// Smoothing of the daily season, from 0 to 1.
+    pub gamma: f64,
+    /// Smoothing of the weekly season, from 0 to 1; unused without one.
+    pub delta: f64,
+    /// The seasons followed.
+    pub seasonality: Seasonality,
+}
+
+/// Where a model stands after the last hour of history.
+struct State {
+    level: f64,
+    trend: f64,
+    daily: Vec<f64>,
+    weekly: Vec<f64>,
+    /// Index of the next hour, counted from the first hour of history.
+    next: usize,
+    /// Sum of the squared one-step errors over the history.
+    sse: f64,
+}
+
+impl HoltWinters {
+    /// Returns a model with settings that suit hourly load, without fitting them.
+    pub fn new(seasonality: Seasonality) -> Self {
+        HoltWinters {
+            alpha: 0.2,
+            beta: 0.0,
+            gamma: 0.1,
+            delta: 0.1,
+            seasonality,
+        }
+    }
+
+    /// Fits a model to `history`, picking from a grid the smoothing settings whose
+    /// one-hour-ahead forecasts over it are closest.
+    ///
+    /// # Arguments
+    ///
+    /// * `history` - The hourly load to fit, e.g. as returned by
+    ///   [`crate::data::process_truth`].
+    /// * `seasonality` - The seasons to follow.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing the fitted model or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InvalidArgument` if `history` is shorter than
+    /// [`Seasonality::min_hours`], and an error if its time column is not a
+    /// datetime or its value column is not numeric.
+    pub fn fit(history: &TimeSeriesDataset, seasonality: Seasonality) -> Result<Self> {
+        let (_, values) = hourly(history, seasonality)?;
+        let deltas: &[f64] = match seasonality {
+            Seasonality::Daily => &[0.0],
+            Seasonality::Weekly => &[0.05, 0.1, 0.3],
+        };
+        let mut best: Option<(f64, HoltWinters)> = None;
+        for alpha in [0.05, 0.1, 0.2, 0.4, 0.6, 0.8] {
+            for beta in [0.0, 0.01, 0.05] {
+                for gamma in [0.05, 0.1, 0.3] {
+                    for delta in deltas {
+                        let model: HoltWinters = HoltWinters {
+                            alpha,
+                            beta,
+                            gamma,
+                            delta: *delta,
+                            seasonality,
+                        };
+                        let sse: f64 = model.smooth(&values).sse;
+                        if best.is_none_or(|(best, _)| sse < best) {
+                            best = Some((sse, model));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(best.map_or(HoltWinters::new(seasonality), |(_, model)| model))
+    }
+
+    /// Forecasts the `hours` hours after the last one of `history`.
+    ///
+    /// Missing hours in `history` are filled with the model's own forecast.
+    ///
+    /// # Arguments
+    ///
+    /// * `history` - The hourly load the forecast follows on from.
+    /// * `hours` - How many hours to forecast.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing the forecast over "Time Stamp" with a "Forecast"
+    ///   column, in the unit and timezone of `history`, or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InvalidArgument` if `history` is shorter than
+    /// [`Seasonality::min_hours`], and an error if its time column is not a
+    /// datetime or its value column is not numeric.
+    pub fn forecast(&self, history: &TimeSeriesDataset, hours: usize) -> Result<TimeSeriesDataset> {
+        let (last, values) = hourly(history, self.seasonality)?;
+        let state: State = self.smooth(&values);
+        let forecast: Vec<f64> = (1..=hours)
+            .map(|ahead| {
+                let season: usize = state.next + ahead - 1;
+                state.level
+                    + ahead as f64 * state.trend
+                    + state.daily[season % DAY]
+                    + state.weekly.get(season % WEEK).copied().unwrap_or(0.0)
+            })
+            .collect();
+        dataset(HOLT_WINTERS_FRAME, history, last, forecast)
+    }
+
+    /// Runs the model over `values`, hourly with gaps as NaN.
+    fn smooth(&self, values: &[f64]) -> State {
+        let season: usize = self.seasonality.min_hours();
+        let level: f64 = values[..season]
+            .iter()
+            .filter(|value| value.is_finite())
+            .sum::<f64>()
+            / values[..season]
+                .iter()
+                .filter(|value| value.is_finite())
+                .count()
+                .max(1) as f64;
+        let mut daily: Vec<f64> = vec![0.0; DAY];
+        let mut counts: Vec<usize> = vec![0; DAY];
+        for (hour, value) in values[..season].iter().enumerate() {
+            if value.is_finite() {
+                daily[hour % DAY] += value - level;
+                counts[hour % DAY] += 1;
+            }
+        }
+        for (total, count) in daily.iter_mut().zip(counts) {
+            *total /= count.max(1) as f64;
+        }
+        let mut weekly: Vec<f64> = match self.seasonality {
+            Seasonality::Daily => Vec::new(),
+            Seasonality::Weekly => values[..WEEK]
+                .iter()
+                .enumerate()
+                .map(|(hour, value)| {
+                    if value.is_finite() {
+                        value - level - daily[hour % DAY]
+                    } else {
+                        0.0
+                    }
+                })
+                .collect(),
+        };
+
+        let (mut level, mut trend, mut sse) = (level, 0.0, 0.0);
+        for (hour, value) in values.iter().enumerate() {
+            let (day, week) = (hour % DAY, hour % WEEK);
+            let weekly_now: f64 = weekly.get(week).copied().unwrap_or(0.0);
+            let expected: f64 = level + trend + daily[day] + weekly_now;
+            if !value.is_finite() {
+                level += trend;
+                continue;
+            }
+            sse += (value - expected).powi(2);
+            let previous: f64 = level;
+            level = self.alpha * (value - daily[day] - weekly_now)
+                + (1.0 - self.alpha) * (level + trend);
+            trend = self.beta * (level - previous) + (1.0 - self.beta) * trend;
+            daily[day] =
+                self.gamma * (value - level - weekly_now) + (1.0 - self.gamma) * daily[day];
+            if let Some(weekly) = weekly.get_mut(week) {
+                *weekly = self.delta * (value - level - daily[day]) + (1.0 - self.delta) * *weekly;
+            }
+        }
+        State {
+            level,
+            trend,
+            daily,
+            weekly,
+            next: values.len(),
+            sse,
+        }
+    }
+}
+
+/// Forecasts the `hours` hours after the last one of `history` as the load of the
+/// same hour a day before, or a week before with [`Seasonality::Weekly`]: the
+/// simplest baseline, which any forecast worth using should beat.
+///
+/// Hours the history lacks are left out.
+///
+/// # Arguments
+///
+/// * `history` - The hourly load the forecast follows on from.
+/// * `hours` - How many hours to forecast.
+/// * `seasonality` - Whether to repeat the last day or the last week.
+///
+/// # Returns
+///
+/// * A `Result` containing the forecast over "Time Stamp" with a "Forecast" column,
+///   in the unit and timezone of `history`, or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::InvalidArgument` if `history` is shorter than
+/// [`Seasonality::min_hours`], and an error if its time column is not a datetime
+/// or its value column is not numeric.
+pub fn persistence(
+    history: &TimeSeriesDataset,
+    hours: usize,
+    seasonality: Seasonality,
+) -> Result<TimeSeriesDataset> {
+    let (last, values) = hourly(history, seasonality)?;
+    let season: usize = seasonality.min_hours();
+    let start: usize = values.len() - season;
+    let forecast: Vec<f64> = (0..hours)
+        .map(|ahead| values[start + ahead % season])
+        .collect();
+    let forecast: TimeSeriesDataset = dataset(PERSISTENCE_FRAME, history, last, forecast)?;
+    forecast.map_frame(|frame| Ok(frame.drop_nulls::<String>(None)?))
+}
+
+/// Returns the last timestamp of `history` and its values at every hour from the
+/// first to the last timestamp, NaN where it has none.
+fn hourly(history: &TimeSeriesDataset, seasonality: Seasonality) -> Result<(i64, Vec<f64>)> {
+    let values: HashMap<i64, f64> = history.values_by_timestamp(history.value_column())?;
+    let first: Option<i64> = values.keys().min().copied();
+    let last: Option<i64> = values.keys().max().copied();
+    let hours: usize = match (first, last) {
+        (Some(first), Some(last)) => ((last - first) / HOUR_MS) as usize + 1,
+        _ => 0,
+    };
+    if hours < seasonality.min_hours() {
+        return Err(MugenError::InvalidArgument(format!(
+            "a {} model needs at least {} hours of {}, got {}",
+            seasonality,
+            seasonality.min_hours(),
+            history.name(),
+            hours
+        )));
+    }
+    let (first, last) = (first.unwrap_or_default(), last.unwrap_or_default());
+    let series: Vec<f64> = (0..hours as i64)
+        .map(|hour| {
+            values
+                .get(&(first + hour * HOUR_MS))
+                .copied()
+                .unwrap_or(f64::NAN)
+        })
+        .collect();
+    Ok((last, series))
+}
+
+/// Builds a forecast named `name` of `values` for the hours after `last`, in the
+/// unit and timezone of `history`; NaN values become nulls.
+fn dataset(
+    name: &str,
+    history: &TimeSeriesDataset,
+    last: i64,
+    values: Vec<f64>,
+) -> Result<TimeSeriesDataset> {
+    let stamps: Vec<i64> = (1..=values.len() as i64)
+        .map(|ahead| last + ahead * HOUR_MS)
+        .collect();
+    let values: Float64Chunked = values
+        .into_iter()
+        .map(|value| value.is_finite().then_some(value))
+        .collect();
+    let frame: DataFrame = DataFrame::new(vec![
+        Int64Chunked::from_vec("Time Stamp", stamps)
+            .into_datetime(TimeUnit::Milliseconds, None)
+            .into_series(),
+        values.into_series().with_name("Forecast"),
+    ])?;
+    Ok(
+        TimeSeriesDataset::new(name, frame, "Time Stamp", vec!["Forecast".to_string()])?
+            .with_unit(history.unit())
+            .with_timezone(history.timezone()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::testing::{dataset, midnight};
+
+    /// Hourly history from midnight on 1 December 2023 with `values`, leaving out
+    /// the hours that are `None`.
+    fn history(values: &[Option<f64>]) -> TimeSeriesDataset {
+        let start: i64 = midnight(2023, 12, 1);
+        let (stamps, load): (Vec<i64>, Vec<f64>) = values
+            .iter()
+            .enumerate()
+            .filter_map(|(hour, value)| Some((start + hour as i64 * HOUR_MS, (*value)?)))
+            .unzip();
+        dataset("load", stamps, vec![Series::new("Load", load)]).with_unit("MW")
+    }
+
+    /// Load that repeats the same shape every day around a flat level.
+    fn daily_shape(hour: usize) -> f64 {
+        5000.0 + 800.0 * (std::f64::consts::TAU * (hour % DAY) as f64 / DAY as f64).sin()
+    }
+
+    #[test]
+    fn holt_winters_reproduces_a_pure_seasonal_series() {
+        let values: Vec<Option<f64>> = (0..3 * DAY).map(|hour| Some(daily_shape(hour))).collect();
+        let history: TimeSeriesDataset = history(&values);
+        let model: HoltWinters = HoltWinters::fit(&history, Seasonality::Daily).unwrap();
+        let forecast: TimeSeriesDataset = model.forecast(&history, DAY).unwrap();
+        assert_eq!(forecast.unit(), "MW");
+        let predicted = forecast.values("Forecast").unwrap();
+        for (ahead, value) in predicted.into_iter().enumerate() {
+            let expected: f64 = daily_shape(3 * DAY + ahead);
+            assert!((value.unwrap() - expected).abs() < 1e-6, "hour {}", ahead);
+        }
+    }
+
+    #[test]
+    fn persistence_leaves_out_hours_missing_a_season_before() {
+        let values: Vec<Option<f64>> = (0..2 * DAY)
+            .map(|hour| (hour != DAY + 6).then_some(hour as f64))
+            .collect();
+        let history: TimeSeriesDataset = history(&values);
+        let forecast: TimeSeriesDataset = persistence(&history, DAY, Seasonality::Daily).unwrap();
+        assert_eq!(forecast.height(), DAY - 1);
+
+        let last: i64 = *history.unique_timestamps().unwrap().last().unwrap();
+        let by_hour: HashMap<i64, f64> = forecast.values_by_timestamp("Forecast").unwrap();
+        for ahead in 0..DAY {
+            let at: i64 = last + (ahead as i64 + 1) * HOUR_MS;
+            let expected: Option<f64> = (ahead != 6).then_some((DAY + ahead) as f64);
+            assert_eq!(by_hour.get(&at).copied(), expected, "hour {}", ahead);
+        }
+    }
+
+    #[test]
+    fn a_history_shorter_than_a_season_is_rejected() {
+        let values: Vec<Option<f64>> = (0..DAY - 1).map(|hour| Some(hour as f64)).collect();
+        let result = persistence(&history(&values), DAY, Seasonality::Daily);
+        assert!(matches!(result, Err(MugenError::InvalidArgument(_))));
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::archive::{archived_files, archived_path};
+use crate::checksum::{file_digest, FileDigest};
+use crate::data::read_csv_to_df;
+use crate::error::{MugenError, Result};
+use crate::zone::Zone;
+
+/// File name of the catalog kept in a data directory.
+pub const CATALOG_FILE: &str = "catalog.json";
+
+/// Formats of the "Time Stamp" column across the NYISO and weather files.
+const TIME_FORMATS: [&str; 2] = ["%m/%d/%Y %H:%M:%S", "%m/%d/%Y %H:%M"];
+
+/// One data file as it was when cataloged.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    /// The file, its size and its hash.
+    #[serde(flatten)]
+    pub file: FileDigest,
+    /// What the file holds, from the name after its date, e.g. "palIntegrated",
+    /// "isolf" or "weather".
+    pub kind: String,
+    /// Earliest "Time Stamp" in the file, or `None` if it has no rows.
+    pub first: Option<NaiveDateTime>,
+    /// Latest "Time Stamp" in the file, or `None` if it has no rows.
+    pub last: Option<NaiveDateTime>,
+    /// Zones the file holds, from its "Name" column or its column names.
+    pub zones: Vec<Zone>,
+    /// Number of rows.
+    pub rows: usize,
+    /// When the file was cataloged, in RFC 3339 format.
+    pub cataloged: String,
+    /// For a daily file deleted once its month was compacted, the monthly file its
+    /// rows are read from, whose size and hash `file` then gives.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive: Option<String>,
+}
+
+impl CatalogEntry {
+    /// Reads the file at `path` and describes it, from its month's archive if it
+    /// was deleted after compaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`read_csv_to_df`], including `MugenError::Checksum`
+    /// if the file no longer matches the hash recorded when it was downloaded.
+    pub fn of_file(path: &str) -> Result<Self> {
+        let archive: Option<String> = archive_of(path);
+        let file: FileDigest = digest(path, archive.as_deref())?;
+        Self::describe(path, file, archive)
+    }
+
+    /// Reads the file at `path` and describes it as `file`, held in `archive` if
+    /// it was deleted after compaction.
+    fn describe(path: &str, file: FileDigest, archive: Option<String>) -> Result<Self> {
+        let frame: DataFrame = read_csv_to_df(path)?;
+        let times: Vec<NaiveDateTime> = match frame.column("Time Stamp") {
+            Ok(column) => column
+                .str()?
+                .into_iter()
+                .flatten()
+                .filter_map(|stamp| {
+                    TIME_FORMATS
+                        .iter()
+                        .find_map(|format| NaiveDateTime::parse_from_str(stamp, format).ok())
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        let zones: BTreeSet<Zone> = match frame.column("Name") {
+            Ok(names) => names
+                .str()?
+                .into_iter()
+                .flatten()
+                .filter_map(|name| name.parse().ok())
+                .collect(),
+            Err(_) => frame
+                .get_column_names()
+                .into_iter()
+                .filter_map(|name| name.parse().ok())
+                .collect(),
+        };
+        Ok(CatalogEntry {
+            kind: kind(path),
+            first: times.iter().min().copied(),
+            last: times.iter().max().copied(),
+            zones: zones.into_iter().collect(),
+            rows: frame.height(),
+            cataloged: Utc::now().to_rfc3339(),
+            file,
+            archive,
+        })
+    }
+
+    /// Returns whether the file holds any time on a day from `from` to `to`,
+    /// inclusive; an open end matches everything on that side, and a file without
+    /// rows only matches when both ends are open.
+    pub fn covers(&self, from: Option<NaiveDate>, to: Option<NaiveDate>) -> bool {
+        match (self.first, self.last) {
+            (Some(first), Some(last)) => {
+                from.is_none_or(|from| last.date() >= from)
+                    && to.is_none_or(|to| first.date() <= to)
+            }
+            _ => from.is_none() && to.is_none(),
+        }
+    }
+}
+
+/// Which catalog entries [`Catalog::select`] returns; every field left `None`
+/// matches everything.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CatalogQuery {
+    /// Only files holding this zone.
+    pub zone: Option<Zone>,
+    /// Only files with data on or after this day.
+    pub from: Option<NaiveDate>,
+    /// Only files with data on or before this day.
+    pub to: Option<NaiveDate>,
+    /// Only files of this kind, e.g. "isolf", ignoring case.
+    pub kind: Option<String>,
+}
+
+/// A catalog of the data files in a directory: each file's hash, time span, zones
+/// and row count, kept as JSON next to the files so questions such as "which
+/// files hold Long Island in March" need no file to be read.
+///
+/// # Examples
+///
+/// ```no_run
+/// use mugen_core::catalog::{Catalog, CatalogQuery};
+/// use mugen_core::zone::Zone;
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let mut catalog = Catalog::load("data")?;
+/// catalog.refresh()?;
+/// catalog.save()?;
+/// let query = CatalogQuery {
+///     zone: Some(Zone::LongIsland),
+///     kind: Some("palIntegrated".to_string()),
+///     ..Default::default()
+/// };
+/// for entry in catalog.select(&query) {
+///     println!("{} {} rows", entry.file.path, entry.rows);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Catalog {
+    dir: String,
+    entries: BTreeMap<String, CatalogEntry>,
+}
+
+/// Layout of the catalog file.
+#[derive(Serialize, Deserialize)]
+struct CatalogFile {
+    files: Vec<CatalogEntry>,
+}
+
+impl Catalog {
+    /// Reads the catalog of `dir`, or starts an empty one if it has none yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InFile` if the catalog cannot be read and
+    /// `MugenError::Config` if it is not a catalog.
+    pub fn load(dir: &str) -> Result<Self> {
+        let path = catalog_path(dir);
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str::<CatalogFile>(&text)
+                .map_err(|err| MugenError::Config {
+                    path: path.clone(),
+                    message: err.to_string(),
+                })?
+                .files
+                .into_iter()
+                .map(|entry| (entry.file.path.clone(), entry))
+                .collect(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(source) => return Err(in_file(&path, source)),
+        };
+        Ok(Catalog {
+            dir: dir.to_string(),
+            entries,
+        })
+    }
+
+    /// Writes the catalog to `catalog.json` in its directory, returning the path.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::Write` if the file cannot be written.
+    pub fn save(&self) -> Result<String> {
+        let path = catalog_path(&self.dir);
+        let file = CatalogFile {
+            files: self.entries.values().cloned().collect(),
+        };
+        let text = serde_json::to_string_pretty(&file)
+            .map_err(|err| MugenError::InvalidArgument(err.to_string()))?;
+        std::fs::write(&path, text).map_err(|source| MugenError::Write {
+            path: path.clone(),
+            source,
+        })?;
+        Ok(path)
+    }
+
+    /// Brings the catalog up to date with the CSVs in its directory and the daily
+    /// files compacted into its archive, see [`crate::archive::archived_files`]:
+    /// files that are new or whose hash changed are read and cataloged again, and
+    /// entries of files that are gone are dropped.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing the paths cataloged, in name order, or a
+    ///   `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InFile` if the directory or a file cannot be read, the
+    /// errors of [`crate::archive::archived_files`] and those of
+    /// [`CatalogEntry::of_file`]; the catalog keeps the files done before the
+    /// failure.
+    pub fn refresh(&mut self) -> Result<Vec<String>> {
+        let listing = std::fs::read_dir(&self.dir).map_err(|source| in_file(&self.dir, source))?;
+        let mut paths: Vec<String> = listing
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path().to_string_lossy().into_owned())
+            .filter(|path| path.ends_with(".csv"))
+            .chain(archived_files(&self.dir)?)
+            .collect();
+        paths.sort();
+        paths.dedup();
+        self.entries
+            .retain(|path, _| paths.binary_search(path).is_ok());
+        // A month's archive holds many daily files, so it is hashed once.
+        let mut archives: BTreeMap<String, FileDigest> = BTreeMap::new();
+        let mut cataloged = Vec::new();
+        for path in paths {
+            let archive: Option<String> = archive_of(&path);
+            let file: FileDigest = match &archive {
+                Some(archive) => {
+                    if !archives.contains_key(archive) {
+                        archives.insert(archive.clone(), file_digest(archive)?);
+                    }
+                    FileDigest {
+                        path: path.clone(),
+                        ..archives[archive].clone()
+                    }
+                }
+                None => file_digest(&path)?,
+            };
+            if self
+                .entries
+                .get(&path)
+                .is_some_and(|entry| entry.file == file)
+            {
+                continue;
+            }
+            self.entries
+                .insert(path.clone(), CatalogEntry::describe(&path, file, archive)?);
+            cataloged.push(path);
+        }
+        Ok(cataloged)
+    }
+
+    /// Returns every entry matching `query`, in path order, which is date order for
+    /// files of one kind.
+    pub fn select(&self, query: &CatalogQuery) -> Vec<&CatalogEntry> {
+        self.entries
+            .values()
+            .filter(|entry| query.zone.is_none_or(|zone| entry.zones.contains(&zone)))
+            .filter(|entry| {
+                query
+                    .kind
+                    .as_ref()
+                    .is_none_or(|kind| entry.kind.eq_ignore_ascii_case(kind))
+            })
+            .filter(|entry| entry.covers(query.from, query.to))
+            .collect()
+    }
+
+    /// Returns the number of files cataloged.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether no file is cataloged.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Returns the path of the catalog of `dir`.
+fn catalog_path(dir: &str) -> String {
+    Path::new(dir)
+        .join(CATALOG_FILE)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Returns the monthly file the daily file at `path` is read from, if it was
+/// deleted after compaction.
+fn archive_of(path: &str) -> Option<String> {
+    if Path::new(path).exists() {
+        return None;
+    }
+    archived_path(path)
+}
+
+/// Hashes the file at `path`, or the monthly file `archive` holding its rows,
+/// describing either as `path`.
+fn digest(path: &str, archive: Option<&str>) -> Result<FileDigest> {
+    Ok(FileDigest {
+        path: path.to_string(),
+        ..file_digest(archive.unwrap_or(path))?
+    })
+}
+
+/// Wraps an error reading `path`.
+fn in_file(path: &str, source: std::io::Error) -> MugenError {
+    MugenError::InFile {
+        path: path.to_string(),
+        source: Box::new(source.into()),
+    }
+}
+
+/// Returns the kind of the data file at `path`: its name without the leading date
+/// and the extension, e.g. "isolf" for "data/20231209isolf.csv".
+pub(crate) fn kind(path: &str) -> String {
+    let stem = Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    stem.trim_start_matches(|c: char| c.is_ascii_digit())
+        .to_string()
+}
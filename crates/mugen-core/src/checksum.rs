@@ -0,0 +1,118 @@
+use std::fs::File;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::{MugenError, Result};
+
+/// Appended to a file's path to name the file recording its expected size and hash,
+/// e.g. "20231209isolf.csv.sha256.json".
+pub const DIGEST_SUFFIX: &str = ".sha256.json";
+
+/// A file and the hash of its contents.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileDigest {
+    /// Path of the file as it was given.
+    pub path: String,
+    /// Size in bytes.
+    pub bytes: u64,
+    /// Lowercase hex SHA-256 of the contents.
+    pub sha256: String,
+}
+
+impl FileDigest {
+    /// Describes `bytes` as the contents of `path`.
+    pub fn of_bytes(path: &str, bytes: &[u8]) -> Self {
+        FileDigest {
+            path: path.to_string(),
+            bytes: bytes.len() as u64,
+            sha256: format!("{:x}", Sha256::digest(bytes)),
+        }
+    }
+}
+
+/// Hashes the file at `path`.
+///
+/// # Errors
+///
+/// Returns `MugenError::InFile`, naming `path`, if it cannot be read.
+pub fn file_digest(path: &str) -> Result<FileDigest> {
+    let mut hasher = Sha256::new();
+    let bytes = File::open(path)
+        .and_then(|mut file| std::io::copy(&mut file, &mut hasher))
+        .map_err(|source| in_file(path, source))?;
+    Ok(FileDigest {
+        path: path.to_string(),
+        bytes,
+        sha256: format!("{:x}", hasher.finalize()),
+    })
+}
+
+/// Returns the path of the file recording the expected size and hash of `path`.
+pub fn digest_path(path: &str) -> String {
+    format!("{}{}", path, DIGEST_SUFFIX)
+}
+
+/// Records `digest` as what the file at `digest.path` must contain, so later reads
+/// can tell if it was truncated or changed.
+///
+/// # Errors
+///
+/// Returns `MugenError::Write` if the record cannot be written.
+pub fn record(digest: &FileDigest) -> Result<()> {
+    let path = digest_path(&digest.path);
+    let text = serde_json::to_string_pretty(digest)
+        .map_err(|err| MugenError::InvalidArgument(err.to_string()))?;
+    std::fs::write(&path, text).map_err(|source| MugenError::Write { path, source })
+}
+
+/// Checks the file at `path` against its recorded size and hash, if it has one.
+///
+/// Files without a record, such as ones copied in by hand, are not checked.
+///
+/// # Returns
+///
+/// * A `Result` containing whether a record was found, or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::Checksum` if the file does not match its record or the
+/// record cannot be parsed, and `MugenError::InFile` if either cannot be read.
+pub fn verify(path: &str) -> Result<bool> {
+    let record_path = digest_path(path);
+    if !Path::new(&record_path).exists() {
+        return Ok(false);
+    }
+    let text =
+        std::fs::read_to_string(&record_path).map_err(|source| in_file(&record_path, source))?;
+    let expected: FileDigest = serde_json::from_str(&text).map_err(|err| MugenError::Checksum {
+        path: path.to_string(),
+        message: format!("unreadable record \"{}\": {}", record_path, err),
+    })?;
+    let actual = file_digest(path)?;
+    if actual.bytes != expected.bytes || actual.sha256 != expected.sha256 {
+        let hint = if actual.bytes < expected.bytes {
+            "; the file looks truncated"
+        } else {
+            ""
+        };
+        return Err(MugenError::Checksum {
+            path: path.to_string(),
+            message: format!(
+                "expected {} bytes with SHA-256 {}, found {} bytes with SHA-256 {}{}; \
+                 delete it and fetch it again",
+                expected.bytes, expected.sha256, actual.bytes, actual.sha256, hint
+            ),
+        });
+    }
+    Ok(true)
+}
+
+/// Wraps the I/O error `source` of reading `path`.
+fn in_file(path: &str, source: std::io::Error) -> MugenError {
+    MugenError::InFile {
+        path: path.to_string(),
+        source: Box::new(source.into()),
+    }
+}
@@ -0,0 +1,2136 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use chrono::prelude::*;
+use polars::prelude::*;
+use tracing::instrument;
+
+use crate::archive::read_archived;
+use crate::checksum::verify;
+use crate::dataset::TimeSeriesDataset;
+use crate::error::{MugenError, Result};
+use crate::iso::{FileFormat, FileKind, Iso, IsoZone, Layout, Nyiso};
+use crate::metrics::{bias, mape, peak_error};
+use crate::resources::Resources;
+use crate::zone::Zone;
+
+/// Format of the "Time Stamp" column in NYISO's five-minute files, which matches
+/// their actual-load files.
+const TRUTH_TIME_FORMAT: &str = "%m/%d/%Y %H:%M:%S";
+
+/// Name of real-time load data in datasets and errors.
+const REALTIME_FRAME: &str = "real-time load";
+
+/// Format of the "Time Stamp" column in NYISO's hourly price files, which matches
+/// their forecast files.
+const FORECAST_TIME_FORMAT: &str = "%m/%d/%Y %H:%M";
+
+/// Name of zonal price data in datasets and errors.
+const PRICE_FRAME: &str = "price";
+
+/// Column holding the zonal price in NYISO's LBMP files.
+const LBMP_COLUMN: &str = "LBMP ($/MWHr)";
+
+/// Name of temperature data in datasets and errors.
+const WEATHER_FRAME: &str = "weather";
+
+/// Name of demand from EIA in datasets and errors.
+const DEMAND_FRAME: &str = "EIA demand";
+
+/// Reads a CSV file into a DataFrame.
+///
+/// This function reads the CSV file located at the specified `path` and returns
+/// the resulting DataFrame. It infers the schema and assumes the CSV file has a header.
+///
+/// # Arguments
+///
+/// * `path` - A string slice representing the file path to the CSV file.
+///
+/// # Returns
+///
+/// * A `Result` containing the DataFrame or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::Read`, naming `path`, if the CSV file cannot be read or parsed,
+/// and `MugenError::Checksum` if it no longer matches the size and hash recorded when
+/// it was downloaded, see [`crate::checksum::verify`].
+///
+/// A daily file deleted once its month was compacted is read back from the archive
+/// next to it, see [`crate::archive::read_archived`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use mugen_core::data::read_csv_to_df;
+///
+/// let df = read_csv_to_df("data.csv").expect("Failed to read CSV file");
+/// ```
+#[instrument(name = "load", skip_all, fields(path = %path))]
+pub fn read_csv_to_df(path: &str) -> Result<DataFrame> {
+    if !Path::new(path).exists() {
+        if let Some(df) = read_archived(path)? {
+            return Ok(df);
+        }
+    }
+    verify(path)?;
+    let read_error = |source: PolarsError| MugenError::Read {
+        path: path.to_string(),
+        source,
+    };
+    let df: DataFrame = CsvReader::from_path(path)
+        .map_err(read_error)?
+        .infer_schema(None)
+        .has_header(true)
+        .finish()
+        .map_err(read_error)?;
+    Ok(df)
+}
+
+/// Reads CSV text into a DataFrame, like [`read_csv_to_df`] reads a file, e.g. a
+/// file a browser has fetched, where there is no file system to read from.
+///
+/// # Arguments
+///
+/// * `name` - Where the text came from, e.g. "20231209palIntegrated.csv", used in
+///   errors.
+/// * `text` - The CSV text, with a header row.
+///
+/// # Returns
+///
+/// * A `Result` containing the DataFrame or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::Read`, naming `name`, if the text cannot be parsed.
+///
+/// # Examples
+///
+/// ```
+/// use mugen_core::data::read_csv_text;
+///
+/// let df = read_csv_text("inline.csv", "Name,Load\nN.Y.C.,4810.5\n").unwrap();
+/// assert_eq!(df.height(), 1);
+/// ```
+pub fn read_csv_text(name: &str, text: &str) -> Result<DataFrame> {
+    CsvReader::new(std::io::Cursor::new(text.as_bytes()))
+        .infer_schema(None)
+        .has_header(true)
+        .finish()
+        .map_err(|source| MugenError::Read {
+            path: name.to_string(),
+            source,
+        })
+}
+
+
+/// Reads multiple CSV files into a single DataFrame by vertically stacking them.
+///
+/// This function reads each CSV file specified in the `paths` vector, and vertically
+/// stacks them into a single DataFrame. Files are read in parallel, one thread per
+/// core; see [`read_multiple_csvs_with`] to limit the threads and memory.
+///
+/// # Arguments
+///
+/// * `paths` - A vector of string slices representing the file paths to the CSV files.
+///
+/// # Returns
+///
+/// * A `Result` containing the combined DataFrame or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::Read`, naming the path, if a CSV file cannot be read or
+/// parsed, and `MugenError::InFile`, naming the path, if a file's columns do not
+/// match those of the files before it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use mugen_core::data::read_multiple_csvs;
+///
+/// let paths = vec!["data1.csv", "data2.csv"];
+/// let combined_df = read_multiple_csvs(paths).expect("Failed to read and combine CSV files");
+/// ```
+pub fn read_multiple_csvs(paths: Vec<&str>) -> Result<DataFrame> {
+    read_multiple_csvs_with(paths, &Resources::default())
+}
+
+/// Reads multiple CSV files into a single DataFrame like [`read_multiple_csvs`],
+/// within `resources`.
+///
+/// Files are read in parallel on up to [`Resources::threads`] threads, or one at a
+/// time if together they are not expected to fit the memory budget.
+///
+/// # Arguments
+///
+/// * `paths` - The CSV files, stacked in this order.
+/// * `resources` - The threads and memory the read may use.
+///
+/// # Returns
+///
+/// * A `Result` containing the combined DataFrame or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns the errors of [`read_multiple_csvs`].
+#[instrument(name = "load", skip_all, fields(files = paths.len()))]
+pub fn read_multiple_csvs_with(paths: Vec<&str>, resources: &Resources) -> Result<DataFrame> {
+    let mut dfs: DataFrame = DataFrame::default();
+    let frames: Vec<DataFrame> = read_each_csv_with(&paths, resources)?;
+    for (path, df) in paths.into_iter().zip(frames) {
+        dfs.vstack_mut(&df)
+            .map_err(|source| in_file(path, source.into()))?;
+    }
+    Ok(dfs)
+}
+
+/// Reads each CSV file within `resources`, like [`read_multiple_csvs_with`] but
+/// without stacking them, e.g. to tell the files' rows apart.
+///
+/// # Errors
+///
+/// Returns the errors of [`read_csv_to_df`] for the first file that fails.
+pub(crate) fn read_each_csv_with(paths: &[&str], resources: &Resources) -> Result<Vec<DataFrame>> {
+    let workers: usize = resources.threads().min(paths.len());
+
+    if workers <= 1 || !resources.fits(paths) {
+        return paths.iter().map(|path| read_csv_to_df(path)).collect();
+    }
+
+    let chunk: usize = paths.len().div_ceil(workers);
+    let frames: Vec<Result<DataFrame>> = std::thread::scope(|scope| {
+        let readers: Vec<_> = paths
+            .chunks(chunk)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|path| read_csv_to_df(path))
+                        .collect::<Vec<Result<DataFrame>>>()
+                })
+            })
+            .collect();
+        readers
+            .into_iter()
+            .flat_map(|reader| {
+                reader
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    });
+    frames.into_iter().collect()
+}
+
+/// Scans multiple CSV files into a single lazy plan that stacks them vertically.
+///
+/// Only each file's schema is read up front; rows are read when the plan is
+/// collected, so filters and selections added to the plan run while scanning and
+/// nothing is materialized twice. Columns typed differently across files are
+/// widened to a common type.
+/// A file deleted once its month was compacted is read whole from the archive
+/// instead, like [`read_csv_to_df`] does.
+///
+/// # Arguments
+///
+/// * `paths` - The CSV files to scan, stacked in this order.
+///
+/// # Returns
+///
+/// * A `Result` containing the plan or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::Read`, naming the path, if a file cannot be opened or its
+/// schema inferred, and `MugenError::Checksum` if it fails
+/// [`crate::checksum::verify`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use mugen_core::data::{scan_csvs, truth_plan};
+/// use mugen_core::zone::Zone;
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let plan = truth_plan(scan_csvs(&["data/20231209palIntegrated.csv"])?, Zone::NewYorkCity)?;
+/// let ground_truth = plan.collect()?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn scan_csvs(paths: &[&str]) -> Result<LazyFrame> {
+    let scans: Vec<LazyFrame> = paths
+        .iter()
+        .map(|path| {
+            if !Path::new(path).exists() {
+                if let Some(df) = read_archived(path)? {
+                    return Ok(df.lazy());
+                }
+            }
+            verify(path)?;
+            LazyCsvReader::new(path)
+                .has_header(true)
+                .with_infer_schema_length(None)
+                .finish()
+                .map_err(|source| MugenError::Read {
+                    path: path.to_string(),
+                    source,
+                })
+        })
+        .collect::<Result<Vec<LazyFrame>>>()?;
+    if scans.is_empty() {
+        return Ok(DataFrame::default().lazy());
+    }
+    let args = UnionArgs {
+        to_supertypes: true,
+        ..Default::default()
+    };
+    Ok(concat(scans, args)?)
+}
+
+/// Parses a series of date strings into a millisecond datetime series named
+/// "timestamp".
+///
+/// Parsing never fails: values that are null or do not match `format` become null,
+/// as does every value of a series that does not hold text, so the result keeps one
+/// entry per input row. Check its null count to find values that did not parse.
+///
+/// # Arguments
+///
+/// * `str_val` - The date strings to convert.
+/// * `format` - The `chrono` format of the date strings.
+///
+/// # Returns
+///
+/// * A datetime `Series` as long as `str_val`, null where a value did not parse.
+pub fn str_to_datetime(str_val: &Series, format: &str) -> Series {
+    let parsed: PolarsResult<Series> = DataFrame::new(vec![str_val.clone().with_name("timestamp")])
+        .and_then(|frame| {
+            frame
+                .lazy()
+                .select([parse_time_stamp("timestamp", format)])
+                .collect()
+        })
+        .and_then(|frame| Ok(frame.column("timestamp")?.clone()));
+    match parsed {
+        Ok(parsed) if str_val.dtype() == &DataType::String => parsed,
+        _ => Series::full_null(
+            "timestamp",
+            str_val.len(),
+            &DataType::Datetime(TimeUnit::Milliseconds, None),
+        ),
+    }
+}
+
+/// Smooths a numeric series with a trailing moving average.
+///
+/// Each output value is the mean of the current value and the `window - 1` values
+/// before it. The first `window - 1` positions, and any window containing a null,
+/// are null.
+///
+/// # Arguments
+///
+/// * `values` - The numeric series to smooth, in time order.
+/// * `window` - The number of points averaged for each output value.
+///
+/// # Returns
+///
+/// * A `Result` containing the smoothed `Float64` series or a `MugenError`.
+///
+/// # Errors
+///
+/// This function will return an error if `window` is zero or `values` cannot be cast to `f64`.
+pub fn moving_average(values: &Series, window: usize) -> Result<Series> {
+    if window == 0 {
+        return Err(MugenError::InvalidArgument(
+            "moving average window must be at least 1".to_string(),
+        ));
+    }
+    let name = format!("{} (MA {})", values.name(), window);
+    let values: Vec<Option<f64>> = values.cast(&DataType::Float64)?.f64()?.into_iter().collect();
+    let averages: Float64Chunked = (0..values.len())
+        .map(|i| {
+            if i + 1 < window {
+                return None;
+            }
+            let sum: Option<f64> = values[i + 1 - window..=i].iter().copied().sum();
+            sum.map(|sum| sum / window as f64)
+        })
+        .collect();
+    Ok(averages.with_name(&name).into_series())
+}
+
+/// Downsamples a time series by keeping the extremes of each bucket.
+///
+/// The dataset is sorted by time and its timestamps split into `buckets` runs. From
+/// each run only the rows holding the minimum and maximum of the primary value
+/// column are kept, in time order, so the result has at most `2 * buckets`
+/// timestamps and retains every local peak and trough the eye would notice. A run
+/// without values keeps its first row, so gaps in the data stay gaps.
+///
+/// # Arguments
+///
+/// * `dataset` - The time series to downsample.
+/// * `buckets` - The number of buckets to split the timestamps into.
+///
+/// # Returns
+///
+/// * A `Result` containing the downsampled dataset or a `MugenError`.
+///
+/// # Errors
+///
+/// This function will return an error if sorting fails or the primary value column is
+/// not numeric.
+pub fn min_max_downsample(
+    dataset: &TimeSeriesDataset,
+    buckets: usize,
+) -> Result<TimeSeriesDataset> {
+    let mut downsampled = min_max_downsample_aligned(&[dataset], buckets)?;
+    Ok(downsampled.remove(0))
+}
+
+/// Downsamples several time series drawn on one time axis by keeping the extremes
+/// of each bucket of that axis.
+///
+/// The distinct timestamps of all `datasets` are split into `buckets` runs. Each run
+/// keeps the timestamps where any of the datasets has its minimum or maximum primary
+/// value within the run, and every dataset keeps its rows at exactly those
+/// timestamps, so the series still line up point for point. A run where no dataset
+/// has a value keeps its first timestamp, so an outage stays a gap rather than
+/// being bridged. The result has at most `2 * datasets.len() * buckets` timestamps.
+///
+/// # Arguments
+///
+/// * `datasets` - The time series to downsample.
+/// * `buckets` - The number of buckets to split the shared timestamps into.
+///
+/// # Returns
+///
+/// * A `Result` containing the downsampled datasets, sorted by time and in the order
+///   of `datasets`, or a `MugenError`.
+///
+/// # Errors
+///
+/// This function will return an error if sorting fails or a primary value column is
+/// not numeric.
+pub fn min_max_downsample_aligned(
+    datasets: &[&TimeSeriesDataset],
+    buckets: usize,
+) -> Result<Vec<TimeSeriesDataset>> {
+    let sorted: Vec<TimeSeriesDataset> = datasets
+        .iter()
+        .map(|dataset| dataset.sorted())
+        .collect::<Result<_>>()?;
+    let mut axis: Vec<i64> = Vec::new();
+    for dataset in &sorted {
+        axis.extend(dataset.unique_timestamps()?);
+    }
+    axis.sort_unstable();
+    axis.dedup();
+    if buckets == 0 || axis.len() <= 2 * buckets {
+        return Ok(sorted);
+    }
+
+    let values: Vec<HashMap<i64, f64>> = sorted
+        .iter()
+        .map(|dataset| dataset.values_by_timestamp(dataset.value_column()))
+        .collect::<Result<_>>()?;
+    let bucket_size = axis.len().div_ceil(buckets);
+
+    let mut keep: HashSet<i64> = HashSet::with_capacity(2 * datasets.len() * buckets);
+    for bucket in axis.chunks(bucket_size) {
+        let mut kept = false;
+        for values in &values {
+            let present = bucket.iter().filter_map(|dt| Some((*dt, *values.get(dt)?)));
+            let min = present.clone().min_by(|a, b| a.1.total_cmp(&b.1));
+            let max = present.max_by(|a, b| a.1.total_cmp(&b.1));
+            for (dt, _) in min.into_iter().chain(max) {
+                keep.insert(dt);
+                kept = true;
+            }
+        }
+        if !kept {
+            keep.insert(bucket[0]);
+        }
+    }
+
+    sorted
+        .iter()
+        .map(|dataset| {
+            let mask: BooleanChunked = dataset
+                .timestamps()?
+                .into_iter()
+                .map(|dt| dt.is_some_and(|dt| keep.contains(&dt)))
+                .collect();
+            dataset.map_frame(|frame| Ok(frame.filter(&mask)?))
+        })
+        .collect()
+}
+
+/// Processes the ground truth DataFrame by filtering and transforming columns.
+///
+/// This function filters the `ground_truth` DataFrame to include only rows where the "Name"
+/// column is the given zone. It then drops the columns "Time Zone", "Name", and "PTID", and
+/// converts the "Time Stamp" column from a string to a `NaiveDateTime`. The result is a
+/// dataset of "Integrated Load" in MW over "Time Stamp", in New York time.
+///
+/// # Arguments
+///
+/// * `ground_truth` - The input DataFrame containing the ground truth data.
+/// * `zone` - The zone to keep.
+///
+/// # Returns
+///
+/// * A `Result` containing the processed dataset or an error.
+///
+/// # Errors
+///
+/// This function will return an error if filtering or column transformation fails.
+#[instrument(name = "process", skip_all, fields(%zone))]
+pub fn process_truth(ground_truth: DataFrame, zone: Zone) -> Result<TimeSeriesDataset>{
+    process_load(&Nyiso, FileKind::Actual, ground_truth, &zone.into())
+}
+
+/// Builds the lazy plan behind [`process_truth`]: `zone`'s rows of `ground_truth`,
+/// with "Time Stamp" parsed by polars and "Integrated Load".
+///
+/// Nothing is read until the plan is collected, so it can be combined with
+/// [`scan_csvs`] and [`join_plan`] into one pass over the files.
+///
+/// # Arguments
+///
+/// * `ground_truth` - The raw ground truth, e.g. from [`scan_csvs`].
+/// * `zone` - The zone to keep.
+///
+/// # Returns
+///
+/// * A `Result` containing the plan or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::MissingColumn` if a column the plan reads is absent and
+/// `MugenError::ColumnType` if "Name" or "Time Stamp" is not text.
+pub fn truth_plan(ground_truth: LazyFrame, zone: Zone) -> Result<LazyFrame> {
+    load_plan(&Nyiso, FileKind::Actual, ground_truth, &zone.into())
+}
+
+/// Processes the ground truth DataFrame for several zones at once.
+///
+/// Like [`process_truth`], but keeps every row whose "Name" column matches any of
+/// `zones` and retains the "Name" column, so the result stays in long format
+/// ("Time Stamp", "Name", "Integrated Load") with one row per zone and timestamp.
+///
+/// # Arguments
+///
+/// * `ground_truth` - The input DataFrame containing the ground truth data.
+/// * `zones` - The zones to keep.
+///
+/// # Returns
+///
+/// * A `Result` containing the processed DataFrame or a `MugenError`.
+///
+/// # Errors
+///
+/// This function will return an error if filtering or column transformation fails.
+#[instrument(name = "process", skip_all, fields(zones = zones.len()))]
+pub fn process_truth_regions(
+    ground_truth: DataFrame,
+    zones: &[Zone],
+) -> Result<DataFrame> {
+    let format: FileFormat = Nyiso.format(FileKind::Actual);
+    let Layout::Long { zone_column, value_column } = format.layout else {
+        unreachable!("NYISO actual-load files have one row per zone")
+    };
+    let raw: DataFrame = ground_truth.clone();
+    let ground_truth: LazyFrame = ground_truth.lazy();
+    let schema: SchemaRef = ground_truth.schema()?;
+    require_text(&schema, "ground truth", zone_column)?;
+    require_text(&schema, "ground truth", format.time_column)?;
+    schema_type(&schema, "ground truth", value_column)?;
+    let in_zones: Expr = zones.iter().fold(lit(false), |mask, zone| {
+        mask.or(col(zone_column).eq(lit(zone.truth_name())))
+    });
+    let ground_truth_filtered: DataFrame = ground_truth
+        .filter(in_zones)
+        .select([
+            parse_time_stamp(format.time_column, format.time_format).alias("Time Stamp"),
+            col(zone_column),
+            col(value_column),
+        ])
+        .collect()?;
+    check_time_column(
+        &ground_truth_filtered,
+        &raw,
+        format.time_column,
+        "ground truth",
+        format.time_format,
+    )?;
+    Ok(ground_truth_filtered)
+}
+
+/// Processes real-time actual load, published every five minutes in NYISO's `pal`
+/// files, for one zone.
+///
+/// Like [`process_truth`], but reads the "Load" column of the real-time files. The
+/// result is a dataset of "Load" in MW over "Time Stamp", in New York time; pass it
+/// to [`resample`] to compare it with hourly data such as forecasts.
+///
+/// # Arguments
+///
+/// * `realtime` - The raw real-time load, with one row per zone and timestamp.
+/// * `zone` - The zone to keep.
+///
+/// # Returns
+///
+/// * A `Result` containing the processed dataset or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns the errors of [`realtime_plan`], or `MugenError::ColumnType` if no
+/// "Time Stamp" matches the expected format.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{Duration, NaiveDate};
+/// use mugen_core::data::{process_realtime, resample};
+/// use mugen_core::synthetic::Synthetic;
+/// use mugen_core::zone::Zone;
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let day = NaiveDate::from_ymd_opt(2023, 12, 9).unwrap();
+/// let realtime = process_realtime(Synthetic::default().realtime(day..=day)?, Zone::NewYorkCity)?;
+/// let hourly = resample(&realtime, Duration::hours(1))?;
+/// assert_eq!(hourly.height(), 24);
+/// # Ok(())
+/// # }
+/// ```
+#[instrument(name = "process", skip_all, fields(%zone))]
+pub fn process_realtime(realtime: DataFrame, zone: Zone) -> Result<TimeSeriesDataset> {
+    let filtered: DataFrame = realtime_plan(realtime.clone().lazy(), zone)?.collect()?;
+    check_time_stamps(&filtered, &realtime, REALTIME_FRAME, TRUTH_TIME_FORMAT)?;
+    let dataset = TimeSeriesDataset::new(
+        REALTIME_FRAME,
+        filtered,
+        "Time Stamp",
+        vec!["Load".to_string()],
+    )?;
+    Ok(dataset.with_unit("MW").with_timezone(Nyiso.timezone()))
+}
+
+/// Builds the lazy plan behind [`process_realtime`]: `zone`'s rows of `realtime`,
+/// with "Time Stamp" parsed by polars and "Load".
+///
+/// # Arguments
+///
+/// * `realtime` - The raw real-time load, e.g. from [`scan_csvs`].
+/// * `zone` - The zone to keep.
+///
+/// # Returns
+///
+/// * A `Result` containing the plan or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::MissingColumn` if a column the plan reads is absent and
+/// `MugenError::ColumnType` if "Name" or "Time Stamp" is not text.
+pub fn realtime_plan(realtime: LazyFrame, zone: Zone) -> Result<LazyFrame> {
+    let schema: SchemaRef = realtime.schema()?;
+    require_text(&schema, REALTIME_FRAME, "Name")?;
+    require_text(&schema, REALTIME_FRAME, "Time Stamp")?;
+    schema_type(&schema, REALTIME_FRAME, "Load")?;
+    Ok(realtime
+        .filter(col("Name").eq(lit(zone.truth_name())))
+        .select([
+            parse_time_stamp("Time Stamp", TRUTH_TIME_FORMAT),
+            col("Load"),
+        ]))
+}
+
+/// Processes a zonal price (LBMP) DataFrame, day-ahead or real-time, into `zone`'s
+/// price.
+///
+/// Keeps the rows whose "Name" is the zone and parses "Time Stamp", with or without
+/// seconds. The result is a dataset of "LBMP" in $/MWh over "Time Stamp", in New
+/// York time; resample real-time prices with [`resample`] to compare them with
+/// hourly load.
+///
+/// # Arguments
+///
+/// * `price` - The raw prices, e.g. from a `damlbmp_zone` or `realtime_zone` file.
+/// * `zone` - The zone to keep.
+///
+/// # Returns
+///
+/// * A `Result` containing the processed dataset or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns the errors of [`price_plan`], or `MugenError::ColumnType` if no
+/// "Time Stamp" matches the expected format.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use mugen_core::data::process_price;
+/// use mugen_core::synthetic::Synthetic;
+/// use mugen_core::zone::Zone;
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let day = NaiveDate::from_ymd_opt(2023, 12, 9).unwrap();
+/// let price = process_price(Synthetic::default().day_ahead_price(day..=day)?, Zone::NewYorkCity)?;
+/// assert_eq!(price.height(), 24);
+/// # Ok(())
+/// # }
+/// ```
+#[instrument(name = "process", skip_all, fields(%zone))]
+pub fn process_price(price: DataFrame, zone: Zone) -> Result<TimeSeriesDataset> {
+    let filtered: DataFrame = price_plan(price.clone().lazy(), zone)?.collect()?;
+    check_time_stamps(&filtered, &price, PRICE_FRAME, FORECAST_TIME_FORMAT)?;
+    let dataset = TimeSeriesDataset::new(
+        PRICE_FRAME,
+        filtered,
+        "Time Stamp",
+        vec!["LBMP".to_string()],
+    )?;
+    Ok(dataset.with_unit("$/MWh").with_timezone(Nyiso.timezone()))
+}
+
+/// Builds the lazy plan behind [`process_price`]: `zone`'s rows of `price`, with
+/// "Time Stamp" parsed by polars and the price as "LBMP".
+///
+/// # Arguments
+///
+/// * `price` - The raw prices, e.g. from [`scan_csvs`].
+/// * `zone` - The zone to keep.
+///
+/// # Returns
+///
+/// * A `Result` containing the plan or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::MissingColumn` if a column the plan reads is absent and
+/// `MugenError::ColumnType` if "Name" or "Time Stamp" is not text.
+pub fn price_plan(price: LazyFrame, zone: Zone) -> Result<LazyFrame> {
+    let schema: SchemaRef = price.schema()?;
+    require_text(&schema, PRICE_FRAME, "Name")?;
+    require_text(&schema, PRICE_FRAME, "Time Stamp")?;
+    schema_type(&schema, PRICE_FRAME, LBMP_COLUMN)?;
+    // Day-ahead files stamp hours without seconds; real-time ones may include them.
+    Ok(price
+        .filter(col("Name").eq(lit(zone.truth_name())))
+        .select([
+            coalesce(&[
+                parse_time_stamp("Time Stamp", FORECAST_TIME_FORMAT),
+                parse_time_stamp("Time Stamp", TRUTH_TIME_FORMAT),
+            ])
+            .alias("Time Stamp"),
+            col(LBMP_COLUMN).cast(DataType::Float64).alias("LBMP"),
+        ]))
+}
+
+/// Processes hourly temperatures, as saved by [`crate::weather::fetch_weather`],
+/// into `zone`'s temperature.
+///
+/// The result is a dataset of "Temperature" in °F over "Time Stamp", in New York
+/// time, on the same hours as the actual load.
+///
+/// # Arguments
+///
+/// * `weather` - The raw temperatures, e.g. from a `weather` file.
+/// * `zone` - The zone to keep.
+///
+/// # Returns
+///
+/// * A `Result` containing the processed dataset or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns the errors of [`weather_plan`], or `MugenError::ColumnType` if no
+/// "Time Stamp" matches the expected format.
+///
+/// # Examples
+///
+/// ```no_run
+/// use mugen_core::data::{process_weather, read_csv_to_df};
+/// use mugen_core::zone::Zone;
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let weather = read_csv_to_df("data/20231209weather.csv")?;
+/// let temperature = process_weather(weather, Zone::NewYorkCity)?;
+/// # Ok(())
+/// # }
+/// ```
+#[instrument(name = "process", skip_all, fields(%zone))]
+pub fn process_weather(weather: DataFrame, zone: Zone) -> Result<TimeSeriesDataset> {
+    let filtered: DataFrame = weather_plan(weather.clone().lazy(), zone)?.collect()?;
+    check_time_stamps(&filtered, &weather, WEATHER_FRAME, TRUTH_TIME_FORMAT)?;
+    let dataset = TimeSeriesDataset::new(
+        WEATHER_FRAME,
+        filtered,
+        "Time Stamp",
+        vec!["Temperature".to_string()],
+    )?;
+    Ok(dataset.with_unit("°F").with_timezone(Nyiso.timezone()))
+}
+
+/// Builds the lazy plan behind [`process_weather`]: `zone`'s rows of `weather`,
+/// with "Time Stamp" parsed by polars and "Temperature".
+///
+/// # Arguments
+///
+/// * `weather` - The raw temperatures, e.g. from [`scan_csvs`].
+/// * `zone` - The zone to keep.
+///
+/// # Returns
+///
+/// * A `Result` containing the plan or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::MissingColumn` if a column the plan reads is absent and
+/// `MugenError::ColumnType` if "Name" or "Time Stamp" is not text.
+pub fn weather_plan(weather: LazyFrame, zone: Zone) -> Result<LazyFrame> {
+    let schema: SchemaRef = weather.schema()?;
+    require_text(&schema, WEATHER_FRAME, "Name")?;
+    require_text(&schema, WEATHER_FRAME, "Time Stamp")?;
+    schema_type(&schema, WEATHER_FRAME, "Temperature")?;
+    Ok(weather
+        .filter(col("Name").eq(lit(zone.truth_name())))
+        .select([
+            parse_time_stamp("Time Stamp", TRUTH_TIME_FORMAT),
+            col("Temperature").cast(DataType::Float64),
+        ]))
+}
+
+/// Processes hourly demand from EIA, as saved by [`crate::eia::fetch_demand`], into
+/// `respondent`'s demand.
+///
+/// The result is a dataset of "Demand" in MW over "Time Stamp", at the start of each
+/// hour in the market's time, on the same hours as the actual load.
+///
+/// # Arguments
+///
+/// * `demand` - The raw demand, e.g. from an `eia_nyis` file.
+/// * `respondent` - The balancing authority to keep, e.g. "NYIS".
+///
+/// # Returns
+///
+/// * A `Result` containing the processed dataset or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns the errors of [`demand_plan`], or `MugenError::ColumnType` if no
+/// "Time Stamp" matches the expected format.
+///
+/// # Examples
+///
+/// ```no_run
+/// use mugen_core::data::{process_demand, read_csv_to_df};
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let demand = process_demand(read_csv_to_df("data/20231209eia_nyis.csv")?, "NYIS")?;
+/// # Ok(())
+/// # }
+/// ```
+#[instrument(name = "process", skip_all, fields(%respondent))]
+pub fn process_demand(demand: DataFrame, respondent: &str) -> Result<TimeSeriesDataset> {
+    let filtered: DataFrame = demand_plan(demand.clone().lazy(), respondent)?.collect()?;
+    check_time_stamps(&filtered, &demand, DEMAND_FRAME, TRUTH_TIME_FORMAT)?;
+    let dataset = TimeSeriesDataset::new(
+        DEMAND_FRAME,
+        filtered,
+        "Time Stamp",
+        vec!["Demand".to_string()],
+    )?;
+    Ok(dataset.with_unit("MW").with_timezone(Nyiso.timezone()))
+}
+
+/// Builds the lazy plan behind [`process_demand`]: `respondent`'s rows of `demand`,
+/// with "Time Stamp" parsed by polars and "Demand".
+///
+/// # Arguments
+///
+/// * `demand` - The raw demand, e.g. from [`scan_csvs`].
+/// * `respondent` - The balancing authority to keep.
+///
+/// # Returns
+///
+/// * A `Result` containing the plan or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::MissingColumn` if a column the plan reads is absent and
+/// `MugenError::ColumnType` if "Respondent" or "Time Stamp" is not text.
+pub fn demand_plan(demand: LazyFrame, respondent: &str) -> Result<LazyFrame> {
+    let schema: SchemaRef = demand.schema()?;
+    require_text(&schema, DEMAND_FRAME, "Respondent")?;
+    require_text(&schema, DEMAND_FRAME, "Time Stamp")?;
+    schema_type(&schema, DEMAND_FRAME, "Demand")?;
+    Ok(demand
+        .filter(col("Respondent").eq(lit(respondent)))
+        .select([
+            parse_time_stamp("Time Stamp", TRUTH_TIME_FORMAT),
+            col("Demand").cast(DataType::Float64),
+        ]))
+}
+
+/// Sums the actual load of every zone into NYISO's total at each timestamp, to
+/// compare with system-wide figures such as EIA's demand.
+///
+/// A zone with two readings at one timestamp, as in the hour repeated when clocks
+/// fall back, counts with their mean.
+///
+/// # Arguments
+///
+/// * `ground_truth` - The raw ground truth, with one row per zone and timestamp.
+///
+/// # Returns
+///
+/// * A `Result` containing a dataset of "Integrated Load" in MW, sorted by time, or
+///   a `MugenError`.
+///
+/// # Errors
+///
+/// Returns the errors of [`process_truth_regions`].
+#[instrument(name = "process", skip_all)]
+pub fn total_load(ground_truth: DataFrame) -> Result<TimeSeriesDataset> {
+    let zones: Vec<Zone> = zones_in(&ground_truth)?;
+    let total: DataFrame = process_truth_regions(ground_truth, &zones)?
+        .lazy()
+        .group_by([col("Time Stamp"), col("Name")])
+        .agg([col("Integrated Load").cast(DataType::Float64).mean()])
+        .group_by([col("Time Stamp")])
+        .agg([col("Integrated Load").sum()])
+        .sort(["Time Stamp"], SortMultipleOptions::default())
+        .collect()?;
+    let dataset = TimeSeriesDataset::new(
+        "total load",
+        total,
+        "Time Stamp",
+        vec!["Integrated Load".to_string()],
+    )?;
+    Ok(dataset.with_unit("MW").with_timezone(Nyiso.timezone()))
+}
+
+/// Averages every value column of `dataset` over consecutive periods of length
+/// `every`, e.g. to put five-minute real-time load on the hourly grid of forecasts
+/// and integrated load.
+///
+/// Periods are aligned to the Unix epoch, so hourly periods start on the hour and
+/// daily ones at midnight, and each row is labeled with the start of its period.
+/// Periods without rows are left out.
+///
+/// # Arguments
+///
+/// * `dataset` - The time series to resample.
+/// * `every` - The length of a period.
+///
+/// # Returns
+///
+/// * A `Result` containing the resampled dataset, sorted by time, or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::InvalidArgument` if `every` is not a positive whole number
+/// of milliseconds, and an error if a value column is not numeric.
+pub fn resample(dataset: &TimeSeriesDataset, every: chrono::Duration) -> Result<TimeSeriesDataset> {
+    let step: i64 = every.num_milliseconds();
+    if step <= 0 {
+        return Err(MugenError::InvalidArgument(format!(
+            "cannot resample {} to periods of {} ms; use a positive period",
+            dataset.name(),
+            step
+        )));
+    }
+    let time: &str = dataset.time_column();
+    let millis: Expr = col(time)
+        .cast(DataType::Datetime(TimeUnit::Milliseconds, None))
+        .cast(DataType::Int64);
+    let means: Vec<Expr> = dataset
+        .value_columns()
+        .iter()
+        .map(|name| col(name).cast(DataType::Float64).mean())
+        .collect();
+    dataset.map_frame(|frame| {
+        Ok(frame
+            .clone()
+            .lazy()
+            .with_column(
+                (millis.clone() - millis.clone() % lit(step))
+                    .cast(DataType::Datetime(TimeUnit::Milliseconds, None))
+                    .alias(time),
+            )
+            .group_by([col(time)])
+            .agg(means)
+            .sort([time], SortMultipleOptions::default())
+            .collect()?)
+    })
+}
+
+/// Processes the prediction DataFrame by filtering and transforming columns.
+///
+/// This function filters the `pred` DataFrame to include only the "Time Stamp" column
+/// and the forecast column of `zone`. It then converts the "Time Stamp" column from a
+/// string to a `NaiveDateTime`. The result is a dataset of the zone's forecast in MW
+/// over "Time Stamp", in New York time.
+///
+/// # Arguments
+///
+/// * `pred` - The input DataFrame containing the prediction data.
+/// * `zone` - The zone whose forecast column is kept.
+///
+/// # Returns
+///
+/// * A `Result` containing the processed dataset or a `MugenError`.
+///
+/// # Errors
+///
+/// This function will return an error if the selection or column transformation fails.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use mugen_core::data::process_pred;
+/// use mugen_core::synthetic::Synthetic;
+/// use mugen_core::zone::Zone;
+///
+/// let day = NaiveDate::from_ymd_opt(2023, 12, 9).unwrap();
+/// let df = Synthetic::default().forecast(day..=day).expect("Failed to generate the forecast");
+/// let processed_df = process_pred(df, Zone::NewYorkCity).expect("Processing failed");
+/// ```
+#[instrument(name = "process", skip_all, fields(%zone))]
+pub fn process_pred(pred: DataFrame, zone: Zone) -> Result<TimeSeriesDataset>{
+    process_load(&Nyiso, FileKind::Forecast, pred, &zone.into())
+}
+
+/// Builds the lazy plan behind [`process_pred`]: "Time Stamp", parsed by polars, and
+/// the forecast column of `zone`.
+///
+/// # Arguments
+///
+/// * `pred` - The raw prediction, e.g. from [`scan_csvs`].
+/// * `zone` - The zone whose forecast column is kept.
+///
+/// # Returns
+///
+/// * A `Result` containing the plan or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::MissingColumn` if a column the plan reads is absent and
+/// `MugenError::ColumnType` if "Time Stamp" is not text.
+pub fn pred_plan(pred: LazyFrame, zone: Zone) -> Result<LazyFrame> {
+    load_plan(&Nyiso, FileKind::Forecast, pred, &zone.into())
+}
+
+/// Processes one zone's load from a file of any market, as [`process_truth`] and
+/// [`process_pred`] do for NYISO.
+///
+/// The result is a dataset over "Time Stamp", parsed with the market's format, of
+/// the column holding the zone's load, in MW and in the market's timezone.
+///
+/// # Arguments
+///
+/// * `iso` - The market the file comes from.
+/// * `kind` - Whether the file holds actual load or a forecast.
+/// * `raw` - The file as read, e.g. by [`read_csv_to_df`].
+/// * `zone` - The zone to keep, e.g. from [`Iso::zone`].
+///
+/// # Returns
+///
+/// * A `Result` containing the processed dataset or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns the errors of [`load_plan`], and `MugenError::ColumnType` if no
+/// timestamp matches the market's format.
+///
+/// # Examples
+///
+/// ```
+/// use mugen_core::data::{process_load, read_csv_text};
+/// use mugen_core::iso::{Ercot, FileKind, Iso};
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let raw = read_csv_text(
+///     "ERCOT actual load",
+///     "OperDay,HourEnding,COAST,FAR_WEST\n\
+///      12/09/2023,01:00,11250.5,6120.1\n\
+///      12/09/2023,24:00,10980.2,6301.7\n",
+/// )?;
+/// let coast = process_load(&Ercot, FileKind::Actual, raw, &Ercot.zone("coast")?)?;
+/// assert_eq!(coast.timezone(), "America/Chicago");
+/// // Hour endings 01:00 and 24:00 are the hours starting at 00:00 and 23:00.
+/// let hour: i64 = 3_600_000;
+/// let stamps: Vec<i64> = coast.unique_timestamps()?;
+/// assert_eq!(stamps[1] - stamps[0], 23 * hour);
+/// assert_eq!(stamps[0] % (24 * hour), 0);
+/// # Ok(())
+/// # }
+/// ```
+#[instrument(name = "process", skip_all, fields(iso = iso.name(), %zone))]
+pub fn process_load(
+    iso: &dyn Iso,
+    kind: FileKind,
+    raw: DataFrame,
+    zone: &IsoZone,
+) -> Result<TimeSeriesDataset> {
+    let format: FileFormat = iso.format(kind);
+    let filtered: DataFrame = load_plan(iso, kind, raw.clone().lazy(), zone)?.collect()?;
+    check_time_column(&filtered, &raw, format.time_column, kind.frame(), format.time_format)?;
+    let dataset = TimeSeriesDataset::new(
+        kind.frame(),
+        filtered,
+        "Time Stamp",
+        vec![format.value_column(zone, kind)],
+    )?;
+    Ok(dataset.with_unit("MW").with_timezone(iso.timezone()))
+}
+
+/// Builds the lazy plan behind [`process_load`]: "Time Stamp", parsed by polars
+/// from the market's time column, and the column holding `zone`'s load, keeping
+/// only `zone`'s rows of files with one row per zone.
+///
+/// # Arguments
+///
+/// * `iso` - The market the file comes from.
+/// * `kind` - Whether the file holds actual load or a forecast.
+/// * `raw` - The raw file, e.g. from [`scan_csvs`].
+/// * `zone` - The zone to keep.
+///
+/// # Returns
+///
+/// * A `Result` containing the plan or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::MissingColumn` if a column the plan reads is absent and
+/// `MugenError::ColumnType` if the time or zone column is not text.
+pub fn load_plan(
+    iso: &dyn Iso,
+    kind: FileKind,
+    raw: LazyFrame,
+    zone: &IsoZone,
+) -> Result<LazyFrame> {
+    let format: FileFormat = iso.format(kind);
+    let frame: &str = kind.frame();
+    let value_column: String = format.value_column(zone, kind);
+    let schema: SchemaRef = raw.schema()?;
+    let raw: LazyFrame = match format.layout {
+        Layout::Long { zone_column, .. } => {
+            require_text(&schema, frame, zone_column)?;
+            raw.filter(col(zone_column).eq(lit(zone.name(kind))))
+        }
+        Layout::Wide { .. } => raw,
+    };
+    require_text(&schema, frame, format.time_column)?;
+    if let Some(hour_column) = format.hour_column {
+        schema_type(&schema, frame, hour_column)?;
+    }
+    schema_type(&schema, frame, &value_column)?;
+    Ok(raw.select([file_time_stamp(&format), col(&value_column)]))
+}
+
+/// Parses the timestamps of a file laid out as `format` into a "Time Stamp" column
+/// of millisecond datetimes, from the hour ending and date when they are apart.
+fn file_time_stamp(format: &FileFormat) -> Expr {
+    let Some(hour_column) = format.hour_column else {
+        return parse_time_stamp(format.time_column, format.time_format).alias("Time Stamp");
+    };
+    let day: Expr = col(format.time_column)
+        .str()
+        .to_date(StrptimeOptions {
+            format: Some(format.time_format.to_string()),
+            strict: false,
+            exact: true,
+            cache: true,
+        })
+        .cast(DataType::Datetime(TimeUnit::Milliseconds, None))
+        .cast(DataType::Int64);
+    let hour_ending: Expr = col(hour_column)
+        .cast(DataType::String)
+        .str()
+        .extract(lit(r"^\s*(\d+)"), 1)
+        .cast(DataType::Int64);
+    (day + (hour_ending - lit(1i64)) * lit(3_600_000i64))
+        .cast(DataType::Datetime(TimeUnit::Milliseconds, None))
+        .alias("Time Stamp")
+}
+
+/// Aligns actuals with forecasts and computes the residual at each timestamp.
+///
+/// This function inner-joins the primary values of the ground truth and prediction
+/// datasets on their timestamps, so only timestamps present in both are kept, and
+/// returns a dataset with the columns "Time Stamp", "Actual", "Forecast" and
+/// "Residual" (actual minus forecast), sorted by time.
+///
+/// # Arguments
+///
+/// * `truth` - The processed ground truth, as returned by `process_truth`.
+/// * `pred` - The processed prediction, as returned by `process_pred`.
+///
+/// # Returns
+///
+/// * A `Result` containing the joined dataset or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::InvalidArgument` if the two datasets are in different
+/// timezones, since their timestamps would be compared as if they were not. This
+/// function will also return an error if a value column is not numeric or the join
+/// fails.
+#[instrument(name = "join", skip_all)]
+pub fn join_actual_forecast(
+    truth: &TimeSeriesDataset,
+    pred: &TimeSeriesDataset,
+) -> Result<TimeSeriesDataset> {
+    same_timezone(truth, pred)?;
+    let plan = |dataset: &TimeSeriesDataset| -> Result<LazyFrame> {
+        let values: &Series = column(dataset.frame(), dataset.name(), dataset.value_column())?;
+        if !values.dtype().is_numeric() {
+            return Err(column_type(
+                dataset.name(),
+                dataset.value_column(),
+                "numeric",
+                values.dtype(),
+            ));
+        }
+        Ok(dataset.frame().clone().lazy().select([
+            col(dataset.time_column()).alias("Time Stamp"),
+            col(dataset.value_column()),
+        ]))
+    };
+    let joined: DataFrame = join_plan(
+        plan(truth)?,
+        truth.value_column(),
+        plan(pred)?,
+        pred.value_column(),
+    )
+    .collect()?;
+    let dataset: TimeSeriesDataset = joined_dataset(joined)?;
+    Ok(dataset.with_unit(truth.unit()).with_timezone(truth.timezone()))
+}
+
+/// Aligns two forecasts of the same zone and computes how far the second moved from
+/// the first at each timestamp, e.g. to compare a forecast with its update.
+///
+/// This function inner-joins the primary values of both datasets on their
+/// timestamps, so hours only one forecast covers are dropped, and returns a dataset
+/// with the columns "Time Stamp", "First", "Second" and "Delta" (second minus
+/// first), sorted by time.
+///
+/// # Arguments
+///
+/// * `first` - The forecast compared against, as returned by `process_pred`.
+/// * `second` - The forecast compared, as returned by `process_pred`.
+///
+/// # Returns
+///
+/// * A `Result` containing the compared dataset or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::InvalidArgument` if the two datasets are in different
+/// timezones, since their timestamps would be compared as if they were not. This
+/// function will also return an error if a value column is not numeric or the join
+/// fails.
+#[instrument(name = "join", skip_all)]
+pub fn compare_forecasts(
+    first: &TimeSeriesDataset,
+    second: &TimeSeriesDataset,
+) -> Result<TimeSeriesDataset> {
+    same_timezone(first, second)?;
+    let plan = |dataset: &TimeSeriesDataset, alias: &str| -> Result<LazyFrame> {
+        f64_column(dataset.frame(), dataset.name(), dataset.value_column())?;
+        Ok(dataset.frame().clone().lazy().select([
+            col(dataset.time_column()).alias("Time Stamp"),
+            col(dataset.value_column()).cast(DataType::Float64).alias(alias),
+        ]))
+    };
+    let compared: DataFrame = plan(first, "First")?
+        .inner_join(plan(second, "Second")?, col("Time Stamp"), col("Time Stamp"))
+        .sort(["Time Stamp"], SortMultipleOptions::default())
+        .with_column((col("Second") - col("First")).alias("Delta"))
+        .collect()?;
+    let value_columns: Vec<String> = ["First", "Second", "Delta"]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    let dataset: TimeSeriesDataset =
+        TimeSeriesDataset::new("forecast comparison", compared, "Time Stamp", value_columns)?;
+    Ok(dataset.with_unit(first.unit()).with_timezone(first.timezone()))
+}
+
+/// Aligns the load a market reported with the demand EIA publishes for it, to catch
+/// hours where the two disagree.
+///
+/// This function inner-joins the primary values of both datasets on their
+/// timestamps, averaging a timestamp that appears twice, as in the hour repeated
+/// when clocks fall back, and returns a dataset with the columns "Time Stamp",
+/// "Reported", "EIA", "Difference" (EIA minus reported) and "Percent" (the
+/// difference in percent of the reported load), sorted by time.
+///
+/// # Arguments
+///
+/// * `reported` - The market's load, e.g. from [`total_load`].
+/// * `eia` - EIA's demand, as returned by [`process_demand`].
+///
+/// # Returns
+///
+/// * A `Result` containing the compared dataset or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::InvalidArgument` if the two datasets are in different
+/// timezones, since their timestamps would be compared as if they were not. This
+/// function will also return an error if a value column is not numeric or the join
+/// fails.
+#[instrument(name = "join", skip_all)]
+pub fn compare_demand(
+    reported: &TimeSeriesDataset,
+    eia: &TimeSeriesDataset,
+) -> Result<TimeSeriesDataset> {
+    same_timezone(reported, eia)?;
+    let plan = |dataset: &TimeSeriesDataset, alias: &str| -> Result<LazyFrame> {
+        f64_column(dataset.frame(), dataset.name(), dataset.value_column())?;
+        Ok(dataset
+            .frame()
+            .clone()
+            .lazy()
+            .group_by([col(dataset.time_column()).alias("Time Stamp")])
+            .agg([col(dataset.value_column()).cast(DataType::Float64).mean().alias(alias)]))
+    };
+    let compared: DataFrame = plan(reported, "Reported")?
+        .inner_join(plan(eia, "EIA")?, col("Time Stamp"), col("Time Stamp"))
+        .sort(["Time Stamp"], SortMultipleOptions::default())
+        .with_column((col("EIA") - col("Reported")).alias("Difference"))
+        .with_column((lit(100.0) * col("Difference") / col("Reported")).alias("Percent"))
+        .collect()?;
+    let value_columns: Vec<String> = ["Reported", "EIA", "Difference", "Percent"]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    let dataset: TimeSeriesDataset =
+        TimeSeriesDataset::new("demand comparison", compared, "Time Stamp", value_columns)?;
+    Ok(dataset.with_unit(reported.unit()).with_timezone(reported.timezone()))
+}
+
+/// Checks that `first` and `second` are in the same timezone before their naive
+/// timestamps are joined.
+///
+/// # Errors
+///
+/// Returns `MugenError::InvalidArgument` naming both timezones if they differ.
+fn same_timezone(first: &TimeSeriesDataset, second: &TimeSeriesDataset) -> Result<()> {
+    if first.timezone() == second.timezone() {
+        return Ok(());
+    }
+    Err(MugenError::InvalidArgument(format!(
+        "{} is in {} but {} is in {}; convert them to one timezone first",
+        first.name(),
+        first.timezone(),
+        second.name(),
+        second.timezone()
+    )))
+}
+
+/// Builds the lazy plan behind [`join_actual_forecast`] from two plans with a parsed
+/// "Time Stamp" column, such as those of [`truth_plan`] and [`pred_plan`].
+///
+/// `truth_column` becomes "Actual" and `pred_column` "Forecast", both as `f64`.
+///
+/// # Arguments
+///
+/// * `truth` - The ground truth plan.
+/// * `truth_column` - The column of `truth` holding the actual values.
+/// * `pred` - The prediction plan.
+/// * `pred_column` - The column of `pred` holding the forecast values.
+///
+/// # Returns
+///
+/// * The plan of the "Time Stamp", "Actual", "Forecast" and "Residual" columns,
+///   sorted by time.
+pub fn join_plan(
+    truth: LazyFrame,
+    truth_column: &str,
+    pred: LazyFrame,
+    pred_column: &str,
+) -> LazyFrame {
+    let actual: LazyFrame = truth.select([
+        col("Time Stamp"),
+        col(truth_column).cast(DataType::Float64).alias("Actual"),
+    ]);
+    let forecast: LazyFrame = pred.select([
+        col("Time Stamp"),
+        col(pred_column).cast(DataType::Float64).alias("Forecast"),
+    ]);
+    actual
+        .inner_join(forecast, col("Time Stamp"), col("Time Stamp"))
+        .sort(["Time Stamp"], SortMultipleOptions::default())
+        .with_column((col("Actual") - col("Forecast")).alias("Residual"))
+}
+
+/// Loads `zone`'s actual load joined against its forecast straight from the files,
+/// as one lazy plan that is collected once.
+///
+/// Gives the same dataset as reading and processing both inputs and calling
+/// [`join_actual_forecast`], but only the zone's rows and columns are ever
+/// materialized. See [`load_joined_with`] to load within a memory budget.
+///
+/// # Arguments
+///
+/// * `truth_paths` - The actual-load CSVs, in any order.
+/// * `forecast_path` - The forecast CSV.
+/// * `zone` - The zone to load.
+///
+/// # Returns
+///
+/// * A `Result` containing the joined dataset, as returned by
+///   [`join_actual_forecast`], or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns the errors of [`scan_csvs`], [`truth_plan`] and [`pred_plan`], or an
+/// error if the files cannot be read while collecting. Errors caused by one file
+/// are returned as `MugenError::InFile`, naming it.
+pub fn load_joined(
+    truth_paths: &[&str],
+    forecast_path: &str,
+    zone: Zone,
+) -> Result<TimeSeriesDataset> {
+    load_joined_with(truth_paths, forecast_path, zone, &Resources::default())
+}
+
+/// Loads `zone`'s actual load joined against its forecast like [`load_joined`],
+/// within `resources`: if the files are not expected to fit the memory budget, the
+/// plan runs in streaming mode, in batches.
+///
+/// # Arguments
+///
+/// * `truth_paths` - The actual-load CSVs, in any order.
+/// * `forecast_path` - The forecast CSV.
+/// * `zone` - The zone to load.
+/// * `resources` - The memory the load may use.
+///
+/// # Returns
+///
+/// * A `Result` containing the joined dataset or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns the errors of [`load_joined`].
+#[instrument(name = "load", skip_all, fields(%zone))]
+pub fn load_joined_with(
+    truth_paths: &[&str],
+    forecast_path: &str,
+    zone: Zone,
+    resources: &Resources,
+) -> Result<TimeSeriesDataset> {
+    let paths: Vec<&str> = truth_paths.iter().copied().chain([forecast_path]).collect();
+    let streaming: bool = !resources.fits(&paths);
+    let collect = || -> Result<DataFrame> {
+        let truth: LazyFrame = truth_plan(scan_csvs(truth_paths)?, zone)?;
+        let pred: LazyFrame = pred_plan(scan_csvs(&[forecast_path])?, zone)?;
+        let zone: IsoZone = zone.into();
+        let truth_column: String =
+            Nyiso.format(FileKind::Actual).value_column(&zone, FileKind::Actual);
+        let pred_column: String =
+            Nyiso.format(FileKind::Forecast).value_column(&zone, FileKind::Forecast);
+        Ok(join_plan(truth, &truth_column, pred, &pred_column)
+            .with_streaming(streaming)
+            .collect()?)
+    };
+    let joined: DataFrame =
+        collect().map_err(|err| locate_error(err, truth_paths, forecast_path, zone))?;
+    let dataset: TimeSeriesDataset = joined_dataset(joined)?;
+    Ok(dataset.with_unit("MW").with_timezone(Nyiso.timezone()))
+}
+
+/// Computes each zone's share of the total load at a single timestamp.
+///
+/// This function keeps the ground truth rows whose "Time Stamp" equals `at` and
+/// returns the columns "Name", "Integrated Load" and "Share", where "Share" is the
+/// zone's fraction of the summed load of all zones at that moment.
+///
+/// # Arguments
+///
+/// * `ground_truth` - The raw ground truth DataFrame, with one row per zone and timestamp.
+/// * `at` - The timestamp to compute the shares for.
+///
+/// # Returns
+///
+/// * A `Result` containing the per-zone DataFrame or a `MugenError`.
+///
+/// # Errors
+///
+/// This function will return an error if the columns cannot be processed, and
+/// `MugenError::NoData` if no rows match `at` or their loads sum to zero, which
+/// leaves no shares to take.
+pub fn zone_shares_at(
+    ground_truth: DataFrame,
+    at: NaiveDateTime,
+) -> Result<DataFrame> {
+    let mut ground_truth: DataFrame = ground_truth;
+    parse_time_stamps(&mut ground_truth, "ground truth", TRUTH_TIME_FORMAT)?;
+
+    let target: i64 = at.and_utc().timestamp_millis();
+    let mask: BooleanChunked = timestamp_column(&ground_truth, "ground truth")?
+        .into_iter()
+        .map(|dt| dt == Some(target))
+        .collect();
+    let mut shares: DataFrame = ground_truth
+        .filter(&mask)?
+        .select(["Name", "Integrated Load"])?;
+    if shares.height() == 0 {
+        return Err(MugenError::NoData(format!(
+            "no ground truth rows at {}",
+            at.format("%m/%d/%Y %H:%M")
+        )));
+    }
+
+    let loads: Float64Chunked = f64_column(&shares, "ground truth", "Integrated Load")?;
+    let total: f64 = loads.sum().unwrap_or(0.0);
+    if total == 0.0 {
+        return Err(MugenError::NoData(format!(
+            "the zones' load sums to zero at {}",
+            at.format("%m/%d/%Y %H:%M")
+        )));
+    }
+    let share: Series = (&loads / total).into_series().with_name("Share");
+    shares.with_column(share)?;
+    Ok(shares)
+}
+
+/// Lists the zones present in the "Name" column of the ground truth.
+///
+/// # Arguments
+///
+/// * `ground_truth` - Raw or long-format ground truth, with one row per zone and timestamp.
+///
+/// # Returns
+///
+/// * A `Result` containing the distinct zones, sorted, or a `MugenError`.
+///
+/// # Errors
+///
+/// This function will return an error if "Name" is missing or not text, or holds a name
+/// that is not a NYISO zone.
+pub fn zones_in(ground_truth: &DataFrame) -> Result<Vec<Zone>> {
+    let mut zones: Vec<Zone> = str_column(ground_truth, "ground truth", "Name")?
+        .into_iter()
+        .flatten()
+        .map(str::parse)
+        .collect::<Result<Vec<Zone>>>()?;
+    zones.sort();
+    zones.dedup();
+    Ok(zones)
+}
+
+/// Keeps the rows whose timestamp falls on a day between `from` and `to`, inclusive.
+///
+/// # Arguments
+///
+/// * `dataset` - A processed time series.
+/// * `from` - The first day to keep.
+/// * `to` - The last day to keep.
+///
+/// # Returns
+///
+/// * A `Result` containing the filtered dataset or a `MugenError`.
+///
+/// # Errors
+///
+/// This function will return an error if filtering fails.
+pub fn filter_period(
+    dataset: TimeSeriesDataset,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<TimeSeriesDataset> {
+    let start: i64 = from.and_time(NaiveTime::MIN).and_utc().timestamp_millis();
+    let end: i64 = to
+        .succ_opt()
+        .unwrap_or(NaiveDate::MAX)
+        .and_time(NaiveTime::MIN)
+        .and_utc()
+        .timestamp_millis();
+    let mask: BooleanChunked = dataset
+        .timestamps()?
+        .into_iter()
+        .map(|dt| dt.is_some_and(|dt| dt >= start && dt < end))
+        .collect();
+    dataset.map_frame(|frame| Ok(frame.filter(&mask)?))
+}
+
+/// Converts the local times in `column` of `frame` from the timezone `from` to the
+/// timezone `to`, both IANA names such as "UTC" or "America/New_York", e.g. to put
+/// a store kept in UTC next to files in New York time.
+///
+/// A time repeated when clocks fall back is taken as its first occurrence, and a
+/// time skipped when they spring forward as the hour after it.
+///
+/// # Arguments
+///
+/// * `frame` - The data, with any number of rows per timestamp.
+/// * `frame_name` - What the data is, used in errors.
+/// * `column` - The datetime column to convert.
+/// * `from` - The timezone the times are in.
+/// * `to` - The timezone to convert them to.
+///
+/// # Returns
+///
+/// * A `Result` containing `frame` with `column` converted, or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::InvalidArgument` if a timezone is unknown, and
+/// `MugenError::MissingColumn` or `MugenError::ColumnType` if `column` is missing or
+/// not a datetime.
+pub fn convert_timezone(
+    frame: &DataFrame,
+    frame_name: &str,
+    column: &str,
+    from: &str,
+    to: &str,
+) -> Result<DataFrame> {
+    let parse = |name: &str| -> Result<chrono_tz::Tz> {
+        name.parse()
+            .map_err(|_| MugenError::InvalidArgument(format!("unknown timezone \"{}\"", name)))
+    };
+    let (source, target) = (parse(from)?, parse(to)?);
+    let times: &DatetimeChunked = datetime_column(frame, frame_name, column)?;
+    if source == target {
+        return Ok(frame.clone());
+    }
+    let convert = |ms: i64| -> Option<i64> {
+        let local: NaiveDateTime = DateTime::from_timestamp_millis(ms)?.naive_utc();
+        let instant: DateTime<Utc> = chrono::TimeZone::from_local_datetime(&source, &local)
+            .earliest()
+            .or_else(|| {
+                let later: NaiveDateTime = local + chrono::Duration::hours(1);
+                chrono::TimeZone::from_local_datetime(&source, &later).earliest()
+            })?
+            .with_timezone(&Utc);
+        Some(
+            instant
+                .with_timezone(&target)
+                .naive_local()
+                .and_utc()
+                .timestamp_millis(),
+        )
+    };
+    let converted: Series = times
+        .into_iter()
+        .map(|ms| ms.and_then(convert))
+        .collect::<Int64Chunked>()
+        .into_datetime(TimeUnit::Milliseconds, None)
+        .into_series()
+        .with_name(column);
+    let mut frame: DataFrame = frame.clone();
+    frame.replace(column, converted)?;
+    Ok(frame)
+}
+
+/// Scores the forecast for every zone that appears in both inputs.
+///
+/// This function matches each zone in the ground truth "Name" column to the forecast
+/// column of the same name (ignoring case, so "HUD VL" matches "Hud Vl"), joins them
+/// with [`join_actual_forecast`] and returns one row per zone with the columns:
+///
+/// * "Name" - The zone, as spelled in the ground truth.
+/// * "MAPE" - Mean absolute percentage error, see [`mape`].
+/// * "Peak Error" - Forecast peak minus actual peak, as a percentage of the actual
+///   peak, see [`peak_error`].
+/// * "Bias" - Mean forecast minus mean actual, as a percentage of the mean actual,
+///   see [`bias`].
+///
+/// Zones without a forecast column or without overlapping timestamps are skipped.
+///
+/// # Arguments
+///
+/// * `ground_truth` - The raw ground truth DataFrame, with one row per zone and timestamp.
+/// * `pred` - The raw prediction DataFrame, with one column per zone.
+///
+/// # Returns
+///
+/// * A `Result` containing the per-zone metrics DataFrame or a `MugenError`.
+///
+/// # Errors
+///
+/// This function will return an error if the columns cannot be processed, a zone name
+/// is not a NYISO zone or no zone could be scored.
+#[instrument(name = "metric", skip_all)]
+pub fn zone_metrics(ground_truth: DataFrame, pred: DataFrame) -> Result<DataFrame> {
+    let zones: Vec<Zone> = zones_in(&ground_truth)?;
+    let ground_truth: LazyFrame = ground_truth.lazy();
+    let pred: LazyFrame = pred.lazy();
+    let pred_schema: SchemaRef = pred.schema()?;
+
+    let mut names: Vec<String> = Vec::new();
+    let mut mapes: Vec<f64> = Vec::new();
+    let mut peak_errors: Vec<f64> = Vec::new();
+    let mut biases: Vec<f64> = Vec::new();
+    for zone in zones {
+        if pred_schema.get(zone.forecast_column()).is_none() {
+            continue;
+        }
+        let joined: DataFrame = join_plan(
+            truth_plan(ground_truth.clone(), zone)?,
+            "Integrated Load",
+            pred_plan(pred.clone(), zone)?,
+            zone.forecast_column(),
+        )
+        .collect()?;
+        if joined.height() == 0 {
+            continue;
+        }
+        let joined: TimeSeriesDataset = joined_dataset(joined)?;
+
+        mapes.push(mape(&joined)?);
+        peak_errors.push(peak_error(&joined)?);
+        biases.push(bias(&joined)?);
+        names.push(zone.to_string());
+    }
+    if names.is_empty() {
+        return Err(MugenError::NoData(
+            "no zone has both ground truth and a forecast".to_string(),
+        ));
+    }
+
+    Ok(DataFrame::new(vec![
+        Series::new("Name", names),
+        Series::new("MAPE", mapes),
+        Series::new("Peak Error", peak_errors),
+        Series::new("Bias", biases),
+    ])?)
+}
+
+/// Parses the text "Time Stamp" column of `df` in place with `format`.
+///
+/// `frame` names `df` in errors.
+///
+/// # Errors
+///
+/// Returns `MugenError::MissingColumn` if there is no "Time Stamp" column and
+/// `MugenError::ColumnType` if it is not text or none of its values match `format`.
+fn parse_time_stamps(df: &mut DataFrame, frame: &str, format: &str) -> Result<()> {
+    require_text(&df.schema(), frame, "Time Stamp")?;
+    let raw: DataFrame = std::mem::take(df);
+    *df = raw
+        .clone()
+        .lazy()
+        .with_column(parse_time_stamp("Time Stamp", format))
+        .collect()?;
+    check_time_stamps(df, &raw, frame, format)
+}
+
+/// Parses the text column `name` into millisecond datetimes with polars' native
+/// `strptime`; values that do not match `format` become null.
+fn parse_time_stamp(name: &str, format: &str) -> Expr {
+    col(name).str().strptime(
+        DataType::Datetime(TimeUnit::Milliseconds, None),
+        StrptimeOptions {
+            format: Some(format.to_string()),
+            strict: false,
+            exact: true,
+            cache: true,
+        },
+        lit("raise"),
+    )
+}
+
+/// Checks that the parsed "Time Stamp" column of `df` is not all null, which means
+/// the text did not match `format` at all. `frame` names `df` in the error, which
+/// quotes the first value of `raw`, the frame before parsing, that does not match
+/// and its row.
+fn check_time_stamps(df: &DataFrame, raw: &DataFrame, frame: &str, format: &str) -> Result<()> {
+    check_time_column(df, raw, "Time Stamp", frame, format)
+}
+
+/// Like [`check_time_stamps`], for a `raw` frame whose timestamps are in
+/// `time_column`.
+fn check_time_column(
+    df: &DataFrame,
+    raw: &DataFrame,
+    time_column: &str,
+    frame: &str,
+    format: &str,
+) -> Result<()> {
+    let parsed: &Series = column(df, frame, "Time Stamp")?;
+    if parsed.is_empty() || parsed.null_count() < parsed.len() {
+        return Ok(());
+    }
+    let found: String = str_column(raw, frame, time_column)?
+        .into_iter()
+        .enumerate()
+        .find_map(|(row, stamp)| {
+            stamp
+                .filter(|stamp| {
+                    NaiveDateTime::parse_from_str(stamp, format).is_err()
+                        && NaiveDate::parse_from_str(stamp, format).is_err()
+                })
+                .map(|stamp| format!("\"{}\" in row {}", stamp, row + 1))
+        })
+        .unwrap_or_else(|| "no value in that format".to_string());
+    Err(column_type(
+        frame,
+        time_column,
+        &format!("timestamps formatted as \"{}\"", format),
+        found,
+    ))
+}
+
+/// Finds the file behind `err`, raised while loading `truth_paths` and
+/// `forecast_path` together, by processing each on its own, and names the first
+/// that fails. Returns `err` unchanged if it already names a file or no file fails
+/// alone.
+fn locate_error(err: MugenError, truth_paths: &[&str], forecast_path: &str, zone: Zone) -> MugenError {
+    if matches!(err, MugenError::Read { .. } | MugenError::InFile { .. }) {
+        return err;
+    }
+    for path in truth_paths {
+        if let Err(source) = read_csv_to_df(path).and_then(|df| process_truth(df, zone)) {
+            return in_file(path, source);
+        }
+    }
+    if let Err(source) = read_csv_to_df(forecast_path).and_then(|df| process_pred(df, zone)) {
+        return in_file(forecast_path, source);
+    }
+    err
+}
+
+/// Wraps `source` as an error in the file at `path`.
+fn in_file(path: &str, source: MugenError) -> MugenError {
+    MugenError::InFile {
+        path: path.to_string(),
+        source: Box::new(source),
+    }
+}
+
+/// Returns the type of `name` in `schema`, naming `frame` in the error if it is
+/// missing.
+fn schema_type<'a>(schema: &'a Schema, frame: &str, name: &str) -> Result<&'a DataType> {
+    schema
+        .get(name)
+        .ok_or_else(|| missing_column(frame, name, schema.iter_names().map(|name| name.as_str())))
+}
+
+/// Checks that `name` in `schema` holds text, naming `frame` in the error if it is
+/// missing or does not.
+fn require_text(schema: &Schema, frame: &str, name: &str) -> Result<()> {
+    let dtype: &DataType = schema_type(schema, frame, name)?;
+    if dtype != &DataType::String {
+        return Err(column_type(frame, name, "text", dtype));
+    }
+    Ok(())
+}
+
+/// Wraps the output of [`join_plan`] as a dataset of its "Actual", "Forecast" and
+/// "Residual" columns.
+fn joined_dataset(joined: DataFrame) -> Result<TimeSeriesDataset> {
+    let value_columns: Vec<String> = ["Actual", "Forecast", "Residual"]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    TimeSeriesDataset::new("joined", joined, "Time Stamp", value_columns)
+}
+
+/// Returns `name` from `df`.
+///
+/// These column helpers name `frame` in their errors, list the columns `df` does
+/// have when `name` is missing and give the actual type when it holds the wrong
+/// one, so use them instead of chaining polars' accessors.
+pub fn column<'a>(df: &'a DataFrame, frame: &str, name: &str) -> Result<&'a Series> {
+    df.column(name)
+        .map_err(|_| missing_column(frame, name, df.get_column_names()))
+}
+
+/// Returns `name` from `df` cast to `f64`, with an error if it is missing or not
+/// numeric.
+pub fn f64_column(df: &DataFrame, frame: &str, name: &str) -> Result<Float64Chunked> {
+    let values: &Series = column(df, frame, name)?;
+    let cast: Series = values
+        .cast(&DataType::Float64)
+        .map_err(|_| column_type(frame, name, "numeric", values.dtype()))?;
+    Ok(cast.f64()?.clone())
+}
+
+/// Returns `name` from `df` as text, with an error if it is missing or not text.
+pub fn str_column<'a>(
+    df: &'a DataFrame,
+    frame: &str,
+    name: &str,
+) -> Result<&'a StringChunked> {
+    let values: &Series = column(df, frame, name)?;
+    values
+        .str()
+        .map_err(|_| column_type(frame, name, "text", values.dtype()))
+}
+
+/// Returns `name` from `df` as datetimes, with an error if it is missing or not a
+/// datetime.
+pub fn datetime_column<'a>(
+    df: &'a DataFrame,
+    frame: &str,
+    name: &str,
+) -> Result<&'a DatetimeChunked> {
+    let values: &Series = column(df, frame, name)?;
+    values
+        .datetime()
+        .map_err(|_| column_type(frame, name, "a datetime", values.dtype()))
+}
+
+/// Returns the parsed "Time Stamp" column of `df`, with an error if it is missing or
+/// not a datetime.
+pub fn timestamp_column<'a>(
+    df: &'a DataFrame,
+    frame: &str,
+) -> Result<&'a DatetimeChunked> {
+    datetime_column(df, frame, "Time Stamp")
+}
+
+/// Builds the error for a column `name` of `frame` that is not among `available`.
+fn missing_column<'a>(
+    frame: &str,
+    name: &str,
+    available: impl IntoIterator<Item = &'a str>,
+) -> MugenError {
+    MugenError::MissingColumn {
+        frame: frame.to_string(),
+        column: name.to_string(),
+        available: available.into_iter().map(str::to_string).collect(),
+    }
+}
+
+/// Builds the error for a column of `frame` that holds `actual` instead of
+/// `expected` values.
+pub(crate) fn column_type(
+    frame: &str,
+    name: &str,
+    expected: &str,
+    actual: impl std::fmt::Display,
+) -> MugenError {
+    MugenError::ColumnType {
+        frame: frame.to_string(),
+        column: name.to_string(),
+        expected: expected.to_string(),
+        actual: actual.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::testing::{dataset, hourly, midnight};
+    use crate::dataset::HOUR_MS;
+    use crate::iso::Miso;
+    use crate::synthetic::Synthetic;
+
+    /// Returns `hour:minute` on 9 December 2023 in milliseconds.
+    fn at(hour: u32, minute: u32) -> i64 {
+        midnight(2023, 12, 9) + i64::from(hour) * HOUR_MS + i64::from(minute) * 60_000
+    }
+
+    /// Returns the processed actual load and forecast of New York City over two days.
+    fn series() -> (TimeSeriesDataset, TimeSeriesDataset) {
+        let days = NaiveDate::from_ymd_opt(2023, 12, 8).unwrap()
+            ..=NaiveDate::from_ymd_opt(2023, 12, 9).unwrap();
+        let synthetic = Synthetic::default();
+        (
+            process_truth(synthetic.truth(days.clone()).unwrap(), Zone::NewYorkCity).unwrap(),
+            process_pred(synthetic.forecast(days).unwrap(), Zone::NewYorkCity).unwrap(),
+        )
+    }
+
+    #[test]
+    fn joins_reject_datasets_in_different_timezones() {
+        let (truth, forecast) = series();
+        let utc = forecast.clone().with_timezone("UTC");
+        let mismatched = |result: Result<TimeSeriesDataset>| {
+            matches!(result, Err(MugenError::InvalidArgument(_)))
+        };
+        assert!(mismatched(join_actual_forecast(&truth, &utc)));
+        assert!(mismatched(compare_forecasts(&forecast, &utc)));
+        assert!(mismatched(compare_demand(&truth, &utc)));
+        assert!(join_actual_forecast(&truth, &utc.in_timezone(truth.timezone()).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn resample_averages_each_period_from_its_start() {
+        let readings: TimeSeriesDataset = dataset(
+            "real-time load",
+            vec![at(0, 30), at(0, 0), at(1, 15), at(3, 45)],
+            vec![Series::new("Load", [3.0, 1.0, 10.0, 4.0])],
+        );
+        let hourly: TimeSeriesDataset = resample(&readings, chrono::Duration::hours(1)).unwrap();
+        assert_eq!(
+            hourly.unique_timestamps().unwrap(),
+            vec![at(0, 0), at(1, 0), at(3, 0)]
+        );
+        let values: Vec<Option<f64>> = hourly.values("Load").unwrap().into_iter().collect();
+        assert_eq!(values, vec![Some(2.0), Some(10.0), Some(4.0)]);
+
+        let never = resample(&readings, chrono::Duration::zero());
+        assert!(matches!(never, Err(MugenError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn convert_timezone_follows_daylight_saving() {
+        let local = |day: (i32, u32, u32), hour: u32, minute: u32| -> i64 {
+            NaiveDate::from_ymd_opt(day.0, day.1, day.2)
+                .and_then(|day| day.and_hms_opt(hour, minute, 0))
+                .unwrap()
+                .and_utc()
+                .timestamp_millis()
+        };
+        let times: Vec<i64> = vec![
+            local((2023, 12, 9), 12, 0),
+            // Repeated when clocks fall back: the first, daylight time, is taken.
+            local((2023, 11, 5), 1, 0),
+            // Skipped when clocks spring forward: taken as the hour after.
+            local((2023, 3, 12), 2, 30),
+        ];
+        let frame: DataFrame = DataFrame::new(vec![Int64Chunked::from_vec("Time Stamp", times)
+            .into_datetime(TimeUnit::Milliseconds, None)
+            .into_series()])
+        .unwrap();
+        let converted: DataFrame =
+            convert_timezone(&frame, "load", "Time Stamp", "America/New_York", "UTC").unwrap();
+        let utc: Vec<Option<i64>> = timestamp_column(&converted, "load")
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(
+            utc,
+            vec![
+                Some(local((2023, 12, 9), 17, 0)),
+                Some(local((2023, 11, 5), 5, 0)),
+                Some(local((2023, 3, 12), 7, 30)),
+            ]
+        );
+
+        let back: DataFrame =
+            convert_timezone(&converted, "load", "Time Stamp", "UTC", "America/New_York").unwrap();
+        assert_eq!(
+            timestamp_column(&back, "load").unwrap().get(0),
+            Some(local((2023, 12, 9), 12, 0))
+        );
+        let unknown = convert_timezone(&frame, "load", "Time Stamp", "Mars/Olympus", "UTC");
+        assert!(matches!(unknown, Err(MugenError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn aligned_downsampling_keeps_the_series_in_step() {
+        let rising: TimeSeriesDataset = hourly(
+            "rising",
+            at(0, 0),
+            vec![Series::new(
+                "Load",
+                (0..12)
+                    .map(|hour| (hour < 8).then_some(f64::from(hour)))
+                    .collect::<Vec<Option<f64>>>(),
+            )],
+        );
+        let falling: TimeSeriesDataset = hourly(
+            "falling",
+            at(0, 0),
+            vec![Series::new(
+                "Load",
+                (0..6)
+                    .map(|hour| 100.0 - f64::from(hour))
+                    .collect::<Vec<f64>>(),
+            )],
+        );
+        let downsampled: Vec<TimeSeriesDataset> =
+            min_max_downsample_aligned(&[&rising, &falling], 3).unwrap();
+        let hours = |dataset: &TimeSeriesDataset| -> Vec<i64> {
+            dataset
+                .unique_timestamps()
+                .unwrap()
+                .into_iter()
+                .map(|stamp| (stamp - at(0, 0)) / HOUR_MS)
+                .collect()
+        };
+        // The extremes of either series in each bucket of four hours, and the first
+        // hour of the last bucket, where neither has a value.
+        assert_eq!(hours(&downsampled[0]), vec![0, 3, 4, 5, 7, 8]);
+        assert_eq!(hours(&downsampled[1]), vec![0, 3, 4, 5]);
+
+        let few: Vec<TimeSeriesDataset> = min_max_downsample_aligned(&[&rising], 6).unwrap();
+        assert_eq!(few[0].height(), 12);
+    }
+
+    #[test]
+    fn hour_endings_stamp_the_start_of_each_hour() {
+        let raw: DataFrame = df!(
+            "Market Day" => vec!["12/09/2023"; 24],
+            "HourEnding" => (1..=24).collect::<Vec<i64>>(),
+            "LRZ1 ActualLoad (MWh)" => (0..24).map(f64::from).collect::<Vec<f64>>(),
+        )
+        .unwrap();
+        let zone: IsoZone = Miso.zone("LRZ1").unwrap();
+        let load: TimeSeriesDataset = process_load(&Miso, FileKind::Actual, raw, &zone).unwrap();
+        let stamps: Vec<i64> = load.unique_timestamps().unwrap();
+        assert_eq!(stamps.len(), 24);
+        assert_eq!(stamps.first(), Some(&at(0, 0)));
+        assert_eq!(stamps.last(), Some(&at(23, 0)));
+        assert_eq!(load.timezone(), "EST");
+    }
+
+    #[test]
+    fn moving_average_trails_and_skips_windows_with_nulls() {
+        let values: Series = Series::new(
+            "Load",
+            [
+                Some(1.0),
+                Some(2.0),
+                Some(3.0),
+                None,
+                Some(5.0),
+                Some(6.0),
+                Some(7.0),
+            ],
+        );
+        let averaged: Series = moving_average(&values, 3).unwrap();
+        assert_eq!(averaged.name(), "Load (MA 3)");
+        let averaged: Vec<Option<f64>> = averaged.f64().unwrap().into_iter().collect();
+        assert_eq!(
+            averaged,
+            vec![None, None, Some(2.0), None, None, None, Some(6.0)]
+        );
+        assert!(matches!(
+            moving_average(&values, 0),
+            Err(MugenError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn zone_shares_need_some_load() {
+        let truth = |loads: [f64; 2]| -> DataFrame {
+            df!(
+                "Time Stamp" => ["12/09/2023 18:00:00", "12/09/2023 18:00:00"],
+                "Name" => ["N.Y.C.", "LONGIL"],
+                "Integrated Load" => loads,
+            )
+            .unwrap()
+        };
+        let six_pm: NaiveDateTime = NaiveDate::from_ymd_opt(2023, 12, 9)
+            .and_then(|day| day.and_hms_opt(18, 0, 0))
+            .unwrap();
+        let shares: DataFrame = zone_shares_at(truth([3000.0, 1000.0]), six_pm).unwrap();
+        let shares: Vec<Option<f64>> = shares
+            .column("Share")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(shares, vec![Some(0.75), Some(0.25)]);
+
+        let dark = zone_shares_at(truth([0.0, 0.0]), six_pm);
+        assert!(matches!(dark, Err(MugenError::NoData(_))));
+    }
+}
@@ -0,0 +1,416 @@
+use std::collections::HashMap;
+
+use chrono::Duration;
+use polars::prelude::*;
+
+use crate::data::{column, column_type, convert_timezone, datetime_column, f64_column};
+use crate::error::{MugenError, Result};
+
+/// One hour, in milliseconds, the unit of [`TimeSeriesDataset::timestamps`].
+pub(crate) const HOUR_MS: i64 = 3_600_000;
+
+/// A DataFrame holding one or more value series over a datetime column, together
+/// with what the values mean.
+///
+/// Processing, metrics and charts read the time and value columns from here instead
+/// of assuming column names, and use `name` to say which input an error is about.
+#[derive(Clone, Debug)]
+pub struct TimeSeriesDataset {
+    name: String,
+    frame: DataFrame,
+    time_column: String,
+    value_columns: Vec<String>,
+    unit: String,
+    timezone: String,
+    frequency: Option<Duration>,
+}
+
+impl TimeSeriesDataset {
+    /// Wraps `frame`, checking that `time_column` is a datetime column and every one
+    /// of the `value_columns` exists, and infers the sampling frequency.
+    ///
+    /// Timestamps in microseconds or nanoseconds are cast to milliseconds, the unit
+    /// [`TimeSeriesDataset::timestamps`] and everything downstream work in.
+    ///
+    /// The unit defaults to none and the timezone to "UTC"; see
+    /// [`TimeSeriesDataset::with_unit`] and [`TimeSeriesDataset::with_timezone`].
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - What the data is, e.g. "ground truth", used in error messages.
+    /// * `frame` - The data, with one row per timestamp.
+    /// * `time_column` - The datetime column.
+    /// * `value_columns` - The columns holding values, the primary one first.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing the dataset or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::MissingColumn` if a column is absent,
+    /// `MugenError::ColumnType` if `time_column` is not a datetime and
+    /// `MugenError::InvalidArgument` if `value_columns` is empty.
+    pub fn new(
+        name: impl Into<String>,
+        mut frame: DataFrame,
+        time_column: impl Into<String>,
+        value_columns: Vec<String>,
+    ) -> Result<Self> {
+        let name: String = name.into();
+        let time_column: String = time_column.into();
+        if value_columns.is_empty() {
+            return Err(MugenError::InvalidArgument(format!(
+                "{} needs at least one value column",
+                name
+            )));
+        }
+        for value_column in &value_columns {
+            column(&frame, &name, value_column)?;
+        }
+        let times: &Series = column(&frame, &name, &time_column)?;
+        match times.dtype() {
+            DataType::Datetime(TimeUnit::Milliseconds, _) => {}
+            DataType::Datetime(_, timezone) => {
+                let millis: Series = times.cast(&DataType::Datetime(
+                    TimeUnit::Milliseconds,
+                    timezone.clone(),
+                ))?;
+                frame.with_column(millis)?;
+            }
+            dtype => return Err(column_type(&name, &time_column, "a datetime", dtype)),
+        }
+
+        let mut dataset = TimeSeriesDataset {
+            name,
+            frame,
+            time_column,
+            value_columns,
+            unit: String::new(),
+            timezone: "UTC".to_string(),
+            frequency: None,
+        };
+        dataset.frequency = dataset.infer_frequency()?;
+        Ok(dataset)
+    }
+
+    /// Sets the unit the values are measured in, e.g. "MW".
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = unit.into();
+        self
+    }
+
+    /// Sets the IANA name of the timezone the timestamps are local to.
+    pub fn with_timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.timezone = timezone.into();
+        self
+    }
+
+    /// Returns the dataset with its timestamps converted to local times in the IANA
+    /// timezone `timezone`, see [`convert_timezone`].
+    ///
+    /// Use [`TimeSeriesDataset::with_timezone`] first if the timestamps are not in
+    /// the timezone the dataset says, e.g. when a file was written in UTC.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InvalidArgument` if either timezone is unknown.
+    pub fn in_timezone(&self, timezone: &str) -> Result<Self> {
+        let converted: Self = self.map_frame(|frame| {
+            convert_timezone(frame, &self.name, &self.time_column, &self.timezone, timezone)
+        })?;
+        Ok(converted.with_timezone(timezone))
+    }
+
+    /// Returns what the data is, as used in error messages.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the underlying DataFrame.
+    pub fn frame(&self) -> &DataFrame {
+        &self.frame
+    }
+
+    /// Unwraps the underlying DataFrame.
+    pub fn into_frame(self) -> DataFrame {
+        self.frame
+    }
+
+    /// Returns the name of the datetime column.
+    pub fn time_column(&self) -> &str {
+        &self.time_column
+    }
+
+    /// Returns the names of the value columns, the primary one first.
+    pub fn value_columns(&self) -> &[String] {
+        &self.value_columns
+    }
+
+    /// Returns the name of the primary value column.
+    pub fn value_column(&self) -> &str {
+        &self.value_columns[0]
+    }
+
+    /// Returns the unit of the values, or an empty string if unknown.
+    pub fn unit(&self) -> &str {
+        &self.unit
+    }
+
+    /// Returns the IANA name of the timezone the timestamps are local to.
+    pub fn timezone(&self) -> &str {
+        &self.timezone
+    }
+
+    /// Returns the most common spacing between consecutive timestamps, or `None` if
+    /// there are fewer than two.
+    pub fn frequency(&self) -> Option<Duration> {
+        self.frequency
+    }
+
+    /// Returns the number of rows.
+    pub fn height(&self) -> usize {
+        self.frame.height()
+    }
+
+    /// Returns the timestamps, in milliseconds, in row order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the time column is no longer a datetime.
+    pub fn timestamps(&self) -> Result<&DatetimeChunked> {
+        datetime_column(&self.frame, &self.name, &self.time_column)
+    }
+
+    /// Returns `column` cast to `f64`, in row order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming this dataset if `column` is missing or not numeric.
+    pub fn values(&self, column: &str) -> Result<Float64Chunked> {
+        f64_column(&self.frame, &self.name, column)
+    }
+
+    /// Maps each timestamp to the value of `column` there. Rows where either is null
+    /// are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming this dataset if `column` is missing or not numeric.
+    pub fn values_by_timestamp(&self, column: &str) -> Result<HashMap<i64, f64>> {
+        Ok(self
+            .timestamps()?
+            .into_iter()
+            .zip(&self.values(column)?)
+            .filter_map(|(dt, value)| Some((dt?, value?)))
+            .collect())
+    }
+
+    /// Returns the sorted, distinct timestamps.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the time column is no longer a datetime.
+    pub fn unique_timestamps(&self) -> Result<Vec<i64>> {
+        let mut timestamps = self
+            .timestamps()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<i64>>();
+        timestamps.sort_unstable();
+        timestamps.dedup();
+        Ok(timestamps)
+    }
+
+    /// Returns the dataset sorted by time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if sorting fails.
+    pub fn sorted(&self) -> Result<Self> {
+        self.map_frame(|frame| {
+            Ok(frame.sort([self.time_column.as_str()], SortMultipleOptions::default())?)
+        })
+    }
+
+    /// Replaces the frame with `f(frame)`, keeping the metadata, e.g. to filter rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `f`, or an error if the new frame lacks the time or value
+    /// columns.
+    pub fn map_frame(&self, f: impl FnOnce(&DataFrame) -> Result<DataFrame>) -> Result<Self> {
+        let dataset = TimeSeriesDataset::new(
+            self.name.clone(),
+            f(&self.frame)?,
+            self.time_column.clone(),
+            self.value_columns.clone(),
+        )?;
+        Ok(dataset
+            .with_unit(self.unit.clone())
+            .with_timezone(self.timezone.clone()))
+    }
+
+    /// Finds the most common gap between consecutive distinct timestamps.
+    fn infer_frequency(&self) -> Result<Option<Duration>> {
+        let timestamps = self.unique_timestamps()?;
+        let mut counts: HashMap<i64, usize> = HashMap::new();
+        for pair in timestamps.windows(2) {
+            *counts.entry(pair[1] - pair[0]).or_default() += 1;
+        }
+        Ok(counts
+            .into_iter()
+            .max_by_key(|&(gap, count)| (count, std::cmp::Reverse(gap)))
+            .map(|(gap, _)| Duration::milliseconds(gap)))
+    }
+}
+
+impl Default for TimeSeriesDataset {
+    /// An empty dataset with a millisecond "Time Stamp" time column and a float
+    /// "Value" value column.
+    fn default() -> Self {
+        // Two empty columns of distinct names and equal length, which `DataFrame::new`
+        // always accepts, so the invariants of `new` hold.
+        let frame: DataFrame = DataFrame::new(vec![
+            Series::new_empty(
+                "Time Stamp",
+                &DataType::Datetime(TimeUnit::Milliseconds, None),
+            ),
+            Series::new_empty("Value", &DataType::Float64),
+        ])
+        .unwrap_or_default();
+        TimeSeriesDataset {
+            name: "dataset".to_string(),
+            frame,
+            time_column: "Time Stamp".to_string(),
+            value_columns: vec!["Value".to_string()],
+            unit: String::new(),
+            timezone: "UTC".to_string(),
+            frequency: None,
+        }
+    }
+}
+
+/// Datasets for the tests of the modules that work on them.
+#[cfg(test)]
+pub(crate) mod testing {
+    use chrono::NaiveDate;
+    use polars::prelude::*;
+
+    use super::{TimeSeriesDataset, HOUR_MS};
+
+    /// Returns midnight at the start of `day`, read as UTC, in milliseconds.
+    pub(crate) fn midnight(year: i32, month: u32, day: u32) -> i64 {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .and_then(|day| day.and_hms_opt(0, 0, 0))
+            .unwrap()
+            .and_utc()
+            .timestamp_millis()
+    }
+
+    /// A dataset named `name` with a millisecond "Time Stamp" column of `stamps` and
+    /// `columns` as its value columns, the first the primary one.
+    pub(crate) fn dataset(name: &str, stamps: Vec<i64>, columns: Vec<Series>) -> TimeSeriesDataset {
+        let value_columns: Vec<String> = columns
+            .iter()
+            .map(|column| column.name().to_string())
+            .collect();
+        let mut frame: Vec<Series> = vec![Int64Chunked::from_vec("Time Stamp", stamps)
+            .into_datetime(TimeUnit::Milliseconds, None)
+            .into_series()];
+        frame.extend(columns);
+        TimeSeriesDataset::new(
+            name,
+            DataFrame::new(frame).unwrap(),
+            "Time Stamp",
+            value_columns,
+        )
+        .unwrap()
+    }
+
+    /// Like [`dataset`], with a row per hour from `start`, as many as the columns are
+    /// long.
+    pub(crate) fn hourly(name: &str, start: i64, columns: Vec<Series>) -> TimeSeriesDataset {
+        let hours: i64 = columns.first().map_or(0, |column| column.len() as i64);
+        dataset(
+            name,
+            (0..hours).map(|hour| start + hour * HOUR_MS).collect(),
+            columns,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    /// Three hours of load with timestamps in `unit`.
+    fn frame(unit: TimeUnit) -> DataFrame {
+        let start = NaiveDate::from_ymd_opt(2023, 12, 9)
+            .and_then(|day| day.and_hms_opt(0, 0, 0))
+            .unwrap();
+        let times: Vec<i64> = (0..3)
+            .map(|hour| {
+                let at = start + Duration::hours(hour);
+                match unit {
+                    TimeUnit::Milliseconds => at.and_utc().timestamp_millis(),
+                    TimeUnit::Microseconds => at.and_utc().timestamp_micros(),
+                    TimeUnit::Nanoseconds => at.and_utc().timestamp_nanos_opt().unwrap(),
+                }
+            })
+            .collect();
+        df!(
+            "Time Stamp" => Series::new("Time Stamp", times)
+                .cast(&DataType::Datetime(unit, None))
+                .unwrap(),
+            "Load" => [4500.0, 4600.0, 4700.0],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn new_casts_timestamps_to_milliseconds() {
+        let expected: Vec<Option<i64>> = TimeSeriesDataset::new(
+            "load",
+            frame(TimeUnit::Milliseconds),
+            "Time Stamp",
+            vec!["Load".to_string()],
+        )
+        .unwrap()
+        .timestamps()
+        .unwrap()
+        .into_iter()
+        .collect();
+        for unit in [TimeUnit::Microseconds, TimeUnit::Nanoseconds] {
+            let dataset =
+                TimeSeriesDataset::new("load", frame(unit), "Time Stamp", vec!["Load".to_string()])
+                    .unwrap();
+            assert_eq!(
+                dataset.frame().column("Time Stamp").unwrap().dtype(),
+                &DataType::Datetime(TimeUnit::Milliseconds, None)
+            );
+            let timestamps: Vec<Option<i64>> = dataset.timestamps().unwrap().into_iter().collect();
+            assert_eq!(timestamps, expected);
+            assert_eq!(dataset.frequency(), Some(Duration::hours(1)));
+        }
+    }
+
+    #[test]
+    fn default_has_its_declared_columns() {
+        let dataset = TimeSeriesDataset::default();
+        assert_eq!(dataset.height(), 0);
+        assert!(dataset.timestamps().is_ok());
+        assert!(dataset.values(dataset.value_column()).is_ok());
+        let rebuilt = dataset.map_frame(|frame| Ok(frame.clone())).unwrap();
+        assert_eq!(rebuilt.value_columns(), ["Value".to_string()]);
+    }
+
+    #[test]
+    fn new_rejects_a_time_column_that_is_not_a_datetime() {
+        let frame = df!("Time Stamp" => [1i64, 2, 3], "Load" => [1.0, 2.0, 3.0]).unwrap();
+        let result = TimeSeriesDataset::new("load", frame, "Time Stamp", vec!["Load".to_string()]);
+        assert!(matches!(result, Err(MugenError::ColumnType { .. })));
+    }
+}
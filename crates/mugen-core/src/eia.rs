@@ -0,0 +1,383 @@
+use std::collections::BTreeMap;
+#[cfg(feature = "fetch")]
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+#[cfg(feature = "fetch")]
+use chrono::{Duration, TimeZone};
+#[cfg(feature = "fetch")]
+use chrono_tz::Tz;
+#[cfg(feature = "fetch")]
+use reqwest::Client;
+use serde_json::Value;
+
+use crate::data::{datetime_column, f64_column};
+use crate::dataset::TimeSeriesDataset;
+use crate::error::{MugenError, Result};
+#[cfg(feature = "fetch")]
+use crate::fetch::{download, is_downloaded, save_download, FetchOptions, Pacer};
+
+/// Root of the EIA open-data API, version 2.
+pub const EIA_BASE_URL: &str = "https://api.eia.gov/v2";
+
+/// Environment variable holding the key for the EIA open-data API, free from
+/// <https://www.eia.gov/opendata/register.php>.
+pub const EIA_API_KEY_VAR: &str = "EIA_API_KEY";
+
+/// EIA's code for the NYISO balancing authority.
+pub const NYISO_RESPONDENT: &str = "NYIS";
+
+/// Most rows the API returns per request; longer periods are read in pages.
+const PAGE_LENGTH: usize = 5000;
+
+/// Header of the daily demand files.
+#[cfg(feature = "fetch")]
+const HEADER: &str = "\"Time Stamp\",\"Time Zone\",\"Respondent\",\"Demand\"";
+
+/// Returns the file name of `respondent`'s hourly demand on `day`, e.g.
+/// "20231209eia_nyis.csv", in the same folder as the NYISO files.
+pub fn file_name(respondent: &str, day: NaiveDate) -> String {
+    format!(
+        "{}eia_{}.csv",
+        day.format("%Y%m%d"),
+        respondent.to_ascii_lowercase()
+    )
+}
+
+/// Returns the URL of one page of the hourly demand `respondent` reported to EIA
+/// for the hours ending from `start` through `end`, in UTC, under `base_url`.
+///
+/// `offset` skips that many rows, to read periods longer than one page.
+pub fn demand_url(
+    base_url: &str,
+    api_key: &str,
+    respondent: &str,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    offset: usize,
+) -> String {
+    format!(
+        "{}/electricity/rto/region-data/data/?api_key={}&frequency=hourly&data[0]=value\
+         &facets[respondent][]={}&facets[type][]=D&start={}&end={}\
+         &sort[0][column]=period&sort[0][direction]=asc&offset={}&length={}",
+        base_url.trim_end_matches('/'),
+        api_key,
+        respondent,
+        start.format("%Y-%m-%dT%H"),
+        end.format("%Y-%m-%dT%H"),
+        offset,
+        PAGE_LENGTH,
+    )
+}
+
+/// Parses one answer of the API's region data into hourly demand in MW, by the UTC
+/// hour each value ends, as EIA-930 reports them.
+///
+/// Hours without a value, which EIA leaves empty until a balancing authority
+/// reports them, are skipped.
+///
+/// # Arguments
+///
+/// * `body` - The response body.
+///
+/// # Returns
+///
+/// * A `Result` containing the demand of every hour with a value, in order, and the
+///   number of rows the page held, or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::NoData` if `body` is not an answer with data, e.g. because
+/// the API key was refused.
+pub fn parse_demand(body: &[u8]) -> Result<(BTreeMap<NaiveDateTime, f64>, usize)> {
+    let json: Value = serde_json::from_slice(body).map_err(|err| {
+        MugenError::NoData(format!(
+            "expected EIA demand as JSON, found an error: {}",
+            err
+        ))
+    })?;
+    let Some(rows) = json["response"]["data"].as_array() else {
+        let error: &Value = &json["error"];
+        return Err(MugenError::NoData(format!(
+            "expected EIA demand, found \"{}\"",
+            error
+                .as_str()
+                .or_else(|| error["message"].as_str())
+                .unwrap_or("no data")
+        )));
+    };
+    let demand: BTreeMap<NaiveDateTime, f64> = rows
+        .iter()
+        .filter_map(|row| {
+            let period = NaiveDateTime::parse_from_str(
+                &format!("{}:00", row["period"].as_str()?),
+                "%Y-%m-%dT%H:%M",
+            )
+            .ok()?;
+            let value: &Value = &row["value"];
+            let value: f64 = value
+                .as_f64()
+                .or_else(|| value.as_str()?.trim().parse().ok())?;
+            Some((period, value))
+        })
+        .collect();
+    Ok((demand, rows.len()))
+}
+
+/// Downloads the hourly demand `respondent` reported to EIA for `from..=to`, days in
+/// `timezone`, and saves one file per day into `dir`, named by [`file_name`].
+///
+/// Each file holds "Time Stamp", at the start of each hour in `timezone`, "Time
+/// Zone", "Respondent" and "Demand" in MW, in the layout of the actual-load files,
+/// so [`crate::data::process_demand`] reads it like them. The API key is read from
+/// [`EIA_API_KEY_VAR`]. If every day's file is already in `dir` and matches its
+/// recorded digest, nothing is downloaded; otherwise the whole period is downloaded,
+/// a page at a time, and every day rewritten. Downloads are paced and temporary
+/// failures retried as `options` says.
+///
+/// # Arguments
+///
+/// * `client` - The HTTP client to download with.
+/// * `base_url` - The API, normally [`EIA_BASE_URL`].
+/// * `respondent` - The balancing authority, e.g. [`NYISO_RESPONDENT`].
+/// * `timezone` - The IANA timezone days and hours are given in.
+/// * `from` - The first day to download.
+/// * `to` - The last day to download, inclusive.
+/// * `dir` - The directory to save into; created on demand.
+/// * `options` - How downloads are retried and paced.
+///
+/// # Returns
+///
+/// * A `Result` containing the local paths of every day's file, in date order, or a
+///   `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::InvalidArgument` if the API key is not set or `timezone` is
+/// unknown, `MugenError::Fetch` if a page still cannot be downloaded after retrying,
+/// `MugenError::NoData` if the API answers with something other than demand, and
+/// `MugenError::Write` if a file cannot be saved.
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "fetch")]
+pub async fn fetch_demand(
+    client: &Client,
+    base_url: &str,
+    respondent: &str,
+    timezone: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+    dir: &str,
+    options: &FetchOptions,
+) -> Result<Vec<String>> {
+    let tz: Tz = parse_timezone(timezone)?;
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|source| MugenError::Write {
+            path: dir.to_string(),
+            source,
+        })?;
+    let days: Vec<NaiveDate> = from.iter_days().take_while(|day| *day <= to).collect();
+    let paths: Vec<String> = days
+        .iter()
+        .map(|day| {
+            Path::new(dir)
+                .join(file_name(respondent, *day))
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    if paths.iter().all(|path| is_downloaded(path)) {
+        return Ok(paths);
+    }
+    let api_key: String = std::env::var(EIA_API_KEY_VAR).map_err(|_| {
+        MugenError::InvalidArgument(format!(
+            "downloading from EIA needs {} set",
+            EIA_API_KEY_VAR
+        ))
+    })?;
+
+    // The first hour ends an hour after local midnight, the last at the next one.
+    let start: NaiveDateTime = utc(tz, from) + Duration::hours(1);
+    let end: NaiveDateTime = utc(tz, to + Duration::days(1));
+    let pacer = Pacer::new(options.rate_limit.interval());
+    let mut demand: BTreeMap<NaiveDateTime, f64> = BTreeMap::new();
+    let mut offset: usize = 0;
+    loop {
+        let url: String = demand_url(base_url, &api_key, respondent, start, end, offset);
+        let (page, rows) = parse_demand(&download(client, url, &options.retry, &pacer).await?)?;
+        demand.extend(page);
+        offset += rows;
+        if rows < PAGE_LENGTH {
+            break;
+        }
+    }
+
+    let mut texts: BTreeMap<NaiveDate, String> = days
+        .iter()
+        .map(|day| (*day, format!("{}\n", HEADER)))
+        .collect();
+    for (ending, value) in demand {
+        let beginning = tz.from_utc_datetime(&(ending - Duration::hours(1)));
+        if let Some(text) = texts.get_mut(&beginning.date_naive()) {
+            text.push_str(&format!(
+                "\"{}\",\"{}\",\"{}\",{}\n",
+                beginning.format("%m/%d/%Y %H:%M:%S"),
+                beginning.format("%Z"),
+                respondent,
+                value
+            ));
+        }
+    }
+    for ((_, text), path) in texts.iter().zip(&paths) {
+        save_download(path, text.as_bytes()).await?;
+    }
+    Ok(paths)
+}
+
+/// Blocking wrapper around [`fetch_demand`] for callers without an async runtime,
+/// such as the command line.
+///
+/// # Errors
+///
+/// Returns the errors of [`fetch_demand`], or `MugenError::Io` if the runtime cannot
+/// be started.
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "fetch")]
+pub fn download_demand(
+    base_url: &str,
+    respondent: &str,
+    timezone: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+    dir: &str,
+    options: &FetchOptions,
+) -> Result<Vec<String>> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let client = Client::new();
+    runtime.block_on(fetch_demand(
+        &client, base_url, respondent, timezone, from, to, dir, options,
+    ))
+}
+
+/// An hour where the load a market reported differs from the demand in EIA's data
+/// by more than the allowed share.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Discrepancy {
+    /// Start of the hour, in the market's time.
+    pub at: NaiveDateTime,
+    /// Load the market reported, in MW.
+    pub reported: f64,
+    /// Demand in EIA's data, in MW.
+    pub eia: f64,
+    /// EIA's demand minus the reported load, in MW.
+    pub difference: f64,
+    /// `difference` as a percentage of the reported load.
+    pub percent: f64,
+}
+
+/// Lists the hours of `compared` where EIA's demand differs from the reported load
+/// by more than `threshold` percent of it.
+///
+/// # Arguments
+///
+/// * `compared` - Reported load against EIA's demand, as returned by
+///   [`crate::data::compare_demand`].
+/// * `threshold` - The largest difference accepted, in percent of the reported load.
+///
+/// # Returns
+///
+/// * A `Result` containing the discrepancies in time order, or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::MissingColumn` or `MugenError::ColumnType` if `compared`
+/// lacks the columns of [`crate::data::compare_demand`].
+pub fn discrepancies(compared: &TimeSeriesDataset, threshold: f64) -> Result<Vec<Discrepancy>> {
+    let frame: &str = compared.name();
+    let times = datetime_column(compared.frame(), frame, compared.time_column())?;
+    let reported = f64_column(compared.frame(), frame, "Reported")?;
+    let eia = f64_column(compared.frame(), frame, "EIA")?;
+    Ok(times
+        .into_iter()
+        .zip(reported.into_iter().zip(&eia))
+        .filter_map(|(at, (reported, eia))| {
+            let at: NaiveDateTime = DateTime::from_timestamp_millis(at?)?.naive_utc();
+            let (reported, eia) = (reported?, eia?);
+            let difference: f64 = eia - reported;
+            let percent: f64 = 100.0 * difference / reported;
+            (percent.abs() > threshold).then_some(Discrepancy {
+                at,
+                reported,
+                eia,
+                difference,
+                percent,
+            })
+        })
+        .collect())
+}
+
+/// Parses the IANA timezone `name`.
+#[cfg(feature = "fetch")]
+fn parse_timezone(name: &str) -> Result<Tz> {
+    name.parse()
+        .map_err(|_| MugenError::InvalidArgument(format!("unknown timezone \"{}\"", name)))
+}
+
+/// Returns local midnight at the start of `day` in `tz` as UTC, taking the first
+/// moment of the day if midnight is skipped.
+#[cfg(feature = "fetch")]
+fn utc(tz: Tz, day: NaiveDate) -> NaiveDateTime {
+    let midnight: NaiveDateTime = day.and_hms_opt(0, 0, 0).unwrap_or_default();
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .map_or(midnight, |local| local.naive_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_demand_skips_hours_without_a_value() {
+        let path =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/eia_demand.json");
+        let (demand, rows) = parse_demand(&std::fs::read(path).unwrap()).unwrap();
+        assert_eq!(rows, 5);
+        let hour = |hour: u32| -> NaiveDateTime {
+            NaiveDate::from_ymd_opt(2023, 12, 9)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+        };
+        // Numbers and numeric strings are read; nulls, blanks and bad periods not.
+        assert_eq!(
+            demand.into_iter().collect::<Vec<(NaiveDateTime, f64)>>(),
+            [(hour(5), 15321.0), (hour(6), 14987.0)]
+        );
+    }
+
+    #[test]
+    fn parse_demand_reports_answers_without_data() {
+        let (demand, rows) = parse_demand(br#"{"response": {"data": []}}"#).unwrap();
+        assert!(demand.is_empty());
+        assert_eq!(rows, 0);
+        let refused = br#"{"error": {"code": "API_KEY_INVALID", "message": "An invalid api_key was supplied."}}"#;
+        match parse_demand(refused) {
+            Err(MugenError::NoData(message)) => {
+                assert!(message.contains("An invalid api_key was supplied."))
+            }
+            other => panic!("expected NoData, got {:?}", other),
+        }
+        assert!(matches!(
+            parse_demand(br#"{"error": "No api_key was supplied."}"#),
+            Err(MugenError::NoData(_))
+        ));
+        assert!(matches!(parse_demand(b""), Err(MugenError::NoData(_))));
+        assert!(matches!(
+            parse_demand(b"<html>Bad Gateway</html>"),
+            Err(MugenError::NoData(_))
+        ));
+    }
+}
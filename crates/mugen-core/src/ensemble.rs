@@ -0,0 +1,289 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::dataset::TimeSeriesDataset;
+use crate::error::{MugenError, Result};
+
+/// Name of the dataset an ensemble forecast is returned as.
+const ENSEMBLE_FRAME: &str = "ensemble forecast";
+
+/// How an ensemble weighs its members at each hour.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Blend {
+    /// Every member counts the same.
+    #[default]
+    Mean,
+    /// Each member counts in inverse proportion to its MAPE over the actual load of
+    /// the hours just before, so the members that have lately been closest count
+    /// the most.
+    Skill,
+}
+
+impl fmt::Display for Blend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Blend::Mean => "mean",
+            Blend::Skill => "skill",
+        })
+    }
+}
+
+impl FromStr for Blend {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "mean" => Ok(Blend::Mean),
+            "skill" => Ok(Blend::Skill),
+            _ => Err(format!(
+                "unknown blend \"{}\", expected mean or skill",
+                name
+            )),
+        }
+    }
+}
+
+/// A forecast blended from several, with how much each member counted.
+#[derive(Clone, Debug)]
+pub struct Ensemble {
+    /// The blended forecast, over "Time Stamp" with a "Forecast" column, which
+    /// joins against actual load like any processed forecast.
+    pub forecast: TimeSeriesDataset,
+    /// Each member's weight averaged over the hours it covers, in the order given.
+    pub weights: Vec<f64>,
+}
+
+/// Blends several forecasts of the same zone into one, e.g. to evaluate a blend of
+/// forecast vintages or vendors against each member with the same metrics and
+/// charts.
+///
+/// Each hour is blended from the members that cover it. With [`Blend::Skill`] a
+/// member's weight at an hour is the inverse of its MAPE against `truth` over the
+/// `window` hours before it, so only actual load already known at the time is used;
+/// the members count the same until all of them have some history.
+///
+/// # Arguments
+///
+/// * `members` - The processed forecasts, e.g. as returned by
+///   [`crate::data::process_pred`], in the same timezone.
+/// * `truth` - The processed actual load the skill is measured against.
+/// * `blend` - How to weigh the members.
+/// * `window` - How many hours of history the skill is measured over.
+///
+/// # Returns
+///
+/// * A `Result` containing the blended forecast and the members' weights, or a
+///   `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::InvalidArgument` if there are no members or they are in
+/// different timezones, and an error if a time column is not a datetime or a value
+/// column is not numeric.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use mugen_core::data::{process_pred, process_truth};
+/// use mugen_core::ensemble::{ensemble, Blend};
+/// use mugen_core::synthetic::Synthetic;
+/// use mugen_core::zone::Zone;
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let first = NaiveDate::from_ymd_opt(2023, 12, 8).unwrap();
+/// let days = first..=NaiveDate::from_ymd_opt(2023, 12, 10).unwrap();
+/// let sharp = Synthetic { forecast_error: 0.01, ..Default::default() };
+/// let rough = Synthetic { forecast_error: 0.08, ..Default::default() };
+/// let truth = process_truth(sharp.truth(days.clone())?, Zone::NewYorkCity)?;
+/// let members = vec![
+///     process_pred(sharp.forecast(days.clone())?, Zone::NewYorkCity)?,
+///     process_pred(rough.forecast(days)?, Zone::NewYorkCity)?,
+/// ];
+/// let blended = ensemble(&members, &truth, Blend::Skill, 24)?;
+/// assert!(blended.weights[0] > blended.weights[1]);
+/// println!("weights: {:?}", blended.weights);
+/// # Ok(())
+/// # }
+/// ```
+pub fn ensemble(
+    members: &[TimeSeriesDataset],
+    truth: &TimeSeriesDataset,
+    blend: Blend,
+    window: usize,
+) -> Result<Ensemble> {
+    let first: &TimeSeriesDataset = members.first().ok_or_else(|| {
+        MugenError::InvalidArgument("an ensemble needs at least one forecast".to_string())
+    })?;
+    if let Some(member) = members.iter().find(|member| member.timezone() != first.timezone()) {
+        return Err(MugenError::InvalidArgument(format!(
+            "ensemble members are in {} and {}; convert them to one timezone first",
+            first.timezone(),
+            member.timezone()
+        )));
+    }
+    let values: Vec<HashMap<i64, f64>> = members
+        .iter()
+        .map(|member| member.values_by_timestamp(member.value_column()))
+        .collect::<Result<_>>()?;
+    let actual: HashMap<i64, f64> = truth.values_by_timestamp(truth.value_column())?;
+    let history: Vec<Skill> = values
+        .iter()
+        .map(|forecast| Skill::new(forecast, &actual))
+        .collect();
+
+    let mut hours: BTreeMap<i64, Vec<usize>> = BTreeMap::new();
+    for (index, forecast) in values.iter().enumerate() {
+        for at in forecast.keys() {
+            hours.entry(*at).or_default().push(index);
+        }
+    }
+    let window_ms: i64 = window as i64 * 3_600_000;
+    let mut totals: Vec<(f64, usize)> = vec![(0.0, 0); members.len()];
+    let mut stamps: Vec<i64> = Vec::with_capacity(hours.len());
+    let mut blended: Vec<f64> = Vec::with_capacity(hours.len());
+    for (at, covering) in hours {
+        let skills: Option<Vec<f64>> = match blend {
+            Blend::Mean => None,
+            Blend::Skill => covering
+                .iter()
+                .map(|index| history[*index].mape(at - window_ms, at))
+                .map(|mape| mape.map(|mape| 1.0 / mape.max(1e-6)))
+                .collect(),
+        };
+        let weights: Vec<f64> = skills.unwrap_or_else(|| vec![1.0; covering.len()]);
+        let sum: f64 = weights.iter().sum();
+        let mut value: f64 = 0.0;
+        for (index, weight) in covering.iter().zip(&weights) {
+            value += values[*index][&at] * weight / sum;
+            totals[*index].0 += weight / sum;
+            totals[*index].1 += 1;
+        }
+        stamps.push(at);
+        blended.push(value);
+    }
+
+    let frame: DataFrame = DataFrame::new(vec![
+        Int64Chunked::from_vec("Time Stamp", stamps)
+            .into_datetime(TimeUnit::Milliseconds, None)
+            .into_series(),
+        Series::new("Forecast", blended),
+    ])?;
+    let forecast: TimeSeriesDataset =
+        TimeSeriesDataset::new(ENSEMBLE_FRAME, frame, "Time Stamp", vec!["Forecast".to_string()])?
+            .with_unit(first.unit())
+            .with_timezone(first.timezone());
+    Ok(Ensemble {
+        forecast,
+        weights: totals
+            .into_iter()
+            .map(|(total, hours)| match hours {
+                0 => 0.0,
+                _ => total / hours as f64,
+            })
+            .collect(),
+    })
+}
+
+/// A member's absolute percentage errors over time, for its MAPE over any window.
+struct Skill {
+    /// Hours with both a forecast and actual load, in order.
+    stamps: Vec<i64>,
+    /// Running sums of the absolute percentage errors, one more than `stamps`.
+    sums: Vec<f64>,
+}
+
+impl Skill {
+    fn new(forecast: &HashMap<i64, f64>, actual: &HashMap<i64, f64>) -> Self {
+        let mut errors: Vec<(i64, f64)> = forecast
+            .iter()
+            .filter_map(|(at, forecast)| {
+                let actual: f64 = *actual.get(at)?;
+                Some((*at, ((actual - forecast) / actual).abs() * 100.0))
+            })
+            .filter(|(_, error)| error.is_finite())
+            .collect();
+        errors.sort_by_key(|(at, _)| *at);
+        let mut sums: Vec<f64> = vec![0.0];
+        for (_, error) in &errors {
+            sums.push(sums[sums.len() - 1] + error);
+        }
+        Skill {
+            stamps: errors.into_iter().map(|(at, _)| at).collect(),
+            sums,
+        }
+    }
+
+    /// Returns the MAPE over the hours from `from` up to, not including, `to`, or
+    /// `None` if there are none.
+    fn mape(&self, from: i64, to: i64) -> Option<f64> {
+        let start: usize = self.stamps.partition_point(|at| *at < from);
+        let end: usize = self.stamps.partition_point(|at| *at < to);
+        (end > start).then(|| (self.sums[end] - self.sums[start]) / (end - start) as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::testing::{hourly, midnight};
+
+    /// Two days of hourly load, the same shape every day.
+    fn load() -> Vec<f64> {
+        (0..48).map(|hour| 4000.0 + 50.0 * (hour % 24) as f64).collect()
+    }
+
+    /// Hourly `values` named `name` from the start of 2023, in New York time.
+    fn series(name: &str, values: Vec<f64>) -> TimeSeriesDataset {
+        hourly(name, midnight(2023, 1, 1), vec![Series::new("Load", values)])
+            .with_timezone("America/New_York")
+    }
+
+    #[test]
+    fn skill_weights_sum_to_one_when_a_member_has_no_error() {
+        let truth: TimeSeriesDataset = series("actual", load());
+        let exact: TimeSeriesDataset = series("exact", load());
+        let high: TimeSeriesDataset =
+            series("high", load().into_iter().map(|value| value * 1.1).collect());
+        let blended: Ensemble = ensemble(&[exact, high], &truth, Blend::Skill, 24).unwrap();
+
+        assert!((blended.weights.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(blended.weights[0] > 0.95, "weights {:?}", blended.weights);
+        let forecast = blended.forecast.values("Forecast").unwrap();
+        // The first hour has no history, so both members count the same there.
+        assert!((forecast.get(0).unwrap() - 4000.0 * 1.05).abs() < 1e-6);
+        for (value, actual) in forecast.into_iter().zip(load()).skip(1) {
+            assert!((value.unwrap() - actual).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn mean_blend_averages_the_members() {
+        let truth: TimeSeriesDataset = series("actual", load());
+        let low: TimeSeriesDataset =
+            series("low", load().into_iter().map(|value| value - 100.0).collect());
+        let high: TimeSeriesDataset =
+            series("high", load().into_iter().map(|value| value + 300.0).collect());
+        let blended: Ensemble = ensemble(&[low, high], &truth, Blend::Mean, 24).unwrap();
+        assert_eq!(blended.weights, vec![0.5, 0.5]);
+        let forecast = blended.forecast.values("Forecast").unwrap();
+        for (value, actual) in forecast.into_iter().zip(load()) {
+            assert!((value.unwrap() - (actual + 100.0)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn members_must_share_a_timezone() {
+        let truth: TimeSeriesDataset = series("actual", load());
+        let utc: TimeSeriesDataset = series("utc", load()).with_timezone("UTC");
+        let mismatched = ensemble(&[truth.clone(), utc], &truth, Blend::Mean, 24);
+        assert!(matches!(mismatched, Err(MugenError::InvalidArgument(_))));
+        let empty = ensemble(&[], &truth, Blend::Mean, 24);
+        assert!(matches!(empty, Err(MugenError::InvalidArgument(_))));
+    }
+}
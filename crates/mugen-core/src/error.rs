@@ -0,0 +1,162 @@
+use polars::prelude::PolarsError;
+use thiserror::Error;
+
+/// Everything that can go wrong while loading, processing or charting load data.
+///
+/// Variants carry the file, column or input that caused the failure, so the message
+/// alone says where to look.
+#[derive(Debug, Error)]
+pub enum MugenError {
+    /// A CSV file could not be read or parsed.
+    #[error("failed to read \"{path}\": {source}")]
+    Read {
+        path: String,
+        #[source]
+        source: PolarsError,
+    },
+
+    /// A remote file could not be downloaded, even after retrying.
+    #[cfg(feature = "fetch")]
+    #[error("failed to fetch \"{url}\" after {attempts} attempt(s): {source}")]
+    Fetch {
+        url: String,
+        attempts: u32,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    /// A database could not be opened or queried.
+    #[cfg(feature = "sqlite")]
+    #[error("failed to query database \"{path}\": {source}")]
+    Database {
+        path: String,
+        #[source]
+        source: rusqlite::Error,
+    },
+
+    /// An ONNX model could not be loaded or run.
+    #[cfg(feature = "onnx")]
+    #[error("failed to run model \"{path}\": {source}")]
+    Model {
+        path: String,
+        #[source]
+        source: ort::Error,
+    },
+
+    /// Data from one file could not be combined or processed; the message names the
+    /// row where that is known.
+    #[error("in \"{path}\": {source}")]
+    InFile {
+        path: String,
+        #[source]
+        source: Box<MugenError>,
+    },
+
+    /// A DataFrame lacks a column the operation relies on.
+    #[error(
+        "{frame} data is missing required column \"{column}\", available columns: {}",
+        .available.join(", ")
+    )]
+    MissingColumn {
+        frame: String,
+        column: String,
+        available: Vec<String>,
+    },
+
+    /// A column exists but holds the wrong type of values.
+    #[error("{frame} column \"{column}\" must be {expected}, found {actual}")]
+    ColumnType {
+        frame: String,
+        column: String,
+        expected: String,
+        actual: String,
+    },
+
+    /// A date or time could not be parsed in any of the accepted formats.
+    #[error("invalid date or time \"{input}\", expected one of: {expected}")]
+    Parse {
+        input: String,
+        expected: String,
+        #[source]
+        source: chrono::ParseError,
+    },
+
+    /// A millisecond timestamp lies outside the range `chrono` can represent.
+    #[error("timestamp {0} ms is out of range")]
+    Timestamp(i64),
+
+    /// A file no longer matches the size and hash recorded when it was downloaded.
+    #[error("\"{path}\" failed verification: {message}")]
+    Checksum { path: String, message: String },
+
+    /// A chart or export could not be written to disk.
+    #[error("failed to write \"{path}\": {source}")]
+    Write {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// An email could not be delivered through a mail server.
+    #[error("failed to send email through \"{server}\": {message}")]
+    Email { server: String, message: String },
+
+    /// A config file could not be read or holds invalid settings.
+    #[error("invalid config \"{path}\": {message}")]
+    Config { path: String, message: String },
+
+    /// Settings checked before a run hold one or more problems, each listed with a
+    /// suggested fix.
+    #[error(
+        "invalid settings:{}",
+        .0.iter().map(|problem| format!("\n  - {}", problem)).collect::<String>()
+    )]
+    Settings(Vec<String>),
+
+    /// An argument is outside the range the operation accepts.
+    #[error("{0}")]
+    InvalidArgument(String),
+
+    /// A zone name matches none of the market's load zones.
+    #[error("unknown zone \"{0}\"")]
+    UnknownZone(String),
+
+    /// A market name matches none of the supported markets.
+    #[error("unknown market \"{0}\"")]
+    UnknownMarket(String),
+
+    /// A downloaded archive could not be unpacked.
+    #[error("failed to unpack \"{url}\": {message}")]
+    Archive { url: String, message: String },
+
+    /// The input holds no rows for what was asked.
+    #[error("{0}")]
+    NoData(String),
+
+    /// A renderer failed to draw or encode a chart.
+    #[error("failed to render chart: {0}")]
+    Render(String),
+
+    /// An image could not be read, decoded or encoded.
+    #[error("failed to process image \"{path}\": {source}")]
+    Image {
+        path: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// A chart no longer matches its stored snapshot, or has none yet.
+    #[error("chart does not match snapshot \"{path}\": {message}")]
+    Snapshot { path: String, message: String },
+
+    /// Any other DataFrame operation failed.
+    #[error(transparent)]
+    Polars(#[from] PolarsError),
+
+    /// Any other filesystem or network operation failed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Shorthand for results whose error defaults to [`MugenError`].
+pub type Result<T, E = MugenError> = std::result::Result<T, E>;
@@ -0,0 +1,218 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::data::filter_period;
+use crate::dataset::TimeSeriesDataset;
+use crate::error::{MugenError, Result};
+use crate::zone::Zone;
+
+/// Name of the single file written in each partition directory.
+pub const PARTITION_FILE: &str = "part-0.parquet";
+
+/// File formats tables are exported in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Comma-separated text with a header row, timestamps as "2023-12-01T13:00:00".
+    #[default]
+    Csv,
+    /// Apache Parquet, keeping the column types; needs the `parquet` feature.
+    Parquet,
+}
+
+impl ExportFormat {
+    /// Returns the file extension of the format, e.g. "csv".
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "parquet" => Ok(ExportFormat::Parquet),
+            _ => Err(format!(
+                "unknown format \"{}\", expected csv or parquet",
+                name
+            )),
+        }
+    }
+}
+
+/// Writes the frame of `dataset` to `path` in `format`, creating or replacing the
+/// file, e.g. the actual load joined against the forecast behind a chart, for
+/// analysts who want the numbers.
+///
+/// # Arguments
+///
+/// * `dataset` - The table to write, e.g. as returned by
+///   [`crate::data::join_actual_forecast`].
+/// * `path` - The file to write.
+/// * `format` - The file format.
+///
+/// # Returns
+///
+/// * A `Result` that is empty on success or contains a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::Write` if the file cannot be created, `MugenError::Polars`
+/// if the table cannot be encoded, and `MugenError::InvalidArgument` for Parquet
+/// without the `parquet` feature.
+pub fn write_table(dataset: &TimeSeriesDataset, path: &str, format: ExportFormat) -> Result<()> {
+    let mut frame: DataFrame = dataset.frame().clone();
+    match format {
+        ExportFormat::Csv => CsvWriter::new(create(path)?)
+            .include_header(true)
+            .with_datetime_format(Some("%Y-%m-%dT%H:%M:%S".to_string()))
+            .finish(&mut frame)?,
+        ExportFormat::Parquet => write_parquet(&mut frame, path)?,
+    }
+    Ok(())
+}
+
+/// Writes `zone`'s `dataset` under `root` as Parquet, one file per day, at
+/// hive-style paths such as `zone=n_y_c/date=2023-12-09/part-0.parquet`, so query
+/// engines such as DuckDB, DataFusion or polars read only the partitions a query
+/// needs.
+///
+/// The zone and date live only in the path, as [`Zone::slug`] and the calendar day
+/// of each timestamp; the files hold the columns of `dataset`. Writing a day again
+/// replaces its file, so overlapping exports and re-ingested days do not duplicate
+/// rows.
+///
+/// # Arguments
+///
+/// * `dataset` - One zone's table, e.g. its actual load or its joined actual and
+///   forecast.
+/// * `root` - Directory of the table, e.g. "store/actual"; created if missing.
+/// * `zone` - The zone the rows belong to.
+///
+/// # Returns
+///
+/// * A `Result` containing the paths written, in date order, or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::Write` if a directory or file cannot be created,
+/// `MugenError::Polars` if a day cannot be encoded, and
+/// `MugenError::InvalidArgument` without the `parquet` feature.
+pub fn write_partitioned(
+    dataset: &TimeSeriesDataset,
+    root: &str,
+    zone: Zone,
+) -> Result<Vec<String>> {
+    let days: BTreeSet<NaiveDate> = dataset
+        .timestamps()?
+        .into_iter()
+        .flatten()
+        .filter_map(DateTime::from_timestamp_millis)
+        .map(|at| at.date_naive())
+        .collect();
+    let mut written = Vec::new();
+    for day in days {
+        let dir = partition_dir(root, zone, day);
+        std::fs::create_dir_all(&dir).map_err(|source| MugenError::Write {
+            path: dir.to_string_lossy().into_owned(),
+            source,
+        })?;
+        let path = dir.join(PARTITION_FILE).to_string_lossy().into_owned();
+        let mut frame: DataFrame = filter_period(dataset.clone(), day, day)?.into_frame();
+        write_parquet(&mut frame, &path)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Returns the path of `zone`'s file for `day` in the store at `root`, as written
+/// by [`write_partitioned`], e.g. to check whether a day is already stored.
+pub fn partition_path(root: &str, zone: Zone, day: NaiveDate) -> String {
+    partition_dir(root, zone, day)
+        .join(PARTITION_FILE)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Returns the directory of `zone`'s partition for `day` under `root`.
+fn partition_dir(root: &str, zone: Zone, day: NaiveDate) -> PathBuf {
+    Path::new(root)
+        .join(format!("zone={}", zone.slug()))
+        .join(format!("date={}", day.format("%Y-%m-%d")))
+}
+
+/// Writes `frame` to a Parquet file at `path`.
+#[cfg(feature = "parquet")]
+pub(crate) fn write_parquet(frame: &mut DataFrame, path: &str) -> Result<()> {
+    ParquetWriter::new(create(path)?).finish(frame)?;
+    Ok(())
+}
+
+/// Refuses to write Parquet, before creating any file, in builds without it.
+#[cfg(not(feature = "parquet"))]
+pub(crate) fn write_parquet(_frame: &mut DataFrame, _path: &str) -> Result<()> {
+    Err(MugenError::InvalidArgument(
+        "this build cannot write Parquet; rebuild with the `parquet` feature".to_string(),
+    ))
+}
+
+/// Returns the number of rows in the Parquet file at `path`, from its metadata.
+#[cfg(feature = "parquet")]
+pub(crate) fn parquet_rows(path: &str) -> Result<usize> {
+    let file = File::open(path).map_err(|source| MugenError::InFile {
+        path: path.to_string(),
+        source: Box::new(source.into()),
+    })?;
+    Ok(ParquetReader::new(file).num_rows()?)
+}
+
+/// Refuses to read Parquet in builds without it.
+#[cfg(not(feature = "parquet"))]
+pub(crate) fn parquet_rows(_path: &str) -> Result<usize> {
+    Err(MugenError::InvalidArgument(
+        "this build cannot read Parquet; rebuild with the `parquet` feature".to_string(),
+    ))
+}
+
+/// Scans the Parquet file at `path` into a lazy plan.
+#[cfg(feature = "parquet")]
+pub(crate) fn scan_parquet(path: &str) -> Result<LazyFrame> {
+    LazyFrame::scan_parquet(path, ScanArgsParquet::default()).map_err(|source| MugenError::Read {
+        path: path.to_string(),
+        source,
+    })
+}
+
+/// Refuses to read Parquet in builds without it.
+#[cfg(not(feature = "parquet"))]
+pub(crate) fn scan_parquet(_path: &str) -> Result<LazyFrame> {
+    Err(MugenError::InvalidArgument(
+        "this build cannot read Parquet; rebuild with the `parquet` feature".to_string(),
+    ))
+}
+
+/// Creates or truncates the file at `path`.
+fn create(path: &str) -> Result<File> {
+    File::create(path).map_err(|source| MugenError::Write {
+        path: path.to_string(),
+        source,
+    })
+}
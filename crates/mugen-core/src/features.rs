@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Weekday};
+use polars::prelude::*;
+
+use crate::dataset::TimeSeriesDataset;
+use crate::error::{MugenError, Result};
+
+/// Name of the dataset the features are returned as.
+const FEATURES_FRAME: &str = "features";
+
+/// Lags, in hours, the features hold by default: the same hour one and two days
+/// before, and one week before.
+pub const DEFAULT_LAGS: [usize; 3] = [24, 48, 168];
+
+/// Returns whether `day` is a NERC holiday, on which load runs like a weekend:
+/// New Year's Day, Memorial Day, Independence Day, Labor Day, Thanksgiving or
+/// Christmas, a holiday falling on a Sunday being observed the Monday after.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use mugen_core::features::is_holiday;
+///
+/// assert!(is_holiday(NaiveDate::from_ymd_opt(2023, 11, 23).unwrap()));
+/// assert!(is_holiday(NaiveDate::from_ymd_opt(2023, 1, 2).unwrap()));
+/// assert!(!is_holiday(NaiveDate::from_ymd_opt(2023, 12, 26).unwrap()));
+/// ```
+pub fn is_holiday(day: NaiveDate) -> bool {
+    let fixed = |month: u32, date: u32| {
+        let Some(holiday) = NaiveDate::from_ymd_opt(day.year(), month, date) else {
+            return false;
+        };
+        match holiday.weekday() {
+            Weekday::Sun => day == holiday.succ_opt().unwrap_or(holiday),
+            _ => day == holiday,
+        }
+    };
+    let nth = |month: u32, weekday: Weekday, n: u8| {
+        NaiveDate::from_weekday_of_month_opt(day.year(), month, weekday, n) == Some(day)
+    };
+    let last_monday_of_may: Option<NaiveDate> =
+        NaiveDate::from_weekday_of_month_opt(day.year(), 5, Weekday::Mon, 5)
+            .or_else(|| NaiveDate::from_weekday_of_month_opt(day.year(), 5, Weekday::Mon, 4));
+    fixed(1, 1)
+        || last_monday_of_may == Some(day)
+        || fixed(7, 4)
+        || nth(9, Weekday::Mon, 1)
+        || nth(11, Weekday::Thu, 4)
+        || fixed(12, 25)
+}
+
+/// Builds a model-ready table from processed actual load: per hour, the load the
+/// given `lags` before, calendar features, the temperature if given, and the load
+/// itself as the target.
+///
+/// The columns are "Time Stamp", "Hour" (0 to 23), "Day of Week" (0 for Monday),
+/// "Month" (1 to 12), "Weekend" and "Holiday" (see [`is_holiday`]),
+/// `"Load Lag <n>h"` for each lag, "Temperature" if `temperature` is given, null
+/// at hours without a reading, and "Target". Hours without the load of every lag,
+/// such as the first week with a 168-hour lag, are left out, so every row can train
+/// on.
+///
+/// # Arguments
+///
+/// * `truth` - The processed actual load, as returned by
+///   [`crate::data::process_truth`].
+/// * `temperature` - Hourly temperatures in the same timezone, as returned by
+///   [`crate::data::process_weather`].
+/// * `lags` - Hours before each hour to take the load of, e.g. [`DEFAULT_LAGS`].
+///
+/// # Returns
+///
+/// * A `Result` containing the features in time order, or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::InvalidArgument` if a lag is 0, and an error if a time
+/// column is not a datetime or a value column is not numeric.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use mugen_core::data::process_truth;
+/// use mugen_core::features::{features, DEFAULT_LAGS};
+/// use mugen_core::synthetic::Synthetic;
+/// use mugen_core::zone::Zone;
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let first = NaiveDate::from_ymd_opt(2023, 12, 1).unwrap();
+/// let last = NaiveDate::from_ymd_opt(2023, 12, 10).unwrap();
+/// let truth = process_truth(Synthetic::default().truth(first..=last)?, Zone::NewYorkCity)?;
+/// let table = features(&truth, None, &DEFAULT_LAGS)?;
+/// // The first week has no load a week before.
+/// assert_eq!(table.height(), truth.height() - 168);
+/// # Ok(())
+/// # }
+/// ```
+pub fn features(
+    truth: &TimeSeriesDataset,
+    temperature: Option<&TimeSeriesDataset>,
+    lags: &[usize],
+) -> Result<TimeSeriesDataset> {
+    if lags.contains(&0) {
+        return Err(MugenError::InvalidArgument(
+            "a lag of 0 hours is the target itself; use 1 or more".to_string(),
+        ));
+    }
+    let load: HashMap<i64, f64> = truth.values_by_timestamp(truth.value_column())?;
+    let temperatures: Option<HashMap<i64, f64>> = temperature
+        .map(|temperature| temperature.values_by_timestamp(temperature.value_column()))
+        .transpose()?;
+    let mut hours: Vec<(i64, f64)> = load.iter().map(|(at, load)| (*at, *load)).collect();
+    hours.sort_by_key(|(at, _)| *at);
+
+    let mut stamps: Vec<i64> = Vec::with_capacity(hours.len());
+    let mut hour_of_day: Vec<u32> = Vec::with_capacity(hours.len());
+    let mut day_of_week: Vec<u32> = Vec::with_capacity(hours.len());
+    let mut month: Vec<u32> = Vec::with_capacity(hours.len());
+    let mut weekend: Vec<bool> = Vec::with_capacity(hours.len());
+    let mut holiday: Vec<bool> = Vec::with_capacity(hours.len());
+    let mut lagged: Vec<Vec<f64>> = vec![Vec::with_capacity(hours.len()); lags.len()];
+    let mut temperature_values: Vec<Option<f64>> = Vec::with_capacity(hours.len());
+    let mut target: Vec<f64> = Vec::with_capacity(hours.len());
+    for (at, value) in hours {
+        let Some(time) = DateTime::from_timestamp_millis(at).map(|at| at.naive_utc()) else {
+            continue;
+        };
+        let before: Option<Vec<f64>> = lags
+            .iter()
+            .map(|lag| load.get(&(at - *lag as i64 * 3_600_000)).copied())
+            .collect();
+        let Some(before) = before else {
+            continue;
+        };
+        stamps.push(at);
+        hour_of_day.push(time.hour());
+        day_of_week.push(time.weekday().num_days_from_monday());
+        month.push(time.month());
+        weekend.push(time.weekday().num_days_from_monday() >= 5);
+        holiday.push(is_holiday(time.date()));
+        for (column, value) in lagged.iter_mut().zip(before) {
+            column.push(value);
+        }
+        if let Some(temperatures) = &temperatures {
+            temperature_values.push(temperatures.get(&at).copied());
+        }
+        target.push(value);
+    }
+
+    let mut columns: Vec<Series> = vec![
+        Int64Chunked::from_vec("Time Stamp", stamps)
+            .into_datetime(TimeUnit::Milliseconds, None)
+            .into_series(),
+        Series::new("Hour", hour_of_day),
+        Series::new("Day of Week", day_of_week),
+        Series::new("Month", month),
+        Series::new("Weekend", weekend),
+        Series::new("Holiday", holiday),
+    ];
+    for (lag, values) in lags.iter().zip(lagged) {
+        columns.push(Series::new(&format!("Load Lag {}h", lag), values));
+    }
+    if temperatures.is_some() {
+        columns.push(Series::new("Temperature", temperature_values));
+    }
+    columns.push(Series::new("Target", target));
+    Ok(TimeSeriesDataset::new(
+        FEATURES_FRAME,
+        DataFrame::new(columns)?,
+        "Time Stamp",
+        vec!["Target".to_string()],
+    )?
+    .with_unit(truth.unit())
+    .with_timezone(truth.timezone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::testing::{dataset, midnight};
+    use crate::dataset::HOUR_MS;
+
+    /// Returns midnight at the start of `day` in November 2023, in milliseconds.
+    fn november(day: u32) -> i64 {
+        midnight(2023, 11, day)
+    }
+
+    /// `hours` hourly values named `column` from midnight on 22 November 2023, each
+    /// its own hour's index, leaving out the hours in `missing`.
+    fn hourly(column: &str, hours: i64, missing: &[i64]) -> TimeSeriesDataset {
+        let kept: Vec<i64> = (0..hours).filter(|hour| !missing.contains(hour)).collect();
+        dataset(
+            column,
+            kept.iter()
+                .map(|hour| november(22) + hour * HOUR_MS)
+                .collect(),
+            vec![Series::new(
+                column,
+                kept.iter().map(|hour| *hour as f64).collect::<Vec<f64>>(),
+            )],
+        )
+    }
+
+    #[test]
+    fn lags_line_up_with_the_hour_they_precede() {
+        let table: TimeSeriesDataset = features(&hourly("Load", 96, &[]), None, &[24]).unwrap();
+        assert_eq!(table.height(), 72);
+        let stamps: Vec<i64> = table.unique_timestamps().unwrap();
+        assert_eq!(stamps[0], november(23));
+        let lag = table.values("Load Lag 24h").unwrap();
+        let target = table.values("Target").unwrap();
+        for (lag, target) in lag.into_iter().zip(&target) {
+            assert_eq!(target.unwrap() - lag.unwrap(), 24.0);
+        }
+    }
+
+    #[test]
+    fn hours_missing_a_lag_are_left_out() {
+        let table: TimeSeriesDataset = features(&hourly("Load", 72, &[30]), None, &[24]).unwrap();
+        let stamps: Vec<i64> = table.unique_timestamps().unwrap();
+        assert_eq!(stamps.len(), 72 - 24 - 2);
+        assert!(!stamps.contains(&(november(22) + 30 * HOUR_MS)));
+        assert!(!stamps.contains(&(november(22) + 54 * HOUR_MS)));
+    }
+
+    #[test]
+    fn calendar_flags_mark_thanksgiving_and_the_weekend() {
+        let temperature: TimeSeriesDataset = hourly("Temperature", 120, &[40]);
+        let table: TimeSeriesDataset =
+            features(&hourly("Load", 120, &[]), Some(&temperature), &[24]).unwrap();
+        let frame: &DataFrame = table.frame();
+        let flags = |name: &str| -> Vec<bool> {
+            frame
+                .column(name)
+                .unwrap()
+                .bool()
+                .unwrap()
+                .into_no_null_iter()
+                .collect()
+        };
+        let (holiday, weekend) = (flags("Holiday"), flags("Weekend"));
+        for (row, at) in table.unique_timestamps().unwrap().into_iter().enumerate() {
+            assert_eq!(holiday[row], (november(23)..november(24)).contains(&at));
+            assert_eq!(weekend[row], at >= november(25));
+        }
+        let temperatures = table.values("Temperature").unwrap();
+        assert_eq!(temperatures.null_count(), 1);
+        assert_eq!(temperatures.get(40 - 24), None);
+    }
+
+    #[test]
+    fn observed_holidays() {
+        let day = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        assert!(is_holiday(day(2021, 7, 5)));
+        assert!(!is_holiday(day(2021, 7, 4)));
+        assert!(is_holiday(day(2024, 5, 27)));
+        assert!(!is_holiday(day(2024, 5, 20)));
+        assert!(is_holiday(day(2023, 9, 4)));
+    }
+
+    #[test]
+    fn a_lag_of_zero_is_rejected() {
+        let result = features(&hourly("Load", 48, &[]), None, &[0, 24]);
+        assert!(matches!(result, Err(MugenError::InvalidArgument(_))));
+    }
+}
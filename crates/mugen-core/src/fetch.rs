@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use chrono::NaiveDate;
+
+#[cfg(feature = "fetch")]
+mod client;
+
+#[cfg(feature = "fetch")]
+pub(crate) use self::client::{download, is_downloaded, save_download, Pacer};
+#[cfg(feature = "fetch")]
+pub use self::client::{download_days, download_market_days, fetch_days, fetch_market_days};
+
+/// Root of NYISO's public CSV archive.
+pub const NYISO_BASE_URL: &str = "http://mis.nyiso.com/public/csv";
+
+/// Environment variable holding the ISO Express user name for ISO-NE's web services.
+pub const ISO_NE_USERNAME_VAR: &str = "ISO_NE_USERNAME";
+
+/// Environment variable holding the ISO Express password for ISO-NE's web services.
+pub const ISO_NE_PASSWORD_VAR: &str = "ISO_NE_PASSWORD";
+
+/// Environment variable holding the security token for the ENTSO-E transparency
+/// platform's API, issued on request from an account's settings.
+pub const ENTSO_E_TOKEN_VAR: &str = "ENTSO_E_SECURITY_TOKEN";
+
+/// How downloads retry failures that are likely temporary, such as NYISO answering
+/// 503 while it publishes the day's files, timeouts and dropped connections.
+///
+/// Each retry waits twice as long as the one before, up to `max_backoff`. A
+/// `Retry-After` header in seconds from the server is honored instead, up to the
+/// same limit. Client errors such as 404 fail at once.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts per file, including the first; 1 disables retries.
+    pub attempts: u32,
+    /// Wait before the first retry.
+    pub initial_backoff: Duration,
+    /// Longest wait between two attempts.
+    pub max_backoff: Duration,
+    /// Limit on each attempt, from sending the request to reading the whole file.
+    pub timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 4,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the wait before retry number `retry`, counting from 0.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+}
+
+/// Caps how hard downloads hit the server, so long backfills do not get the client
+/// blocked.
+///
+/// Requests are spaced evenly at `requests_per_second`, counting every attempt
+/// including retries, across all downloads running at once.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateLimit {
+    /// Most requests started per second; 0 for no limit.
+    pub requests_per_second: f64,
+    /// Most downloads running at once; at least one always runs.
+    pub concurrency: usize,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        RateLimit {
+            requests_per_second: 5.0,
+            concurrency: 8,
+        }
+    }
+}
+
+impl RateLimit {
+    /// Returns the time between the starts of two requests.
+    pub fn interval(&self) -> Duration {
+        if self.requests_per_second > 0.0 && self.requests_per_second.is_finite() {
+            Duration::from_secs_f64(1.0 / self.requests_per_second)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+/// How downloads are retried and paced.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FetchOptions {
+    /// How failed downloads are retried.
+    pub retry: RetryPolicy,
+    /// How fast and how many downloads run at once.
+    pub rate_limit: RateLimit,
+}
+
+/// A daily NYISO report published as one CSV per day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Report {
+    /// Integrated real-time actual load, `YYYYMMDDpalIntegrated.csv`.
+    ActualLoad,
+    /// Day-ahead load forecast, `YYYYMMDDisolf.csv`.
+    LoadForecast,
+    /// Real-time actual load every five minutes, `YYYYMMDDpal.csv`.
+    RealTimeLoad,
+    /// Hourly day-ahead zonal price (LBMP), `YYYYMMDDdamlbmp_zone.csv`.
+    DayAheadPrice,
+    /// Real-time zonal price (LBMP) every five minutes, `YYYYMMDDrealtime_zone.csv`.
+    RealTimePrice,
+}
+
+impl Report {
+    /// Returns the file suffix NYISO names the report by.
+    pub fn name(self) -> &'static str {
+        match self {
+            Report::ActualLoad => "palIntegrated",
+            Report::LoadForecast => "isolf",
+            Report::RealTimeLoad => "pal",
+            Report::DayAheadPrice => "damlbmp_zone",
+            Report::RealTimePrice => "realtime_zone",
+        }
+    }
+
+    /// Returns the archive directory NYISO files the report under, which is the file
+    /// suffix for load reports but not for prices.
+    pub fn directory(self) -> &'static str {
+        match self {
+            Report::DayAheadPrice => "damlbmp",
+            Report::RealTimePrice => "realtime",
+            _ => self.name(),
+        }
+    }
+
+    /// Returns the file name of the report for `day`, e.g. "20231209isolf.csv".
+    pub fn file_name(self, day: NaiveDate) -> String {
+        format!("{}{}.csv", day.format("%Y%m%d"), self.name())
+    }
+
+    /// Returns the URL of the report for `day` under `base_url`.
+    pub fn url(self, base_url: &str, day: NaiveDate) -> String {
+        format!(
+            "{}/{}/{}",
+            base_url.trim_end_matches('/'),
+            self.directory(),
+            self.file_name(day)
+        )
+    }
+}
@@ -0,0 +1,1291 @@
+use std::collections::BTreeMap;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use calamine::{Data, Reader};
+use chrono::{DateTime, Duration as TimeDelta, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+use chrono_tz::Tz;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+use tracing::warn;
+use zip::ZipArchive;
+
+use crate::checksum::{self, FileDigest};
+use crate::error::{MugenError, Result};
+use crate::iso::{EntsoE, FileKind, Iso, IsoNe, IsoZone, Market, Miso, Nyiso};
+
+use super::{
+    FetchOptions, Report, RetryPolicy, ENTSO_E_TOKEN_VAR, ISO_NE_PASSWORD_VAR, ISO_NE_USERNAME_VAR,
+};
+
+/// Downloads the daily `report` files for `from..=to` into `dir`, several at a time.
+///
+/// Files already in `dir` are kept and not downloaded again, so re-running after a
+/// partial failure only fetches what is missing. Each download's size and hash are
+/// recorded next to it, see [`crate::checksum`], before it is moved into place; a
+/// file without a record, left by a download that was cut off, or that no longer
+/// matches its record is downloaded again. Downloads are paced and temporary
+/// failures retried per file as `options` says.
+///
+/// # Arguments
+///
+/// * `client` - The HTTP client to download with.
+/// * `base_url` - Root of the archive, normally [`NYISO_BASE_URL`](super::NYISO_BASE_URL).
+/// * `report` - Which daily report to download.
+/// * `from` - The first day to download.
+/// * `to` - The last day to download, inclusive.
+/// * `dir` - The directory to save into; created on demand.
+/// * `options` - How downloads are retried and paced.
+///
+/// # Returns
+///
+/// * A `Result` containing the local paths of every day's file, in date order, or a
+///   `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::Fetch` if a request still fails or the server still answers
+/// with an error status after retrying, and `MugenError::Write` if a file cannot be
+/// saved. Files finished before the failure stay on disk.
+pub async fn fetch_days(
+    client: &Client,
+    base_url: &str,
+    report: Report,
+    from: NaiveDate,
+    to: NaiveDate,
+    dir: &str,
+    options: &FetchOptions,
+) -> Result<Vec<String>> {
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|source| MugenError::Write {
+            path: dir.to_string(),
+            source,
+        })?;
+
+    let days: Vec<NaiveDate> = from.iter_days().take_while(|day| *day <= to).collect();
+    let pacer = Pacer::new(options.rate_limit.interval());
+    stream::iter(days)
+        .map(|day| {
+            let path = Path::new(dir)
+                .join(report.file_name(day))
+                .to_string_lossy()
+                .to_string();
+            fetch_file(
+                client,
+                report.url(base_url, day),
+                path,
+                &options.retry,
+                &pacer,
+            )
+        })
+        .buffered(options.rate_limit.concurrency.max(1))
+        .try_collect()
+        .await
+}
+
+/// Blocking wrapper around [`fetch_days`] for callers without an async runtime, such
+/// as the command line.
+///
+/// # Errors
+///
+/// Returns the errors of [`fetch_days`], or `MugenError::Io` if the runtime cannot be
+/// started.
+pub fn download_days(
+    base_url: &str,
+    report: Report,
+    from: NaiveDate,
+    to: NaiveDate,
+    dir: &str,
+    options: &FetchOptions,
+) -> Result<Vec<String>> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let client = Client::new();
+    runtime.block_on(fetch_days(
+        &client, base_url, report, from, to, dir, options,
+    ))
+}
+
+/// Downloads `market`'s daily files of `kind` for `from..=to` into `dir`, like
+/// [`fetch_days`] does for one NYISO report, named by [`Iso::file_name`].
+///
+/// NYISO files are downloaded from [`Iso::url`]. ERCOT's are looked up in the list
+/// of the report's documents: the actual load of a day is the file published the
+/// day after, and the forecast of a day the last one published on it. ERCOT only
+/// lists its last few days of postings, so older days cannot be downloaded this
+/// way. Its files are unzipped before they are saved. ISO-NE's web services are
+/// asked for each zone's hourly demand and the day's zonal forecast, logging in
+/// with the ISO Express account in [`ISO_NE_USERNAME_VAR`] and
+/// [`ISO_NE_PASSWORD_VAR`], and the answers are saved as CSVs laid out as
+/// [`IsoNe`] describes; the latest forecast of each hour is kept. The ENTSO-E
+/// platform is asked for each bidding zone's load of the day with the security
+/// token in [`ENTSO_E_TOKEN_VAR`], and its XML answers are averaged into hours and
+/// saved as CSVs laid out as [`EntsoE`] describes. MISO's zonal and regional
+/// workbooks of each day are read and saved together as one CSV, as [`Miso`]
+/// describes, which serves as both the actual-load and the forecast file.
+///
+/// # Arguments
+///
+/// * `client` - The HTTP client to download with.
+/// * `market` - The market to download from.
+/// * `kind` - Whether to download actual load or forecasts.
+/// * `from` - The first day to download.
+/// * `to` - The last day to download, inclusive.
+/// * `dir` - The directory to save into; created on demand.
+/// * `options` - How downloads are retried and paced.
+///
+/// # Returns
+///
+/// * A `Result` containing the local paths of every day's file, in date order, or a
+///   `MugenError`.
+///
+/// # Errors
+///
+/// Returns the errors of [`fetch_days`], `MugenError::NoData` if ERCOT lists no
+/// file for a day or ISO-NE, ENTSO-E or MISO has no data for it,
+/// `MugenError::Archive` if a downloaded file cannot be unzipped or parsed, and
+/// `MugenError::InvalidArgument` if the ISO-NE login or ENTSO-E token is not set.
+pub async fn fetch_market_days(
+    client: &Client,
+    market: Market,
+    kind: FileKind,
+    from: NaiveDate,
+    to: NaiveDate,
+    dir: &str,
+    options: &FetchOptions,
+) -> Result<Vec<String>> {
+    let iso: &dyn Iso = market.iso();
+    match market {
+        Market::Nyiso => {
+            fetch_days(
+                client,
+                iso.base_url(),
+                Nyiso::report(kind),
+                from,
+                to,
+                dir,
+                options,
+            )
+            .await
+        }
+        Market::Ercot => {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|source| MugenError::Write {
+                    path: dir.to_string(),
+                    source,
+                })?;
+            let pacer = Pacer::new(options.rate_limit.interval());
+            let list_url: String = iso.url(iso.base_url(), kind, from);
+            let listing: Vec<u8> =
+                download(client, list_url.clone(), &options.retry, &pacer).await?;
+            let documents: Vec<ErcotDocument> = ErcotDocument::list(&listing, &list_url)?;
+            let days: Vec<NaiveDate> = from.iter_days().take_while(|day| *day <= to).collect();
+            stream::iter(days)
+                .map(|day| {
+                    let path = Path::new(dir)
+                        .join(iso.file_name(kind, day))
+                        .to_string_lossy()
+                        .to_string();
+                    let document: Option<&ErcotDocument> =
+                        ErcotDocument::of_day(&documents, kind, day);
+                    let pacer = &pacer;
+                    async move {
+                        let document = document.ok_or_else(|| {
+                            MugenError::NoData(format!(
+                                "ERCOT lists no {} file for {}; it only keeps recent days",
+                                kind.frame(),
+                                day
+                            ))
+                        })?;
+                        fetch_zipped(
+                            client,
+                            document.url(iso.base_url()),
+                            path,
+                            &options.retry,
+                            pacer,
+                        )
+                        .await
+                    }
+                })
+                .buffered(options.rate_limit.concurrency.max(1))
+                .try_collect()
+                .await
+        }
+        Market::IsoNe => {
+            let login = Login::from_env(ISO_NE_USERNAME_VAR, ISO_NE_PASSWORD_VAR, "ISO-NE")?;
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|source| MugenError::Write {
+                    path: dir.to_string(),
+                    source,
+                })?;
+            let pacer = Pacer::new(options.rate_limit.interval());
+            let days: Vec<NaiveDate> = from.iter_days().take_while(|day| *day <= to).collect();
+            stream::iter(days)
+                .map(|day| {
+                    let path = Path::new(dir)
+                        .join(iso.file_name(kind, day))
+                        .to_string_lossy()
+                        .to_string();
+                    let resource: String = iso.url(iso.base_url(), kind, day);
+                    fetch_iso_ne_day(client, resource, kind, path, &options.retry, &pacer, &login)
+                })
+                .buffered(options.rate_limit.concurrency.max(1))
+                .try_collect()
+                .await
+        }
+        Market::EntsoE => {
+            let token: String = std::env::var(ENTSO_E_TOKEN_VAR).map_err(|_| {
+                MugenError::InvalidArgument(format!(
+                    "downloading from ENTSO-E needs {} set",
+                    ENTSO_E_TOKEN_VAR
+                ))
+            })?;
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|source| MugenError::Write {
+                    path: dir.to_string(),
+                    source,
+                })?;
+            let pacer = Pacer::new(options.rate_limit.interval());
+            let days: Vec<NaiveDate> = from.iter_days().take_while(|day| *day <= to).collect();
+            stream::iter(days)
+                .map(|day| {
+                    let path = Path::new(dir)
+                        .join(iso.file_name(kind, day))
+                        .to_string_lossy()
+                        .to_string();
+                    let query: String = iso.url(iso.base_url(), kind, day);
+                    fetch_entsoe_day(client, query, day, path, &options.retry, &pacer, &token)
+                })
+                .buffered(options.rate_limit.concurrency.max(1))
+                .try_collect()
+                .await
+        }
+        Market::Miso => {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|source| MugenError::Write {
+                    path: dir.to_string(),
+                    source,
+                })?;
+            let pacer = Pacer::new(options.rate_limit.interval());
+            let days: Vec<NaiveDate> = from.iter_days().take_while(|day| *day <= to).collect();
+            stream::iter(days)
+                .map(|day| {
+                    let path = Path::new(dir)
+                        .join(iso.file_name(kind, day))
+                        .to_string_lossy()
+                        .to_string();
+                    fetch_miso_day(client, iso.base_url(), day, path, &options.retry, &pacer)
+                })
+                .buffered(options.rate_limit.concurrency.max(1))
+                .try_collect()
+                .await
+        }
+    }
+}
+
+/// A user name and password sent with every request to a service that needs them.
+struct Login {
+    username: String,
+    password: String,
+}
+
+impl Login {
+    /// Reads the login from the environment variables `username` and `password`;
+    /// `service` names what it is for in the error.
+    fn from_env(username: &str, password: &str, service: &str) -> Result<Self> {
+        match (std::env::var(username), std::env::var(password)) {
+            (Ok(username), Ok(password)) => Ok(Login { username, password }),
+            _ => Err(MugenError::InvalidArgument(format!(
+                "downloading from {} needs {} and {} set",
+                service, username, password
+            ))),
+        }
+    }
+}
+
+/// Saves ISO-NE's load of `kind` for one day, from the web service `resource`, to
+/// `path` as CSV and records its digest, unless `path` already exists and matches
+/// its recorded digest, returning `path`.
+async fn fetch_iso_ne_day(
+    client: &Client,
+    resource: String,
+    kind: FileKind,
+    path: String,
+    retry: &RetryPolicy,
+    pacer: &Pacer,
+    login: &Login,
+) -> Result<String> {
+    if is_downloaded(&path) {
+        return Ok(path);
+    }
+    // The latest load of each hour and zone, with when it was issued.
+    let mut hours: BTreeMap<(NaiveDateTime, &str), (String, f64)> = BTreeMap::new();
+    let requests: Vec<(String, Option<IsoZone>)> = match kind {
+        FileKind::Actual => IsoNe
+            .zones()
+            .into_iter()
+            .filter_map(|zone| {
+                let location: u32 = IsoNe::location(&zone)?;
+                Some((
+                    format!("{}/location/{}.json", resource, location),
+                    Some(zone),
+                ))
+            })
+            .collect(),
+        FileKind::Forecast => vec![(format!("{}.json", resource), None)],
+    };
+    for (url, zone) in requests {
+        let body: Vec<u8> = download_as(client, url.clone(), retry, pacer, Some(login)).await?;
+        for entry in iso_ne_entries(&body, &url)? {
+            let Some(zone) = zone.or_else(|| entry.location.and_then(IsoNe::zone_at)) else {
+                continue;
+            };
+            let key = (entry.begin, zone.actual_name);
+            if hours
+                .get(&key)
+                .is_none_or(|(issued, _)| *issued <= entry.issued)
+            {
+                hours.insert(key, (entry.issued, entry.load));
+            }
+        }
+    }
+    if hours.is_empty() {
+        return Err(MugenError::NoData(format!(
+            "ISO-NE has no {} for {}",
+            kind.frame(),
+            resource
+        )));
+    }
+    let mut body = String::from("Time Stamp,Zone,Load\n");
+    for ((begin, zone), (_, load)) in &hours {
+        body.push_str(&format!(
+            "{},{},{}\n",
+            begin.format("%m/%d/%Y %H:%M:%S"),
+            zone,
+            load
+        ));
+    }
+    save_download(&path, body.as_bytes()).await?;
+    Ok(path)
+}
+
+/// One hour of load in an answer of ISO-NE's web services.
+struct IsoNeEntry {
+    /// Start of the hour, in local time.
+    begin: NaiveDateTime,
+    /// Location id of the zone, when the answer covers several.
+    location: Option<u32>,
+    /// When a forecast was issued, empty for actual load.
+    issued: String,
+    load: f64,
+}
+
+/// Parses the hours out of the JSON `body` downloaded from `url`. The answer wraps
+/// its entries in two objects named after the resource, e.g.
+/// `{"HourlyRtDemands": {"HourlyRtDemand": [...]}}`, and gives a single entry as an
+/// object instead of an array.
+fn iso_ne_entries(body: &[u8], url: &str) -> Result<Vec<IsoNeEntry>> {
+    let invalid = |message: String| MugenError::Archive {
+        url: url.to_string(),
+        message,
+    };
+    let json: Value = serde_json::from_slice(body).map_err(|err| invalid(err.to_string()))?;
+    let inner = |value: &Value| -> Option<Value> { value.as_object()?.values().next().cloned() };
+    let entries: Vec<Value> = match inner(&json).as_ref().and_then(inner) {
+        Some(Value::Array(entries)) => entries,
+        Some(entry @ Value::Object(_)) => vec![entry],
+        // An empty answer is an empty string or object in place of the entries.
+        _ => Vec::new(),
+    };
+    let number = |value: &Value| -> Option<f64> {
+        value
+            .as_f64()
+            .or_else(|| value.as_str()?.trim().parse().ok())
+    };
+    Ok(entries
+        .iter()
+        .filter_map(|entry| {
+            let begin = DateTime::parse_from_rfc3339(entry["BeginDate"].as_str()?).ok()?;
+            let location: Option<f64> = number(&entry["Location"]["@LocId"])
+                .or_else(|| number(&entry["ReliabilityRegionLocId"]));
+            Some(IsoNeEntry {
+                begin: begin.naive_local(),
+                location: location.map(|id| id as u32),
+                issued: entry["CreationDate"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                load: number(&entry["Load"]).or_else(|| number(&entry["LoadMw"]))?,
+            })
+        })
+        .collect())
+}
+
+/// Saves ENTSO-E's load for `day` in every bidding zone, asked for with `query` and
+/// `token`, to `path` as CSV and records its digest, unless `path` already exists
+/// and matches its recorded digest, returning `path`.
+async fn fetch_entsoe_day(
+    client: &Client,
+    query: String,
+    day: NaiveDate,
+    path: String,
+    retry: &RetryPolicy,
+    pacer: &Pacer,
+    token: &str,
+) -> Result<String> {
+    if is_downloaded(&path) {
+        return Ok(path);
+    }
+    // The mean load of each hour and zone, by the hour's start in UTC.
+    let mut hours: BTreeMap<(NaiveDateTime, &str), (f64, u32)> = BTreeMap::new();
+    for zone in EntsoE.zones() {
+        let Some(eic) = EntsoE::eic(&zone) else {
+            continue;
+        };
+        let url: String = format!(
+            "{}&outBiddingZone_Domain={}&securityToken={}",
+            query, eic, token
+        );
+        let body: Vec<u8> = download(client, url, retry, pacer).await?;
+        for (start, load) in entsoe_points(&body, &query)? {
+            let hour: NaiveDateTime = start.with_minute(0).unwrap_or(start);
+            let (sum, count) = hours.entry((hour, zone.actual_name)).or_insert((0.0, 0));
+            *sum += load;
+            *count += 1;
+        }
+    }
+    let mut body = String::from("Time Stamp,Bidding Zone,Load\n");
+    for ((hour, zone), (sum, count)) in &hours {
+        let local: NaiveDateTime = Tz::CET.from_utc_datetime(hour).naive_local();
+        if local.date() != day {
+            continue;
+        }
+        body.push_str(&format!(
+            "{},{},{}\n",
+            local.format("%m/%d/%Y %H:%M:%S"),
+            zone,
+            sum / *count as f64
+        ));
+    }
+    if body.lines().count() == 1 {
+        return Err(MugenError::NoData(format!(
+            "ENTSO-E has no load for {}",
+            day.format("%Y-%m-%d")
+        )));
+    }
+    save_download(&path, body.as_bytes()).await?;
+    Ok(path)
+}
+
+/// Parses the values out of the XML `body` the ENTSO-E platform answered `url`
+/// with, each with the start of its interval in UTC.
+///
+/// Each period gives its start, end and resolution, e.g. "PT15M", and numbers its
+/// points from 1; a point left out repeats the one before it. An acknowledgement
+/// that no data matched is an empty answer.
+fn entsoe_points(body: &[u8], url: &str) -> Result<Vec<(NaiveDateTime, f64)>> {
+    let invalid = |message: String| MugenError::Archive {
+        url: url.to_string(),
+        message,
+    };
+    let text: &str = std::str::from_utf8(body).map_err(|err| invalid(err.to_string()))?;
+    let document = roxmltree::Document::parse(text).map_err(|err| invalid(err.to_string()))?;
+    let child = |node: roxmltree::Node<'_, '_>, name: &str| -> Option<String> {
+        node.children()
+            .find(|child| child.has_tag_name(name))
+            .and_then(|child| child.text())
+            .map(|text| text.trim().to_string())
+    };
+    let root = document.root_element();
+    if root.has_tag_name("Acknowledgement_MarketDocument") {
+        let reason = root.children().find(|child| child.has_tag_name("Reason"));
+        let code: Option<String> = reason.and_then(|reason| child(reason, "code"));
+        if code.as_deref() == Some("999") {
+            return Ok(Vec::new());
+        }
+        return Err(invalid(
+            reason
+                .and_then(|reason| child(reason, "text"))
+                .unwrap_or_else(|| "the request was refused".to_string()),
+        ));
+    }
+    let time = |text: Option<String>| -> Option<NaiveDateTime> {
+        NaiveDateTime::parse_from_str(&text?, "%Y-%m-%dT%H:%MZ").ok()
+    };
+    let mut points: Vec<(NaiveDateTime, f64)> = Vec::new();
+    for period in root
+        .descendants()
+        .filter(|node| node.has_tag_name("Period"))
+    {
+        let interval = period
+            .children()
+            .find(|child| child.has_tag_name("timeInterval"));
+        let (Some(start), Some(end)) = (
+            time(interval.and_then(|interval| child(interval, "start"))),
+            time(interval.and_then(|interval| child(interval, "end"))),
+        ) else {
+            return Err(invalid("a period has no valid time interval".to_string()));
+        };
+        let resolution: Option<String> = child(period, "resolution");
+        let minutes: i64 = match resolution.as_deref() {
+            Some("PT15M") => 15,
+            Some("PT30M") => 30,
+            Some("PT60M" | "PT1H") => 60,
+            other => {
+                return Err(invalid(format!(
+                    "unsupported resolution {}",
+                    other.unwrap_or("(none)")
+                )))
+            }
+        };
+        let values: BTreeMap<i64, f64> = period
+            .children()
+            .filter(|child| child.has_tag_name("Point"))
+            .filter_map(|point| {
+                Some((
+                    child(point, "position")?.parse().ok()?,
+                    child(point, "quantity")?.parse().ok()?,
+                ))
+            })
+            .collect();
+        let mut last: Option<f64> = None;
+        for position in 1..=(end - start).num_minutes() / minutes {
+            last = values.get(&position).copied().or(last);
+            if let Some(load) = last {
+                points.push((start + TimeDelta::minutes((position - 1) * minutes), load));
+            }
+        }
+    }
+    Ok(points)
+}
+
+/// Saves MISO's load for `day`, read from the workbooks of its zonal and regional
+/// reports under `base_url`, to `path` as CSV and records its digest, unless `path`
+/// already exists and matches its recorded digest, returning `path`.
+///
+/// Each row of the zonal report gets the columns of the regional report it lacks,
+/// those of the regions, from the regional row of the same hour.
+async fn fetch_miso_day(
+    client: &Client,
+    base_url: &str,
+    day: NaiveDate,
+    path: String,
+    retry: &RetryPolicy,
+    pacer: &Pacer,
+) -> Result<String> {
+    if is_downloaded(&path) {
+        return Ok(path);
+    }
+    let mut tables: Vec<MisoTable> = Vec::new();
+    for url in [
+        Miso.url(base_url, FileKind::Actual, day),
+        Miso::regional_url(base_url, day),
+    ] {
+        let workbook: Vec<u8> = download(client, url.clone(), retry, pacer).await?;
+        tables.push(MisoTable::read(&workbook, &url)?);
+    }
+    let regional: MisoTable = tables.pop().unwrap_or_default();
+    let zonal: MisoTable = tables.pop().unwrap_or_default();
+    if zonal.rows.is_empty() {
+        return Err(MugenError::NoData(format!(
+            "MISO's report has no hours for {}",
+            day.format("%Y-%m-%d")
+        )));
+    }
+    let body: String = zonal.with_columns_of(&regional).to_csv();
+    save_download(&path, body.as_bytes()).await?;
+    Ok(path)
+}
+
+/// The table of one of MISO's reports, as text.
+#[derive(Default)]
+struct MisoTable {
+    header: Vec<String>,
+    /// One row per hour, as long as the header.
+    rows: Vec<Vec<String>>,
+}
+
+impl MisoTable {
+    /// Reads the table out of the first sheet of the Excel `workbook` downloaded from
+    /// `url`. Title rows above the header and notes below the hours are skipped:
+    /// the header is the first row with a market day column, and the hours are the
+    /// rows below it whose market day is a date.
+    fn read(workbook: &[u8], url: &str) -> Result<Self> {
+        let invalid = |message: String| MugenError::Archive {
+            url: url.to_string(),
+            message,
+        };
+        let format = Miso.format(FileKind::Actual);
+        let mut sheets = calamine::open_workbook_auto_from_rs(Cursor::new(workbook))
+            .map_err(|err| invalid(err.to_string()))?;
+        let sheet = sheets
+            .worksheet_range_at(0)
+            .ok_or_else(|| invalid("the workbook has no sheets".to_string()))?
+            .map_err(|err| invalid(err.to_string()))?;
+        let mut rows = sheet
+            .rows()
+            .map(|row| row.iter().map(miso_cell).collect::<Vec<String>>());
+        let header: Vec<String> = rows
+            .by_ref()
+            .find(|row| row.iter().any(|cell| cell == format.time_column))
+            .ok_or_else(|| invalid(format!("no \"{}\" column", format.time_column)))?;
+        let start: usize = header
+            .iter()
+            .position(|cell| cell == format.time_column)
+            .unwrap_or_default();
+        let end: usize = header
+            .iter()
+            .rposition(|cell| !cell.is_empty())
+            .map_or(start, |last| last + 1);
+        let columns = |row: &[String]| -> Vec<String> {
+            (start..end)
+                .map(|at| row.get(at).cloned().unwrap_or_default())
+                .collect()
+        };
+        Ok(MisoTable {
+            header: columns(&header),
+            rows: rows
+                .filter(|row| {
+                    row.get(start).is_some_and(|day| {
+                        NaiveDate::parse_from_str(day, format.time_format).is_ok()
+                    })
+                })
+                .map(|row| columns(&row))
+                .collect(),
+        })
+    }
+
+    /// Returns the table with the columns of `other` it lacks appended, filled from
+    /// the row of `other` with the same market day and hour, or left empty.
+    fn with_columns_of(mut self, other: &MisoTable) -> Self {
+        let format = Miso.format(FileKind::Actual);
+        let key_columns: Vec<&str> = [Some(format.time_column), format.hour_column]
+            .into_iter()
+            .flatten()
+            .collect();
+        let key = |table: &MisoTable, row: &[String]| -> Vec<String> {
+            key_columns
+                .iter()
+                .map(|name| {
+                    table
+                        .header
+                        .iter()
+                        .position(|column| column == name)
+                        .and_then(|at| row.get(at))
+                        .cloned()
+                        .unwrap_or_default()
+                })
+                .collect()
+        };
+        let extra: Vec<usize> = (0..other.header.len())
+            .filter(|at| !self.header.contains(&other.header[*at]))
+            .collect();
+        let others: BTreeMap<Vec<String>, &Vec<String>> = other
+            .rows
+            .iter()
+            .map(|row| (key(other, row), row))
+            .collect();
+        let keys: Vec<Vec<String>> = self.rows.iter().map(|row| key(&self, row)).collect();
+        for (row, row_key) in self.rows.iter_mut().zip(keys) {
+            let matching: Option<&&Vec<String>> = others.get(&row_key);
+            row.extend(extra.iter().map(|at| {
+                matching
+                    .and_then(|other_row| other_row.get(*at))
+                    .cloned()
+                    .unwrap_or_default()
+            }));
+        }
+        self.header
+            .extend(extra.iter().map(|at| other.header[*at].clone()));
+        self
+    }
+
+    /// Writes the table as CSV, quoting the fields that need it.
+    fn to_csv(&self) -> String {
+        let line = |row: &[String]| -> String {
+            let fields: Vec<String> = row
+                .iter()
+                .map(|field| {
+                    if field.contains([',', '"', '\n']) {
+                        format!("\"{}\"", field.replace('"', "\"\""))
+                    } else {
+                        field.clone()
+                    }
+                })
+                .collect();
+            fields.join(",") + "\n"
+        };
+        std::iter::once(&self.header)
+            .chain(&self.rows)
+            .map(|row| line(row))
+            .collect()
+    }
+}
+
+/// Returns the text of a workbook cell, with dates as MISO writes them, e.g.
+/// "12/09/2023", and whole numbers without a decimal point.
+fn miso_cell(cell: &Data) -> String {
+    match cell {
+        Data::DateTime(at) => at.as_datetime().map_or_else(
+            || cell.to_string(),
+            |at| {
+                at.format(Miso.format(FileKind::Actual).time_format)
+                    .to_string()
+            },
+        ),
+        _ => cell.to_string().trim().to_string(),
+    }
+}
+
+/// Blocking wrapper around [`fetch_market_days`] for callers without an async
+/// runtime, such as the command line.
+///
+/// # Errors
+///
+/// Returns the errors of [`fetch_market_days`], or `MugenError::Io` if the runtime
+/// cannot be started.
+pub fn download_market_days(
+    market: Market,
+    kind: FileKind,
+    from: NaiveDate,
+    to: NaiveDate,
+    dir: &str,
+    options: &FetchOptions,
+) -> Result<Vec<String>> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let client = Client::new();
+    runtime.block_on(fetch_market_days(
+        &client, market, kind, from, to, dir, options,
+    ))
+}
+
+/// One posting of an ERCOT report, as listed by its document list.
+struct ErcotDocument {
+    id: String,
+    /// Local day the document was published on, in Central Prevailing Time.
+    published: NaiveDate,
+    /// Publication time, for picking the last posting of a day.
+    published_at: String,
+}
+
+impl ErcotDocument {
+    /// Parses the CSV postings out of the JSON document list downloaded from `url`,
+    /// skipping the XML ones.
+    fn list(body: &[u8], url: &str) -> Result<Vec<ErcotDocument>> {
+        let invalid = |message: String| MugenError::Archive {
+            url: url.to_string(),
+            message,
+        };
+        let json: Value = serde_json::from_slice(body).map_err(|err| invalid(err.to_string()))?;
+        let listed: &Vec<Value> = json["ListDocsByRptTypeRes"]["DocumentList"]
+            .as_array()
+            .ok_or_else(|| invalid("no document list in the response".to_string()))?;
+        Ok(listed
+            .iter()
+            .map(|entry| &entry["Document"])
+            .filter(|document| {
+                document["FriendlyName"]
+                    .as_str()
+                    .is_some_and(|name| name.ends_with("_csv"))
+            })
+            .filter_map(|document| {
+                let published_at: &str = document["PublishDate"].as_str()?;
+                Some(ErcotDocument {
+                    id: match &document["DocID"] {
+                        Value::String(id) => id.clone(),
+                        id => id.to_string(),
+                    },
+                    published: DateTime::parse_from_rfc3339(published_at)
+                        .ok()?
+                        .date_naive(),
+                    published_at: published_at.to_string(),
+                })
+            })
+            .collect())
+    }
+
+    /// Finds the document of `kind` holding `day`: the actual load published the next
+    /// day, or the last forecast published on the day.
+    fn of_day(
+        documents: &[ErcotDocument],
+        kind: FileKind,
+        day: NaiveDate,
+    ) -> Option<&ErcotDocument> {
+        let published: NaiveDate = match kind {
+            FileKind::Actual => day + TimeDelta::days(1),
+            FileKind::Forecast => day,
+        };
+        documents
+            .iter()
+            .filter(|document| document.published == published)
+            .max_by(|a, b| a.published_at.cmp(&b.published_at))
+    }
+
+    /// Returns the URL the document is downloaded from.
+    fn url(&self, base_url: &str) -> String {
+        format!(
+            "{}/misdownload/servlets/mirDownload?doclookupId={}",
+            base_url.trim_end_matches('/'),
+            self.id
+        )
+    }
+}
+
+/// Like [`fetch_file`], for `url` serving a zip archive whose first file is saved to
+/// `path`.
+async fn fetch_zipped(
+    client: &Client,
+    url: String,
+    path: String,
+    retry: &RetryPolicy,
+    pacer: &Pacer,
+) -> Result<String> {
+    if is_downloaded(&path) {
+        return Ok(path);
+    }
+    let archive = download(client, url.clone(), retry, pacer).await?;
+    let body: Vec<u8> =
+        unzip_first(&archive).map_err(|message| MugenError::Archive { url, message })?;
+    save_download(&path, &body).await?;
+    Ok(path)
+}
+
+/// Returns the contents of the first file in the zip `archive`, as listed in its
+/// central directory.
+fn unzip_first(archive: &[u8]) -> std::result::Result<Vec<u8>, String> {
+    let mut archive = ZipArchive::new(Cursor::new(archive)).map_err(|err| err.to_string())?;
+    if archive.is_empty() {
+        return Err("archive is empty".to_string());
+    }
+    let mut file = archive.by_index(0).map_err(|err| err.to_string())?;
+    let mut body: Vec<u8> = Vec::new();
+    file.read_to_end(&mut body).map_err(|err| err.to_string())?;
+    Ok(body)
+}
+
+/// Saves `url` to `path` and records its digest, unless `path` already exists and
+/// matches its recorded digest, returning `path`.
+async fn fetch_file(
+    client: &Client,
+    url: String,
+    path: String,
+    retry: &RetryPolicy,
+    pacer: &Pacer,
+) -> Result<String> {
+    if is_downloaded(&path) {
+        return Ok(path);
+    }
+    let body = download(client, url, retry, pacer).await?;
+    save_download(&path, &body).await?;
+    Ok(path)
+}
+
+/// Returns whether `path` holds a finished download: the file exists and matches
+/// the digest recorded when it was saved. A file without a record is taken for a
+/// download cut off before it was recorded, so it is stale like a changed one.
+pub(crate) fn is_downloaded(path: &str) -> bool {
+    if !Path::new(path).is_file() {
+        return false;
+    }
+    match checksum::verify(path) {
+        Ok(true) => true,
+        Ok(false) => {
+            warn!(path, "no recorded digest, downloading again");
+            false
+        }
+        Err(err) => {
+            warn!(error = %err, "downloading again");
+            false
+        }
+    }
+}
+
+/// Saves `body` as the download at `path`: it is written to a temporary file next
+/// to `path`, its digest recorded, and then renamed into place, so `path` only
+/// ever holds a whole download with a matching record.
+///
+/// # Errors
+///
+/// Returns `MugenError::Write` if the file or its record cannot be written.
+pub(crate) async fn save_download(path: &str, body: &[u8]) -> Result<()> {
+    let partial: String = format!("{}.part", path);
+    let write = |source| MugenError::Write {
+        path: path.to_string(),
+        source,
+    };
+    tokio::fs::write(&partial, body).await.map_err(write)?;
+    checksum::record(&FileDigest::of_bytes(path, body))?;
+    tokio::fs::rename(&partial, path).await.map_err(write)
+}
+
+/// Downloads `url`, waiting for its turn with `pacer` before every attempt and
+/// retrying temporary failures as `retry` says.
+///
+/// # Errors
+///
+/// Returns `MugenError::Fetch` if the last attempt still fails.
+pub(crate) async fn download(
+    client: &Client,
+    url: String,
+    retry: &RetryPolicy,
+    pacer: &Pacer,
+) -> Result<Vec<u8>> {
+    download_as(client, url, retry, pacer, None).await
+}
+
+/// Like [`download`], logging in with `login` if there is one.
+async fn download_as(
+    client: &Client,
+    url: String,
+    retry: &RetryPolicy,
+    pacer: &Pacer,
+    login: Option<&Login>,
+) -> Result<Vec<u8>> {
+    let mut attempt: u32 = 1;
+    loop {
+        pacer.wait().await;
+        match get(client, &url, retry.timeout, login).await {
+            Ok(body) => return Ok(body),
+            Err(failure) if failure.transient && attempt < retry.attempts => {
+                let wait = failure
+                    .retry_after
+                    .unwrap_or_else(|| retry.backoff(attempt - 1))
+                    .min(retry.max_backoff);
+                warn!(%url, attempt, error = %failure.source, ?wait, "retrying download");
+                tokio::time::sleep(wait).await;
+                attempt += 1;
+            }
+            Err(failure) => {
+                return Err(MugenError::Fetch {
+                    url,
+                    attempts: attempt,
+                    source: failure.source,
+                })
+            }
+        }
+    }
+}
+
+/// Why one download attempt failed, and whether trying again may help.
+struct Failure {
+    source: reqwest::Error,
+    /// Server errors, rate limiting, timeouts and connection problems.
+    transient: bool,
+    /// How long the server asked to wait, if it did.
+    retry_after: Option<Duration>,
+}
+
+impl From<reqwest::Error> for Failure {
+    fn from(source: reqwest::Error) -> Self {
+        let transient = match source.status() {
+            Some(status) => status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+            None => !source.is_builder() && !source.is_redirect(),
+        };
+        Failure {
+            source,
+            transient,
+            retry_after: None,
+        }
+    }
+}
+
+/// Downloads `url` once, giving up after `timeout`.
+async fn get(
+    client: &Client,
+    url: &str,
+    timeout: Duration,
+    login: Option<&Login>,
+) -> std::result::Result<Vec<u8>, Failure> {
+    let mut request = client.get(url).timeout(timeout);
+    if let Some(login) = login {
+        request = request.basic_auth(&login.username, Some(&login.password));
+    }
+    let response = request.send().await?;
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs);
+    let response = response.error_for_status().map_err(|source| Failure {
+        retry_after,
+        ..Failure::from(source)
+    })?;
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// Spaces out the starts of requests shared by concurrent downloads.
+pub(crate) struct Pacer {
+    interval: Duration,
+    /// Earliest time the next request may start.
+    next: Mutex<Instant>,
+}
+
+impl Pacer {
+    pub(crate) fn new(interval: Duration) -> Self {
+        Pacer {
+            interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Waits for this request's turn, reserving the next one for the caller after.
+    async fn wait(&self) {
+        if self.interval.is_zero() {
+            return;
+        }
+        let start = {
+            let mut next = self.next.lock().unwrap_or_else(|err| err.into_inner());
+            let start = (*next).max(Instant::now());
+            *next = start + self.interval;
+            start
+        };
+        tokio::time::sleep(start.saturating_duration_since(Instant::now())).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads the sample payload `name` from `tests/fixtures`.
+    fn fixture(name: &str) -> Vec<u8> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name);
+        std::fs::read(path).unwrap()
+    }
+
+    #[test]
+    fn only_recorded_whole_downloads_are_kept() {
+        let dir = std::env::temp_dir().join(format!("mugen-fetch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path: String = dir.join("20231209isolf.csv").to_string_lossy().to_string();
+        // A download cut off before its digest was recorded.
+        std::fs::write(&path, "Time Stamp,N.Y.C.\n").unwrap();
+        assert!(!is_downloaded(&path));
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let body: &[u8] = b"Time Stamp,N.Y.C.\n12/09/2023 00:00,4657\n";
+        runtime.block_on(save_download(&path, body)).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), body);
+        assert!(!Path::new(&format!("{}.part", path)).exists());
+        assert!(is_downloaded(&path));
+
+        std::fs::write(&path, &body[..20]).unwrap();
+        assert!(!is_downloaded(&path));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn unzip_reads_entries_with_a_data_descriptor() {
+        // Written as a stream, so the local header gives no sizes.
+        let archive: Vec<u8> = fixture("ercot_actual.zip");
+        assert_eq!(archive[6] & 0x08, 0x08);
+        let body: String = String::from_utf8(unzip_first(&archive).unwrap()).unwrap();
+        assert!(body.starts_with("OperDay,HourEnding,COAST,"));
+        assert_eq!(body.lines().count(), 3);
+    }
+
+    #[test]
+    fn unzip_rejects_what_is_not_an_archive() {
+        assert!(unzip_first(b"").is_err());
+        assert!(unzip_first(b"OperDay,HourEnding\r\n").is_err());
+        let archive: Vec<u8> = fixture("ercot_actual.zip");
+        assert!(unzip_first(&archive[..archive.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn ercot_list_keeps_the_csv_postings() {
+        let url = "https://www.ercot.com/misapp/servlets/IceDocListJsonWS?reportTypeId=13101";
+        let documents: Vec<ErcotDocument> =
+            ErcotDocument::list(&fixture("ercot_documents.json"), url).unwrap();
+        // The XML posting and the one with an unreadable date are skipped.
+        let ids: Vec<&str> = documents
+            .iter()
+            .map(|document| document.id.as_str())
+            .collect();
+        assert_eq!(ids, ["963212345", "962998871", "962998872"]);
+        assert_eq!(
+            documents[0].published,
+            NaiveDate::from_ymd_opt(2023, 12, 10).unwrap()
+        );
+
+        // The actual load of a day is the last posting of the next day.
+        let day = NaiveDate::from_ymd_opt(2023, 12, 8).unwrap();
+        let actual = ErcotDocument::of_day(&documents, FileKind::Actual, day).unwrap();
+        assert_eq!(actual.id, "962998872");
+        assert_eq!(
+            actual.url("https://www.ercot.com/"),
+            "https://www.ercot.com/misdownload/servlets/mirDownload?doclookupId=962998872"
+        );
+        assert!(ErcotDocument::of_day(&documents, FileKind::Forecast, day).is_none());
+    }
+
+    #[test]
+    fn ercot_list_rejects_answers_without_a_list() {
+        let url = "https://www.ercot.com/misapp/servlets/IceDocListJsonWS?reportTypeId=13101";
+        let empty = br#"{"ListDocsByRptTypeRes": {"DocumentList": []}}"#;
+        assert!(ErcotDocument::list(empty, url).unwrap().is_empty());
+        assert!(ErcotDocument::list(b"", url).is_err());
+        assert!(ErcotDocument::list(b"<html>Service unavailable</html>", url).is_err());
+        assert!(ErcotDocument::list(br#"{"ListDocsByRptTypeRes": {}}"#, url).is_err());
+    }
+
+    #[test]
+    fn iso_ne_entries_read_numbers_and_strings() {
+        let url = "https://webservices.iso-ne.com/api/v1.1/hourlyloadforecast/day/20231209.json";
+        let entries: Vec<IsoNeEntry> =
+            iso_ne_entries(&fixture("iso_ne_forecast.json"), url).unwrap();
+        // Entries without a load or a readable start are skipped.
+        assert_eq!(entries.len(), 2);
+        let midnight = NaiveDate::from_ymd_opt(2023, 12, 9)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        assert_eq!(entries[0].begin, midnight);
+        assert_eq!(entries[0].location, Some(4004));
+        assert_eq!(entries[0].issued, "2023-12-08T09:45:00.000-05:00");
+        assert_eq!(entries[0].load, 3109.0);
+        assert_eq!(entries[1].location, Some(4001));
+        assert_eq!(entries[1].load, 1187.5);
+    }
+
+    #[test]
+    fn iso_ne_entries_take_single_and_empty_answers() {
+        let url =
+            "https://webservices.iso-ne.com/api/v1.1/hourlysysload/day/20231209/location/4004.json";
+        let single = br#"{"HourlyRtDemands": {"HourlyRtDemand": {
+            "BeginDate": "2023-12-09T05:00:00.000-05:00",
+            "Location": {"$": ".Z.CONNECTICUT", "@LocId": "4004"},
+            "Load": 2891.2
+        }}}"#;
+        let entries: Vec<IsoNeEntry> = iso_ne_entries(single, url).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].location, Some(4004));
+        assert!(entries[0].issued.is_empty());
+        assert!(iso_ne_entries(br#"{"HourlyRtDemands": ""}"#, url)
+            .unwrap()
+            .is_empty());
+        assert!(iso_ne_entries(br#"{"HourlyRtDemands": {}}"#, url)
+            .unwrap()
+            .is_empty());
+        assert!(iso_ne_entries(b"", url).is_err());
+        assert!(iso_ne_entries(b"{\"HourlyRtDemands\": [", url).is_err());
+    }
+
+    #[test]
+    fn entsoe_points_repeat_left_out_positions() {
+        let url = "https://web-api.tp.entsoe.eu/api?documentType=A65&processType=A16";
+        let points: Vec<(NaiveDateTime, f64)> =
+            entsoe_points(&fixture("entsoe_load.xml"), url).unwrap();
+        let at = |day: u32, hour: u32, minute: u32| -> NaiveDateTime {
+            NaiveDate::from_ymd_opt(2023, 12, day)
+                .unwrap()
+                .and_hms_opt(hour, minute, 0)
+                .unwrap()
+        };
+        assert_eq!(
+            points,
+            [
+                (at(8, 23, 0), 52013.0),
+                (at(8, 23, 15), 52013.0),
+                (at(8, 23, 30), 51247.5),
+                (at(8, 23, 45), 51247.5),
+                (at(9, 0, 0), 50110.0),
+                (at(9, 1, 0), 49876.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn entsoe_points_tell_no_data_from_refusals() {
+        let url = "https://web-api.tp.entsoe.eu/api?documentType=A65&processType=A16";
+        let acknowledgement = |code: &str, text: &str| -> String {
+            format!(
+                "<Acknowledgement_MarketDocument xmlns=\"urn:iec62325.351:tc57wg16:451-1:acknowledgementdocument:7:0\">\
+                 <Reason><code>{}</code><text>{}</text></Reason>\
+                 </Acknowledgement_MarketDocument>",
+                code, text
+            )
+        };
+        let empty: String = acknowledgement(
+            "999",
+            "No matching data found for Data item Actual Total Load",
+        );
+        assert!(entsoe_points(empty.as_bytes(), url).unwrap().is_empty());
+        let refused: String = acknowledgement("401", "Unauthorized");
+        match entsoe_points(refused.as_bytes(), url) {
+            Err(MugenError::Archive { message, .. }) => assert_eq!(message, "Unauthorized"),
+            other => panic!(
+                "expected a refusal, got {:?}",
+                other.map(|points| points.len())
+            ),
+        }
+
+        assert!(entsoe_points(b"", url).is_err());
+        assert!(entsoe_points(b"<GL_MarketDocument><TimeSeries>", url).is_err());
+        let document: String = String::from_utf8(fixture("entsoe_load.xml")).unwrap();
+        let daily: String = document.replace("PT60M", "P1D");
+        assert!(entsoe_points(daily.as_bytes(), url).is_err());
+        let open: String = document.replace("<end>2023-12-09T02:00Z</end>", "");
+        assert!(entsoe_points(open.as_bytes(), url).is_err());
+    }
+
+    #[test]
+    fn miso_tables_join_the_regions_onto_the_zones() {
+        let url = "https://docs.misoenergy.org/marketreports/20231209_df_al.xlsx";
+        // Title rows, a blank first column and a closing note around the hours.
+        let zonal = MisoTable::read(&fixture("miso_zonal.xlsx"), url).unwrap();
+        assert_eq!(zonal.header[..2], ["Market Day", "HourEnding"]);
+        assert_eq!(zonal.rows.len(), 24);
+        assert_eq!(zonal.rows[0][..3], ["12/09/2023", "1", "10001"]);
+        // Dates as text, and no regional row for hour 5.
+        let regional = MisoTable::read(&fixture("miso_regional.xlsx"), url).unwrap();
+        assert_eq!(regional.rows.len(), 23);
+
+        let csv: String = zonal.with_columns_of(&regional).to_csv();
+        let expected: Vec<u8> = fixture("miso_20231209_df_al.csv");
+        assert_eq!(csv, String::from_utf8(expected).unwrap());
+    }
+
+    #[test]
+    fn miso_table_rejects_what_is_not_a_report() {
+        let url = "https://docs.misoenergy.org/marketreports/20231209_df_al.xlsx";
+        assert!(MisoTable::read(b"", url).is_err());
+        assert!(MisoTable::read(b"Market Day,HourEnding\n12/09/2023,1\n", url).is_err());
+        let workbook: Vec<u8> = fixture("miso_zonal.xlsx");
+        assert!(MisoTable::read(&workbook[..workbook.len() / 2], url).is_err());
+        // A zip archive that is not a workbook.
+        assert!(MisoTable::read(&fixture("ercot_actual.zip"), url).is_err());
+    }
+
+    #[test]
+    fn miso_csv_quotes_what_needs_it() {
+        let table = MisoTable {
+            header: vec!["Market Day".to_string(), "Note".to_string()],
+            rows: vec![vec![
+                "12/09/2023".to_string(),
+                "peak, \"estimated\"".to_string(),
+            ]],
+        };
+        assert_eq!(
+            table.to_csv(),
+            "Market Day,Note\n12/09/2023,\"peak, \"\"estimated\"\"\"\n"
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use chrono::NaiveDateTime;
+use rusqlite::{params, Connection};
+
+use crate::error::{MugenError, Result};
+use crate::zone::Zone;
+
+/// Layout of the history database, created on first use.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    started TEXT NOT NULL,
+    version TEXT NOT NULL,
+    command TEXT NOT NULL,
+    zone TEXT NOT NULL,
+    data_start TEXT,
+    data_end TEXT,
+    manifest TEXT
+);
+CREATE TABLE IF NOT EXISTS metrics (
+    run_id INTEGER NOT NULL REFERENCES runs (id),
+    name TEXT NOT NULL,
+    value REAL NOT NULL
+);
+CREATE INDEX IF NOT EXISTS metrics_by_name ON metrics (name, run_id);
+";
+
+/// Format of the data times stored, e.g. "2023-12-10T23:00:00", which sorts and
+/// reads with SQLite's date functions.
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// One run's metrics and what the run was, as appended to a [`MetricsHistory`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunRecord {
+    /// When the run started, in RFC 3339 format.
+    pub started: String,
+    /// Version of the crate that produced the run.
+    pub version: String,
+    /// The command line the run was started with.
+    pub command: String,
+    /// The zone the metrics score.
+    pub zone: Zone,
+    /// Earliest timestamp of the data scored, if known.
+    pub data_start: Option<NaiveDateTime>,
+    /// Latest timestamp of the data scored, if known.
+    pub data_end: Option<NaiveDateTime>,
+    /// Path of the run's manifest, if one was written.
+    pub manifest: Option<String>,
+    /// Every metric by name, e.g. ("MAPE", 2.4).
+    pub metrics: Vec<(String, f64)>,
+}
+
+/// One metric of one zone summarized over the runs whose data ended in a month.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonthlyMetric {
+    /// The month, e.g. "2023-12".
+    pub month: String,
+    /// Number of runs recorded.
+    pub runs: usize,
+    /// Mean of the metric over those runs.
+    pub mean: f64,
+    /// Smallest value recorded.
+    pub min: f64,
+    /// Largest value recorded.
+    pub max: f64,
+}
+
+/// A SQLite database that each run's metrics are appended to, with the run's time,
+/// version, command line, zone and data period, so accuracy can be followed across
+/// runs, e.g. to compare December with November.
+///
+/// Runs go in a `runs` table and their metrics in a `metrics` table keyed by
+/// `run_id`, so the database can also be queried directly, e.g.
+///
+/// ```sql
+/// SELECT substr(data_end, 1, 7) AS month, avg(value)
+/// FROM runs JOIN metrics ON metrics.run_id = runs.id
+/// WHERE name = 'MAPE' AND zone = 'N.Y.C.'
+/// GROUP BY month;
+/// ```
+///
+/// # Examples
+///
+/// ```no_run
+/// use mugen_core::history::{MetricsHistory, RunRecord};
+/// use mugen_core::zone::Zone;
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let mut history = MetricsHistory::open("history.sqlite")?;
+/// history.append(&RunRecord {
+///     started: "2023-12-11T06:05:00+00:00".to_string(),
+///     zone: Zone::NewYorkCity,
+///     metrics: vec![("MAPE".to_string(), 2.4)],
+///     ..Default::default()
+/// })?;
+/// for month in history.monthly(Zone::NewYorkCity, "MAPE")? {
+///     println!("{}: {:.2} over {} runs", month.month, month.mean, month.runs);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MetricsHistory {
+    path: String,
+    connection: Connection,
+}
+
+impl MetricsHistory {
+    /// Opens the history database at `path`, creating the file and its tables if
+    /// missing.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the database file, e.g. "history.sqlite".
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing the history or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::Database` if the file cannot be opened or is not a
+    /// SQLite database.
+    pub fn open(path: &str) -> Result<Self> {
+        let connection = Connection::open(path).map_err(|source| MugenError::Database {
+            path: path.to_string(),
+            source,
+        })?;
+        let history = MetricsHistory {
+            path: path.to_string(),
+            connection,
+        };
+        history
+            .connection
+            .execute_batch(SCHEMA)
+            .map_err(|source| history.error(source))?;
+        Ok(history)
+    }
+
+    /// Returns the path of the database file.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Appends `run` and its metrics in one transaction, so a failed append leaves
+    /// no partial run behind.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing the id of the new row in `runs` or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::Database` if the rows cannot be written.
+    pub fn append(&mut self, run: &RunRecord) -> Result<i64> {
+        let path = self.path.clone();
+        let error = |source| MugenError::Database {
+            path: path.clone(),
+            source,
+        };
+        let time = |at: Option<NaiveDateTime>| at.map(|at| at.format(TIME_FORMAT).to_string());
+        let transaction = self.connection.transaction().map_err(error)?;
+        transaction
+            .execute(
+                "INSERT INTO runs (started, version, command, zone, data_start, data_end, \
+                 manifest) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    run.started,
+                    run.version,
+                    run.command,
+                    run.zone.to_string(),
+                    time(run.data_start),
+                    time(run.data_end),
+                    run.manifest,
+                ],
+            )
+            .map_err(error)?;
+        let id = transaction.last_insert_rowid();
+        for (name, value) in &run.metrics {
+            transaction
+                .execute(
+                    "INSERT INTO metrics (run_id, name, value) VALUES (?1, ?2, ?3)",
+                    params![id, name, value],
+                )
+                .map_err(error)?;
+        }
+        transaction.commit().map_err(error)?;
+        Ok(id)
+    }
+
+    /// Summarizes `metric` of `zone` per month, oldest first.
+    ///
+    /// Runs count towards the month their data ends in, or the month they started
+    /// in if the data period is unknown, so a rerun over old data lands in the
+    /// month it scores.
+    ///
+    /// # Arguments
+    ///
+    /// * `zone` - The zone the runs scored.
+    /// * `metric` - Name of the metric, e.g. "MAPE".
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing one entry per month with runs, or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::Database` if the tables cannot be read.
+    pub fn monthly(&self, zone: Zone, metric: &str) -> Result<Vec<MonthlyMetric>> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT substr(coalesce(data_end, started), 1, 7) AS month, count(*), \
+                 avg(value), min(value), max(value) \
+                 FROM runs JOIN metrics ON metrics.run_id = runs.id \
+                 WHERE zone = ?1 AND name = ?2 GROUP BY month ORDER BY month",
+            )
+            .map_err(|source| self.error(source))?;
+        let rows = statement
+            .query_map(params![zone.to_string(), metric], |row| {
+                Ok(MonthlyMetric {
+                    month: row.get(0)?,
+                    runs: row.get::<_, i64>(1)? as usize,
+                    mean: row.get(2)?,
+                    min: row.get(3)?,
+                    max: row.get(4)?,
+                })
+            })
+            .map_err(|source| self.error(source))?;
+        rows.collect::<rusqlite::Result<Vec<MonthlyMetric>>>()
+            .map_err(|source| self.error(source))
+    }
+
+    /// Wraps a database error with the path of the file.
+    fn error(&self, source: rusqlite::Error) -> MugenError {
+        MugenError::Database {
+            path: self.path.clone(),
+            source,
+        }
+    }
+}
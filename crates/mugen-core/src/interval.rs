@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Timelike};
+use polars::prelude::*;
+
+use crate::dataset::TimeSeriesDataset;
+use crate::error::{MugenError, Result};
+
+/// Name of the dataset a prediction interval is returned as.
+const INTERVAL_FRAME: &str = "prediction interval";
+
+/// The spread of past forecast errors at each hour of the day, to put empirical
+/// prediction intervals around new forecasts.
+///
+/// Errors are relative, the actual load over the forecast minus one, so bounds
+/// learned on one level of load carry over to another.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ErrorQuantiles {
+    /// Share of the actual load each interval should hold, e.g. 0.8.
+    pub coverage: f64,
+    /// The lower quantile of the relative error at each hour of the day, 0 to 23.
+    pub lower: [f64; 24],
+    /// The upper quantile of the relative error at each hour of the day, 0 to 23.
+    pub upper: [f64; 24],
+}
+
+impl ErrorQuantiles {
+    /// Learns the central `coverage` quantiles of the relative error at each hour of
+    /// the day from past forecasts scored against the actual load.
+    ///
+    /// An hour of the day without errors of its own takes the quantiles of all
+    /// hours.
+    ///
+    /// # Arguments
+    ///
+    /// * `joined` - Actual load joined against past forecasts, as returned by
+    ///   [`crate::data::join_actual_forecast`], e.g. the folds of a
+    ///   [`crate::backtest::backtest`].
+    /// * `coverage` - Share of the actual load the intervals should hold, between 0
+    ///   and 1.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing the quantiles or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InvalidArgument` if `coverage` is not between 0 and 1,
+    /// `MugenError::NoData` if `joined` holds no errors, and an error if a time
+    /// column is not a datetime or the "Actual" or "Forecast" column is missing or
+    /// not numeric.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use mugen_core::data::{join_actual_forecast, process_pred, process_truth};
+    /// use mugen_core::interval::ErrorQuantiles;
+    /// use mugen_core::synthetic::Synthetic;
+    /// use mugen_core::zone::Zone;
+    ///
+    /// # fn main() -> mugen_core::error::Result<()> {
+    /// let synthetic = Synthetic::default();
+    /// let day = NaiveDate::from_ymd_opt(2023, 12, 8).unwrap();
+    /// let truth = process_truth(synthetic.truth(day..=day)?, Zone::NewYorkCity)?;
+    /// let pred = process_pred(synthetic.forecast(day..=day)?, Zone::NewYorkCity)?;
+    /// let errors = ErrorQuantiles::fit(&[join_actual_forecast(&truth, &pred)?], 0.8)?;
+    /// let next_day = day.succ_opt().unwrap();
+    /// let next = process_pred(synthetic.forecast(next_day..=next_day)?, Zone::NewYorkCity)?;
+    /// let interval = errors.interval(&next)?;
+    /// assert_eq!(interval.height(), 24);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fit(joined: &[TimeSeriesDataset], coverage: f64) -> Result<Self> {
+        if !(coverage > 0.0 && coverage < 1.0) {
+            return Err(MugenError::InvalidArgument(format!(
+                "interval coverage {} must be between 0 and 1, e.g. 0.8",
+                coverage
+            )));
+        }
+        let mut errors: Vec<Vec<f64>> = vec![Vec::new(); 24];
+        for dataset in joined {
+            let actual: HashMap<i64, f64> = dataset.values_by_timestamp("Actual")?;
+            let forecast: HashMap<i64, f64> = dataset.values_by_timestamp("Forecast")?;
+            for (at, forecast) in forecast {
+                let (Some(actual), Some(hour)) = (actual.get(&at), hour_of_day(at)) else {
+                    continue;
+                };
+                let error: f64 = actual / forecast - 1.0;
+                if error.is_finite() {
+                    errors[hour].push(error);
+                }
+            }
+        }
+        let mut all: Vec<f64> = errors.concat();
+        if all.is_empty() {
+            return Err(MugenError::NoData(
+                "no hours with both actual load and a forecast to learn errors from".to_string(),
+            ));
+        }
+        all.sort_by(f64::total_cmp);
+        let (low, high): (f64, f64) = ((1.0 - coverage) / 2.0, (1.0 + coverage) / 2.0);
+        let mut lower: [f64; 24] = [0.0; 24];
+        let mut upper: [f64; 24] = [0.0; 24];
+        for (hour, mut errors) in errors.into_iter().enumerate() {
+            if errors.is_empty() {
+                errors = all.clone();
+            }
+            errors.sort_by(f64::total_cmp);
+            lower[hour] = quantile(&errors, low);
+            upper[hour] = quantile(&errors, high);
+        }
+        Ok(ErrorQuantiles {
+            coverage,
+            lower,
+            upper,
+        })
+    }
+
+    /// Puts the learned interval around every hour of a point forecast.
+    ///
+    /// # Arguments
+    ///
+    /// * `forecast` - Any processed forecast, e.g. as returned by
+    ///   [`crate::data::process_pred`] or a [`crate::baseline`] model.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing a dataset over "Time Stamp" with the columns
+    ///   "Forecast", "Lower" and "Upper", in the unit and timezone of `forecast`,
+    ///   or a `MugenError`. It joins against actual load like the forecast itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the time column of `forecast` is not a datetime or its
+    /// value column is not numeric.
+    pub fn interval(&self, forecast: &TimeSeriesDataset) -> Result<TimeSeriesDataset> {
+        let mut values: Vec<(i64, f64)> = forecast
+            .values_by_timestamp(forecast.value_column())?
+            .into_iter()
+            .collect();
+        values.sort_by_key(|(at, _)| *at);
+        let mut stamps: Vec<i64> = Vec::with_capacity(values.len());
+        let mut points: Vec<f64> = Vec::with_capacity(values.len());
+        let mut lower: Vec<f64> = Vec::with_capacity(values.len());
+        let mut upper: Vec<f64> = Vec::with_capacity(values.len());
+        for (at, value) in values {
+            let Some(hour) = hour_of_day(at) else {
+                continue;
+            };
+            stamps.push(at);
+            points.push(value);
+            lower.push(value * (1.0 + self.lower[hour]));
+            upper.push(value * (1.0 + self.upper[hour]));
+        }
+        let frame: DataFrame = DataFrame::new(vec![
+            Int64Chunked::from_vec("Time Stamp", stamps)
+                .into_datetime(TimeUnit::Milliseconds, None)
+                .into_series(),
+            Series::new("Forecast", points),
+            Series::new("Lower", lower),
+            Series::new("Upper", upper),
+        ])?;
+        Ok(TimeSeriesDataset::new(
+            INTERVAL_FRAME,
+            frame,
+            "Time Stamp",
+            vec!["Forecast".into(), "Lower".into(), "Upper".into()],
+        )?
+        .with_unit(forecast.unit())
+        .with_timezone(forecast.timezone()))
+    }
+}
+
+/// Returns the `q` quantile of sorted, non-empty `values`, interpolating linearly
+/// between the nearest two.
+fn quantile(values: &[f64], q: f64) -> f64 {
+    let position: f64 = q * (values.len() - 1) as f64;
+    let below: usize = position.floor() as usize;
+    let above: usize = position.ceil() as usize;
+    values[below] + (values[above] - values[below]) * (position - below as f64)
+}
+
+/// Returns the hour of the day of the local time `ms` milliseconds after the epoch.
+fn hour_of_day(ms: i64) -> Option<usize> {
+    DateTime::from_timestamp_millis(ms).map(|at| at.naive_utc().hour() as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::testing::{dataset, hourly};
+    use crate::dataset::HOUR_MS;
+
+    /// Relative errors from -10% to +10% in steps of 0.2%, 101 in all.
+    fn errors() -> Vec<f64> {
+        (0..=100).map(|step| -0.1 + 0.002 * step as f64).collect()
+    }
+
+    /// A forecast of 1000 at `hours` of each of 101 days, with the actual load off
+    /// by one of [`errors`] each day.
+    fn joined(hours: &[i64]) -> TimeSeriesDataset {
+        let (mut stamps, mut actual): (Vec<i64>, Vec<f64>) = (Vec::new(), Vec::new());
+        for (day, error) in errors().into_iter().enumerate() {
+            for hour in hours {
+                stamps.push((day as i64 * 24 + hour) * HOUR_MS);
+                actual.push(1000.0 * (1.0 + error));
+            }
+        }
+        dataset(
+            "joined",
+            stamps,
+            vec![
+                Series::new("Actual", &actual),
+                Series::new("Forecast", vec![1000.0; actual.len()]),
+            ],
+        )
+    }
+
+    /// A forecast of `value` at every hour of the first day.
+    fn forecast(value: f64) -> TimeSeriesDataset {
+        hourly(
+            "forecast",
+            0,
+            vec![Series::new("Forecast", vec![value; 24])],
+        )
+        .with_unit("MW")
+    }
+
+    #[test]
+    fn intervals_cover_the_requested_share_of_known_errors() {
+        let all_hours: Vec<i64> = (0..24).collect();
+        let quantiles: ErrorQuantiles = ErrorQuantiles::fit(&[joined(&all_hours)], 0.8).unwrap();
+        for hour in 0..24 {
+            assert!((quantiles.lower[hour] + 0.08).abs() < 1e-9);
+            assert!((quantiles.upper[hour] - 0.08).abs() < 1e-9);
+        }
+        let inside: usize = errors()
+            .into_iter()
+            .filter(|error| (quantiles.lower[0]..=quantiles.upper[0]).contains(error))
+            .count();
+        assert!((inside as f64 / errors().len() as f64 - 0.8).abs() < 0.02);
+
+        let interval: TimeSeriesDataset = quantiles.interval(&forecast(2000.0)).unwrap();
+        assert_eq!(interval.unit(), "MW");
+        let lower = interval.values("Lower").unwrap();
+        let upper = interval.values("Upper").unwrap();
+        assert!((lower.get(0).unwrap() - 1840.0).abs() < 1e-6);
+        assert!((upper.get(23).unwrap() - 2160.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn hours_without_errors_take_the_quantiles_of_all_hours() {
+        let quantiles: ErrorQuantiles = ErrorQuantiles::fit(&[joined(&[6, 18])], 0.5).unwrap();
+        for hour in 0..24 {
+            assert_eq!(quantiles.lower[hour], quantiles.lower[6]);
+            assert_eq!(quantiles.upper[hour], quantiles.upper[18]);
+        }
+        assert!((quantiles.lower[0] + 0.05).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fitting_needs_a_coverage_and_errors() {
+        let all_hours: Vec<i64> = (0..24).collect();
+        for coverage in [0.0, 1.0, f64::NAN] {
+            let result = ErrorQuantiles::fit(&[joined(&all_hours)], coverage);
+            assert!(matches!(result, Err(MugenError::InvalidArgument(_))));
+        }
+        let result = ErrorQuantiles::fit(&[], 0.8);
+        assert!(matches!(result, Err(MugenError::NoData(_))));
+    }
+}
@@ -0,0 +1,832 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+
+use crate::error::{MugenError, Result};
+use crate::fetch::{Report, NYISO_BASE_URL};
+use crate::zone::Zone;
+
+/// Which of a market's two daily load files a format describes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FileKind {
+    /// Metered actual load.
+    Actual,
+    /// The day-ahead load forecast.
+    Forecast,
+}
+
+impl FileKind {
+    /// Returns the name of the data in datasets and errors, "ground truth" or
+    /// "forecast".
+    pub fn frame(self) -> &'static str {
+        match self {
+            FileKind::Actual => "ground truth",
+            FileKind::Forecast => "forecast",
+        }
+    }
+}
+
+/// How a file arranges the load of its zones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    /// One row per zone and time, like NYISO's `palIntegrated` files.
+    Long {
+        /// Column naming the zone of each row.
+        zone_column: &'static str,
+        /// Column holding the load.
+        value_column: &'static str,
+    },
+    /// One row per time with a load column per zone, named after the zone followed
+    /// by `suffix`, like NYISO's `isolf` files, whose suffix is empty.
+    Wide {
+        /// Text after the zone in the column names, e.g. " MTLF (MWh)".
+        suffix: &'static str,
+    },
+}
+
+/// The columns and timestamp format of one kind of file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileFormat {
+    /// Column holding the timestamps, or only their dates if `hour_column` is set, as
+    /// text.
+    pub time_column: &'static str,
+    /// `strftime` format of the timestamps, or of the dates.
+    pub time_format: &'static str,
+    /// Column holding the hour ending, from 1 to 24 or "01:00" to "24:00", for files
+    /// that split timestamps into a date and an hour. Each row is stamped with the
+    /// start of its hour, like NYISO's files.
+    pub hour_column: Option<&'static str>,
+    /// Where the zones and their load are.
+    pub layout: Layout,
+}
+
+impl FileFormat {
+    /// Returns the column holding `zone`'s load in a file of `kind`.
+    pub fn value_column(&self, zone: &IsoZone, kind: FileKind) -> String {
+        match self.layout {
+            Layout::Long { value_column, .. } => value_column.to_string(),
+            Layout::Wide { suffix } => format!("{}{}", zone.name(kind), suffix),
+        }
+    }
+}
+
+/// A load zone of some market, with its spellings in the market's files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IsoZone {
+    /// The zone as spelled in actual-load files, which is also how charts and config
+    /// files name it.
+    pub actual_name: &'static str,
+    /// The zone as spelled in forecast files.
+    pub forecast_name: &'static str,
+    /// The full name of the zone, e.g. "Hudson Valley".
+    pub full_name: &'static str,
+}
+
+impl IsoZone {
+    /// Returns the zone as spelled in files of `kind`.
+    pub fn name(&self, kind: FileKind) -> &'static str {
+        match kind {
+            FileKind::Actual => self.actual_name,
+            FileKind::Forecast => self.forecast_name,
+        }
+    }
+
+    /// Returns whether `name` is any of the zone's spellings, ignoring case, spaces and
+    /// punctuation.
+    pub fn matches(&self, name: &str) -> bool {
+        let key = |text: &str| -> String {
+            text.chars()
+                .filter(char::is_ascii_alphanumeric)
+                .map(|c| c.to_ascii_lowercase())
+                .collect()
+        };
+        let name: String = key(name);
+        [self.actual_name, self.forecast_name, self.full_name]
+            .iter()
+            .any(|spelling| key(spelling) == name)
+    }
+}
+
+impl fmt::Display for IsoZone {
+    /// Writes the actual-load spelling.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.actual_name)
+    }
+}
+
+impl From<Zone> for IsoZone {
+    fn from(zone: Zone) -> Self {
+        IsoZone {
+            actual_name: zone.truth_name(),
+            forecast_name: zone.forecast_column(),
+            full_name: zone.full_name(),
+        }
+    }
+}
+
+/// What the pipeline needs to know about one market operator: its zones, where its
+/// daily files are published, how they are laid out and which timezone they are in.
+///
+/// [`crate::data::process_load`] and [`crate::data::load_plan`] turn any market's
+/// files into the same datasets, with a parsed "Time Stamp" column, so joining,
+/// metrics and charts do not depend on the market. [`Nyiso`] is the market the rest
+/// of the crate defaults to.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use mugen_core::iso::{FileKind, Iso, Nyiso};
+///
+/// let day = NaiveDate::from_ymd_opt(2023, 12, 9).unwrap();
+/// assert_eq!(Nyiso.file_name(FileKind::Forecast, day), "20231209isolf.csv");
+/// assert_eq!(Nyiso.zone("hudson valley").unwrap().actual_name, "HUD VL");
+/// ```
+pub trait Iso: Send + Sync {
+    /// Returns the short name of the market, e.g. "NYISO".
+    fn name(&self) -> &'static str;
+
+    /// Returns the IANA timezone the market writes its timestamps in.
+    fn timezone(&self) -> &'static str;
+
+    /// Returns every load zone of the market, in the order charts list them.
+    fn zones(&self) -> Vec<IsoZone>;
+
+    /// Returns the layout of files of `kind`.
+    fn format(&self, kind: FileKind) -> FileFormat;
+
+    /// Returns the root of the market's public archive.
+    fn base_url(&self) -> &'static str;
+
+    /// Returns the local file name of the file of `kind` for `day`.
+    fn file_name(&self, kind: FileKind, day: NaiveDate) -> String;
+
+    /// Returns the URL of the file of `kind` for `day` under `base_url`, normally
+    /// [`Iso::base_url`].
+    fn url(&self, base_url: &str, kind: FileKind, day: NaiveDate) -> String;
+
+    /// Finds the zone spelled `name` in any of the ways [`IsoZone::matches`] accepts.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::UnknownZone` if no zone of the market matches.
+    fn zone(&self, name: &str) -> Result<IsoZone> {
+        self.zones()
+            .into_iter()
+            .find(|zone| zone.matches(name))
+            .ok_or_else(|| MugenError::UnknownZone(name.to_string()))
+    }
+}
+
+/// The New York ISO, whose files the crate was written for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Nyiso;
+
+impl Nyiso {
+    /// Returns the daily report holding files of `kind`.
+    pub fn report(kind: FileKind) -> Report {
+        match kind {
+            FileKind::Actual => Report::ActualLoad,
+            FileKind::Forecast => Report::LoadForecast,
+        }
+    }
+}
+
+impl Iso for Nyiso {
+    fn name(&self) -> &'static str {
+        "NYISO"
+    }
+
+    fn timezone(&self) -> &'static str {
+        "America/New_York"
+    }
+
+    fn zones(&self) -> Vec<IsoZone> {
+        Zone::ALL.into_iter().map(IsoZone::from).collect()
+    }
+
+    fn format(&self, kind: FileKind) -> FileFormat {
+        match kind {
+            FileKind::Actual => FileFormat {
+                time_column: "Time Stamp",
+                time_format: "%m/%d/%Y %H:%M:%S",
+                hour_column: None,
+                layout: Layout::Long {
+                    zone_column: "Name",
+                    value_column: "Integrated Load",
+                },
+            },
+            FileKind::Forecast => FileFormat {
+                time_column: "Time Stamp",
+                time_format: "%m/%d/%Y %H:%M",
+                hour_column: None,
+                layout: Layout::Wide { suffix: "" },
+            },
+        }
+    }
+
+    fn base_url(&self) -> &'static str {
+        NYISO_BASE_URL
+    }
+
+    fn file_name(&self, kind: FileKind, day: NaiveDate) -> String {
+        Nyiso::report(kind).file_name(day)
+    }
+
+    fn url(&self, base_url: &str, kind: FileKind, day: NaiveDate) -> String {
+        Nyiso::report(kind).url(base_url, day)
+    }
+}
+
+/// Root of ERCOT's public website, which lists and serves its market reports.
+pub const ERCOT_BASE_URL: &str = "https://www.ercot.com";
+
+/// ERCOT, the Texas grid operator, by weather zone.
+///
+/// Actual load comes from the "Actual Loads of Weather Zones" report (NP6-345-CD),
+/// published the morning after each operating day, and forecasts from the
+/// "Seven-Day Load Forecast by Weather Zone" report (NP3-561-CD), published every
+/// hour. Both give each hour as its operating day and hour ending in Central
+/// Prevailing Time, with one column per weather zone and the system total; the
+/// extra hour when daylight saving time ends is kept as a second row with the same
+/// timestamp.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Ercot;
+
+impl Ercot {
+    /// Returns ERCOT's report type id of the report holding files of `kind`.
+    pub fn report_type(kind: FileKind) -> u32 {
+        match kind {
+            FileKind::Actual => 13101,
+            FileKind::Forecast => 12312,
+        }
+    }
+}
+
+impl Iso for Ercot {
+    fn name(&self) -> &'static str {
+        "ERCOT"
+    }
+
+    fn timezone(&self) -> &'static str {
+        "America/Chicago"
+    }
+
+    fn zones(&self) -> Vec<IsoZone> {
+        [
+            ("COAST", "Coast", "Coast"),
+            ("EAST", "East", "East"),
+            ("FAR_WEST", "FarWest", "Far West"),
+            ("NORTH", "North", "North"),
+            ("NORTH_C", "NorthCentral", "North Central"),
+            ("SOUTHERN", "Southern", "Southern"),
+            ("SOUTH_C", "SouthCentral", "South Central"),
+            ("WEST", "West", "West"),
+            ("TOTAL", "SystemTotal", "ERCOT Total"),
+        ]
+        .into_iter()
+        .map(|(actual_name, forecast_name, full_name)| IsoZone {
+            actual_name,
+            forecast_name,
+            full_name,
+        })
+        .collect()
+    }
+
+    fn format(&self, kind: FileKind) -> FileFormat {
+        FileFormat {
+            time_column: match kind {
+                FileKind::Actual => "OperDay",
+                FileKind::Forecast => "DeliveryDate",
+            },
+            time_format: "%m/%d/%Y",
+            hour_column: Some("HourEnding"),
+            layout: Layout::Wide { suffix: "" },
+        }
+    }
+
+    fn base_url(&self) -> &'static str {
+        ERCOT_BASE_URL
+    }
+
+    /// Returns e.g. "20231209ercot_actual.csv" for the actual load of December 9 and
+    /// "20231209ercot_forecast.csv" for the forecast published that day.
+    fn file_name(&self, kind: FileKind, day: NaiveDate) -> String {
+        let suffix: &str = match kind {
+            FileKind::Actual => "actual",
+            FileKind::Forecast => "forecast",
+        };
+        format!("{}ercot_{}.csv", day.format("%Y%m%d"), suffix)
+    }
+
+    /// Returns the JSON list of the report's recent documents, among which
+    /// [`crate::fetch::fetch_market_days`] finds the day's file; ERCOT's download links
+    /// cannot be built from the date alone.
+    fn url(&self, base_url: &str, kind: FileKind, _day: NaiveDate) -> String {
+        format!(
+            "{}/misapp/servlets/IceDocListJsonWS?reportTypeId={}",
+            base_url.trim_end_matches('/'),
+            Ercot::report_type(kind)
+        )
+    }
+}
+
+/// Root of ISO New England's web services, which need an ISO Express account.
+pub const ISO_NE_BASE_URL: &str = "https://webservices.iso-ne.com/api/v1.1";
+
+/// ISO New England, by load zone.
+///
+/// Its web services answer in JSON rather than with daily files, so
+/// [`crate::fetch::fetch_market_days`] maps the hourly real-time demand of each load
+/// zone and the reliability-region load forecast into daily CSVs of its own, with
+/// one row per zone and hour: "Time Stamp" at the start of the hour in Eastern
+/// Prevailing Time, "Zone" and "Load".
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IsoNe;
+
+impl IsoNe {
+    /// The load zones with the location ids the web services know them by.
+    const ZONES: [(&'static str, &'static str, u32); 8] = [
+        ("ME", "Maine", 4001),
+        ("NH", "New Hampshire", 4002),
+        ("VT", "Vermont", 4003),
+        ("CT", "Connecticut", 4004),
+        ("RI", "Rhode Island", 4005),
+        ("SEMA", "Southeast Massachusetts", 4006),
+        ("WCMA", "Western/Central Massachusetts", 4007),
+        ("NEMA", "Northeast Massachusetts and Boston", 4008),
+    ];
+
+    /// Returns the location id of `zone` in the web services, e.g. 4004 for "CT".
+    pub fn location(zone: &IsoZone) -> Option<u32> {
+        IsoNe::ZONES
+            .iter()
+            .find(|(name, _, _)| *name == zone.actual_name)
+            .map(|(_, _, location)| *location)
+    }
+
+    /// Returns the zone the web services know by location id `location`.
+    pub fn zone_at(location: u32) -> Option<IsoZone> {
+        IsoNe::ZONES
+            .iter()
+            .find(|(_, _, id)| *id == location)
+            .map(|&(name, full_name, _)| IsoZone {
+                actual_name: name,
+                forecast_name: name,
+                full_name,
+            })
+    }
+}
+
+impl Iso for IsoNe {
+    fn name(&self) -> &'static str {
+        "ISO-NE"
+    }
+
+    fn timezone(&self) -> &'static str {
+        "America/New_York"
+    }
+
+    fn zones(&self) -> Vec<IsoZone> {
+        IsoNe::ZONES
+            .iter()
+            .filter_map(|(_, _, location)| IsoNe::zone_at(*location))
+            .collect()
+    }
+
+    fn format(&self, _kind: FileKind) -> FileFormat {
+        FileFormat {
+            time_column: "Time Stamp",
+            time_format: "%m/%d/%Y %H:%M:%S",
+            hour_column: None,
+            layout: Layout::Long {
+                zone_column: "Zone",
+                value_column: "Load",
+            },
+        }
+    }
+
+    fn base_url(&self) -> &'static str {
+        ISO_NE_BASE_URL
+    }
+
+    /// Returns e.g. "20231209isone_actual.csv" or "20231209isone_forecast.csv".
+    fn file_name(&self, kind: FileKind, day: NaiveDate) -> String {
+        let suffix: &str = match kind {
+            FileKind::Actual => "actual",
+            FileKind::Forecast => "forecast",
+        };
+        format!("{}isone_{}.csv", day.format("%Y%m%d"), suffix)
+    }
+
+    /// Returns the web service resource of the day, e.g.
+    /// ".../realtimehourlydemand/day/20231209"; actual load is asked for per zone,
+    /// under "/location/{id}" below it.
+    fn url(&self, base_url: &str, kind: FileKind, day: NaiveDate) -> String {
+        let resource: &str = match kind {
+            FileKind::Actual => "realtimehourlydemand",
+            FileKind::Forecast => "reliabilityregionloadforecast",
+        };
+        format!(
+            "{}/{}/day/{}",
+            base_url.trim_end_matches('/'),
+            resource,
+            day.format("%Y%m%d")
+        )
+    }
+}
+
+/// Root of MISO's market reports.
+pub const MISO_BASE_URL: &str = "https://docs.misoenergy.org/marketreports";
+
+/// MISO, the Midcontinent ISO, by Local Resource Zone and region.
+///
+/// MISO's "Daily Forecast and Actual Load by Local Resource Zone" report
+/// (`df_al`) holds both the medium-term load forecast (MTLF) and the actual load of
+/// each hour, in columns such as "LRZ1 MTLF (MWh)" and "LRZ1 ActualLoad (MWh)", so
+/// the same file is read for both. Some zones are only reported together, e.g.
+/// "LRZ2_7". Its regional report (`rf_al`) has the same columns for the North,
+/// Central and South regions, which cut across those pairs and so are zones of
+/// their own here; both reports have the MISO total. Hours are given as the market
+/// day and hour ending in Eastern Standard Time, all year.
+///
+/// MISO publishes the reports as Excel workbooks, so
+/// [`crate::fetch::fetch_market_days`] reads the table out of both and saves it as
+/// one daily CSV of its own, with the zonal report's columns followed by the
+/// regions'.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Miso;
+
+impl Miso {
+    /// The regions of the regional report.
+    pub const REGIONS: [&'static str; 3] = ["North", "Central", "South"];
+
+    /// Every zone as spelled in the column names, with its full name.
+    const ZONES: [(&'static str, &'static str); 10] = [
+        ("LRZ1", "Local Resource Zone 1"),
+        ("LRZ2_7", "Local Resource Zones 2 and 7"),
+        ("LRZ3_5", "Local Resource Zones 3 and 5"),
+        ("LRZ4", "Local Resource Zone 4"),
+        ("LRZ6", "Local Resource Zone 6"),
+        ("LRZ8_9_10", "Local Resource Zones 8, 9 and 10"),
+        ("North", "MISO North"),
+        ("Central", "MISO Central"),
+        ("South", "MISO South"),
+        ("MISO", "MISO Total"),
+    ];
+
+    /// Returns whether `zone` is one of the regions, found in the regional report
+    /// rather than the zonal one.
+    pub fn is_region(zone: &IsoZone) -> bool {
+        Miso::REGIONS.contains(&zone.actual_name)
+    }
+
+    /// Returns the workbook of the regional report for `day` under `base_url`, e.g.
+    /// ".../20231209_rf_al.xls".
+    pub fn regional_url(base_url: &str, day: NaiveDate) -> String {
+        format!(
+            "{}/{}_rf_al.xls",
+            base_url.trim_end_matches('/'),
+            day.format("%Y%m%d")
+        )
+    }
+}
+
+impl Iso for Miso {
+    fn name(&self) -> &'static str {
+        "MISO"
+    }
+
+    fn timezone(&self) -> &'static str {
+        "EST"
+    }
+
+    fn zones(&self) -> Vec<IsoZone> {
+        Miso::ZONES
+            .iter()
+            .map(|&(name, full_name)| IsoZone {
+                actual_name: name,
+                forecast_name: name,
+                full_name,
+            })
+            .collect()
+    }
+
+    fn format(&self, kind: FileKind) -> FileFormat {
+        FileFormat {
+            time_column: "Market Day",
+            time_format: "%m/%d/%Y",
+            hour_column: Some("HourEnding"),
+            layout: Layout::Wide {
+                suffix: match kind {
+                    FileKind::Actual => " ActualLoad (MWh)",
+                    FileKind::Forecast => " MTLF (MWh)",
+                },
+            },
+        }
+    }
+
+    fn base_url(&self) -> &'static str {
+        MISO_BASE_URL
+    }
+
+    /// Returns e.g. "20231209_df_al.csv", the table of both reports saved as CSV, for
+    /// either kind.
+    fn file_name(&self, _kind: FileKind, day: NaiveDate) -> String {
+        format!("{}_df_al.csv", day.format("%Y%m%d"))
+    }
+
+    /// Returns the workbook of the zonal report, e.g. ".../20231209_df_al.xls".
+    fn url(&self, base_url: &str, _kind: FileKind, day: NaiveDate) -> String {
+        format!(
+            "{}/{}_df_al.xls",
+            base_url.trim_end_matches('/'),
+            day.format("%Y%m%d")
+        )
+    }
+}
+
+/// Root of the ENTSO-E transparency platform's API, which needs a security token.
+pub const ENTSO_E_BASE_URL: &str = "https://web-api.tp.entsoe.eu/api";
+
+/// The European grid, by bidding zone, through the ENTSO-E transparency platform.
+///
+/// The platform answers each bidding zone's actual total load and day-ahead load
+/// forecast with an XML document of quarter-hourly, half-hourly or hourly values
+/// in UTC, so [`crate::fetch::fetch_market_days`] averages them into hours and saves
+/// daily CSVs of its own, with one row per zone and hour: "Time Stamp" at the start
+/// of the hour in Central European Time, "Bidding Zone" and "Load". Only bidding
+/// zones that keep Central European Time are included, so every day has the same
+/// hours in all of them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EntsoE;
+
+impl EntsoE {
+    /// The bidding zones with the EIC codes the platform knows them by.
+    const ZONES: [(&'static str, &'static str, &'static str); 18] = [
+        ("AT", "Austria", "10YAT-APG------L"),
+        ("BE", "Belgium", "10YBE----------2"),
+        ("CH", "Switzerland", "10YCH-SWISSGRIDZ"),
+        ("CZ", "Czech Republic", "10YCZ-CEPS-----N"),
+        ("DE_LU", "Germany and Luxembourg", "10Y1001A1001A82H"),
+        ("DK_1", "Western Denmark", "10YDK-1--------W"),
+        ("DK_2", "Eastern Denmark", "10YDK-2--------M"),
+        ("ES", "Spain", "10YES-REE------0"),
+        ("FR", "France", "10YFR-RTE------C"),
+        ("HR", "Croatia", "10YHR-HEP------M"),
+        ("HU", "Hungary", "10YHU-MAVIR----U"),
+        ("IT_NORD", "Northern Italy", "10Y1001A1001A73I"),
+        ("NL", "Netherlands", "10YNL----------L"),
+        ("NO_1", "Southeastern Norway", "10YNO-1--------2"),
+        ("PL", "Poland", "10YPL-AREA-----S"),
+        ("SE_3", "Central Sweden", "10Y1001A1001A46L"),
+        ("SI", "Slovenia", "10YSI-ELES-----O"),
+        ("SK", "Slovakia", "10YSK-SEPS-----K"),
+    ];
+
+    /// Returns the EIC code of `zone` on the platform, e.g. "10YFR-RTE------C" for
+    /// "FR".
+    pub fn eic(zone: &IsoZone) -> Option<&'static str> {
+        EntsoE::ZONES
+            .iter()
+            .find(|(name, _, _)| *name == zone.actual_name)
+            .map(|(_, _, eic)| *eic)
+    }
+
+    /// Returns the platform's process type for `kind`: "A16", realised, for actual
+    /// load and "A01", day ahead, for forecasts.
+    pub fn process_type(kind: FileKind) -> &'static str {
+        match kind {
+            FileKind::Actual => "A16",
+            FileKind::Forecast => "A01",
+        }
+    }
+}
+
+impl Iso for EntsoE {
+    fn name(&self) -> &'static str {
+        "ENTSO-E"
+    }
+
+    fn timezone(&self) -> &'static str {
+        "CET"
+    }
+
+    fn zones(&self) -> Vec<IsoZone> {
+        EntsoE::ZONES
+            .iter()
+            .map(|&(name, full_name, _)| IsoZone {
+                actual_name: name,
+                forecast_name: name,
+                full_name,
+            })
+            .collect()
+    }
+
+    fn format(&self, _kind: FileKind) -> FileFormat {
+        FileFormat {
+            time_column: "Time Stamp",
+            time_format: "%m/%d/%Y %H:%M:%S",
+            hour_column: None,
+            layout: Layout::Long {
+                zone_column: "Bidding Zone",
+                value_column: "Load",
+            },
+        }
+    }
+
+    fn base_url(&self) -> &'static str {
+        ENTSO_E_BASE_URL
+    }
+
+    /// Returns e.g. "20231209entsoe_actual.csv" or "20231209entsoe_forecast.csv".
+    fn file_name(&self, kind: FileKind, day: NaiveDate) -> String {
+        let suffix: &str = match kind {
+            FileKind::Actual => "actual",
+            FileKind::Forecast => "forecast",
+        };
+        format!("{}entsoe_{}.csv", day.format("%Y%m%d"), suffix)
+    }
+
+    /// Returns the query for the total load of the day, from midnight to midnight in
+    /// Central European Time, e.g. "...?documentType=A65&processType=A16
+    /// &periodStart=202312082300&periodEnd=202312092300"; the bidding zone and
+    /// security token are added to it for each request.
+    fn url(&self, base_url: &str, kind: FileKind, day: NaiveDate) -> String {
+        let utc = |day: NaiveDate| -> NaiveDateTime {
+            let midnight: NaiveDateTime = day.and_hms_opt(0, 0, 0).unwrap_or_default();
+            Tz::CET
+                .from_local_datetime(&midnight)
+                .earliest()
+                .map_or(midnight, |local| local.naive_utc())
+        };
+        format!(
+            "{}?documentType=A65&processType={}&periodStart={}&periodEnd={}",
+            base_url.trim_end_matches('/'),
+            EntsoE::process_type(kind),
+            utc(day).format("%Y%m%d%H%M"),
+            utc(day + chrono::Duration::days(1)).format("%Y%m%d%H%M")
+        )
+    }
+}
+
+/// A market supported out of the box, named by its short name, e.g. "ercot".
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Market {
+    /// The New York ISO.
+    #[default]
+    Nyiso,
+    /// ERCOT, in Texas.
+    Ercot,
+    /// ISO New England.
+    IsoNe,
+    /// MISO, the Midcontinent ISO.
+    Miso,
+    /// The European bidding zones of the ENTSO-E transparency platform.
+    EntsoE,
+}
+
+impl Market {
+    /// Every supported market.
+    pub const ALL: [Market; 5] = [
+        Market::Nyiso,
+        Market::Ercot,
+        Market::IsoNe,
+        Market::Miso,
+        Market::EntsoE,
+    ];
+
+    /// Returns what the pipeline needs to know about the market.
+    pub fn iso(self) -> &'static dyn Iso {
+        match self {
+            Market::Nyiso => &Nyiso,
+            Market::Ercot => &Ercot,
+            Market::IsoNe => &IsoNe,
+            Market::Miso => &Miso,
+            Market::EntsoE => &EntsoE,
+        }
+    }
+}
+
+impl fmt::Display for Market {
+    /// Writes the market's short name, e.g. "NYISO".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.iso().name())
+    }
+}
+
+impl FromStr for Market {
+    type Err = MugenError;
+
+    /// Parses the market's short name, ignoring case and punctuation, so "ISO-NE" and
+    /// "isone" both work.
+    fn from_str(s: &str) -> Result<Self> {
+        let key = |text: &str| -> String {
+            text.chars()
+                .filter(char::is_ascii_alphanumeric)
+                .map(|c| c.to_ascii_lowercase())
+                .collect()
+        };
+        Market::ALL
+            .into_iter()
+            .find(|market| key(market.iso().name()) == key(s))
+            .ok_or_else(|| MugenError::UnknownMarket(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, month, day).unwrap()
+    }
+
+    #[test]
+    fn markets_parse_from_their_names() {
+        for market in Market::ALL {
+            assert_eq!(market.to_string().parse::<Market>().unwrap(), market);
+        }
+        assert_eq!("isone".parse::<Market>().unwrap(), Market::IsoNe);
+        assert_eq!("Entso-E".parse::<Market>().unwrap(), Market::EntsoE);
+        assert!(matches!(
+            "pjm".parse::<Market>(),
+            Err(MugenError::UnknownMarket(_))
+        ));
+    }
+
+    #[test]
+    fn zones_are_found_by_any_spelling() {
+        assert_eq!(Ercot.zone("far west").unwrap().actual_name, "FAR_WEST");
+        assert_eq!(Ercot.zone("SouthCentral").unwrap().actual_name, "SOUTH_C");
+        assert_eq!(
+            Nyiso.zone("N.Y.C.").unwrap(),
+            IsoZone::from(Zone::NewYorkCity)
+        );
+        assert!(matches!(Miso.zone("LRZ2"), Err(MugenError::UnknownZone(_))));
+        for market in Market::ALL {
+            let iso: &dyn Iso = market.iso();
+            for zone in iso.zones() {
+                assert_eq!(iso.zone(zone.full_name).unwrap(), zone, "{}", market);
+            }
+        }
+    }
+
+    #[test]
+    fn wide_files_name_a_column_per_zone() {
+        let zone: IsoZone = Miso.zone("LRZ1").unwrap();
+        let actual: FileFormat = Miso.format(FileKind::Actual);
+        let forecast: FileFormat = Miso.format(FileKind::Forecast);
+        assert_eq!(
+            actual.value_column(&zone, FileKind::Actual),
+            "LRZ1 ActualLoad (MWh)"
+        );
+        assert_eq!(
+            forecast.value_column(&zone, FileKind::Forecast),
+            "LRZ1 MTLF (MWh)"
+        );
+
+        let coast: IsoZone = Ercot.zone("coast").unwrap();
+        let format: FileFormat = Ercot.format(FileKind::Forecast);
+        assert_eq!(format.value_column(&coast, FileKind::Forecast), "Coast");
+        assert_eq!(format.hour_column, Some("HourEnding"));
+    }
+
+    #[test]
+    fn iso_ne_locations_round_trip() {
+        for zone in IsoNe.zones() {
+            let location: u32 = IsoNe::location(&zone).unwrap();
+            assert_eq!(IsoNe::zone_at(location), Some(zone));
+        }
+        assert_eq!(
+            IsoNe::location(&IsoNe.zone("connecticut").unwrap()),
+            Some(4004)
+        );
+        assert_eq!(IsoNe::zone_at(4000), None);
+    }
+
+    #[test]
+    fn file_names_and_urls_are_per_day() {
+        assert_eq!(
+            Ercot.file_name(FileKind::Actual, day(12, 9)),
+            "20231209ercot_actual.csv"
+        );
+        assert_eq!(
+            IsoNe.url("https://example.com/api/", FileKind::Forecast, day(12, 9)),
+            "https://example.com/api/reliabilityregionloadforecast/day/20231209"
+        );
+        assert_eq!(
+            Miso.url(MISO_BASE_URL, FileKind::Actual, day(12, 9)),
+            format!("{}/20231209_df_al.xls", MISO_BASE_URL)
+        );
+        // ENTSO-E asks for the Central European day in UTC, an hour behind in
+        // winter and two in summer.
+        let winter: String = EntsoE.url(ENTSO_E_BASE_URL, FileKind::Actual, day(12, 9));
+        assert!(winter.ends_with("periodStart=202312082300&periodEnd=202312092300"));
+        let summer: String = EntsoE.url(ENTSO_E_BASE_URL, FileKind::Forecast, day(7, 1));
+        assert!(summer.contains("processType=A01"));
+        assert!(summer.ends_with("periodStart=202306302200&periodEnd=202307012200"));
+    }
+}
@@ -0,0 +1,117 @@
+//! Loading and processing of zonal load, load forecasts and prices from NYISO and
+//! other markets.
+//!
+//! Raw files are read into polars DataFrames and reshaped into
+//! [`dataset::TimeSeriesDataset`]s, single series that know their time and value
+//! columns, unit and timezone. Actual load joined against a forecast is what the
+//! metrics, intervals, backtests and exports work on. Reading, processing, joining
+//! and metrics run inside `tracing` spans named `load`, `process`, `join` and
+//! `metric`, so any subscriber can time each stage.
+//!
+//! This crate has no charting or command-line dependencies, so services can embed
+//! it on its own. Charts live in `mugen-viz` and the `mugen_park` binary in
+//! `mugen-cli`.
+//!
+//! # Modules
+//!
+//! Loading data:
+//!
+//! * [`data`] - Reading the NYISO actual, real-time, forecast and price CSVs, and
+//!   reshaping them eagerly or as lazy polars plans ending in [`data::load_joined`].
+//! * [`iso`] - What differs between markets; NYISO is the default, with ERCOT,
+//!   ISO-NE, MISO and ENTSO-E readers, all read by [`data::process_load`].
+//! * [`source`] - Loading from local files, HTTP, S3 or SQLite.
+//! * [`fetch`] - Downloading the daily files of each market.
+//! * [`weather`] and [`eia`] - Zone temperatures and the demand reported to the EIA.
+//! * [`checksum`], [`catalog`] and [`archive`] - Checking downloads, listing data
+//!   files and compacting past months into Parquet.
+//! * [`synthetic`] - Data in the NYISO layout for tests and examples.
+//!
+//! Evaluating forecasts:
+//!
+//! * [`metrics`] - Error metrics over actual load joined against a forecast.
+//! * [`interval`] - Empirical prediction intervals from past errors by hour.
+//! * [`ensemble`] and [`baseline`] - Blended forecasts and persistence and
+//!   Holt-Winters baselines.
+//! * [`split`] and [`backtest`] - Train/test splits over past periods and scoring a
+//!   forecaster on each.
+//! * [`scenario`] - What-if adjustments such as 5% more load.
+//! * [`features`] and `onnx` - A table to train models on, and running ONNX models
+//!   over it.
+//! * [`export`] - Writing tables to CSV or Parquet.
+//!
+//! Running:
+//!
+//! * [`resources`] - Thread and memory budgets for loading.
+//! * [`schedule`] - When recurring runs are due, in local time.
+//! * `history` - Each run's metrics kept in SQLite.
+//! * [`alert`] - Rules on a run's data and errors, such as gaps in the actual load.
+//!
+//! # Features
+//!
+//! * `fetch` - Downloading from NYISO, ERCOT, ISO-NE, ENTSO-E, HTTP and S3 in [`fetch`]
+//!   and [`source`], temperatures in [`weather`] and EIA demand in [`eia`].
+//! * `onnx` - Running ONNX models on the feature table in `onnx`, with the ONNX
+//!   Runtime library loaded at run time from `ORT_DYLIB_PATH`.
+//! * `parquet` - Writing Parquet files with [`export::write_table`] and
+//!   [`archive::compact`], and reading deleted daily files back from the archive.
+//! * `sqlite` - Reading from SQLite databases in [`source`] and the metrics history
+//!   in `history`, with a bundled build of SQLite.
+
+/// Rules on the data and the forecast checked after every run, such as data gaps.
+pub mod alert;
+/// Compacting old daily CSVs into one Parquet file per kind and month.
+pub mod archive;
+/// Forecasters rolled over past periods and scored on each.
+pub mod backtest;
+/// Forecasts made from the load history alone: persistence and Holt-Winters.
+pub mod baseline;
+/// A JSON catalog of the data files in a directory, with their spans, zones and hashes.
+pub mod catalog;
+/// Recording and checking the size and hash of downloaded files.
+pub mod checksum;
+/// Reading NYISO CSVs and reshaping them into per-zone load and forecast frames.
+pub mod data;
+/// Time series with their time column, value columns, unit, timezone and frequency.
+pub mod dataset;
+/// The error type shared by every mugen crate.
+pub mod error;
+/// Hourly demand from the EIA open-data API, compared against the reported actuals.
+pub mod eia;
+/// Forecasts blended from several, evenly or by their recent skill.
+pub mod ensemble;
+/// Writing tables such as the joined actual and forecast to CSV or Parquet files.
+pub mod export;
+/// Lagged load, calendar, holiday and temperature features for training models.
+pub mod features;
+/// The daily NYISO reports and download settings, and with the "fetch" feature
+/// concurrent downloads from every market, with blocking wrappers.
+pub mod fetch;
+/// Each run's metrics and metadata appended to a SQLite database.
+#[cfg(feature = "sqlite")]
+pub mod history;
+/// Empirical prediction intervals from the spread of past errors by hour of day.
+pub mod interval;
+/// The markets whose files can be loaded, their zones, file layouts and timezones.
+pub mod iso;
+/// Forecast error metrics over actual load joined against a forecast.
+pub mod metrics;
+/// Forecasts from ONNX models run on the feature table with ONNX Runtime.
+#[cfg(feature = "onnx")]
+pub mod onnx;
+/// Limits on the threads and memory used for loading and processing.
+pub mod resources;
+/// What-if adjustments to actual or forecast load, such as 5% more load.
+pub mod scenario;
+/// Cron-like schedules in a timezone, for running jobs at set local times.
+pub mod schedule;
+/// Where raw data is loaded from: local CSVs, HTTP, S3 or SQLite.
+pub mod source;
+/// Expanding and sliding train/test splits over whole days, for fair evaluation.
+pub mod split;
+/// Realistic synthetic load and forecasts for tests, examples and benchmarks.
+pub mod synthetic;
+/// Hourly temperatures of each zone's weather station, downloaded into daily files.
+pub mod weather;
+/// The NYISO load zones and their spellings in the source files.
+pub mod zone;
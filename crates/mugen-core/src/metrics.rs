@@ -0,0 +1,118 @@
+use polars::prelude::*;
+
+use crate::dataset::TimeSeriesDataset;
+use crate::error::Result;
+
+/// Computes the mean absolute percentage error of the forecast, in percent.
+///
+/// # Arguments
+///
+/// * `joined` - Actual load joined against the forecast, as returned by
+///   [`crate::data::join_actual_forecast`].
+///
+/// # Returns
+///
+/// * A `Result` containing the error, NaN if `joined` is empty, or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns an error if the "Actual" or "Residual" column is missing or not numeric.
+pub fn mape(joined: &TimeSeriesDataset) -> Result<f64> {
+    let actual = joined.values("Actual")?;
+    let absolute_error = joined.values("Residual")?.apply_values(f64::abs);
+    Ok((&absolute_error / &actual).mean().unwrap_or(f64::NAN) * 100.0)
+}
+
+/// Computes the forecast peak minus the actual peak, as a percentage of the actual
+/// peak.
+///
+/// # Arguments
+///
+/// * `joined` - Actual load joined against the forecast.
+///
+/// # Returns
+///
+/// * A `Result` containing the error, NaN if `joined` is empty, or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns an error if the "Actual" or "Forecast" column is missing or not numeric.
+pub fn peak_error(joined: &TimeSeriesDataset) -> Result<f64> {
+    let actual_peak: f64 = joined.values("Actual")?.max().unwrap_or(f64::NAN);
+    let forecast_peak: f64 = joined.values("Forecast")?.max().unwrap_or(f64::NAN);
+    Ok((forecast_peak - actual_peak) / actual_peak * 100.0)
+}
+
+/// Computes the mean forecast minus the mean actual, as a percentage of the mean
+/// actual.
+///
+/// # Arguments
+///
+/// * `joined` - Actual load joined against the forecast.
+///
+/// # Returns
+///
+/// * A `Result` containing the bias, NaN if `joined` is empty, or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns an error if the "Actual" or "Forecast" column is missing or not numeric.
+pub fn bias(joined: &TimeSeriesDataset) -> Result<f64> {
+    let actual_mean: f64 = joined.values("Actual")?.mean().unwrap_or(f64::NAN);
+    let forecast_mean: f64 = joined.values("Forecast")?.mean().unwrap_or(f64::NAN);
+    Ok((forecast_mean - actual_mean) / actual_mean * 100.0)
+}
+
+/// Computes the share of hours whose actual load falls inside the prediction
+/// interval, in percent; an interval learned for 80% coverage should come close
+/// to 80.
+///
+/// # Arguments
+///
+/// * `truth` - The processed actual load.
+/// * `interval` - The prediction interval, as returned by
+///   [`crate::interval::ErrorQuantiles::interval`].
+///
+/// # Returns
+///
+/// * A `Result` containing the coverage, NaN if no hour has both, or a
+///   `MugenError`.
+///
+/// # Errors
+///
+/// Returns an error if a time column is not a datetime, or the "Lower" or "Upper"
+/// column is missing or not numeric.
+pub fn coverage(truth: &TimeSeriesDataset, interval: &TimeSeriesDataset) -> Result<f64> {
+    let actual = truth.values_by_timestamp(truth.value_column())?;
+    let lower = interval.values_by_timestamp("Lower")?;
+    let upper = interval.values_by_timestamp("Upper")?;
+    let inside: Vec<bool> = lower
+        .iter()
+        .filter_map(|(at, lower)| Some((*actual.get(at)?, *lower, *upper.get(at)?)))
+        .map(|(actual, lower, upper)| lower <= actual && actual <= upper)
+        .collect();
+    let count: usize = inside.iter().filter(|inside| **inside).count();
+    Ok(count as f64 / inside.len() as f64 * 100.0)
+}
+
+/// Computes the mean width of the prediction interval, as a percentage of the
+/// forecast; narrower is better at the same coverage.
+///
+/// # Arguments
+///
+/// * `interval` - The prediction interval, as returned by
+///   [`crate::interval::ErrorQuantiles::interval`].
+///
+/// # Returns
+///
+/// * A `Result` containing the width, NaN if `interval` is empty, or a
+///   `MugenError`.
+///
+/// # Errors
+///
+/// Returns an error if the "Forecast", "Lower" or "Upper" column is missing or not
+/// numeric.
+pub fn interval_width(interval: &TimeSeriesDataset) -> Result<f64> {
+    let width = &interval.values("Upper")? - &interval.values("Lower")?;
+    Ok((&width / &interval.values("Forecast")?).mean().unwrap_or(f64::NAN) * 100.0)
+}
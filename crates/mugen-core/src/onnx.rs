@@ -0,0 +1,166 @@
+use ort::session::Session;
+use ort::value::Tensor;
+use polars::prelude::*;
+
+use crate::dataset::TimeSeriesDataset;
+use crate::error::{MugenError, Result};
+
+/// Name of the dataset a model's forecast is returned as.
+const MODEL_FRAME: &str = "model forecast";
+
+/// Environment variable holding the path of the ONNX Runtime library.
+const RUNTIME_VARIABLE: &str = "ORT_DYLIB_PATH";
+
+/// A forecasting model trained elsewhere, e.g. on a table written by
+/// `export features`, run with ONNX Runtime.
+///
+/// The model takes one float input of shape `[hours, features]`, the feature
+/// columns of [`crate::features::features`] in table order, and returns the load
+/// of each hour as its first output, of shape `[hours]` or `[hours, 1]`.
+///
+/// ONNX Runtime itself is loaded when the first model is, from the library
+/// `ORT_DYLIB_PATH` points to, e.g. `libonnxruntime.so`.
+pub struct OnnxModel {
+    /// Where the model was loaded from, for error messages.
+    path: String,
+    /// The loaded model.
+    session: Session,
+}
+
+impl OnnxModel {
+    /// Loads the model at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InvalidArgument` if `ORT_DYLIB_PATH` is unset or names
+    /// no file, and `MugenError::Model` if ONNX Runtime cannot be loaded or the file
+    /// is not a valid model.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use chrono::NaiveDate;
+    /// use mugen_core::data::process_truth;
+    /// use mugen_core::features::{features, DEFAULT_LAGS};
+    /// use mugen_core::onnx::OnnxModel;
+    /// use mugen_core::synthetic::Synthetic;
+    /// use mugen_core::zone::Zone;
+    ///
+    /// # fn main() -> mugen_core::error::Result<()> {
+    /// let first = NaiveDate::from_ymd_opt(2023, 12, 1).unwrap();
+    /// let last = NaiveDate::from_ymd_opt(2023, 12, 10).unwrap();
+    /// let truth = process_truth(Synthetic::default().truth(first..=last)?, Zone::NewYorkCity)?;
+    /// let table = features(&truth, None, &DEFAULT_LAGS)?;
+    /// let forecast = OnnxModel::load("models/load.onnx")?.forecast(&table)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load(path: &str) -> Result<Self> {
+        // ort panics rather than failing when it cannot find the library.
+        match std::env::var_os(RUNTIME_VARIABLE) {
+            Some(library) if std::path::Path::new(&library).is_file() => {}
+            Some(library) => {
+                return Err(MugenError::InvalidArgument(format!(
+                    "{} is set to \"{}\", which is not a file; point it to the ONNX \
+                     Runtime library, e.g. libonnxruntime.so",
+                    RUNTIME_VARIABLE,
+                    library.to_string_lossy()
+                )))
+            }
+            None => {
+                return Err(MugenError::InvalidArgument(format!(
+                    "{} is not set; point it to the ONNX Runtime library, e.g. \
+                     libonnxruntime.so",
+                    RUNTIME_VARIABLE
+                )))
+            }
+        }
+        let failed = |source: ort::Error| MugenError::Model {
+            path: path.to_string(),
+            source,
+        };
+        let session: Session = Session::builder()
+            .map_err(failed)?
+            .commit_from_file(path)
+            .map_err(failed)?;
+        Ok(OnnxModel {
+            path: path.to_string(),
+            session,
+        })
+    }
+
+    /// Forecasts the load of every hour in a feature table.
+    ///
+    /// Flags count as 1 when set and 0 otherwise; missing values, such as the
+    /// temperature at an hour without a reading, are passed as NaN.
+    ///
+    /// # Arguments
+    ///
+    /// * `features` - The features, as returned by [`crate::features::features`]
+    ///   with the same lags and columns the model was trained on.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing a dataset over "Time Stamp" with a "Forecast" column,
+    ///   in the unit and timezone of `features`, which joins against actual load like
+    ///   any processed forecast, or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::Model` if the model fails to run,
+    /// `MugenError::InvalidArgument` if it returns a value count other than one per
+    /// hour, and an error if a feature column is not numeric or boolean.
+    pub fn forecast(&mut self, features: &TimeSeriesDataset) -> Result<TimeSeriesDataset> {
+        let frame: &DataFrame = features.frame();
+        let columns: Vec<&Series> = frame
+            .get_columns()
+            .iter()
+            .filter(|column| {
+                column.name() != features.time_column() && column.name() != features.value_column()
+            })
+            .collect();
+        let hours: usize = frame.height();
+        let mut values: Vec<f32> = vec![0.0; hours * columns.len()];
+        for (index, column) in columns.iter().enumerate() {
+            let column: Series = column.cast(&DataType::Float32)?;
+            for (hour, value) in column.f32()?.into_iter().enumerate() {
+                values[hour * columns.len() + index] = value.unwrap_or(f32::NAN);
+            }
+        }
+
+        let failed = |source: ort::Error| MugenError::Model {
+            path: self.path.clone(),
+            source,
+        };
+        let input = Tensor::from_array(([hours, columns.len()], values)).map_err(failed)?;
+        let outputs = self.session.run(ort::inputs![input]).map_err(failed)?;
+        let (_, predicted) = outputs[0].try_extract_tensor::<f32>().map_err(failed)?;
+        if predicted.len() != hours {
+            return Err(MugenError::InvalidArgument(format!(
+                "model \"{}\" returned {} values for {} hours; it should return one per hour",
+                self.path,
+                predicted.len(),
+                hours
+            )));
+        }
+
+        let forecast: DataFrame = DataFrame::new(vec![
+            frame
+                .column(features.time_column())?
+                .clone()
+                .with_name("Time Stamp"),
+            Series::new(
+                "Forecast",
+                predicted
+                    .iter()
+                    .map(|value| *value as f64)
+                    .collect::<Vec<f64>>(),
+            ),
+        ])?;
+        Ok(
+            TimeSeriesDataset::new(MODEL_FRAME, forecast, "Time Stamp", vec!["Forecast".into()])?
+                .with_unit(features.unit())
+                .with_timezone(features.timezone()),
+        )
+    }
+}
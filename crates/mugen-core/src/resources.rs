@@ -0,0 +1,176 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor};
+
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Rows of each file parsed to measure how much memory its rows take once loaded.
+const SAMPLE_ROWS: usize = 1000;
+
+/// Limits on the threads and memory loading and processing may use, so the same
+/// binary behaves on a laptop and on a many-core server.
+///
+/// Pass them to the loaders that take them, such as
+/// [`crate::data::read_multiple_csvs_with`] and [`crate::data::load_joined_with`].
+/// Loads whose estimated memory exceeds the budget run lazy plans in streaming
+/// mode, in batches, and read files one at a time instead of in parallel.
+///
+/// `threads` bounds the files read in parallel. polars keeps its own thread pool,
+/// sized from the `POLARS_MAX_THREADS` environment variable when first used, so a
+/// program limiting `threads` should set that variable as well, first thing in
+/// `main` before any other thread runs.
+///
+/// # Examples
+///
+/// ```no_run
+/// use mugen_core::data::read_multiple_csvs_with;
+/// use mugen_core::resources::Resources;
+///
+/// let resources = Resources {
+///     threads: Some(4),
+///     memory_mb: Some(2048),
+/// };
+/// let combined_df = read_multiple_csvs_with(vec!["data1.csv", "data2.csv"], &resources)
+///     .expect("Failed to read and combine CSV files");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Resources {
+    /// Threads for parallel file reads; `None` for one per core.
+    pub threads: Option<usize>,
+    /// Approximate memory a load may use, in MiB; `None` for no limit.
+    pub memory_mb: Option<u64>,
+}
+
+impl Resources {
+    /// Returns how many threads parallel work may use.
+    pub fn threads(&self) -> usize {
+        self.threads
+            .unwrap_or_else(|| {
+                std::thread::available_parallelism()
+                    .map(|threads| threads.get())
+                    .unwrap_or(1)
+            })
+            .max(1)
+    }
+
+    /// Returns whether loading `paths` at once is expected to fit the memory budget.
+    ///
+    /// Each file's memory is estimated by parsing its first rows and scaling their
+    /// size in memory by the size of the file. Files that cannot be inspected count
+    /// as empty, since reading them fails anyway.
+    pub fn fits(&self, paths: &[&str]) -> bool {
+        let Some(memory_mb) = self.memory_mb else {
+            return true;
+        };
+        let bytes: u64 = paths.iter().map(|path| estimated_size(path)).sum();
+        bytes <= memory_mb.saturating_mul(1024 * 1024)
+    }
+}
+
+/// Estimates the bytes the rows of the CSV file at `path` take once parsed, from
+/// its first [`SAMPLE_ROWS`] rows; 0 if the file cannot be read or parsed.
+fn estimated_size(path: &str) -> u64 {
+    let Ok(file) = File::open(path) else {
+        return 0;
+    };
+    let Ok(total) = file.metadata().map(|metadata| metadata.len()) else {
+        return 0;
+    };
+    let mut reader = BufReader::new(file);
+    let mut sample: Vec<u8> = Vec::new();
+    // The header and the sampled rows.
+    for _ in 0..=SAMPLE_ROWS {
+        match reader.read_until(b'\n', &mut sample) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+    }
+    let Ok(parsed) = CsvReader::new(Cursor::new(&sample))
+        .infer_schema(None)
+        .has_header(true)
+        .finish()
+    else {
+        return 0;
+    };
+    if sample.is_empty() {
+        return 0;
+    }
+    (parsed.estimated_size() as f64 * total as f64 / sample.len() as f64) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::data::{load_joined_with, read_multiple_csvs_with};
+    use crate::synthetic::Synthetic;
+    use crate::zone::Zone;
+
+    /// Writes two days of synthetic actual load and a forecast as CSVs under a
+    /// directory of their own, returning their paths.
+    fn files(name: &str) -> (String, String) {
+        let dir: PathBuf =
+            std::env::temp_dir().join(format!("mugen-resources-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let days = NaiveDate::from_ymd_opt(2023, 12, 8).unwrap()
+            ..=NaiveDate::from_ymd_opt(2023, 12, 9).unwrap();
+        let synthetic: Synthetic = Synthetic::default();
+        let write = |file: &str, mut frame: DataFrame| -> String {
+            let path: PathBuf = dir.join(file);
+            CsvWriter::new(File::create(&path).unwrap())
+                .finish(&mut frame)
+                .unwrap();
+            path.to_string_lossy().into_owned()
+        };
+        (
+            write("truth.csv", synthetic.truth(days.clone()).unwrap()),
+            write("forecast.csv", synthetic.forecast(days).unwrap()),
+        )
+    }
+
+    #[test]
+    fn the_estimate_is_held_against_the_budget() {
+        let (truth, _) = files("fits");
+        let loaded: usize = read_multiple_csvs_with(vec![&truth], &Resources::default())
+            .unwrap()
+            .estimated_size();
+        let estimate: u64 = estimated_size(&truth);
+        assert!(estimate > 0);
+        assert!((estimate as f64 / loaded as f64 - 1.0).abs() < 0.5);
+
+        let budget = |memory_mb: Option<u64>| Resources {
+            threads: None,
+            memory_mb,
+        };
+        assert!(budget(None).fits(&[&truth]));
+        assert!(budget(Some(1024)).fits(&[&truth]));
+        assert!(!budget(Some(0)).fits(&[&truth]));
+        assert!(budget(Some(0)).fits(&["no such file.csv"]));
+        std::fs::remove_dir_all(Path::new(&truth).parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn loads_over_the_budget_stream_to_the_same_result() {
+        let (truth, forecast) = files("stream");
+        let over: Resources = Resources {
+            threads: Some(1),
+            memory_mb: Some(0),
+        };
+        assert!(!over.fits(&[&truth, &forecast]));
+        let streamed = load_joined_with(&[&truth], &forecast, Zone::NewYorkCity, &over).unwrap();
+        let at_once = load_joined_with(
+            &[&truth],
+            &forecast,
+            Zone::NewYorkCity,
+            &Resources::default(),
+        )
+        .unwrap();
+        assert_eq!(streamed.height(), 48);
+        assert!(streamed.frame().equals_missing(at_once.frame()));
+        std::fs::remove_dir_all(Path::new(&truth).parent().unwrap()).unwrap();
+    }
+}
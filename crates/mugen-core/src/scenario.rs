@@ -0,0 +1,337 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Timelike};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::dataset::TimeSeriesDataset;
+use crate::error::{MugenError, Result};
+
+/// Which series a scenario adjusts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Target {
+    /// The forecast, e.g. to see how a forecast that ran hot would have scored.
+    #[default]
+    Forecast,
+    /// The actual load, e.g. to see how the forecast holds up on a hotter day.
+    Actual,
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Target::Forecast => "forecast",
+            Target::Actual => "actual",
+        })
+    }
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "forecast" => Ok(Target::Forecast),
+            "actual" => Ok(Target::Actual),
+            _ => Err(format!(
+                "unknown target \"{}\", expected forecast or actual",
+                name
+            )),
+        }
+    }
+}
+
+/// One what-if change to a load series.
+///
+/// On the command line an adjustment is written as `scale=5%`, `shift=1h` or
+/// `add=500@18`, where `@18` limits it to the hour starting at 18:00.
+///
+/// # Examples
+///
+/// ```
+/// use mugen_core::scenario::Adjustment;
+///
+/// let evening: Adjustment = "add=500@18".parse().unwrap();
+/// assert_eq!(evening, Adjustment::Add { megawatts: 500.0, hour: Some(18) });
+/// assert_eq!(evening.to_string(), "add 500 MW at 18:00");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Adjustment {
+    /// Scales the load by `percent`, e.g. 5 for 5% more.
+    Scale {
+        /// Change in percent; negative for less load.
+        percent: f64,
+        /// Hour of the day to scale, 0 to 23, or every hour.
+        hour: Option<u32>,
+    },
+    /// Moves the whole series `hours` later, so its peak comes `hours` later too;
+    /// negative moves it earlier.
+    Shift {
+        /// Hours to move by.
+        hours: i64,
+    },
+    /// Adds `megawatts` to the load, e.g. a new industrial customer.
+    Add {
+        /// Load to add; negative to take away.
+        megawatts: f64,
+        /// Hour of the day to add to, 0 to 23, or every hour.
+        hour: Option<u32>,
+    },
+}
+
+impl fmt::Display for Adjustment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let at = |hour: &Option<u32>| match hour {
+            Some(hour) => format!(" at {:02}:00", hour),
+            None => String::new(),
+        };
+        match self {
+            Adjustment::Scale { percent, hour } => {
+                write!(f, "scale {:+}%{}", percent, at(hour))
+            }
+            Adjustment::Shift { hours } => write!(f, "shift {:+}h", hours),
+            Adjustment::Add { megawatts, hour } => {
+                write!(f, "add {} MW{}", megawatts, at(hour))
+            }
+        }
+    }
+}
+
+impl FromStr for Adjustment {
+    type Err = String;
+
+    fn from_str(text: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid adjustment \"{}\", expected e.g. scale=5%, shift=1h or add=500@18",
+                text
+            )
+        };
+        let (kind, amount) = text.split_once('=').ok_or_else(invalid)?;
+        let (amount, hour) = match amount.split_once('@') {
+            Some((amount, hour)) => (amount, Some(hour.trim().parse().map_err(|_| invalid())?)),
+            None => (amount, None),
+        };
+        let amount: &str = amount.trim();
+        let adjustment: Adjustment = match kind.trim().to_ascii_lowercase().as_str() {
+            "scale" => Adjustment::Scale {
+                percent: amount
+                    .trim_end_matches('%')
+                    .parse()
+                    .map_err(|_| invalid())?,
+                hour,
+            },
+            "shift" if hour.is_none() => Adjustment::Shift {
+                hours: amount
+                    .trim_end_matches('h')
+                    .parse()
+                    .map_err(|_| invalid())?,
+            },
+            "add" => Adjustment::Add {
+                megawatts: amount
+                    .trim_end_matches("MW")
+                    .trim()
+                    .parse()
+                    .map_err(|_| invalid())?,
+                hour,
+            },
+            _ => return Err(invalid()),
+        };
+        match adjustment.problem() {
+            Some(problem) => Err(problem),
+            None => Ok(adjustment),
+        }
+    }
+}
+
+impl Adjustment {
+    /// Describes what is wrong with the adjustment, or `None` if it is valid.
+    pub fn problem(&self) -> Option<String> {
+        match self {
+            Adjustment::Scale {
+                hour: Some(hour), ..
+            }
+            | Adjustment::Add {
+                hour: Some(hour), ..
+            } if *hour > 23 => Some(format!(
+                "adjustment hour {} must be 0 to 23, the hour of the day it starts",
+                hour
+            )),
+            Adjustment::Scale { percent, .. } if !(percent.is_finite() && *percent > -100.0) => {
+                Some(format!("scale {}% must be above -100%", percent))
+            }
+            Adjustment::Add { megawatts, .. } if !megawatts.is_finite() => {
+                Some(format!("add {} MW must be a number", megawatts))
+            }
+            _ => None,
+        }
+    }
+
+    /// Applies the adjustment to the primary values of `dataset`.
+    ///
+    /// # Arguments
+    ///
+    /// * `dataset` - A processed load series, actual or forecast.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing a dataset with the time and primary value columns of
+    ///   `dataset`, adjusted, in its unit and timezone, or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InvalidArgument` if the adjustment is invalid, and an
+    /// error if the time column of `dataset` is not a datetime or its value column is
+    /// not numeric.
+    pub fn apply(&self, dataset: &TimeSeriesDataset) -> Result<TimeSeriesDataset> {
+        if let Some(problem) = self.problem() {
+            return Err(MugenError::InvalidArgument(problem));
+        }
+        let in_hour = |at: i64, hour: &Option<u32>| {
+            hour.is_none_or(|hour| {
+                DateTime::from_timestamp_millis(at).is_some_and(|at| at.hour() == hour)
+            })
+        };
+        let mut stamps: Vec<i64> = Vec::with_capacity(dataset.height());
+        let mut values: Vec<Option<f64>> = Vec::with_capacity(dataset.height());
+        let original = dataset.values(dataset.value_column())?;
+        for (at, value) in dataset.timestamps()?.into_iter().zip(&original) {
+            let Some(at) = at else {
+                continue;
+            };
+            let (at, value): (i64, Option<f64>) = match self {
+                Adjustment::Scale { percent, hour } if in_hour(at, hour) => {
+                    (at, value.map(|value| value * (1.0 + percent / 100.0)))
+                }
+                Adjustment::Shift { hours } => (at + hours * 3_600_000, value),
+                Adjustment::Add { megawatts, hour } if in_hour(at, hour) => {
+                    (at, value.map(|value| value + megawatts))
+                }
+                _ => (at, value),
+            };
+            stamps.push(at);
+            values.push(value);
+        }
+        let frame: DataFrame = DataFrame::new(vec![
+            Int64Chunked::from_vec(dataset.time_column(), stamps)
+                .into_datetime(TimeUnit::Milliseconds, None)
+                .into_series(),
+            Series::new(dataset.value_column(), values),
+        ])?;
+        Ok(TimeSeriesDataset::new(
+            dataset.name(),
+            frame,
+            dataset.time_column(),
+            vec![dataset.value_column().to_string()],
+        )?
+        .with_unit(dataset.unit())
+        .with_timezone(dataset.timezone()))
+    }
+}
+
+/// Applies `adjustments` to `dataset` one after another, e.g. to scale the load by
+/// 5% and then add 500 MW at 18:00 to the scaled load.
+///
+/// # Errors
+///
+/// Returns the first error of an adjustment, see [`Adjustment::apply`].
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use mugen_core::data::process_pred;
+/// use mugen_core::scenario::{scenario, Adjustment};
+/// use mugen_core::synthetic::Synthetic;
+/// use mugen_core::zone::Zone;
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let day = NaiveDate::from_ymd_opt(2023, 12, 9).unwrap();
+/// let forecast = process_pred(Synthetic::default().forecast(day..=day)?, Zone::NewYorkCity)?;
+/// let adjustments = [
+///     Adjustment::Scale { percent: 5.0, hour: None },
+///     Adjustment::Add { megawatts: 500.0, hour: Some(18) },
+/// ];
+/// let heatwave = scenario(&forecast, &adjustments)?;
+/// assert_eq!(heatwave.height(), forecast.height());
+/// # Ok(())
+/// # }
+/// ```
+pub fn scenario(
+    dataset: &TimeSeriesDataset,
+    adjustments: &[Adjustment],
+) -> Result<TimeSeriesDataset> {
+    let mut adjusted: TimeSeriesDataset = dataset.clone();
+    for adjustment in adjustments {
+        adjusted = adjustment.apply(&adjusted)?;
+    }
+    Ok(adjusted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::testing::hourly;
+    use crate::dataset::HOUR_MS;
+
+    /// Two days of a flat 1000 MW forecast from the epoch.
+    fn flat() -> TimeSeriesDataset {
+        hourly(
+            "forecast",
+            0,
+            vec![Series::new("Forecast", vec![1000.0; 48])],
+        )
+        .with_unit("MW")
+    }
+
+    #[test]
+    fn an_hourly_addition_only_touches_that_hour() {
+        let evening: Adjustment = "add=500@18".parse().unwrap();
+        let adjusted: TimeSeriesDataset = evening.apply(&flat()).unwrap();
+        assert_eq!(adjusted.unit(), "MW");
+        let values = adjusted.values_by_timestamp("Forecast").unwrap();
+        for hour in 0..48 {
+            let expected: f64 = if hour % 24 == 18 { 1500.0 } else { 1000.0 };
+            assert_eq!(values[&(hour * HOUR_MS)], expected, "hour {}", hour);
+        }
+    }
+
+    #[test]
+    fn adjustments_apply_one_after_another() {
+        let adjustments: [Adjustment; 3] = [
+            "scale=10%".parse().unwrap(),
+            "add=-100".parse().unwrap(),
+            "shift=2h".parse().unwrap(),
+        ];
+        let adjusted: TimeSeriesDataset = scenario(&flat(), &adjustments).unwrap();
+        let stamps: Vec<i64> = adjusted.unique_timestamps().unwrap();
+        assert_eq!(stamps.first(), Some(&(2 * HOUR_MS)));
+        assert_eq!(stamps.last(), Some(&(49 * HOUR_MS)));
+        let values = adjusted.values("Forecast").unwrap();
+        assert!(values.into_iter().all(|value| value == Some(1000.0)));
+    }
+
+    #[test]
+    fn invalid_adjustments_are_rejected() {
+        for text in [
+            "add=500@24",
+            "scale=-100%",
+            "shift=1h@3",
+            "grow=5%",
+            "add=lots",
+        ] {
+            assert!(text.parse::<Adjustment>().is_err(), "{}", text);
+        }
+        let never = Adjustment::Scale {
+            percent: 5.0,
+            hour: Some(24),
+        };
+        assert!(matches!(
+            never.apply(&flat()),
+            Err(MugenError::InvalidArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::error::{MugenError, Result};
+
+/// Longest stretch searched for the next run; a schedule such as February 29th on a
+/// Monday can go years without one.
+const SEARCH_DAYS: u32 = 366 * 28;
+
+/// When recurring jobs run: a five-field cron expression read in a timezone, so
+/// "5 6 * * *" in "America/New_York" runs at 06:05 Eastern all year round.
+///
+/// The fields are minute (0-59), hour (0-23), day of the month (1-31), month (1-12)
+/// and day of the week (0-7, 0 and 7 both Sunday). Each is `*`, a number, a range
+/// such as `1-5`, any of those with a step such as `*/15`, or a comma-separated list
+/// of them. As in cron, when both day fields are restricted a day matching either
+/// one runs; a day field starting with `*`, such as `*/2`, counts as unrestricted
+/// there, so a day must match both fields. Times skipped by a daylight saving change
+/// do not run that day; times repeated by one run once.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use mugen_core::schedule::Schedule;
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let schedule = Schedule::new("5 6 * * 1-5", "America/New_York")?;
+/// // Friday 2023-12-08 12:00 UTC is 07:00 Eastern, so the next run is Monday's.
+/// let next = schedule.next_after(Utc.with_ymd_and_hms(2023, 12, 8, 12, 0, 0).unwrap());
+/// assert_eq!(next, Some(Utc.with_ymd_and_hms(2023, 12, 11, 11, 5, 0).unwrap()));
+/// // Stopped from Thursday to Tuesday noon, the runs of Friday, Monday and Tuesday
+/// // were missed.
+/// let missed = schedule.runs_between(
+///     Utc.with_ymd_and_hms(2023, 12, 7, 12, 0, 0).unwrap(),
+///     Utc.with_ymd_and_hms(2023, 12, 12, 12, 0, 0).unwrap(),
+/// );
+/// assert_eq!(
+///     missed,
+///     [8, 11, 12].map(|day| Utc.with_ymd_and_hms(2023, 12, day, 11, 5, 0).unwrap())
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schedule {
+    expression: String,
+    timezone: Tz,
+    minutes: BTreeSet<u32>,
+    hours: BTreeSet<u32>,
+    days: BTreeSet<u32>,
+    months: BTreeSet<u32>,
+    weekdays: BTreeSet<u32>,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Schedule {
+    /// Parses the cron `expression`, read in `timezone`.
+    ///
+    /// # Arguments
+    ///
+    /// * `expression` - Five fields separated by whitespace, e.g. "5 6 * * *".
+    /// * `timezone` - IANA name of the timezone the fields are read in, e.g.
+    ///   "America/New_York" or "UTC".
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing the schedule or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InvalidArgument` naming the field if the expression is
+    /// malformed or a value is out of range, or naming the timezone if it is
+    /// unknown.
+    pub fn new(expression: &str, timezone: &str) -> Result<Self> {
+        let timezone: Tz = timezone.parse().map_err(|_| {
+            MugenError::InvalidArgument(format!(
+                "unknown timezone \"{}\", expected an IANA name such as \"America/New_York\"",
+                timezone
+            ))
+        })?;
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(MugenError::InvalidArgument(format!(
+                "schedule \"{}\" must have five fields: minute hour day month weekday",
+                expression
+            )));
+        };
+        let mut weekdays = field(weekday, "weekday", 0, 7)?;
+        if weekdays.remove(&7) {
+            weekdays.insert(0);
+        }
+        Ok(Schedule {
+            expression: expression.to_string(),
+            timezone,
+            minutes: field(minute, "minute", 0, 59)?,
+            hours: field(hour, "hour", 0, 23)?,
+            days: field(day, "day", 1, 31)?,
+            months: field(month, "month", 1, 12)?,
+            weekdays,
+            any_day: day.starts_with('*'),
+            any_weekday: weekday.starts_with('*'),
+        })
+    }
+
+    /// Returns the timezone the schedule is read in.
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+    }
+
+    /// Returns whether jobs run on the local calendar day `day`.
+    pub fn runs_on(&self, day: NaiveDate) -> bool {
+        if !self.months.contains(&day.month()) {
+            return false;
+        }
+        let by_day = self.days.contains(&day.day());
+        let by_weekday = self
+            .weekdays
+            .contains(&day.weekday().num_days_from_sunday());
+        if self.any_day || self.any_weekday {
+            by_day && by_weekday
+        } else {
+            by_day || by_weekday
+        }
+    }
+
+    /// Returns the first run strictly after `after`, or `None` if there is none in
+    /// the coming decades, e.g. for February 30th.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut day = after.with_timezone(&self.timezone).date_naive();
+        for _ in 0..SEARCH_DAYS {
+            if self.runs_on(day) {
+                for hour in &self.hours {
+                    for minute in &self.minutes {
+                        let local = day.and_hms_opt(*hour, *minute, 0)?;
+                        let Some(at) = self.timezone.from_local_datetime(&local).earliest() else {
+                            continue;
+                        };
+                        let at = at.with_timezone(&Utc);
+                        if at > after {
+                            return Some(at);
+                        }
+                    }
+                }
+            }
+            day = day.succ_opt()?;
+        }
+        None
+    }
+
+    /// Returns every run after `after` and up to `until`, inclusive, oldest first,
+    /// e.g. to catch up on the runs missed while stopped.
+    pub fn runs_between(&self, after: DateTime<Utc>, until: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let mut runs = Vec::new();
+        let mut at = after;
+        while let Some(next) = self.next_after(at).filter(|next| *next <= until) {
+            runs.push(next);
+            at = next;
+        }
+        runs
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.expression, self.timezone)
+    }
+}
+
+/// Parses one field of a cron expression into the values it allows.
+fn field(text: &str, name: &str, min: u32, max: u32) -> Result<BTreeSet<u32>> {
+    let invalid = || {
+        MugenError::InvalidArgument(format!(
+            "invalid {} field \"{}\" in schedule, expected values from {} to {}",
+            name, text, min, max
+        ))
+    };
+    let mut values = BTreeSet::new();
+    for item in text.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| invalid())?),
+            None => (item, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (
+                    start.parse::<u32>().map_err(|_| invalid())?,
+                    end.parse::<u32>().map_err(|_| invalid())?,
+                ),
+                // A single value with a step runs from there to the end, as in cron.
+                None => {
+                    let start = range.parse::<u32>().map_err(|_| invalid())?;
+                    (start, if step > 1 { max } else { start })
+                }
+            },
+        };
+        if step == 0 || start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        values.extend((start..=end).step_by(step as usize));
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the days of December 2023 `schedule` runs on.
+    fn december(schedule: &Schedule) -> Vec<u32> {
+        (1..=31)
+            .filter(|day| schedule.runs_on(NaiveDate::from_ymd_opt(2023, 12, *day).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn stepped_star_day_narrows_the_weekday() {
+        // Odd days that are also Mondays; December 1st 2023 was a Friday.
+        let schedule = Schedule::new("0 6 */2 * 1", "UTC").unwrap();
+        assert_eq!(december(&schedule), [11, 25]);
+        let schedule = Schedule::new("0 6 */2 * *", "UTC").unwrap();
+        assert_eq!(
+            december(&schedule),
+            (1..=31).step_by(2).collect::<Vec<u32>>()
+        );
+    }
+
+    #[test]
+    fn restricted_day_fields_run_on_either() {
+        let schedule = Schedule::new("0 6 1,15 * 1", "UTC").unwrap();
+        assert_eq!(december(&schedule), [1, 4, 11, 15, 18, 25]);
+        let schedule = Schedule::new("0 6 * * 1", "UTC").unwrap();
+        assert_eq!(december(&schedule), [4, 11, 18, 25]);
+    }
+}
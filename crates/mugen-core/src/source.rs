@@ -0,0 +1,328 @@
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use polars::prelude::*;
+#[cfg(feature = "sqlite")]
+use rusqlite::types::ValueRef;
+#[cfg(feature = "sqlite")]
+use rusqlite::{Connection, OpenFlags};
+
+use crate::data::{column, read_csv_to_df, str_column};
+#[cfg(feature = "sqlite")]
+use crate::error::MugenError;
+use crate::error::Result;
+#[cfg(feature = "fetch")]
+use crate::fetch::{download_days, FetchOptions};
+use crate::fetch::Report;
+use crate::iso::{Iso, Nyiso};
+use crate::zone::Zone;
+
+/// Where raw NYISO data comes from.
+///
+/// A source returns frames in the layout NYISO publishes them, so everything in
+/// [`crate::data`] works on its output no matter where the data was stored. New
+/// storage only needs a new implementation.
+pub trait DataSource {
+    /// Returns the report this source serves.
+    fn report(&self) -> Report;
+
+    /// Loads the report for every day in `range`.
+    ///
+    /// With a `zone`, actual load keeps only that zone's rows and forecasts keep only
+    /// the "Time Stamp" and that zone's column; with `None` every zone is kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data for a day cannot be read, or `zone` is missing
+    /// from it.
+    fn load(&self, range: RangeInclusive<NaiveDate>, zone: Option<Zone>) -> Result<DataFrame>;
+
+    /// Returns the IANA timezone of the timestamps this source returns, New York time
+    /// unless the source says otherwise.
+    fn timezone(&self) -> &str {
+        Nyiso.timezone()
+    }
+
+    /// Returns this source declaring its timestamps in `timezone`, e.g. "UTC" for a
+    /// store that keeps them in UTC.
+    fn with_timezone(self, timezone: impl Into<String>) -> WithTimezone<Self>
+    where
+        Self: Sized,
+    {
+        WithTimezone {
+            source: self,
+            timezone: timezone.into(),
+        }
+    }
+}
+
+/// A source whose timestamps are in a timezone other than New York time, made by
+/// [`DataSource::with_timezone`].
+#[derive(Clone, Debug)]
+pub struct WithTimezone<S> {
+    /// Source the data comes from.
+    pub source: S,
+    /// IANA timezone of its timestamps.
+    pub timezone: String,
+}
+
+impl<S: DataSource> DataSource for WithTimezone<S> {
+    fn report(&self) -> Report {
+        self.source.report()
+    }
+
+    fn load(&self, range: RangeInclusive<NaiveDate>, zone: Option<Zone>) -> Result<DataFrame> {
+        self.source.load(range, zone)
+    }
+
+    fn timezone(&self) -> &str {
+        &self.timezone
+    }
+}
+
+/// Daily CSVs in a local directory, named like NYISO names them, e.g.
+/// `data/20231209palIntegrated.csv`.
+#[derive(Clone, Debug)]
+pub struct CsvSource {
+    /// Directory holding the files.
+    pub dir: String,
+    /// Report the files hold.
+    pub report: Report,
+}
+
+impl DataSource for CsvSource {
+    fn report(&self) -> Report {
+        self.report
+    }
+
+    fn load(&self, range: RangeInclusive<NaiveDate>, zone: Option<Zone>) -> Result<DataFrame> {
+        let mut frames: DataFrame = DataFrame::default();
+        for day in days(&range) {
+            let path = Path::new(&self.dir).join(self.report.file_name(day));
+            frames.vstack_mut(&read_csv_to_df(&path.to_string_lossy())?)?;
+        }
+        narrow(frames, self.report, zone)
+    }
+}
+
+/// Daily CSVs served over HTTP in NYISO's archive layout, e.g.
+/// `<base_url>/isolf/20231209isolf.csv`.
+///
+/// Files are downloaded concurrently into `cache_dir` and read from there, so a day
+/// is only downloaded once.
+#[cfg(feature = "fetch")]
+#[derive(Clone, Debug)]
+pub struct HttpSource {
+    /// Root of the archive, e.g. [`crate::fetch::NYISO_BASE_URL`].
+    pub base_url: String,
+    /// Report to download.
+    pub report: Report,
+    /// Directory downloads are kept in.
+    pub cache_dir: String,
+    /// How downloads are retried and paced.
+    pub options: FetchOptions,
+}
+
+#[cfg(feature = "fetch")]
+impl DataSource for HttpSource {
+    fn report(&self) -> Report {
+        self.report
+    }
+
+    fn load(&self, range: RangeInclusive<NaiveDate>, zone: Option<Zone>) -> Result<DataFrame> {
+        download_days(
+            &self.base_url,
+            self.report,
+            *range.start(),
+            *range.end(),
+            &self.cache_dir,
+            &self.options,
+        )?;
+        CsvSource {
+            dir: self.cache_dir.clone(),
+            report: self.report,
+        }
+        .load(range, zone)
+    }
+}
+
+/// Daily CSVs in an S3 bucket under `prefix`, in NYISO's archive layout, e.g.
+/// `s3://<bucket>/<prefix>/isolf/20231209isolf.csv`.
+///
+/// Objects are fetched anonymously over HTTPS, so the bucket or prefix must allow
+/// public reads. Set `endpoint` for S3-compatible stores such as MinIO.
+#[cfg(feature = "fetch")]
+#[derive(Clone, Debug)]
+pub struct S3Source {
+    /// Bucket name.
+    pub bucket: String,
+    /// AWS region of the bucket, e.g. "us-east-1".
+    pub region: String,
+    /// Key prefix the report folders live under; may be empty.
+    pub prefix: String,
+    /// Overrides the AWS endpoint, e.g. "http://localhost:9000"; the bucket is then
+    /// addressed by path.
+    pub endpoint: Option<String>,
+    /// Report to download.
+    pub report: Report,
+    /// Directory downloads are kept in.
+    pub cache_dir: String,
+    /// How downloads are retried and paced.
+    pub options: FetchOptions,
+}
+
+#[cfg(feature = "fetch")]
+impl S3Source {
+    /// Returns the HTTP root the report folders are under.
+    fn base_url(&self) -> String {
+        let root = match &self.endpoint {
+            Some(endpoint) => format!("{}/{}", endpoint.trim_end_matches('/'), self.bucket),
+            None => format!("https://{}.s3.{}.amazonaws.com", self.bucket, self.region),
+        };
+        match self.prefix.trim_matches('/') {
+            "" => root,
+            prefix => format!("{}/{}", root, prefix),
+        }
+    }
+}
+
+#[cfg(feature = "fetch")]
+impl DataSource for S3Source {
+    fn report(&self) -> Report {
+        self.report
+    }
+
+    fn load(&self, range: RangeInclusive<NaiveDate>, zone: Option<Zone>) -> Result<DataFrame> {
+        HttpSource {
+            base_url: self.base_url(),
+            report: self.report,
+            cache_dir: self.cache_dir.clone(),
+            options: self.options.clone(),
+        }
+        .load(range, zone)
+    }
+}
+
+/// A table in a SQLite database with the same columns as the report's CSVs, one row
+/// per CSV row.
+///
+/// Unlike the file sources, which pick forecast files by issue day, rows are picked
+/// by the date of their "Time Stamp", since a table has no notion of files.
+#[cfg(feature = "sqlite")]
+#[derive(Clone, Debug)]
+pub struct SqliteSource {
+    /// Path of the database file; opened read-only.
+    pub path: String,
+    /// Table holding the rows.
+    pub table: String,
+    /// Report the table holds.
+    pub report: Report,
+}
+
+#[cfg(feature = "sqlite")]
+impl DataSource for SqliteSource {
+    fn report(&self) -> Report {
+        self.report
+    }
+
+    fn load(&self, range: RangeInclusive<NaiveDate>, zone: Option<Zone>) -> Result<DataFrame> {
+        let frame = self.read_table()?;
+        let mask: BooleanChunked = str_column(&frame, frame_name(self.report), "Time Stamp")?
+            .into_iter()
+            .map(|stamp| {
+                stamp
+                    .and_then(|stamp| stamp.get(..10))
+                    .and_then(|date| NaiveDate::parse_from_str(date, "%m/%d/%Y").ok())
+                    .is_some_and(|date| range.contains(&date))
+            })
+            .collect();
+        narrow(frame.filter(&mask)?, self.report, zone)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteSource {
+    /// Reads the whole table, typing each column by the SQLite values it holds.
+    fn read_table(&self) -> Result<DataFrame> {
+        let database_error = |source: rusqlite::Error| MugenError::Database {
+            path: self.path.clone(),
+            source,
+        };
+        let connection = Connection::open_with_flags(&self.path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(database_error)?;
+        // Identifiers cannot be bound as parameters; quoting keeps odd names working.
+        let mut statement = connection
+            .prepare(&format!(
+                "SELECT * FROM \"{}\"",
+                self.table.replace('"', "\"\"")
+            ))
+            .map_err(database_error)?;
+        let names: Vec<String> = statement
+            .column_names()
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        let mut columns: Vec<Vec<AnyValue<'static>>> = vec![Vec::new(); names.len()];
+        let mut rows = statement.query([]).map_err(database_error)?;
+        while let Some(row) = rows.next().map_err(database_error)? {
+            for (i, column) in columns.iter_mut().enumerate() {
+                column.push(match row.get_ref(i).map_err(database_error)? {
+                    ValueRef::Null | ValueRef::Blob(_) => AnyValue::Null,
+                    ValueRef::Integer(value) => AnyValue::Int64(value),
+                    ValueRef::Real(value) => AnyValue::Float64(value),
+                    ValueRef::Text(text) => {
+                        AnyValue::StringOwned(String::from_utf8_lossy(text).as_ref().into())
+                    }
+                });
+            }
+        }
+
+        let series = names
+            .iter()
+            .zip(&columns)
+            .map(|(name, values)| Series::from_any_values(name, values, false))
+            .collect::<PolarsResult<Vec<Series>>>()?;
+        Ok(DataFrame::new(series)?)
+    }
+}
+
+/// Returns every day in `range`, in order.
+pub(crate) fn days(range: &RangeInclusive<NaiveDate>) -> impl Iterator<Item = NaiveDate> + '_ {
+    range
+        .start()
+        .iter_days()
+        .take_while(move |day| day <= range.end())
+}
+
+/// Keeps only `zone` of a frame in `report`'s layout, or everything if `zone` is
+/// `None`.
+pub(crate) fn narrow(frame: DataFrame, report: Report, zone: Option<Zone>) -> Result<DataFrame> {
+    let Some(zone) = zone else {
+        return Ok(frame);
+    };
+    match report {
+        Report::ActualLoad
+        | Report::RealTimeLoad
+        | Report::DayAheadPrice
+        | Report::RealTimePrice => {
+            let names = str_column(&frame, frame_name(report), "Name")?;
+            Ok(frame.filter(&names.equal(zone.truth_name()))?)
+        }
+        Report::LoadForecast => {
+            column(&frame, frame_name(report), zone.forecast_column())?;
+            Ok(frame.select(["Time Stamp", zone.forecast_column()])?)
+        }
+    }
+}
+
+/// Names the data in `report` the way errors elsewhere in the crate do.
+fn frame_name(report: Report) -> &'static str {
+    match report {
+        Report::ActualLoad => "ground truth",
+        Report::LoadForecast => "forecast",
+        Report::RealTimeLoad => "real-time load",
+        Report::DayAheadPrice | Report::RealTimePrice => "price",
+    }
+}
@@ -0,0 +1,234 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::data::filter_period;
+use crate::dataset::TimeSeriesDataset;
+use crate::error::{MugenError, Result};
+
+/// How the training period moves from one split to the next.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Window {
+    /// Training starts on the same day for every split and grows to the day before
+    /// each test period, so later splits learn from more history.
+    #[default]
+    Expanding,
+    /// Training covers the same number of days right before each test period, so
+    /// every split learns from as much history as the others.
+    Sliding,
+}
+
+impl fmt::Display for Window {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Window::Expanding => "expanding",
+            Window::Sliding => "sliding",
+        })
+    }
+}
+
+impl FromStr for Window {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "expanding" => Ok(Window::Expanding),
+            "sliding" => Ok(Window::Sliding),
+            _ => Err(format!(
+                "unknown window \"{}\", expected expanding or sliding",
+                name
+            )),
+        }
+    }
+}
+
+/// One train/test split over whole days, both periods inclusive; the test period
+/// always starts the day after training ends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Split {
+    /// First day of training.
+    pub train_from: NaiveDate,
+    /// Last day of training.
+    pub train_to: NaiveDate,
+    /// First day to forecast and score.
+    pub test_from: NaiveDate,
+    /// Last day to forecast and score.
+    pub test_to: NaiveDate,
+}
+
+impl Split {
+    /// Returns the number of days in the test period.
+    pub fn test_days(&self) -> i64 {
+        (self.test_to - self.test_from).num_days() + 1
+    }
+
+    /// Keeps the rows of `dataset` in the training period.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if filtering fails.
+    pub fn train(&self, dataset: &TimeSeriesDataset) -> Result<TimeSeriesDataset> {
+        filter_period(dataset.clone(), self.train_from, self.train_to)
+    }
+
+    /// Keeps the rows of `dataset` in the test period.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if filtering fails.
+    pub fn test(&self, dataset: &TimeSeriesDataset) -> Result<TimeSeriesDataset> {
+        filter_period(dataset.clone(), self.test_from, self.test_to)
+    }
+}
+
+/// Splits the days `from..=to` into consecutive test periods of `test_days`, each
+/// with the training period before it, so every forecaster is trained and scored on
+/// the same days.
+///
+/// The first split trains on the `train_days` before `from`. With
+/// [`Window::Expanding`] later splits keep that first training day and train up to
+/// their test period; with [`Window::Sliding`] they train on the `train_days` right
+/// before it. The last test period ends on `to`, so it may be shorter.
+///
+/// # Arguments
+///
+/// * `from` - The first day to test.
+/// * `to` - The last day to test, inclusive.
+/// * `window` - How training moves from one split to the next.
+/// * `train_days` - How many days the first split trains on.
+/// * `test_days` - How many days each split tests on.
+///
+/// # Returns
+///
+/// * A `Result` containing the splits in time order, or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::InvalidArgument` if `from` is after `to`, or `train_days` or
+/// `test_days` is 0.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use mugen_core::split::{splits, Window};
+///
+/// let day = |day| NaiveDate::from_ymd_opt(2023, 12, day).unwrap();
+/// let sliding = splits(day(8), day(10), Window::Sliding, 7, 2).unwrap();
+/// assert_eq!(sliding.len(), 2);
+/// assert_eq!((sliding[1].train_from, sliding[1].train_to), (day(3), day(9)));
+/// assert_eq!((sliding[1].test_from, sliding[1].test_to), (day(10), day(10)));
+///
+/// let expanding = splits(day(8), day(10), Window::Expanding, 7, 2).unwrap();
+/// assert_eq!(expanding[1].train_from, day(1));
+/// ```
+pub fn splits(
+    from: NaiveDate,
+    to: NaiveDate,
+    window: Window,
+    train_days: u32,
+    test_days: u32,
+) -> Result<Vec<Split>> {
+    if from > to {
+        return Err(MugenError::InvalidArgument(format!(
+            "splits start on {} after they end on {}",
+            from, to
+        )));
+    }
+    if train_days == 0 || test_days == 0 {
+        return Err(MugenError::InvalidArgument(format!(
+            "splits need at least one day to train and one to test, not {} and {}",
+            train_days, test_days
+        )));
+    }
+    let train: Duration = Duration::days(i64::from(train_days));
+    let origin: NaiveDate = from - train;
+    let mut result: Vec<Split> = Vec::new();
+    let mut test_from: NaiveDate = from;
+    while test_from <= to {
+        let test_to: NaiveDate = (test_from + Duration::days(i64::from(test_days) - 1)).min(to);
+        result.push(Split {
+            train_from: match window {
+                Window::Expanding => origin,
+                Window::Sliding => test_from - train,
+            },
+            train_to: test_from - Duration::days(1),
+            test_from,
+            test_to,
+        });
+        test_from = test_to + Duration::days(1);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 12, day).unwrap()
+    }
+
+    /// Checks that the test periods of `result` tile `from..=to` in order and each
+    /// trains up to the day before it.
+    fn assert_tiles(result: &[Split], from: NaiveDate, to: NaiveDate) {
+        assert_eq!(result.first().unwrap().test_from, from);
+        assert_eq!(result.last().unwrap().test_to, to);
+        for split in result {
+            assert_eq!(split.train_to + Duration::days(1), split.test_from);
+            assert!(split.test_from <= split.test_to);
+        }
+        for pair in result.windows(2) {
+            assert_eq!(pair[0].test_to + Duration::days(1), pair[1].test_from);
+        }
+    }
+
+    #[test]
+    fn expanding_splits_keep_the_first_training_day() {
+        let result: Vec<Split> = splits(day(10), day(16), Window::Expanding, 5, 3).unwrap();
+        assert_tiles(&result, day(10), day(16));
+        assert_eq!(result.len(), 3);
+        assert!(result.iter().all(|split| split.train_from == day(5)));
+        assert_eq!(result[2].train_to, day(15));
+        assert_eq!(result[2].test_days(), 1);
+    }
+
+    #[test]
+    fn sliding_splits_keep_the_training_length() {
+        let result: Vec<Split> = splits(day(10), day(16), Window::Sliding, 5, 3).unwrap();
+        assert_tiles(&result, day(10), day(16));
+        let starts: Vec<NaiveDate> = result.iter().map(|split| split.train_from).collect();
+        assert_eq!(starts, vec![day(5), day(8), day(11)]);
+        assert!(result
+            .iter()
+            .all(|split| (split.train_to - split.train_from).num_days() == 4));
+    }
+
+    #[test]
+    fn a_single_day_gives_one_split() {
+        let result: Vec<Split> = splits(day(10), day(10), Window::Sliding, 1, 7).unwrap();
+        assert_eq!(
+            result,
+            vec![Split {
+                train_from: day(9),
+                train_to: day(9),
+                test_from: day(10),
+                test_to: day(10),
+            }]
+        );
+    }
+
+    #[test]
+    fn empty_ranges_and_periods_are_rejected() {
+        for result in [
+            splits(day(11), day(10), Window::Expanding, 7, 1),
+            splits(day(10), day(11), Window::Expanding, 0, 1),
+            splits(day(10), day(11), Window::Sliding, 7, 0),
+        ] {
+            assert!(matches!(result, Err(MugenError::InvalidArgument(_))));
+        }
+    }
+}
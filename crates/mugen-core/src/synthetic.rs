@@ -0,0 +1,404 @@
+use std::f64::consts::PI;
+use std::ops::RangeInclusive;
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike, Weekday};
+use polars::prelude::*;
+
+use crate::error::Result;
+use crate::fetch::Report;
+use crate::source::{days, narrow, DataSource};
+use crate::zone::Zone;
+
+/// Price in $/MWh of a zone running at its typical load.
+const BASE_PRICE: f64 = 40.0;
+
+/// Settings for generating realistic actual load and load forecasts without NYISO
+/// files, e.g. for tests, examples and benchmarks.
+///
+/// Each zone follows its typical load with a daily cycle that bottoms out around
+/// 4:00 and peaks around 16:00, less load on weekends, and random noise. The forecast
+/// is the load without noise, off by a random error and a bias. Frames come in the
+/// layout NYISO publishes, so everything in [`crate::data`] works on them.
+///
+/// Noise is drawn per zone and timestamp from `seed` alone, so the same settings
+/// always give the same values for a timestamp, whatever range it is generated in.
+/// Timestamps run in local time without daylight saving gaps, all marked "EST".
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use mugen_core::data::{join_actual_forecast, process_pred, process_truth};
+/// use mugen_core::metrics::mape;
+/// use mugen_core::synthetic::Synthetic;
+/// use mugen_core::zone::Zone;
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let synthetic = Synthetic {
+///     forecast_error: 0.05,
+///     ..Default::default()
+/// };
+/// let day = NaiveDate::from_ymd_opt(2023, 12, 9).unwrap();
+/// let truth = process_truth(synthetic.truth(day..=day)?, Zone::NewYorkCity)?;
+/// let forecast = process_pred(synthetic.forecast(day..=day)?, Zone::NewYorkCity)?;
+/// let joined = join_actual_forecast(&truth, &forecast)?;
+/// assert_eq!(joined.height(), 24);
+/// assert!(mape(&joined)? < 20.0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Synthetic {
+    /// Seed of the random noise and forecast error.
+    pub seed: u64,
+    /// Multiplies every zone's typical load, e.g. 1.3 for a summer heat wave.
+    pub scale: f64,
+    /// Height of the daily cycle above and below the day's mean, relative to it.
+    pub daily_swing: f64,
+    /// Drop in load on Saturdays and Sundays, relative to weekdays.
+    pub weekend_dip: f64,
+    /// Standard deviation of the noise on actual load, relative to the load.
+    pub noise: f64,
+    /// Standard deviation of the forecast error, relative to the load.
+    pub forecast_error: f64,
+    /// Mean forecast error relative to the load; positive values over-forecast.
+    pub forecast_bias: f64,
+    /// Days each forecast file covers, starting on its issue day, as NYISO's cover
+    /// several days ahead.
+    pub horizon_days: u32,
+    /// Growth of the forecast error per day between issue and target day, e.g. 0.5
+    /// makes day-ahead errors 1.5 times as large as same-day ones.
+    pub lead_error_growth: f64,
+}
+
+impl Default for Synthetic {
+    fn default() -> Self {
+        Synthetic {
+            seed: 0,
+            scale: 1.0,
+            daily_swing: 0.12,
+            weekend_dip: 0.06,
+            noise: 0.01,
+            forecast_error: 0.03,
+            forecast_bias: 0.0,
+            horizon_days: 1,
+            lead_error_growth: 0.5,
+        }
+    }
+}
+
+impl Synthetic {
+    /// Generates hourly actual load of every zone for every day in `range`, in the
+    /// layout of the `palIntegrated` files.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The days to generate.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing a DataFrame with the columns "Time Stamp", "Time
+    ///   Zone", "Name", "PTID" and "Integrated Load", or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the DataFrame cannot be built.
+    pub fn truth(&self, range: RangeInclusive<NaiveDate>) -> Result<DataFrame> {
+        self.actual(times(&range, 60), "Integrated Load")
+    }
+
+    /// Generates real-time actual load of every zone every five minutes for every day
+    /// in `range`, in the layout of the `pal` files.
+    ///
+    /// The load follows the same curve as [`Synthetic::truth`], so averaging it per
+    /// hour gives values close to the hourly load.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The days to generate.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing a DataFrame with the columns "Time Stamp", "Time
+    ///   Zone", "Name", "PTID" and "Load", or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the DataFrame cannot be built.
+    pub fn realtime(&self, range: RangeInclusive<NaiveDate>) -> Result<DataFrame> {
+        self.actual(times(&range, 5), "Load")
+    }
+
+    /// Generates hourly day-ahead zonal prices (LBMP) of every zone for every day in
+    /// `range`, in the layout of the `damlbmp_zone` files.
+    ///
+    /// Prices rise steeply with load relative to the zone's typical load, so they
+    /// spike when `scale` is high, and follow the load without noise, as bid the day
+    /// before.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The days to generate.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing a DataFrame with the columns "Time Stamp", "Name",
+    ///   "PTID" and "LBMP ($/MWHr)", or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the DataFrame cannot be built.
+    pub fn day_ahead_price(&self, range: RangeInclusive<NaiveDate>) -> Result<DataFrame> {
+        self.price(times(&range, 60), false)
+    }
+
+    /// Generates real-time zonal prices (LBMP) of every zone every five minutes for
+    /// every day in `range`, in the layout of the `realtime_zone` files.
+    ///
+    /// Prices follow the actual load of [`Synthetic::realtime`], noise included.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The days to generate.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing a DataFrame with the columns "Time Stamp", "Name",
+    ///   "PTID" and "LBMP ($/MWHr)", or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the DataFrame cannot be built.
+    pub fn realtime_price(&self, range: RangeInclusive<NaiveDate>) -> Result<DataFrame> {
+        self.price(times(&range, 5), true)
+    }
+
+    /// Generates the price of every zone at every time in `times`, from the actual
+    /// load if `realtime` is set and the load without noise otherwise.
+    fn price(
+        &self,
+        times: impl Iterator<Item = NaiveDateTime>,
+        realtime: bool,
+    ) -> Result<DataFrame> {
+        let mut stamps: Vec<String> = Vec::new();
+        let mut names: Vec<&str> = Vec::new();
+        let mut ptids: Vec<i64> = Vec::new();
+        let mut prices: Vec<f64> = Vec::new();
+        for at in times {
+            for zone in Zone::ALL {
+                let mut load = self.expected(zone, at);
+                if realtime {
+                    let (noise, _) = self.normals(zone, at, 0);
+                    load *= 1.0 + self.noise * noise;
+                }
+                stamps.push(at.format("%m/%d/%Y %H:%M").to_string());
+                names.push(zone.truth_name());
+                ptids.push(ptid(zone));
+                let price = BASE_PRICE * (load / typical_load(zone)).powi(4);
+                prices.push((price * 100.0).round() / 100.0);
+            }
+        }
+        Ok(DataFrame::new(vec![
+            Series::new("Time Stamp", stamps),
+            Series::new("Name", names),
+            Series::new("PTID", ptids),
+            Series::new("LBMP ($/MWHr)", prices),
+        ])?)
+    }
+
+    /// Generates actual load of every zone at every time in `times`, with the values
+    /// in the column `load`.
+    fn actual(&self, times: impl Iterator<Item = NaiveDateTime>, load: &str) -> Result<DataFrame> {
+        let mut stamps: Vec<String> = Vec::new();
+        let mut names: Vec<&str> = Vec::new();
+        let mut ptids: Vec<i64> = Vec::new();
+        let mut loads: Vec<f64> = Vec::new();
+        for at in times {
+            for zone in Zone::ALL {
+                let (noise, _) = self.normals(zone, at, 0);
+                stamps.push(at.format("%m/%d/%Y %H:%M:%S").to_string());
+                names.push(zone.truth_name());
+                ptids.push(ptid(zone));
+                loads.push(self.expected(zone, at) * (1.0 + self.noise * noise));
+            }
+        }
+        let time_zones: Vec<&str> = vec!["EST"; stamps.len()];
+        Ok(DataFrame::new(vec![
+            Series::new("Time Stamp", stamps),
+            Series::new("Time Zone", time_zones),
+            Series::new("Name", names),
+            Series::new("PTID", ptids),
+            Series::new(load, loads),
+        ])?)
+    }
+
+    /// Generates the hourly forecasts of every zone issued on every day in `range`, in
+    /// the layout of the `isolf` files.
+    ///
+    /// The file issued on each day covers `horizon_days` days from that day, so with
+    /// a horizon over one day, later days are forecast again by later issues, with
+    /// independent and smaller errors, in issue order.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The issue days to generate.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing a DataFrame with a "Time Stamp" column, one column per
+    ///   zone and an "NYISO" total, or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the DataFrame cannot be built.
+    pub fn forecast(&self, range: RangeInclusive<NaiveDate>) -> Result<DataFrame> {
+        let times: Vec<(u32, NaiveDateTime)> = days(&range)
+            .flat_map(|issued| {
+                (0..self.horizon_days.max(1)).flat_map(move |lead| {
+                    let day = issued + chrono::Duration::days(lead as i64);
+                    (0..24).filter_map(move |hour| Some((lead, day.and_hms_opt(hour, 0, 0)?)))
+                })
+            })
+            .collect();
+        let stamps: Vec<String> = times
+            .iter()
+            .map(|(_, at)| at.format("%m/%d/%Y %H:%M").to_string())
+            .collect();
+        let mut columns: Vec<Series> = vec![Series::new("Time Stamp", stamps)];
+        let mut totals: Vec<i64> = vec![0; times.len()];
+        for zone in Zone::ALL {
+            let values: Vec<i64> = times
+                .iter()
+                .map(|&(lead, at)| {
+                    let (_, error) = self.normals(zone, at, lead);
+                    let spread = self.forecast_error * (1.0 + self.lead_error_growth * lead as f64);
+                    let factor = 1.0 + self.forecast_bias + spread * error;
+                    (self.expected(zone, at) * factor).round() as i64
+                })
+                .collect();
+            totals
+                .iter_mut()
+                .zip(&values)
+                .for_each(|(total, value)| *total += value);
+            columns.push(Series::new(zone.forecast_column(), values));
+        }
+        columns.push(Series::new("NYISO", totals));
+        Ok(DataFrame::new(columns)?)
+    }
+
+    /// Returns the load of `zone` at `at` before noise.
+    fn expected(&self, zone: Zone, at: NaiveDateTime) -> f64 {
+        let hour = at.hour() as f64 + at.minute() as f64 / 60.0;
+        let cycle = 1.0 - self.daily_swing * (2.0 * PI * (hour - 4.0) / 24.0).cos();
+        let week = match at.weekday() {
+            Weekday::Sat | Weekday::Sun => 1.0 - self.weekend_dip,
+            _ => 1.0,
+        };
+        typical_load(zone) * self.scale * cycle * week
+    }
+
+    /// Draws the noise on actual load and the forecast error of `zone` at `at`, both
+    /// standard normal, for a forecast issued `lead` days before.
+    fn normals(&self, zone: Zone, at: NaiveDateTime, lead: u32) -> (f64, f64) {
+        let mut state = self.seed
+            ^ (zone as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (at.and_utc().timestamp() as u64).wrapping_mul(0xBF58_476D_1CE4_E5B9)
+            ^ (lead as u64).wrapping_mul(0x94D0_49BB_1331_11EB);
+        // Box-Muller: two uniforms in (0, 1) give two independent normals.
+        let u1 = unit(splitmix64(&mut state));
+        let u2 = unit(splitmix64(&mut state));
+        let radius = (-2.0 * u1.ln()).sqrt();
+        (
+            radius * (2.0 * PI * u2).cos(),
+            radius * (2.0 * PI * u2).sin(),
+        )
+    }
+}
+
+/// Serves synthetic data as a [`DataSource`], e.g. to run a pipeline without NYISO
+/// files.
+#[derive(Clone, Debug)]
+pub struct SyntheticSource {
+    /// How the data is generated; use the same settings for both reports so the
+    /// forecast matches the actual load.
+    pub synthetic: Synthetic,
+    /// Report to generate.
+    pub report: Report,
+}
+
+impl DataSource for SyntheticSource {
+    fn report(&self) -> Report {
+        self.report
+    }
+
+    fn load(&self, range: RangeInclusive<NaiveDate>, zone: Option<Zone>) -> Result<DataFrame> {
+        let frame = match self.report {
+            Report::ActualLoad => self.synthetic.truth(range)?,
+            Report::LoadForecast => self.synthetic.forecast(range)?,
+            Report::RealTimeLoad => self.synthetic.realtime(range)?,
+            Report::DayAheadPrice => self.synthetic.day_ahead_price(range)?,
+            Report::RealTimePrice => self.synthetic.realtime_price(range)?,
+        };
+        narrow(frame, self.report, zone)
+    }
+}
+
+/// Returns every `minutes` of every day in `range` from midnight, in order.
+fn times(
+    range: &RangeInclusive<NaiveDate>,
+    minutes: u32,
+) -> impl Iterator<Item = NaiveDateTime> + '_ {
+    days(range).flat_map(move |day| {
+        (0..24 * 60)
+            .step_by(minutes as usize)
+            .filter_map(move |minute| day.and_hms_opt(minute / 60, minute % 60, 0))
+    })
+}
+
+/// Returns the mean hourly load of `zone` in MW, taken from December 2023.
+fn typical_load(zone: Zone) -> f64 {
+    match zone {
+        Zone::Capital => 1184.0,
+        Zone::Central => 1613.0,
+        Zone::Dunwoodie => 534.0,
+        Zone::Genesee => 988.0,
+        Zone::HudsonValley => 1018.0,
+        Zone::LongIsland => 1972.0,
+        Zone::MohawkValley => 879.0,
+        Zone::Millwood => 308.0,
+        Zone::NewYorkCity => 4925.0,
+        Zone::North => 673.0,
+        Zone::West => 1477.0,
+    }
+}
+
+/// Returns the NYISO point identifier of `zone`, as in the "PTID" column.
+fn ptid(zone: Zone) -> i64 {
+    match zone {
+        Zone::West => 61752,
+        Zone::Genesee => 61753,
+        Zone::Central => 61754,
+        Zone::North => 61755,
+        Zone::MohawkValley => 61756,
+        Zone::Capital => 61757,
+        Zone::HudsonValley => 61758,
+        Zone::Millwood => 61759,
+        Zone::Dunwoodie => 61760,
+        Zone::NewYorkCity => 61761,
+        Zone::LongIsland => 61762,
+    }
+}
+
+/// Advances the SplitMix64 generator in `state` and returns its next value.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Maps `bits` to a uniform value in the open interval (0, 1).
+fn unit(bits: u64) -> f64 {
+    ((bits >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+}
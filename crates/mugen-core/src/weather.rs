@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+#[cfg(feature = "fetch")]
+use std::path::Path;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, Timelike};
+#[cfg(feature = "fetch")]
+use futures::stream::{self, StreamExt, TryStreamExt};
+#[cfg(feature = "fetch")]
+use reqwest::Client;
+
+use crate::error::{MugenError, Result};
+#[cfg(feature = "fetch")]
+use crate::fetch::{download, is_downloaded, save_download, FetchOptions, Pacer};
+#[cfg(feature = "fetch")]
+use crate::zone::Zone;
+
+/// Iowa Environmental Mesonet's archive of NOAA ASOS airport observations, which
+/// serves any station and period as CSV without an API key.
+pub const WEATHER_BASE_URL: &str = "https://mesonet.agron.iastate.edu/cgi-bin/request/asos.py";
+
+/// Header of the daily temperature files.
+#[cfg(feature = "fetch")]
+const HEADER: &str = "\"Time Stamp\",\"Name\",\"Station\",\"Temperature\"";
+
+/// Returns the file name of the hourly temperatures of every zone on `day`, e.g.
+/// "20231209weather.csv", in the same folder as the NYISO files.
+pub fn file_name(day: NaiveDate) -> String {
+    format!("{}weather.csv", day.format("%Y%m%d"))
+}
+
+/// Returns the URL of the temperatures `station` observed from `from` through `to`,
+/// in New York time, under `base_url`.
+///
+/// The query starts a day early, since the report just before midnight stands for
+/// the first hour of `from`.
+pub fn station_url(base_url: &str, station: &str, from: NaiveDate, to: NaiveDate) -> String {
+    let start = from - Duration::days(1);
+    let end = to + Duration::days(1);
+    format!(
+        "{}?station={}&data=tmpf&year1={}&month1={}&day1={}&year2={}&month2={}&day2={}\
+         &tz=America%2FNew_York&format=onlycomma&latlon=no&missing=M&report_type=3",
+        base_url,
+        station,
+        start.format("%Y"),
+        start.format("%-m"),
+        start.format("%-d"),
+        end.format("%Y"),
+        end.format("%-m"),
+        end.format("%-d"),
+    )
+}
+
+/// Parses observations in the archive's CSV layout, "station,valid,tmpf", into hourly
+/// temperatures in °F.
+///
+/// Observations are rounded to the nearest hour, since routine reports come a few
+/// minutes before it, and averaged when several land on one hour. Missing readings
+/// are skipped.
+///
+/// # Arguments
+///
+/// * `text` - The response body.
+///
+/// # Returns
+///
+/// * A `Result` containing the temperature of every hour with a reading, in order,
+///   or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::NoData` if `text` does not start with the expected header,
+/// e.g. because the archive answered with an error page.
+pub fn parse_observations(text: &str) -> Result<BTreeMap<NaiveDateTime, f64>> {
+    let mut lines = text.lines().filter(|line| !line.starts_with('#'));
+    if lines.next().map(str::trim) != Some("station,valid,tmpf") {
+        return Err(MugenError::NoData(format!(
+            "expected weather observations with the header \"station,valid,tmpf\", found \"{}\"",
+            text.lines().next().unwrap_or_default()
+        )));
+    }
+    let mut sums: BTreeMap<NaiveDateTime, (f64, u32)> = BTreeMap::new();
+    for line in lines {
+        let mut fields = line.split(',').skip(1);
+        let (Some(valid), Some(tmpf)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let (Ok(valid), Ok(tmpf)) = (
+            NaiveDateTime::parse_from_str(valid.trim(), "%Y-%m-%d %H:%M"),
+            tmpf.trim().parse::<f64>(),
+        ) else {
+            continue;
+        };
+        let hour = (valid + Duration::minutes(30))
+            .with_minute(0)
+            .unwrap_or(valid);
+        let (sum, count) = sums.entry(hour).or_insert((0.0, 0));
+        *sum += tmpf;
+        *count += 1;
+    }
+    Ok(sums
+        .into_iter()
+        .map(|(hour, (sum, count))| (hour, sum / count as f64))
+        .collect())
+}
+
+/// Downloads the hourly temperatures of every zone's station, see
+/// [`Zone::weather_station`], for `from..=to` and saves one file per day into `dir`,
+/// named by [`file_name`].
+///
+/// Each file holds "Time Stamp", "Name", "Station" and "Temperature" in °F for every
+/// zone and hour with a reading, in the layout of the actual-load files, so
+/// [`crate::data::process_weather`] reads it like them. If every day's file is
+/// already in `dir` and matches its recorded digest, nothing is downloaded;
+/// otherwise each station is downloaded once for the whole period and every day is
+/// rewritten. Downloads are paced and temporary failures retried as `options` says.
+///
+/// # Arguments
+///
+/// * `client` - The HTTP client to download with.
+/// * `base_url` - The observation archive, normally [`WEATHER_BASE_URL`].
+/// * `from` - The first day to download.
+/// * `to` - The last day to download, inclusive.
+/// * `dir` - The directory to save into; created on demand.
+/// * `options` - How downloads are retried and paced.
+///
+/// # Returns
+///
+/// * A `Result` containing the local paths of every day's file, in date order, or a
+///   `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::Fetch` if a station still cannot be downloaded after
+/// retrying, `MugenError::NoData` if the archive answers with something other than
+/// observations, and `MugenError::Write` if a file cannot be saved.
+#[cfg(feature = "fetch")]
+pub async fn fetch_weather(
+    client: &Client,
+    base_url: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+    dir: &str,
+    options: &FetchOptions,
+) -> Result<Vec<String>> {
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|source| MugenError::Write {
+            path: dir.to_string(),
+            source,
+        })?;
+    let days: Vec<NaiveDate> = from.iter_days().take_while(|day| *day <= to).collect();
+    let paths: Vec<String> = days
+        .iter()
+        .map(|day| {
+            Path::new(dir)
+                .join(file_name(*day))
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    if paths.iter().all(|path| is_downloaded(path)) {
+        return Ok(paths);
+    }
+
+    let mut stations: Vec<&str> = Zone::ALL
+        .iter()
+        .map(|zone| zone.weather_station())
+        .collect();
+    stations.sort_unstable();
+    stations.dedup();
+    let pacer = Pacer::new(options.rate_limit.interval());
+    let observations: BTreeMap<&str, BTreeMap<NaiveDateTime, f64>> = stream::iter(stations)
+        .map(|station| {
+            let url = station_url(base_url, station, from, to);
+            let pacer = &pacer;
+            async move {
+                let body = download(client, url, &options.retry, pacer).await?;
+                Ok::<_, MugenError>((
+                    station,
+                    parse_observations(&String::from_utf8_lossy(&body))?,
+                ))
+            }
+        })
+        .buffered(options.rate_limit.concurrency.max(1))
+        .try_collect()
+        .await?;
+
+    for (day, path) in days.iter().zip(&paths) {
+        let mut text = format!("{}\n", HEADER);
+        for hour in 0..24 {
+            let Some(at) = day.and_hms_opt(hour, 0, 0) else {
+                continue;
+            };
+            for zone in Zone::ALL {
+                let station = zone.weather_station();
+                if let Some(temperature) = observations[station].get(&at) {
+                    text.push_str(&format!(
+                        "\"{}\",\"{}\",\"{}\",{:.1}\n",
+                        at.format("%m/%d/%Y %H:%M:%S"),
+                        zone.truth_name(),
+                        station,
+                        temperature
+                    ));
+                }
+            }
+        }
+        save_download(path, text.as_bytes()).await?;
+    }
+    Ok(paths)
+}
+
+/// Blocking wrapper around [`fetch_weather`] for callers without an async runtime,
+/// such as the command line.
+///
+/// # Errors
+///
+/// Returns the errors of [`fetch_weather`], or `MugenError::Io` if the runtime cannot
+/// be started.
+#[cfg(feature = "fetch")]
+pub fn download_weather(
+    base_url: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+    dir: &str,
+    options: &FetchOptions,
+) -> Result<Vec<String>> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let client = Client::new();
+    runtime.block_on(fetch_weather(&client, base_url, from, to, dir, options))
+}
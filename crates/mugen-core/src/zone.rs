@@ -0,0 +1,191 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::MugenError;
+
+/// One of the eleven NYISO load zones.
+///
+/// The actual-load (`palIntegrated`) files name zones in upper case ("HUD VL") and
+/// the forecast (`isolf`) files in title case ("Hud Vl"); [`Zone::truth_name`] and
+/// [`Zone::forecast_column`] give each spelling, and parsing accepts either as well
+/// as the full name. Variants are ordered like the ground truth names sort, so sorted
+/// zones match the order of the source files. Config files store the ground truth
+/// spelling.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Zone {
+    /// Zone F.
+    Capital,
+    /// Zone C.
+    Central,
+    /// Zone I.
+    Dunwoodie,
+    /// Zone B.
+    Genesee,
+    /// Zone G.
+    HudsonValley,
+    /// Zone K.
+    LongIsland,
+    /// Zone E.
+    MohawkValley,
+    /// Zone H.
+    Millwood,
+    /// Zone J.
+    #[default]
+    NewYorkCity,
+    /// Zone D.
+    North,
+    /// Zone A.
+    West,
+}
+
+impl Zone {
+    /// Every zone, in sorted order.
+    pub const ALL: [Zone; 11] = [
+        Zone::Capital,
+        Zone::Central,
+        Zone::Dunwoodie,
+        Zone::Genesee,
+        Zone::HudsonValley,
+        Zone::LongIsland,
+        Zone::MohawkValley,
+        Zone::Millwood,
+        Zone::NewYorkCity,
+        Zone::North,
+        Zone::West,
+    ];
+
+    /// Returns the zone as spelled in the "Name" column of the ground truth.
+    pub fn truth_name(self) -> &'static str {
+        match self {
+            Zone::Capital => "CAPITL",
+            Zone::Central => "CENTRL",
+            Zone::Dunwoodie => "DUNWOD",
+            Zone::Genesee => "GENESE",
+            Zone::HudsonValley => "HUD VL",
+            Zone::LongIsland => "LONGIL",
+            Zone::MohawkValley => "MHK VL",
+            Zone::Millwood => "MILLWD",
+            Zone::NewYorkCity => "N.Y.C.",
+            Zone::North => "NORTH",
+            Zone::West => "WEST",
+        }
+    }
+
+    /// Returns the name of the forecast column holding the zone.
+    pub fn forecast_column(self) -> &'static str {
+        match self {
+            Zone::Capital => "Capitl",
+            Zone::Central => "Centrl",
+            Zone::Dunwoodie => "Dunwod",
+            Zone::Genesee => "Genese",
+            Zone::HudsonValley => "Hud Vl",
+            Zone::LongIsland => "Longil",
+            Zone::MohawkValley => "Mhk Vl",
+            Zone::Millwood => "Millwd",
+            Zone::NewYorkCity => "N.Y.C.",
+            Zone::North => "North",
+            Zone::West => "West",
+        }
+    }
+
+    /// Returns the full name of the zone, e.g. "Hudson Valley".
+    pub fn full_name(self) -> &'static str {
+        match self {
+            Zone::Capital => "Capital",
+            Zone::Central => "Central",
+            Zone::Dunwoodie => "Dunwoodie",
+            Zone::Genesee => "Genesee",
+            Zone::HudsonValley => "Hudson Valley",
+            Zone::LongIsland => "Long Island",
+            Zone::MohawkValley => "Mohawk Valley",
+            Zone::Millwood => "Millwood",
+            Zone::NewYorkCity => "New York City",
+            Zone::North => "North",
+            Zone::West => "West",
+        }
+    }
+
+    /// Returns the ground truth spelling made safe for file names and paths, in
+    /// lowercase with runs of punctuation and spaces turned into underscores, e.g.
+    /// "n_y_c" or "hud_vl".
+    pub fn slug(self) -> String {
+        self.truth_name()
+            .to_lowercase()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<&str>>()
+            .join("_")
+    }
+
+    /// Returns the airport weather station whose hourly temperature stands for the
+    /// zone, by its ASOS identifier, e.g. "LGA" for New York City.
+    ///
+    /// Millwood and Dunwoodie share the White Plains station.
+    pub fn weather_station(self) -> &'static str {
+        match self {
+            Zone::Capital => "ALB",
+            Zone::Central => "SYR",
+            Zone::Dunwoodie => "HPN",
+            Zone::Genesee => "ROC",
+            Zone::HudsonValley => "POU",
+            Zone::LongIsland => "ISP",
+            Zone::MohawkValley => "UCA",
+            Zone::Millwood => "HPN",
+            Zone::NewYorkCity => "LGA",
+            Zone::North => "PBG",
+            Zone::West => "BUF",
+        }
+    }
+}
+
+impl fmt::Display for Zone {
+    /// Writes the ground truth spelling, which is also how charts label the zone.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.truth_name())
+    }
+}
+
+impl FromStr for Zone {
+    type Err = MugenError;
+
+    /// Parses any of the ground truth, forecast or full spellings, ignoring case,
+    /// spaces and punctuation, so "N.Y.C.", "nyc", "Hud Vl" and "hudson-valley" all
+    /// work.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key: String = s
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        Zone::ALL
+            .into_iter()
+            .find(|zone| {
+                [zone.truth_name(), zone.forecast_column(), zone.full_name()]
+                    .iter()
+                    .any(|name| {
+                        name.chars()
+                            .filter(char::is_ascii_alphanumeric)
+                            .map(|c| c.to_ascii_lowercase())
+                            .eq(key.chars())
+                    })
+            })
+            .ok_or_else(|| MugenError::UnknownZone(s.to_string()))
+    }
+}
+
+impl Serialize for Zone {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.truth_name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Zone {
+    /// Accepts any spelling [`Zone::from_str`] does.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,60 @@
+/* C declarations of the mugen-ffi crate; link against libmugen. */
+
+#ifndef MUGEN_H
+#define MUGEN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Outcome of a call. */
+typedef enum MugenStatus {
+    /* The call succeeded. */
+    MUGEN_OK = 0,
+    /* A pointer argument was null. */
+    MUGEN_NULL_POINTER = 1,
+    /* No hour had both an actual value and a forecast. */
+    MUGEN_NO_DATA = 2,
+    /* The values could not be scored; see mugen_last_error. */
+    MUGEN_FAILED = 3,
+} MugenStatus;
+
+/* Forecast-quality metrics, as written by mugen_metrics. */
+typedef struct MugenMetrics {
+    /* Number of hours with both an actual value and a forecast. */
+    size_t hours;
+    /* Mean absolute percentage error, in percent. */
+    double mape;
+    /* Mean forecast minus mean actual, as a percentage of the mean actual. */
+    double bias;
+    /* Forecast peak minus actual peak, as a percentage of the actual peak. */
+    double peak_error;
+} MugenMetrics;
+
+/*
+ * Scores a forecast against actual load. Each array holds len values; the values at
+ * a position belong to the timestamp at that position, in milliseconds since
+ * 1970-01-01 in the timezone of the data. NaN marks a missing value. The arrays
+ * may be NULL when len is 0. On success the metrics are written to *metrics, which
+ * is otherwise left untouched.
+ */
+MugenStatus mugen_metrics(const int64_t *timestamps,
+                          const double *actual,
+                          const double *forecast,
+                          size_t len,
+                          MugenMetrics *metrics);
+
+/*
+ * Returns the UTF-8 message of the last failed call on this thread, or NULL. The
+ * text stays valid until the next failing call on the same thread.
+ */
+const char *mugen_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MUGEN_H */
@@ -0,0 +1,298 @@
+//! A C ABI for scoring load forecasts, so services that cannot link Rust, such as a
+//! C# reporting service, compute the same metrics as `mugen_park` without running
+//! the binary.
+//!
+//! Builds `libmugen.so`, `mugen.dll` or `libmugen.dylib`, and a static library;
+//! the declarations are in `include/mugen.h`. Every function returns a
+//! [`MugenStatus`]; on failure, [`mugen_last_error`] describes what went wrong.
+//!
+//! From C#:
+//!
+//! ```csharp
+//! [StructLayout(LayoutKind.Sequential)]
+//! struct MugenMetrics { public UIntPtr Hours; public double Mape, Bias, PeakError; }
+//!
+//! [DllImport("mugen")]
+//! static extern int mugen_metrics(long[] timestamps, double[] actual, double[] forecast,
+//!                                 UIntPtr len, out MugenMetrics metrics);
+//! [DllImport("mugen")]
+//! static extern IntPtr mugen_last_error();
+//!
+//! if (mugen_metrics(times, actual, forecast, (UIntPtr)times.Length, out var metrics) != 0)
+//!     throw new Exception(Marshal.PtrToStringUTF8(mugen_last_error()));
+//! ```
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use mugen_core::data::join_actual_forecast;
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::{MugenError, Result};
+use mugen_core::metrics::{bias, mape, peak_error};
+use polars::prelude::*;
+
+/// Outcome of a call.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MugenStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A pointer argument was null.
+    NullPointer = 1,
+    /// No hour had both an actual value and a forecast.
+    NoData = 2,
+    /// The values could not be scored; see [`mugen_last_error`].
+    Failed = 3,
+}
+
+/// Forecast-quality metrics, as written by [`mugen_metrics`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MugenMetrics {
+    /// Number of hours with both an actual value and a forecast.
+    pub hours: usize,
+    /// Mean absolute percentage error, in percent.
+    pub mape: f64,
+    /// Mean forecast minus mean actual, as a percentage of the mean actual.
+    pub bias: f64,
+    /// Forecast peak minus actual peak, as a percentage of the actual peak.
+    pub peak_error: f64,
+}
+
+thread_local! {
+    /// Message of the last failed call on this thread.
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Scores a forecast against actual load, as `mugen_park` scores a zone: the
+/// values at each position belong to the timestamp at that position, and only
+/// timestamps with a finite actual and forecast are scored.
+///
+/// # Arguments
+///
+/// * `timestamps` - `len` timestamps, in milliseconds since 1970-01-01 in the
+///   timezone of the data.
+/// * `actual` - `len` actual loads; NaN marks a missing value.
+/// * `forecast` - `len` forecast loads; NaN marks a missing value.
+/// * `len` - Number of values in each array; when 0, the arrays may be null.
+/// * `metrics` - Written with the metrics on success and left untouched otherwise.
+///
+/// # Returns
+///
+/// * [`MugenStatus::Ok`], or why the metrics could not be computed.
+///
+/// # Safety
+///
+/// `timestamps`, `actual` and `forecast` must each point to `len` readable values
+/// unless `len` is 0, and `metrics` to a writable [`MugenMetrics`], for the length
+/// of the call.
+#[no_mangle]
+pub unsafe extern "C" fn mugen_metrics(
+    timestamps: *const i64,
+    actual: *const f64,
+    forecast: *const f64,
+    len: usize,
+    metrics: *mut MugenMetrics,
+) -> MugenStatus {
+    let arrays_null: bool = timestamps.is_null() || actual.is_null() || forecast.is_null();
+    if metrics.is_null() || (len > 0 && arrays_null) {
+        return fail(MugenStatus::NullPointer, "a pointer argument is null");
+    }
+    // Empty arrays may come as null pointers, which slices cannot be built from.
+    let (timestamps, actual, forecast): (&[i64], &[f64], &[f64]) = if len == 0 {
+        (&[], &[], &[])
+    } else {
+        // SAFETY: the caller guarantees each array holds `len` values.
+        unsafe {
+            (
+                std::slice::from_raw_parts(timestamps, len),
+                std::slice::from_raw_parts(actual, len),
+                std::slice::from_raw_parts(forecast, len),
+            )
+        }
+    };
+    match catch_unwind(AssertUnwindSafe(|| score(timestamps, actual, forecast))) {
+        Ok(Ok(scored)) => {
+            // SAFETY: the caller guarantees `metrics` is writable.
+            unsafe { metrics.write(scored) };
+            MugenStatus::Ok
+        }
+        Ok(Err(err @ MugenError::NoData(_))) => fail(MugenStatus::NoData, &err.to_string()),
+        Ok(Err(err)) => fail(MugenStatus::Failed, &err.to_string()),
+        Err(_) => fail(MugenStatus::Failed, "scoring panicked"),
+    }
+}
+
+/// Returns the message of the last call on this thread that did not return
+/// [`MugenStatus::Ok`], as UTF-8, or null if there was none.
+///
+/// The text belongs to the library and stays valid until the next failing call on
+/// the same thread; copy it to keep it.
+#[no_mangle]
+pub extern "C" fn mugen_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Joins the values on their timestamps and computes the metrics.
+fn score(timestamps: &[i64], actual: &[f64], forecast: &[f64]) -> Result<MugenMetrics> {
+    let truth: TimeSeriesDataset = series("actual", timestamps, actual)?;
+    let pred: TimeSeriesDataset = series("forecast", timestamps, forecast)?;
+    let joined: TimeSeriesDataset = join_actual_forecast(&truth, &pred)?;
+    if joined.height() == 0 {
+        return Err(MugenError::NoData(
+            "no timestamp has both a finite actual value and forecast".to_string(),
+        ));
+    }
+    Ok(MugenMetrics {
+        hours: joined.height(),
+        mape: mape(&joined)?,
+        bias: bias(&joined)?,
+        peak_error: peak_error(&joined)?,
+    })
+}
+
+/// Builds a dataset of the finite `values` at `timestamps`, in milliseconds.
+fn series(name: &str, timestamps: &[i64], values: &[f64]) -> Result<TimeSeriesDataset> {
+    let (times, values): (Vec<i64>, Vec<f64>) = timestamps
+        .iter()
+        .zip(values)
+        .filter(|(_, value)| value.is_finite())
+        .map(|(at, value)| (*at, *value))
+        .unzip();
+    let times: Series =
+        Series::new("Time Stamp", times).cast(&DataType::Datetime(TimeUnit::Milliseconds, None))?;
+    let frame: DataFrame = DataFrame::new(vec![times, Series::new(name, values)])?;
+    TimeSeriesDataset::new(name, frame, "Time Stamp", vec![name.to_string()])
+}
+
+/// Records `message` as the last error and returns `status`.
+fn fail(status: MugenStatus, message: &str) -> MugenStatus {
+    let message: CString = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use super::*;
+
+    /// Calls [`mugen_metrics`] on the first `len` values of each array, returning
+    /// the status and what was written to the metrics.
+    fn metrics(
+        timestamps: &[i64],
+        actual: &[f64],
+        forecast: &[f64],
+        len: usize,
+    ) -> (MugenStatus, MugenMetrics) {
+        let mut metrics: MugenMetrics = MugenMetrics::default();
+        // SAFETY: the arrays outlive the call and hold at least `len` values.
+        let status: MugenStatus = unsafe {
+            mugen_metrics(
+                timestamps.as_ptr(),
+                actual.as_ptr(),
+                forecast.as_ptr(),
+                len,
+                &mut metrics,
+            )
+        };
+        (status, metrics)
+    }
+
+    /// Returns the message of the last failed call on this thread.
+    fn last_error() -> String {
+        let message: *const c_char = mugen_last_error();
+        assert!(!message.is_null());
+        // SAFETY: the message stays valid until the next failing call.
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn scores_the_hours_with_both_values() {
+        let timestamps: Vec<i64> = (0..4).map(|hour| hour * 3_600_000).collect();
+        let actual: [f64; 4] = [100.0, 200.0, f64::NAN, 400.0];
+        let forecast: [f64; 4] = [110.0, 180.0, 300.0, 400.0];
+        let (status, scored) = metrics(&timestamps, &actual, &forecast, 4);
+        assert_eq!(status, MugenStatus::Ok);
+        assert_eq!(scored.hours, 3);
+        assert!((scored.mape - 20.0 / 3.0).abs() < 1e-9);
+        assert!((scored.bias - (-10.0 / 700.0 * 100.0)).abs() < 1e-9);
+        assert!(scored.peak_error.abs() < 1e-9);
+    }
+
+    #[test]
+    fn arrays_longer_than_len_are_read_to_len() {
+        let timestamps: Vec<i64> = (0..3).map(|hour| hour * 3_600_000).collect();
+        let actual: [f64; 3] = [100.0, 200.0, 300.0];
+        let forecast: [f64; 2] = [110.0, 220.0];
+        let (status, scored) = metrics(&timestamps, &actual, &forecast, 2);
+        assert_eq!(status, MugenStatus::Ok);
+        assert_eq!(scored.hours, 2);
+        assert!((scored.mape - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn null_arrays_are_accepted_when_empty() {
+        let mut scored: MugenMetrics = MugenMetrics::default();
+        // SAFETY: no array is read when `len` is 0.
+        let status: MugenStatus = unsafe {
+            mugen_metrics(
+                std::ptr::null(),
+                std::ptr::null(),
+                std::ptr::null(),
+                0,
+                &mut scored,
+            )
+        };
+        assert_eq!(status, MugenStatus::NoData);
+        assert!(last_error().contains("no timestamp"));
+        assert_eq!(metrics(&[], &[], &[], 0).0, MugenStatus::NoData);
+        assert_eq!(scored, MugenMetrics::default());
+    }
+
+    #[test]
+    fn null_pointers_fail_and_leave_an_error() {
+        let forecast: [f64; 1] = [100.0];
+        let mut scored: MugenMetrics = MugenMetrics::default();
+        // SAFETY: the null array is refused before anything is read.
+        let status: MugenStatus = unsafe {
+            mugen_metrics(
+                std::ptr::null(),
+                forecast.as_ptr(),
+                forecast.as_ptr(),
+                1,
+                &mut scored,
+            )
+        };
+        assert_eq!(status, MugenStatus::NullPointer);
+        assert_eq!(last_error(), "a pointer argument is null");
+
+        // SAFETY: the null metrics are refused before anything is written.
+        let status: MugenStatus = unsafe {
+            mugen_metrics(
+                [0].as_ptr(),
+                forecast.as_ptr(),
+                forecast.as_ptr(),
+                1,
+                std::ptr::null_mut(),
+            )
+        };
+        assert_eq!(status, MugenStatus::NullPointer);
+    }
+
+    #[test]
+    fn hours_without_both_values_are_no_data() {
+        let (status, scored) = metrics(&[0, 3_600_000], &[100.0, f64::NAN], &[f64::NAN, 100.0], 2);
+        assert_eq!(status, MugenStatus::NoData);
+        assert_eq!(scored, MugenMetrics::default());
+        assert!(last_error().contains("no timestamp"));
+    }
+}
@@ -0,0 +1,391 @@
+//! Python bindings of the NYISO load pipeline, so notebooks run the same loading,
+//! processing, metrics and chart specs as the `mugen_park` command-line tool.
+//!
+//! Built into a wheel with [maturin](https://www.maturin.rs), e.g.
+//! `maturin develop -m crates/mugen-py/Cargo.toml`, and imported as `mugen_park`.
+//! Tables come back as polars DataFrames; call `.to_pandas()` on them for pandas.
+//! Files are read and scored without holding the GIL, so other Python threads keep
+//! running meanwhile.
+//!
+//! ```python
+//! import glob
+//! import mugen_park
+//!
+//! actual = sorted(glob.glob("data/*palIntegrated.csv"))
+//! joined = mugen_park.joined(actual, "data/20231209isolf.csv", "nyc")
+//! print(mugen_park.metrics(actual, "data/20231209isolf.csv", "nyc")["mape"])
+//! option = mugen_park.line_chart(actual, "data/20231209isolf.csv", "nyc")
+//! ```
+
+use std::sync::Mutex;
+
+use mugen_core::data::{
+    join_actual_forecast, load_joined_with, process_pred, process_truth, read_csv_to_df,
+    read_multiple_csvs_with, zone_metrics as score_zones,
+};
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::MugenError;
+use mugen_core::metrics::{bias, mape, peak_error};
+use mugen_core::resources::Resources;
+use mugen_core::zone::Zone;
+use mugen_viz::config::graph_config;
+use mugen_viz::graph::{Graph, GraphConfig, LineGraph};
+use polars::prelude::*;
+use polars_io::ipc::IpcWriter;
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+create_exception!(
+    mugen_park,
+    MugenParkError,
+    PyException,
+    "Raised when data cannot be read, processed or charted."
+);
+
+/// The thread and memory budget of reading files, set with `limit_resources`.
+static RESOURCES: Mutex<Resources> = Mutex::new(Resources {
+    threads: None,
+    memory_mb: None,
+});
+
+/// Result of the bindings, with errors of the pipeline raised as `MugenParkError`.
+type Result<T> = std::result::Result<T, Error>;
+
+/// An error on its way to Python.
+#[derive(Debug)]
+struct Error(PyErr);
+
+impl From<MugenError> for Error {
+    fn from(err: MugenError) -> Self {
+        Error(MugenParkError::new_err(err.to_string()))
+    }
+}
+
+impl From<PolarsError> for Error {
+    fn from(err: PolarsError) -> Self {
+        MugenError::from(err).into()
+    }
+}
+
+impl From<PyErr> for Error {
+    fn from(err: PyErr) -> Self {
+        Error(err)
+    }
+}
+
+impl From<Error> for PyErr {
+    fn from(err: Error) -> Self {
+        err.0
+    }
+}
+
+/// Reads a CSV file as it is, checking it against the hash recorded when it was
+/// downloaded.
+#[pyfunction]
+fn read_csv(py: Python<'_>, path: &str) -> Result<PyObject> {
+    let df: DataFrame = py.allow_threads(|| read_csv_to_df(path))?;
+    frame(py, &df)
+}
+
+/// Reads `zone`'s actual load from the `palIntegrated` CSVs at `paths`, one row per
+/// hour.
+#[pyfunction]
+fn actual(py: Python<'_>, paths: Vec<String>, zone: &str) -> Result<PyObject> {
+    let zone: Zone = zone.parse()?;
+    let truth: TimeSeriesDataset = py.allow_threads(|| read_actual(&paths, zone))?;
+    frame(py, truth.frame())
+}
+
+/// Reads `zone`'s forecast from the `isolf` CSV at `path`, one row per hour.
+#[pyfunction]
+fn forecast(py: Python<'_>, path: &str, zone: &str) -> Result<PyObject> {
+    let zone: Zone = zone.parse()?;
+    let forecast: TimeSeriesDataset = py.allow_threads(|| read_forecast(path, zone))?;
+    frame(py, forecast.frame())
+}
+
+/// Reads `zone`'s actual load from `actual_paths` joined against its forecast from
+/// `forecast_path`, one row per hour with both.
+#[pyfunction]
+fn joined(
+    py: Python<'_>,
+    actual_paths: Vec<String>,
+    forecast_path: &str,
+    zone: &str,
+) -> Result<PyObject> {
+    let zone: Zone = zone.parse()?;
+    let paths: Vec<&str> = actual_paths.iter().map(String::as_str).collect();
+    let joined: TimeSeriesDataset =
+        py.allow_threads(|| load_joined_with(&paths, forecast_path, zone, &resources()))?;
+    frame(py, joined.frame())
+}
+
+/// Scores `zone`'s forecast against its actual load, returning a dict of the hours
+/// scored and the MAPE, bias and peak error in percent.
+#[pyfunction]
+fn metrics(
+    py: Python<'_>,
+    actual_paths: Vec<String>,
+    forecast_path: &str,
+    zone: &str,
+) -> Result<PyObject> {
+    let zone: Zone = zone.parse()?;
+    let (hours, mape, bias, peak_error) = py.allow_threads(|| -> Result<_> {
+        let joined: TimeSeriesDataset = join_actual_forecast(
+            &read_actual(&actual_paths, zone)?,
+            &read_forecast(forecast_path, zone)?,
+        )?;
+        if joined.height() == 0 {
+            return Err(MugenError::NoData(format!(
+                "no hours with both actual load and a forecast for {}",
+                zone
+            ))
+            .into());
+        }
+        Ok((
+            joined.height(),
+            mape(&joined)?,
+            bias(&joined)?,
+            peak_error(&joined)?,
+        ))
+    })?;
+    let scores = PyDict::new(py);
+    scores.set_item("zone", zone.to_string())?;
+    scores.set_item("hours", hours)?;
+    scores.set_item("mape", mape)?;
+    scores.set_item("bias", bias)?;
+    scores.set_item("peak_error", peak_error)?;
+    Ok(scores.into_any().unbind())
+}
+
+/// Scores the forecast of every zone, one row per zone with its MAPE, peak error
+/// and bias.
+#[pyfunction]
+fn zone_metrics(
+    py: Python<'_>,
+    actual_paths: Vec<String>,
+    forecast_path: &str,
+) -> Result<PyObject> {
+    let paths: Vec<&str> = actual_paths.iter().map(String::as_str).collect();
+    let scores: DataFrame = py.allow_threads(|| {
+        score_zones(
+            read_multiple_csvs_with(paths, &resources())?,
+            read_csv_to_df(forecast_path)?,
+        )
+    })?;
+    frame(py, &scores)
+}
+
+/// Builds the ECharts option of `zone`'s actual vs forecast chart as JSON, as the
+/// `mugen_park serve` command draws it; `config` is the JSON of the chart settings,
+/// with fields left out keeping their defaults.
+#[pyfunction]
+#[pyo3(signature = (actual_paths, forecast_path, zone, config=None))]
+fn line_chart(
+    py: Python<'_>,
+    actual_paths: Vec<String>,
+    forecast_path: &str,
+    zone: &str,
+    config: Option<&str>,
+) -> Result<String> {
+    let zone: Zone = zone.parse()?;
+    let config: GraphConfig = graph_config(config.unwrap_or("{}"))?;
+    py.allow_threads(|| {
+        let line_graph: LineGraph = LineGraph::builder()
+            .config(GraphConfig {
+                deterministic: true,
+                ..config.clone()
+            })
+            .truth(read_actual(&actual_paths, zone)?)
+            .forecast(read_forecast(forecast_path, zone)?)
+            .zone(zone)
+            .title(config.labels.titles.served.as_str())
+            .error_band(true)
+            .max_points(2000)
+            .time_format("%m/%d %H:%M")
+            .build()?;
+        Ok(line_graph.to_echarts_json()?)
+    })
+}
+
+/// Limits the threads reading files in parallel and the memory, in MiB, a load may
+/// use before it reads in batches instead; `None` lifts a limit. polars sizes its
+/// own thread pool from `POLARS_MAX_THREADS` before the module is first used.
+#[pyfunction]
+#[pyo3(signature = (threads=None, memory_mb=None))]
+fn limit_resources(threads: Option<usize>, memory_mb: Option<u64>) {
+    *RESOURCES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Resources { threads, memory_mb };
+}
+
+/// Returns the NYISO names of every zone, e.g. "N.Y.C.".
+#[pyfunction]
+fn zones() -> Vec<String> {
+    Zone::ALL.iter().map(Zone::to_string).collect()
+}
+
+/// The `mugen_park` Python module.
+#[pymodule]
+fn mugen_park(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("MugenParkError", m.py().get_type::<MugenParkError>())?;
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add_function(wrap_pyfunction!(read_csv, m)?)?;
+    m.add_function(wrap_pyfunction!(actual, m)?)?;
+    m.add_function(wrap_pyfunction!(forecast, m)?)?;
+    m.add_function(wrap_pyfunction!(joined, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics, m)?)?;
+    m.add_function(wrap_pyfunction!(zone_metrics, m)?)?;
+    m.add_function(wrap_pyfunction!(line_chart, m)?)?;
+    m.add_function(wrap_pyfunction!(limit_resources, m)?)?;
+    m.add_function(wrap_pyfunction!(zones, m)?)?;
+    Ok(())
+}
+
+/// Reads and processes `zone`'s actual load from `paths`.
+fn read_actual(paths: &[String], zone: Zone) -> Result<TimeSeriesDataset> {
+    let raw: DataFrame =
+        read_multiple_csvs_with(paths.iter().map(String::as_str).collect(), &resources())?;
+    Ok(process_truth(raw, zone)?)
+}
+
+/// Reads and processes `zone`'s forecast from `path`.
+fn read_forecast(path: &str, zone: Zone) -> Result<TimeSeriesDataset> {
+    Ok(process_pred(read_csv_to_df(path)?, zone)?)
+}
+
+/// Returns the budget set with `limit_resources`.
+fn resources() -> Resources {
+    RESOURCES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// Hands `df` to Python as a polars DataFrame, passed over in the Arrow IPC format
+/// so the columns keep their types and no value becomes a Python object on the way.
+fn frame(py: Python<'_>, df: &DataFrame) -> Result<PyObject> {
+    let ipc: Vec<u8> = py.allow_threads(|| ipc(df))?;
+    let polars = py.import("polars")?;
+    Ok(polars
+        .getattr("read_ipc")?
+        .call1((PyBytes::new(py, &ipc),))?
+        .unbind())
+}
+
+/// Encodes `df` in the Arrow IPC format, as [`frame`] hands it to Python.
+fn ipc(df: &DataFrame) -> Result<Vec<u8>> {
+    let mut ipc: Vec<u8> = Vec::new();
+    IpcWriter::new(&mut ipc).finish(&mut df.clone())?;
+    Ok(ipc)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::path::PathBuf;
+
+    use polars_io::ipc::IpcReader;
+    use polars_io::SerReader;
+
+    use super::*;
+
+    /// Writes three hours of New York City's actual load and forecast as CSVs under
+    /// a directory of their own, returning their paths.
+    fn files(name: &str) -> (String, String) {
+        let dir: PathBuf =
+            std::env::temp_dir().join(format!("mugen-py-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let write = |file: &str, text: &str| -> String {
+            let path: PathBuf = dir.join(file);
+            std::fs::write(&path, text).unwrap();
+            path.to_string_lossy().into_owned()
+        };
+        (
+            write(
+                "20231209palIntegrated.csv",
+                "\"Time Stamp\",\"Time Zone\",\"Name\",\"PTID\",\"Integrated Load\"\n\
+                 \"12/09/2023 00:00:00\",\"EST\",\"N.Y.C.\",61761,4800.0\n\
+                 \"12/09/2023 01:00:00\",\"EST\",\"N.Y.C.\",61761,4600.0\n\
+                 \"12/09/2023 02:00:00\",\"EST\",\"N.Y.C.\",61761,4500.0\n",
+            ),
+            write(
+                "20231209isolf.csv",
+                "\"Time Stamp\",\"N.Y.C.\"\n\
+                 \"12/09/2023 00:00\",4848\n\
+                 \"12/09/2023 01:00\",4554\n\
+                 \"12/09/2023 02:00\",4545\n",
+            ),
+        )
+    }
+
+    /// Removes the directory of the files written by [`files`].
+    fn remove(path: &str) {
+        std::fs::remove_dir_all(PathBuf::from(path).parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn frames_keep_their_types_through_arrow_ipc() {
+        let (actual, forecast) = files("ipc");
+        let truth: TimeSeriesDataset =
+            read_actual(std::slice::from_ref(&actual), Zone::NewYorkCity).unwrap();
+        let pred: TimeSeriesDataset = read_forecast(&forecast, Zone::NewYorkCity).unwrap();
+        for df in [truth.frame(), pred.frame()] {
+            let back: DataFrame = IpcReader::new(Cursor::new(ipc(df).unwrap()))
+                .finish()
+                .unwrap();
+            assert_eq!(back.height(), 3);
+            assert_eq!(back.schema(), df.schema());
+            assert!(back.equals_missing(df));
+        }
+        remove(&actual);
+    }
+
+    #[test]
+    fn metrics_and_charts_are_built_from_the_files() {
+        let (actual, forecast) = files("metrics");
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let scores: PyObject = metrics(py, vec![actual.clone()], &forecast, "nyc").unwrap();
+            let scores: &Bound<'_, PyDict> = scores.bind(py).downcast().unwrap();
+            let item = |key: &str| scores.get_item(key).unwrap().unwrap();
+            assert_eq!(item("hours").extract::<usize>().unwrap(), 3);
+            assert_eq!(item("zone").extract::<String>().unwrap(), "N.Y.C.");
+            assert!((item("mape").extract::<f64>().unwrap() - 1.0).abs() < 1e-9);
+
+            let chart: String =
+                line_chart(py, vec![actual.clone()], &forecast, "nyc", None).unwrap();
+            assert!(chart.contains("N.Y.C."));
+        });
+        remove(&actual);
+    }
+
+    #[test]
+    fn pipeline_errors_are_raised_as_mugen_park_error() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let missing: PyErr = read_csv(py, "no such file.csv").unwrap_err().into();
+            assert!(missing.is_instance_of::<MugenParkError>(py));
+            assert!(missing.value(py).to_string().contains("no such file.csv"));
+
+            let polars: PyErr = Error::from(PolarsError::NoData("empty".into())).into();
+            assert!(polars.is_instance_of::<MugenParkError>(py));
+        });
+    }
+
+    #[test]
+    fn resources_are_limited_until_lifted() {
+        limit_resources(Some(1), Some(64));
+        assert_eq!(
+            resources(),
+            Resources {
+                threads: Some(1),
+                memory_mb: Some(64),
+            }
+        );
+        limit_resources(None, None);
+        assert_eq!(resources(), Resources::default());
+    }
+}
@@ -0,0 +1,930 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use chrono::format::{Item, StrftimeItems};
+use mugen_core::alert::Rule;
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::{MugenError, Result};
+use mugen_core::resources::Resources;
+use mugen_core::schedule::Schedule;
+use mugen_core::zone::Zone;
+use serde::de::{DeserializeOwned, Error as _};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::email::Email;
+use crate::graph::{GraphConfig, NumberFormat, Watermark};
+use crate::notify::Notify;
+use crate::sink::Sink;
+
+/// Smallest and largest chart width or height, in pixels, that renders sensibly.
+const CHART_SIZE: std::ops::RangeInclusive<u32> = 100..=16384;
+
+/// The CSS color keywords, which ECharts accepts alongside hex and `rgb()` colors.
+const CSS_COLORS: [&str; 149] = [
+    "aliceblue",
+    "antiquewhite",
+    "aqua",
+    "aquamarine",
+    "azure",
+    "beige",
+    "bisque",
+    "black",
+    "blanchedalmond",
+    "blue",
+    "blueviolet",
+    "brown",
+    "burlywood",
+    "cadetblue",
+    "chartreuse",
+    "chocolate",
+    "coral",
+    "cornflowerblue",
+    "cornsilk",
+    "crimson",
+    "cyan",
+    "darkblue",
+    "darkcyan",
+    "darkgoldenrod",
+    "darkgray",
+    "darkgreen",
+    "darkgrey",
+    "darkkhaki",
+    "darkmagenta",
+    "darkolivegreen",
+    "darkorange",
+    "darkorchid",
+    "darkred",
+    "darksalmon",
+    "darkseagreen",
+    "darkslateblue",
+    "darkslategray",
+    "darkslategrey",
+    "darkturquoise",
+    "darkviolet",
+    "deeppink",
+    "deepskyblue",
+    "dimgray",
+    "dimgrey",
+    "dodgerblue",
+    "firebrick",
+    "floralwhite",
+    "forestgreen",
+    "fuchsia",
+    "gainsboro",
+    "ghostwhite",
+    "gold",
+    "goldenrod",
+    "gray",
+    "green",
+    "greenyellow",
+    "grey",
+    "honeydew",
+    "hotpink",
+    "indianred",
+    "indigo",
+    "ivory",
+    "khaki",
+    "lavender",
+    "lavenderblush",
+    "lawngreen",
+    "lemonchiffon",
+    "lightblue",
+    "lightcoral",
+    "lightcyan",
+    "lightgoldenrodyellow",
+    "lightgray",
+    "lightgreen",
+    "lightgrey",
+    "lightpink",
+    "lightsalmon",
+    "lightseagreen",
+    "lightskyblue",
+    "lightslategray",
+    "lightslategrey",
+    "lightsteelblue",
+    "lightyellow",
+    "lime",
+    "limegreen",
+    "linen",
+    "magenta",
+    "maroon",
+    "mediumaquamarine",
+    "mediumblue",
+    "mediumorchid",
+    "mediumpurple",
+    "mediumseagreen",
+    "mediumslateblue",
+    "mediumspringgreen",
+    "mediumturquoise",
+    "mediumvioletred",
+    "midnightblue",
+    "mintcream",
+    "mistyrose",
+    "moccasin",
+    "navajowhite",
+    "navy",
+    "oldlace",
+    "olive",
+    "olivedrab",
+    "orange",
+    "orangered",
+    "orchid",
+    "palegoldenrod",
+    "palegreen",
+    "paleturquoise",
+    "palevioletred",
+    "papayawhip",
+    "peachpuff",
+    "peru",
+    "pink",
+    "plum",
+    "powderblue",
+    "purple",
+    "rebeccapurple",
+    "red",
+    "rosybrown",
+    "royalblue",
+    "saddlebrown",
+    "salmon",
+    "sandybrown",
+    "seagreen",
+    "seashell",
+    "sienna",
+    "silver",
+    "skyblue",
+    "slateblue",
+    "slategray",
+    "slategrey",
+    "snow",
+    "springgreen",
+    "steelblue",
+    "tan",
+    "teal",
+    "thistle",
+    "tomato",
+    "transparent",
+    "turquoise",
+    "violet",
+    "wheat",
+    "white",
+    "whitesmoke",
+    "yellow",
+    "yellowgreen",
+];
+
+/// Settings for a run of the pipeline: which files to read, which zone to chart, how
+/// charts look and how many threads and how much memory loading may use.
+///
+/// Every field has a default, so a config file only needs the settings it changes.
+///
+/// # Examples
+///
+/// ```toml
+/// truth_files = ["data/20231209palIntegrated.csv", "data/20231210palIntegrated.csv"]
+/// forecast_file = "data/20231209isolf.csv"
+/// forecast_files = ["data/20231208isolf.csv", "data/20231210isolf.csv"]
+/// price_files = ["data/20231209damlbmp_zone.csv", "data/20231210damlbmp_zone.csv"]
+/// zone = "LONGIL"
+/// history = "history.sqlite"
+///
+/// [graph]
+/// graph_width = 1920
+/// graph_height = 1080
+/// output_dir = "out"
+///
+/// [capacities]
+/// LONGIL = 6000
+///
+/// [resources]
+/// threads = 4
+/// memory_mb = 2048
+///
+/// [timezones]
+/// truth = "UTC"
+/// display = "America/New_York"
+/// ```
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PipelineConfig {
+    /// Actual-load CSVs, in any order.
+    pub truth_files: Vec<String>,
+    /// Forecast CSV the charts compare against.
+    pub forecast_file: String,
+    /// More forecast CSVs, e.g. one per issue day, that `live`, `serve` and
+    /// `dashboard` pick from along with `forecast_file`, taking each hour from the
+    /// latest-starting file that covers it; none by default.
+    pub forecast_files: Vec<String>,
+    /// Zonal price (LBMP) CSVs, day-ahead or real-time, overlaid on the line chart;
+    /// none by default.
+    pub price_files: Vec<String>,
+    /// Zone charted on its own.
+    pub zone: Zone,
+    /// Full-scale load of each zone's peak gauge in MW, keyed by the ground truth
+    /// spelling, e.g. `"N.Y.C." = 12000`; the gauge of a zone left out is scaled to
+    /// its data.
+    pub capacities: BTreeMap<Zone, f64>,
+    /// Settings shared by every chart; its title and axis names are those of the
+    /// main actual vs forecast chart, see [`crate::graph::Titles`] for the others.
+    /// Fields a file leaves out keep the defaults here, not those of
+    /// [`GraphConfig::default`].
+    #[serde(deserialize_with = "graph_over_defaults")]
+    pub graph: GraphConfig,
+    /// Threads and memory loading may use.
+    pub resources: Resources,
+    /// Where to post a summary of each run.
+    pub notify: Notify,
+    /// Who to email the report of each run to.
+    pub email: Email,
+    /// Storage the outputs of each run are copied to, in order; none by default.
+    pub sinks: Vec<Sink>,
+    /// When the `daemon` command runs and what it downloads.
+    pub daemon: Daemon,
+    /// SQLite database each run's metrics are appended to, with the run's time,
+    /// version, command line, zone and data period, see
+    /// `mugen_core::history::MetricsHistory`; none by default.
+    pub history: Option<String>,
+    /// Timezones the input files are in and the one charts and reports show.
+    pub timezones: Timezones,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        PipelineConfig {
+            truth_files: (1..=10)
+                .map(|day| format!("data/202312{:02}palIntegrated.csv", day))
+                .collect(),
+            forecast_file: "data/20231209isolf.csv".to_string(),
+            forecast_files: Vec::new(),
+            price_files: Vec::new(),
+            zone: Zone::NewYorkCity,
+            capacities: BTreeMap::new(),
+            graph: GraphConfig {
+                title: "Ground Truth VS Predictions for {region} {start_date}–{end_date}"
+                    .to_string(),
+                x_label: "Time".to_string(),
+                y_label: "Megawatts".to_string(),
+                number_format: NumberFormat::default().with_unit("MW"),
+                ..Default::default()
+            },
+            resources: Resources::default(),
+            notify: Notify::default(),
+            email: Email::default(),
+            sinks: Vec::new(),
+            daemon: Daemon::default(),
+            history: None,
+            timezones: Timezones::default(),
+        }
+    }
+}
+
+/// IANA timezones the actual load, forecast and price files are written in, New
+/// York time as NYISO publishes them by default, and the timezone charts and
+/// reports show, e.g. to compare a store kept in UTC against NYISO's files.
+///
+/// # Examples
+///
+/// ```toml
+/// [timezones]
+/// truth = "UTC"
+/// forecast = "America/New_York"
+/// display = "America/New_York"
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Timezones {
+    /// Timezone of the actual-load files.
+    pub truth: String,
+    /// Timezone of the forecast file.
+    pub forecast: String,
+    /// Timezone of the price files.
+    pub price: String,
+    /// Timezone charts and reports show; the actual-load files' by default.
+    pub display: Option<String>,
+}
+
+impl Default for Timezones {
+    fn default() -> Self {
+        Timezones {
+            truth: "America/New_York".to_string(),
+            forecast: "America/New_York".to_string(),
+            price: "America/New_York".to_string(),
+            display: None,
+        }
+    }
+}
+
+impl Timezones {
+    /// Returns the timezone charts and reports show.
+    pub fn shown(&self) -> &str {
+        self.display.as_deref().unwrap_or(&self.truth)
+    }
+
+    /// Returns whether every file is already in the timezone shown, so nothing needs
+    /// converting.
+    pub fn is_uniform(&self) -> bool {
+        [&self.truth, &self.forecast, &self.price]
+            .iter()
+            .all(|timezone| timezone.as_str() == self.shown())
+    }
+
+    /// Returns processed actual load, read from the actual-load files, in the
+    /// timezone shown.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InvalidArgument` if a timezone is unknown.
+    pub fn truth(&self, dataset: TimeSeriesDataset) -> Result<TimeSeriesDataset> {
+        dataset.with_timezone(&self.truth).in_timezone(self.shown())
+    }
+
+    /// Returns a processed forecast, read from the forecast file, in the timezone
+    /// shown.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InvalidArgument` if a timezone is unknown.
+    pub fn forecast(&self, dataset: TimeSeriesDataset) -> Result<TimeSeriesDataset> {
+        dataset.with_timezone(&self.forecast).in_timezone(self.shown())
+    }
+
+    /// Returns a processed price, read from the price files, in the timezone shown.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InvalidArgument` if a timezone is unknown.
+    pub fn price(&self, dataset: TimeSeriesDataset) -> Result<TimeSeriesDataset> {
+        dataset.with_timezone(&self.price).in_timezone(self.shown())
+    }
+}
+
+/// When the `daemon` command runs the standard chart set and what it downloads
+/// first.
+///
+/// Each run downloads the actual load of the run's day and the `days` before it,
+/// and the forecast issued that day, into `data_dir`, replacing the files of the
+/// last two days, which NYISO still adds to, once their new versions are complete.
+/// Every run missed by at most `catch_up_hours`, e.g. while the machine was down, is
+/// made up on start, oldest first, since each charts a different day.
+/// With `metrics_address` set, the outcome of the last run and the quality of its
+/// forecast are served on `/metrics` there for Prometheus to scrape.
+///
+/// # Examples
+///
+/// ```toml
+/// [daemon]
+/// schedule = "5 6 * * *"
+/// timezone = "America/New_York"
+/// days = 6
+/// catch_up_hours = 24
+/// metrics_address = "127.0.0.1:9100"
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Daemon {
+    /// Cron expression of the run times, see [`Schedule`].
+    pub schedule: String,
+    /// IANA timezone the schedule is read in.
+    pub timezone: String,
+    /// Days before the run's day to download and chart.
+    pub days: u32,
+    /// Directory the daily files are downloaded into.
+    pub data_dir: String,
+    /// How late a missed run may still be made up on start; 0 to skip missed runs.
+    pub catch_up_hours: u32,
+    /// File recording when the last run was due, for catching up.
+    pub state_file: String,
+    /// Address to serve Prometheus metrics on while running, e.g.
+    /// "127.0.0.1:9100"; none by default.
+    pub metrics_address: Option<String>,
+}
+
+impl Default for Daemon {
+    fn default() -> Self {
+        Daemon {
+            schedule: "5 6 * * *".to_string(),
+            timezone: "America/New_York".to_string(),
+            days: 6,
+            data_dir: "data".to_string(),
+            catch_up_hours: 24,
+            state_file: "mugen_park.daemon".to_string(),
+            metrics_address: None,
+        }
+    }
+}
+
+impl PipelineConfig {
+    /// Checks the settings before a run and describes every problem found, each with
+    /// a suggested fix, so they can all be fixed at once instead of failing mid-run
+    /// on the first.
+    ///
+    /// Checks that the input files exist, the chart size is sane, colors, the time
+    /// format and scales are valid, and the watermark image and output directory
+    /// can be used.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems: Vec<String> = Vec::new();
+        if self.truth_files.is_empty() {
+            problems.push(
+                "no truth files are set; list the actual-load CSVs in truth_files".to_string(),
+            );
+        }
+        for path in self
+            .truth_files
+            .iter()
+            .chain([&self.forecast_file])
+            .chain(&self.forecast_files)
+            .chain(&self.price_files)
+        {
+            if !Path::new(path).is_file() {
+                problems.push(format!(
+                    "input file \"{}\" does not exist; fix the path or download the day with `mugen_park fetch`",
+                    path
+                ));
+            }
+        }
+        problems.extend(self.setting_problems());
+        problems
+    }
+
+    /// Checks the settings like [`PipelineConfig::problems`], except that the input
+    /// files exist, for runs that download them first.
+    pub fn setting_problems(&self) -> Vec<String> {
+        let mut problems: Vec<String> = Vec::new();
+        let graph: &GraphConfig = &self.graph;
+        for (name, size) in [("width", graph.graph_width), ("height", graph.graph_height)] {
+            if !CHART_SIZE.contains(&size) {
+                problems.push(format!(
+                    "chart {} {} px is outside {}-{} px; pick a size in that range or a --resolution preset",
+                    name,
+                    size,
+                    CHART_SIZE.start(),
+                    CHART_SIZE.end()
+                ));
+            }
+        }
+        if StrftimeItems::new(&graph.time_format).any(|item| matches!(item, Item::Error)) {
+            problems.push(format!(
+                "time format \"{}\" is not a valid chrono format; use specifiers such as \"%m/%d/%Y %H:%M\"",
+                graph.time_format
+            ));
+        }
+        if graph.label_every == Some(0) {
+            problems.push("label_every is 0; use 1 or more, or leave it unset".to_string());
+        }
+        for (name, scale) in [
+            ("font_scale", graph.style.font_scale),
+            ("line_scale", graph.style.line_scale),
+        ] {
+            if !(scale.is_finite() && scale > 0.0) {
+                problems.push(format!(
+                    "style {} {} must be above 0; 1 keeps the default size",
+                    name, scale
+                ));
+            }
+        }
+
+        let colors = graph
+            .style
+            .palette
+            .iter()
+            .map(|color| ("palette".to_string(), color))
+            .chain(
+                graph
+                    .style
+                    .background
+                    .iter()
+                    .map(|color| ("background".to_string(), color)),
+            )
+            .chain(graph.thresholds.iter().map(|threshold| {
+                (
+                    format!("threshold \"{}\"", threshold.label),
+                    &threshold.color,
+                )
+            }));
+        for (owner, color) in colors {
+            if !is_color(color) {
+                let suggestion = match closest_color(color) {
+                    Some(name) => format!("did you mean \"{}\"?", name),
+                    None => "use a CSS color name or a hex code such as \"#1f77b4\"".to_string(),
+                };
+                problems.push(format!(
+                    "{} color \"{}\" is not a color; {}",
+                    owner, color, suggestion
+                ));
+            }
+        }
+
+        if let Some(Watermark::Image(path)) = &graph.watermark {
+            if !Path::new(path).is_file() {
+                problems.push(format!(
+                    "watermark image \"{}\" does not exist; fix the path or remove the watermark",
+                    path
+                ));
+            }
+        }
+        for (zone, capacity) in &self.capacities {
+            if !(capacity.is_finite() && *capacity > 0.0) {
+                problems.push(format!(
+                    "capacity {} of {} must be above 0; leave the zone out to scale its gauge to the data",
+                    capacity,
+                    zone.truth_name()
+                ));
+            }
+        }
+        if Path::new(&graph.output_dir).is_file() {
+            problems.push(format!(
+                "output directory \"{}\" is a file; pick another output_dir",
+                graph.output_dir
+            ));
+        }
+        if self.resources.threads == Some(0) {
+            problems.push(
+                "resources.threads is 0; use at least 1, or leave it out for one per core"
+                    .to_string(),
+            );
+        }
+        if self.resources.memory_mb == Some(0) {
+            problems.push(
+                "resources.memory_mb is 0; set a budget in MiB, or leave it out for no limit"
+                    .to_string(),
+            );
+        }
+        if let Some(webhook) = &self.notify.slack_webhook {
+            if !webhook.starts_with("https://") {
+                problems.push(
+                    "notify.slack_webhook is not an https:// URL; copy the webhook URL from Slack"
+                        .to_string(),
+                );
+            }
+        }
+        for (metric, limit) in &self.notify.alerts {
+            if !(limit.is_finite() && *limit >= 0.0) {
+                problems.push(format!(
+                    "notify alert limit {} for \"{}\" must be a number of 0 or more",
+                    limit, metric
+                ));
+            }
+        }
+        problems.extend(self.notify.rules.iter().filter_map(Rule::problem));
+        if self.email.server.is_some() {
+            if self.email.to.is_empty() {
+                problems.push(
+                    "email.server is set but email.to is empty; list at least one recipient"
+                        .to_string(),
+                );
+            }
+            if !self.email.from.contains('@') {
+                problems.push(format!(
+                    "email.from \"{}\" is not an email address; set the sender's address",
+                    self.email.from
+                ));
+            }
+            if self.email.username.is_some() && self.email.password_env.is_none() {
+                problems.push(
+                    "email.username is set without email.password_env; name the environment \
+                     variable holding the password"
+                        .to_string(),
+                );
+            }
+        }
+        for recipient in self.email.to.iter().filter(|to| !to.contains('@')) {
+            problems.push(format!(
+                "email recipient \"{}\" is not an email address; fix or remove it",
+                recipient
+            ));
+        }
+        for mailbox in std::iter::once(&self.email.from).chain(&self.email.to) {
+            if mailbox.contains(['\r', '\n']) {
+                problems.push(format!(
+                    "email address \"{}\" contains a line break; give a single address",
+                    mailbox.escape_debug()
+                ));
+            }
+        }
+        for (index, sink) in self.sinks.iter().enumerate() {
+            let (name, endpoint) = match sink {
+                Sink::Local(local) => (("dir", &local.dir), None),
+                Sink::S3(s3) => (("bucket", &s3.bucket), s3.endpoint.as_ref()),
+                Sink::Gcs(gcs) => (("bucket", &gcs.bucket), None),
+                Sink::Azure(azure) => (("container", &azure.container), azure.endpoint.as_ref()),
+            };
+            if name.1.is_empty() || (name.0 != "dir" && name.1.contains('/')) {
+                problems.push(format!(
+                    "sink {} has no valid {}; set it, and put folders in prefix",
+                    index + 1,
+                    name.0
+                ));
+            }
+            if let Sink::Azure(azure) = sink {
+                if azure.account.is_empty() && azure.endpoint.is_none() {
+                    problems.push(format!(
+                        "sink {} has no account; set the Azure storage account name",
+                        index + 1
+                    ));
+                }
+            }
+            if let Some(endpoint) = endpoint {
+                if !endpoint.starts_with("https://") && !endpoint.starts_with("http://") {
+                    problems.push(format!(
+                        "sink {} endpoint \"{}\" is not an http:// or https:// URL",
+                        index + 1,
+                        endpoint
+                    ));
+                }
+            }
+        }
+        if let Err(err) = Schedule::new(&self.daemon.schedule, &self.daemon.timezone) {
+            problems.push(format!("daemon schedule: {}", err));
+        }
+        let timezones: &Timezones = &self.timezones;
+        for (name, timezone) in [
+            ("truth", Some(&timezones.truth)),
+            ("forecast", Some(&timezones.forecast)),
+            ("price", Some(&timezones.price)),
+            ("display", timezones.display.as_ref()),
+        ] {
+            if let Some(timezone) = timezone.filter(|tz| tz.parse::<chrono_tz::Tz>().is_err()) {
+                problems.push(format!(
+                    "timezones.{} \"{}\" is not an IANA timezone; use a name such as \"America/New_York\" or \"UTC\"",
+                    name, timezone
+                ));
+            }
+        }
+        problems
+    }
+
+    /// Checks the settings before a run, see [`PipelineConfig::problems`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::Settings` listing every problem if there are any.
+    pub fn validate(&self) -> Result<()> {
+        let problems: Vec<String> = self.problems();
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(MugenError::Settings(problems))
+    }
+}
+
+/// Returns whether `color` is a CSS color ECharts understands: a keyword, a `#`
+/// hex code or an `rgb()`, `rgba()`, `hsl()` or `hsla()` function.
+fn is_color(color: &str) -> bool {
+    let color = color.trim().to_ascii_lowercase();
+    if let Some(hex) = color.strip_prefix('#') {
+        return matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+    if ["rgb(", "rgba(", "hsl(", "hsla("]
+        .iter()
+        .any(|function| color.starts_with(function))
+    {
+        return color.ends_with(')');
+    }
+    CSS_COLORS.contains(&color.as_str())
+}
+
+/// Returns the CSS color keyword within two edits of `color`, if there is one.
+fn closest_color(color: &str) -> Option<&'static str> {
+    let color = color.trim().to_ascii_lowercase();
+    CSS_COLORS
+        .iter()
+        .map(|name| (edit_distance(&color, name), *name))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, name)| name)
+}
+
+/// Counts the single-character insertions, deletions and substitutions that turn
+/// `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current: Vec<usize> = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Reads the `graph` settings of a [`PipelineConfig`] over those of
+/// [`PipelineConfig::default`], so a file that only moves the output directory keeps
+/// the default title, axis names and unit.
+fn graph_over_defaults<'de, D>(deserializer: D) -> std::result::Result<GraphConfig, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let mut graph: Value =
+        serde_json::to_value(PipelineConfig::default().graph).map_err(D::Error::custom)?;
+    merge(&mut graph, Value::deserialize(deserializer)?);
+    serde_json::from_value(graph).map_err(D::Error::custom)
+}
+
+/// Reads chart settings from JSON like the `graph` table of a config file, fields
+/// left out keeping the defaults of [`PipelineConfig::default`].
+///
+/// # Errors
+///
+/// Returns `MugenError::Config` if `text` does not hold valid chart settings.
+pub fn graph_config(text: &str) -> Result<GraphConfig> {
+    let config_error = |err: serde_json::Error| MugenError::Config {
+        path: "config".to_string(),
+        message: err.to_string(),
+    };
+    let value: Value = serde_json::from_str(text).map_err(config_error)?;
+    graph_over_defaults(value).map_err(config_error)
+}
+
+/// Overwrites `base` with `over`, field by field within objects.
+fn merge(base: &mut Value, over: Value) {
+    match (base, over) {
+        (Value::Object(base), Value::Object(over)) => {
+            for (key, value) in over {
+                merge(base.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (base, over) => *base = over,
+    }
+}
+
+/// Reads settings from a TOML or JSON file, chosen by the `.toml` or `.json`
+/// extension of `path`.
+///
+/// Works for [`PipelineConfig`], [`GraphConfig`] and the chart structs in
+/// [`crate::graph`], whose data fields are left empty.
+///
+/// # Arguments
+///
+/// * `path` - The config file to read.
+///
+/// # Returns
+///
+/// * A `Result` containing the settings or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::Config` if the file cannot be read, has another extension or
+/// does not hold valid settings.
+pub fn read_config<T: DeserializeOwned>(path: &str) -> Result<T> {
+    let config_error = |message: String| MugenError::Config {
+        path: path.to_string(),
+        message,
+    };
+    let text = fs::read_to_string(path).map_err(|err| config_error(err.to_string()))?;
+    match extension(path) {
+        Some("toml") => toml::from_str(&text).map_err(|err| config_error(err.to_string())),
+        Some("json") => serde_json::from_str(&text).map_err(|err| config_error(err.to_string())),
+        _ => Err(config_error("expected a .toml or .json file".to_string())),
+    }
+}
+
+/// Writes settings to a TOML or JSON file, chosen by the `.toml` or `.json`
+/// extension of `path`, so that [`read_config`] reads back the same value.
+///
+/// # Arguments
+///
+/// * `config` - The settings to write.
+/// * `path` - The config file to create or replace.
+///
+/// # Errors
+///
+/// Returns `MugenError::Config` if `path` has another extension or the settings
+/// cannot be encoded, and `MugenError::Write` if the file cannot be written.
+pub fn write_config<T: Serialize>(config: &T, path: &str) -> Result<()> {
+    let config_error = |message: String| MugenError::Config {
+        path: path.to_string(),
+        message,
+    };
+    let text = match extension(path) {
+        Some("toml") => {
+            toml::to_string_pretty(config).map_err(|err| config_error(err.to_string()))?
+        }
+        Some("json") => {
+            serde_json::to_string_pretty(config).map_err(|err| config_error(err.to_string()))?
+        }
+        _ => return Err(config_error("expected a .toml or .json file".to_string())),
+    };
+    fs::write(path, text).map_err(|source| MugenError::Write {
+        path: path.to_string(),
+        source,
+    })
+}
+
+/// Returns "toml" or "json" if the extension of `path` names one of those formats.
+fn extension(path: &str) -> Option<&'static str> {
+    match Path::new(path)
+        .extension()?
+        .to_str()?
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "toml" => Some("toml"),
+        "json" => Some("json"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graph_settings_are_read_over_the_pipeline_defaults() {
+        let settings: PipelineConfig = toml::from_str(
+            r#"
+            [graph]
+            output_dir = "out"
+
+            [graph.labels.titles]
+            gauge = "Spitze"
+            "#,
+        )
+        .unwrap();
+        let defaults: PipelineConfig = PipelineConfig::default();
+        assert_eq!(settings.graph.output_dir, "out");
+        assert_eq!(settings.graph.title, defaults.graph.title);
+        assert_eq!(settings.graph.y_label, "Megawatts");
+        assert_eq!(settings.graph.number_format.unit.as_deref(), Some("MW"));
+        assert_eq!(settings.graph.labels.titles.gauge, "Spitze");
+        assert_eq!(
+            settings.graph.labels.titles.overlay,
+            defaults.graph.labels.titles.overlay
+        );
+
+        let graph: GraphConfig = graph_config(r#"{"x_label": "Zeit"}"#).unwrap();
+        assert_eq!(graph.x_label, "Zeit");
+        assert_eq!(graph.y_label, "Megawatts");
+        assert!(graph_config("[").is_err());
+    }
+
+    #[test]
+    fn the_default_settings_are_valid() {
+        assert!(PipelineConfig::default().setting_problems().is_empty());
+    }
+
+    #[test]
+    fn every_bad_setting_is_reported() {
+        let settings: PipelineConfig = toml::from_str(
+            r#"
+            [graph]
+            graph_width = 10
+            time_format = "%m/%d %"
+            label_every = 0
+
+            [graph.style]
+            palette = ["bleu"]
+            font_scale = 0.0
+
+            [capacities]
+            "N.Y.C." = -1
+
+            [resources]
+            threads = 0
+            memory_mb = 0
+            "#,
+        )
+        .unwrap();
+        let problems: Vec<String> = settings.setting_problems();
+        for expected in [
+            "chart width 10 px",
+            "time format \"%m/%d %\"",
+            "label_every is 0",
+            "style font_scale 0",
+            "did you mean \"blue\"?",
+            "capacity -1 of N.Y.C.",
+            "resources.threads is 0",
+            "resources.memory_mb is 0",
+        ] {
+            assert!(
+                problems.iter().any(|problem| problem.contains(expected)),
+                "{} not in {:?}",
+                expected,
+                problems
+            );
+        }
+        assert_eq!(problems.len(), 8, "{:?}", problems);
+    }
+
+    #[test]
+    fn only_runs_reading_the_inputs_need_them_to_exist() {
+        let settings: PipelineConfig = PipelineConfig {
+            truth_files: vec!["no/such/truth.csv".to_string()],
+            forecast_file: "no/such/forecast.csv".to_string(),
+            ..Default::default()
+        };
+        let problems: Vec<String> = settings.problems();
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert!(problems[0].contains("\"no/such/truth.csv\" does not exist"));
+        assert!(settings.setting_problems().is_empty());
+
+        let unset: PipelineConfig = PipelineConfig {
+            truth_files: Vec::new(),
+            ..settings
+        };
+        assert!(unset.problems()[0].starts_with("no truth files are set"));
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeDelta};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Sparkline};
+use ratatui::{DefaultTerminal, Frame};
+
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::Result;
+use mugen_core::zone::Zone;
+
+/// Hours without a new actual value after which a zone's data is shown as stale.
+const STALE_AFTER_HOURS: i64 = 2;
+
+/// Width of the text to the left of each zone's sparklines, in characters.
+const TEXT_WIDTH: u16 = 58;
+
+/// The latest state of one zone, as shown on a row of the dashboard.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZoneStatus {
+    /// The zone.
+    pub zone: Zone,
+    /// Actual load at each hour shown, oldest first, `None` where it is missing.
+    pub actual: Vec<Option<f64>>,
+    /// Forecast load at the same hours.
+    pub forecast: Vec<Option<f64>>,
+    /// Latest hour with an actual value, or `None` if there is none.
+    pub latest: Option<NaiveDateTime>,
+    /// Forecast minus actual at the latest hour with both, as a percentage of the
+    /// actual.
+    pub error_percent: Option<f64>,
+    /// How long ago the latest actual value was, measured in the data's timezone.
+    pub age: Option<TimeDelta>,
+}
+
+impl ZoneStatus {
+    /// Describes `zone` from its actual load and forecast: the last `hours` hours up
+    /// to the latest actual value, the forecast error at the latest hour with both,
+    /// and how old the data is.
+    ///
+    /// # Arguments
+    ///
+    /// * `zone` - The zone the series belong to.
+    /// * `truth` - The zone's actual load.
+    /// * `forecast` - The zone's forecast load.
+    /// * `hours` - Number of hours the sparklines show.
+    /// * `now` - The current time, in the timezone of the series.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing the status or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::Polars` if the series cannot be read.
+    pub fn of(
+        zone: Zone,
+        truth: &TimeSeriesDataset,
+        forecast: &TimeSeriesDataset,
+        hours: usize,
+        now: NaiveDateTime,
+    ) -> Result<Self> {
+        let actual: HashMap<i64, f64> = truth.values_by_timestamp(truth.value_column())?;
+        let predicted: HashMap<i64, f64> = forecast.values_by_timestamp(forecast.value_column())?;
+        let latest: Option<i64> = actual.keys().max().copied();
+        let mut times: Vec<i64> = actual
+            .keys()
+            .chain(predicted.keys())
+            .copied()
+            .filter(|at| latest.is_some_and(|latest| *at <= latest))
+            .collect();
+        times.sort_unstable();
+        times.dedup();
+        let shown: &[i64] = &times[times.len().saturating_sub(hours)..];
+        let error_percent: Option<f64> = times.iter().rev().find_map(|at| {
+            let (actual, predicted) = (actual.get(at)?, predicted.get(at)?);
+            (*actual != 0.0).then(|| (predicted - actual) / actual * 100.0)
+        });
+        let latest: Option<NaiveDateTime> = latest
+            .and_then(DateTime::from_timestamp_millis)
+            .map(|at| at.naive_utc());
+        Ok(ZoneStatus {
+            zone,
+            actual: shown.iter().map(|at| actual.get(at).copied()).collect(),
+            forecast: shown.iter().map(|at| predicted.get(at).copied()).collect(),
+            latest,
+            error_percent,
+            age: latest.map(|latest| now - latest),
+        })
+    }
+
+    /// Returns whether the latest actual value is older than two hours, or missing.
+    pub fn is_stale(&self) -> bool {
+        self.age
+            .is_none_or(|age| age > TimeDelta::hours(STALE_AFTER_HOURS))
+    }
+}
+
+/// Shows a full-screen dashboard of `load`'s zones in the terminal until `q`, `Esc`
+/// or `Ctrl-C` is pressed: for each zone, sparklines of its recent actual and
+/// forecast load, its current forecast error and how fresh its data is.
+///
+/// `load` is called at start and every `interval`, or at once when `r` is pressed,
+/// so files dropped in meanwhile show up without a restart. A failing `load` is
+/// shown at the bottom and the last statuses stay on screen.
+///
+/// # Arguments
+///
+/// * `interval` - Time between refreshes.
+/// * `load` - Reads the current status of every zone to show.
+///
+/// # Errors
+///
+/// Returns `MugenError::Io` if the terminal cannot be set up, drawn or read.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use mugen_viz::dashboard::{self, ZoneStatus};
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// dashboard::run(Duration::from_secs(60), || -> mugen_core::error::Result<Vec<ZoneStatus>> {
+///     Ok(Vec::new())
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn run<L>(interval: Duration, mut load: L) -> Result<()>
+where
+    L: FnMut() -> Result<Vec<ZoneStatus>>,
+{
+    let mut terminal: DefaultTerminal = ratatui::try_init()?;
+    let outcome = show(&mut terminal, interval, &mut load);
+    ratatui::try_restore()?;
+    outcome
+}
+
+/// Redraws the dashboard, refreshing it when due, until a key asks to quit.
+fn show<L>(terminal: &mut DefaultTerminal, interval: Duration, load: &mut L) -> Result<()>
+where
+    L: FnMut() -> Result<Vec<ZoneStatus>>,
+{
+    let mut statuses: Vec<ZoneStatus> = Vec::new();
+    let mut refreshed: Option<DateTime<Local>> = None;
+    let mut problem: Option<String> = None;
+    let mut due: Instant = Instant::now();
+    loop {
+        if Instant::now() >= due {
+            match load() {
+                Ok(fresh) => {
+                    statuses = fresh;
+                    refreshed = Some(Local::now());
+                    problem = None;
+                }
+                Err(err) => problem = Some(err.to_string()),
+            }
+            due = Instant::now() + interval;
+        }
+        terminal.draw(|frame| draw(frame, &statuses, refreshed, problem.as_deref()))?;
+        if !event::poll(due.saturating_duration_since(Instant::now()))? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(())
+                }
+                KeyCode::Char('r') => due = Instant::now(),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Draws a title line, one row of two lines per zone and a status line.
+fn draw(
+    frame: &mut Frame,
+    statuses: &[ZoneStatus],
+    refreshed: Option<DateTime<Local>>,
+    problem: Option<&str>,
+) {
+    let [title, body, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(
+                "Load vs forecast",
+                Style::new().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("   "),
+            Span::styled("actual", Style::new().fg(Color::Cyan)),
+            Span::raw(" / "),
+            Span::styled("forecast", Style::new().fg(Color::Green)),
+        ])),
+        title,
+    );
+    let rows = Layout::vertical(statuses.iter().map(|_| Constraint::Length(2))).split(body);
+    for (status, area) in statuses.iter().zip(rows.iter()) {
+        draw_zone(frame, status, *area);
+    }
+    let footer_line: Line = match problem {
+        Some(problem) => Line::styled(
+            format!("Refresh failed: {}", problem),
+            Style::new().fg(Color::Red),
+        ),
+        None => Line::raw(match refreshed {
+            Some(at) => format!("Refreshed {}   r refresh   q quit", at.format("%H:%M:%S")),
+            None => "Loading...   q quit".to_string(),
+        }),
+    };
+    frame.render_widget(Paragraph::new(footer_line), footer);
+}
+
+/// Draws one zone: its name, latest values, error and age on the left, and its
+/// actual and forecast sparklines, on one scale, on the right.
+fn draw_zone(frame: &mut Frame, status: &ZoneStatus, area: Rect) {
+    let [text, lines] =
+        Layout::horizontal([Constraint::Length(TEXT_WIDTH), Constraint::Min(0)]).areas(area);
+    let latest = |values: &[Option<f64>]| match values.last().copied().flatten() {
+        Some(value) => format!("{:>8.0} MW", value),
+        None => format!("{:>11}", "-"),
+    };
+    let error: Span = match status.error_percent {
+        Some(error) => Span::styled(
+            format!("{:+7.1}%", error),
+            Style::new().fg(match error.abs() {
+                error if error >= 10.0 => Color::Red,
+                error if error >= 5.0 => Color::Yellow,
+                _ => Color::Reset,
+            }),
+        ),
+        None => Span::raw(format!("{:>8}", "-")),
+    };
+    let age: Span = Span::styled(
+        match status.age {
+            Some(age) => format!("{}h{:02}m old", age.num_hours(), age.num_minutes() % 60),
+            None => "no data".to_string(),
+        },
+        Style::new().fg(if status.is_stale() {
+            Color::Red
+        } else {
+            Color::Reset
+        }),
+    );
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<14}", status.zone.to_string()),
+                    Style::new().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!("actual   {}  error ", latest(&status.actual))),
+                error,
+            ]),
+            Line::from(vec![
+                Span::raw(format!(
+                    "{:<14}forecast {}  ",
+                    status
+                        .latest
+                        .map(|at| at.format("%m/%d %H:%M").to_string())
+                        .unwrap_or_default(),
+                    latest(&status.forecast)
+                )),
+                age,
+            ]),
+        ]),
+        text,
+    );
+    // Bars start at the lowest value shown, so the daily shape fills the height.
+    let values = status.actual.iter().chain(&status.forecast).flatten();
+    let floor: f64 = values
+        .clone()
+        .fold(f64::INFINITY, |low, value| low.min(*value));
+    let top: f64 = values.fold(f64::NEG_INFINITY, |high, value| high.max(*value));
+    let bars = |values: &[Option<f64>]| -> Vec<Option<u64>> {
+        values
+            .iter()
+            .map(|value| value.map(|value| (value - floor + 1.0).max(0.0) as u64))
+            .collect()
+    };
+    let max: u64 = (top - floor + 1.0).max(1.0) as u64;
+    let [actual, forecast] =
+        Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(lines);
+    for (values, area, color) in [
+        (&status.actual, actual, Color::Cyan),
+        (&status.forecast, forecast, Color::Green),
+    ] {
+        let shown: &[Option<f64>] = &values[values.len().saturating_sub(area.width as usize)..];
+        frame.render_widget(
+            Sparkline::default()
+                .data(bars(shown))
+                .max(max)
+                .style(Style::new().fg(color)),
+            area,
+        );
+    }
+}
@@ -0,0 +1,279 @@
+#[cfg(feature = "email")]
+use std::time::Duration;
+
+#[cfg(feature = "email")]
+use lettre::message::header::ContentType;
+#[cfg(feature = "email")]
+use lettre::message::{Attachment, Mailbox, Message, MultiPart, SinglePart};
+#[cfg(feature = "email")]
+use lettre::transport::smtp::authentication::Credentials;
+#[cfg(feature = "email")]
+use lettre::{SmtpTransport, Transport};
+#[cfg(feature = "email")]
+use mugen_core::error::{MugenError, Result};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "email")]
+use crate::pipeline::PipelineReport;
+#[cfg(feature = "email")]
+use crate::report::{self, content_type};
+
+/// How the connection to the mail server is encrypted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Security {
+    /// Connects in plain text and upgrades with the STARTTLS command, on port 587 by
+    /// default.
+    #[default]
+    StartTls,
+    /// Connects over TLS from the start, on port 465 by default.
+    Tls,
+    /// No encryption, on port 25 by default; only for a relay on the same host or
+    /// network, since the password would be sent in the clear.
+    None,
+}
+
+/// Where to email the HTML report of each run, see [`crate::report::html`], with
+/// the charts shown inline.
+///
+/// Nothing is sent without a `server` and at least one recipient. The password is
+/// read from the environment variable named by `password_env`, so it stays out of
+/// config files and run manifests. Mail goes out at the end of every run, so
+/// scheduling the run, e.g. with cron, schedules the email.
+///
+/// # Examples
+///
+/// ```toml
+/// [email]
+/// server = "smtp.example.com"
+/// security = "starttls"
+/// username = "forecasts@example.com"
+/// password_env = "MUGEN_SMTP_PASSWORD"
+/// from = "Load forecasts <forecasts@example.com>"
+/// to = ["operations@example.com", "trading@example.com"]
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Email {
+    /// Host name of the SMTP server.
+    pub server: Option<String>,
+    /// Port of the SMTP server; `None` for the usual port of `security`.
+    pub port: Option<u16>,
+    /// How the connection is encrypted.
+    pub security: Security,
+    /// User to log in as; `None` to send without logging in.
+    pub username: Option<String>,
+    /// Name of the environment variable holding the password of `username`.
+    pub password_env: Option<String>,
+    /// Sender, as an address or "Name <address>".
+    pub from: String,
+    /// Recipients, each as an address or "Name <address>".
+    pub to: Vec<String>,
+}
+
+impl Email {
+    /// Returns the port to connect to.
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(match self.security {
+            Security::StartTls => 587,
+            Security::Tls => 465,
+            Security::None => 25,
+        })
+    }
+
+    /// Builds the email for `report`: a MIME message with `title` as its subject, the
+    /// HTML report as its body and every chart file attached and referred to by the
+    /// body.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InvalidArgument` if the sender or a recipient is not an
+    /// address or "Name <address>", e.g. because it holds a line break, and
+    /// `MugenError::InFile` if a chart cannot be read.
+    #[cfg(feature = "email")]
+    pub fn message(&self, title: &str, report: &PipelineReport) -> Result<Message> {
+        let mut builder = Message::builder()
+            .from(mailbox("email.from", &self.from)?)
+            .subject(title.replace(['\r', '\n'], " "));
+        for recipient in &self.to {
+            builder = builder.to(mailbox("email.to", recipient)?);
+        }
+        let ids: Vec<(String, String)> = report
+            .artifacts
+            .iter()
+            .enumerate()
+            .map(|(index, chart)| (chart.path.clone(), format!("chart{}@mugen-park", index)))
+            .collect();
+        let body = report::html(title, report, |chart| {
+            ids.iter()
+                .find(|(path, _)| *path == chart.path)
+                .map(|(_, id)| format!("cid:{}", id))
+                .unwrap_or_default()
+        });
+
+        let mut parts = MultiPart::related().singlepart(SinglePart::html(body));
+        for (chart, (_, id)) in report.artifacts.iter().zip(&ids) {
+            let bytes = std::fs::read(&chart.path).map_err(|source| MugenError::InFile {
+                path: chart.path.clone(),
+                source: Box::new(source.into()),
+            })?;
+            let kind = ContentType::parse(content_type(&chart.path))
+                .map_err(|err| MugenError::InvalidArgument(format!("email: {}", err)))?;
+            parts = parts.singlepart(
+                Attachment::new_inline_with_name(id.clone(), report::file_name(chart))
+                    .body(bytes, kind),
+            );
+        }
+        builder
+            .multipart(parts)
+            .map_err(|err| MugenError::InvalidArgument(format!("email: {}", err)))
+    }
+
+    /// Emails the report of `report` to every recipient, unless there is no server or
+    /// no recipient.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing whether an email was sent, or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::Email` if the server cannot be reached, refuses the login,
+    /// a recipient or the message, or if the password variable is not set; the
+    /// password is left out of the error. Returns `MugenError::InvalidArgument`,
+    /// before connecting, if the sender or a recipient is not a valid mailbox.
+    #[cfg(feature = "email")]
+    pub fn send(&self, title: &str, report: &PipelineReport) -> Result<bool> {
+        let Some(server) = &self.server else {
+            return Ok(false);
+        };
+        if self.to.is_empty() {
+            return Ok(false);
+        }
+        let message = self.message(title, report)?;
+        let failed = |message: String| MugenError::Email {
+            server: format!("{}:{}", server, self.port()),
+            message,
+        };
+        let mut transport = match self.security {
+            Security::StartTls => SmtpTransport::starttls_relay(server),
+            Security::Tls => SmtpTransport::relay(server),
+            Security::None => Ok(SmtpTransport::builder_dangerous(server)),
+        }
+        .map_err(|err| failed(err.to_string()))?
+        .port(self.port())
+        .timeout(Some(Duration::from_secs(60)));
+        if let Some(username) = &self.username {
+            let variable = self.password_env.as_deref().unwrap_or_default();
+            let password = std::env::var(variable).map_err(|_| {
+                failed(format!(
+                    "no password for \"{}\"; set the environment variable named by \
+                     email.password_env",
+                    username
+                ))
+            })?;
+            transport = transport.credentials(Credentials::new(username.clone(), password));
+        }
+        transport
+            .build()
+            .send(&message)
+            .map_err(|err| failed(err.to_string()))?;
+        Ok(true)
+    }
+}
+
+/// Parses `mailbox`, an address or "Name <address>".
+///
+/// # Errors
+///
+/// Returns `MugenError::InvalidArgument`, naming `field`, if `mailbox` is not one.
+#[cfg(feature = "email")]
+fn mailbox(field: &str, mailbox: &str) -> Result<Mailbox> {
+    mailbox.trim().parse().map_err(|err| {
+        MugenError::InvalidArgument(format!(
+            "{} \"{}\" is not an address or \"Name <address>\": {}",
+            field,
+            mailbox.escape_debug(),
+            err
+        ))
+    })
+}
+
+#[cfg(all(test, feature = "email"))]
+mod tests {
+    use super::*;
+
+    fn email(from: &str, to: &[&str]) -> Email {
+        Email {
+            from: from.to_string(),
+            to: to.iter().map(|recipient| recipient.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the headers of `message`, before the first blank line.
+    fn headers(message: &Message) -> String {
+        let text = String::from_utf8(message.formatted()).unwrap();
+        text.split("\r\n\r\n").next().unwrap().to_string()
+    }
+
+    #[test]
+    fn message_headers() {
+        let message = email(
+            "Load forecasts <forecasts@example.com>",
+            &["operations@example.com", "Trading <trading@example.com>"],
+        )
+        .message("Daily run", &PipelineReport::default())
+        .unwrap();
+        let headers = headers(&message);
+        assert!(headers.contains("From: \"Load forecasts\" <forecasts@example.com>\r\n"));
+        assert!(headers.contains("To: operations@example.com,"));
+        assert!(headers.contains("Trading <trading@example.com>\r\n"));
+        assert!(headers.contains("Subject: Daily run\r\n"));
+        assert!(headers.contains("MIME-Version: 1.0\r\n"));
+        assert!(headers.contains("Content-Type: multipart/related;"));
+        let envelope = message.envelope();
+        assert_eq!(envelope.to().len(), 2);
+        assert_eq!(
+            envelope.from().map(|from| from.to_string()),
+            Some("forecasts@example.com".to_string())
+        );
+    }
+
+    #[test]
+    fn message_rejects_line_breaks_in_mailboxes() {
+        let report = PipelineReport::default();
+        let injected = "forecasts@example.com\r\nBcc: everyone@example.com";
+        for email in [
+            email(injected, &["operations@example.com"]),
+            email("forecasts@example.com", &[injected]),
+            email("forecasts@example.com", &["not an address"]),
+        ] {
+            assert!(matches!(
+                email.message("Daily run", &report),
+                Err(MugenError::InvalidArgument(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn message_keeps_the_subject_on_its_header() {
+        let message = email("forecasts@example.com", &["ops@example.com"])
+            .message("Daily\r\nBcc: x@example.com", &PipelineReport::default())
+            .unwrap();
+        let headers = headers(&message);
+        assert!(headers.contains("Subject: Daily  Bcc: x@example.com\r\n"));
+        assert!(!headers.contains("\r\nBcc:"));
+    }
+
+    #[test]
+    fn message_encodes_non_ascii_names() {
+        let message = email("Prévisions <forecasts@example.com>", &["ops@example.com"])
+            .message("Prévision de charge", &PipelineReport::default())
+            .unwrap();
+        let headers = headers(&message);
+        assert!(headers.is_ascii());
+        assert!(headers.contains("=?utf-8?b?"));
+        assert!(headers.contains("<forecasts@example.com>"));
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::btree_map::{BTreeMap, Entry};
+
+use chrono::DateTime;
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::{MugenError, Result};
+use mugen_core::zone::Zone;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Series served for every zone, in the order they are listed.
+pub const SERIES: [&str; 3] = ["actual", "forecast", "error"];
+
+/// Body of a Grafana `/search` request.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SearchRequest {
+    target: String,
+}
+
+/// Body of a Grafana `/query` request, keeping the fields used.
+#[derive(Debug, Deserialize)]
+struct QueryRequest {
+    range: Range,
+    #[serde(default)]
+    targets: Vec<Target>,
+}
+
+/// Time range of a query, as RFC 3339 times.
+#[derive(Debug, Deserialize)]
+struct Range {
+    from: String,
+    to: String,
+}
+
+/// One series asked for, named as listed by [`targets`].
+#[derive(Debug, Deserialize)]
+struct Target {
+    #[serde(default)]
+    target: String,
+}
+
+/// One timestamp of actual load joined against the forecast.
+struct Point {
+    millis: i64,
+    actual: f64,
+    forecast: f64,
+    error: f64,
+}
+
+/// Lists every series that can be queried: each zone's actual load, forecast and
+/// error, e.g. "N.Y.C. actual".
+pub fn targets() -> Vec<String> {
+    Zone::ALL
+        .into_iter()
+        .flat_map(|zone| SERIES.map(|series| format!("{} {}", zone, series)))
+        .collect()
+}
+
+/// Answers a Grafana SimpleJSON `/search` request with the [`targets`] containing its
+/// `target` text, ignoring case; an empty or missing body lists them all.
+///
+/// # Errors
+///
+/// Returns `MugenError::InvalidArgument` if `body` is not a search request.
+pub fn search(body: &[u8]) -> Result<Value> {
+    let request: SearchRequest = if body.iter().all(u8::is_ascii_whitespace) {
+        SearchRequest::default()
+    } else {
+        parse(body, "search")?
+    };
+    let text = request.target.to_lowercase();
+    let matching: Vec<String> = targets()
+        .into_iter()
+        .filter(|target| target.to_lowercase().contains(&text))
+        .collect();
+    Ok(json!(matching))
+}
+
+/// Answers a Grafana SimpleJSON `/query` request with the points of each target in
+/// its time range, as `{"target", "datapoints": [[value, epoch millis], ...]}`.
+///
+/// The "error" series is the residual, actual minus forecast. Timestamps are
+/// converted from the timezone of `joined` to UTC, which Grafana sends the range in
+/// and expects the points in.
+///
+/// # Arguments
+///
+/// * `body` - The JSON of the request.
+/// * `joined` - Returns a zone's actual load joined against its forecast, as from
+///   `mugen_core::data::join_actual_forecast`; called once per zone queried.
+///
+/// # Returns
+///
+/// * A `Result` containing the response or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns `MugenError::InvalidArgument` if `body` is not a query request or a target
+/// is not one of [`targets`], and the errors of `joined` and of converting its
+/// timestamps to UTC.
+pub fn query(body: &[u8], joined: impl Fn(Zone) -> Result<TimeSeriesDataset>) -> Result<Value> {
+    let request: QueryRequest = parse(body, "query")?;
+    let from = millis(&request.range.from)?;
+    let to = millis(&request.range.to)?;
+    let mut cache: BTreeMap<Zone, Vec<Point>> = BTreeMap::new();
+    let mut answer = Vec::new();
+    for target in &request.targets {
+        let (zone, series) = target
+            .target
+            .rsplit_once(' ')
+            .filter(|(_, series)| SERIES.contains(series))
+            .ok_or_else(|| {
+                MugenError::InvalidArgument(format!(
+                    "unknown target \"{}\", expected a zone and one of: {}",
+                    target.target,
+                    SERIES.join(", ")
+                ))
+            })?;
+        let zone: Zone = zone.parse()?;
+        let points = match cache.entry(zone) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(joined_points(&joined(zone)?)?),
+        };
+        let datapoints: Vec<Value> = points
+            .iter()
+            .filter(|point| point.millis >= from && point.millis <= to)
+            .map(|point| {
+                let value = match series {
+                    "actual" => point.actual,
+                    "forecast" => point.forecast,
+                    _ => point.error,
+                };
+                json!([value, point.millis])
+            })
+            .collect();
+        answer.push(json!({ "target": target.target, "datapoints": datapoints }));
+    }
+    Ok(Value::Array(answer))
+}
+
+/// Lays out `joined` as rows of `{"time", "actual", "forecast", "error"}`, with the
+/// time as RFC 3339 in UTC, for the Grafana Infinity datasource and other tools that
+/// read JSON tables.
+///
+/// # Errors
+///
+/// Returns an error if `joined` lacks the joined columns or its timezone is unknown.
+pub fn rows(joined: &TimeSeriesDataset) -> Result<Value> {
+    let rows: Vec<Value> = joined_points(joined)?
+        .into_iter()
+        .filter_map(|point| {
+            let time = DateTime::from_timestamp_millis(point.millis)?;
+            Some(json!({
+                "time": time.to_rfc3339(),
+                "actual": point.actual,
+                "forecast": point.forecast,
+                "error": point.error,
+            }))
+        })
+        .collect();
+    Ok(Value::Array(rows))
+}
+
+/// Reads the complete rows of a dataset returned by `join_actual_forecast`, with
+/// their timestamps in UTC.
+fn joined_points(joined: &TimeSeriesDataset) -> Result<Vec<Point>> {
+    let joined: TimeSeriesDataset = joined.in_timezone("UTC")?;
+    let actual = joined.values("Actual")?;
+    let forecast = joined.values("Forecast")?;
+    let residual = joined.values("Residual")?;
+    Ok(joined
+        .timestamps()?
+        .into_iter()
+        .zip(actual.into_iter().zip(forecast.into_iter().zip(&residual)))
+        .filter_map(|(millis, (actual, (forecast, error)))| {
+            Some(Point {
+                millis: millis?,
+                actual: actual?,
+                forecast: forecast?,
+                error: error?,
+            })
+        })
+        .collect())
+}
+
+/// Parses the JSON body of a Grafana `kind` request.
+fn parse<'a, T: Deserialize<'a>>(body: &'a [u8], kind: &str) -> Result<T> {
+    serde_json::from_slice(body)
+        .map_err(|err| MugenError::InvalidArgument(format!("invalid Grafana {}: {}", kind, err)))
+}
+
+/// Reads an RFC 3339 time as milliseconds since the epoch.
+fn millis(time: &str) -> Result<i64> {
+    DateTime::parse_from_rfc3339(time)
+        .map(|time| time.timestamp_millis())
+        .map_err(|err| {
+            MugenError::InvalidArgument(format!("invalid time \"{}\" in query: {}", time, err))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use polars::prelude::*;
+
+    use super::*;
+
+    /// Returns the epoch milliseconds of `hour`:00 on `day` of November 2023, read as
+    /// UTC.
+    fn november(day: u32, hour: u32) -> i64 {
+        NaiveDate::from_ymd_opt(2023, 11, day)
+            .and_then(|day| day.and_hms_opt(hour, 0, 0))
+            .unwrap()
+            .and_utc()
+            .timestamp_millis()
+    }
+
+    /// Noon in New York on the days either side of the end of daylight saving time
+    /// on 5 November 2023, joined against a forecast 100 MW low.
+    fn joined() -> TimeSeriesDataset {
+        let frame: DataFrame = df!(
+            "Time Stamp" => Series::new("Time Stamp", [november(4, 12), november(5, 12)])
+                .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))
+                .unwrap(),
+            "Actual" => [5000.0, 5200.0],
+            "Forecast" => [4900.0, 5100.0],
+            "Residual" => [100.0, 100.0],
+        )
+        .unwrap();
+        TimeSeriesDataset::new(
+            "joined",
+            frame,
+            "Time Stamp",
+            vec!["Actual".to_string(), "Forecast".to_string()],
+        )
+        .unwrap()
+        .with_timezone("America/New_York")
+    }
+
+    /// Queries the actual load of New York City from `from` to `to`.
+    fn query_actual(from: &str, to: &str) -> Value {
+        let body: String = json!({
+            "range": { "from": from, "to": to },
+            "targets": [{ "target": "N.Y.C. actual" }],
+        })
+        .to_string();
+        query(body.as_bytes(), |_| Ok(joined())).unwrap()
+    }
+
+    #[test]
+    fn points_are_sent_and_filtered_in_utc_across_daylight_saving() {
+        // Noon is 16:00 UTC in daylight time and 17:00 UTC after it ends.
+        let both: Value = query_actual("2023-11-04T16:00:00Z", "2023-11-05T17:00:00Z");
+        assert_eq!(
+            both[0]["datapoints"],
+            json!([[5000.0, november(4, 16)], [5200.0, november(5, 17)]])
+        );
+        let first: Value = query_actual("2023-11-04T15:30:00Z", "2023-11-05T16:30:00Z");
+        assert_eq!(first[0]["datapoints"], json!([[5000.0, november(4, 16)]]));
+        let none: Value = query_actual("2023-11-04T11:30:00Z", "2023-11-04T12:30:00Z");
+        assert_eq!(none[0]["datapoints"], json!([]));
+
+        let rows: Value = rows(&joined()).unwrap();
+        assert_eq!(rows[0]["time"], "2023-11-04T16:00:00+00:00");
+        assert_eq!(rows[1]["time"], "2023-11-05T17:00:00+00:00");
+        assert_eq!(rows[1]["error"], 100.0);
+    }
+}
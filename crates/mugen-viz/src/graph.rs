@@ -0,0 +1,3113 @@
+use crate::render::{echarts_option, Backend, Renderer};
+use charming::{
+    component::{
+        Axis, Grid, Legend, RadarAxisName, RadarCoordinate, RadarIndicator, Title, VisualMap,
+    },
+    datatype::{CompositeValue, DataPoint, DataPointItem},
+    element::{
+        AreaStyle, AxisLabel, AxisLine, AxisLineStyle, AxisType, Color, Formatter, ItemStyle,
+        Label, LineStyle, LineStyleType, MarkArea, MarkAreaData, MarkLine, MarkLineData,
+        MarkLineVariant, NameLocation, Orient, SplitArea, Symbol, TextStyle,
+    },
+    series::{Bar, Gauge, GaugeDetail, GaugeTitle, Heatmap, Line, Pie, Radar, Scatter},
+    Chart,
+};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Timelike, Utc};
+use image::ImageFormat;
+use mugen_core::data::{
+    f64_column, join_actual_forecast, min_max_downsample_aligned, moving_average, str_column,
+    timestamp_column, zones_in,
+};
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::{MugenError, Result};
+use mugen_core::zone::Zone;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::default::Default;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+use std::str::FromStr;
+use tracing::{info_span, instrument};
+
+/// A chart that can be built from its data and saved as an image.
+///
+/// Implementors provide [`Graph::config`] and [`Graph::chart`]; the other methods
+/// export, render or save that chart, rendering through a [`Renderer`]. The trait is
+/// object safe, so charts of different types can be collected as `Box<dyn Graph>`
+/// and drawn together with [`draw_all`].
+pub trait Graph {
+    /// Returns the shared chart settings.
+    fn config(&self) -> &GraphConfig;
+
+    /// Returns the output path for a chart of `graph_type` under the configured
+    /// output directory.
+    ///
+    /// Charts are timestamped so successive runs don't overwrite each other, except
+    /// in deterministic mode where the path depends only on `graph_type`. A
+    /// configured [`GraphConfig::filename`] is used as `{stem}_{graph_type}.{ext}`
+    /// instead, so charts of different types drawn with one config keep separate
+    /// files. Either way [`GraphConfig::on_collision`] decides what happens if the
+    /// file already exists.
+    fn generate_filename(&self, graph_type: &str) -> String {
+        let output_dir = &self.config().output_dir;
+        let extension = self.config().backend.renderer().extension(ImageFormat::Png);
+        if let Some(filename) = &self.config().filename {
+            let filename = Path::new(filename);
+            let stem = filename.with_extension("");
+            let extension = match filename.extension() {
+                Some(given) => given.to_string_lossy().into_owned(),
+                None => extension.to_string(),
+            };
+            let name = format!("{}_{}.{}", stem.to_string_lossy(), graph_type, extension);
+            return Path::new(output_dir)
+                .join(name)
+                .to_string_lossy()
+                .into_owned();
+        }
+        if self.config().deterministic {
+            return format!("{}/{}.{}", output_dir, graph_type, extension);
+        }
+        let now: DateTime<Utc> = Utc::now();
+        format!(
+            "{}/{}_{}.{}",
+            output_dir,
+            graph_type,
+            now.format("%Y%m%d%H%M%S"),
+            extension
+        )
+    }
+
+    /// Builds the chart, including any text watermark, without rendering it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the frame and column if the data lacks a column the
+    /// chart reads or holds values of the wrong type.
+    fn chart(&self) -> Result<Chart>;
+
+    /// Serializes the chart to its ECharts option JSON.
+    ///
+    /// The output can be passed straight to `echartsInstance.setOption` so a web
+    /// frontend re-renders exactly the chart that `draw` would save. Image
+    /// watermarks are composited at render time and are not part of the spec.
+    /// In deterministic mode animations are switched off in the spec, so every
+    /// client renders the final frame straight away.
+    ///
+    /// The spec is strict JSON. Value axes carry the unit as a string template, so
+    /// browsers write their numbers with ECharts' own grouping; to show the
+    /// configured separators too, set their label formatter to
+    /// [`NumberFormat::axis_function`], as the live page and notebooks do.
+    fn to_echarts_json(&self) -> Result<String> {
+        echarts_option(&self.chart()?, self.config())
+    }
+
+    /// Renders the chart, including any watermark, to encoded image bytes in `format`
+    /// with the configured backend, without touching the filesystem.
+    ///
+    /// Useful for embedding charts in HTTP responses or generated reports.
+    fn render_to_bytes(&self, format: ImageFormat) -> Result<Vec<u8>> {
+        self.render_with(self.config().backend.renderer().as_ref(), format)
+    }
+
+    /// Renders the chart like [`Graph::render_to_bytes`] and writes it to `writer`,
+    /// e.g. an HTTP response body, a zip archive entry or an upload stream, without
+    /// a temporary file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the chart cannot be built or rendered, or `MugenError::Io`
+    /// if `writer` fails.
+    fn render_to_writer(&self, writer: &mut dyn Write, format: ImageFormat) -> Result<()> {
+        writer.write_all(&self.render_to_bytes(format)?)?;
+        Ok(writer.flush()?)
+    }
+
+    /// Renders the chart with `renderer` instead of the configured backend, e.g. a
+    /// custom or test renderer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the chart cannot be built, or the error of `renderer`.
+    fn render_with(&self, renderer: &dyn Renderer, format: ImageFormat) -> Result<Vec<u8>> {
+        let _span = info_span!("render", ?format).entered();
+        renderer.render(&self.chart()?, self.config(), format)
+    }
+
+    /// Renders the chart with the configured backend to a file under the output
+    /// directory, a PNG by default, and describes what was written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the chart cannot be built or rendered, or the image cannot
+    /// be written.
+    fn draw(&self) -> Result<RenderedChart>;
+}
+
+/// Describes a chart image written by [`Graph::draw`].
+#[derive(Clone, Debug)]
+pub struct RenderedChart {
+    /// Where the image was written.
+    pub path: String,
+    /// Image width in pixels.
+    pub width: u32,
+    /// Image height in pixels.
+    pub height: u32,
+    /// Encoding requested from the renderer; backends that do not draw images write
+    /// their own format, named by the file extension.
+    pub format: ImageFormat,
+    /// Number of series in the chart, including helper series such as thresholds.
+    pub series_count: usize,
+    /// Number of data points across all series, not counting gaps.
+    pub point_count: usize,
+}
+
+impl fmt::Display for RenderedChart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}x{} {:?}, {} series, {} points)",
+            self.path, self.width, self.height, self.format, self.series_count, self.point_count
+        )
+    }
+}
+
+/// Draws every chart in `graphs` in order and describes what was written.
+///
+/// # Errors
+///
+/// Stops at the first chart that fails to draw and returns its error; charts drawn
+/// before it stay on disk.
+pub fn draw_all(graphs: &[Box<dyn Graph>]) -> Result<Vec<RenderedChart>> {
+    graphs.iter().map(|graph| graph.draw()).collect()
+}
+
+/// A mark stamped onto every rendered chart, e.g. to flag drafts before they circulate.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Watermark {
+    /// Translucent text placed in the bottom-right corner of the chart.
+    Text(String),
+    /// Path to an image (typically a PNG logo with transparency) overlaid in the
+    /// bottom-right corner of the rendered chart.
+    Image(String),
+}
+
+/// A labeled horizontal reference line, such as a zone's capacity or an alert level.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Threshold {
+    /// Legend name of the line.
+    pub label: String,
+    /// Height of the line, in the units of the value axis.
+    pub value: f64,
+    /// CSS color of the line.
+    pub color: String,
+}
+
+/// Output size presets. Everything drawn on the chart scales with the size, see
+/// [`GraphConfig::px`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Resolution {
+    /// 640x360, for previews and link unfurls.
+    Thumbnail,
+    /// 1920x1080, for full-screen presentation slides.
+    Slide,
+    /// 2400x1600, for figures in printed or PDF reports.
+    Report,
+    /// 3840x2160.
+    #[default]
+    #[serde(rename = "4k")]
+    Uhd4k,
+}
+
+impl Resolution {
+    /// Returns the chart width and height in pixels.
+    pub fn size(self) -> (u32, u32) {
+        match self {
+            Resolution::Thumbnail => (640, 360),
+            Resolution::Slide => (1920, 1080),
+            Resolution::Report => (2400, 1600),
+            Resolution::Uhd4k => (3840, 2160),
+        }
+    }
+}
+
+impl FromStr for Resolution {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "thumbnail" => Ok(Resolution::Thumbnail),
+            "slide" => Ok(Resolution::Slide),
+            "report" => Ok(Resolution::Report),
+            "4k" => Ok(Resolution::Uhd4k),
+            _ => Err(format!(
+                "unknown resolution \"{}\", expected one of: thumbnail, slide, report, 4k",
+                value
+            )),
+        }
+    }
+}
+
+/// What to do when a chart is saved to a path that already exists.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Collision {
+    /// Replaces the existing file.
+    #[default]
+    Overwrite,
+    /// Saves under the first free name with a counter before the extension, e.g.
+    /// `LineChart_1.png`.
+    Increment,
+    /// Fails with `MugenError::Write` and leaves the existing file alone.
+    Error,
+}
+
+impl FromStr for Collision {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "overwrite" => Ok(Collision::Overwrite),
+            "increment" => Ok(Collision::Increment),
+            "error" => Ok(Collision::Error),
+            _ => Err(format!(
+                "unknown collision policy \"{}\", expected one of: overwrite, increment, error",
+                value
+            )),
+        }
+    }
+}
+
+/// How axis values are written: digit grouping, decimal mark and an optional unit,
+/// e.g. "5,200 MW" for US English or "5.200 MW" for German.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NumberFormat {
+    /// Separator between groups of three integer digits.
+    pub grouping: String,
+    /// Separator between the integer and fractional digits.
+    pub decimal: String,
+    /// Appended after the number, separated by a space.
+    pub unit: Option<String>,
+}
+
+impl NumberFormat {
+    /// Returns the separators used in `locale`, a BCP 47 tag such as "en-US" or
+    /// "de-DE", or `None` if the locale is not known. Only the language and region
+    /// are looked at.
+    pub fn for_locale(locale: &str) -> Option<Self> {
+        let locale = locale.replace('_', "-").to_ascii_lowercase();
+        let language = locale.split('-').next().unwrap_or_default();
+        let (grouping, decimal) = match (language, locale.as_str()) {
+            (_, "de-ch" | "fr-ch" | "it-ch") => ("’", "."),
+            ("en" | "ja" | "zh" | "ko" | "he" | "th", _) => (",", "."),
+            ("de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr", _) => (".", ","),
+            ("fr", _) => ("\u{202f}", ","),
+            ("sv" | "nb" | "no" | "fi" | "pl" | "cs" | "sk" | "ru" | "uk" | "hu", _) => {
+                ("\u{a0}", ",")
+            }
+            _ => return None,
+        };
+        Some(NumberFormat {
+            grouping: grouping.to_string(),
+            decimal: decimal.to_string(),
+            unit: None,
+        })
+    }
+
+    /// Appends `unit` after every formatted value.
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    /// Writes `value` in this format, rounded to two decimals like axis labels are,
+    /// e.g. "5,200.5 MW".
+    pub fn format(&self, value: f64) -> String {
+        let rounded = format!("{}", (value * 100.0).round() / 100.0);
+        let (integer, fraction) = match rounded.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (rounded.as_str(), None),
+        };
+        let (sign, digits) = match integer.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", integer),
+        };
+        let mut text = sign.to_string();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                text.push_str(&self.grouping);
+            }
+            text.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            text.push_str(&self.decimal);
+            text.push_str(fraction);
+        }
+        if let Some(unit) = &self.unit {
+            text.push(' ');
+            text.push_str(unit);
+        }
+        text
+    }
+
+    /// Returns the ECharts label template for value axes in JSON specs, e.g.
+    /// "{value} MW". ECharts fills in the value with its own grouping, so the
+    /// separators of this format only show through [`NumberFormat::axis_function`].
+    pub fn axis_template(&self) -> String {
+        format!("{{value}}{}", self.suffix())
+    }
+
+    /// Returns the source of a JavaScript label formatter writing values in this
+    /// format, for pages that draw a JSON spec and want its separators. Values are
+    /// rounded to two decimals, which only shows on small axis ranges.
+    pub fn axis_function(&self) -> String {
+        // JSON string literals are valid JavaScript, which takes care of escaping.
+        let quote = |text: &str| serde_json::to_string(text).unwrap();
+        format!(
+            "function (value) {{ \
+             var parts = String(Math.round(value * 100) / 100).split('.'); \
+             parts[0] = parts[0].replace(/\\B(?=(\\d{{3}})+(?!\\d))/g, {}); \
+             return parts.join({}) + {}; }}",
+            quote(&self.grouping),
+            quote(&self.decimal),
+            quote(&self.suffix())
+        )
+    }
+
+    /// Builds the label formatter of value axes in rendered images, where ECharts
+    /// runs in-process and can call [`NumberFormat::axis_function`].
+    fn axis_formatter(&self) -> Formatter {
+        // charming doesn't export its raw JavaScript string type, but it can be
+        // reached through `Into`.
+        Formatter::Function(self.axis_function().into())
+    }
+
+    /// Returns the unit with its leading space, or nothing without a unit.
+    fn suffix(&self) -> String {
+        match &self.unit {
+            Some(unit) => format!(" {}", unit),
+            None => String::new(),
+        }
+    }
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat::for_locale("en-US").unwrap()
+    }
+}
+
+impl FromStr for NumberFormat {
+    type Err = String;
+
+    fn from_str(locale: &str) -> Result<Self, Self::Err> {
+        NumberFormat::for_locale(locale).ok_or_else(|| format!("unsupported locale \"{}\"", locale))
+    }
+}
+
+/// A named look shared by every chart that references it, so charts stay
+/// consistent without repeating colors and sizes. See [`Style::named`] for the
+/// built-in templates.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Style {
+    /// Template name, as accepted by [`Style::named`].
+    pub name: String,
+    /// Default series colors, in order; empty keeps the theme's palette. Series
+    /// with an explicit color keep it.
+    pub palette: Vec<String>,
+    /// Overrides the theme's background color.
+    pub background: Option<String>,
+    /// Renders with the dark ECharts theme instead of the light default one.
+    pub dark: bool,
+    /// Multiplies every font size.
+    pub font_scale: f64,
+    /// Multiplies every line width.
+    pub line_scale: f64,
+    /// Left margin of cartesian grids, as a percentage of the chart width.
+    pub grid_left: String,
+    /// Right margin of cartesian grids, as a percentage of the chart width.
+    pub grid_right: String,
+}
+
+impl Style {
+    /// Returns the built-in template called `name`:
+    ///
+    /// * "dark" - The default: dark theme at the base font and line sizes.
+    /// * "light" - The light theme on a white background.
+    /// * "print" - Light, with a palette that survives grayscale printing and
+    ///   heavier lines.
+    /// * "presentation" - Dark, with larger text and thicker lines for projectors.
+    pub fn named(name: &str) -> Option<Self> {
+        let base = Style {
+            name: name.to_string(),
+            palette: Vec::new(),
+            background: None,
+            dark: true,
+            font_scale: 1.0,
+            line_scale: 1.0,
+            grid_left: "4%".to_string(),
+            grid_right: "5%".to_string(),
+        };
+        match name {
+            "dark" => Some(base),
+            "light" => Some(Style {
+                dark: false,
+                background: Some("#ffffff".to_string()),
+                ..base
+            }),
+            "print" => Some(Style {
+                dark: false,
+                background: Some("#ffffff".to_string()),
+                palette: ["#000000", "#e66101", "#5e3c99", "#fdb863", "#b2abd2"]
+                    .iter()
+                    .map(|color| color.to_string())
+                    .collect(),
+                line_scale: 1.5,
+                grid_left: "6%".to_string(),
+                grid_right: "6%".to_string(),
+                ..base
+            }),
+            "presentation" => Some(Style {
+                palette: ["#4cc9f0", "#f72585", "#b5e48c", "#ffd166", "#9d4edd"]
+                    .iter()
+                    .map(|color| color.to_string())
+                    .collect(),
+                font_scale: 1.25,
+                line_scale: 1.5,
+                ..base
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns a cartesian grid with this style's side margins, labels included.
+    fn grid(&self) -> Grid {
+        Grid::new()
+            .left(self.grid_left.as_str())
+            .right(self.grid_right.as_str())
+            .contain_label(true)
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style::named("dark").unwrap()
+    }
+}
+
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Style::named(name).ok_or_else(|| {
+            format!(
+                "unknown style \"{}\", expected one of: dark, light, print, presentation",
+                name
+            )
+        })
+    }
+}
+
+/// Fixed text drawn on charts: series and legend names, metric names, captions and
+/// chart titles.
+///
+/// Override any of them, e.g. to produce charts in another language; fields
+/// missing from a config file keep their English defaults. Text with values in it
+/// takes `{name}` placeholders like [`GraphConfig::title`]; `{unit}` is the unit of
+/// the data, and is dropped with the space before it if the data has none.
+///
+/// # Examples
+///
+/// ```toml
+/// [graph.labels]
+/// actual = "Ist-Last"
+/// forecast = "Prognose"
+/// moving_average = "Gleitender Mittelwert ({window} Punkte)"
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Labels {
+    /// Actual load, in legends.
+    pub actual: String,
+    /// The forecast, in legends.
+    pub forecast: String,
+    /// Caption of the residual panel's axis.
+    pub residual: String,
+    /// The moving average series; `{window}` is its number of points.
+    pub moving_average: String,
+    /// Points of the actual-vs-forecast scatter chart.
+    pub hourly_load: String,
+    /// Diagonal of the actual-vs-forecast scatter chart.
+    pub perfect_forecast: String,
+    /// Series of the load heatmap.
+    pub load: String,
+    /// The mean absolute percentage error metric.
+    pub mape: String,
+    /// The peak error metric.
+    pub peak_error: String,
+    /// The bias metric.
+    pub bias: String,
+    /// Radar chart axes; `{metric}` is the metric's label and `{max}` its largest
+    /// value across zones.
+    pub metric_axis: String,
+    /// Caption of the peak gauge; `{day}` is the day and `{peak}` the forecast peak.
+    pub forecast_peak: String,
+    /// Value shown under the peak gauge; ECharts fills in `{value}`.
+    pub gauge_value: String,
+    /// Label of a threshold line; ECharts fills in its name `{b}` and value `{c}`.
+    pub threshold: String,
+    /// Label of a slice of the zone share pie; ECharts fills in the zone `{b}`, its
+    /// load `{c}` and its share in percent `{d}`.
+    pub zone_share: String,
+    /// The zonal price overlaid on the load chart, in legends and on its axis.
+    pub price: String,
+    /// Values on the price axis; ECharts fills in `{value}`.
+    pub price_value: String,
+    /// Caption of the forecast axis of the actual-vs-forecast scatter chart.
+    pub forecast_axis: String,
+    /// Caption of the actual axis of the actual-vs-forecast scatter chart.
+    pub actual_axis: String,
+    /// Caption of the day axis of the load heatmap.
+    pub day: String,
+    /// Caption of the hour axis of the load heatmap.
+    pub hour: String,
+    /// How far one forecast moved from another, in legends and on its axis.
+    pub difference: String,
+    /// Titles of the charts the command-line tool draws besides the main one.
+    pub titles: Titles,
+}
+
+impl Default for Labels {
+    fn default() -> Self {
+        Labels {
+            actual: "Actual".to_string(),
+            forecast: "Forecast".to_string(),
+            residual: "Residual".to_string(),
+            moving_average: "{window}-point moving average".to_string(),
+            hourly_load: "Hourly load".to_string(),
+            perfect_forecast: "Perfect forecast".to_string(),
+            load: "Load".to_string(),
+            mape: "MAPE".to_string(),
+            peak_error: "Peak Error".to_string(),
+            bias: "Bias".to_string(),
+            metric_axis: "{metric} (max {max}%)".to_string(),
+            forecast_peak: "Forecast peak {day}: {peak} {unit}".to_string(),
+            gauge_value: "{value} {unit}".to_string(),
+            threshold: "{b}: {c} {unit}".to_string(),
+            zone_share: "{b}\n{c} {unit} ({d}%)".to_string(),
+            price: "Price ($/MWh)".to_string(),
+            price_value: "${value}".to_string(),
+            forecast_axis: "Forecast ({unit})".to_string(),
+            actual_axis: "Actual ({unit})".to_string(),
+            day: "Day".to_string(),
+            hour: "Hour".to_string(),
+            difference: "Difference".to_string(),
+            titles: Titles::default(),
+        }
+    }
+}
+
+/// Titles of the charts the command-line tool draws, except its main actual vs
+/// forecast chart, whose title is [`GraphConfig::title`]; they take the same
+/// placeholders, and the few noted below.
+///
+/// # Examples
+///
+/// ```toml
+/// [graph.labels.titles]
+/// gauge = "{region}: Last vs Prognosespitze am {end_date}"
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Titles {
+    /// The peak gauge.
+    pub gauge: String,
+    /// The downstate zones overlaid on one chart.
+    pub overlay: String,
+    /// The actual-vs-forecast scatter chart.
+    pub scatter: String,
+    /// The load heatmap by day and hour.
+    pub heatmap: String,
+    /// Every forecast issued for one day.
+    pub vintages: String,
+    /// A blend of forecasts and its members.
+    pub ensemble: String,
+    /// The Holt-Winters and persistence forecasts.
+    pub baseline: String,
+    /// A forecast with its prediction interval; `{level}` is its coverage in percent.
+    pub interval: String,
+    /// A what-if scenario against the forecast.
+    pub scenario: String,
+    /// The day-ahead forecast against the same-day update.
+    pub day_ahead: String,
+    /// How far one forecast moved from another.
+    pub difference: String,
+    /// Another market's load against its forecast; `{market}` is the market's name.
+    pub market: String,
+    /// NYISO's load against the demand it reported to the EIA.
+    pub crosscheck: String,
+    /// Every zone's load stacked.
+    pub zonal_load: String,
+    /// Each zone's forecast errors on a radar.
+    pub zone_errors: String,
+    /// Each zone's share of the load; `{at}` is the time shown.
+    pub zone_shares: String,
+    /// A forecast of an ONNX model.
+    pub model: String,
+    /// The self-refreshing chart page.
+    pub live: String,
+    /// Charts of the chart server and the Python and JavaScript bindings.
+    pub served: String,
+}
+
+impl Default for Titles {
+    fn default() -> Self {
+        Titles {
+            gauge: "{region} Load vs Forecast Peak on {end_date}".to_string(),
+            overlay: "Downstate Zone Loads {start_date}–{end_date}".to_string(),
+            scatter: "{region} Actual vs Forecast {start_date}–{end_date}".to_string(),
+            heatmap: "{region} Load by Hour {start_date}–{end_date}".to_string(),
+            vintages: "{region} Forecast Vintages for {start_date}".to_string(),
+            ensemble: "{region} Forecast Ensemble {start_date}–{end_date}".to_string(),
+            baseline: "{region} Baseline Forecasts {start_date}–{end_date}".to_string(),
+            interval: "{region} Forecast with {level}% Interval {start_date}–{end_date}"
+                .to_string(),
+            scenario: "{region} Scenario {start_date}–{end_date}".to_string(),
+            day_ahead: "{region} Day-Ahead vs Same-Day Forecast {start_date} - {end_date}"
+                .to_string(),
+            difference: "{region} Forecast Difference {start_date} to {end_date}".to_string(),
+            market: "{market} {region} Load vs Forecast {start_date}–{end_date}".to_string(),
+            crosscheck: "NYISO Load vs EIA Demand {start_date}–{end_date}".to_string(),
+            zonal_load: "NYISO Zonal Load {start_date}–{end_date}".to_string(),
+            zone_errors: "Forecast Error by Zone".to_string(),
+            zone_shares: "NYISO Zone Load Share at {at}".to_string(),
+            model: "{region} Model Forecast {start_date}–{end_date}".to_string(),
+            live: "Live Load vs Forecast for {region} through {end_date}".to_string(),
+            served: "Load vs Forecast for {region} through {end_date}".to_string(),
+        }
+    }
+}
+
+/// Settings shared by every chart type: titles, size, output location and styling.
+///
+/// Start from [`GraphConfig::preset`] or `Default` and override fields with struct
+/// update syntax.
+///
+/// Serializes to and from TOML or JSON with [`crate::config::read_config`] and
+/// [`crate::config::write_config`]; fields missing from a file keep their defaults.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphConfig {
+    /// Chart title; may contain placeholders such as `{region}`, filled per chart.
+    pub title: String,
+    /// Name of the x-axis.
+    pub x_label: String,
+    /// Name of the value axis.
+    pub y_label: String,
+    /// Image width in pixels.
+    pub graph_width: u32,
+    /// Image height in pixels.
+    pub graph_height: u32,
+    /// Mark stamped onto the rendered chart.
+    pub watermark: Option<Watermark>,
+    /// Horizontal reference lines drawn on charts with a value axis.
+    pub thresholds: Vec<Threshold>,
+    /// Makes byte-identical inputs produce byte-identical outputs: charts are saved
+    /// under a fixed filename and exported specs have animation disabled.
+    pub deterministic: bool,
+    /// `chrono` format string for timestamps on the x-axis.
+    pub time_format: String,
+    /// Shows one x-axis label every this many points; `None` lets ECharts choose.
+    pub label_every: Option<usize>,
+    /// Directory charts are saved into; created on demand.
+    pub output_dir: String,
+    /// File name to save charts under, relative to `output_dir`, instead of one
+    /// generated from the chart type; the chart type is appended to its stem.
+    pub filename: Option<String>,
+    /// What to do if the file a chart is saved to already exists.
+    pub on_collision: Collision,
+    /// How numbers on value axes are written.
+    pub number_format: NumberFormat,
+    /// Shared look: palette, background, font and line scaling, grid margins.
+    pub style: Style,
+    /// Fixed text drawn on charts, such as legend names.
+    pub labels: Labels,
+    /// Renderer charts are drawn with.
+    pub backend: Backend,
+}
+
+impl GraphConfig {
+    /// Returns the default settings sized for `resolution`.
+    pub fn preset(resolution: Resolution) -> Self {
+        let (graph_width, graph_height) = resolution.size();
+        GraphConfig {
+            graph_width,
+            graph_height,
+            ..Default::default()
+        }
+    }
+
+    /// Scales a size in pixels, tuned for a 3840x2160 chart, to this chart's size so
+    /// fonts, line widths and offsets keep their proportions at any resolution.
+    pub fn px(&self, size: f64) -> f64 {
+        let scale = (self.graph_width as f64 / 3840.0).min(self.graph_height as f64 / 2160.0);
+        size * scale
+    }
+
+    /// Scales a font size like [`GraphConfig::px`], then by the style's font scale.
+    pub fn font(&self, size: f64) -> f64 {
+        self.px(size) * self.style.font_scale
+    }
+
+    /// Scales a line width like [`GraphConfig::px`], then by the style's line scale.
+    pub fn line(&self, width: f64) -> f64 {
+        self.px(width) * self.style.line_scale
+    }
+}
+
+impl Default for GraphConfig {
+    fn default() -> Self {
+        GraphConfig {
+            title: "Graph".to_string(),
+            x_label: "X-axis".to_string(),
+            y_label: "Y-axis".to_string(),
+            graph_width: 3840,
+            graph_height: 2160,
+            watermark: None,
+            thresholds: Vec::new(),
+            deterministic: false,
+            time_format: "%m/%d/%Y %H:%M".to_string(),
+            label_every: None,
+            output_dir: "charts".to_string(),
+            filename: None,
+            on_collision: Collision::default(),
+            number_format: NumberFormat::default(),
+            style: Style::default(),
+            labels: Labels::default(),
+            backend: Backend::default(),
+        }
+    }
+}
+
+/// Actual load of one zone against its forecast over time.
+///
+/// `data` is ground truth as returned by `process_truth` and `forecast` is the
+/// matching output of `process_pred`; each is drawn from its primary value column.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LineGraph {
+    /// Shared chart settings.
+    pub config: GraphConfig,
+    /// Processed ground truth.
+    #[serde(skip)]
+    pub data: TimeSeriesDataset,
+    /// Free-form notes kept with the chart; not drawn.
+    pub notes: String,
+    /// Processed forecast.
+    #[serde(skip)]
+    pub forecast: TimeSeriesDataset,
+    /// Line width before resolution scaling.
+    pub line_thickness: u32,
+    /// CSS color of the forecast line.
+    pub forecast_color: String,
+    /// Zone shown, used for the `{region}` title placeholder.
+    pub zone: Zone,
+    /// Shades the gap between actual and forecast.
+    pub error_band: bool,
+    /// Window, in points, of an extra moving-average line over the actuals.
+    pub moving_average: Option<usize>,
+    /// Draws a vertical line at every midnight.
+    pub day_markers: bool,
+    /// Shades alternate days.
+    pub day_shading: bool,
+    /// Downsamples both series to at most this many points.
+    pub max_points: Option<usize>,
+    /// Draws the forecast as a step line.
+    pub step_forecast: bool,
+    /// Adds a residual panel below the main chart.
+    pub residual_panel: bool,
+    /// Zonal price, as returned by `process_price`, drawn on a secondary axis at the
+    /// right; prices at times without load are left out.
+    #[serde(skip)]
+    pub price: Option<TimeSeriesDataset>,
+    /// Prediction interval around the forecast, as returned by
+    /// `ErrorQuantiles::interval`, shaded between its "Lower" and "Upper" columns.
+    #[serde(skip)]
+    pub interval: Option<TimeSeriesDataset>,
+}
+
+impl Default for LineGraph {
+    fn default() -> Self {
+        LineGraph {
+            config: GraphConfig::default(),
+            data: TimeSeriesDataset::default(),
+            notes: String::new(),
+            forecast: TimeSeriesDataset::default(),
+            line_thickness: 5,
+            forecast_color: "GREEN".to_string(),
+            zone: Zone::NewYorkCity,
+            error_band: false,
+            moving_average: None,
+            day_markers: false,
+            day_shading: false,
+            max_points: None,
+            step_forecast: false,
+            residual_panel: false,
+            price: None,
+            interval: None,
+        }
+    }
+}
+
+impl LineGraph {
+    /// Starts building a `LineGraph` with the default settings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use mugen_core::data::{process_pred, process_truth};
+    /// use mugen_core::synthetic::Synthetic;
+    /// use mugen_viz::graph::LineGraph;
+    /// use mugen_core::zone::Zone;
+    ///
+    /// # fn main() -> mugen_core::error::Result<()> {
+    /// let day = NaiveDate::from_ymd_opt(2023, 12, 9).unwrap();
+    /// let synthetic = Synthetic::default();
+    /// let ground_truth = process_truth(synthetic.truth(day..=day)?, Zone::NewYorkCity)?;
+    /// let predictions = process_pred(synthetic.forecast(day..=day)?, Zone::NewYorkCity)?;
+    /// let line_graph = LineGraph::builder()
+    ///     .truth(ground_truth)
+    ///     .forecast(predictions)
+    ///     .title("Ground Truth VS Predictions for NYC")
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn builder() -> LineGraphBuilder {
+        LineGraphBuilder::default()
+    }
+
+    /// Returns a copy of the graph whose x-axis keeps at most `max_points` points.
+    ///
+    /// The shared time axis is split into buckets and only the times of the minimum
+    /// and maximum of each series in every bucket are kept, for all series alike,
+    /// which preserves peaks and troughs and the gaps of missing data while keeping
+    /// large renders fast.
+    fn downsampled(&self, max_points: usize) -> Result<LineGraph> {
+        let mut series: Vec<&TimeSeriesDataset> = vec![&self.data, &self.forecast];
+        series.extend(&self.price);
+        let buckets = (max_points / (2 * series.len())).max(1);
+        let mut downsampled = min_max_downsample_aligned(&series, buckets)?.into_iter();
+        let mut graph = self.clone();
+        graph.data = downsampled.next().unwrap_or_default();
+        graph.forecast = downsampled.next().unwrap_or_default();
+        graph.price = downsampled.next();
+        graph.max_points = None;
+        Ok(graph)
+    }
+
+    /// Fills the title template with values taken from the data.
+    ///
+    /// Supported placeholders are `{region}`, `{start_date}` and `{end_date}` (the
+    /// span of the x-axis), and `{peak_load}` (the highest actual load).
+    fn title(&self) -> Result<String> {
+        let timestamps = self.timestamps()?;
+        let peak_load = self
+            .data
+            .values(self.data.value_column())?
+            .max()
+            .unwrap_or(f64::NAN);
+        Ok(fill_title(
+            &self.config.title,
+            &[
+                ("region", self.zone.to_string()),
+                ("start_date", date_label(timestamps.first())),
+                ("end_date", date_label(timestamps.last())),
+                ("peak_load", format!("{:.0}", peak_load)),
+            ],
+        ))
+    }
+
+    /// Returns the sorted union of the actual and forecast timestamps, which is the
+    /// category x-axis of the chart.
+    fn timestamps(&self) -> Result<Vec<i64>> {
+        let mut timestamps = self.data.unique_timestamps()?;
+        timestamps.extend(self.forecast.unique_timestamps()?);
+        timestamps.sort_unstable();
+        timestamps.dedup();
+        Ok(timestamps)
+    }
+
+    /// Builds the forecast as a step line: a hidden time-valued x-axis in the
+    /// second grid and a series that holds each forecast value until the next one.
+    ///
+    /// charming has no `step` option, so every value is emitted twice, at its own
+    /// timestamp and at the next, which draws the flat runs and vertical jumps. The
+    /// axis range is padded by half a category so points line up with the centers
+    /// of the visible category axis.
+    fn step_forecast_parts(&self) -> Result<(Axis, Line)> {
+        let timestamps = self.timestamps()?;
+        let spacing = match (timestamps.first(), timestamps.last()) {
+            (Some(first), Some(last)) if timestamps.len() > 1 => {
+                (last - first) as f64 / (timestamps.len() - 1) as f64
+            }
+            _ => 0.0,
+        };
+
+        let forecast = self.forecast.sorted()?;
+        let points: Vec<(f64, Option<f64>)> = forecast
+            .timestamps()?
+            .into_iter()
+            .zip(&forecast.values(forecast.value_column())?)
+            .filter_map(|(dt, value)| Some((dt? as f64, value)))
+            .collect();
+
+        // A missing value breaks the line instead of stretching the previous step
+        // across the outage.
+        let mut steps: Vec<CompositeValue> = Vec::with_capacity(points.len() * 2);
+        for (i, &(dt, value)) in points.iter().enumerate() {
+            let Some(value) = value else {
+                steps.push(CompositeValue::from("-"));
+                continue;
+            };
+            let until = points.get(i + 1).map_or(dt + spacing, |next| next.0);
+            steps.push(CompositeValue::from(vec![dt, value]));
+            steps.push(CompositeValue::from(vec![until, value]));
+        }
+
+        let x_axis = Axis::new()
+            .show(false)
+            .grid_index(1)
+            .type_(AxisType::Value)
+            .min(*timestamps.first().unwrap_or(&0) as f64 - spacing / 2.0)
+            .max(*timestamps.last().unwrap_or(&0) as f64 + spacing / 2.0);
+        let series = Line::new()
+            .name(self.config.labels.forecast.as_str())
+            .x_axis_index(1)
+            .y_axis_index(1)
+            .show_symbol(false)
+            .line_style(
+                LineStyle::new()
+                    .width(self.config.line(self.line_thickness as f64))
+                    .color(self.forecast_color.as_str()),
+            )
+            .data(steps);
+        Ok((x_axis, series))
+    }
+
+    /// Builds the residual panel: an x-axis sharing the main chart's categories, a
+    /// y-axis for the residual, and a bar series of actual minus forecast, all in the
+    /// third grid.
+    fn residual_panel_parts(&self) -> Result<(Axis, Axis, Bar)> {
+        let timestamps = self.timestamps()?;
+        let joined = join_actual_forecast(&self.data, &self.forecast)?;
+        let residuals = joined.values_by_timestamp("Residual")?;
+
+        let x_axis = Axis::new()
+            .grid_index(2)
+            .type_(AxisType::Category)
+            .axis_label(AxisLabel::new().show(false))
+            .data(
+                timestamps
+                    .iter()
+                    .map(|dt| category_label(*dt, &self.config.time_format))
+                    .collect::<Result<Vec<String>>>()?,
+            );
+        let y_axis = Axis::new()
+            .grid_index(2)
+            .name(self.config.labels.residual.as_str())
+            .name_gap(self.config.px(35.0))
+            .axis_label(value_axis_label(&self.config))
+            .name_text_style(TextStyle::new().font_size(self.config.font(40.0)));
+        let series = Bar::new()
+            .name(self.config.labels.residual.as_str())
+            .x_axis_index(2)
+            .y_axis_index(2)
+            .item_style(ItemStyle::new().color("#fac858"))
+            .data(align_to_axis(&timestamps, &residuals));
+        Ok((x_axis, y_axis, series))
+    }
+
+    /// Builds the price overlay: a y-axis at the right of the main grid and a line of
+    /// the price aligned to the chart's x-axis, drawn against the y-axis at
+    /// `axis_index`.
+    fn price_parts(&self, price: &TimeSeriesDataset, axis_index: usize) -> Result<(Axis, Line)> {
+        let y_axis = Axis::new()
+            .name(self.config.labels.price.as_str())
+            .name_gap(self.config.px(35.0))
+            .position("right")
+            .axis_label(
+                AxisLabel::new()
+                    .font_size(self.config.font(30.0))
+                    .formatter(self.config.labels.price_value.as_str()),
+            )
+            .name_text_style(TextStyle::new().font_size(self.config.font(60.0)));
+        let series = Line::new()
+            .name(self.config.labels.price.as_str())
+            .y_axis_index(axis_index as f64)
+            .show_symbol(false)
+            .line_style(
+                LineStyle::new()
+                    .width(self.config.line(self.line_thickness as f64 / 2.0))
+                    .color("#9a60b4"),
+            )
+            .data(align_to_axis(
+                &self.timestamps()?,
+                &price.values_by_timestamp(price.value_column())?,
+            ));
+        Ok((y_axis, series))
+    }
+
+    /// Builds an empty series that carries the midnight markers and alternating day
+    /// shading as mark lines and mark areas on the chart's x-axis.
+    fn day_boundary_series(&self) -> Result<Line> {
+        let timestamps = self.timestamps()?;
+        let mut days: Vec<(NaiveDate, String, String)> = Vec::new();
+        for dt in &timestamps {
+            let date = naive_datetime(*dt)?.date();
+            let label = category_label(*dt, &self.config.time_format)?;
+            match days.last_mut() {
+                Some((day, _, last)) if *day == date => *last = label,
+                _ => days.push((date, label.clone(), label)),
+            }
+        }
+
+        let mut series = Line::new()
+            .show_symbol(false)
+            .data(vec!["-"; timestamps.len()]);
+
+        if self.day_markers {
+            series = series.mark_line(
+                MarkLine::new()
+                    .symbol(vec![Symbol::None, Symbol::None])
+                    .label(Label::new().show(false))
+                    .line_style(
+                        LineStyle::new()
+                            .color("rgba(255, 255, 255, 0.4)")
+                            .width(self.config.line(2.0))
+                            .type_(LineStyleType::Dashed),
+                    )
+                    .data(
+                        days.iter()
+                            .skip(1)
+                            .map(|(_, first, _)| {
+                                MarkLineVariant::Simple(MarkLineData::new().x_axis(first.as_str()))
+                            })
+                            .collect(),
+                    ),
+            );
+        }
+
+        if self.day_shading {
+            series = series.mark_area(
+                MarkArea::new()
+                    .silent(true)
+                    .item_style(ItemStyle::new().color("rgba(255, 255, 255, 0.05)"))
+                    .data(
+                        days.iter()
+                            .skip(1)
+                            .step_by(2)
+                            .map(|(_, first, last)| {
+                                (
+                                    MarkAreaData::new().x_axis(first.as_str()),
+                                    MarkAreaData::new().x_axis(last.as_str()),
+                                )
+                            })
+                            .collect(),
+                    ),
+            );
+        }
+
+        Ok(series)
+    }
+
+    /// Builds the dashed moving-average series of the actual load, aligned to the
+    /// chart's x-axis.
+    fn moving_average_series(&self, window: usize) -> Result<Line> {
+        let sorted = self.data.sorted()?;
+        let smoothed =
+            moving_average(&sorted.values(sorted.value_column())?.into_series(), window)?;
+        let averages: HashMap<i64, f64> = sorted
+            .timestamps()?
+            .into_iter()
+            .zip(smoothed.f64()?)
+            .filter_map(|(dt, value)| Some((dt?, value?)))
+            .collect();
+
+        Ok(Line::new()
+            .name(fill_title(
+                &self.config.labels.moving_average,
+                &[("window", window.to_string())],
+            ))
+            .show_symbol(false)
+            .line_style(
+                LineStyle::new()
+                    .width(self.config.line(self.line_thickness as f64))
+                    .type_(LineStyleType::Dashed),
+            )
+            .data(align_to_axis(&self.timestamps()?, &averages)))
+    }
+
+    /// Builds the translucent fill between the actual and forecast lines.
+    ///
+    /// The band is three series stacked on the main axis: an invisible base at the
+    /// lower of the two values, then the over-forecast gap (actual above forecast)
+    /// and the under-forecast gap, each in its own color so the sign of a miss is
+    /// visible. Timestamps without both an actual and a forecast are left empty.
+    fn error_band_series(&self) -> Result<Vec<Line>> {
+        let actuals = self.data.values_by_timestamp(self.data.value_column())?;
+        let forecasts = self
+            .forecast
+            .values_by_timestamp(self.forecast.value_column())?;
+        let timestamps = self.timestamps()?;
+
+        let mut base = Vec::with_capacity(timestamps.len());
+        let mut over = Vec::with_capacity(timestamps.len());
+        let mut under = Vec::with_capacity(timestamps.len());
+        for dt in &timestamps {
+            match (actuals.get(dt), forecasts.get(dt)) {
+                (Some(&actual), Some(&forecast)) => {
+                    base.push(CompositeValue::from(actual.min(forecast)));
+                    over.push(CompositeValue::from((actual - forecast).max(0.0)));
+                    under.push(CompositeValue::from((forecast - actual).max(0.0)));
+                }
+                _ => {
+                    base.push(CompositeValue::from("-"));
+                    over.push(CompositeValue::from("-"));
+                    under.push(CompositeValue::from("-"));
+                }
+            }
+        }
+
+        let band = |data: Vec<CompositeValue>, color: Option<&str>| {
+            let line = Line::new()
+                .stack("error band")
+                .show_symbol(false)
+                .line_style(LineStyle::new().opacity(0))
+                .data(data);
+            match color {
+                Some(color) => line.area_style(AreaStyle::new().color(color).opacity(0.3)),
+                None => line,
+            }
+        };
+
+        Ok(vec![
+            band(base, None),
+            band(over, Some("#ee6666")),
+            band(under, Some("#5470c6")),
+        ])
+    }
+
+    /// Builds the shaded prediction interval: an invisible base at the lower bound
+    /// with the interval's width stacked on it, in the forecast color.
+    fn interval_band_series(&self, interval: &TimeSeriesDataset) -> Result<Vec<Line>> {
+        let lower = interval.values_by_timestamp("Lower")?;
+        let upper = interval.values_by_timestamp("Upper")?;
+        let (base, width): (Vec<CompositeValue>, Vec<CompositeValue>) = self
+            .timestamps()?
+            .iter()
+            .map(|dt| match (lower.get(dt), upper.get(dt)) {
+                (Some(&lower), Some(&upper)) => (
+                    CompositeValue::from(lower),
+                    CompositeValue::from(upper - lower),
+                ),
+                _ => (CompositeValue::from("-"), CompositeValue::from("-")),
+            })
+            .unzip();
+
+        let band = |data: Vec<CompositeValue>| {
+            Line::new()
+                .stack("prediction interval")
+                .show_symbol(false)
+                .line_style(LineStyle::new().opacity(0))
+                .data(data)
+        };
+        Ok(vec![
+            band(base),
+            band(width).area_style(
+                AreaStyle::new()
+                    .color(self.forecast_color.as_str())
+                    .opacity(0.2),
+            ),
+        ])
+    }
+}
+
+/// Fluent builder for [`LineGraph`].
+#[derive(Default)]
+pub struct LineGraphBuilder {
+    graph: LineGraph,
+}
+
+impl LineGraphBuilder {
+    /// Sets the processed ground truth.
+    pub fn truth(mut self, data: TimeSeriesDataset) -> Self {
+        self.graph.data = data;
+        self
+    }
+
+    /// Sets the processed forecast.
+    pub fn forecast(mut self, forecast: TimeSeriesDataset) -> Self {
+        self.graph.forecast = forecast;
+        self
+    }
+
+    /// Sets the title; see [`GraphConfig::title`] for placeholders.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.graph.config.title = title.into();
+        self
+    }
+
+    /// Sets the name of the x-axis.
+    pub fn x_label(mut self, x_label: impl Into<String>) -> Self {
+        self.graph.config.x_label = x_label.into();
+        self
+    }
+
+    /// Sets the name of the value axis.
+    pub fn y_label(mut self, y_label: impl Into<String>) -> Self {
+        self.graph.config.y_label = y_label.into();
+        self
+    }
+
+    /// Stamps `watermark` onto the rendered chart.
+    pub fn watermark(mut self, watermark: Watermark) -> Self {
+        self.graph.config.watermark = Some(watermark);
+        self
+    }
+
+    /// Shades the gap between actual and forecast.
+    pub fn error_band(mut self, error_band: bool) -> Self {
+        self.graph.error_band = error_band;
+        self
+    }
+
+    /// Overlays a dashed moving average of the actual load over `window` points.
+    pub fn moving_average(mut self, window: usize) -> Self {
+        self.graph.moving_average = Some(window);
+        self
+    }
+
+    /// Draws a vertical marker at every midnight on the x-axis.
+    pub fn day_markers(mut self, day_markers: bool) -> Self {
+        self.graph.day_markers = day_markers;
+        self
+    }
+
+    /// Shades every other day so multi-day charts are easy to read at a glance.
+    pub fn day_shading(mut self, day_shading: bool) -> Self {
+        self.graph.day_shading = day_shading;
+        self
+    }
+
+    /// Adds a labeled horizontal reference line at `value`.
+    pub fn threshold(
+        mut self,
+        label: impl Into<String>,
+        value: f64,
+        color: impl Into<String>,
+    ) -> Self {
+        self.graph.config.thresholds.push(Threshold {
+            label: label.into(),
+            value,
+            color: color.into(),
+        });
+        self
+    }
+
+    /// Caps the number of points drawn per series, see [`LineGraph::downsampled`].
+    pub fn max_points(mut self, max_points: usize) -> Self {
+        self.graph.max_points = Some(max_points);
+        self
+    }
+
+    /// Replaces the shared chart settings; call before the setters that refine them.
+    pub fn config(mut self, config: GraphConfig) -> Self {
+        self.graph.config = config;
+        self
+    }
+
+    /// Sizes the chart, its fonts and its lines for `resolution`.
+    pub fn resolution(mut self, resolution: Resolution) -> Self {
+        (
+            self.graph.config.graph_width,
+            self.graph.config.graph_height,
+        ) = resolution.size();
+        self
+    }
+
+    /// Sets the directory the chart is saved into.
+    pub fn output_dir(mut self, output_dir: impl Into<String>) -> Self {
+        self.graph.config.output_dir = output_dir.into();
+        self
+    }
+
+    /// Replaces the fixed text drawn on the chart, such as legend names.
+    pub fn labels(mut self, labels: Labels) -> Self {
+        self.graph.config.labels = labels;
+        self
+    }
+
+    /// Saves the chart as `filename`, with "_LineChart" appended to its stem, under
+    /// the output directory instead of a generated name.
+    pub fn filename(mut self, filename: impl Into<String>) -> Self {
+        self.graph.config.filename = Some(filename.into());
+        self
+    }
+
+    /// Sets what happens if the chart's file already exists.
+    pub fn on_collision(mut self, on_collision: Collision) -> Self {
+        self.graph.config.on_collision = on_collision;
+        self
+    }
+
+    /// Saves the chart under a fixed filename and disables animation in its spec.
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.graph.config.deterministic = deterministic;
+        self
+    }
+
+    /// Sets the `chrono` format string used for x-axis timestamps.
+    pub fn time_format(mut self, time_format: impl Into<String>) -> Self {
+        self.graph.config.time_format = time_format.into();
+        self
+    }
+
+    /// Shows one x-axis label every `label_every` points.
+    pub fn label_every(mut self, label_every: usize) -> Self {
+        self.graph.config.label_every = Some(label_every);
+        self
+    }
+
+    /// Draws the forecast as a step line, since hourly forecasts are piecewise constant.
+    pub fn step_forecast(mut self, step_forecast: bool) -> Self {
+        self.graph.step_forecast = step_forecast;
+        self
+    }
+
+    /// Adds a smaller panel of residuals (actual minus forecast) below the main chart.
+    pub fn residual_panel(mut self, residual_panel: bool) -> Self {
+        self.graph.residual_panel = residual_panel;
+        self
+    }
+
+    /// Sets the zone named in the title.
+    pub fn zone(mut self, zone: Zone) -> Self {
+        self.graph.zone = zone;
+        self
+    }
+
+    /// Overlays the zonal price on a secondary axis, e.g. to see whether forecast
+    /// misses line up with price spikes.
+    pub fn price(mut self, price: TimeSeriesDataset) -> Self {
+        self.graph.price = Some(price);
+        self
+    }
+
+    /// Shades a prediction interval around the forecast, e.g. from
+    /// `ErrorQuantiles::interval`.
+    pub fn interval(mut self, interval: TimeSeriesDataset) -> Self {
+        self.graph.interval = Some(interval);
+        self
+    }
+
+    /// Validates the collected settings and returns the finished `LineGraph`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InvalidArgument` if the ground truth or forecast was not
+    /// set or has no rows, lacks its declared time or value columns, or if the two
+    /// are measured in different units, since they share one value axis.
+    pub fn build(self) -> Result<LineGraph> {
+        let (truth, forecast) = (&self.graph.data, &self.graph.forecast);
+        for (dataset, setter) in [(truth, "truth"), (forecast, "forecast")] {
+            require_rows(dataset, setter)?;
+        }
+        if truth.unit() != forecast.unit() {
+            return Err(MugenError::InvalidArgument(format!(
+                "{} is in \"{}\" but {} is in \"{}\"",
+                truth.name(),
+                truth.unit(),
+                forecast.name(),
+                forecast.unit()
+            )));
+        }
+        Ok(self.graph)
+    }
+}
+
+/// Checks that `dataset`, set with the builder method `setter`, has its declared
+/// time and value columns and at least one row.
+fn require_rows(dataset: &TimeSeriesDataset, setter: &str) -> Result<()> {
+    let frame: &DataFrame = dataset.frame();
+    let declared = std::iter::once(dataset.time_column())
+        .chain(dataset.value_columns().iter().map(String::as_str));
+    for column in declared {
+        if frame.column(column).is_err() {
+            return Err(MugenError::InvalidArgument(format!(
+                "{} lacks its column \"{}\"",
+                dataset.name(),
+                column
+            )));
+        }
+    }
+    if dataset.height() == 0 {
+        return Err(MugenError::InvalidArgument(format!(
+            "{} has no rows; set it with LineGraphBuilder::{}",
+            dataset.name(),
+            setter
+        )));
+    }
+    Ok(())
+}
+
+/// Applies the style in `config` to a built chart and adds the text watermark, if
+/// any. Every chart passes through here last.
+fn finish_chart(chart: Chart, config: &GraphConfig) -> Chart {
+    let style = &config.style;
+    let mut chart = chart;
+    if !style.palette.is_empty() {
+        chart = chart.color(
+            style
+                .palette
+                .iter()
+                .map(|color| Color::from(color.as_str()))
+                .collect(),
+        );
+    }
+    if let Some(background) = &style.background {
+        chart = chart.background_color(background.as_str());
+    }
+    stamp_text_watermark(chart, config)
+}
+
+/// Adds the text watermark in `config`, if any, to `chart` as a translucent title.
+fn stamp_text_watermark(chart: Chart, config: &GraphConfig) -> Chart {
+    match &config.watermark {
+        Some(Watermark::Text(text)) => chart.title(
+            Title::new()
+                .text(text.as_str())
+                .text_style(TextStyle::new().font_size(config.font(80.0)).color(
+                    if config.style.dark {
+                        "rgba(255, 255, 255, 0.3)"
+                    } else {
+                        "rgba(0, 0, 0, 0.25)"
+                    },
+                ))
+                .right("3%")
+                .bottom("3%"),
+        ),
+        _ => chart,
+    }
+}
+
+/// Renders `chart` to a PNG at `filename` with the backend, size and watermark in
+/// `config`, handling an existing file as `config.on_collision` says.
+#[instrument(name = "render", skip_all, fields(path = %filename))]
+fn save_chart(chart: Chart, config: &GraphConfig, filename: String) -> Result<RenderedChart> {
+    let (series_count, point_count) = count_series_and_points(&chart);
+    let dir = Path::new(&filename)
+        .parent()
+        .unwrap_or_else(|| Path::new(&config.output_dir));
+    std::fs::create_dir_all(dir).map_err(|source| MugenError::Write {
+        path: dir.to_string_lossy().into_owned(),
+        source,
+    })?;
+    let bytes = config
+        .backend
+        .renderer()
+        .render(&chart, config, ImageFormat::Png)?;
+    let filename = write_chart(filename, &bytes, config.on_collision)?;
+
+    Ok(RenderedChart {
+        path: filename,
+        width: config.graph_width,
+        height: config.graph_height,
+        format: ImageFormat::Png,
+        series_count,
+        point_count,
+    })
+}
+
+/// Writes `bytes` to `filename`, or to the path `on_collision` picks if it exists,
+/// and returns the path written.
+///
+/// Files are created exclusively unless overwriting, so charts saved at the same
+/// moment cannot claim the same name.
+fn write_chart(filename: String, bytes: &[u8], on_collision: Collision) -> Result<String> {
+    let write_error = |path: &str, source| MugenError::Write {
+        path: path.to_string(),
+        source,
+    };
+    if on_collision == Collision::Overwrite {
+        std::fs::write(&filename, bytes).map_err(|source| write_error(&filename, source))?;
+        return Ok(filename);
+    }
+
+    let path = Path::new(&filename);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    for attempt in 0.. {
+        let candidate = match attempt {
+            0 => filename.clone(),
+            n => path
+                .with_file_name(format!("{}_{}{}", stem, n, extension))
+                .to_string_lossy()
+                .into_owned(),
+        };
+        match OpenOptions::new().write(true).create_new(true).open(&candidate) {
+            Ok(mut file) => {
+                file.write_all(bytes)
+                    .map_err(|source| write_error(&candidate, source))?;
+                return Ok(candidate);
+            }
+            Err(source)
+                if source.kind() == ErrorKind::AlreadyExists
+                    && on_collision == Collision::Increment => {}
+            Err(source) => return Err(write_error(&candidate, source)),
+        }
+    }
+    unreachable!("every counter value collided")
+}
+
+/// Counts the series in `chart` and the non-missing data points they hold.
+fn count_series_and_points(chart: &Chart) -> (usize, usize) {
+    let option: serde_json::Value = serde_json::to_value(chart).unwrap_or_default();
+    let series = match option["series"].as_array() {
+        Some(series) => series,
+        None => return (0, 0),
+    };
+    let points = series
+        .iter()
+        .filter_map(|series| series["data"].as_array())
+        .map(|data| {
+            data.iter()
+                .filter(|point| point.as_str() != Some("-"))
+                .count()
+        })
+        .sum();
+    (series.len(), points)
+}
+
+/// Builds an empty series carrying the thresholds in `config` as labeled horizontal
+/// mark lines, their values in `unit`, or `None` if there are no thresholds to draw.
+fn threshold_series(config: &GraphConfig, unit: &str) -> Option<Line> {
+    let thresholds: &[Threshold] = &config.thresholds;
+    if thresholds.is_empty() {
+        return None;
+    }
+    let label: String = fill_unit(&config.labels.threshold, unit);
+
+    let mark_lines = thresholds
+        .iter()
+        .map(|threshold| {
+            MarkLineVariant::Simple(
+                MarkLineData::new()
+                    .name(threshold.label.as_str())
+                    .y_axis(threshold.value)
+                    .label(
+                        Label::new()
+                            .show(true)
+                            .formatter(label.as_str())
+                            .color(threshold.color.as_str())
+                            .font_size(config.font(40.0)),
+                    ),
+            )
+        })
+        .collect();
+
+    // ECharts styles mark lines per series, so individual colors only reach the
+    // labels unless there is a single threshold.
+    let mut line_style = LineStyle::new()
+        .width(config.line(3.0))
+        .type_(LineStyleType::Dashed);
+    if let [threshold] = thresholds {
+        line_style = line_style.color(threshold.color.as_str());
+    }
+
+    Some(
+        Line::new().data(Vec::<f64>::new()).mark_line(
+            MarkLine::new()
+                .symbol(vec![Symbol::None, Symbol::None])
+                .line_style(line_style)
+                .data(mark_lines),
+        ),
+    )
+}
+
+/// Replaces every `{name}` placeholder in `template` with its value from `values`.
+/// Unknown placeholders are left as-is, so charts can fill in the rest.
+///
+/// # Examples
+///
+/// ```
+/// use mugen_viz::graph::fill_title;
+///
+/// let title = fill_title("{region} at {level}%", &[("level", "80".to_string())]);
+/// assert_eq!(title, "{region} at 80%");
+/// ```
+pub fn fill_title(template: &str, values: &[(&str, String)]) -> String {
+    values
+        .iter()
+        .fold(template.to_string(), |title, (name, value)| {
+            title.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+/// Fills the `{unit}` placeholder of a label, dropping it with the space before it
+/// if `unit` is empty.
+fn fill_unit(template: &str, unit: &str) -> String {
+    if unit.is_empty() {
+        return template.replace(" {unit}", "").replace("{unit}", "");
+    }
+    template.replace("{unit}", unit)
+}
+
+/// Returns the unit of the value axes, for charts whose data does not carry one.
+fn axis_unit(config: &GraphConfig) -> &str {
+    config.number_format.unit.as_deref().unwrap_or_default()
+}
+
+/// Formats a millisecond timestamp as a date for chart titles, or "?" if the chart
+/// has no data.
+fn date_label(dt: Option<&i64>) -> String {
+    dt.and_then(|dt| DateTime::from_timestamp(dt / 1000, 0))
+        .map(|dt| dt.naive_local().format("%m/%d/%Y").to_string())
+        .unwrap_or_else(|| "?".to_string())
+}
+
+/// Formats a millisecond timestamp as a category label on the chart's x-axis,
+/// using a `chrono` format string.
+fn category_label(dt: i64, format: &str) -> Result<String> {
+    Ok(naive_datetime(dt)?.format(format).to_string())
+}
+
+/// Converts a millisecond timestamp to the wall-clock time it was written as.
+fn naive_datetime(dt: i64) -> Result<NaiveDateTime> {
+    DateTime::from_timestamp_millis(dt)
+        .map(|dt| dt.naive_utc())
+        .ok_or(MugenError::Timestamp(dt))
+}
+
+/// Builds the labels of a value axis, written in `config.number_format`.
+fn value_axis_label(config: &GraphConfig) -> AxisLabel {
+    AxisLabel::new()
+        .font_size(config.font(30.0))
+        .formatter(config.number_format.axis_formatter())
+}
+
+/// Builds the rotated x-axis labels, thinned out to one label every
+/// `config.label_every` points when set.
+fn x_axis_label(config: &GraphConfig) -> AxisLabel {
+    let label = AxisLabel::new().rotate(60).font_size(config.font(30.0));
+    match config.label_every {
+        Some(every) => label.interval(every.saturating_sub(1) as f64),
+        None => label,
+    }
+}
+
+/// Maps each timestamp in `df` to the value of `column` there, cast to `f64`.
+/// Rows where the timestamp or the value is null are skipped, so they show up as
+/// gaps once aligned to an axis. `frame` names `df` in errors.
+fn values_by_timestamp(df: &DataFrame, frame: &str, column: &str) -> Result<HashMap<i64, f64>> {
+    Ok(timestamp_column(df, frame)?
+        .into_iter()
+        .zip(&f64_column(df, frame, column)?)
+        .filter_map(|(dt, value)| Some((dt?, value?)))
+        .collect())
+}
+
+/// Maps each timestamp to the load of `zone` in long-format ground truth.
+fn zone_loads(data: &DataFrame, zone: Zone) -> Result<HashMap<i64, f64>> {
+    let names = str_column(data, "ground truth", "Name")?;
+    let zone_data = data.filter(&names.equal(zone.truth_name()))?;
+    values_by_timestamp(&zone_data, "ground truth", "Integrated Load")
+}
+
+/// Returns the sorted, distinct timestamps in `data`, naming `frame` in errors.
+fn unique_timestamps(data: &DataFrame, frame: &str) -> Result<Vec<i64>> {
+    let mut timestamps = timestamp_column(data, frame)?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<i64>>();
+    timestamps.sort_unstable();
+    timestamps.dedup();
+    Ok(timestamps)
+}
+
+/// Returns the chart labels of `zones`, in order.
+fn zone_labels(zones: &[Zone]) -> Vec<String> {
+    zones.iter().map(Zone::to_string).collect()
+}
+
+/// Lays `values` out along a category axis of `timestamps`, leaving "-" (an empty
+/// point in ECharts) wherever a timestamp has no value.
+fn align_to_axis(timestamps: &[i64], values: &HashMap<i64, f64>) -> Vec<CompositeValue> {
+    timestamps
+        .iter()
+        .map(|dt| match values.get(dt) {
+            Some(value) => CompositeValue::from(*value),
+            None => CompositeValue::from("-"),
+        })
+        .collect()
+}
+
+impl Graph for LineGraph {
+    fn config(&self) -> &GraphConfig {
+        &self.config
+    }
+
+    fn chart(&self) -> Result<Chart> {
+        if let Some(max_points) = self.max_points {
+            if self.timestamps()?.len() > max_points {
+                return self.downsampled(max_points)?.chart();
+            }
+        }
+
+        // Both series are laid out on the union of their timestamps, so a missing
+        // or null value leaves a gap rather than pulling later points to the left.
+        let timestamps = self.timestamps()?;
+        let x_axis_data = timestamps
+            .iter()
+            .map(|dt| category_label(*dt, &self.config.time_format))
+            .collect::<Result<Vec<String>>>()?;
+
+        let loads = self.data.values(self.data.value_column())?;
+        let (Some(min_y), Some(max_y)) = (loads.min(), loads.max()) else {
+            return Err(MugenError::NoData(
+                "ground truth has no load values to plot".to_string(),
+            ));
+        };
+
+        // Keep the prediction interval inside the visible range.
+        let (min_y, max_y) = match &self.interval {
+            Some(interval) => (
+                min_y.min(interval.values("Lower")?.min().unwrap_or(min_y)),
+                max_y.max(interval.values("Upper")?.max().unwrap_or(max_y)),
+            ),
+            None => (min_y, max_y),
+        };
+
+        // Keep every threshold line inside the visible range.
+        let (min_y, max_y) = self
+            .config
+            .thresholds
+            .iter()
+            .fold((min_y, max_y), |(min, max), threshold| {
+                (min.min(threshold.value), max.max(threshold.value))
+            });
+
+        let mut legend: Vec<&str> = vec![
+            self.config.labels.actual.as_str(),
+            self.config.labels.forecast.as_str(),
+        ];
+        if self.price.is_some() {
+            legend.push(self.config.labels.price.as_str());
+        }
+
+        // With the residual panel, the main chart gives up the bottom quarter.
+        let main_bottom = if self.residual_panel { "30%" } else { "3%" };
+
+        let mut chart = Chart::new()
+            .title(
+                Title::new()
+                    .text(self.title()?)
+                    .text_style(TextStyle::new().font_size(self.config.font(100.0)))
+                    .left("center"),
+            )
+            .grid(self.config.style.grid().bottom(main_bottom).top("5%"))
+            .grid(self.config.style.grid().bottom(main_bottom).top("5%"))
+            .x_axis(
+                Axis::new()
+                    .name(self.config.x_label.as_str())
+                    .axis_label(x_axis_label(&self.config))
+                    .name_text_style(TextStyle::new().font_size(self.config.font(60.0)))
+                    .type_(AxisType::Category)
+                    .data(x_axis_data.clone()),
+            )
+            .y_axis(
+                Axis::new()
+                    .name(self.config.y_label.as_str())
+                    .name_gap(self.config.px(35.0))
+                    .axis_label(value_axis_label(&self.config))
+                    .name_text_style(TextStyle::new().font_size(self.config.font(60.0)))
+                    .min((min_y / 100.0).floor() * 100.0)
+                    .max((max_y / 100.0).ceil() * 100.0),
+            )
+            .series(
+                Line::new()
+                    .name(self.config.labels.actual.as_str())
+                    .line_style(
+                        LineStyle::new().width(self.config.line(self.line_thickness as f64)),
+                    )
+                    .data(align_to_axis(
+                        &timestamps,
+                        &self.data.values_by_timestamp(self.data.value_column())?,
+                    )),
+            )
+            .y_axis(
+                Axis::new()
+                    .show(false)
+                    .grid_index(1)
+                    .min((min_y / 100.0).floor() * 100.0)
+                    .max((max_y / 100.0).ceil() * 100.0),
+            )
+            .legend(
+                Legend::new()
+                    .left(self.config.px(50.0))
+                    .top(self.config.px(50.0))
+                    .data(legend),
+            );
+
+        chart = if self.step_forecast {
+            let (x_axis, series) = self.step_forecast_parts()?;
+            chart.x_axis(x_axis).series(series)
+        } else {
+            chart
+                .x_axis(Axis::new().show(false).grid_index(1).data(x_axis_data))
+                .series(
+                    Line::new()
+                        .name(self.config.labels.forecast.as_str())
+                        .line_style(
+                            LineStyle::new()
+                                .width(self.config.line(self.line_thickness as f64))
+                                .color(self.forecast_color.as_str()),
+                        )
+                        .data(align_to_axis(
+                            &timestamps,
+                            &self
+                                .forecast
+                                .values_by_timestamp(self.forecast.value_column())?,
+                        )),
+                )
+        };
+
+        if self.residual_panel {
+            let (x_axis, y_axis, series) = self.residual_panel_parts()?;
+            chart = chart
+                .grid(self.config.style.grid().bottom("3%").top("75%"))
+                .x_axis(x_axis)
+                .y_axis(y_axis)
+                .series(series);
+        }
+
+        if let Some(price) = &self.price {
+            // After the main, step forecast and residual panel y-axes.
+            let axis_index = if self.residual_panel { 3 } else { 2 };
+            let (y_axis, series) = self.price_parts(price, axis_index)?;
+            chart = chart.y_axis(y_axis).series(series);
+        }
+
+        if self.error_band {
+            for band in self.error_band_series()? {
+                chart = chart.series(band);
+            }
+        }
+
+        if let Some(interval) = &self.interval {
+            for band in self.interval_band_series(interval)? {
+                chart = chart.series(band);
+            }
+        }
+
+        if let Some(window) = self.moving_average {
+            chart = chart.series(self.moving_average_series(window)?);
+        }
+
+        if self.day_markers || self.day_shading {
+            chart = chart.series(self.day_boundary_series()?);
+        }
+
+        if let Some(thresholds) = threshold_series(&self.config, self.data.unit()) {
+            chart = chart.series(thresholds);
+        }
+
+        Ok(finish_chart(chart, &self.config))
+    }
+
+    fn draw(&self) -> Result<RenderedChart> {
+        let filename = self.generate_filename("LineChart");
+        save_chart(self.chart()?, &self.config, filename)
+    }
+}
+
+/// Actual loads of several zones drawn as one line each on a shared time axis.
+///
+/// `data` is long-format ground truth as returned by `process_truth_regions`.
+/// Zones are drawn in the order of `zones`; `colors[i]` styles `zones[i]`, and zones
+/// without a color fall back to the theme palette.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlayGraph {
+    /// Shared chart settings.
+    pub config: GraphConfig,
+    /// The frame described above.
+    #[serde(skip)]
+    pub data: DataFrame,
+    /// Zones to draw, in order.
+    pub zones: Vec<Zone>,
+    /// Line colors, matched to `zones` by position.
+    pub colors: Vec<String>,
+    /// Line width before resolution scaling.
+    pub line_thickness: u32,
+}
+
+impl Default for OverlayGraph {
+    fn default() -> Self {
+        OverlayGraph {
+            config: GraphConfig::default(),
+            data: DataFrame::default(),
+            zones: Vec::new(),
+            colors: Vec::new(),
+            line_thickness: 5,
+        }
+    }
+}
+
+impl Graph for OverlayGraph {
+    fn config(&self) -> &GraphConfig {
+        &self.config
+    }
+
+    fn chart(&self) -> Result<Chart> {
+        let timestamps = unique_timestamps(&self.data, "ground truth")?;
+
+        let x_axis_data = timestamps
+            .iter()
+            .map(|dt| category_label(*dt, &self.config.time_format))
+            .collect::<Result<Vec<String>>>()?;
+
+        let mut chart = Chart::new()
+            .title(
+                Title::new()
+                    .text(fill_title(
+                        &self.config.title,
+                        &[
+                            ("region", zone_labels(&self.zones).join(", ")),
+                            ("start_date", date_label(timestamps.first())),
+                            ("end_date", date_label(timestamps.last())),
+                        ],
+                    ))
+                    .text_style(TextStyle::new().font_size(self.config.font(100.0)))
+                    .left("center"),
+            )
+            .grid(self.config.style.grid().bottom("3%").top("8%"))
+            .legend(
+                Legend::new()
+                    .left(self.config.px(50.0))
+                    .top(self.config.px(50.0))
+                    .text_style(TextStyle::new().font_size(self.config.font(40.0)))
+                    .data(zone_labels(&self.zones)),
+            )
+            .x_axis(
+                Axis::new()
+                    .name(self.config.x_label.as_str())
+                    .axis_label(x_axis_label(&self.config))
+                    .name_text_style(TextStyle::new().font_size(self.config.font(60.0)))
+                    .type_(AxisType::Category)
+                    .data(x_axis_data),
+            )
+            .y_axis(
+                Axis::new()
+                    .name(self.config.y_label.as_str())
+                    .name_gap(self.config.px(35.0))
+                    .axis_label(value_axis_label(&self.config))
+                    .name_text_style(TextStyle::new().font_size(self.config.font(60.0))),
+            );
+
+        for (i, &zone) in self.zones.iter().enumerate() {
+            let loads = zone_loads(&self.data, zone)?;
+
+            let mut line_style =
+                LineStyle::new().width(self.config.line(self.line_thickness as f64));
+            if let Some(color) = self.colors.get(i) {
+                line_style = line_style.color(color.as_str());
+            }
+
+            chart = chart.series(
+                Line::new()
+                    .name(zone.truth_name())
+                    .show_symbol(false)
+                    .line_style(line_style)
+                    .data(align_to_axis(&timestamps, &loads)),
+            );
+        }
+
+        if let Some(thresholds) = threshold_series(&self.config, axis_unit(&self.config)) {
+            chart = chart.series(thresholds);
+        }
+
+        Ok(finish_chart(chart, &self.config))
+    }
+
+    fn draw(&self) -> Result<RenderedChart> {
+        let filename = self.generate_filename("OverlayChart");
+        save_chart(self.chart()?, &self.config, filename)
+    }
+}
+
+/// One issue of the forecast, drawn as its own line in a [`VintageGraph`] or
+/// [`ForecastDiffGraph`].
+#[derive(Clone, Default)]
+pub struct Vintage {
+    /// Legend label identifying when the forecast was issued.
+    pub issued: String,
+    /// Processed prediction, as returned by `process_pred`.
+    pub forecast: TimeSeriesDataset,
+}
+
+/// Several forecast vintages for the same target period drawn against the actuals,
+/// showing how the forecast converged as the day approached.
+///
+/// `data` is processed ground truth as returned by `process_truth`. `vintages` are
+/// drawn oldest first, with older issues fainter.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VintageGraph {
+    /// Shared chart settings.
+    pub config: GraphConfig,
+    /// The dataset described above.
+    #[serde(skip)]
+    pub data: TimeSeriesDataset,
+    /// Forecast issues to overlay, oldest first.
+    #[serde(skip)]
+    pub vintages: Vec<Vintage>,
+    /// Zone shown, used for the `{region}` title placeholder.
+    pub zone: Zone,
+}
+
+impl Graph for VintageGraph {
+    fn config(&self) -> &GraphConfig {
+        &self.config
+    }
+
+    fn chart(&self) -> Result<Chart> {
+        let mut timestamps = self.data.unique_timestamps()?;
+        for vintage in &self.vintages {
+            timestamps.extend(vintage.forecast.unique_timestamps()?);
+        }
+        timestamps.sort();
+        timestamps.dedup();
+
+        let mut legend = vec![self.config.labels.actual.clone()];
+        legend.extend(self.vintages.iter().map(|vintage| vintage.issued.clone()));
+
+        let mut chart = Chart::new()
+            .title(
+                Title::new()
+                    .text(fill_title(
+                        &self.config.title,
+                        &[
+                            ("region", self.zone.to_string()),
+                            ("start_date", date_label(timestamps.first())),
+                            ("end_date", date_label(timestamps.last())),
+                        ],
+                    ))
+                    .text_style(TextStyle::new().font_size(self.config.font(100.0)))
+                    .left("center"),
+            )
+            .grid(self.config.style.grid().bottom("3%").top("8%"))
+            .legend(
+                Legend::new()
+                    .left(self.config.px(50.0))
+                    .top(self.config.px(50.0))
+                    .orient(Orient::Vertical)
+                    .text_style(TextStyle::new().font_size(self.config.font(40.0)))
+                    .data(legend),
+            )
+            .x_axis(
+                Axis::new()
+                    .name(self.config.x_label.as_str())
+                    .axis_label(x_axis_label(&self.config))
+                    .name_text_style(TextStyle::new().font_size(self.config.font(60.0)))
+                    .type_(AxisType::Category)
+                    .data(
+                        timestamps
+                            .iter()
+                            .map(|dt| category_label(*dt, &self.config.time_format))
+                            .collect::<Result<Vec<String>>>()?,
+                    ),
+            )
+            .y_axis(
+                Axis::new()
+                    .name(self.config.y_label.as_str())
+                    .name_gap(self.config.px(35.0))
+                    .scale(true)
+                    .axis_label(value_axis_label(&self.config))
+                    .name_text_style(TextStyle::new().font_size(self.config.font(60.0))),
+            )
+            .series(
+                Line::new()
+                    .name(self.config.labels.actual.as_str())
+                    .show_symbol(false)
+                    .line_style(LineStyle::new().width(self.config.line(6.0)))
+                    .data(align_to_axis(
+                        &timestamps,
+                        &self.data.values_by_timestamp(self.data.value_column())?,
+                    )),
+            );
+
+        let count = self.vintages.len();
+        for (i, vintage) in self.vintages.iter().enumerate() {
+            // Oldest issue at 35% opacity, rising to fully opaque for the latest.
+            let opacity = 0.35 + 0.65 * (i + 1) as f64 / count as f64;
+            chart = chart.series(
+                Line::new()
+                    .name(vintage.issued.as_str())
+                    .show_symbol(false)
+                    .line_style(
+                        LineStyle::new()
+                            .width(self.config.line(3.0))
+                            .type_(LineStyleType::Dashed)
+                            .opacity(opacity),
+                    )
+                    .data(align_to_axis(
+                        &timestamps,
+                        &vintage
+                            .forecast
+                            .values_by_timestamp(vintage.forecast.value_column())?,
+                    )),
+            );
+        }
+
+        if let Some(thresholds) = threshold_series(&self.config, self.data.unit()) {
+            chart = chart.series(thresholds);
+        }
+
+        Ok(finish_chart(chart, &self.config))
+    }
+
+    fn draw(&self) -> Result<RenderedChart> {
+        let filename = self.generate_filename("VintageChart");
+        save_chart(self.chart()?, &self.config, filename)
+    }
+}
+
+/// Two forecasts of one zone drawn over each other, with the second minus the first
+/// at each hour as bars against an axis on the right, showing where and by how much
+/// a revised forecast moved.
+///
+/// Both are processed predictions as returned by `process_pred`; bars are drawn
+/// only at hours both forecasts cover.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForecastDiffGraph {
+    /// Shared chart settings.
+    pub config: GraphConfig,
+    /// The forecast compared against, drawn solid.
+    #[serde(skip)]
+    pub first: Vintage,
+    /// The forecast compared, drawn dashed.
+    #[serde(skip)]
+    pub second: Vintage,
+    /// Zone shown, used for the `{region}` title placeholder.
+    pub zone: Zone,
+}
+
+impl Graph for ForecastDiffGraph {
+    fn config(&self) -> &GraphConfig {
+        &self.config
+    }
+
+    fn chart(&self) -> Result<Chart> {
+        let mut timestamps = self.first.forecast.unique_timestamps()?;
+        timestamps.extend(self.second.forecast.unique_timestamps()?);
+        timestamps.sort_unstable();
+        timestamps.dedup();
+
+        let first = self
+            .first
+            .forecast
+            .values_by_timestamp(self.first.forecast.value_column())?;
+        let second = self
+            .second
+            .forecast
+            .values_by_timestamp(self.second.forecast.value_column())?;
+        let differences: HashMap<i64, f64> = second
+            .iter()
+            .filter_map(|(dt, value)| Some((*dt, value - first.get(dt)?)))
+            .collect();
+
+        let chart = Chart::new()
+            .title(
+                Title::new()
+                    .text(fill_title(
+                        &self.config.title,
+                        &[
+                            ("region", self.zone.to_string()),
+                            ("start_date", date_label(timestamps.first())),
+                            ("end_date", date_label(timestamps.last())),
+                        ],
+                    ))
+                    .text_style(TextStyle::new().font_size(self.config.font(100.0)))
+                    .left("center"),
+            )
+            .grid(self.config.style.grid().bottom("3%").top("8%"))
+            .legend(
+                Legend::new()
+                    .left(self.config.px(50.0))
+                    .top(self.config.px(50.0))
+                    .orient(Orient::Vertical)
+                    .text_style(TextStyle::new().font_size(self.config.font(40.0)))
+                    .data(vec![
+                        self.first.issued.as_str(),
+                        self.second.issued.as_str(),
+                        self.config.labels.difference.as_str(),
+                    ]),
+            )
+            .x_axis(
+                Axis::new()
+                    .name(self.config.x_label.as_str())
+                    .axis_label(x_axis_label(&self.config))
+                    .name_text_style(TextStyle::new().font_size(self.config.font(60.0)))
+                    .type_(AxisType::Category)
+                    .data(
+                        timestamps
+                            .iter()
+                            .map(|dt| category_label(*dt, &self.config.time_format))
+                            .collect::<Result<Vec<String>>>()?,
+                    ),
+            )
+            .y_axis(
+                Axis::new()
+                    .name(self.config.y_label.as_str())
+                    .name_gap(self.config.px(35.0))
+                    .scale(true)
+                    .axis_label(value_axis_label(&self.config))
+                    .name_text_style(TextStyle::new().font_size(self.config.font(60.0))),
+            )
+            .y_axis(
+                Axis::new()
+                    .name(self.config.labels.difference.as_str())
+                    .name_gap(self.config.px(35.0))
+                    .position("right")
+                    .axis_label(value_axis_label(&self.config))
+                    .name_text_style(TextStyle::new().font_size(self.config.font(60.0))),
+            )
+            .series(
+                Line::new()
+                    .name(self.first.issued.as_str())
+                    .show_symbol(false)
+                    .line_style(LineStyle::new().width(self.config.line(5.0)))
+                    .data(align_to_axis(&timestamps, &first)),
+            )
+            .series(
+                Line::new()
+                    .name(self.second.issued.as_str())
+                    .show_symbol(false)
+                    .line_style(
+                        LineStyle::new()
+                            .width(self.config.line(5.0))
+                            .type_(LineStyleType::Dashed),
+                    )
+                    .data(align_to_axis(&timestamps, &second)),
+            )
+            .series(
+                Bar::new()
+                    .name(self.config.labels.difference.as_str())
+                    .y_axis_index(1)
+                    .item_style(ItemStyle::new().color("#fac858").opacity(0.6))
+                    .data(align_to_axis(&timestamps, &differences)),
+            );
+
+        Ok(finish_chart(chart, &self.config))
+    }
+
+    fn draw(&self) -> Result<RenderedChart> {
+        let filename = self.generate_filename("ForecastDiffChart");
+        save_chart(self.chart()?, &self.config, filename)
+    }
+}
+
+/// Zonal loads stacked on top of each other, so the top edge traces the system total.
+///
+/// `data` is long-format ground truth as returned by `process_truth_regions`. Zones
+/// are stacked in the order of `zones`, bottom first; when `zones` is empty every
+/// zone in `data` is stacked in [`Zone`] order.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StackedAreaGraph {
+    /// Shared chart settings.
+    pub config: GraphConfig,
+    /// The frame described above.
+    #[serde(skip)]
+    pub data: DataFrame,
+    /// Zones to stack, bottom first; empty stacks every zone.
+    pub zones: Vec<Zone>,
+}
+
+impl StackedAreaGraph {
+    /// Returns the zones to stack, bottom first.
+    fn zones(&self) -> Result<Vec<Zone>> {
+        if !self.zones.is_empty() {
+            return Ok(self.zones.clone());
+        }
+        zones_in(&self.data)
+    }
+}
+
+impl Graph for StackedAreaGraph {
+    fn config(&self) -> &GraphConfig {
+        &self.config
+    }
+
+    fn chart(&self) -> Result<Chart> {
+        let timestamps = unique_timestamps(&self.data, "ground truth")?;
+        let zones = self.zones()?;
+
+        let mut chart = Chart::new()
+            .title(
+                Title::new()
+                    .text(fill_title(
+                        &self.config.title,
+                        &[
+                            ("region", zone_labels(&zones).join(", ")),
+                            ("start_date", date_label(timestamps.first())),
+                            ("end_date", date_label(timestamps.last())),
+                        ],
+                    ))
+                    .text_style(TextStyle::new().font_size(self.config.font(100.0)))
+                    .left("center"),
+            )
+            .grid(self.config.style.grid().bottom("3%").top("8%"))
+            .legend(
+                Legend::new()
+                    .left(self.config.px(50.0))
+                    .top(self.config.px(50.0))
+                    .text_style(TextStyle::new().font_size(self.config.font(40.0)))
+                    .data(zone_labels(&zones)),
+            )
+            .x_axis(
+                Axis::new()
+                    .name(self.config.x_label.as_str())
+                    .axis_label(x_axis_label(&self.config))
+                    .name_text_style(TextStyle::new().font_size(self.config.font(60.0)))
+                    .type_(AxisType::Category)
+                    .boundary_gap(false)
+                    .data(
+                        timestamps
+                            .iter()
+                            .map(|dt| category_label(*dt, &self.config.time_format))
+                            .collect::<Result<Vec<String>>>()?,
+                    ),
+            )
+            .y_axis(
+                Axis::new()
+                    .name(self.config.y_label.as_str())
+                    .name_gap(self.config.px(35.0))
+                    .axis_label(value_axis_label(&self.config))
+                    .name_text_style(TextStyle::new().font_size(self.config.font(60.0))),
+            );
+
+        for &zone in &zones {
+            let loads = zone_loads(&self.data, zone)?;
+            chart = chart.series(
+                Line::new()
+                    .name(zone.truth_name())
+                    .stack("total")
+                    .show_symbol(false)
+                    .line_style(LineStyle::new().width(self.config.line(1.0)))
+                    .area_style(AreaStyle::new())
+                    .data(align_to_axis(&timestamps, &loads)),
+            );
+        }
+
+        if let Some(thresholds) = threshold_series(&self.config, axis_unit(&self.config)) {
+            chart = chart.series(thresholds);
+        }
+
+        Ok(finish_chart(chart, &self.config))
+    }
+
+    fn draw(&self) -> Result<RenderedChart> {
+        let filename = self.generate_filename("StackedAreaChart");
+        save_chart(self.chart()?, &self.config, filename)
+    }
+}
+
+/// Share of the total load taken by each zone, as returned by `zone_shares_at`.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PieGraph {
+    /// Shared chart settings.
+    pub config: GraphConfig,
+    /// The frame described above.
+    #[serde(skip)]
+    pub data: DataFrame,
+    /// Free-form notes kept with the chart; not drawn.
+    pub notes: String,
+}
+
+impl Graph for PieGraph {
+    fn config(&self) -> &GraphConfig {
+        &self.config
+    }
+
+    fn chart(&self) -> Result<Chart> {
+        let slices = str_column(&self.data, "zone shares", "Name")?
+            .into_iter()
+            .zip(&f64_column(&self.data, "zone shares", "Integrated Load")?)
+            .filter_map(|(name, load)| Some((load?.round(), name?.to_string())))
+            .collect::<Vec<(f64, String)>>();
+        let slice_label: String =
+            fill_unit(&self.config.labels.zone_share, axis_unit(&self.config));
+
+        let chart = Chart::new()
+            .title(
+                Title::new()
+                    .text(self.config.title.as_str())
+                    .subtext(self.notes.as_str())
+                    .text_style(TextStyle::new().font_size(self.config.font(100.0)))
+                    .subtext_style(TextStyle::new().font_size(self.config.font(50.0)))
+                    .left("center"),
+            )
+            .series(
+                Pie::new()
+                    .radius("60%")
+                    .center(vec!["50%", "55%"])
+                    .label(
+                        Label::new()
+                            .show(true)
+                            .formatter(slice_label.as_str())
+                            .font_size(self.config.font(40.0)),
+                    )
+                    .data(slices),
+            );
+
+        Ok(finish_chart(chart, &self.config))
+    }
+
+    fn draw(&self) -> Result<RenderedChart> {
+        let filename = self.generate_filename("PieChart");
+        save_chart(self.chart()?, &self.config, filename)
+    }
+}
+
+/// Actual load plotted against forecast load, one point per timestamp.
+///
+/// `data` is the output of `join_actual_forecast`. Points on the dashed diagonal were
+/// forecast exactly; points above it were under-forecast and points below it
+/// over-forecast.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScatterGraph {
+    /// Shared chart settings.
+    pub config: GraphConfig,
+    /// The dataset described above.
+    #[serde(skip)]
+    pub data: TimeSeriesDataset,
+}
+
+impl Graph for ScatterGraph {
+    fn config(&self) -> &GraphConfig {
+        &self.config
+    }
+
+    fn chart(&self) -> Result<Chart> {
+        let timestamps = self.data.unique_timestamps()?;
+        let actual = self.data.values("Actual")?;
+        let points = self
+            .data
+            .values("Forecast")?
+            .into_iter()
+            .zip(&actual)
+            .filter_map(|(forecast, actual)| Some(vec![forecast?, actual?]))
+            .collect::<Vec<Vec<f64>>>();
+        let low = points
+            .iter()
+            .flatten()
+            .cloned()
+            .fold(f64::INFINITY, f64::min);
+        let high = points
+            .iter()
+            .flatten()
+            .cloned()
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        let mut chart = Chart::new()
+            .title(
+                Title::new()
+                    .text(fill_title(
+                        &self.config.title,
+                        &[
+                            ("start_date", date_label(timestamps.first())),
+                            ("end_date", date_label(timestamps.last())),
+                        ],
+                    ))
+                    .text_style(TextStyle::new().font_size(self.config.font(100.0)))
+                    .left("center"),
+            )
+            .grid(self.config.style.grid().bottom("3%").top("8%"))
+            .x_axis(
+                Axis::new()
+                    .type_(AxisType::Value)
+                    .scale(true)
+                    .name(self.config.x_label.as_str())
+                    .name_location(NameLocation::Center)
+                    .name_gap(self.config.px(80.0))
+                    .axis_label(value_axis_label(&self.config))
+                    .name_text_style(TextStyle::new().font_size(self.config.font(60.0))),
+            )
+            .y_axis(
+                Axis::new()
+                    .type_(AxisType::Value)
+                    .scale(true)
+                    .name(self.config.y_label.as_str())
+                    .name_gap(self.config.px(35.0))
+                    .axis_label(value_axis_label(&self.config))
+                    .name_text_style(TextStyle::new().font_size(self.config.font(60.0))),
+            )
+            .series(
+                Scatter::new()
+                    .name(self.config.labels.hourly_load.as_str())
+                    .symbol_size(self.config.px(14.0))
+                    .data(points),
+            );
+
+        if low.is_finite() {
+            chart = chart.series(
+                Line::new()
+                    .name(self.config.labels.perfect_forecast.as_str())
+                    .show_symbol(false)
+                    .line_style(
+                        LineStyle::new()
+                            .type_(LineStyleType::Dashed)
+                            .width(self.config.line(3.0)),
+                    )
+                    .data(vec![vec![low, low], vec![high, high]]),
+            );
+        }
+
+        Ok(finish_chart(chart, &self.config))
+    }
+
+    fn draw(&self) -> Result<RenderedChart> {
+        let filename = self.generate_filename("ScatterChart");
+        save_chart(self.chart()?, &self.config, filename)
+    }
+}
+
+/// Load of one zone laid out as hour of day against date, colored by megawatts.
+///
+/// `data` is processed ground truth as returned by `process_truth`. Daily and weekly
+/// patterns show up as vertical and horizontal bands.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeatmapGraph {
+    /// Shared chart settings.
+    pub config: GraphConfig,
+    /// The dataset described above.
+    #[serde(skip)]
+    pub data: TimeSeriesDataset,
+}
+
+impl Graph for HeatmapGraph {
+    fn config(&self) -> &GraphConfig {
+        &self.config
+    }
+
+    fn chart(&self) -> Result<Chart> {
+        let timestamps = self
+            .data
+            .timestamps()?
+            .into_iter()
+            .collect::<Vec<Option<i64>>>();
+        let loads = self
+            .data
+            .values(self.data.value_column())?
+            .into_iter()
+            .collect::<Vec<Option<f64>>>();
+
+        let mut dates = timestamps
+            .iter()
+            .flatten()
+            .filter_map(|dt| DateTime::from_timestamp_millis(*dt))
+            .map(|dt| dt.date_naive())
+            .collect::<Vec<NaiveDate>>();
+        dates.sort();
+        dates.dedup();
+
+        let cells = timestamps
+            .iter()
+            .zip(&loads)
+            .filter_map(|(dt, load)| {
+                let dt = DateTime::from_timestamp_millis((*dt)?)?;
+                let day = dates.binary_search(&dt.date_naive()).ok()?;
+                Some(vec![
+                    DataPoint::from(day as i64),
+                    DataPoint::from(dt.hour() as i64),
+                    DataPoint::from(load.as_ref()?.round()),
+                ])
+            })
+            .collect::<Vec<Vec<DataPoint>>>();
+        let present = loads.iter().flatten();
+        let low = present.clone().cloned().fold(f64::INFINITY, f64::min);
+        let high = present.cloned().fold(f64::NEG_INFINITY, f64::max);
+
+        let chart = Chart::new()
+            .title(
+                Title::new()
+                    .text(fill_title(
+                        &self.config.title,
+                        &[
+                            ("start_date", date_label(timestamps.iter().flatten().min())),
+                            ("end_date", date_label(timestamps.iter().flatten().max())),
+                        ],
+                    ))
+                    .text_style(TextStyle::new().font_size(self.config.font(100.0)))
+                    .left("center"),
+            )
+            .grid(self.config.style.grid().bottom("12%").top("8%"))
+            .x_axis(
+                Axis::new()
+                    .type_(AxisType::Category)
+                    .name(self.config.x_label.as_str())
+                    .axis_label(AxisLabel::new().font_size(self.config.font(30.0)))
+                    .name_text_style(TextStyle::new().font_size(self.config.font(60.0)))
+                    .split_area(SplitArea::new().show(true))
+                    .data(
+                        dates
+                            .iter()
+                            .map(|date| date.format("%m/%d").to_string())
+                            .collect::<Vec<String>>(),
+                    ),
+            )
+            .y_axis(
+                Axis::new()
+                    .type_(AxisType::Category)
+                    .name(self.config.y_label.as_str())
+                    .axis_label(AxisLabel::new().font_size(self.config.font(30.0)))
+                    .name_text_style(TextStyle::new().font_size(self.config.font(60.0)))
+                    .split_area(SplitArea::new().show(true))
+                    .data(
+                        (0..24)
+                            .map(|hour| format!("{:02}:00", hour))
+                            .collect::<Vec<String>>(),
+                    ),
+            )
+            .visual_map(
+                VisualMap::new()
+                    .min(if low.is_finite() { low.floor() } else { 0.0 })
+                    .max(if high.is_finite() { high.ceil() } else { 1.0 })
+                    .calculable(true)
+                    .orient(Orient::Horizontal)
+                    .left("center")
+                    .bottom("2%")
+                    .item_width(self.config.px(40.0))
+                    .item_height(self.config.px(800.0))
+                    .text_style(TextStyle::new().font_size(self.config.font(30.0))),
+            )
+            .series(
+                Heatmap::new()
+                    .name(self.config.labels.load.as_str())
+                    .data(cells),
+            );
+
+        Ok(finish_chart(chart, &self.config))
+    }
+
+    fn draw(&self) -> Result<RenderedChart> {
+        let filename = self.generate_filename("HeatmapChart");
+        save_chart(self.chart()?, &self.config, filename)
+    }
+}
+
+/// Forecast quality per zone on a radar, one polygon per zone.
+///
+/// `data` is the output of `zone_metrics`. Each metric is taken as an absolute value
+/// and divided by its largest value across zones, so every spoke runs from 0 to 1 and
+/// the worst zone on a metric touches the rim. The raw maximum is kept in the spoke
+/// name for scale.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RadarGraph {
+    /// Shared chart settings.
+    pub config: GraphConfig,
+    /// The frame described above.
+    #[serde(skip)]
+    pub data: DataFrame,
+}
+
+impl RadarGraph {
+    const METRICS: [&'static str; 3] = ["MAPE", "Peak Error", "Bias"];
+}
+
+impl Graph for RadarGraph {
+    fn config(&self) -> &GraphConfig {
+        &self.config
+    }
+
+    fn chart(&self) -> Result<Chart> {
+        let zones = str_column(&self.data, "zone metrics", "Name")?
+            .into_iter()
+            .map(|zone| zone.unwrap_or_default().to_string())
+            .collect::<Vec<String>>();
+        let metrics = RadarGraph::METRICS
+            .iter()
+            .map(|metric| {
+                Ok(f64_column(&self.data, "zone metrics", metric)?
+                    .into_iter()
+                    .map(|value| value.map_or(0.0, f64::abs))
+                    .collect::<Vec<f64>>())
+            })
+            .collect::<Result<Vec<Vec<f64>>>>()?;
+        let maxima = metrics
+            .iter()
+            .map(|values| values.iter().cloned().fold(0.0, f64::max))
+            .collect::<Vec<f64>>();
+
+        let labels = &self.config.labels;
+        let indicators = [&labels.mape, &labels.peak_error, &labels.bias]
+            .iter()
+            .zip(&maxima)
+            .map(|(metric, max)| {
+                RadarIndicator::new()
+                    .name(fill_title(
+                        &labels.metric_axis,
+                        &[("metric", metric.to_string()), ("max", format!("{:.1}", max))],
+                    ))
+                    .min(0.0)
+                    .max(1.0)
+            })
+            .collect::<Vec<RadarIndicator>>();
+        let polygons = zones
+            .iter()
+            .enumerate()
+            .map(|(row, zone)| {
+                let normalized = metrics
+                    .iter()
+                    .zip(&maxima)
+                    .map(|(values, max)| {
+                        let value = if *max > 0.0 { values[row] / max } else { 0.0 };
+                        CompositeValue::from(value)
+                    })
+                    .collect::<Vec<CompositeValue>>();
+                DataPointItem::new(normalized).name(zone.as_str())
+            })
+            .collect::<Vec<DataPointItem>>();
+
+        let chart = Chart::new()
+            .title(
+                Title::new()
+                    .text(self.config.title.as_str())
+                    .text_style(TextStyle::new().font_size(self.config.font(100.0)))
+                    .left("center"),
+            )
+            .legend(
+                Legend::new()
+                    .left(self.config.px(50.0))
+                    .top(self.config.px(50.0))
+                    .orient(Orient::Vertical)
+                    .text_style(TextStyle::new().font_size(self.config.font(40.0)))
+                    .data(zones.clone()),
+            )
+            .radar(
+                RadarCoordinate::new()
+                    .center(vec!["50%", "55%"])
+                    .radius("65%")
+                    .axis_name(RadarAxisName::new().font_size(self.config.font(50.0)))
+                    .indicator(indicators),
+            )
+            .series(
+                Radar::new()
+                    .line_style(LineStyle::new().width(self.config.line(4.0)))
+                    .data(polygons),
+            );
+
+        Ok(finish_chart(chart, &self.config))
+    }
+
+    fn draw(&self) -> Result<RenderedChart> {
+        let filename = self.generate_filename("RadarChart");
+        save_chart(self.chart()?, &self.config, filename)
+    }
+}
+
+/// The latest actual load of one zone on a dial scaled to its capacity, green up to
+/// that day's forecast peak and red above it.
+///
+/// Zones differ in size by two orders of magnitude, so without a `capacity` the
+/// dial is scaled to the data instead, see [`GaugeGraph::dial_max`].
+///
+/// `data` is processed ground truth as returned by `process_truth` and `forecast`
+/// the matching output of `process_pred`.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct GaugeGraph {
+    /// Shared chart settings.
+    pub config: GraphConfig,
+    /// Processed ground truth.
+    #[serde(skip)]
+    pub data: TimeSeriesDataset,
+    /// Processed forecast.
+    #[serde(skip)]
+    pub forecast: TimeSeriesDataset,
+    /// Zone shown, used for the `{region}` title placeholder.
+    pub zone: Zone,
+    /// Full-scale value of the dial, e.g. the zone's capacity; `None` scales the dial
+    /// to the data.
+    pub capacity: Option<f64>,
+}
+
+impl Default for GaugeGraph {
+    fn default() -> Self {
+        GaugeGraph {
+            config: GraphConfig::default(),
+            data: TimeSeriesDataset::default(),
+            forecast: TimeSeriesDataset::default(),
+            zone: Zone::NewYorkCity,
+            capacity: None,
+        }
+    }
+}
+
+impl GaugeGraph {
+    /// Returns the most recent actual load and the date it was recorded on.
+    fn latest_actual(&self) -> Result<(NaiveDate, f64)> {
+        let latest = self
+            .data
+            .sorted()?
+            .map_frame(|frame| Ok(frame.tail(Some(1))))?;
+
+        let timestamp = latest.timestamps()?.get(0);
+        let load = latest.values(latest.value_column())?.get(0);
+        let (Some(timestamp), Some(load)) = (timestamp, load) else {
+            return Err(MugenError::NoData(
+                "ground truth has no timestamped load to show".to_string(),
+            ));
+        };
+        Ok((naive_datetime(timestamp)?.date(), load))
+    }
+
+    /// Returns the highest forecast value on the given day.
+    fn forecast_peak(&self, day: NaiveDate) -> Result<f64> {
+        let timestamps = self.forecast.timestamps()?;
+        let values = self.forecast.values(self.forecast.value_column())?;
+
+        Ok(timestamps
+            .into_iter()
+            .zip(&values)
+            .filter_map(|(dt, value)| {
+                let date = naive_datetime(dt?).ok()?.date();
+                (date == day).then_some(value).flatten()
+            })
+            .fold(f64::NAN, f64::max))
+    }
+
+    /// Returns the full-scale value of the dial: the configured capacity, or a fifth
+    /// above the larger of `actual` and `peak`, rounded up to two significant digits.
+    fn dial_max(&self, actual: f64, peak: f64) -> f64 {
+        if let Some(capacity) = self.capacity {
+            return capacity;
+        }
+        let top = actual.max(peak) * 1.2;
+        if !(top.is_finite() && top > 0.0) {
+            return 1.0;
+        }
+        let step = 10f64.powf(top.log10().floor() - 1.0);
+        (top / step).ceil() * step
+    }
+}
+
+impl Graph for GaugeGraph {
+    fn config(&self) -> &GraphConfig {
+        &self.config
+    }
+
+    fn chart(&self) -> Result<Chart> {
+        let (day, actual) = self.latest_actual()?;
+        let peak = self.forecast_peak(day)?;
+        let capacity = self.dial_max(actual, peak);
+        let value_label: String = fill_unit(&self.config.labels.gauge_value, self.data.unit());
+
+        let chart = Chart::new()
+            .title(
+                Title::new()
+                    .text(fill_title(
+                        &self.config.title,
+                        &[
+                            ("region", self.zone.to_string()),
+                            ("start_date", day.format("%m/%d/%Y").to_string()),
+                            ("end_date", day.format("%m/%d/%Y").to_string()),
+                            ("peak_load", format!("{:.0}", peak)),
+                        ],
+                    ))
+                    .text_style(TextStyle::new().font_size(self.config.font(100.0)))
+                    .left("center"),
+            )
+            .series(
+                Gauge::new()
+                    .min(0)
+                    .max(capacity)
+                    .split_number(10)
+                    .radius("80%")
+                    .center(("50%", "60%"))
+                    .axis_line(
+                        AxisLine::new().line_style(
+                            AxisLineStyle::new()
+                                .width(self.config.line(60.0))
+                                .color(((peak / capacity).min(1.0), "#91cc75"))
+                                .color((1.0, "#ee6666")),
+                        ),
+                    )
+                    .axis_label(
+                        AxisLabel::new()
+                            .font_size(self.config.font(40.0))
+                            .distance(self.config.px(80.0)),
+                    )
+                    .title(GaugeTitle::new().offset_center(("0%", "30%")))
+                    .detail(
+                        GaugeDetail::new()
+                            .font_size(self.config.font(80.0))
+                            .formatter(value_label.as_str()),
+                    )
+                    .data(vec![(
+                        actual.round(),
+                        fill_title(
+                            &fill_unit(&self.config.labels.forecast_peak, self.data.unit()),
+                            &[
+                                ("day", day.format("%m/%d/%Y").to_string()),
+                                ("peak", format!("{:.0}", peak)),
+                            ],
+                        ),
+                    )]),
+            );
+
+        Ok(finish_chart(chart, &self.config))
+    }
+
+    fn draw(&self) -> Result<RenderedChart> {
+        let filename = self.generate_filename("GaugeChart");
+        save_chart(self.chart()?, &self.config, filename)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ten days of hourly load from `phase`, missing in the hours `missing`.
+    fn hourly(name: &str, phase: f64, missing: std::ops::Range<usize>) -> TimeSeriesDataset {
+        let start = NaiveDate::from_ymd_opt(2023, 12, 1)
+            .and_then(|day| day.and_hms_opt(0, 0, 0))
+            .unwrap();
+        let times: Vec<i64> = (0..240)
+            .map(|hour| {
+                (start + chrono::Duration::hours(hour))
+                    .and_utc()
+                    .timestamp_millis()
+            })
+            .collect();
+        let loads: Vec<Option<f64>> = (0..240)
+            .map(|hour| {
+                let angle = (hour as f64 + phase) * std::f64::consts::PI / 12.0;
+                (!missing.contains(&hour)).then_some(5000.0 + 1000.0 * angle.sin())
+            })
+            .collect();
+        let frame = df!(
+            "Time Stamp" => Series::new("Time Stamp", times)
+                .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))
+                .unwrap(),
+            "Load" => loads,
+        )
+        .unwrap();
+        TimeSeriesDataset::new(name, frame, "Time Stamp", vec!["Load".to_string()]).unwrap()
+    }
+
+    #[test]
+    fn downsampling_shares_one_axis_and_keeps_gaps() {
+        // An outage in both series over one bucket, and in the actual load over the
+        // next, of the 10 buckets of 24 hours 40 points make for two series.
+        let graph = LineGraph::builder()
+            .truth(hourly("actual", 0.0, 96..144))
+            .forecast(hourly("forecast", 3.0, 96..120))
+            .max_points(40)
+            .build()
+            .unwrap();
+        let option = serde_json::to_value(graph.chart().unwrap()).unwrap();
+        let categories: usize = option["xAxis"][0]["data"].as_array().unwrap().len();
+        assert!(categories <= 40, "{} categories", categories);
+
+        let gaps = |name: &str| -> usize {
+            let series = option["series"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|series| series["name"] == name)
+                .unwrap();
+            let data = series["data"].as_array().unwrap();
+            assert_eq!(data.len(), categories);
+            data.iter().filter(|point| *point == "-").count()
+        };
+        // The shared outage keeps one point, the forecast's extremes the other two.
+        assert_eq!(gaps("Actual"), 3);
+        assert_eq!(gaps("Forecast"), 1);
+    }
+
+    #[test]
+    fn gauge_dial_scales_to_the_zone() {
+        let gauge = GaugeGraph::default();
+        assert_eq!(gauge.dial_max(610.0, 700.0), 840.0);
+        assert_eq!(gauge.dial_max(9500.0, f64::NAN), 12000.0);
+        assert_eq!(gauge.dial_max(0.0, f64::NAN), 1.0);
+
+        let gauge = GaugeGraph {
+            capacity: Some(3000.0),
+            ..Default::default()
+        };
+        assert_eq!(gauge.dial_max(610.0, 700.0), 3000.0);
+    }
+
+    #[test]
+    fn build_requires_truth_and_forecast() {
+        let missing =
+            |result: Result<LineGraph>| matches!(result, Err(MugenError::InvalidArgument(_)));
+        assert!(missing(LineGraph::builder().build()));
+        assert!(missing(
+            LineGraph::builder()
+                .truth(hourly("actual", 0.0, 0..0))
+                .build()
+        ));
+        assert!(missing(
+            LineGraph::builder()
+                .truth(hourly("actual", 0.0, 0..0))
+                .forecast(hourly("forecast", 3.0, 0..0).with_unit("GW"))
+                .build()
+        ));
+        assert!(LineGraph::builder()
+            .truth(hourly("actual", 0.0, 0..0))
+            .forecast(hourly("forecast", 3.0, 0..0))
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn configured_filename_keeps_chart_types_apart() {
+        let config = GraphConfig {
+            output_dir: "charts".to_string(),
+            filename: Some("nyc.png".to_string()),
+            ..Default::default()
+        };
+        let line = LineGraph {
+            config: config.clone(),
+            ..Default::default()
+        };
+        let gauge = GaugeGraph {
+            config,
+            ..Default::default()
+        };
+        let line_path = line.generate_filename("LineChart");
+        assert_eq!(Path::new(&line_path), Path::new("charts/nyc_LineChart.png"));
+        assert_ne!(line_path, gauge.generate_filename("GaugeChart"));
+    }
+}
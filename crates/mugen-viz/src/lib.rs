@@ -0,0 +1,101 @@
+//! Charts of NYISO zonal load and load forecasts.
+//!
+//! Builds on the frames and series produced by `mugen-core`:
+//!
+//! 1. [`graph`] turns those series and frames into ECharts charts. Every chart type
+//!    implements [`graph::Graph`] and is configured through a shared
+//!    [`graph::GraphConfig`].
+//! 2. Charts are saved as images with [`graph::Graph::draw`], rendered in memory with
+//!    [`graph::Graph::render_to_bytes`], written to any `std::io::Write` with
+//!    [`graph::Graph::render_to_writer`], printed to a terminal with
+//!    [`terminal::braille_chart`], shown for every zone at once by
+//!    `dashboard::run`, or served as a self-refreshing page with
+//!    `live::serve`, which also serves forecast-quality gauges to Prometheus from
+//!    [`prometheus`]. `server::serve` answers requests for single charts and
+//!    metrics, built on demand, and for the series of [`grafana`].
+//! 3. Rendering goes through a [`render::Renderer`], so the ECharts image renderer
+//!    can be swapped for another backend. Rendering runs inside a `tracing` span
+//!    named `render`.
+//!
+//! [`pipeline::Pipeline`] strings these steps together for one zone, from data
+//! sources to drawn charts, in a single call. Its [`pipeline::PipelineReport`]
+//! summarizes the datasets, metrics, charts and warnings of a run for other code to
+//! inspect. It can also score the forecasts issued the day before against the
+//! same-day updates, to show what the intraday updates are worth.
+//!
+//! [`notify::Notify`] posts a summary of a run to Slack, always or only when a
+//! metric crosses its alert limit or one of its alert rules, such as a gap in the
+//! data, fires. [`report::html`] lays the same summary out as an
+//! HTML page with the charts, which [`email::Email`] mails to a list of recipients.
+//!
+//! [`manifest::Manifest`] records the inputs, settings and charts of a run, with
+//! file hashes, so every chart can be traced back to its data. Each
+//! [`sink::ArtifactSink`] copies the charts, report and manifest of a run to a
+//! directory, S3, Google Cloud Storage or Azure Blob Storage, and the manifest
+//! records where.
+//!
+//! [`snapshot::Snapshots`] checks charts against golden files, so changes to the
+//! chart code can be shown not to change their output.
+//!
+//! # Features
+//!
+//! Only `image-render` is on by default. Without any image backend, charts can
+//! still be written as ECharts options with the spec renderer. Built that way, with
+//! no feature at all, the crate also compiles to `wasm32-unknown-unknown`, which the
+//! `mugen-wasm` crate uses to hand chart specs to a browser.
+//!
+//! * `image-render` - Rendering charts to images with ECharts, which embeds a
+//!   JavaScript engine.
+//! * `plotters` - Rendering charts to images with plotters instead.
+//! * `html` - The `live` chart page.
+//! * `tui` - The `dashboard` module, a terminal dashboard of every zone.
+//! * `notebook` - The `notebook` module, showing charts inline in evcxr notebooks.
+//! * `server` - The `server` module, answering HTTP requests for charts and
+//!   metrics.
+//! * `notify` - Posting run summaries to Slack with [`notify::Notify::send`].
+//! * `email` - Emailing run reports over SMTP with `email::Email::send`.
+//! * `s3`, `gcs`, `azure` - Copying run outputs to that storage with
+//!   [`sink::ArtifactSink::store`]; without them, those sinks fail to store.
+
+/// A full-screen terminal dashboard of every zone's load, forecast error and
+/// freshness.
+#[cfg(feature = "tui")]
+pub mod dashboard;
+/// Run settings, checking them before a run, and reading and writing them as TOML
+/// or JSON files.
+pub mod config;
+/// Emailing run reports over SMTP.
+pub mod email;
+/// Chart types, their shared configuration and rendering to images.
+pub mod graph;
+/// Actual, forecast and error series in the JSON Grafana datasources read.
+pub mod grafana;
+/// A minimal HTTP server for a self-refreshing chart page.
+#[cfg(feature = "html")]
+pub mod live;
+/// Records of what a run read and wrote, written next to its charts.
+pub mod manifest;
+/// Charts shown inline in evcxr Jupyter notebooks, as HTML or PNG.
+#[cfg(feature = "notebook")]
+pub mod notebook;
+/// Posting run summaries and alerts to Slack.
+pub mod notify;
+/// Composing sources, transforms, metrics and charts into one evaluation run.
+pub mod pipeline;
+/// Forecast-quality gauges in the Prometheus text format.
+pub mod prometheus;
+/// HTML reports of runs, written next to their charts.
+pub mod report;
+/// Renderers that turn built charts into images or other output.
+pub mod render;
+/// Uploading run outputs to S3.
+pub mod s3;
+/// A minimal HTTP server for charts and metrics rendered on demand.
+#[cfg(feature = "server")]
+pub mod server;
+/// Copying run outputs to local or cloud storage.
+pub mod sink;
+/// Comparing rendered charts against stored golden files.
+pub mod snapshot;
+/// Braille line charts for the terminal.
+pub mod terminal;
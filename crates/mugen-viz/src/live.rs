@@ -0,0 +1,96 @@
+use mugen_core::error::Result;
+
+use crate::graph::GraphConfig;
+use crate::prometheus;
+use crate::render::{localize_script, ECHARTS_CDN};
+use crate::server::{self, Request, Response, Routes};
+
+/// Serves a self-refreshing chart page on `address` until the process is stopped.
+///
+/// `GET /` returns an HTML page sized to the config that loads ECharts and polls
+/// `GET /option` every `interval_secs` seconds. Each `/option` request calls `spec`,
+/// so the chart is rebuilt from whatever data is on disk at that moment. A failing
+/// `spec` is reported as an error status and the page keeps showing the last good
+/// chart. `GET /metrics` likewise calls `metrics` for Prometheus to scrape, see
+/// [`crate::prometheus::exposition`]. Requests are answered as
+/// [`crate::server::serve`] answers them, each on its own blocking thread and with
+/// the same limits, so a dashboard and a scraper never wait on each other.
+///
+/// # Arguments
+///
+/// * `address` - The socket address to listen on, e.g. `127.0.0.1:8080`.
+/// * `interval_secs` - How often the page fetches a fresh chart.
+/// * `config` - Supplies the page size and whether to use the dark ECharts theme.
+/// * `spec` - Builds the current ECharts option, as returned by `Graph::to_echarts_json`.
+/// * `metrics` - Builds the current metrics in the Prometheus text format.
+///
+/// # Errors
+///
+/// Returns an error if the address cannot be bound.
+pub fn serve<F, M>(
+    address: &str,
+    interval_secs: u64,
+    config: &GraphConfig,
+    spec: F,
+    metrics: M,
+) -> Result<()>
+where
+    F: Fn() -> Result<String> + Send + Sync + 'static,
+    M: Fn() -> Result<String> + Send + Sync + 'static,
+{
+    let page = live_page(interval_secs, config);
+    let routes = Routes::new()
+        .get("/", move |_: &Request| {
+            Response::ok("text/html; charset=utf-8", page.as_str())
+        })
+        .get("/option", move |_: &Request| {
+            spec()
+                .map(|option| Response::ok("application/json", option))
+                .unwrap_or_else(|err| Response::error(&err))
+        })
+        .get("/metrics", move |_: &Request| {
+            metrics()
+                .map(|text| Response::ok(prometheus::CONTENT_TYPE, text))
+                .unwrap_or_else(|err| Response::error(&err))
+        });
+    server::serve(address, routes)
+}
+
+/// Builds the HTML page that draws the chart and re-fetches its option on an interval,
+/// labeling its value axes in the configured number format.
+fn live_page(interval_secs: u64, config: &GraphConfig) -> String {
+    let theme = if config.style.dark { "'dark'" } else { "null" };
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Live load</title>
+<script src="{cdn}"></script>
+</head>
+<body style="margin:0;background:{background}">
+<div id="chart" style="width:{width}px;height:{height}px"></div>
+<script>
+{localize}
+const chart = echarts.init(document.getElementById('chart'), {theme});
+function refresh() {{
+  fetch('/option', {{ cache: 'no-store' }})
+    .then(response => response.ok ? response.json() : Promise.reject(response.status))
+    .then(option => chart.setOption(localize(option), true))
+    .catch(err => console.error('live chart refresh failed', err));
+}}
+refresh();
+setInterval(refresh, {interval_ms});
+</script>
+</body>
+</html>
+"#,
+        cdn = ECHARTS_CDN,
+        background = config.style.background.as_deref().unwrap_or("transparent"),
+        width = config.graph_width,
+        height = config.graph_height,
+        theme = theme,
+        localize = localize_script(config),
+        interval_ms = interval_secs.max(1) * 1000,
+    )
+}
@@ -0,0 +1,181 @@
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+pub use mugen_core::checksum::{file_digest, FileDigest};
+use mugen_core::error::{MugenError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::graph::RenderedChart;
+
+/// File name of the manifest written next to a run's charts.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// A record of one run: what it read, with which settings and version, and what it
+/// wrote, so any chart can be traced back to the exact data behind it.
+///
+/// Files are identified by their SHA-256 hash, so a changed input shows up even if
+/// its name stays the same.
+///
+/// # Examples
+///
+/// ```no_run
+/// use mugen_viz::config::PipelineConfig;
+/// use mugen_viz::graph::Graph;
+/// # use mugen_viz::graph::LineGraph;
+/// use mugen_viz::manifest::Manifest;
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// # let graph = LineGraph::builder().build()?;
+/// let settings = PipelineConfig::default();
+/// let mut manifest = Manifest::new(&settings)?;
+/// manifest.input(&settings.forecast_file)?;
+/// manifest.artifact(&graph.draw()?)?;
+/// manifest.write()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Version of the crate that produced the run.
+    pub version: String,
+    /// When the run started, in RFC 3339 format.
+    pub created: String,
+    /// The command line the run was started with.
+    pub command: Vec<String>,
+    /// The settings of the run.
+    pub parameters: Value,
+    /// Every file the run read, in the order added.
+    pub inputs: Vec<FileDigest>,
+    /// Every chart the run wrote, in the order added.
+    pub artifacts: Vec<Artifact>,
+    /// Every file copied to a sink, with where the copy can be found, in the order
+    /// added.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub uploads: Vec<Upload>,
+}
+
+/// A chart written by the run.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Artifact {
+    /// The written file.
+    #[serde(flatten)]
+    pub file: FileDigest,
+    /// Image width in pixels.
+    pub width: u32,
+    /// Image height in pixels.
+    pub height: u32,
+}
+
+/// A file of the run copied to a sink, see [`crate::sink::ArtifactSink`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Upload {
+    /// Local path of the file.
+    pub path: String,
+    /// URL or path of the copy.
+    pub url: String,
+}
+
+impl Manifest {
+    /// Starts a manifest for a run with `parameters`, such as its
+    /// [`crate::config::PipelineConfig`], recording the current command line and
+    /// time.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InvalidArgument` if `parameters` cannot be serialized.
+    pub fn new(parameters: &impl Serialize) -> Result<Self> {
+        Ok(Manifest {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            created: Utc::now().to_rfc3339(),
+            command: std::env::args().collect(),
+            parameters: serde_json::to_value(parameters)
+                .map_err(|err| MugenError::InvalidArgument(err.to_string()))?,
+            inputs: Vec::new(),
+            artifacts: Vec::new(),
+            uploads: Vec::new(),
+        })
+    }
+
+    /// Records the file at `path` as read by the run.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InFile`, naming `path`, if it cannot be read.
+    pub fn input(&mut self, path: &str) -> Result<()> {
+        self.inputs.push(file_digest(path)?);
+        Ok(())
+    }
+
+    /// Records `chart` as written by the run.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the chart's path if it cannot be read back.
+    pub fn artifact(&mut self, chart: &RenderedChart) -> Result<()> {
+        self.artifacts.push(Artifact {
+            file: file_digest(&chart.path)?,
+            width: chart.width,
+            height: chart.height,
+        });
+        Ok(())
+    }
+
+    /// Records the file at `path` as copied to `url`.
+    pub fn upload(&mut self, path: &str, url: &str) {
+        self.uploads.push(Upload {
+            path: path.to_string(),
+            url: url.to_string(),
+        });
+    }
+
+    /// Returns the deepest directory holding every artifact, or the current
+    /// directory if there are none; the manifest is written there.
+    pub fn dir(&self) -> PathBuf {
+        artifact_dir(&self.artifacts)
+    }
+
+    /// Writes the manifest as pretty-printed JSON to [`MANIFEST_FILE`] in
+    /// [`Manifest::dir`], replacing an earlier one, and returns its path.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::Write` if the file cannot be written.
+    pub fn write(&self) -> Result<String> {
+        let path = self.dir().join(MANIFEST_FILE);
+        let path = path.to_string_lossy().into_owned();
+        let text = serde_json::to_string_pretty(self)
+            .map_err(|err| MugenError::InvalidArgument(err.to_string()))?;
+        std::fs::write(&path, text).map_err(|source| MugenError::Write {
+            path: path.clone(),
+            source,
+        })?;
+        Ok(path)
+    }
+}
+
+/// Returns the deepest directory containing every artifact, or the current
+/// directory if there are none.
+fn artifact_dir(artifacts: &[Artifact]) -> PathBuf {
+    let mut dirs = artifacts.iter().map(|artifact| {
+        Path::new(&artifact.file.path)
+            .parent()
+            .unwrap_or(Path::new(""))
+    });
+    let Some(first) = dirs.next() else {
+        return PathBuf::from(".");
+    };
+    let common = dirs.fold(first.to_path_buf(), |common, dir| {
+        common
+            .components()
+            .zip(dir.components())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a)
+            .collect()
+    });
+    if common.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        common
+    }
+}
@@ -0,0 +1,131 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use image::ImageFormat;
+use mugen_core::error::Result;
+
+use crate::graph::Graph;
+use crate::render::{localize_script, ECHARTS_CDN};
+
+/// Number of charts shown as HTML so far, so each gets its own element.
+static SHOWN: AtomicUsize = AtomicUsize::new(0);
+
+/// A chart as an HTML fragment, shown by evcxr as the output of a cell.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Html(pub String);
+
+impl Html {
+    /// Prints the fragment in the form evcxr shows as HTML output; called by evcxr
+    /// when an `Html` is the value of a cell.
+    pub fn evcxr_display(&self) {
+        println!(
+            "EVCXR_BEGIN_CONTENT text/html\n{}\nEVCXR_END_CONTENT",
+            self.0
+        );
+    }
+}
+
+/// A chart as PNG bytes, shown by evcxr as the output of a cell.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Png(pub Vec<u8>);
+
+impl Png {
+    /// Prints the image in the form evcxr shows as an image; called by evcxr when a
+    /// `Png` is the value of a cell.
+    pub fn evcxr_display(&self) {
+        println!(
+            "EVCXR_BEGIN_CONTENT image/png\n{}\nEVCXR_END_CONTENT",
+            STANDARD.encode(&self.0)
+        );
+    }
+}
+
+/// Shows any chart inline in an evcxr Jupyter notebook, without writing files.
+///
+/// [`Notebook::html`] embeds the chart's ECharts option in the cell, so it stays
+/// interactive and needs no image backend; ECharts is loaded from a CDN the first
+/// time. [`Notebook::png`] renders it with the configured backend, for notebooks
+/// that are viewed offline or exported.
+///
+/// # Examples
+///
+/// In a notebook cell, with the crate added through `:dep mugen-viz = { features =
+/// ["notebook"] }`:
+///
+/// ```no_run
+/// use chrono::NaiveDate;
+/// use mugen_core::data::{process_pred, process_truth};
+/// use mugen_core::synthetic::Synthetic;
+/// use mugen_core::zone::Zone;
+/// use mugen_viz::graph::{GraphConfig, LineGraph};
+/// use mugen_viz::notebook::Notebook;
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let zone = Zone::NewYorkCity;
+/// let day = NaiveDate::from_ymd_opt(2023, 12, 9).unwrap();
+/// let truth = process_truth(Synthetic::default().truth(day..=day)?, zone)?;
+/// let forecast = process_pred(Synthetic::default().forecast(day..=day)?, zone)?;
+/// let graph = LineGraph::builder()
+///     .config(GraphConfig::default())
+///     .truth(truth)
+///     .forecast(forecast)
+///     .build()?;
+/// graph.html()?.evcxr_display();
+/// # Ok(())
+/// # }
+/// ```
+pub trait Notebook: Graph {
+    /// Returns the chart as an HTML fragment that draws it with ECharts, sized and
+    /// themed like the images.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Graph::to_echarts_json`].
+    fn html(&self) -> Result<Html> {
+        let option: String = self.to_echarts_json()?;
+        let config = self.config();
+        let id: String = format!(
+            "mugen-chart-{}-{}",
+            std::process::id(),
+            SHOWN.fetch_add(1, Ordering::Relaxed)
+        );
+        Ok(Html(format!(
+            r#"<div id="{id}" style="width:{width}px;height:{height}px;background:{background}"></div>
+<script>
+(function() {{
+  {localize}
+  const draw = () => echarts.init(document.getElementById('{id}'), {theme}).setOption(localize({option}));
+  if (window.echarts) {{
+    draw();
+  }} else {{
+    const script = document.createElement('script');
+    script.src = '{cdn}';
+    script.onload = draw;
+    document.head.appendChild(script);
+  }}
+}})();
+</script>"#,
+            id = id,
+            width = config.graph_width,
+            height = config.graph_height,
+            background = config.style.background.as_deref().unwrap_or("transparent"),
+            theme = if config.style.dark { "'dark'" } else { "null" },
+            option = option,
+            localize = localize_script(config),
+            cdn = ECHARTS_CDN,
+        )))
+    }
+
+    /// Returns the chart rendered as a PNG with the configured backend, watermark
+    /// included.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Graph::render_to_bytes`].
+    fn png(&self) -> Result<Png> {
+        Ok(Png(self.render_to_bytes(ImageFormat::Png)?))
+    }
+}
+
+impl<G: Graph + ?Sized> Notebook for G {}
@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use mugen_core::alert::Rule;
+#[cfg(feature = "notify")]
+use mugen_core::error::{MugenError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::pipeline::PipelineReport;
+
+/// Where and when to post a summary of a run, e.g. to a Slack channel.
+///
+/// Nothing is posted without `slack_webhook`. Every metric named in `alerts` fires
+/// when its absolute value exceeds the limit, and every rule in `rules` when the
+/// run's data breaks it, e.g. a gap in the actual load.
+///
+/// # Examples
+///
+/// ```toml
+/// [notify]
+/// slack_webhook = "https://hooks.slack.com/services/T000/B000/XXXX"
+/// chart_base_url = "https://charts.example.com/"
+/// only_alerts = true
+///
+/// [notify.alerts]
+/// MAPE = 5.0
+/// "Peak Error" = 3.0
+///
+/// [[notify.rules]]
+/// kind = "gap"
+/// hours = 1.0
+///
+/// [[notify.rules]]
+/// kind = "ape"
+/// percent = 10.0
+/// hours = 3
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Notify {
+    /// Slack incoming webhook URL; keep it out of shared config files, since anyone
+    /// holding it can post to the channel.
+    pub slack_webhook: Option<String>,
+    /// URL the output directory is published under; when set, the rendered charts
+    /// are shown in the message, since webhooks cannot upload files.
+    pub chart_base_url: Option<String>,
+    /// Posts only when an alert fires instead of after every run.
+    pub only_alerts: bool,
+    /// Largest acceptable absolute value of each metric, by name, e.g. "MAPE".
+    pub alerts: BTreeMap<String, f64>,
+    /// Rules checked against each run's data, see [`Rule`]; none by default.
+    pub rules: Vec<Rule>,
+}
+
+impl Notify {
+    /// Describes every metric of `report` over its limit in `alerts`, in the order of
+    /// the report, then every alert the run's rules raised.
+    pub fn fired(&self, report: &PipelineReport) -> Vec<String> {
+        report
+            .metrics
+            .iter()
+            .filter_map(|(name, value)| {
+                let limit = self.alerts.get(name)?;
+                (value.abs() > *limit)
+                    .then(|| format!("{} is {:.2}, over {:.2}", name, value, limit))
+            })
+            .chain(
+                report
+                    .alerts
+                    .iter()
+                    .map(|alert| format!("{}: {}", alert.rule, alert.message)),
+            )
+            .collect()
+    }
+
+    /// Builds the Slack message for `report`: `title`, any fired alerts, the metrics,
+    /// the charts written and the warnings, as Block Kit blocks with a plain text
+    /// fallback.
+    pub fn message(&self, title: &str, report: &PipelineReport) -> Value {
+        let fired = self.fired(report);
+        let mut lines: Vec<String> = Vec::new();
+        for alert in &fired {
+            lines.push(format!(":rotating_light: {}", alert));
+        }
+        for (name, value) in &report.metrics {
+            lines.push(format!("*{}*: {:.2}", name, value));
+        }
+        for dataset in &report.datasets {
+            lines.push(format!("{}: {} rows", dataset.name, dataset.rows));
+        }
+        for warning in &report.warnings {
+            lines.push(format!(":warning: {}", warning));
+        }
+        let text = if fired.is_empty() {
+            title.to_string()
+        } else {
+            format!("{}: {} alert(s)", title, fired.len())
+        };
+
+        let mut blocks: Vec<Value> = vec![json!({
+            "type": "header",
+            "text": { "type": "plain_text", "text": text },
+        })];
+        if !lines.is_empty() {
+            blocks.push(json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": lines.join("\n") },
+            }));
+        }
+        for chart in &report.artifacts {
+            let name = Path::new(&chart.path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| chart.path.clone());
+            match &self.chart_base_url {
+                Some(base_url) => blocks.push(json!({
+                    "type": "image",
+                    "image_url": format!("{}/{}", base_url.trim_end_matches('/'), name),
+                    "alt_text": name,
+                })),
+                None => blocks.push(json!({
+                    "type": "context",
+                    "elements": [{ "type": "mrkdwn", "text": format!("Chart: `{}`", chart.path) }],
+                })),
+            }
+        }
+        json!({ "text": text, "blocks": blocks })
+    }
+
+    /// Posts the summary of `report` to the Slack webhook, unless there is none or
+    /// `only_alerts` is set and no alert fired.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing whether a message was posted, or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::Fetch` if the webhook cannot be reached or answers with an
+    /// error; the webhook URL is left out of the error, since it is a secret.
+    #[cfg(feature = "notify")]
+    pub fn send(&self, title: &str, report: &PipelineReport) -> Result<bool> {
+        let Some(webhook) = &self.slack_webhook else {
+            return Ok(false);
+        };
+        if self.only_alerts && self.fired(report).is_empty() {
+            return Ok(false);
+        }
+        let body = serde_json::to_vec(&self.message(title, report))
+            .map_err(|err| MugenError::InvalidArgument(err.to_string()))?;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        runtime
+            .block_on(async {
+                reqwest::Client::new()
+                    .post(webhook)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .timeout(std::time::Duration::from_secs(30))
+                    .body(body)
+                    .send()
+                    .await?
+                    .error_for_status()
+            })
+            .map_err(|source| MugenError::Fetch {
+                url: "Slack webhook".to_string(),
+                attempts: 1,
+                source: source.without_url(),
+            })?;
+        Ok(true)
+    }
+}
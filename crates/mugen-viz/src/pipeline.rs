@@ -0,0 +1,582 @@
+use std::ops::RangeInclusive;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime};
+use mugen_core::alert::{check_all, Alert, Rule};
+use mugen_core::data::{
+    filter_period, join_actual_forecast, process_pred, process_price, process_realtime,
+    process_truth, resample,
+};
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::{MugenError, Result};
+use mugen_core::fetch::Report;
+use mugen_core::source::DataSource;
+use mugen_core::zone::Zone;
+use polars::prelude::*;
+use tracing::info_span;
+
+use crate::graph::{draw_all, Graph, RenderedChart, Vintage};
+
+/// Turns a processed series into another, e.g. to downsample or filter it.
+type Transform = Box<dyn Fn(TimeSeriesDataset) -> Result<TimeSeriesDataset>>;
+
+/// Scores a forecast from actual load joined against it.
+type Metric = Box<dyn Fn(&TimeSeriesDataset) -> Result<f64>>;
+
+/// Builds a chart from the results of a run.
+type ChartBuilder = Box<dyn Fn(&Evaluation) -> Result<Box<dyn Graph>>>;
+
+/// A forecast evaluation for one zone, composed step by step and run with
+/// [`Pipeline::run`].
+///
+/// A run loads the actual load and the forecast for the period from their
+/// [`DataSource`]s, processes both into series, averaging real-time load per hour,
+/// checks the alert rules, applies every transform to each series in the order
+/// added, joins them, computes the metrics and draws the charts.
+///
+/// Every series is converted to the display timezone before the period is cut and
+/// the series are joined, so sources kept in different timezones line up; see
+/// [`Pipeline::display_timezone`]. Such sources are also loaded for the day on each
+/// side of the period, where they have it, to fill its edges.
+///
+/// # Examples
+///
+/// ```no_run
+/// use chrono::NaiveDate;
+/// use mugen_core::data::min_max_downsample;
+/// use mugen_core::fetch::Report;
+/// use mugen_core::metrics::mape;
+/// use mugen_core::synthetic::{Synthetic, SyntheticSource};
+/// use mugen_core::zone::Zone;
+/// use mugen_viz::graph::{Graph, ScatterGraph};
+/// use mugen_viz::pipeline::Pipeline;
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let evaluation = Pipeline::new()
+///     .source(SyntheticSource { synthetic: Synthetic::default(), report: Report::ActualLoad })
+///     .source(SyntheticSource { synthetic: Synthetic::default(), report: Report::LoadForecast })
+///     .period(
+///         NaiveDate::from_ymd_opt(2023, 12, 9).unwrap(),
+///         NaiveDate::from_ymd_opt(2023, 12, 10).unwrap(),
+///     )
+///     .region(Zone::LongIsland)
+///     .transform(|series| min_max_downsample(&series, 500))
+///     .metric("MAPE", mape)
+///     .chart(|evaluation| {
+///         Ok(Box::new(ScatterGraph {
+///             data: evaluation.joined.clone(),
+///             ..Default::default()
+///         }) as Box<dyn Graph>)
+///     })
+///     .run()?;
+/// println!("{:?}", evaluation.metrics);
+/// for warning in &evaluation.report()?.warnings {
+///     eprintln!("Warning: {}", warning);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct Pipeline {
+    truth: Option<Box<dyn DataSource>>,
+    forecast: Option<Box<dyn DataSource>>,
+    price: Option<Box<dyn DataSource>>,
+    period: Option<RangeInclusive<NaiveDate>>,
+    zone: Zone,
+    transforms: Vec<Transform>,
+    metrics: Vec<(String, Metric)>,
+    charts: Vec<ChartBuilder>,
+    day_ahead: bool,
+    display_timezone: Option<String>,
+    rules: Vec<Rule>,
+}
+
+/// What a [`Pipeline`] run produced.
+#[derive(Clone, Debug)]
+pub struct Evaluation {
+    /// The zone evaluated.
+    pub zone: Zone,
+    /// The processed and transformed actual load.
+    pub truth: TimeSeriesDataset,
+    /// The processed and transformed forecast.
+    pub forecast: TimeSeriesDataset,
+    /// `truth` joined against `forecast`, as returned by
+    /// [`mugen_core::data::join_actual_forecast`].
+    pub joined: TimeSeriesDataset,
+    /// Every metric by name, in the order added.
+    pub metrics: Vec<(String, f64)>,
+    /// Every chart drawn, in the order added; empty while charts are being built.
+    pub charts: Vec<RenderedChart>,
+    /// Problems with the data that did not stop the run, such as hours left out.
+    pub warnings: Vec<String>,
+    /// Every alert raised by the pipeline's rules, see [`Pipeline::rule`].
+    pub alerts: Vec<Alert>,
+    /// Day-ahead against same-day forecasts, if the run compared them, see
+    /// [`Pipeline::compare_day_ahead`].
+    pub day_ahead: Option<DayAheadComparison>,
+    /// The hourly zonal price, if the pipeline has a price source; not transformed.
+    pub price: Option<TimeSeriesDataset>,
+}
+
+/// The forecasts issued the day before each day against the updates issued on the
+/// day itself, to quantify what the intraday updates are worth.
+#[derive(Clone, Debug)]
+pub struct DayAheadComparison {
+    /// The processed and transformed forecasts issued the day before each day.
+    pub day_ahead: TimeSeriesDataset,
+    /// The processed and transformed forecasts issued on each day.
+    pub same_day: TimeSeriesDataset,
+    /// Every metric by name with its day-ahead and same-day value, in the order
+    /// added.
+    pub metrics: Vec<(String, f64, f64)>,
+}
+
+impl DayAheadComparison {
+    /// Returns how much closer to zero each metric got with the same-day update, by
+    /// name; negative values mean the update made the forecast worse.
+    pub fn improvement(&self) -> Vec<(String, f64)> {
+        self.metrics
+            .iter()
+            .map(|(name, day_ahead, same_day)| (name.clone(), day_ahead.abs() - same_day.abs()))
+            .collect()
+    }
+
+    /// Returns both forecasts as vintages for a [`crate::graph::VintageGraph`],
+    /// day-ahead first.
+    pub fn vintages(&self) -> Vec<Vintage> {
+        vec![
+            Vintage {
+                issued: "Day-ahead".to_string(),
+                forecast: self.day_ahead.clone(),
+            },
+            Vintage {
+                issued: "Same-day".to_string(),
+                forecast: self.same_day.clone(),
+            },
+        ]
+    }
+}
+
+/// What a run produced, without the data itself, for code that drives runs and
+/// inspects their results, e.g. to compare runs or decide whether to publish.
+#[derive(Clone, Debug, Default)]
+pub struct PipelineReport {
+    /// Every dataset the run worked with, in the order added.
+    pub datasets: Vec<DatasetSummary>,
+    /// Every metric by name, in the order added.
+    pub metrics: Vec<(String, f64)>,
+    /// Every chart written, in the order added.
+    pub artifacts: Vec<RenderedChart>,
+    /// Problems with the data that did not stop the run.
+    pub warnings: Vec<String>,
+    /// Rules that fired on the run's data, see [`mugen_core::alert`].
+    pub alerts: Vec<Alert>,
+}
+
+/// Size and time span of a dataset.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DatasetSummary {
+    /// Name of the dataset, as returned by [`TimeSeriesDataset::name`].
+    pub name: String,
+    /// Number of rows.
+    pub rows: usize,
+    /// Earliest timestamp, or `None` if there are no rows.
+    pub start: Option<NaiveDateTime>,
+    /// Latest timestamp, or `None` if there are no rows.
+    pub end: Option<NaiveDateTime>,
+}
+
+impl DatasetSummary {
+    /// Summarizes `dataset`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the time column of `dataset` is no longer a datetime.
+    pub fn of(dataset: &TimeSeriesDataset) -> Result<Self> {
+        let timestamps = dataset.timestamps()?;
+        let at = |ms: Option<i64>| {
+            ms.and_then(DateTime::from_timestamp_millis)
+                .map(|at| at.naive_utc())
+        };
+        Ok(DatasetSummary {
+            name: dataset.name().to_string(),
+            rows: dataset.height(),
+            start: at(timestamps.min()),
+            end: at(timestamps.max()),
+        })
+    }
+}
+
+impl Evaluation {
+    /// Summarizes the run: its three series, metrics, charts and warnings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a series' time column is no longer a datetime.
+    pub fn report(&self) -> Result<PipelineReport> {
+        Ok(PipelineReport {
+            datasets: vec![
+                DatasetSummary::of(&self.truth)?,
+                DatasetSummary::of(&self.forecast)?,
+                DatasetSummary::of(&self.joined)?,
+            ]
+            .into_iter()
+            .chain(self.price.as_ref().map(DatasetSummary::of).transpose()?)
+            .collect(),
+            metrics: self
+                .metrics
+                .iter()
+                .cloned()
+                .chain(self.day_ahead.iter().flat_map(|comparison| {
+                    comparison
+                        .metrics
+                        .iter()
+                        .flat_map(|(name, day_ahead, same_day)| {
+                            [
+                                (format!("{} (day-ahead)", name), *day_ahead),
+                                (format!("{} (same-day)", name), *same_day),
+                            ]
+                        })
+                }))
+                .collect(),
+            artifacts: self.charts.clone(),
+            warnings: self.warnings.clone(),
+            alerts: self.alerts.clone(),
+        })
+    }
+}
+
+impl Pipeline {
+    /// Starts an empty pipeline for the default zone.
+    pub fn new() -> Self {
+        Pipeline::default()
+    }
+
+    /// Loads the report `source` serves from it: actual load or the forecast.
+    ///
+    /// Real-time load counts as actual load and is averaged per hour to match the
+    /// forecast. Prices are optional and, likewise, averaged per hour into
+    /// [`Evaluation::price`]. A later source of the same kind replaces the earlier one.
+    pub fn source(mut self, source: impl DataSource + 'static) -> Self {
+        match source.report() {
+            Report::ActualLoad | Report::RealTimeLoad => self.truth = Some(Box::new(source)),
+            Report::LoadForecast => self.forecast = Some(Box::new(source)),
+            Report::DayAheadPrice | Report::RealTimePrice => self.price = Some(Box::new(source)),
+        }
+        self
+    }
+
+    /// Evaluates the days `from..=to`.
+    ///
+    /// Forecasts issued on those days are loaded; where several cover the same hour
+    /// the latest issue is kept.
+    pub fn period(mut self, from: NaiveDate, to: NaiveDate) -> Self {
+        self.period = Some(from..=to);
+        self
+    }
+
+    /// Evaluates `zone`.
+    pub fn region(mut self, zone: Zone) -> Self {
+        self.zone = zone;
+        self
+    }
+
+    /// Applies `transform` to the processed actual load and forecast, after the
+    /// transforms added before it.
+    pub fn transform(
+        mut self,
+        transform: impl Fn(TimeSeriesDataset) -> Result<TimeSeriesDataset> + 'static,
+    ) -> Self {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+
+    /// Computes `metric` over the joined series and reports it as `name`, e.g.
+    /// [`mugen_core::metrics::mape`].
+    pub fn metric(
+        mut self,
+        name: impl Into<String>,
+        metric: impl Fn(&TimeSeriesDataset) -> Result<f64> + 'static,
+    ) -> Self {
+        self.metrics.push((name.into(), Box::new(metric)));
+        self
+    }
+
+    /// Draws the chart `chart` builds from the run's series and metrics.
+    pub fn chart(
+        mut self,
+        chart: impl Fn(&Evaluation) -> Result<Box<dyn Graph>> + 'static,
+    ) -> Self {
+        self.charts.push(Box::new(chart));
+        self
+    }
+
+    /// Also evaluates, for every day of the period, the forecast issued the day before
+    /// against the one issued on the day, with the same transforms and metrics, in
+    /// [`Evaluation::day_ahead`].
+    ///
+    /// The forecast source is loaded one issue day at a time, so it must pick
+    /// forecasts by issue day as the file and HTTP sources do.
+    pub fn compare_day_ahead(mut self, compare: bool) -> Self {
+        self.day_ahead = compare;
+        self
+    }
+
+    /// Checks `rule` against the actual load and its join against the forecast,
+    /// before any transform, raising [`Evaluation::alerts`].
+    pub fn rule(mut self, rule: Rule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Shows every series in the IANA timezone `timezone`, e.g. "America/New_York" to
+    /// show a store kept in UTC in New York time, instead of the actual load
+    /// source's timezone, see [`DataSource::timezone`].
+    ///
+    /// The period is the days `from..=to` in this timezone.
+    pub fn display_timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.display_timezone = Some(timezone.into());
+        self
+    }
+
+    /// Runs every step and returns what they produced.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InvalidArgument` if a source or the period is missing,
+    /// and otherwise the first error of a source, transform, metric or chart.
+    pub fn run(&self) -> Result<Evaluation> {
+        let missing = |what: &str| MugenError::InvalidArgument(format!("pipeline has no {}", what));
+        let truth_source = self
+            .truth
+            .as_ref()
+            .ok_or_else(|| missing("actual load source"))?;
+        let forecast_source = self
+            .forecast
+            .as_ref()
+            .ok_or_else(|| missing("forecast source"))?;
+        let period = self.period.clone().ok_or_else(|| missing("period"))?;
+        let (from, to) = (*period.start(), *period.end());
+        let shown: &str = self
+            .display_timezone
+            .as_deref()
+            .unwrap_or(truth_source.timezone());
+        let mut warnings: Vec<String> = Vec::new();
+
+        let (raw_truth, raw_forecast) = {
+            let _span = info_span!("load", zone = %self.zone).entered();
+            (
+                load(
+                    truth_source.as_ref(),
+                    shown,
+                    &period,
+                    self.zone,
+                    &mut warnings,
+                )?,
+                load(
+                    forecast_source.as_ref(),
+                    shown,
+                    &period,
+                    self.zone,
+                    &mut warnings,
+                )?,
+            )
+        };
+        let truth = match truth_source.report() {
+            Report::RealTimeLoad => {
+                resample(&process_realtime(raw_truth, self.zone)?, Duration::hours(1))?
+            }
+            _ => process_truth(raw_truth, self.zone)?,
+        };
+        let truth = shift(truth, truth_source.as_ref(), shown)?;
+        let mut truth = filter_period(truth, from, to)?;
+        let forecast = shift(
+            process_pred(raw_forecast, self.zone)?,
+            forecast_source.as_ref(),
+            shown,
+        )?;
+        let forecast = filter_period(forecast, from, to)?;
+        let time_column = [forecast.time_column().to_string()];
+        let issues = forecast.height();
+        let mut forecast = forecast.map_frame(|frame| {
+            Ok(frame.unique_stable(Some(&time_column), UniqueKeepStrategy::Last, None)?)
+        })?;
+        if forecast.height() < issues {
+            warnings.push(format!(
+                "{} forecast rows were superseded by a later issue for the same hour",
+                issues - forecast.height()
+            ));
+        }
+        let alerts: Vec<Alert> = if self.rules.is_empty() {
+            Vec::new()
+        } else {
+            check_all(
+                &self.rules,
+                &truth,
+                &join_actual_forecast(&truth, &forecast)?,
+            )?
+        };
+        for transform in &self.transforms {
+            truth = transform(truth)?;
+            forecast = transform(forecast)?;
+        }
+        let joined = join_actual_forecast(&truth, &forecast)?;
+        if joined.height() < truth.height() {
+            warnings.push(format!(
+                "{} of {} actual load rows have no forecast and were left out",
+                truth.height() - joined.height(),
+                truth.height()
+            ));
+        }
+
+        let metrics = self
+            .metrics
+            .iter()
+            .map(|(name, metric)| Ok((name.clone(), metric(&joined)?)))
+            .collect::<Result<Vec<(String, f64)>>>()?;
+        let day_ahead = if self.day_ahead {
+            Some(self.compare(forecast_source.as_ref(), &truth, &period, shown)?)
+        } else {
+            None
+        };
+        let price = match &self.price {
+            Some(source) => {
+                let raw = {
+                    let _span = info_span!("load", zone = %self.zone).entered();
+                    load(source.as_ref(), shown, &period, self.zone, &mut warnings)?
+                };
+                let price = shift(process_price(raw, self.zone)?, source.as_ref(), shown)?;
+                let hourly = resample(&price, Duration::hours(1))?;
+                Some(filter_period(hourly, from, to)?)
+            }
+            None => None,
+        };
+        let mut evaluation = Evaluation {
+            zone: self.zone,
+            truth,
+            forecast,
+            joined,
+            metrics,
+            charts: Vec::new(),
+            warnings,
+            alerts,
+            day_ahead,
+            price,
+        };
+        let graphs = self
+            .charts
+            .iter()
+            .map(|chart| chart(&evaluation))
+            .collect::<Result<Vec<Box<dyn Graph>>>>()?;
+        evaluation.charts = draw_all(&graphs)?;
+        Ok(evaluation)
+    }
+
+    /// Evaluates the forecasts issued the day before and on each day of `period`
+    /// against `truth`.
+    fn compare(
+        &self,
+        source: &dyn DataSource,
+        truth: &TimeSeriesDataset,
+        period: &RangeInclusive<NaiveDate>,
+        shown: &str,
+    ) -> Result<DayAheadComparison> {
+        let day_ahead = self.issued_before(source, period, 1, shown)?;
+        let same_day = self.issued_before(source, period, 0, shown)?;
+        let day_ahead_joined = join_actual_forecast(truth, &day_ahead)?;
+        let same_day_joined = join_actual_forecast(truth, &same_day)?;
+        let metrics = self
+            .metrics
+            .iter()
+            .map(|(name, metric)| {
+                Ok((
+                    name.clone(),
+                    metric(&day_ahead_joined)?,
+                    metric(&same_day_joined)?,
+                ))
+            })
+            .collect::<Result<Vec<(String, f64, f64)>>>()?;
+        Ok(DayAheadComparison {
+            day_ahead,
+            same_day,
+            metrics,
+        })
+    }
+
+    /// Loads, for every day of `period`, the forecast of that day issued `lead` days
+    /// before it, processed, shown in `shown` and transformed.
+    fn issued_before(
+        &self,
+        source: &dyn DataSource,
+        period: &RangeInclusive<NaiveDate>,
+        lead: i64,
+        shown: &str,
+    ) -> Result<TimeSeriesDataset> {
+        let mut frames: Option<TimeSeriesDataset> = None;
+        for day in period
+            .start()
+            .iter_days()
+            .take_while(|day| day <= period.end())
+        {
+            let issued = day - Duration::days(lead);
+            let raw = {
+                let _span = info_span!("load", zone = %self.zone, %issued).entered();
+                source.load(issued..=issued, Some(self.zone))?
+            };
+            let forecast = shift(process_pred(raw, self.zone)?, source, shown)?;
+            let forecast = filter_period(forecast, day, day)?;
+            frames = Some(match frames {
+                None => forecast,
+                Some(frames) => frames.map_frame(|frame| Ok(frame.vstack(forecast.frame())?))?,
+            });
+        }
+        let mut forecast = frames.ok_or_else(|| {
+            MugenError::InvalidArgument("pipeline period has no days".to_string())
+        })?;
+        for transform in &self.transforms {
+            forecast = transform(forecast)?;
+        }
+        Ok(forecast)
+    }
+}
+
+/// Loads `zone`'s data for `period` in the timezone `shown` from `source`: `period`
+/// itself and, if `source` is in another timezone, the day on each side where it has
+/// one, noting in `warnings` a side it lacks.
+fn load(
+    source: &dyn DataSource,
+    shown: &str,
+    period: &RangeInclusive<NaiveDate>,
+    zone: Zone,
+    warnings: &mut Vec<String>,
+) -> Result<DataFrame> {
+    let mut frame: DataFrame = source.load(period.clone(), Some(zone))?;
+    if source.timezone() == shown {
+        return Ok(frame);
+    }
+    for day in [
+        *period.start() - Duration::days(1),
+        *period.end() + Duration::days(1),
+    ] {
+        match source.load(day..=day, Some(zone)) {
+            Ok(edge) => {
+                frame.vstack_mut(&edge)?;
+            }
+            Err(_) => warnings.push(format!(
+                "no {} data for {}, so hours at the edges of the period in {} may be missing",
+                source.report().name(),
+                day,
+                shown
+            )),
+        }
+    }
+    Ok(frame)
+}
+
+/// Returns `dataset`, loaded from `source`, converted to the timezone `shown`.
+fn shift(
+    dataset: TimeSeriesDataset,
+    source: &dyn DataSource,
+    shown: &str,
+) -> Result<TimeSeriesDataset> {
+    dataset.with_timezone(source.timezone()).in_timezone(shown)
+}
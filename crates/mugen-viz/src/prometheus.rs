@@ -0,0 +1,187 @@
+use std::path::Path;
+use std::time::SystemTime;
+
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::Result;
+use mugen_core::metrics::{bias, mape, peak_error};
+use mugen_core::zone::Zone;
+
+/// MIME type of the Prometheus text exposition format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// One value in the Prometheus text format, such as the latest MAPE of a zone.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gauge {
+    /// Metric name, e.g. "mugen_forecast_mape_percent".
+    pub name: String,
+    /// What the metric measures, shown by Prometheus as its help text.
+    pub help: String,
+    /// Label names and values telling series of the same metric apart.
+    pub labels: Vec<(String, String)>,
+    /// Current value.
+    pub value: f64,
+}
+
+impl Gauge {
+    /// A gauge for `zone`, labeled with its ground truth spelling.
+    fn zonal(name: &str, help: &str, zone: Zone, value: f64) -> Self {
+        Gauge {
+            name: name.to_string(),
+            help: help.to_string(),
+            labels: vec![("zone".to_string(), zone.truth_name().to_string())],
+            value,
+        }
+    }
+}
+
+/// Describes the quality of the forecast in `joined` and the freshness of the files
+/// it was read from as gauges, for alerting on forecast degradation.
+///
+/// The gauges are the MAPE, bias and peak error of `joined` as computed by
+/// [`mugen_core::metrics`], the number of hours scored, and the seconds since the
+/// newest of `inputs` was modified. Missing files are ignored; with none found the
+/// age gauge is left out.
+///
+/// # Arguments
+///
+/// * `zone` - The zone `joined` belongs to, used as the `zone` label.
+/// * `joined` - Actual load joined against the forecast.
+/// * `inputs` - The files the data was read from.
+///
+/// # Returns
+///
+/// * A `Result` containing the gauges or a `MugenError`.
+///
+/// # Errors
+///
+/// Returns the errors of the metrics if `joined` lacks the joined columns; an empty
+/// `joined` gives NaN metrics.
+pub fn forecast_gauges(
+    zone: Zone,
+    joined: &TimeSeriesDataset,
+    inputs: &[&str],
+) -> Result<Vec<Gauge>> {
+    let mut gauges = vec![
+        Gauge::zonal(
+            "mugen_forecast_mape_percent",
+            "Mean absolute percentage error of the latest forecast against actual load.",
+            zone,
+            mape(joined)?,
+        ),
+        Gauge::zonal(
+            "mugen_forecast_bias_percent",
+            "Mean forecast minus mean actual load, as a percentage of the mean actual.",
+            zone,
+            bias(joined)?,
+        ),
+        Gauge::zonal(
+            "mugen_forecast_peak_error_percent",
+            "Forecast peak minus actual peak, as a percentage of the actual peak.",
+            zone,
+            peak_error(joined)?,
+        ),
+        Gauge::zonal(
+            "mugen_forecast_hours",
+            "Hours with both actual load and a forecast.",
+            zone,
+            joined.height() as f64,
+        ),
+    ];
+    let newest = inputs
+        .iter()
+        .filter_map(|path| Path::new(path).metadata().ok()?.modified().ok())
+        .max();
+    if let Some(newest) = newest {
+        let age = SystemTime::now()
+            .duration_since(newest)
+            .unwrap_or_default()
+            .as_secs_f64();
+        gauges.push(Gauge::zonal(
+            "mugen_data_age_seconds",
+            "Seconds since the newest input file was modified.",
+            zone,
+            age,
+        ));
+    }
+    Ok(gauges)
+}
+
+/// Describes the outcome of the last scheduled run as gauges, for alerting when runs
+/// stop or fail: when it finished, in seconds since the Unix epoch, and whether it
+/// succeeded, as 1 or 0.
+pub fn run_gauges(finished: SystemTime, succeeded: bool) -> Vec<Gauge> {
+    let finished = finished
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+    vec![
+        Gauge {
+            name: "mugen_last_run_timestamp_seconds".to_string(),
+            help: "When the last scheduled run finished, in seconds since the Unix epoch."
+                .to_string(),
+            labels: Vec::new(),
+            value: finished,
+        },
+        Gauge {
+            name: "mugen_last_run_success".to_string(),
+            help: "Whether the last scheduled run succeeded, 1 if it did and 0 if it failed."
+                .to_string(),
+            labels: Vec::new(),
+            value: if succeeded { 1.0 } else { 0.0 },
+        },
+    ]
+}
+
+/// Writes `gauges` in the Prometheus text exposition format, as served on a
+/// `/metrics` endpoint.
+///
+/// Each metric gets its help and type lines before its first series, so gauges of
+/// the same metric should be next to each other.
+pub fn exposition(gauges: &[Gauge]) -> String {
+    let mut text = String::new();
+    let mut previous: Option<&str> = None;
+    for gauge in gauges {
+        if previous != Some(gauge.name.as_str()) {
+            text.push_str(&format!(
+                "# HELP {} {}\n",
+                gauge.name,
+                escape(&gauge.help, false)
+            ));
+            text.push_str(&format!("# TYPE {} gauge\n", gauge.name));
+            previous = Some(&gauge.name);
+        }
+        let labels: Vec<String> = gauge
+            .labels
+            .iter()
+            .map(|(name, value)| format!("{}=\"{}\"", name, escape(value, true)))
+            .collect();
+        if labels.is_empty() {
+            text.push_str(&gauge.name);
+        } else {
+            text.push_str(&format!("{}{{{}}}", gauge.name, labels.join(",")));
+        }
+        text.push_str(&format!(" {}\n", number(gauge.value)));
+    }
+    text
+}
+
+/// Escapes backslashes and newlines, and double quotes in label values.
+fn escape(text: &str, quotes: bool) -> String {
+    let text = text.replace('\\', "\\\\").replace('\n', "\\n");
+    if quotes {
+        text.replace('"', "\\\"")
+    } else {
+        text
+    }
+}
+
+/// Formats `value` the way Prometheus spells special floats.
+fn number(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
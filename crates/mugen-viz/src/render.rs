@@ -0,0 +1,257 @@
+#[cfg(any(feature = "image-render", feature = "plotters"))]
+use std::io::Cursor;
+use std::str::FromStr;
+
+use charming::Chart;
+#[cfg(feature = "image-render")]
+use charming::{renderer::image_renderer::ImageRenderer, theme::Theme, EchartsError};
+use image::ImageFormat;
+#[cfg(any(feature = "image-render", feature = "plotters"))]
+use image::{DynamicImage, RgbaImage};
+use mugen_core::error::{MugenError, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::graph::GraphConfig;
+#[cfg(any(feature = "image-render", feature = "plotters"))]
+use crate::graph::Watermark;
+
+/// Script that pages showing ECharts options in a browser load ECharts from.
+#[cfg(any(feature = "html", feature = "notebook"))]
+pub(crate) const ECHARTS_CDN: &str = "https://cdn.jsdelivr.net/npm/echarts@5/dist/echarts.min.js";
+
+#[cfg(feature = "plotters")]
+mod plotters;
+
+#[cfg(feature = "plotters")]
+pub use self::plotters::PlottersRenderer;
+
+/// Turns a built chart into bytes, e.g. an encoded image.
+///
+/// Charts are defined once as ECharts options in [`crate::graph`]; a renderer decides
+/// how that definition becomes output, so the chart types do not depend on any one
+/// charting library. Pass a renderer to [`crate::graph::Graph::render_with`] directly,
+/// or pick a built-in one for every chart through [`GraphConfig::backend`].
+pub trait Renderer {
+    /// Renders `chart`, sized and styled by `config`, to bytes in `format`.
+    ///
+    /// Renderers that do not produce images may ignore `format`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the chart cannot be rendered or encoded.
+    fn render(&self, chart: &Chart, config: &GraphConfig, format: ImageFormat) -> Result<Vec<u8>>;
+
+    /// Returns the file extension of output rendered in `format`, e.g. "png".
+    fn extension(&self, format: ImageFormat) -> &'static str {
+        format.extensions_str().first().copied().unwrap_or("bin")
+    }
+}
+
+/// Renders with charming's server-side ECharts renderer, compositing image
+/// watermarks onto the pixels afterwards since ECharts cannot load local files.
+#[cfg(feature = "image-render")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EchartsRenderer;
+
+#[cfg(feature = "image-render")]
+impl Renderer for EchartsRenderer {
+    fn render(&self, chart: &Chart, config: &GraphConfig, format: ImageFormat) -> Result<Vec<u8>> {
+        let theme = if config.style.dark {
+            Theme::Dark
+        } else {
+            Theme::Default
+        };
+        let mut renderer = ImageRenderer::new(config.graph_width, config.graph_height).theme(theme);
+        let Some(Watermark::Image(_)) = &config.watermark else {
+            return renderer.render_format(format, chart).map_err(echarts_error);
+        };
+
+        let bytes = renderer
+            .render_format(ImageFormat::Png, chart)
+            .map_err(echarts_error)?;
+        let image = image::load_from_memory(&bytes)
+            .map_err(|e| MugenError::Render(e.to_string()))?
+            .to_rgba8();
+        encode_image(image, config, format)
+    }
+}
+
+/// Converts an error from charming's renderer.
+#[cfg(feature = "image-render")]
+fn echarts_error(error: EchartsError) -> MugenError {
+    let message = match error {
+        EchartsError::HtmlRenderingError(message)
+        | EchartsError::ImageRenderingError(message)
+        | EchartsError::JsRuntimeError(message)
+        | EchartsError::WasmError(message) => message,
+    };
+    MugenError::Render(message)
+}
+
+/// Writes the ECharts option instead of an image, without starting a JavaScript
+/// engine.
+///
+/// Useful headless, e.g. in CI or tests that compare chart definitions, and for
+/// handing charts to a web frontend. The output is the same as
+/// [`crate::graph::Graph::to_echarts_json`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SpecRenderer;
+
+impl Renderer for SpecRenderer {
+    fn render(&self, chart: &Chart, config: &GraphConfig, _format: ImageFormat) -> Result<Vec<u8>> {
+        Ok(echarts_option(chart, config)?.into_bytes())
+    }
+
+    fn extension(&self, _format: ImageFormat) -> &'static str {
+        "json"
+    }
+}
+
+/// The built-in renderers, selectable by name from config files and the command line.
+///
+/// Defaults to the first one compiled in of ECharts images, plotters images and the
+/// spec.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// [`EchartsRenderer`]: images through charming.
+    #[cfg(feature = "image-render")]
+    Echarts,
+    /// [`SpecRenderer`]: the ECharts option as JSON.
+    Spec,
+    /// [`PlottersRenderer`]: images through plotters, without a JavaScript engine.
+    #[cfg(feature = "plotters")]
+    Plotters,
+}
+
+impl Backend {
+    /// Returns the renderer this backend names.
+    pub fn renderer(self) -> Box<dyn Renderer> {
+        match self {
+            #[cfg(feature = "image-render")]
+            Backend::Echarts => Box::new(EchartsRenderer),
+            Backend::Spec => Box::new(SpecRenderer),
+            #[cfg(feature = "plotters")]
+            Backend::Plotters => Box::<PlottersRenderer>::default(),
+        }
+    }
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        #[cfg(feature = "image-render")]
+        return Backend::Echarts;
+        #[cfg(all(feature = "plotters", not(feature = "image-render")))]
+        return Backend::Plotters;
+        #[cfg(not(any(feature = "image-render", feature = "plotters")))]
+        return Backend::Spec;
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            #[cfg(feature = "image-render")]
+            "echarts" => Ok(Backend::Echarts),
+            #[cfg(not(feature = "image-render"))]
+            "echarts" => Err("the echarts backend needs the `image-render` feature".to_string()),
+            "spec" => Ok(Backend::Spec),
+            #[cfg(feature = "plotters")]
+            "plotters" => Ok(Backend::Plotters),
+            #[cfg(not(feature = "plotters"))]
+            "plotters" => Err("the plotters backend needs the `plotters` feature".to_string()),
+            _ => Err(format!(
+                "unknown backend \"{}\", expected one of: echarts, spec, plotters",
+                name
+            )),
+        }
+    }
+}
+
+/// Overlays the image watermark in `config`, if any, onto the bottom-right corner of
+/// `image` and encodes the result in `format`.
+#[cfg(any(feature = "image-render", feature = "plotters"))]
+fn encode_image(
+    mut image: RgbaImage,
+    config: &GraphConfig,
+    format: ImageFormat,
+) -> Result<Vec<u8>> {
+    if let Some(Watermark::Image(path)) = &config.watermark {
+        let logo = image::open(path)
+            .map_err(|source| MugenError::Image {
+                path: path.clone(),
+                source: Box::new(source),
+            })?
+            .to_rgba8();
+        let margin = config.graph_width / 50;
+        let x = image.width().saturating_sub(logo.width() + margin);
+        let y = image.height().saturating_sub(logo.height() + margin);
+        image::imageops::overlay(&mut image, &logo, x.into(), y.into());
+    }
+
+    // JPEG has no alpha channel, so flatten before encoding.
+    let image = match format {
+        ImageFormat::Jpeg => DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(image).to_rgb8()),
+        _ => DynamicImage::ImageRgba8(image),
+    };
+    let mut encoded = Cursor::new(Vec::new());
+    image
+        .write_to(&mut encoded, format)
+        .map_err(|e| MugenError::Render(e.to_string()))?;
+    Ok(encoded.into_inner())
+}
+
+/// Serializes `chart` to its ECharts option as strict JSON, with animation switched
+/// off in deterministic mode so every client renders the final frame straight away.
+///
+/// Value axes are labeled with [`crate::graph::NumberFormat::axis_template`] in place
+/// of the JavaScript formatter images are rendered with.
+pub(crate) fn echarts_option(chart: &Chart, config: &GraphConfig) -> Result<String> {
+    let json_error = |e: serde_json::Error| MugenError::Render(e.to_string());
+    let mut option: serde_json::Value = serde_json::to_value(chart).map_err(json_error)?;
+    let function: String = config.number_format.axis_function();
+    for key in ["xAxis", "yAxis"] {
+        let axes: Vec<&mut serde_json::Value> = match option.get_mut(key) {
+            Some(serde_json::Value::Array(axes)) => axes.iter_mut().collect(),
+            Some(axis) => vec![axis],
+            None => Vec::new(),
+        };
+        for axis in axes {
+            let Some(formatter) = axis.pointer_mut("/axisLabel/formatter") else {
+                continue;
+            };
+            if formatter
+                .as_str()
+                .is_some_and(|text| text.contains(&function))
+            {
+                *formatter = config.number_format.axis_template().into();
+            }
+        }
+    }
+    // charming's `Chart` has no animation setting, so it is added to the option.
+    if config.deterministic {
+        option["animation"] = false.into();
+    }
+    serde_json::to_string_pretty(&option).map_err(json_error)
+}
+
+/// Returns a script defining `localize(option)`, which gives the value axes of a
+/// JSON spec from [`echarts_option`] the label formatter of
+/// `config.number_format`, so pages show the same separators as the images.
+#[cfg(any(feature = "html", feature = "notebook"))]
+pub(crate) fn localize_script(config: &GraphConfig) -> String {
+    format!(
+        "function localize(option) {{\n  \
+         [].concat(option.xAxis || [], option.yAxis || []).forEach(axis => {{\n    \
+         if (axis.axisLabel && axis.axisLabel.formatter === {template}) {{\n      \
+         axis.axisLabel.formatter = {function};\n    \
+         }}\n  \
+         }});\n  \
+         return option;\n\
+         }}",
+        template = serde_json::to_string(&config.number_format.axis_template()).unwrap(),
+        function = config.number_format.axis_function(),
+    )
+}
@@ -0,0 +1,620 @@
+use std::fmt;
+use std::path::Path;
+use std::sync::Mutex;
+
+use charming::Chart;
+use image::{DynamicImage, ImageFormat, RgbImage};
+use mugen_core::error::{MugenError, Result};
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::style::{register_font, FontStyle};
+use serde_json::Value;
+
+use super::{encode_image, Renderer};
+use crate::graph::{GraphConfig, Watermark};
+
+/// Fonts tried, in order, when no font is set, covering common Linux, macOS and
+/// Windows installs.
+const FONT_PATHS: [&str; 7] = [
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationSans-Regular.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/Library/Fonts/Arial.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+];
+
+/// The ECharts palettes, so series keep their colors across backends.
+const LIGHT_PALETTE: [&str; 9] = [
+    "#5470c6", "#91cc75", "#fac858", "#ee6666", "#73c0de", "#3ba272", "#fc8452", "#9a60b4",
+    "#ea7ccc",
+];
+const DARK_PALETTE: [&str; 9] = [
+    "#4992ff", "#7cffb2", "#fddd60", "#ff6e76", "#58d9f9", "#05c091", "#ff8a45", "#8d48e3",
+    "#dd79ff",
+];
+
+/// Font file currently registered with plotters, which keeps fonts process-wide.
+static REGISTERED_FONT: Mutex<Option<String>> = Mutex::new(None);
+
+/// Renders with plotters, the renderer this crate started out with, for machines
+/// where the ECharts image renderer's JavaScript engine is unavailable.
+///
+/// Draws the main grid of line, scatter, bar and heatmap charts, with thresholds,
+/// day markers, the legend and watermarks, and gauges as a horizontal bar; pie and
+/// radar charts, styling details and secondary panels are left to the ECharts
+/// renderer. Text is drawn with a TrueType
+/// font read from `font`, or from the first of a few common system fonts found.
+#[derive(Clone, Debug, Default)]
+pub struct PlottersRenderer {
+    /// TrueType font file used for all text.
+    pub font: Option<String>,
+}
+
+impl PlottersRenderer {
+    /// Makes the configured font, or the first system font found, the one plotters
+    /// draws with. Font data stays loaded for the rest of the process.
+    fn register_font(&self) -> Result<()> {
+        let path = match &self.font {
+            Some(font) => font.clone(),
+            None => FONT_PATHS
+                .iter()
+                .find(|path| Path::new(path).exists())
+                .ok_or_else(|| {
+                    MugenError::Render(
+                        "no TrueType font found for the plotters backend; set \
+                         PlottersRenderer::font"
+                            .to_string(),
+                    )
+                })?
+                .to_string(),
+        };
+
+        let mut registered = REGISTERED_FONT
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        if registered.as_deref() == Some(path.as_str()) {
+            return Ok(());
+        }
+        let bytes = std::fs::read(&path)?;
+        register_font(
+            "sans-serif",
+            FontStyle::Normal,
+            Box::leak(bytes.into_boxed_slice()),
+        )
+        .map_err(|_| MugenError::Render(format!("\"{}\" is not a TrueType font", path)))?;
+        *registered = Some(path);
+        Ok(())
+    }
+}
+
+impl Renderer for PlottersRenderer {
+    fn render(&self, chart: &Chart, config: &GraphConfig, format: ImageFormat) -> Result<Vec<u8>> {
+        self.register_font()?;
+        let option = serde_json::to_value(chart).map_err(|e| MugenError::Render(e.to_string()))?;
+        let plot = Plot::from_option(&option)?;
+
+        let (width, height) = (config.graph_width, config.graph_height);
+        let mut buffer = vec![0u8; width as usize * height as usize * 3];
+        plot.draw(
+            BitMapBackend::with_buffer(&mut buffer, (width, height)).into_drawing_area(),
+            config,
+        )?;
+        let image = RgbImage::from_raw(width, height, buffer)
+            .ok_or_else(|| MugenError::Render("plotters buffer has the wrong size".to_string()))?;
+        encode_image(DynamicImage::ImageRgb8(image).to_rgba8(), config, format)
+    }
+}
+
+/// One series of the main grid, read from the ECharts option.
+struct PlotSeries {
+    kind: String,
+    name: Option<String>,
+    color: Option<RGBColor>,
+    /// Points in order; `None` breaks the line.
+    points: Vec<Option<(f64, f64)>>,
+}
+
+/// A gauge, drawn as a bar filled from `min` to `value`.
+struct PlotGauge {
+    value: f64,
+    min: f64,
+    max: f64,
+    label: String,
+}
+
+/// The parts of an ECharts option this backend draws.
+struct Plot {
+    title: String,
+    x_name: String,
+    y_name: String,
+    /// Labels of a category x-axis, drawn at x = 0, 1, 2, ...
+    categories: Vec<String>,
+    /// Labels of a category y-axis, drawn at y = 0, 1, 2, ...
+    y_categories: Vec<String>,
+    series: Vec<PlotSeries>,
+    /// Heatmap cells: x, y and value.
+    cells: Vec<(f64, f64, f64)>,
+    /// Values mapped to the ends of the heatmap color ramp.
+    cell_range: (f64, f64),
+    gauge: Option<PlotGauge>,
+    /// Vertical mark lines, at x positions.
+    x_marks: Vec<f64>,
+    /// Horizontal mark lines: height, label and color.
+    y_marks: Vec<(f64, Option<String>, Option<RGBColor>)>,
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+}
+
+impl Plot {
+    /// Picks the title, first axes and the series on them out of `option`.
+    fn from_option(option: &Value) -> Result<Self> {
+        let x_axis = first(&option["xAxis"]);
+        let y_axis = first(&option["yAxis"]);
+        let axis_labels = |axis: &Value| -> Vec<String> {
+            axis["data"]
+                .as_array()
+                .map(|labels| labels.iter().map(label_text).collect())
+                .unwrap_or_default()
+        };
+        let categories = axis_labels(x_axis);
+        let y_categories = axis_labels(y_axis);
+
+        let mut series = Vec::new();
+        let mut cells = Vec::new();
+        let mut gauge = None;
+        let mut x_marks = Vec::new();
+        let mut y_marks = Vec::new();
+        for item in option["series"].as_array().into_iter().flatten() {
+            let kind = item["type"].as_str().unwrap_or("line").to_string();
+            match kind.as_str() {
+                "line" | "scatter" | "bar" => {}
+                "heatmap" => {
+                    cells.extend(item["data"].as_array().into_iter().flatten().filter_map(
+                        |cell| Some((cell[0].as_f64()?, cell[1].as_f64()?, cell[2].as_f64()?)),
+                    ));
+                    continue;
+                }
+                "gauge" => {
+                    let reading = first(&item["data"]);
+                    gauge = Some(PlotGauge {
+                        value: reading["value"].as_f64().unwrap_or_default(),
+                        min: item["min"].as_f64().unwrap_or(0.0),
+                        max: item["max"].as_f64().unwrap_or(100.0),
+                        label: label_text(&reading["name"]),
+                    });
+                    continue;
+                }
+                _ => {
+                    return Err(MugenError::Render(format!(
+                        "the plotters backend cannot draw {} series",
+                        kind
+                    )))
+                }
+            }
+            let on_main_grid = [&item["xAxisIndex"], &item["yAxisIndex"]]
+                .iter()
+                .all(|index| index.as_f64().unwrap_or(0.0) == 0.0);
+            // Series with invisible lines only carry the stacked error band.
+            let hidden = item["lineStyle"]["opacity"].as_f64() == Some(0.0);
+            if !on_main_grid || hidden {
+                continue;
+            }
+
+            let line_color = parse_color(&item["markLine"]["lineStyle"]["color"]);
+            for mark in item["markLine"]["data"].as_array().into_iter().flatten() {
+                if let Some(height) = mark["yAxis"].as_f64() {
+                    let label = mark["name"].as_str().map(String::from);
+                    y_marks.push((height, label, line_color));
+                } else if let Some(x) = category_position(&mark["xAxis"], &categories) {
+                    x_marks.push(x);
+                }
+            }
+
+            let points: Vec<Option<(f64, f64)>> = item["data"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .enumerate()
+                .map(|(i, point)| data_point(i, point))
+                .collect();
+            if points.iter().any(Option::is_some) {
+                series.push(PlotSeries {
+                    kind,
+                    name: item["name"].as_str().map(String::from),
+                    color: parse_color(&item["lineStyle"]["color"])
+                        .or_else(|| parse_color(&item["itemStyle"]["color"])),
+                    points,
+                });
+            }
+        }
+
+        let visual_map = first(&option["visualMap"]);
+        let cell_values = cells.iter().map(|cell| cell.2);
+        let cell_range = (
+            visual_map["min"]
+                .as_f64()
+                .unwrap_or_else(|| cell_values.clone().fold(f64::INFINITY, f64::min)),
+            visual_map["max"]
+                .as_f64()
+                .unwrap_or_else(|| cell_values.fold(f64::NEG_INFINITY, f64::max)),
+        );
+
+        // Heatmap cells are centered on their category; lines run edge to edge.
+        let category_range = |count: usize| {
+            if cells.is_empty() {
+                (0.0, (count.max(2) - 1) as f64)
+            } else {
+                (-0.5, count as f64 - 0.5)
+            }
+        };
+        let (x_range, y_range) = if let Some(gauge) = &gauge {
+            ((gauge.min, gauge.max), (0.0, 1.0))
+        } else {
+            let xs = series
+                .iter()
+                .flat_map(|s| s.points.iter().flatten().map(|p| p.0));
+            let x_range = if categories.is_empty() {
+                padded_range(xs.collect(), None, None)
+            } else {
+                category_range(categories.len())
+            };
+            let ys = series
+                .iter()
+                .flat_map(|s| s.points.iter().flatten().map(|p| p.1))
+                .chain(y_marks.iter().map(|mark| mark.0))
+                .collect();
+            let y_range = if y_categories.is_empty() {
+                padded_range(ys, y_axis["min"].as_f64(), y_axis["max"].as_f64())
+            } else {
+                category_range(y_categories.len())
+            };
+            (x_range, y_range)
+        };
+
+        Ok(Plot {
+            title: label_text(&first(&option["title"])["text"]),
+            x_name: label_text(&x_axis["name"]),
+            y_name: label_text(&y_axis["name"]),
+            categories,
+            y_categories,
+            series,
+            cells,
+            cell_range,
+            gauge,
+            x_marks,
+            y_marks,
+            x_range,
+            y_range,
+        })
+    }
+
+    /// Draws the plot onto `root`, sized and colored by `config`.
+    fn draw<DB: DrawingBackend>(
+        &self,
+        root: DrawingArea<DB, plotters::coord::Shift>,
+        config: &GraphConfig,
+    ) -> Result<()>
+    where
+        DB::ErrorType: 'static,
+    {
+        let (background, foreground, palette) = if config.style.dark {
+            (RGBColor(16, 12, 42), RGBColor(238, 238, 238), DARK_PALETTE)
+        } else {
+            (WHITE, RGBColor(51, 51, 51), LIGHT_PALETTE)
+        };
+        let background = config
+            .style
+            .background
+            .as_deref()
+            .and_then(|color| parse_color(&Value::from(color)))
+            .unwrap_or(background);
+        let palette: Vec<RGBColor> = if config.style.palette.is_empty() {
+            palette
+                .iter()
+                .filter_map(|c| parse_color(&Value::from(*c)))
+                .collect()
+        } else {
+            config
+                .style
+                .palette
+                .iter()
+                .filter_map(|c| parse_color(&Value::from(c.as_str())))
+                .collect()
+        };
+        let font = |size: f64| {
+            ("sans-serif", config.font(size))
+                .into_font()
+                .color(&foreground)
+        };
+        let px = |size: f64| config.px(size).round().max(1.0) as u32;
+        let stroke = config.line(5.0).round().max(1.0) as u32;
+
+        root.fill(&background).map_err(draw_error)?;
+        let mut chart = ChartBuilder::on(&root)
+            .margin(px(60.0))
+            .caption(&self.title, font(100.0))
+            .x_label_area_size(px(200.0))
+            .y_label_area_size(px(320.0))
+            .build_cartesian_2d(
+                self.x_range.0..self.x_range.1,
+                self.y_range.0..self.y_range.1,
+            )
+            .map_err(draw_error)?;
+
+        let label = |categories: &[String], value: f64| {
+            if categories.is_empty() {
+                return config.number_format.format(value);
+            }
+            // Only whole positions have a category.
+            if (value - value.round()).abs() > 1e-6 {
+                return String::new();
+            }
+            categories
+                .get(value.round().max(0.0) as usize)
+                .cloned()
+                .unwrap_or_default()
+        };
+        // A gauge's value axis runs along x and its y-axis is only the bar's height.
+        let x_label = |x: &f64| match &self.gauge {
+            Some(_) => config.number_format.format(*x),
+            None => label(&self.categories, *x),
+        };
+        let y_label = |y: &f64| match &self.gauge {
+            Some(_) => String::new(),
+            None => label(&self.y_categories, *y),
+        };
+        chart
+            .configure_mesh()
+            .x_desc(self.x_name.as_str())
+            .y_desc(self.y_name.as_str())
+            .x_labels(8)
+            .y_labels(10)
+            .x_label_formatter(&x_label)
+            .y_label_formatter(&y_label)
+            .label_style(font(30.0))
+            .axis_desc_style(font(60.0))
+            .axis_style(foreground)
+            .bold_line_style(foreground.mix(0.15))
+            .light_line_style(TRANSPARENT)
+            .draw()
+            .map_err(draw_error)?;
+
+        let (low, high) = self.cell_range;
+        chart
+            .draw_series(self.cells.iter().map(|&(x, y, value)| {
+                let share = if high > low {
+                    (value - low) / (high - low)
+                } else {
+                    0.5
+                };
+                Rectangle::new(
+                    [(x - 0.5, y - 0.5), (x + 0.5, y + 0.5)],
+                    heat_color(share).filled(),
+                )
+            }))
+            .map_err(draw_error)?;
+
+        if let Some(gauge) = &self.gauge {
+            let color = palette.first().copied().unwrap_or(foreground);
+            chart
+                .draw_series([
+                    Rectangle::new(
+                        [(gauge.min, 0.3), (gauge.max, 0.7)],
+                        foreground.mix(0.15).filled(),
+                    ),
+                    Rectangle::new([(gauge.min, 0.3), (gauge.value, 0.7)], color.filled()),
+                ])
+                .map_err(draw_error)?;
+            chart
+                .draw_series(std::iter::once(Text::new(
+                    format!(
+                        "{}  {}",
+                        config.number_format.format(gauge.value),
+                        gauge.label
+                    ),
+                    (gauge.min, 0.85),
+                    font(50.0),
+                )))
+                .map_err(draw_error)?;
+        }
+
+        let marker_style = foreground.mix(0.4).stroke_width(px(3.0));
+        for &x in &self.x_marks {
+            chart
+                .draw_series(std::iter::once(PathElement::new(
+                    vec![(x, self.y_range.0), (x, self.y_range.1)],
+                    marker_style,
+                )))
+                .map_err(draw_error)?;
+        }
+        for (height, label, color) in &self.y_marks {
+            let style = color.unwrap_or(foreground).stroke_width(px(3.0));
+            let drawn = chart
+                .draw_series(std::iter::once(PathElement::new(
+                    vec![(self.x_range.0, *height), (self.x_range.1, *height)],
+                    style,
+                )))
+                .map_err(draw_error)?;
+            if let Some(label) = label {
+                drawn
+                    .label(label.as_str())
+                    .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 40, y)], style));
+            }
+        }
+
+        let mut labeled = !self.y_marks.iter().all(|mark| mark.1.is_none());
+        for (i, series) in self.series.iter().enumerate() {
+            let color = series
+                .color
+                .or_else(|| palette.get(i % palette.len().max(1)).copied())
+                .unwrap_or(foreground);
+            let style = color.stroke_width(stroke);
+            let drawn = match series.kind.as_str() {
+                "scatter" => chart.draw_series(
+                    series
+                        .points
+                        .iter()
+                        .flatten()
+                        .map(|&point| Circle::new(point, px(7.0), color.filled())),
+                ),
+                "bar" => {
+                    let base = 0f64.clamp(self.y_range.0, self.y_range.1);
+                    chart.draw_series(series.points.iter().flatten().map(|&(x, y)| {
+                        Rectangle::new([(x - 0.4, base), (x + 0.4, y)], color.filled())
+                    }))
+                }
+                _ => chart.draw_series(
+                    segments(&series.points)
+                        .into_iter()
+                        .map(|segment| PathElement::new(segment, style)),
+                ),
+            }
+            .map_err(draw_error)?;
+            if let Some(name) = &series.name {
+                labeled = true;
+                drawn
+                    .label(name.as_str())
+                    .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 40, y)], style));
+            }
+        }
+
+        if labeled {
+            chart
+                .configure_series_labels()
+                .position(SeriesLabelPosition::UpperLeft)
+                .label_font(font(40.0))
+                .background_style(background.mix(0.8))
+                .border_style(foreground)
+                .draw()
+                .map_err(draw_error)?;
+        }
+
+        if let Some(Watermark::Text(text)) = &config.watermark {
+            let (width, height) = root.dim_in_pixel();
+            let margin = (width / 33) as i32;
+            root.draw(&Text::new(
+                text.as_str(),
+                (width as i32 - margin, height as i32 - margin),
+                ("sans-serif", config.font(80.0))
+                    .into_font()
+                    .color(&foreground.mix(0.3))
+                    .pos(Pos::new(HPos::Right, VPos::Bottom)),
+            ))
+            .map_err(draw_error)?;
+        }
+        root.present().map_err(draw_error)
+    }
+}
+
+/// Returns the first element of an ECharts component list, or the component itself
+/// if it is not a list.
+fn first(component: &Value) -> &Value {
+    match component {
+        Value::Array(items) => items.first().unwrap_or(&Value::Null),
+        _ => component,
+    }
+}
+
+/// Returns a label as text, whether it is a string, a number or `{"value": ...}`.
+fn label_text(label: &Value) -> String {
+    match label {
+        Value::String(text) => text.clone(),
+        Value::Object(object) => object.get("value").map(label_text).unwrap_or_default(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Returns the x position of a mark line on a category or value axis.
+fn category_position(value: &Value, categories: &[String]) -> Option<f64> {
+    match value {
+        Value::String(label) => categories
+            .iter()
+            .position(|category| category == label)
+            .map(|i| i as f64),
+        other => other.as_f64(),
+    }
+}
+
+/// Reads the `index`-th data item of a series: a bare value on a category axis, an
+/// `[x, y]` pair or `{"value": ...}`. Gaps such as "-" read as `None`.
+fn data_point(index: usize, item: &Value) -> Option<(f64, f64)> {
+    match item {
+        Value::Number(y) => Some((index as f64, y.as_f64()?)),
+        Value::Array(pair) => Some((pair.first()?.as_f64()?, pair.get(1)?.as_f64()?)),
+        Value::Object(object) => data_point(index, object.get("value")?),
+        _ => None,
+    }
+}
+
+/// Splits points into the runs between gaps, each drawn as its own line.
+fn segments(points: &[Option<(f64, f64)>]) -> Vec<Vec<(f64, f64)>> {
+    points
+        .split(Option::is_none)
+        .filter(|run| !run.is_empty())
+        .map(|run| run.iter().flatten().copied().collect())
+        .collect()
+}
+
+/// Spans `values` with 5% padding on each side, unless the axis fixes a bound. An
+/// empty chart spans 0 to 1.
+fn padded_range(values: Vec<f64>, min: Option<f64>, max: Option<f64>) -> (f64, f64) {
+    let low = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let high = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if !low.is_finite() || !high.is_finite() {
+        return (min.unwrap_or(0.0), max.unwrap_or(1.0));
+    }
+    let padding = ((high - low) * 0.05).max(1.0);
+    (min.unwrap_or(low - padding), max.unwrap_or(high + padding))
+}
+
+/// Parses the CSS colors charts use: `#rgb`, `#rrggbb` and a few names.
+fn parse_color(color: &Value) -> Option<RGBColor> {
+    let color = color.as_str()?.trim().to_ascii_lowercase();
+    if let Some(hex) = color.strip_prefix('#') {
+        let digits: Vec<u8> = match hex.len() {
+            3 => hex
+                .chars()
+                .map(|c| c.to_digit(16).map(|d| d as u8 * 17))
+                .collect::<Option<_>>()?,
+            6 => (0..3)
+                .map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok())
+                .collect::<Option<_>>()?,
+            _ => return None,
+        };
+        return Some(RGBColor(digits[0], digits[1], digits[2]));
+    }
+    match color.as_str() {
+        "black" => Some(BLACK),
+        "white" => Some(WHITE),
+        "red" => Some(RGBColor(255, 0, 0)),
+        "green" => Some(RGBColor(0, 128, 0)),
+        "blue" => Some(RGBColor(0, 0, 255)),
+        "orange" => Some(RGBColor(255, 165, 0)),
+        "gray" | "grey" => Some(RGBColor(128, 128, 128)),
+        _ => None,
+    }
+}
+
+/// Returns the color of a heatmap cell `share` of the way up the value range, on
+/// ECharts' default yellow-to-red ramp.
+fn heat_color(share: f64) -> RGBColor {
+    const RAMP: [(f64, f64, f64); 3] = [
+        (246.0, 239.0, 166.0),
+        (216.0, 130.0, 115.0),
+        (191.0, 68.0, 76.0),
+    ];
+    let position = share.clamp(0.0, 1.0) * (RAMP.len() - 1) as f64;
+    let index = (position.floor() as usize).min(RAMP.len() - 2);
+    let t = position - index as f64;
+    let (from, to) = (RAMP[index], RAMP[index + 1]);
+    let mix = |a: f64, b: f64| (a + (b - a) * t).round() as u8;
+    RGBColor(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+}
+
+/// Wraps a plotters drawing error.
+fn draw_error(err: impl fmt::Display) -> MugenError {
+    MugenError::Render(err.to_string())
+}
@@ -0,0 +1,171 @@
+use std::path::Path;
+
+use chrono::Utc;
+use mugen_core::error::{MugenError, Result};
+
+use crate::graph::RenderedChart;
+use crate::pipeline::PipelineReport;
+
+/// File name of the HTML report written next to a run's charts.
+pub const REPORT_FILE: &str = "report.html";
+
+/// Builds a self-contained HTML page summarizing `report` for people to review: the
+/// alerts, the metrics, the datasets, the warnings and the charts.
+///
+/// Charts that are images are shown inline, others are linked.
+///
+/// # Arguments
+///
+/// * `title` - Heading and title of the page.
+/// * `report` - The run to summarize.
+/// * `source` - Returns the address the page refers to each chart by, e.g. its file
+///   name when the page sits next to the charts, or a `cid:` address in an email.
+///
+/// # Returns
+///
+/// * The page as a string.
+pub fn html(
+    title: &str,
+    report: &PipelineReport,
+    source: impl Fn(&RenderedChart) -> String,
+) -> String {
+    let mut page = String::new();
+    page.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    page.push_str(&format!("<title>{}</title>\n", escape(title)));
+    page.push_str(
+        "<style>body{font-family:sans-serif;margin:2em;color:#222}\
+         table{border-collapse:collapse;margin-bottom:1.5em}\
+         th,td{border:1px solid #ccc;padding:4px 10px;text-align:left}\
+         td.number{text-align:right}img{max-width:100%;margin-bottom:1.5em}</style>\n",
+    );
+    page.push_str("</head>\n<body>\n");
+    page.push_str(&format!("<h1>{}</h1>\n", escape(title)));
+    page.push_str(&format!(
+        "<p>Generated {}</p>\n",
+        Utc::now().format("%Y-%m-%d %H:%M UTC")
+    ));
+
+    if !report.alerts.is_empty() {
+        page.push_str("<h2>Alerts</h2>\n<ul>\n");
+        for alert in &report.alerts {
+            page.push_str(&format!(
+                "<li><b>{}</b>: {}</li>\n",
+                escape(&alert.rule),
+                escape(&alert.message)
+            ));
+        }
+        page.push_str("</ul>\n");
+    }
+    if !report.metrics.is_empty() {
+        page.push_str("<h2>Metrics</h2>\n<table>\n<tr><th>Metric</th><th>Value</th></tr>\n");
+        for (name, value) in &report.metrics {
+            page.push_str(&format!(
+                "<tr><td>{}</td><td class=\"number\">{:.2}</td></tr>\n",
+                escape(name),
+                value
+            ));
+        }
+        page.push_str("</table>\n");
+    }
+    if !report.datasets.is_empty() {
+        page.push_str(
+            "<h2>Data</h2>\n<table>\n\
+             <tr><th>Dataset</th><th>Rows</th><th>From</th><th>To</th></tr>\n",
+        );
+        for dataset in &report.datasets {
+            let time = |at: Option<chrono::NaiveDateTime>| {
+                at.map(|at| at.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default()
+            };
+            page.push_str(&format!(
+                "<tr><td>{}</td><td class=\"number\">{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape(&dataset.name),
+                dataset.rows,
+                time(dataset.start),
+                time(dataset.end)
+            ));
+        }
+        page.push_str("</table>\n");
+    }
+    if !report.warnings.is_empty() {
+        page.push_str("<h2>Warnings</h2>\n<ul>\n");
+        for warning in &report.warnings {
+            page.push_str(&format!("<li>{}</li>\n", escape(warning)));
+        }
+        page.push_str("</ul>\n");
+    }
+    if !report.artifacts.is_empty() {
+        page.push_str("<h2>Charts</h2>\n");
+        for chart in &report.artifacts {
+            let name = file_name(chart);
+            let source = escape(&source(chart));
+            if content_type(&chart.path).starts_with("image/") {
+                page.push_str(&format!(
+                    "<p><img src=\"{}\" alt=\"{}\"></p>\n",
+                    source,
+                    escape(&name)
+                ));
+            } else {
+                page.push_str(&format!(
+                    "<p><a href=\"{}\">{}</a></p>\n",
+                    source,
+                    escape(&name)
+                ));
+            }
+        }
+    }
+    page.push_str("</body>\n</html>\n");
+    page
+}
+
+/// Writes the [`html`] report of `report` to [`REPORT_FILE`] in `dir`, referring to
+/// the charts by file name, so it belongs in the directory holding them, and
+/// returns its path.
+///
+/// # Errors
+///
+/// Returns `MugenError::Write` if the file cannot be written.
+pub fn write_html(title: &str, report: &PipelineReport, dir: &str) -> Result<String> {
+    let path = Path::new(dir).join(REPORT_FILE);
+    let path = path.to_string_lossy().into_owned();
+    let page = html(title, report, file_name);
+    std::fs::write(&path, page).map_err(|source| MugenError::Write {
+        path: path.clone(),
+        source,
+    })?;
+    Ok(path)
+}
+
+/// Returns the file name of `chart`, or its whole path if it has none.
+pub(crate) fn file_name(chart: &RenderedChart) -> String {
+    Path::new(&chart.path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| chart.path.clone())
+}
+
+/// Returns the MIME type of a chart file by its extension.
+pub(crate) fn content_type(path: &str) -> &'static str {
+    let extension = Path::new(path)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "json" => "application/json",
+        "html" => "text/html",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Escapes the characters HTML gives a meaning to.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
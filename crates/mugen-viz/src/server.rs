@@ -0,0 +1,408 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use axum::body::Bytes;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{DefaultBodyLimit, Path, Query, State};
+use axum::http::{header, StatusCode, Uri};
+use axum::middleware::{self, Next};
+use axum::response::IntoResponse;
+use axum::routing::{self, MethodFilter};
+use axum::Router;
+use mugen_core::error::{MugenError, Result};
+use tokio::sync::{mpsc, Semaphore};
+use tracing::{info, warn};
+
+use crate::report::content_type;
+
+/// Largest request body read, e.g. a Grafana query; larger requests get a 413.
+const MAX_BODY: usize = 1 << 20;
+
+/// Most requests answered at once; further ones get a 503 until one finishes.
+const MAX_REQUESTS: usize = 64;
+
+/// Answers a request, e.g. by rendering the chart it names.
+type Handler = Arc<dyn Fn(&Request) -> Response + Send + Sync>;
+
+/// A request to the chart server: its path, parameters and body.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Request {
+    /// The path without the query, e.g. "/chart/nyc".
+    pub path: String,
+    /// The parameters named in the route's path, decoded, e.g. `("zone", "nyc")` for
+    /// "/chart/{zone}".
+    pub segments: Vec<(String, String)>,
+    /// The query parameters in order, decoded, e.g. `("from", "2023-12-01")`.
+    pub query: Vec<(String, String)>,
+    /// The body, e.g. the JSON of a POST; empty for a GET.
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Returns the first value of the query parameter `name`, if given and not empty.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, value)| key == name && !value.is_empty())
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the path parameter `name`, e.g. "nyc" for `zone` of "/chart/{zone}",
+    /// or `None` if the route does not name it.
+    pub fn segment(&self, name: &str) -> Option<&str> {
+        self.segments
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// The answer to a [`Request`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Response {
+    /// HTTP status code.
+    pub status: u16,
+    /// MIME type of the body.
+    pub content_type: String,
+    /// The body.
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// A 200 response with `body` of type `content_type`.
+    pub fn ok(content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        Response {
+            status: 200,
+            content_type: content_type.to_string(),
+            body: body.into(),
+        }
+    }
+
+    /// A 200 response with the contents of a file named `name`, its type picked
+    /// from the extension, e.g. "image/png" for "chart.png".
+    pub fn file(name: &str, body: impl Into<Vec<u8>>) -> Self {
+        Response::ok(content_type(name), body)
+    }
+
+    /// A 200 response with the JSON `value`.
+    pub fn json(value: &serde_json::Value) -> Self {
+        Response::ok("application/json", value.to_string())
+    }
+
+    /// A plain-text response with `status`, e.g. 404 and "Not found".
+    pub fn text(status: u16, message: &str) -> Self {
+        Response {
+            status,
+            content_type: "text/plain; charset=utf-8".to_string(),
+            body: message.as_bytes().to_vec(),
+        }
+    }
+
+    /// A 404 response.
+    pub fn not_found() -> Self {
+        Response::text(404, "Not found")
+    }
+
+    /// The response to a request that failed with `err`: 400 for bad parameters,
+    /// 404 for an unknown zone or missing data, and 500 otherwise.
+    pub fn error(err: &MugenError) -> Self {
+        let status = match err {
+            MugenError::InvalidArgument(_) | MugenError::Parse { .. } => 400,
+            MugenError::UnknownZone(_) | MugenError::NoData(_) => 404,
+            _ => 500,
+        };
+        Response::text(status, &err.to_string())
+    }
+}
+
+impl IntoResponse for Response {
+    fn into_response(self) -> axum::response::Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let headers = [
+            (header::CONTENT_TYPE, self.content_type),
+            (header::CACHE_CONTROL, "no-store".to_string()),
+        ];
+        (status, headers, self.body).into_response()
+    }
+}
+
+/// Connections subscribed to a WebSocket path, which every message broadcast on the
+/// channel is pushed to, e.g. new data points as they are ingested.
+///
+/// Clones share the same subscribers, so one clone can be handed to
+/// [`Routes::channel`] and another to the thread that broadcasts.
+#[derive(Clone, Debug, Default)]
+pub struct Channel {
+    queues: Arc<Mutex<Vec<mpsc::UnboundedSender<String>>>>,
+}
+
+impl Channel {
+    /// A channel without subscribers.
+    pub fn new() -> Self {
+        Channel::default()
+    }
+
+    /// Returns how many connections are subscribed.
+    pub fn subscribers(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Queues `text` for every subscriber as a text message, dropping those that
+    /// closed.
+    pub fn broadcast(&self, text: &str) {
+        self.lock()
+            .retain(|queue| queue.send(text.to_string()).is_ok());
+    }
+
+    /// Subscribes `socket` and sends it what is broadcast until it closes.
+    async fn subscribe(self, mut socket: WebSocket) {
+        let (queue, mut messages) = mpsc::unbounded_channel();
+        self.lock().push(queue);
+        while let Some(text) = messages.recv().await {
+            if socket.send(Message::Text(text.into())).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Locks the subscribers, carrying on if a broadcast panicked while holding them.
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<mpsc::UnboundedSender<String>>> {
+        self.queues
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The paths the chart server answers and how, served with [`serve`].
+///
+/// Paths name their parameters in braces, e.g. "/chart/{zone}", which handlers read
+/// with [`Request::segment`]. Other paths get a 404, and other methods on a known
+/// path a 405.
+#[derive(Clone, Debug, Default)]
+pub struct Routes {
+    router: Router,
+}
+
+impl Routes {
+    /// Routes without any path.
+    pub fn new() -> Self {
+        Routes::default()
+    }
+
+    /// Answers `GET` requests to `path` with `handle`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` is not a valid route or `GET` on it is already routed.
+    pub fn get<H>(self, path: &str, handle: H) -> Self
+    where
+        H: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        self.route(path, MethodFilter::GET, Arc::new(handle))
+    }
+
+    /// Answers `POST` requests to `path` with `handle`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` is not a valid route or `POST` on it is already routed.
+    pub fn post<H>(self, path: &str, handle: H) -> Self
+    where
+        H: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        self.route(path, MethodFilter::POST, Arc::new(handle))
+    }
+
+    /// Accepts WebSocket connections on `path`, subscribing them to `channel`'s
+    /// broadcasts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` is not a valid route or `GET` on it is already routed.
+    pub fn channel(self, path: &str, channel: &Channel) -> Self {
+        let channel: Channel = channel.clone();
+        let subscribe = move |upgrade: WebSocketUpgrade| {
+            let channel: Channel = channel.clone();
+            async move { upgrade.on_upgrade(move |socket| channel.subscribe(socket)) }
+        };
+        Routes {
+            router: self.router.route(path, routing::get(subscribe)),
+        }
+    }
+
+    /// Routes `method` requests to `path` to `handle`.
+    fn route(self, path: &str, method: MethodFilter, handle: Handler) -> Self {
+        let answer = move |uri: Uri,
+                           segments: Option<Path<Vec<(String, String)>>>,
+                           Query(query): Query<Vec<(String, String)>>,
+                           body: Bytes| {
+            let request = Request {
+                path: uri.path().to_string(),
+                segments: segments.map(|Path(segments)| segments).unwrap_or_default(),
+                query,
+                body: body.to_vec(),
+            };
+            respond(Arc::clone(&handle), request)
+        };
+        Routes {
+            router: self.router.route(path, routing::on(method, answer)),
+        }
+    }
+
+    /// The router answering these routes, within the server's limits.
+    fn into_router(self) -> Router {
+        self.router
+            .fallback(|| async { Response::not_found() })
+            .layer(DefaultBodyLimit::max(MAX_BODY))
+            .layer(middleware::from_fn_with_state(
+                Arc::new(Semaphore::new(MAX_REQUESTS)),
+                limit,
+            ))
+    }
+}
+
+/// Answers `request` with `handle` on a thread that may block, e.g. to render a
+/// chart, and logs the request if it failed.
+async fn respond(handle: Handler, request: Request) -> Response {
+    let path: String = request.path.clone();
+    let response: Response = tokio::task::spawn_blocking(move || handle(&request))
+        .await
+        .unwrap_or_else(|err| Response::text(500, &format!("The request failed: {}", err)));
+    if response.status >= 400 {
+        warn!(
+            path,
+            status = response.status,
+            message = %String::from_utf8_lossy(&response.body),
+            "request failed"
+        );
+    }
+    response
+}
+
+/// Passes `request` on while fewer than [`MAX_REQUESTS`] are being answered, and
+/// answers it with a 503 otherwise.
+async fn limit(
+    State(permits): State<Arc<Semaphore>>,
+    request: axum::extract::Request,
+    next: Next,
+) -> axum::response::Response {
+    match permits.try_acquire() {
+        Ok(_permit) => next.run(request).await,
+        Err(_) => Response::text(503, "Too many requests; try again shortly").into_response(),
+    }
+}
+
+/// Serves `routes` on `address` until the process is stopped, so dashboards can
+/// pull fresh charts and metrics over HTTP instead of reading the chart folder.
+///
+/// Each request is answered on a blocking thread of a tokio runtime, so a slow
+/// chart holds up no other request, up to 64 at once; further requests get a 503.
+/// Bodies over 1 MiB get a 413. Failed requests are logged through `tracing` with
+/// their status.
+///
+/// # Arguments
+///
+/// * `address` - The socket address to listen on, e.g. `127.0.0.1:8080`.
+/// * `routes` - The paths to answer and how.
+///
+/// # Errors
+///
+/// Returns an error if the runtime cannot start or the address cannot be bound.
+pub fn serve(address: &str, routes: Routes) -> Result<()> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind(address).await?;
+        let bound: SocketAddr = listener.local_addr()?;
+        info!(address = %bound, "serving charts");
+        axum::serve(listener, routes.into_router()).await?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    use super::*;
+
+    /// Serves `routes` on a free local port for as long as the returned runtime
+    /// lives, and returns that runtime and the port's address.
+    fn spawn(routes: Routes) -> (tokio::runtime::Runtime, SocketAddr) {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let listener = runtime
+            .block_on(tokio::net::TcpListener::bind("127.0.0.1:0"))
+            .unwrap();
+        let address: SocketAddr = listener.local_addr().unwrap();
+        runtime.spawn(async move { axum::serve(listener, routes.into_router()).await });
+        (runtime, address)
+    }
+
+    /// Sends `method` `target` to `address` and returns the whole answer.
+    fn fetch(address: SocketAddr, method: &str, target: &str) -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            method, target
+        )
+        .unwrap();
+        let mut answer = String::new();
+        stream.read_to_string(&mut answer).unwrap();
+        answer
+    }
+
+    #[test]
+    fn routes_pass_path_and_query_parameters() {
+        let routes = Routes::new().get("/chart/{zone}", |request: &Request| {
+            let answer = format!(
+                "{} {:?} {:?}",
+                request.segment("zone").unwrap_or_default(),
+                request.param("from"),
+                request.param("to"),
+            );
+            Response::text(200, &answer)
+        });
+        let (_runtime, address) = spawn(routes);
+
+        let answer = fetch(address, "GET", "/chart/new%20york?from=2023-12-01&to=");
+
+        assert!(answer.starts_with("HTTP/1.1 200"), "{}", answer);
+        assert!(answer.to_lowercase().contains("cache-control: no-store"));
+        assert!(
+            answer.ends_with("new york Some(\"2023-12-01\") None"),
+            "{}",
+            answer
+        );
+    }
+
+    #[test]
+    fn unknown_paths_and_methods_are_refused() {
+        let routes = Routes::new()
+            .get("/metrics", |_: &Request| Response::text(200, "up 1"))
+            .post("/query", |request: &Request| {
+                Response::ok("application/json", request.body.clone())
+            });
+        let (_runtime, address) = spawn(routes);
+
+        assert!(fetch(address, "GET", "/metrics").starts_with("HTTP/1.1 200"));
+        assert!(fetch(address, "POST", "/query").starts_with("HTTP/1.1 200"));
+        assert!(fetch(address, "GET", "/nowhere").starts_with("HTTP/1.1 404"));
+        assert!(fetch(address, "POST", "/metrics").starts_with("HTTP/1.1 405"));
+        assert!(fetch(address, "GET", "/query").starts_with("HTTP/1.1 405"));
+    }
+
+    #[test]
+    fn errors_answer_with_their_status() {
+        let status = |err: MugenError| Response::error(&err).status;
+        assert_eq!(status(MugenError::InvalidArgument("from".to_string())), 400);
+        assert_eq!(status(MugenError::UnknownZone("atlantis".to_string())), 404);
+        assert_eq!(status(MugenError::NoData("no forecast".to_string())), 404);
+        assert_eq!(status(MugenError::Render("no font".to_string())), 500);
+    }
+}
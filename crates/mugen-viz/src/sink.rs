@@ -0,0 +1,514 @@
+use std::path::Path;
+
+#[cfg(any(feature = "s3", feature = "gcs", feature = "azure"))]
+use std::time::Duration;
+
+use mugen_core::error::{MugenError, Result};
+#[cfg(any(feature = "s3", feature = "gcs", feature = "azure"))]
+use mugen_core::fetch::RetryPolicy;
+use serde::{Deserialize, Serialize};
+#[cfg(any(feature = "s3", feature = "gcs", feature = "azure"))]
+use tracing::warn;
+
+#[cfg(any(feature = "gcs", feature = "azure"))]
+use crate::report::content_type;
+use crate::s3::S3;
+
+/// Characters left as they are in object names; everything else is percent-encoded.
+const UNRESERVED: &[u8] = b"-._~/";
+
+/// Somewhere the outputs of a run are copied to once they are written, such as a
+/// shared directory or a cloud storage bucket.
+pub trait ArtifactSink {
+    /// Describes where copies go, for messages, e.g. "S3 bucket \"charts\"".
+    fn describe(&self) -> String;
+
+    /// Copies every file in `paths`.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing the path of every file copied and where its copy can
+    ///   be found, in order, or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be read or copied; files before it stay
+    /// copied.
+    fn store(&self, paths: &[&str]) -> Result<Vec<(String, String)>>;
+}
+
+/// One storage the outputs of each run are copied to, chosen by its `kind`, so each
+/// team can route charts to its own storage.
+///
+/// # Examples
+///
+/// ```toml
+/// [[sinks]]
+/// kind = "local"
+/// dir = "/mnt/shared/forecasts"
+///
+/// [[sinks]]
+/// kind = "s3"
+/// bucket = "forecast-charts"
+/// prefix = "nyiso/daily"
+///
+/// [[sinks]]
+/// kind = "gcs"
+/// bucket = "forecast-charts"
+///
+/// [[sinks]]
+/// kind = "azure"
+/// account = "forecasts"
+/// container = "charts"
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Sink {
+    /// A directory on this machine or a mounted share.
+    Local(LocalDir),
+    /// An Amazon S3 bucket or S3-compatible service.
+    S3(S3),
+    /// A Google Cloud Storage bucket.
+    Gcs(Gcs),
+    /// An Azure Blob Storage container.
+    Azure(Azure),
+}
+
+impl Sink {
+    /// Returns the sink as a trait object.
+    pub fn as_sink(&self) -> &dyn ArtifactSink {
+        match self {
+            Sink::Local(sink) => sink,
+            Sink::S3(sink) => sink,
+            Sink::Gcs(sink) => sink,
+            Sink::Azure(sink) => sink,
+        }
+    }
+}
+
+impl ArtifactSink for Sink {
+    fn describe(&self) -> String {
+        self.as_sink().describe()
+    }
+
+    fn store(&self, paths: &[&str]) -> Result<Vec<(String, String)>> {
+        self.as_sink().store(paths)
+    }
+}
+
+/// Copies files into a directory, created on demand.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocalDir {
+    /// Directory to copy into.
+    pub dir: String,
+}
+
+impl ArtifactSink for LocalDir {
+    fn describe(&self) -> String {
+        format!("directory \"{}\"", self.dir)
+    }
+
+    fn store(&self, paths: &[&str]) -> Result<Vec<(String, String)>> {
+        std::fs::create_dir_all(&self.dir).map_err(|source| MugenError::Write {
+            path: self.dir.clone(),
+            source,
+        })?;
+        let mut stored = Vec::new();
+        for path in paths {
+            let copy = Path::new(&self.dir).join(file_name(path));
+            let copy = copy.to_string_lossy().into_owned();
+            std::fs::copy(path, &copy).map_err(|source| MugenError::Write {
+                path: copy.clone(),
+                source,
+            })?;
+            stored.push((path.to_string(), copy));
+        }
+        Ok(stored)
+    }
+}
+
+/// Uploads files to a Google Cloud Storage bucket.
+///
+/// Requests are authorized with an OAuth access token read from the environment
+/// variable named by `token_env`, e.g. one printed by `gcloud auth
+/// print-access-token` or served by the metadata server.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Gcs {
+    /// Bucket to upload into.
+    pub bucket: String,
+    /// Folder within the bucket; empty for the top level.
+    pub prefix: String,
+    /// Name of the environment variable holding the access token.
+    pub token_env: String,
+}
+
+impl Default for Gcs {
+    fn default() -> Self {
+        Gcs {
+            bucket: String::new(),
+            prefix: String::new(),
+            token_env: "GOOGLE_OAUTH_ACCESS_TOKEN".to_string(),
+        }
+    }
+}
+
+impl ArtifactSink for Gcs {
+    fn describe(&self) -> String {
+        format!("GCS bucket \"{}\"", self.bucket)
+    }
+
+    fn store(&self, paths: &[&str]) -> Result<Vec<(String, String)>> {
+        #[cfg(feature = "gcs")]
+        {
+            let token = secret(&self.token_env, "uploading to GCS")?;
+            put_files(paths, &upload_retry(), |path, _| {
+                let url = format!(
+                    "https://storage.googleapis.com/{}/{}",
+                    self.bucket,
+                    encode(&object_key(&self.prefix, &file_name(path)))
+                );
+                Put {
+                    url: url.clone(),
+                    location: url,
+                    headers: vec![
+                        ("authorization".to_string(), format!("Bearer {}", token)),
+                        ("content-type".to_string(), content_type(path).to_string()),
+                    ],
+                }
+            })
+        }
+        #[cfg(not(feature = "gcs"))]
+        {
+            let _ = paths;
+            Err(missing_feature("gcs"))
+        }
+    }
+}
+
+/// Uploads files to an Azure Blob Storage container as block blobs.
+///
+/// Requests are authorized with a shared access signature (SAS) read from the
+/// environment variable named by `sas_env`, which must allow creating and writing
+/// blobs in the container.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Azure {
+    /// Storage account name.
+    pub account: String,
+    /// Container to upload into.
+    pub container: String,
+    /// Folder within the container; empty for the top level.
+    pub prefix: String,
+    /// Name of the environment variable holding the SAS token.
+    pub sas_env: String,
+    /// Base URL of the account, for the Azurite emulator or sovereign clouds;
+    /// `None` for the public cloud.
+    pub endpoint: Option<String>,
+}
+
+impl Default for Azure {
+    fn default() -> Self {
+        Azure {
+            account: String::new(),
+            container: String::new(),
+            prefix: String::new(),
+            sas_env: "AZURE_STORAGE_SAS_TOKEN".to_string(),
+            endpoint: None,
+        }
+    }
+}
+
+impl ArtifactSink for Azure {
+    fn describe(&self) -> String {
+        format!(
+            "Azure container \"{}\" of account \"{}\"",
+            self.container, self.account
+        )
+    }
+
+    fn store(&self, paths: &[&str]) -> Result<Vec<(String, String)>> {
+        #[cfg(feature = "azure")]
+        {
+            let sas = secret(&self.sas_env, "uploading to Azure")?;
+            let base = match &self.endpoint {
+                Some(endpoint) => endpoint.trim_end_matches('/').to_string(),
+                None => format!("https://{}.blob.core.windows.net", self.account),
+            };
+            put_files(paths, &upload_retry(), |path, _| {
+                let location = format!(
+                    "{}/{}/{}",
+                    base,
+                    self.container,
+                    encode(&object_key(&self.prefix, &file_name(path)))
+                );
+                Put {
+                    url: format!("{}?{}", location, sas.trim_start_matches('?')),
+                    location,
+                    headers: vec![
+                        ("content-type".to_string(), content_type(path).to_string()),
+                        ("x-ms-blob-type".to_string(), "BlockBlob".to_string()),
+                    ],
+                }
+            })
+        }
+        #[cfg(not(feature = "azure"))]
+        {
+            let _ = paths;
+            Err(missing_feature("azure"))
+        }
+    }
+}
+
+/// Returns the name of the object a file named `name` is stored as under `prefix`.
+#[cfg(any(feature = "s3", feature = "gcs", feature = "azure"))]
+pub(crate) fn object_key(prefix: &str, name: &str) -> String {
+    match prefix.trim_matches('/') {
+        "" => name.to_string(),
+        prefix => format!("{}/{}", prefix, name),
+    }
+}
+
+/// Returns the file name of `path`, or the whole path if it has none.
+pub(crate) fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Percent-encodes `key` for a URL path, keeping slashes.
+pub(crate) fn encode(key: &str) -> String {
+    key.bytes()
+        .map(|byte| {
+            if byte.is_ascii_alphanumeric() || UNRESERVED.contains(&byte) {
+                (byte as char).to_string()
+            } else {
+                format!("%{:02X}", byte)
+            }
+        })
+        .collect()
+}
+
+/// The error of a sink whose feature was left out of the build.
+#[cfg(not(all(feature = "s3", feature = "gcs", feature = "azure")))]
+pub(crate) fn missing_feature(feature: &str) -> MugenError {
+    MugenError::InvalidArgument(format!(
+        "this build cannot upload there; rebuild with the `{}` feature",
+        feature
+    ))
+}
+
+/// Reads the credential in the environment variable `variable`.
+#[cfg(any(feature = "gcs", feature = "azure"))]
+fn secret(variable: &str, purpose: &str) -> Result<String> {
+    std::env::var(variable)
+        .map_err(|_| MugenError::InvalidArgument(format!("{} needs {} set", purpose, variable)))
+}
+
+/// One upload request.
+#[cfg(any(feature = "s3", feature = "gcs", feature = "azure"))]
+pub(crate) struct Put {
+    /// URL to send the file to, including any credentials in the query.
+    pub url: String,
+    /// URL the file can be found at afterwards, safe to show.
+    pub location: String,
+    /// Headers to send, with lowercase names.
+    pub headers: Vec<(String, String)>,
+}
+
+/// Reads every file in `paths` and sends it with the request `request` builds from
+/// its path and contents, one at a time.
+///
+/// Uploads are retried as `retry` says, e.g. [`upload_retry`], when they fail for a reason that
+/// may pass, such as a server error, rate limiting, a timeout or a dropped
+/// connection; `request` is called again for every attempt, so signatures stay
+/// fresh. Other client errors, such as a refused credential, fail at once.
+///
+/// # Errors
+///
+/// Returns `MugenError::InFile` if a file cannot be read and `MugenError::Fetch`,
+/// naming only the location, if the last attempt at an upload fails.
+#[cfg(any(feature = "s3", feature = "gcs", feature = "azure"))]
+pub(crate) fn put_files(
+    paths: &[&str],
+    retry: &RetryPolicy,
+    request: impl Fn(&str, &[u8]) -> Put,
+) -> Result<Vec<(String, String)>> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let client = reqwest::Client::new();
+    let mut stored = Vec::new();
+    for path in paths {
+        let body = std::fs::read(path).map_err(|source| MugenError::InFile {
+            path: path.to_string(),
+            source: Box::new(source.into()),
+        })?;
+        let mut attempt: u32 = 1;
+        let location = loop {
+            let put = request(path, &body);
+            let sent = runtime.block_on(async {
+                let mut builder = client.put(&put.url).timeout(retry.timeout);
+                // The client derives the host from the URL itself.
+                for (name, value) in put.headers.iter().filter(|(name, _)| name != "host") {
+                    builder = builder.header(name.as_str(), value.as_str());
+                }
+                builder.body(body.clone()).send().await?.error_for_status()
+            });
+            match sent {
+                Ok(_) => break put.location,
+                Err(source) if transient(&source) && attempt < retry.attempts => {
+                    let wait: Duration = retry.backoff(attempt - 1);
+                    warn!(
+                        location = %put.location,
+                        attempt,
+                        error = %source.without_url(),
+                        ?wait,
+                        "retrying upload"
+                    );
+                    std::thread::sleep(wait);
+                    attempt += 1;
+                }
+                Err(source) => {
+                    return Err(MugenError::Fetch {
+                        url: put.location,
+                        attempts: attempt,
+                        source: source.without_url(),
+                    })
+                }
+            }
+        };
+        stored.push((path.to_string(), location));
+    }
+    Ok(stored)
+}
+
+/// How uploads are retried: as downloads are by default, with longer attempts since
+/// run outputs can be larger than a day's file.
+#[cfg(any(feature = "s3", feature = "gcs", feature = "azure"))]
+pub(crate) fn upload_retry() -> RetryPolicy {
+    RetryPolicy {
+        timeout: Duration::from_secs(120),
+        ..Default::default()
+    }
+}
+
+/// Returns whether trying an upload that failed with `error` again may help: server
+/// errors, rate limiting, timeouts and connection problems.
+#[cfg(any(feature = "s3", feature = "gcs", feature = "azure"))]
+fn transient(error: &reqwest::Error) -> bool {
+    match error.status() {
+        Some(status) => {
+            status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        }
+        None => !error.is_builder() && !error.is_redirect(),
+    }
+}
+
+#[cfg(all(test, any(feature = "s3", feature = "gcs", feature = "azure")))]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    use super::*;
+
+    /// Answers one request per status in `statuses`, in order, on a local port,
+    /// returning its base URL and the request lines it got.
+    fn server(statuses: &'static [u16]) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for status in statuses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                requests.push(line.trim_end().to_string());
+                let mut length: usize = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                reader.read_exact(&mut vec![0; length]).unwrap();
+                write!(
+                    reader.get_mut(),
+                    "HTTP/1.1 {} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                )
+                .unwrap();
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    /// Retries without waiting.
+    fn retry(attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            attempts,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// Writes a file to upload, returning its path.
+    fn file(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("mugen-sink-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, "Time Stamp,Load\n").unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    fn put(url: &str) -> Put {
+        Put {
+            url: format!("{}/report.csv", url),
+            location: format!("{}/report.csv", url),
+            headers: vec![("content-type".to_string(), "text/csv".to_string())],
+        }
+    }
+
+    #[test]
+    fn put_files_retries_server_errors() {
+        let (url, server) = server(&[503, 429, 200]);
+        let path = file("retried.csv");
+        let stored = put_files(&[&path], &retry(4), |_, _| put(&url)).unwrap();
+        assert_eq!(stored, vec![(path, format!("{}/report.csv", url))]);
+        assert_eq!(server.join().unwrap(), vec!["PUT /report.csv HTTP/1.1"; 3]);
+    }
+
+    #[test]
+    fn put_files_gives_up_after_the_last_attempt() {
+        let (url, server) = server(&[500, 502]);
+        let path = file("failing.csv");
+        match put_files(&[&path], &retry(2), |_, _| put(&url)) {
+            Err(MugenError::Fetch { attempts, .. }) => assert_eq!(attempts, 2),
+            other => panic!("expected a failed upload, got {:?}", other),
+        }
+        assert_eq!(server.join().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn put_files_does_not_retry_client_errors() {
+        let (url, server) = server(&[403]);
+        let path = file("refused.csv");
+        match put_files(&[&path], &retry(4), |_, _| put(&url)) {
+            Err(MugenError::Fetch { attempts, .. }) => assert_eq!(attempts, 1),
+            other => panic!("expected a refused upload, got {:?}", other),
+        }
+        assert_eq!(server.join().unwrap().len(), 1);
+    }
+}
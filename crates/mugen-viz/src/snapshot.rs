@@ -0,0 +1,311 @@
+use std::path::Path;
+
+use image::{ImageFormat, RgbaImage};
+use mugen_core::error::{MugenError, Result};
+
+use crate::graph::Graph;
+
+/// Environment variable that, when set, makes [`Snapshots::check`] rewrite golden
+/// files from the current output instead of comparing against them.
+pub const UPDATE_VAR: &str = "MUGEN_UPDATE_SNAPSHOTS";
+
+/// Checks charts against golden files stored under one directory.
+///
+/// Each chart is rendered with its configured backend and compared with
+/// `{dir}/{name}.{extension}`. Text output such as ECharts specs must match exactly.
+/// Images are decoded and compared pixel by pixel, so small differences in
+/// anti-aliasing or encoding between machines do not fail the check.
+///
+/// On a mismatch the new output is written next to the golden file as
+/// `{name}.new.{extension}` for inspection. Set [`UPDATE_VAR`] to accept new output
+/// as the golden files.
+///
+/// # Examples
+///
+/// ```no_run
+/// use chrono::NaiveDate;
+/// use mugen_core::data::{join_actual_forecast, process_pred, process_truth};
+/// use mugen_core::synthetic::Synthetic;
+/// use mugen_core::zone::Zone;
+/// use mugen_viz::graph::{GraphConfig, ScatterGraph};
+/// use mugen_viz::render::Backend;
+/// use mugen_viz::snapshot::Snapshots;
+///
+/// # fn main() -> mugen_core::error::Result<()> {
+/// let day = NaiveDate::from_ymd_opt(2023, 12, 9).unwrap();
+/// let truth = process_truth(Synthetic::default().truth(day..=day)?, Zone::NewYorkCity)?;
+/// let forecast = process_pred(Synthetic::default().forecast(day..=day)?, Zone::NewYorkCity)?;
+/// let graph = ScatterGraph {
+///     data: join_actual_forecast(&truth, &forecast)?,
+///     config: GraphConfig {
+///         deterministic: true,
+///         backend: Backend::Spec,
+///         ..Default::default()
+///     },
+///     ..Default::default()
+/// };
+/// Snapshots::new("tests/snapshots").check("scatter", &graph)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Snapshots {
+    /// Directory holding the golden files.
+    pub dir: String,
+    /// Image encoding requested from the renderer.
+    pub format: ImageFormat,
+    /// Largest difference in any color channel for a pixel to count as unchanged.
+    pub channel_tolerance: u8,
+    /// Largest fraction of pixels, from 0 to 1, allowed to change.
+    pub pixel_tolerance: f64,
+    /// Overwrites golden files with the current output instead of comparing.
+    pub update: bool,
+}
+
+impl Default for Snapshots {
+    fn default() -> Self {
+        Snapshots {
+            dir: "tests/snapshots".to_string(),
+            format: ImageFormat::Png,
+            channel_tolerance: 2,
+            pixel_tolerance: 0.001,
+            update: std::env::var_os(UPDATE_VAR).is_some(),
+        }
+    }
+}
+
+impl Snapshots {
+    /// Returns the default tolerances for golden files under `dir`.
+    pub fn new(dir: impl Into<String>) -> Self {
+        Snapshots {
+            dir: dir.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Renders `graph` and compares it with the golden file `name`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - File name of the golden file, without extension.
+    /// * `graph` - Chart to check; it must be configured as deterministic.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` that is `Ok` if the output matches or the golden file was
+    ///   updated, or a `MugenError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MugenError::InvalidArgument` if `graph` is not deterministic,
+    /// `MugenError::Snapshot` if the golden file is missing or differs beyond the
+    /// tolerances, and otherwise the error of rendering or file access.
+    pub fn check(&self, name: &str, graph: &dyn Graph) -> Result<()> {
+        let actual = render_snapshot(graph, self.format)?;
+        let extension = graph.config().backend.renderer().extension(self.format);
+        let path = format!("{}/{}.{}", self.dir, name, extension);
+        if self.update {
+            std::fs::create_dir_all(&self.dir).map_err(|source| MugenError::Write {
+                path: self.dir.clone(),
+                source,
+            })?;
+            return write(&path, &actual);
+        }
+
+        if !Path::new(&path).exists() {
+            return Err(MugenError::Snapshot {
+                path,
+                message: format!("no golden file; set {} to record it", UPDATE_VAR),
+            });
+        }
+        let expected = std::fs::read(&path)?;
+        let difference = if extension == "json" {
+            compare_text(&expected, &actual)
+        } else {
+            self.compare_images(&path, &expected, &actual)?
+        };
+        match difference {
+            None => Ok(()),
+            Some(message) => {
+                write(&format!("{}/{}.new.{}", self.dir, name, extension), &actual)?;
+                Err(MugenError::Snapshot { path, message })
+            }
+        }
+    }
+
+    /// Describes how the image `actual` differs from `expected` beyond the
+    /// tolerances, or returns `None` if it does not.
+    fn compare_images(&self, path: &str, expected: &[u8], actual: &[u8]) -> Result<Option<String>> {
+        let expected = decode(path, expected)?;
+        let actual = decode(path, actual)?;
+        if expected.dimensions() != actual.dimensions() {
+            return Ok(Some(format!(
+                "size changed from {}x{} to {}x{}",
+                expected.width(),
+                expected.height(),
+                actual.width(),
+                actual.height()
+            )));
+        }
+
+        let changed = expected
+            .pixels()
+            .zip(actual.pixels())
+            .filter(|(before, after)| {
+                before
+                    .0
+                    .iter()
+                    .zip(after.0.iter())
+                    .any(|(a, b)| a.abs_diff(*b) > self.channel_tolerance)
+            })
+            .count();
+        let total = (expected.width() as usize * expected.height() as usize).max(1);
+        let fraction = changed as f64 / total as f64;
+        if fraction > self.pixel_tolerance {
+            return Ok(Some(format!(
+                "{} of {} pixels ({:.3}%) changed, at most {:.3}% allowed",
+                changed,
+                total,
+                fraction * 100.0,
+                self.pixel_tolerance * 100.0
+            )));
+        }
+        Ok(None)
+    }
+}
+
+/// Renders `graph` to bytes in `format` for a snapshot.
+///
+/// # Errors
+///
+/// Returns `MugenError::InvalidArgument` if `graph` is not configured as
+/// deterministic, since its output could then differ between runs, and otherwise the
+/// error of rendering.
+pub fn render_snapshot(graph: &dyn Graph, format: ImageFormat) -> Result<Vec<u8>> {
+    if !graph.config().deterministic {
+        return Err(MugenError::InvalidArgument(
+            "snapshots need a deterministic chart".to_string(),
+        ));
+    }
+    graph.render_to_bytes(format)
+}
+
+/// Describes the first line where the text `actual` differs from `expected`, or
+/// returns `None` if they are identical.
+fn compare_text(expected: &[u8], actual: &[u8]) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+    let expected = String::from_utf8_lossy(expected);
+    let actual = String::from_utf8_lossy(actual);
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(before), Some(after)) if before == after => line += 1,
+            (before, after) => {
+                return Some(format!(
+                    "line {} changed from {:?} to {:?}",
+                    line,
+                    before.unwrap_or(""),
+                    after.unwrap_or("")
+                ))
+            }
+        }
+    }
+}
+
+/// Decodes the image `bytes` checked against the golden file `path`.
+fn decode(path: &str, bytes: &[u8]) -> Result<RgbaImage> {
+    Ok(image::load_from_memory(bytes)
+        .map_err(|source| MugenError::Image {
+            path: path.to_string(),
+            source: Box::new(source),
+        })?
+        .to_rgba8())
+}
+
+/// Writes `bytes` to `path`.
+fn write(path: &str, bytes: &[u8]) -> Result<()> {
+    std::fs::write(path, bytes).map_err(|source| MugenError::Write {
+        path: path.to_string(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use image::Rgba;
+
+    use super::*;
+
+    /// Encodes `image` as PNG.
+    fn png(image: &RgbaImage) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    /// A 100 by 100 gradient, so every pixel differs from its neighbours.
+    fn gradient() -> RgbaImage {
+        RgbaImage::from_fn(100, 100, |x, y| {
+            Rgba([(2 * x) as u8, (2 * y) as u8, 128, 255])
+        })
+    }
+
+    #[test]
+    fn images_within_the_tolerances_match() {
+        let snapshots = Snapshots::new("tests/snapshots");
+        let expected: Vec<u8> = png(&gradient());
+        let mut actual: RgbaImage = gradient();
+        // One pixel of 10,000 changed entirely, under the 0.1% allowed.
+        actual.put_pixel(50, 50, Rgba([255, 0, 0, 255]));
+        // Anti-aliasing noise within the channel tolerance.
+        for pixel in actual.pixels_mut().step_by(7) {
+            pixel.0[2] += 2;
+        }
+        let difference = snapshots
+            .compare_images("line.png", &expected, &png(&actual))
+            .unwrap();
+        assert_eq!(difference, None);
+    }
+
+    #[test]
+    fn images_beyond_the_tolerances_differ() {
+        let snapshots = Snapshots::new("tests/snapshots");
+        let expected: Vec<u8> = png(&gradient());
+        // A broad shift of every pixel by a few levels.
+        let mut shifted: RgbaImage = gradient();
+        for pixel in shifted.pixels_mut() {
+            pixel.0[2] += 5;
+        }
+        let difference = snapshots
+            .compare_images("line.png", &expected, &png(&shifted))
+            .unwrap();
+        assert!(difference.is_some_and(|message| message.starts_with("10000 of 10000 pixels")));
+
+        // Eleven changed pixels are one too many.
+        let mut spotted: RgbaImage = gradient();
+        for x in 0..11 {
+            spotted.put_pixel(x, 0, Rgba([255, 255, 255, 255]));
+        }
+        let difference = snapshots
+            .compare_images("line.png", &expected, &png(&spotted))
+            .unwrap();
+        assert!(difference.is_some());
+
+        let smaller: Vec<u8> = png(&RgbaImage::new(100, 99));
+        let difference = snapshots
+            .compare_images("line.png", &expected, &smaller)
+            .unwrap();
+        assert_eq!(
+            difference.as_deref(),
+            Some("size changed from 100x100 to 100x99")
+        );
+    }
+}
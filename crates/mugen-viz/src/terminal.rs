@@ -0,0 +1,184 @@
+use chrono::DateTime;
+
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::{MugenError, Result};
+
+const ACTUAL_COLOR: &str = "\x1b[36m";
+const FORECAST_COLOR: &str = "\x1b[32m";
+const OVERLAP_COLOR: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+/// Width of the y-axis labels, in characters.
+const LABEL_WIDTH: usize = 9;
+
+/// Dot bits of a braille cell, indexed by `[row][column]` within the 2x4 cell.
+const BRAILLE_DOTS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// A grid of braille cells, each holding 2x4 dots.
+struct BrailleLayer {
+    columns: usize,
+    rows: usize,
+    cells: Vec<u8>,
+}
+
+impl BrailleLayer {
+    fn new(columns: usize, rows: usize) -> Self {
+        BrailleLayer {
+            columns,
+            rows,
+            cells: vec![0; columns * rows],
+        }
+    }
+
+    /// Sets the dot at `(x, y)`, with `y` counted down from the top; dots off the grid are ignored.
+    fn set(&mut self, x: i64, y: i64) {
+        if x < 0 || y < 0 || x >= (self.columns * 2) as i64 || y >= (self.rows * 4) as i64 {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        self.cells[(y / 4) * self.columns + x / 2] |= BRAILLE_DOTS[y % 4][x % 2];
+    }
+
+    /// Draws a straight segment between two dots using Bresenham's algorithm.
+    fn line(&mut self, (x0, y0): (i64, i64), (x1, y1): (i64, i64)) {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = ((x1 - x0).signum(), (y1 - y0).signum());
+        let (mut x, mut y, mut err) = (x0, y0, dx + dy);
+        loop {
+            self.set(x, y);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let doubled = 2 * err;
+            if doubled >= dy {
+                err += dy;
+                x += sx;
+            }
+            if doubled <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    /// Plots `values` evenly across the full width, scaled so `min..=max` spans the
+    /// full height. Missing values leave a gap in the line.
+    fn plot(&mut self, values: &[Option<f64>], min: f64, max: f64) {
+        let last_x = (self.columns * 2 - 1) as f64;
+        let last_y = (self.rows * 4 - 1) as f64;
+        let steps = values.len().saturating_sub(1).max(1) as f64;
+        let dot = |i: usize, value: f64| {
+            let x = (i as f64 / steps * last_x).round() as i64;
+            let y = ((max - value) / (max - min) * last_y).round() as i64;
+            (x, y)
+        };
+
+        let mut previous: Option<(i64, i64)> = None;
+        for (i, value) in values.iter().enumerate() {
+            let current = value.map(|value| dot(i, value));
+            match (previous, current) {
+                (Some(from), Some(to)) => self.line(from, to),
+                (None, Some(to)) => self.set(to.0, to.1),
+                _ => {}
+            }
+            previous = current;
+        }
+    }
+}
+
+/// Renders actual against forecast load as a colored braille line chart for the terminal.
+///
+/// `joined` is the output of `join_actual_forecast`. The plot area is `columns`
+/// characters wide and `rows` lines tall, with the y-axis range on the left and the
+/// first and last timestamps underneath. Cells where both lines pass are highlighted.
+///
+/// # Errors
+///
+/// Returns an error if a required column is missing or there are no values to plot.
+pub fn braille_chart(joined: &TimeSeriesDataset, columns: usize, rows: usize) -> Result<String> {
+    let timestamps: Vec<i64> = joined.timestamps()?.into_no_null_iter().collect();
+    let actual: Vec<Option<f64>> = joined.values("Actual")?.into_iter().collect();
+    let forecast: Vec<Option<f64>> = joined.values("Forecast")?.into_iter().collect();
+
+    let present = actual.iter().chain(&forecast).flatten();
+    let min = present.clone().cloned().fold(f64::INFINITY, f64::min);
+    let max = present.cloned().fold(f64::NEG_INFINITY, f64::max);
+    if !min.is_finite() {
+        return Err(MugenError::NoData(
+            "no actual or forecast values to plot".to_string(),
+        ));
+    }
+    let (min, max) = if max > min {
+        (min, max)
+    } else {
+        (min - 1.0, max + 1.0)
+    };
+
+    let mut actual_layer = BrailleLayer::new(columns, rows);
+    actual_layer.plot(&actual, min, max);
+    let mut forecast_layer = BrailleLayer::new(columns, rows);
+    forecast_layer.plot(&forecast, min, max);
+
+    let mut output = String::new();
+    for row in 0..rows {
+        let label = match row {
+            0 => format!("{:.0}", max),
+            _ if row == rows - 1 => format!("{:.0}", min),
+            _ => String::new(),
+        };
+        output.push_str(&format!("{:>width$} ┤", label, width = LABEL_WIDTH));
+        for column in 0..columns {
+            let index = row * columns + column;
+            let (a, f) = (actual_layer.cells[index], forecast_layer.cells[index]);
+            let color = match (a, f) {
+                (0, 0) => {
+                    output.push(' ');
+                    continue;
+                }
+                (_, 0) => ACTUAL_COLOR,
+                (0, _) => FORECAST_COLOR,
+                _ => OVERLAP_COLOR,
+            };
+            let cell = char::from_u32(0x2800 + (a | f) as u32).unwrap_or(' ');
+            output.push_str(&format!("{}{}{}", color, cell, RESET));
+        }
+        output.push('\n');
+    }
+    output.push_str(&format!(
+        "{:>width$} └{}\n",
+        "",
+        "─".repeat(columns),
+        width = LABEL_WIDTH
+    ));
+
+    let first = timestamp_label(timestamps.first());
+    let last = timestamp_label(timestamps.last());
+    output.push_str(&format!(
+        "{:>width$}  {}{:>gap$}\n",
+        "",
+        first,
+        last,
+        width = LABEL_WIDTH,
+        gap = columns.saturating_sub(first.chars().count()),
+    ));
+    output.push_str(&format!(
+        "{:>width$}  {}⣿ Actual{}  {}⣿ Forecast{}  {}⣿ Both{}\n",
+        "",
+        ACTUAL_COLOR,
+        RESET,
+        FORECAST_COLOR,
+        RESET,
+        OVERLAP_COLOR,
+        RESET,
+        width = LABEL_WIDTH
+    ));
+    Ok(output)
+}
+
+/// Formats a millisecond timestamp for the x-axis, or an empty string if absent.
+fn timestamp_label(timestamp: Option<&i64>) -> String {
+    timestamp
+        .and_then(|ms| DateTime::from_timestamp_millis(*ms))
+        .map(|dt| dt.format("%m/%d %H:%M").to_string())
+        .unwrap_or_default()
+}
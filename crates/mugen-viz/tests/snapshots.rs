@@ -0,0 +1,231 @@
+//! Checks the ECharts spec of every chart type against the golden files in
+//! `tests/snapshots`, built from synthetic data so no NYISO files are needed. With
+//! the "plotters" feature the line chart is also checked as a rendered image.
+//!
+//! After an intended change to a chart, record its new spec with
+//! `MUGEN_UPDATE_SNAPSHOTS=1 cargo test -p mugen-viz --all-features --test snapshots`
+//! and review the diff of the golden files.
+
+use chrono::NaiveDate;
+use mugen_core::data::{
+    join_actual_forecast, process_pred, process_truth, process_truth_regions, zone_metrics,
+    zone_shares_at,
+};
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::Result;
+use mugen_core::synthetic::Synthetic;
+use mugen_core::zone::Zone;
+use mugen_viz::graph::{
+    ForecastDiffGraph, GaugeGraph, Graph, GraphConfig, HeatmapGraph, LineGraph, NumberFormat,
+    OverlayGraph, PieGraph, RadarGraph, ScatterGraph, StackedAreaGraph, Vintage, VintageGraph,
+};
+use mugen_viz::render::Backend;
+use mugen_viz::snapshot::Snapshots;
+use polars::prelude::*;
+
+/// The zone every single-zone chart is drawn for.
+const ZONE: Zone = Zone::NewYorkCity;
+
+/// Checks `graph` against the golden spec `name`.
+fn check(name: &str, graph: &dyn Graph) -> Result<()> {
+    Snapshots::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots")).check(name, graph)
+}
+
+/// Chart settings that give the same spec on every run.
+fn config(title: &str) -> GraphConfig {
+    GraphConfig {
+        title: title.to_string(),
+        deterministic: true,
+        backend: Backend::Spec,
+        ..Default::default()
+    }
+}
+
+/// Returns the first of the two days the charts cover.
+fn first_day() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2023, 12, 8).unwrap()
+}
+
+/// Returns the second of the two days the charts cover.
+fn last_day() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2023, 12, 9).unwrap()
+}
+
+/// Returns the raw actual load of every zone over both days.
+fn raw_truth() -> Result<DataFrame> {
+    Synthetic::default().truth(first_day()..=last_day())
+}
+
+/// Returns the raw forecast of every zone over both days, from `synthetic`.
+fn raw_forecast(synthetic: &Synthetic) -> Result<DataFrame> {
+    synthetic.forecast(first_day()..=last_day())
+}
+
+/// Returns the processed actual load and forecast of [`ZONE`].
+fn series() -> Result<(TimeSeriesDataset, TimeSeriesDataset)> {
+    Ok((
+        process_truth(raw_truth()?, ZONE)?,
+        process_pred(raw_forecast(&Synthetic::default())?, ZONE)?,
+    ))
+}
+
+#[test]
+fn line() -> Result<()> {
+    let (truth, forecast) = series()?;
+    let graph: LineGraph = LineGraph::builder()
+        .config(config("{region} {start_date}–{end_date}"))
+        .truth(truth)
+        .forecast(forecast)
+        .zone(ZONE)
+        .error_band(true)
+        .residual_panel(true)
+        .build()?;
+    check("line", &graph)
+}
+
+/// The line chart as the plotters backend draws it, compared as an image within
+/// the tolerances of [`Snapshots`]. The golden file was drawn with DejaVu Sans, the
+/// first font the backend looks for.
+#[cfg(feature = "plotters")]
+#[test]
+fn line_image() -> Result<()> {
+    let (truth, forecast) = series()?;
+    let graph: LineGraph = LineGraph::builder()
+        .config(GraphConfig {
+            backend: Backend::Plotters,
+            graph_width: 800,
+            graph_height: 450,
+            ..config("{region} {start_date}–{end_date}")
+        })
+        .truth(truth)
+        .forecast(forecast)
+        .zone(ZONE)
+        .build()?;
+    check("line", &graph)
+}
+
+#[test]
+fn overlay() -> Result<()> {
+    let zones: Vec<Zone> = vec![Zone::NewYorkCity, Zone::LongIsland];
+    let graph = OverlayGraph {
+        config: config("Downstate"),
+        data: process_truth_regions(raw_truth()?, &zones)?,
+        zones,
+        ..Default::default()
+    };
+    check("overlay", &graph)
+}
+
+#[test]
+fn vintage() -> Result<()> {
+    let (truth, forecast) = series()?;
+    let later: Synthetic = Synthetic {
+        seed: 7,
+        ..Default::default()
+    };
+    let graph = VintageGraph {
+        config: config("Vintages"),
+        data: truth,
+        vintages: vec![
+            Vintage {
+                issued: "Day ahead".to_string(),
+                forecast,
+            },
+            Vintage {
+                issued: "Same day".to_string(),
+                forecast: process_pred(raw_forecast(&later)?, ZONE)?,
+            },
+        ],
+        zone: ZONE,
+    };
+    check("vintage", &graph)
+}
+
+#[test]
+fn forecast_diff() -> Result<()> {
+    let (_, forecast) = series()?;
+    let later: Synthetic = Synthetic {
+        seed: 7,
+        ..Default::default()
+    };
+    let graph = ForecastDiffGraph {
+        config: config("Forecast difference"),
+        first: Vintage {
+            issued: "Day ahead".to_string(),
+            forecast,
+        },
+        second: Vintage {
+            issued: "Same day".to_string(),
+            forecast: process_pred(raw_forecast(&later)?, ZONE)?,
+        },
+        zone: ZONE,
+    };
+    check("forecast_diff", &graph)
+}
+
+#[test]
+fn stacked_area() -> Result<()> {
+    let zones: Vec<Zone> = vec![Zone::NewYorkCity, Zone::LongIsland, Zone::West];
+    let graph = StackedAreaGraph {
+        config: config("Zonal load"),
+        data: process_truth_regions(raw_truth()?, &zones)?,
+        zones,
+    };
+    check("stacked_area", &graph)
+}
+
+#[test]
+fn pie() -> Result<()> {
+    let at = first_day().and_hms_opt(18, 0, 0).unwrap();
+    let graph = PieGraph {
+        config: GraphConfig {
+            number_format: NumberFormat::default().with_unit("MW"),
+            ..config("Zone shares")
+        },
+        data: zone_shares_at(raw_truth()?, at)?,
+        ..Default::default()
+    };
+    check("pie", &graph)
+}
+
+#[test]
+fn scatter() -> Result<()> {
+    let (truth, forecast) = series()?;
+    let graph = ScatterGraph {
+        config: config("Actual vs forecast"),
+        data: join_actual_forecast(&truth, &forecast)?,
+    };
+    check("scatter", &graph)
+}
+
+#[test]
+fn heatmap() -> Result<()> {
+    let (truth, forecast) = series()?;
+    let graph = HeatmapGraph {
+        config: config("Errors by hour"),
+        data: join_actual_forecast(&truth, &forecast)?,
+    };
+    check("heatmap", &graph)
+}
+
+#[test]
+fn radar() -> Result<()> {
+    let graph = RadarGraph {
+        config: config("Zone errors"),
+        data: zone_metrics(raw_truth()?, raw_forecast(&Synthetic::default())?)?,
+    };
+    check("radar", &graph)
+}
+
+#[test]
+fn gauge() -> Result<()> {
+    let (truth, forecast) = series()?;
+    let graph = GaugeGraph {
+        config: config("Latest load"),
+        data: truth,
+        forecast,
+        zone: ZONE,
+        capacity: Some(12000.0),
+    };
+    check("gauge", &graph)
+}
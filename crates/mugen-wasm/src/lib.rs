@@ -0,0 +1,196 @@
+//! NYISO load vs forecast charts and metrics for the browser.
+//!
+//! Compiles `mugen-core`'s data preparation and `mugen-viz`'s chart definitions to
+//! WebAssembly, so a web front-end builds the same ECharts options as the
+//! `mugen_park` command-line tool and server, and draws them with ECharts itself.
+//! No images are rendered here: the browser is handed the chart spec as JSON.
+//!
+//! Build it with [wasm-pack](https://rustwasm.github.io/wasm-pack/):
+//!
+//! ```text
+//! wasm-pack build crates/mugen-wasm --target web
+//! ```
+//!
+//! and call it with the text of the NYISO CSVs:
+//!
+//! ```js
+//! import init, { lineChart, metrics } from "./pkg/mugen_wasm.js";
+//!
+//! await init();
+//! const actual = await (await fetch("20231210palIntegrated.csv")).text();
+//! const forecast = await (await fetch("20231210isolf.csv")).text();
+//! echarts.init(element).setOption(JSON.parse(lineChart(actual, forecast, "nyc", "")));
+//! console.log(JSON.parse(metrics(actual, forecast, "nyc")).mape);
+//! ```
+
+use mugen_core::data::{join_actual_forecast, process_pred, process_truth, read_csv_text};
+use mugen_core::dataset::TimeSeriesDataset;
+use mugen_core::error::{MugenError, Result};
+use mugen_core::metrics::{bias, mape, peak_error};
+use mugen_core::zone::Zone;
+use mugen_viz::config::graph_config;
+use mugen_viz::graph::{Graph, GraphConfig, LineGraph};
+use wasm_bindgen::prelude::*;
+
+/// Builds the ECharts option of `zone`'s actual vs forecast load chart, as the
+/// `mugen_park serve` command draws it, with the forecast error shaded.
+///
+/// # Arguments
+///
+/// * `actual_csv` - Text of one or more `palIntegrated` CSVs, with one header row.
+/// * `forecast_csv` - Text of an `isolf` CSV.
+/// * `zone` - The zone, spelled any way [`Zone`] parses, e.g. "nyc" or "N.Y.C.".
+/// * `config` - Chart settings as the JSON of a [`GraphConfig`], or "" for the
+///   defaults; fields left out keep their defaults.
+///
+/// # Returns
+///
+/// * The option as JSON, for `echarts.setOption`, or an error naming what was wrong.
+///
+/// # Errors
+///
+/// Fails if the zone is unknown, the config is not valid JSON, or a CSV cannot be
+/// parsed or lacks the zone's columns.
+#[wasm_bindgen(js_name = lineChart)]
+pub fn line_chart(
+    actual_csv: &str,
+    forecast_csv: &str,
+    zone: &str,
+    config: &str,
+) -> std::result::Result<String, JsError> {
+    let (zone, truth, forecast) = series(actual_csv, forecast_csv, zone)?;
+    let config: GraphConfig = match config.trim() {
+        "" => graph_config("{}")?,
+        text => graph_config(text)?,
+    };
+    let line_graph: LineGraph = LineGraph::builder()
+        .config(GraphConfig {
+            deterministic: true,
+            ..config.clone()
+        })
+        .truth(truth)
+        .forecast(forecast)
+        .zone(zone)
+        .title(config.labels.titles.served.as_str())
+        .error_band(true)
+        .max_points(2000)
+        .time_format("%m/%d %H:%M")
+        .build()?;
+    Ok(line_graph.to_echarts_json()?)
+}
+
+/// Scores `zone`'s forecast against its actual load over the hours that have both,
+/// as the server's `/metrics/{zone}.json` does.
+///
+/// # Arguments
+///
+/// * `actual_csv` - Text of one or more `palIntegrated` CSVs, with one header row.
+/// * `forecast_csv` - Text of an `isolf` CSV.
+/// * `zone` - The zone, spelled any way [`Zone`] parses.
+///
+/// # Returns
+///
+/// * JSON with the zone, the number of hours scored and the MAPE, bias and peak
+///   error, or an error naming what was wrong.
+///
+/// # Errors
+///
+/// Fails if the zone is unknown, a CSV cannot be parsed or lacks the zone's
+/// columns, or no hour has both an actual value and a forecast.
+#[wasm_bindgen]
+pub fn metrics(
+    actual_csv: &str,
+    forecast_csv: &str,
+    zone: &str,
+) -> std::result::Result<String, JsError> {
+    let (zone, truth, forecast) = series(actual_csv, forecast_csv, zone)?;
+    let joined: TimeSeriesDataset = join_actual_forecast(&truth, &forecast)?;
+    if joined.height() == 0 {
+        return Err(MugenError::NoData(format!(
+            "no hours with both actual load and a forecast for {}",
+            zone
+        ))
+        .into());
+    }
+    Ok(serde_json::json!({
+        "zone": zone,
+        "hours": joined.height(),
+        "mape": mape(&joined)?,
+        "bias": bias(&joined)?,
+        "peak_error": peak_error(&joined)?,
+    })
+    .to_string())
+}
+
+/// Returns the NYISO names of every zone, e.g. "N.Y.C.", in the order the charts
+/// list them.
+#[wasm_bindgen]
+pub fn zones() -> Vec<String> {
+    Zone::ALL.iter().map(Zone::to_string).collect()
+}
+
+/// Parses `zone` and reads its actual load and forecast from CSV text.
+fn series(
+    actual_csv: &str,
+    forecast_csv: &str,
+    zone: &str,
+) -> Result<(Zone, TimeSeriesDataset, TimeSeriesDataset)> {
+    let zone: Zone = zone.parse()?;
+    let truth = process_truth(read_csv_text("actual", actual_csv)?, zone)?;
+    let forecast = process_pred(read_csv_text("forecast", forecast_csv)?, zone)?;
+    Ok((zone, truth, forecast))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    /// A day of N.Y.C. actual load and forecast as NYISO publishes them.
+    fn csvs() -> (String, String) {
+        let mut actual =
+            String::from("\"Time Stamp\",\"Time Zone\",\"Name\",\"PTID\",\"Integrated Load\"\n");
+        let mut forecast = String::from("\"Time Stamp\",\"N.Y.C.\",\"NYISO\"\n");
+        for hour in 0..24 {
+            let load = 4500.0 + 50.0 * hour as f64;
+            actual.push_str(&format!(
+                "\"12/09/2023 {:02}:00:00\",\"EST\",\"N.Y.C.\",61761,{}\n",
+                hour, load
+            ));
+            forecast.push_str(&format!(
+                "\"12/09/2023 {:02}:00\",{},{}\n",
+                hour,
+                load + 100.0,
+                3.0 * load
+            ));
+        }
+        (actual, forecast)
+    }
+
+    #[test]
+    fn line_chart_is_json_option() {
+        let (actual, forecast) = csvs();
+        let option: Value =
+            serde_json::from_str(&line_chart(&actual, &forecast, "nyc", "").unwrap()).unwrap();
+        assert_eq!(
+            option["title"][0]["text"],
+            "Load vs Forecast for N.Y.C. through 12/09/2023"
+        );
+        assert_eq!(option["xAxis"][0]["data"].as_array().unwrap().len(), 24);
+        assert_eq!(option["xAxis"][0]["data"][0], "12/09 00:00");
+        let series = option["series"].as_array().unwrap();
+        assert_eq!(series[0]["data"][0], 4500.0);
+        assert!(series.iter().any(|line| line["data"][0] == 4600.0));
+    }
+
+    #[test]
+    fn metrics_is_json_scores() {
+        let (actual, forecast) = csvs();
+        let scores: Value =
+            serde_json::from_str(&metrics(&actual, &forecast, "N.Y.C.").unwrap()).unwrap();
+        assert_eq!(scores["zone"], "N.Y.C.");
+        assert_eq!(scores["hours"], 24);
+        assert!(scores["mape"].as_f64().unwrap() > 0.0);
+        assert!(scores["bias"].as_f64().unwrap() > 0.0);
+    }
+}